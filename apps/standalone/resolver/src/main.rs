@@ -27,8 +27,7 @@ fn main() {
     // Server-side: Use dioxus::serve with API routes
     #[cfg(feature = "server")]
     dioxus::serve(|| async move {
-        use psa_core::{config::AppConfig, db::Database};
-        use psa_ticketing::{handlers::TicketingState, ticketing_routes};
+        use psa_core::{compose_api_router, config::AppConfig, db::Database};
 
         // Load configuration
         let config = AppConfig::from_env().expect("Failed to load configuration");
@@ -43,12 +42,8 @@ fn main() {
 
         tracing::info!("Database connected and migrations complete");
 
-        // Create ticketing state
-        let ticketing_state = TicketingState { db: db.clone() };
-
         // Build API routes
-        let api_router = axum::Router::new()
-            .nest("/tickets", ticketing_routes(ticketing_state));
+        let api_router = compose_api_router(&db, &[psa_ticketing::API_MODULE]);
 
         // Merge with Dioxus router
         let router = dioxus::server::router(App)
@@ -34,7 +34,7 @@ fn main() {
     // Server-side: Use dioxus::serve with API routes
     #[cfg(feature = "server")]
     dioxus::serve(|| async move {
-        use psa_core::{config::AppConfig, db::Database};
+        use psa_core::{compose_api_router, config::AppConfig, db::Database, ApiModule};
 
         // Load configuration
         let config = AppConfig::from_env().expect("Failed to load configuration");
@@ -49,66 +49,14 @@ fn main() {
 
         tracing::info!("Database connected and migrations complete");
 
-        // Build API router with all enabled modules
-        let mut api_router = axum::Router::new();
-
-        // Mount ticketing routes
-        #[cfg(feature = "ticketing")]
-        {
-            use psa_ticketing::{handlers::TicketingState, ticketing_routes};
-            let ticketing_state = TicketingState { db: db.clone() };
-            api_router = api_router.nest("/tickets", ticketing_routes(ticketing_state));
-            tracing::info!("Ticketing module enabled");
-        }
-
-        // Mount time tracking routes
-        #[cfg(feature = "time-tracking")]
-        {
-            // TODO: Add time tracking routes when module is implemented
-            tracing::info!("Time Tracking module enabled");
-        }
-
-        // Mount project routes
-        #[cfg(feature = "projects")]
-        {
-            // TODO: Add project routes when module is implemented
-            tracing::info!("Projects module enabled");
-        }
-
-        // Mount CRM routes
-        #[cfg(feature = "crm")]
-        {
-            // TODO: Add CRM routes when module is implemented
-            tracing::info!("CRM module enabled");
-        }
-
-        // Mount billing routes
-        #[cfg(feature = "billing")]
-        {
-            // TODO: Add billing routes when module is implemented
-            tracing::info!("Billing module enabled");
-        }
-
-        // Mount assets routes
-        #[cfg(feature = "assets")]
-        {
-            // TODO: Add assets routes when module is implemented
-            tracing::info!("Assets module enabled");
-        }
-
-        // Mount knowledge base routes
-        #[cfg(feature = "knowledge-base")]
-        {
-            // TODO: Add knowledge base routes when module is implemented
-            tracing::info!("Knowledge Base module enabled");
-        }
-
-        // Mount calendar routes
-        #[cfg(feature = "calendar")]
-        {
-            // TODO: Add calendar routes when module is implemented
-            tracing::info!("Calendar module enabled");
-        }
+        // Build API router with all enabled modules. Module crates without
+        // API routes yet aren't listed; they join as they expose an
+        // `API_MODULE`.
+        let enabled_modules: &[ApiModule] = &[
+            #[cfg(feature = "ticketing")]
+            psa_ticketing::API_MODULE,
+        ];
+        let api_router = compose_api_router(&db, enabled_modules);
 
         // Merge with Dioxus router
         let router = dioxus::server::router(App)
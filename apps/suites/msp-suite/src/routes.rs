@@ -2,7 +2,7 @@
 
 use dioxus::prelude::*;
 
-use psa_ui::{AppShell, NavItem, SimplePage};
+use psa_ui::{AppShell, CommandPalette, NavItem, PaletteCommand, SimplePage};
 
 /// Build navigation items based on enabled modules
 fn nav_items() -> Vec<NavItem> {
@@ -79,6 +79,33 @@ fn nav_items() -> Vec<NavItem> {
     items
 }
 
+/// Quick actions for the command palette, based on enabled modules
+fn palette_commands() -> Vec<PaletteCommand> {
+    let mut commands = vec![PaletteCommand::new("dashboard", "Go to dashboard", "Navigate", Route::Dashboard {})];
+
+    #[cfg(feature = "ticketing")]
+    commands.extend([
+        PaletteCommand::new("new-ticket", "New ticket", "Action", Route::NewTicket {}).with_keywords(&["create", "issue"]),
+        PaletteCommand::new("tickets", "Go to tickets", "Navigate", Route::Tickets {}),
+    ]);
+
+    #[cfg(feature = "time-tracking")]
+    commands.push(PaletteCommand::new("new-time-entry", "Log time", "Action", Route::NewTimeEntry {}));
+
+    #[cfg(feature = "crm")]
+    commands.extend([
+        PaletteCommand::new("companies", "Go to company", "Navigate", Route::Companies {}).with_keywords(&["clients", "customers"]),
+        PaletteCommand::new("contacts", "Go to contacts", "Navigate", Route::Contacts {}),
+    ]);
+
+    #[cfg(feature = "billing")]
+    commands.push(PaletteCommand::new("invoices", "Go to invoices", "Navigate", Route::Invoices {}));
+
+    commands.push(PaletteCommand::new("settings", "Settings", "Navigate", Route::Settings {}));
+
+    commands
+}
+
 /// Application routes
 #[derive(Clone, Routable, Debug, PartialEq)]
 #[rustfmt::skip]
@@ -172,7 +199,20 @@ fn Layout() -> Element {
         _ => "/",
     };
 
+    // Most recent first, without repeats
+    let mut recent = use_signal(Vec::<PaletteCommand>::new);
+
     rsx! {
+        CommandPalette {
+            commands: palette_commands(),
+            recent: recent(),
+            on_select: move |command: PaletteCommand| {
+                let mut recent = recent.write();
+                recent.retain(|entry| entry.id != command.id);
+                recent.insert(0, command);
+                recent.truncate(5);
+            },
+        }
         AppShell {
             nav_items: nav_items(),
             current_path: current_path.to_string(),
@@ -26,6 +26,18 @@ impl Database {
         Ok(Self { pool })
     }
 
+    /// Create a pool that connects on first use instead of up front
+    pub fn connect_lazy(database_url: &str) -> Result<Self> {
+        let pool = PgPoolOptions::new()
+            .max_connections(20)
+            .acquire_timeout(Duration::from_secs(30))
+            .idle_timeout(Duration::from_secs(600))
+            .connect_lazy(database_url)
+            .map_err(|e| CoreError::Database(format!("Invalid database URL: {}", e)))?;
+
+        Ok(Self { pool })
+    }
+
     /// Get a reference to the connection pool
     pub fn pool(&self) -> &PgPool {
         &self.pool
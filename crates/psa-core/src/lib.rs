@@ -37,6 +37,9 @@ pub mod audit;
 #[cfg(feature = "server")]
 pub mod middleware;
 
+#[cfg(feature = "server")]
+pub mod router;
+
 // Re-export commonly used types
 pub use error::{CoreError, Result};
 pub use models::*;
@@ -44,6 +47,9 @@ pub use models::*;
 #[cfg(feature = "server")]
 pub use db::Database;
 
+#[cfg(feature = "server")]
+pub use router::{compose_api_router, ApiModule};
+
 /// Check if running in SaaS mode
 #[inline]
 pub fn is_saas() -> bool {
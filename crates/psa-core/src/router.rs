@@ -0,0 +1,70 @@
+//! API router composition
+//!
+//! Each module crate exposes a `routes(db)` entry and an [`ApiModule`]
+//! describing where it mounts. Apps collect the modules they were built with
+//! and hand them to [`compose_api_router`] instead of nesting each one by hand.
+
+use axum::Router;
+
+use crate::db::Database;
+
+/// A module's API routes and the prefix they mount under
+#[derive(Debug, Clone, Copy)]
+pub struct ApiModule {
+    /// Module name, for logging
+    pub name: &'static str,
+    /// Path the module's routes are nested at, e.g. `/tickets`
+    pub prefix: &'static str,
+    /// Builds the module's router from the shared database
+    pub routes: fn(Database) -> Router,
+}
+
+/// Nest every enabled module's routes under its prefix
+///
+/// # Panics
+///
+/// If two modules share a prefix, like any overlapping `Router::nest`.
+pub fn compose_api_router(db: &Database, enabled_modules: &[ApiModule]) -> Router {
+    enabled_modules.iter().fold(Router::new(), |router, module| {
+        tracing::info!("{} module enabled at {}", module.name, module.prefix);
+        router.nest(module.prefix, (module.routes)(db.clone()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request, http::StatusCode, routing::get};
+    use tower::ServiceExt;
+
+    fn ok_routes(_db: Database) -> Router {
+        Router::new().route("/", get(|| async { "ok" }))
+    }
+
+    const TICKETS: ApiModule = ApiModule { name: "Ticketing", prefix: "/tickets", routes: ok_routes };
+    const INVOICES: ApiModule = ApiModule { name: "Billing", prefix: "/invoices", routes: ok_routes };
+    const ASSETS: ApiModule = ApiModule { name: "Assets", prefix: "/assets", routes: ok_routes };
+
+    async fn status(router: &Router, path: &str) -> StatusCode {
+        let request = Request::builder().uri(path).body(Body::empty()).unwrap();
+        router.clone().oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_mounts_only_enabled_modules() {
+        // Never connects: the test routes don't touch the database
+        let db = Database::connect_lazy("postgres://localhost/psa_test").unwrap();
+        let router = compose_api_router(&db, &[TICKETS, ASSETS]);
+
+        assert_eq!(status(&router, "/tickets").await, StatusCode::OK);
+        assert_eq!(status(&router, "/assets").await, StatusCode::OK);
+        assert_eq!(status(&router, "/invoices").await, StatusCode::NOT_FOUND);
+
+        let router = compose_api_router(&db, &[INVOICES]);
+        assert_eq!(status(&router, "/invoices").await, StatusCode::OK);
+        assert_eq!(status(&router, "/tickets").await, StatusCode::NOT_FOUND);
+
+        let router = compose_api_router(&db, &[]);
+        assert_eq!(status(&router, "/tickets").await, StatusCode::NOT_FOUND);
+    }
+}
@@ -38,4 +38,4 @@ pub mod routes;
 pub use models::*;
 
 #[cfg(feature = "server")]
-pub use routes::ticketing_routes;
+pub use routes::{routes, ticketing_routes, API_MODULE};
@@ -5,8 +5,22 @@ use axum::{
     Router,
 };
 
+use psa_core::{ApiModule, Database};
+
 use crate::handlers::*;
 
+/// Ticketing's entry for `psa_core::compose_api_router`
+pub const API_MODULE: ApiModule = ApiModule {
+    name: "Ticketing",
+    prefix: "/tickets",
+    routes,
+};
+
+/// Create the ticketing router over the shared database
+pub fn routes(db: Database) -> Router {
+    ticketing_routes(TicketingState { db })
+}
+
 /// Create the ticketing router
 ///
 /// This router can be mounted:
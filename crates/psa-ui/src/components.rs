@@ -1,11 +1,13 @@
 //! Reusable UI components
 
 pub mod buttons;
+pub mod command_palette;
 pub mod forms;
 pub mod tables;
 pub mod feedback;
 
 pub use buttons::*;
+pub use command_palette::*;
 pub use forms::*;
 pub use tables::*;
 pub use feedback::*;
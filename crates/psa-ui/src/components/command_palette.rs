@@ -0,0 +1,375 @@
+//! Command palette (Cmd/Ctrl-K)
+//!
+//! A quick-jump box over the app's quick actions, recent results and
+//! whatever search results the host feeds it. The host owns searching:
+//! it gets each query through `on_query` and passes matches back in as
+//! `results`. Choosing an entry navigates to its route.
+
+use dioxus::prelude::*;
+
+/// Registers the Cmd/Ctrl-K listener and posts to Rust each time it fires
+const SHORTCUT_LISTENER: &str = r#"
+    document.addEventListener('keydown', (event) => {
+        if ((event.metaKey || event.ctrlKey) && event.key.toLowerCase() === 'k') {
+            event.preventDefault();
+            dioxus.send(true);
+        }
+    });
+"#;
+
+/// Entries shown at once
+const MAX_ENTRIES: usize = 10;
+
+/// An entry in the palette: a quick action or a search result
+#[derive(Clone, Debug, PartialEq)]
+pub struct PaletteCommand {
+    /// Stable id, used to de-duplicate recents against results
+    pub id: String,
+    pub label: String,
+    /// Shown alongside the label, e.g. "Action" or "Company"
+    pub group: String,
+    /// Extra words the entry matches on
+    pub keywords: Vec<String>,
+    /// Where choosing the entry goes, usually built from the app's `Route`
+    pub target: NavigationTarget,
+}
+
+impl PaletteCommand {
+    pub fn new(
+        id: impl Into<String>,
+        label: impl Into<String>,
+        group: impl Into<String>,
+        target: impl Into<NavigationTarget>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            label: label.into(),
+            group: group.into(),
+            keywords: Vec::new(),
+            target: target.into(),
+        }
+    }
+
+    pub fn with_keywords(mut self, keywords: &[&str]) -> Self {
+        self.keywords = keywords.iter().map(|keyword| keyword.to_string()).collect();
+        self
+    }
+
+    /// Best fuzzy score of the query against the label or a keyword
+    fn score(&self, query: &str) -> Option<i32> {
+        std::iter::once(&self.label)
+            .chain(&self.keywords)
+            .filter_map(|text| fuzzy_score(query, text))
+            .max()
+    }
+}
+
+/// Score `text` against `query` when every query character appears in order,
+/// ignoring case and spaces. Runs of consecutive characters and matches at
+/// the start of a word score higher; gaps cost a little.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut last_match: Option<usize> = None;
+    let mut position = 0;
+
+    for wanted in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = (position..text.len()).find(|&i| text[i] == wanted)?;
+
+        score += 1;
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 8;
+        }
+        match last_match {
+            Some(last) if found == last + 1 => score += 5,
+            Some(last) => score -= (found - last - 1).min(3) as i32,
+            None => score -= found.min(3) as i32,
+        }
+
+        last_match = Some(found);
+        position = found + 1;
+    }
+
+    Some(score)
+}
+
+/// A key the palette handles
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PaletteKey {
+    Up,
+    Down,
+    Enter,
+    Escape,
+}
+
+impl PaletteKey {
+    pub fn from_key(key: &Key) -> Option<Self> {
+        match key {
+            Key::ArrowUp => Some(Self::Up),
+            Key::ArrowDown => Some(Self::Down),
+            Key::Enter => Some(Self::Enter),
+            Key::Escape => Some(Self::Escape),
+            _ => None,
+        }
+    }
+}
+
+/// What a key press asks of the palette
+#[derive(Clone, Debug, PartialEq)]
+pub enum PaletteOutcome {
+    /// Moved the selection, or nothing to do
+    Stay,
+    Invoke(PaletteCommand),
+    Close,
+}
+
+/// Query and selection, kept apart from the component so the filtering and
+/// key handling can be exercised without a renderer
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PaletteState {
+    pub query: String,
+    pub selected: usize,
+}
+
+impl PaletteState {
+    pub fn set_query(&mut self, query: String) {
+        self.query = query;
+        self.selected = 0;
+    }
+
+    /// Entries for the current query. With no query: recent results, then
+    /// the quick actions. Otherwise everything that matches, best first.
+    pub fn entries(
+        &self,
+        commands: &[PaletteCommand],
+        recent: &[PaletteCommand],
+        results: &[PaletteCommand],
+    ) -> Vec<PaletteCommand> {
+        let mut seen = std::collections::HashSet::new();
+        let candidates = recent
+            .iter()
+            .chain(commands)
+            .chain(results)
+            .filter(|command| seen.insert(command.id.as_str()));
+
+        if self.query.trim().is_empty() {
+            return candidates.take(MAX_ENTRIES).cloned().collect();
+        }
+
+        let mut ranked: Vec<(i32, &PaletteCommand)> = candidates
+            .filter_map(|command| command.score(&self.query).map(|score| (score, command)))
+            .collect();
+        // Stable, so equal scores keep recents ahead of commands ahead of results
+        ranked.sort_by_key(|r| std::cmp::Reverse(r.0));
+        ranked.into_iter().take(MAX_ENTRIES).map(|(_, command)| command.clone()).collect()
+    }
+
+    pub fn key(&mut self, key: PaletteKey, entries: &[PaletteCommand]) -> PaletteOutcome {
+        match key {
+            PaletteKey::Up => {
+                self.selected = self.selected.saturating_sub(1);
+                PaletteOutcome::Stay
+            }
+            PaletteKey::Down => {
+                if self.selected + 1 < entries.len() {
+                    self.selected += 1;
+                }
+                PaletteOutcome::Stay
+            }
+            PaletteKey::Enter => match entries.get(self.selected) {
+                Some(command) => PaletteOutcome::Invoke(command.clone()),
+                None => PaletteOutcome::Stay,
+            },
+            PaletteKey::Escape => PaletteOutcome::Close,
+        }
+    }
+}
+
+/// Command palette, opened with Cmd/Ctrl-K. Mount it once in the app layout,
+/// inside the router.
+#[component]
+pub fn CommandPalette(
+    /// Quick actions, always available
+    commands: Vec<PaletteCommand>,
+    /// Recently opened entries, listed first
+    #[props(default)]
+    recent: Vec<PaletteCommand>,
+    /// Search results for the current query
+    #[props(default)]
+    results: Vec<PaletteCommand>,
+    /// Called as the query changes, to run a search
+    on_query: Option<EventHandler<String>>,
+    /// Called with the entry chosen, before navigating, e.g. to record it as recent
+    on_select: Option<EventHandler<PaletteCommand>>,
+) -> Element {
+    let mut is_open = use_signal(|| false);
+    let mut state = use_signal(PaletteState::default);
+
+    use_future(move || async move {
+        let mut listener = document::eval(SHORTCUT_LISTENER);
+        while listener.recv::<bool>().await.is_ok() {
+            state.set(PaletteState::default());
+            is_open.set(true);
+        }
+    });
+
+    if !*is_open.read() {
+        return rsx! {};
+    }
+
+    let entries = state.read().entries(&commands, &recent, &results);
+    let selected = state.read().selected;
+
+    let mut invoke = move |command: PaletteCommand| {
+        is_open.set(false);
+        if let Some(handler) = &on_select {
+            handler.call(command.clone());
+        }
+        navigator().push(command.target);
+    };
+
+    let key_entries = entries.clone();
+
+    rsx! {
+        div { class: "fixed inset-0 z-50 overflow-y-auto",
+            div {
+                class: "fixed inset-0 bg-gray-500 bg-opacity-75 transition-opacity",
+                onclick: move |_| is_open.set(false),
+            }
+
+            div { class: "relative mx-auto mt-24 max-w-xl rounded-lg bg-white dark:bg-gray-800 shadow-xl",
+                input {
+                    r#type: "text",
+                    class: "block w-full rounded-t-lg border-0 border-b border-gray-200 dark:border-gray-700 dark:bg-gray-800 dark:text-white px-4 py-3 focus:ring-0 sm:text-sm",
+                    placeholder: "Type a command or search…",
+                    autofocus: true,
+                    value: "{state.read().query}",
+                    oninput: move |evt| {
+                        let query = evt.value();
+                        state.write().set_query(query.clone());
+                        if let Some(handler) = &on_query {
+                            handler.call(query);
+                        }
+                    },
+                    onkeydown: move |evt| {
+                        let Some(key) = PaletteKey::from_key(&evt.key()) else {
+                            return;
+                        };
+                        evt.prevent_default();
+                        let outcome = state.write().key(key, &key_entries);
+                        match outcome {
+                            PaletteOutcome::Stay => {}
+                            PaletteOutcome::Invoke(command) => invoke(command),
+                            PaletteOutcome::Close => is_open.set(false),
+                        }
+                    },
+                }
+
+                if entries.is_empty() {
+                    p { class: "px-4 py-6 text-center text-sm text-gray-500 dark:text-gray-400",
+                        "No matches"
+                    }
+                } else {
+                    ul { class: "max-h-80 overflow-y-auto py-2",
+                        for (index, command) in entries.into_iter().enumerate() {
+                            {
+                                let row_class = if index == selected {
+                                    "bg-primary-600 text-white"
+                                } else {
+                                    "text-gray-900 dark:text-gray-100"
+                                };
+                                let id = command.id.clone();
+                                let label = command.label.clone();
+                                let group = command.group.clone();
+
+                                rsx! {
+                                    li {
+                                        key: "{id}",
+                                        class: "flex cursor-pointer items-center justify-between px-4 py-2 text-sm {row_class}",
+                                        onmouseenter: move |_| state.write().selected = index,
+                                        onclick: move |_| invoke(command.clone()),
+                                        span { "{label}" }
+                                        span { class: "text-xs opacity-75", "{group}" }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(id: &str, label: &str, path: &str) -> PaletteCommand {
+        PaletteCommand::new(id, label, "Action", NavigationTarget::Internal(path.to_string()))
+    }
+
+    #[test]
+    fn test_filter_and_invoke() {
+        let commands = vec![
+            command("new-ticket", "New ticket", "/tickets/new"),
+            command("companies", "Go to company", "/companies").with_keywords(&["clients"]),
+            command("time", "Log time", "/time/new"),
+        ];
+        let recent = vec![command("ticket-42", "T000042 Printer offline", "/tickets/42")];
+        let mut state = PaletteState::default();
+
+        // Nothing typed: recents first, then every command
+        let ids = |entries: &[PaletteCommand]| entries.iter().map(|c| c.id.clone()).collect::<Vec<_>>();
+        assert_eq!(
+            ids(&state.entries(&commands, &recent, &[])),
+            ["ticket-42", "new-ticket", "companies", "time"]
+        );
+
+        // Fuzzy: "nt" matches "New ticket" at two word starts ahead of
+        // "Printer offline"'s scattered letters
+        state.set_query("nt".to_string());
+        let entries = state.entries(&commands, &recent, &[]);
+        assert_eq!(entries[0].id, "new-ticket");
+        assert!(!ids(&entries).contains(&"time".to_string()));
+
+        // Keywords match too, and search results join in
+        state.set_query("client".to_string());
+        let results = vec![command("company-7", "Contoso Ltd", "/companies/7").with_keywords(&["client"])];
+        assert_eq!(ids(&state.entries(&commands, &recent, &results)), ["companies", "company-7"]);
+
+        state.set_query("zzz".to_string());
+        assert!(state.entries(&commands, &recent, &[]).is_empty());
+
+        // Arrow down to the second match and invoke it
+        state.set_query("o".to_string());
+        let entries = state.entries(&commands, &recent, &[]);
+        assert_eq!(state.key(PaletteKey::Down, &entries), PaletteOutcome::Stay);
+        let PaletteOutcome::Invoke(chosen) = state.key(PaletteKey::Enter, &entries) else {
+            panic!("Enter should invoke the selected entry");
+        };
+        assert_eq!(chosen, entries[1]);
+
+        // Selection stops at the ends; Escape closes
+        for _ in 0..10 {
+            state.key(PaletteKey::Down, &entries);
+        }
+        assert_eq!(state.selected, entries.len() - 1);
+        state.key(PaletteKey::Up, &entries);
+        assert_eq!(state.selected, entries.len() - 2);
+        assert_eq!(state.key(PaletteKey::Escape, &entries), PaletteOutcome::Close);
+        assert_eq!(state.key(PaletteKey::Enter, &[]), PaletteOutcome::Stay);
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("nt", "New ticket").is_some());
+        assert!(fuzzy_score("tn", "New ticket").is_none());
+        assert!(fuzzy_score("NEW", "new ticket").is_some());
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+
+        // Consecutive and word-start matches beat scattered ones
+        assert!(fuzzy_score("tick", "New ticket") > fuzzy_score("tick", "Time check"));
+        assert!(fuzzy_score("co", "Go to company") > fuzzy_score("co", "Log a record"));
+    }
+}
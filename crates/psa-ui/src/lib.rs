@@ -14,3 +14,4 @@ pub mod theme;
 // Re-export commonly used components
 pub use components::*;
 pub use layout::*;
+pub use theme::{use_theme, Theme, ThemeProvider};
@@ -1,11 +1,13 @@
 //! Theme configuration for PSA UI
 //!
-//! Provides theming support for standalone apps and suites.
+//! Provides theming support for standalone apps and suites, and per-tenant
+//! branding through [`ThemeProvider`].
 
+use dioxus::prelude::*;
 use serde::{Deserialize, Serialize};
 
 /// Theme configuration for an application
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Theme {
     /// Application name
     pub app_name: String,
@@ -31,6 +33,61 @@ impl Default for Theme {
     }
 }
 
+impl Theme {
+    /// CSS custom properties for the theme colors. Anything that isn't a hex
+    /// color falls back to the default so tenant input can't inject styles.
+    pub fn css_variables(&self) -> String {
+        let defaults = Theme::default();
+        let color = |value: &str, default: String| {
+            let hex = value.strip_prefix('#').unwrap_or_default();
+            if matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                value.to_string()
+            } else {
+                default
+            }
+        };
+
+        format!(
+            "--psa-primary-color: {}; --psa-secondary-color: {};",
+            color(&self.primary_color, defaults.primary_color),
+            color(&self.secondary_color, defaults.secondary_color),
+        )
+    }
+}
+
+/// Applies a theme's colors as CSS variables to everything inside it and makes
+/// the theme available to descendants through [`use_theme`]
+#[component]
+pub fn ThemeProvider(
+    /// Theme to apply, typically built from the tenant's branding
+    theme: Theme,
+    children: Element,
+) -> Element {
+    let mut current = use_context_provider(|| Signal::new(theme.clone()));
+    if *current.peek() != theme {
+        current.set(theme.clone());
+    }
+
+    let style = theme.css_variables();
+
+    rsx! {
+        if let Some(favicon) = theme.favicon_url.clone() {
+            document::Link { rel: "icon", href: favicon }
+        }
+        document::Title { "{theme.app_name}" }
+        div { class: "contents", style: "{style}",
+            {children}
+        }
+    }
+}
+
+/// The theme from the nearest [`ThemeProvider`], or the default theme
+pub fn use_theme() -> Theme {
+    try_use_context::<Signal<Theme>>()
+        .map(|theme| theme.read().clone())
+        .unwrap_or_default()
+}
+
 /// Predefined themes for standalone apps
 pub mod standalone {
     use super::Theme;
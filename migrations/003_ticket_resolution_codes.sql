@@ -0,0 +1,53 @@
+-- Ticket resolution codes
+-- Adds a managed list of close reasons per tenant and records the code on tickets
+
+-- ============================================================================
+-- RESOLUTION CODES
+-- ============================================================================
+
+CREATE TABLE ticket_resolution_codes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    code VARCHAR(50) NOT NULL,
+    name VARCHAR(100) NOT NULL,
+    description TEXT,
+    is_active BOOLEAN DEFAULT TRUE,
+    sort_order INTEGER DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(tenant_id, code)
+);
+
+CREATE INDEX idx_ticket_resolution_codes_tenant ON ticket_resolution_codes(tenant_id);
+
+CREATE TRIGGER update_ticket_resolution_codes_updated_at
+    BEFORE UPDATE ON ticket_resolution_codes
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE ticket_resolution_codes ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON ticket_resolution_codes
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
+
+-- Closed statuses can require a resolution code before a ticket may enter them
+ALTER TABLE ticket_statuses ADD COLUMN requires_resolution_code BOOLEAN DEFAULT FALSE;
+
+-- Resolution code recorded when the ticket was closed
+ALTER TABLE tickets ADD COLUMN resolution_code VARCHAR(50);
+
+CREATE INDEX idx_tickets_resolution_code ON tickets(tenant_id, resolution_code) WHERE resolution_code IS NOT NULL;
+
+-- ============================================================================
+-- DEFAULT RESOLUTION CODES
+-- ============================================================================
+
+INSERT INTO ticket_resolution_codes (tenant_id, code, name, description, sort_order) VALUES
+('00000000-0000-0000-0000-000000000001', 'resolved', 'Resolved', 'Issue was fixed', 1),
+('00000000-0000-0000-0000-000000000001', 'workaround', 'Workaround Provided', 'Workaround given, root cause remains', 2),
+('00000000-0000-0000-0000-000000000001', 'duplicate', 'Duplicate', 'Duplicate of another ticket', 3),
+('00000000-0000-0000-0000-000000000001', 'no_response', 'No Response', 'Client did not respond', 4),
+('00000000-0000-0000-0000-000000000001', 'not_an_issue', 'Not an Issue', 'Working as intended or user error', 5),
+('00000000-0000-0000-0000-000000000001', 'cancelled', 'Cancelled', 'Request withdrawn by client', 6);
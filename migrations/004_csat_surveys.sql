@@ -0,0 +1,46 @@
+-- Customer satisfaction surveys
+-- One tokenized survey is issued to the ticket contact when a ticket closes
+
+-- ============================================================================
+-- CSAT SURVEYS
+-- ============================================================================
+
+CREATE TABLE csat_surveys (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    ticket_id UUID NOT NULL REFERENCES tickets(id) ON DELETE CASCADE,
+    contact_id UUID REFERENCES contacts(id) ON DELETE SET NULL,
+    -- Technician the ticket was assigned to when it closed
+    technician_id UUID REFERENCES users(id),
+    -- Single-use response token
+    token VARCHAR(64) NOT NULL UNIQUE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    -- Response
+    rating VARCHAR(10) CHECK (rating IN ('positive', 'negative')),
+    comment TEXT,
+    responded_at TIMESTAMPTZ,
+    sent_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_csat_surveys_tenant ON csat_surveys(tenant_id);
+CREATE INDEX idx_csat_surveys_ticket ON csat_surveys(ticket_id);
+CREATE INDEX idx_csat_surveys_technician ON csat_surveys(technician_id);
+CREATE INDEX idx_csat_surveys_responded ON csat_surveys(tenant_id, responded_at) WHERE responded_at IS NOT NULL;
+
+ALTER TABLE csat_surveys ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON csat_surveys
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
+
+-- ============================================================================
+-- DEFAULT SURVEY TEMPLATE
+-- ============================================================================
+
+INSERT INTO notification_templates (tenant_id, name, event_type, channel_type, subject, body_text, body_html) VALUES
+('00000000-0000-0000-0000-000000000001', 'CSAT Survey - Email', 'ticket.csat_survey', 'email',
+    'How did we do? Ticket #{{ticket.number}}',
+    'Your ticket #{{ticket.number}} ({{ticket.title}}) has been closed.\n\nPlease let us know how we did:\n{{survey.url}}\n\nThis link expires on {{survey.expires_at}}.',
+    '<h2>How did we do?</h2><p>Your ticket <strong>#{{ticket.number}}</strong> ({{ticket.title}}) has been closed.</p><p><a href="{{survey.url}}?rating=positive">&#128077; Good</a> &nbsp; <a href="{{survey.url}}?rating=negative">&#128078; Bad</a></p><p>This link expires on {{survey.expires_at}}.</p>');
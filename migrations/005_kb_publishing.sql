@@ -0,0 +1,18 @@
+-- Knowledge base publishing workflow
+-- Adds an in-review state, reviewer tracking and scheduled publishing
+
+-- ============================================================================
+-- ARTICLE STATUS
+-- ============================================================================
+
+ALTER TABLE kb_articles DROP CONSTRAINT kb_articles_status_check;
+ALTER TABLE kb_articles ADD CONSTRAINT kb_articles_status_check
+    CHECK (status IN ('draft', 'in_review', 'published', 'archived'));
+
+-- Who approved the article for publishing when review is required
+ALTER TABLE kb_articles ADD COLUMN reviewed_by_id UUID REFERENCES users(id);
+ALTER TABLE kb_articles ADD COLUMN reviewed_at TIMESTAMPTZ;
+
+-- published_at may be in the future for scheduled articles; the portal only
+-- serves published articles whose publish time has passed
+CREATE INDEX idx_kb_articles_portal ON kb_articles(tenant_id, published_at) WHERE status = 'published';
@@ -0,0 +1,59 @@
+-- Software license tracking
+-- Licenses with purchased seat counts, and seat assignments to assets or contacts
+
+-- ============================================================================
+-- SOFTWARE LICENSES
+-- ============================================================================
+
+CREATE TABLE software_licenses (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    company_id UUID REFERENCES companies(id),
+    product_name VARCHAR(255) NOT NULL,
+    vendor VARCHAR(100),
+    license_key_encrypted TEXT,
+    seats_purchased INTEGER NOT NULL DEFAULT 1 CHECK (seats_purchased >= 0),
+    expires_on DATE,
+    notes TEXT,
+    is_active BOOLEAN DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_software_licenses_tenant ON software_licenses(tenant_id);
+CREATE INDEX idx_software_licenses_company ON software_licenses(company_id);
+CREATE INDEX idx_software_licenses_expiry ON software_licenses(tenant_id, expires_on) WHERE expires_on IS NOT NULL;
+
+CREATE TRIGGER update_software_licenses_updated_at
+    BEFORE UPDATE ON software_licenses
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE software_licenses ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON software_licenses
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
+
+-- Seat assignments; a seat belongs to an asset, a contact, or both
+CREATE TABLE software_license_assignments (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    license_id UUID NOT NULL REFERENCES software_licenses(id) ON DELETE CASCADE,
+    asset_id UUID REFERENCES assets(id) ON DELETE CASCADE,
+    contact_id UUID REFERENCES contacts(id) ON DELETE CASCADE,
+    assigned_by_id UUID REFERENCES users(id),
+    assigned_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (asset_id IS NOT NULL OR contact_id IS NOT NULL)
+);
+
+CREATE INDEX idx_license_assignments_license ON software_license_assignments(license_id);
+CREATE INDEX idx_license_assignments_asset ON software_license_assignments(asset_id);
+
+ALTER TABLE software_license_assignments ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON software_license_assignments
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
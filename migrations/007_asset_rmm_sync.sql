@@ -0,0 +1,13 @@
+-- Asset inventory sync from RMM agents
+-- Tracks hardware specs and when an agent last reported, and flags assets that
+-- stopped reporting instead of deleting them
+
+ALTER TABLE assets ADD COLUMN specs JSONB NOT NULL DEFAULT '{}';
+ALTER TABLE assets ADD COLUMN last_seen_at TIMESTAMPTZ;
+ALTER TABLE assets ADD COLUMN possibly_decommissioned BOOLEAN DEFAULT FALSE;
+
+CREATE INDEX idx_assets_tenant_rmm ON assets(tenant_id, rmm_device_id) WHERE rmm_device_id IS NOT NULL;
+CREATE INDEX idx_assets_decommissioned ON assets(tenant_id) WHERE possibly_decommissioned = TRUE;
+
+-- Agent to company lookups during sync
+CREATE INDEX idx_rmm_mappings_device ON rmm_device_mappings(tenant_id, rmm_device_id);
@@ -0,0 +1,25 @@
+-- Asset lifecycle and depreciation
+-- Replaces the loose active/inactive states with an explicit lifecycle and adds
+-- the inputs for straight-line depreciation
+
+-- ============================================================================
+-- LIFECYCLE STATUS
+-- ============================================================================
+
+ALTER TABLE assets DROP CONSTRAINT assets_status_check;
+
+UPDATE assets SET status = 'deployed' WHERE status = 'active';
+UPDATE assets SET status = 'in_stock' WHERE status = 'inactive' OR status IS NULL;
+
+ALTER TABLE assets ALTER COLUMN status SET DEFAULT 'in_stock';
+ALTER TABLE assets ADD CONSTRAINT assets_status_check
+    CHECK (status IN ('in_stock', 'deployed', 'in_repair', 'retired'));
+
+ALTER TABLE assets ADD COLUMN retired_at TIMESTAMPTZ;
+
+-- ============================================================================
+-- DEPRECIATION
+-- ============================================================================
+
+ALTER TABLE assets ADD COLUMN useful_life_months INTEGER CHECK (useful_life_months > 0);
+ALTER TABLE assets ADD COLUMN salvage_value DECIMAL(12, 2) NOT NULL DEFAULT 0;
@@ -0,0 +1,86 @@
+-- Calendar appointment booking
+-- Staff publish bookable slots and send contacts a tokenized invitation; the
+-- contact picks a slot, which creates an appointment and a scheduled ticket
+
+-- ============================================================================
+-- BOOKING INVITATIONS
+-- ============================================================================
+
+CREATE TABLE booking_invitations (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    company_id UUID NOT NULL REFERENCES companies(id),
+    contact_id UUID NOT NULL REFERENCES contacts(id) ON DELETE CASCADE,
+    token VARCHAR(64) NOT NULL UNIQUE,
+    -- Title and description of the ticket created on booking
+    title VARCHAR(255) NOT NULL,
+    description TEXT,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_by_id UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_booking_invitations_tenant ON booking_invitations(tenant_id);
+CREATE INDEX idx_booking_invitations_contact ON booking_invitations(contact_id);
+
+CREATE TRIGGER update_booking_invitations_updated_at
+    BEFORE UPDATE ON booking_invitations
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE booking_invitations ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON booking_invitations
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
+
+-- ============================================================================
+-- BOOKABLE SLOTS
+-- ============================================================================
+
+CREATE TABLE bookable_slots (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    technician_id UUID NOT NULL REFERENCES users(id),
+    start_time TIMESTAMPTZ NOT NULL,
+    end_time TIMESTAMPTZ NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'available' CHECK (status IN ('available', 'booked', 'cancelled')),
+    -- Booking
+    invitation_id UUID REFERENCES booking_invitations(id) ON DELETE SET NULL,
+    appointment_id UUID REFERENCES appointments(id) ON DELETE SET NULL,
+    ticket_id UUID REFERENCES tickets(id) ON DELETE SET NULL,
+    -- Token for the reschedule/cancel links in the confirmation email
+    manage_token VARCHAR(64) UNIQUE,
+    booked_at TIMESTAMPTZ,
+    created_by_id UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (end_time > start_time)
+);
+
+CREATE INDEX idx_bookable_slots_tenant ON bookable_slots(tenant_id);
+CREATE INDEX idx_bookable_slots_technician ON bookable_slots(technician_id, start_time);
+CREATE INDEX idx_bookable_slots_available ON bookable_slots(tenant_id, start_time) WHERE status = 'available';
+
+CREATE TRIGGER update_bookable_slots_updated_at
+    BEFORE UPDATE ON bookable_slots
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE bookable_slots ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON bookable_slots
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
+
+-- ============================================================================
+-- CONFIRMATION TEMPLATE
+-- ============================================================================
+
+INSERT INTO notification_templates (tenant_id, name, event_type, channel_type, subject, body_text) VALUES
+('00000000-0000-0000-0000-000000000001', 'Booking Confirmation - Email', 'appointment.booked', 'email',
+    'Appointment confirmed: {{appointment.start}}',
+    'Your appointment for "{{ticket.title}}" is confirmed for {{appointment.start}} with {{appointment.technician}}.\n\nTicket: #{{ticket.number}}\n\nReschedule: {{booking.reschedule_url}}\nCancel: {{booking.cancel_url}}');
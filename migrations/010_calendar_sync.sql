@@ -0,0 +1,79 @@
+-- Calendar sync with external providers
+-- Two-way sync of a technician's PSA calendar with Google Calendar or
+-- Microsoft Graph: PSA appointments are pushed out, external events are
+-- pulled back in as busy blocks for availability
+
+-- ============================================================================
+-- CALENDAR CONNECTIONS
+-- ============================================================================
+
+CREATE TABLE calendar_connections (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    provider VARCHAR(20) NOT NULL CHECK (provider IN ('google', 'microsoft')),
+    -- Remote calendar; 'primary' for Google, ignored for Microsoft (default calendar)
+    calendar_id VARCHAR(255) NOT NULL DEFAULT 'primary',
+    access_token_encrypted TEXT NOT NULL,
+    -- Google nextSyncToken or Microsoft Graph deltaLink from the last sync
+    sync_token TEXT,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    last_sync_at TIMESTAMPTZ,
+    sync_status VARCHAR(20) NOT NULL DEFAULT 'never' CHECK (sync_status IN ('never', 'success', 'failed')),
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(user_id, provider)
+);
+
+CREATE INDEX idx_calendar_connections_tenant ON calendar_connections(tenant_id);
+
+CREATE TRIGGER update_calendar_connections_updated_at
+    BEFORE UPDATE ON calendar_connections
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE calendar_connections ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON calendar_connections
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
+
+-- ============================================================================
+-- EXTERNAL EVENT MAPPINGS
+-- ============================================================================
+
+-- One row per remote event. Rows with an appointment_id were pushed from the
+-- PSA; rows without one are external busy blocks pulled in.
+CREATE TABLE calendar_event_mappings (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    connection_id UUID NOT NULL REFERENCES calendar_connections(id) ON DELETE CASCADE,
+    external_event_id VARCHAR(1024) NOT NULL,
+    appointment_id UUID REFERENCES appointments(id) ON DELETE CASCADE,
+    title VARCHAR(255),
+    start_time TIMESTAMPTZ NOT NULL,
+    end_time TIMESTAMPTZ NOT NULL,
+    etag VARCHAR(255),
+    synced_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(connection_id, external_event_id),
+    UNIQUE(connection_id, appointment_id)
+);
+
+CREATE INDEX idx_calendar_event_mappings_tenant ON calendar_event_mappings(tenant_id);
+CREATE INDEX idx_calendar_event_mappings_time ON calendar_event_mappings(connection_id, start_time, end_time);
+
+CREATE TRIGGER update_calendar_event_mappings_updated_at
+    BEFORE UPDATE ON calendar_event_mappings
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE calendar_event_mappings ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON calendar_event_mappings
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
@@ -0,0 +1,14 @@
+-- Time entry approval thresholds
+-- Small entries and entries from trusted roles can be approved automatically;
+-- the approval path is recorded for audit
+
+ALTER TABLE time_entries
+    ADD COLUMN approval_method VARCHAR(10) CHECK (approval_method IN ('auto', 'manual')),
+    ADD COLUMN is_flagged BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN flag_reason TEXT;
+
+-- Default rules: entries under 15 minutes approve automatically, no trusted roles
+INSERT INTO tenant_settings (tenant_id, category, key, value) VALUES
+    ('00000000-0000-0000-0000-000000000001', 'time_tracking', 'auto_approve_under_minutes', '15'),
+    ('00000000-0000-0000-0000-000000000001', 'time_tracking', 'trusted_roles', '[]')
+ON CONFLICT (tenant_id, category, key) DO NOTHING;
@@ -0,0 +1,14 @@
+-- Email threading for ticket notes
+-- Outbound note emails carry a Message-ID that is stored per note so replies
+-- can be matched on In-Reply-To/References rather than the subject tag alone
+
+ALTER TABLE ticket_notes
+    ADD COLUMN email_message_id VARCHAR(255);
+
+CREATE UNIQUE INDEX idx_ticket_notes_email_message_id
+    ON ticket_notes(tenant_id, email_message_id)
+    WHERE email_message_id IS NOT NULL;
+
+CREATE INDEX idx_tickets_email_message_id
+    ON tickets(tenant_id, email_message_id)
+    WHERE email_message_id IS NOT NULL;
@@ -0,0 +1,31 @@
+-- Per-queue email addresses
+-- Maps inbound recipient addresses to ticket queues so several brands can
+-- share one tenant (support@brand-a.com and help@brand-b.com land in
+-- different queues), and replies go out from the queue's own address
+
+CREATE TABLE queue_email_addresses (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    queue_id UUID NOT NULL REFERENCES ticket_queues(id) ON DELETE CASCADE,
+    -- Stored lowercased, bare address without display name
+    email_address VARCHAR(255) NOT NULL,
+    -- Display name for outbound mail, e.g. 'Brand A Support'
+    from_name VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(tenant_id, email_address)
+);
+
+CREATE INDEX idx_queue_email_addresses_queue ON queue_email_addresses(queue_id);
+
+CREATE TRIGGER update_queue_email_addresses_updated_at
+    BEFORE UPDATE ON queue_email_addresses
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE queue_email_addresses ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON queue_email_addresses
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
@@ -0,0 +1,27 @@
+-- Ticket links
+-- Typed relationships between tickets beyond parent/child. Links are
+-- directional ("A blocks B", "A is a duplicate of B"); 'related_to' is
+-- symmetric and stored once with the lower ticket id first
+
+CREATE TABLE ticket_links (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    from_ticket_id UUID NOT NULL REFERENCES tickets(id) ON DELETE CASCADE,
+    to_ticket_id UUID NOT NULL REFERENCES tickets(id) ON DELETE CASCADE,
+    link_type VARCHAR(20) NOT NULL CHECK (link_type IN ('related_to', 'duplicate_of', 'blocks')),
+    created_by_id UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(from_ticket_id, to_ticket_id, link_type),
+    CHECK (from_ticket_id <> to_ticket_id)
+);
+
+CREATE INDEX idx_ticket_links_tenant ON ticket_links(tenant_id);
+CREATE INDEX idx_ticket_links_from ON ticket_links(from_ticket_id);
+CREATE INDEX idx_ticket_links_to ON ticket_links(to_ticket_id);
+
+ALTER TABLE ticket_links ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON ticket_links
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
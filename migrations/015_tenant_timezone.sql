@@ -0,0 +1,7 @@
+-- Tenant time zone
+-- Day-bucketed reports and "due today" filters are computed in this zone;
+-- timestamps themselves stay in UTC
+
+INSERT INTO tenant_settings (tenant_id, category, key, value) VALUES
+    ('00000000-0000-0000-0000-000000000001', 'general', 'timezone', '"UTC"')
+ON CONFLICT (tenant_id, category, key) DO NOTHING;
@@ -0,0 +1,7 @@
+-- Per-type required ticket fields
+-- Each ticket type lists the fields a ticket of that type must have, e.g.
+-- '{asset_id}' for incidents or '{scheduled_window}' for change requests.
+-- Custom fields are named 'custom_fields.<key>'.
+
+ALTER TABLE ticket_types
+    ADD COLUMN required_fields TEXT[] NOT NULL DEFAULT '{}';
@@ -0,0 +1,32 @@
+-- Ticket status history
+-- One row per stay in a status. The row for the current status has no
+-- left_at; a status change closes it and opens the next one. Time in
+-- status reporting sums these intervals
+
+CREATE TABLE ticket_status_history (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    ticket_id UUID NOT NULL REFERENCES tickets(id) ON DELETE CASCADE,
+    status_id UUID NOT NULL REFERENCES ticket_statuses(id),
+    -- NULL for automation and other system changes
+    changed_by_id UUID REFERENCES users(id),
+    entered_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    left_at TIMESTAMPTZ,
+    CHECK (left_at IS NULL OR left_at >= entered_at)
+);
+
+CREATE INDEX idx_ticket_status_history_ticket ON ticket_status_history(ticket_id, entered_at);
+CREATE INDEX idx_ticket_status_history_tenant ON ticket_status_history(tenant_id, entered_at);
+CREATE UNIQUE INDEX idx_ticket_status_history_current ON ticket_status_history(ticket_id) WHERE left_at IS NULL;
+
+ALTER TABLE ticket_status_history ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON ticket_status_history
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
+
+-- Earlier transitions were never recorded; existing tickets start with
+-- their current status from creation
+INSERT INTO ticket_status_history (tenant_id, ticket_id, status_id, entered_at)
+SELECT tenant_id, id, status_id, created_at FROM tickets;
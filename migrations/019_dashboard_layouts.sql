@@ -0,0 +1,26 @@
+-- Dashboard layouts
+-- Per-user choice and order of dashboard widgets. Users without a row get
+-- the default layout
+
+CREATE TABLE dashboard_layouts (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- Enabled widgets in display order, e.g. {'open_tickets','sla_at_risk'}
+    widgets TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(tenant_id, user_id)
+);
+
+CREATE TRIGGER update_dashboard_layouts_updated_at
+    BEFORE UPDATE ON dashboard_layouts
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE dashboard_layouts ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON dashboard_layouts
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
@@ -0,0 +1,9 @@
+-- Request correlation ids
+-- The X-Request-Id of the API request that wrote the row, so an audit entry
+-- or webhook delivery can be matched to server logs and error reports
+
+ALTER TABLE audit_log ADD COLUMN request_id VARCHAR(128);
+ALTER TABLE webhook_deliveries ADD COLUMN request_id VARCHAR(128);
+
+CREATE INDEX idx_audit_log_request ON audit_log(request_id) WHERE request_id IS NOT NULL;
+CREATE INDEX idx_webhook_deliveries_request ON webhook_deliveries(request_id) WHERE request_id IS NOT NULL;
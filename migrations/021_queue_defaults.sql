@@ -0,0 +1,10 @@
+-- Queue defaults
+-- Values a ticket takes on entering a queue, on create or when moved, unless
+-- the caller sets them. default_sla_id existed without a foreign key.
+
+ALTER TABLE ticket_queues
+    ADD CONSTRAINT ticket_queues_default_sla_id_fkey
+        FOREIGN KEY (default_sla_id) REFERENCES sla_policies(id) ON DELETE SET NULL,
+    ADD COLUMN default_priority_id UUID REFERENCES ticket_priorities(id) ON DELETE SET NULL,
+    ADD COLUMN default_assignee_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    ADD COLUMN default_team_id UUID REFERENCES teams(id) ON DELETE SET NULL;
@@ -0,0 +1,7 @@
+-- Final ticket statuses
+-- Closed statuses that are not final, like the seeded "Resolved", are waiting
+-- on the customer and are auto-closed into the final status after a grace period
+
+ALTER TABLE ticket_statuses ADD COLUMN is_final BOOLEAN NOT NULL DEFAULT FALSE;
+
+UPDATE ticket_statuses SET is_final = TRUE WHERE is_closed = TRUE AND name = 'Closed';
@@ -0,0 +1,45 @@
+-- Contact roles
+-- A contact can hold several roles at their company (billing, technical,
+-- decision maker...). The company's default billing and technical contacts
+-- are picked from its own contacts.
+
+CREATE TABLE contact_roles (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    company_id UUID NOT NULL REFERENCES companies(id) ON DELETE CASCADE,
+    contact_id UUID NOT NULL REFERENCES contacts(id) ON DELETE CASCADE,
+    role VARCHAR(30) NOT NULL CHECK (role IN ('primary', 'billing', 'technical', 'decision_maker')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(contact_id, company_id, role)
+);
+
+CREATE INDEX idx_contact_roles_company ON contact_roles(company_id, role);
+
+ALTER TABLE contact_roles ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON contact_roles
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
+
+-- Carry over the single contact_type
+INSERT INTO contact_roles (tenant_id, company_id, contact_id, role)
+SELECT tenant_id, company_id, id, contact_type
+FROM contacts
+WHERE contact_type IN ('primary', 'billing', 'technical');
+
+-- Defaults must be contacts of the company; drop any that are not
+UPDATE companies c SET default_billing_contact_id = NULL
+WHERE default_billing_contact_id IS NOT NULL AND NOT EXISTS (
+    SELECT 1 FROM contacts ct WHERE ct.id = c.default_billing_contact_id AND ct.company_id = c.id
+);
+UPDATE companies c SET default_technical_contact_id = NULL
+WHERE default_technical_contact_id IS NOT NULL AND NOT EXISTS (
+    SELECT 1 FROM contacts ct WHERE ct.id = c.default_technical_contact_id AND ct.company_id = c.id
+);
+
+ALTER TABLE companies
+    ADD CONSTRAINT companies_default_billing_contact_id_fkey
+        FOREIGN KEY (default_billing_contact_id) REFERENCES contacts(id) ON DELETE SET NULL,
+    ADD CONSTRAINT companies_default_technical_contact_id_fkey
+        FOREIGN KEY (default_technical_contact_id) REFERENCES contacts(id) ON DELETE SET NULL;
@@ -0,0 +1,48 @@
+-- Fixed-fee project billing
+-- Fixed-price projects bill either per milestone, for the milestone's agreed
+-- amount once it is completed, or by progress, for the share of the fee that
+-- the project's completion has earned since the last progress invoice.
+
+ALTER TABLE projects
+    ADD COLUMN fixed_fee DECIMAL(12, 2),
+    ADD COLUMN fixed_fee_billing VARCHAR(20) NOT NULL DEFAULT 'milestone'
+        CHECK (fixed_fee_billing IN ('milestone', 'progress')),
+    ADD COLUMN percent_complete DECIMAL(5, 2) NOT NULL DEFAULT 0
+        CHECK (percent_complete >= 0 AND percent_complete <= 100),
+    -- Completion already covered by progress invoices
+    ADD COLUMN progress_billed_percent DECIMAL(5, 2) NOT NULL DEFAULT 0
+        CHECK (progress_billed_percent >= 0 AND progress_billed_percent <= 100);
+
+CREATE TABLE project_milestones (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL,
+    description TEXT,
+    amount DECIMAL(12, 2) NOT NULL CHECK (amount >= 0),
+    due_date DATE,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'completed')),
+    completed_at TIMESTAMPTZ,
+    -- Set once, when the milestone is billed
+    invoice_id UUID REFERENCES invoices(id) ON DELETE SET NULL,
+    invoiced_at TIMESTAMPTZ,
+    sort_order INTEGER DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_project_milestones_project ON project_milestones(project_id);
+
+CREATE TRIGGER update_project_milestones_updated_at
+    BEFORE UPDATE ON project_milestones
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE project_milestones ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON project_milestones
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
+
+ALTER TABLE invoice_lines ADD COLUMN milestone_id UUID REFERENCES project_milestones(id) ON DELETE SET NULL;
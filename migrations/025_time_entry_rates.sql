@@ -0,0 +1,23 @@
+-- Time entry rates
+-- A rate card can apply to a single contract or company, overriding the
+-- tenant's default card. Work outside the card's business day is billed at
+-- the item's after-hours rate, or the hourly rate times the card's multiplier.
+-- The resolved rate is stored on each entry so later rate changes don't
+-- reprice work already logged.
+
+ALTER TABLE rate_cards
+    ADD COLUMN company_id UUID REFERENCES companies(id) ON DELETE CASCADE,
+    ADD COLUMN contract_id UUID REFERENCES contracts(id) ON DELETE CASCADE,
+    ADD COLUMN after_hours_multiplier DECIMAL(4, 2) NOT NULL DEFAULT 1.5
+        CHECK (after_hours_multiplier >= 1),
+    ADD COLUMN business_day_start TIME NOT NULL DEFAULT '08:00',
+    ADD COLUMN business_day_end TIME NOT NULL DEFAULT '18:00',
+    ADD COLUMN weekends_after_hours BOOLEAN NOT NULL DEFAULT TRUE,
+    ADD CONSTRAINT rate_cards_business_day_check CHECK (business_day_end > business_day_start);
+
+CREATE INDEX idx_rate_cards_company ON rate_cards(company_id) WHERE company_id IS NOT NULL;
+CREATE INDEX idx_rate_cards_contract ON rate_cards(contract_id) WHERE contract_id IS NOT NULL;
+
+ALTER TABLE time_entries
+    ADD COLUMN rate_card_id UUID REFERENCES rate_cards(id) ON DELETE SET NULL,
+    ADD COLUMN is_after_hours BOOLEAN NOT NULL DEFAULT FALSE;
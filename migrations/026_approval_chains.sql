@@ -0,0 +1,120 @@
+-- Approval chains
+-- Submissions over a threshold (expenses to start with) need sign-off from a
+-- sequence of approver roles before they can be billed. The chain with the
+-- highest threshold not above the amount applies. A rejection sends the
+-- submission back to the submitter, and resubmitting starts the chain over.
+
+CREATE TABLE expenses (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id),
+    company_id UUID REFERENCES companies(id),
+    ticket_id UUID REFERENCES tickets(id) ON DELETE SET NULL,
+    project_id UUID REFERENCES projects(id) ON DELETE SET NULL,
+    expense_date DATE NOT NULL,
+    category VARCHAR(50) NOT NULL,
+    description TEXT NOT NULL,
+    amount DECIMAL(12, 2) NOT NULL CHECK (amount >= 0),
+    is_billable BOOLEAN NOT NULL DEFAULT TRUE,
+    approval_status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (approval_status IN ('pending', 'approved', 'rejected')),
+    approved_at TIMESTAMPTZ,
+    rejection_reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_expenses_tenant ON expenses(tenant_id);
+CREATE INDEX idx_expenses_user ON expenses(user_id);
+CREATE INDEX idx_expenses_approval ON expenses(tenant_id, approval_status);
+
+CREATE TRIGGER update_expenses_updated_at
+    BEFORE UPDATE ON expenses
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TABLE approval_chains (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    subject_type VARCHAR(20) NOT NULL CHECK (subject_type IN ('expense')),
+    name VARCHAR(100) NOT NULL,
+    -- Applies to submissions of at least this amount
+    min_amount DECIMAL(12, 2) NOT NULL DEFAULT 0 CHECK (min_amount >= 0),
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_approval_chains_tenant ON approval_chains(tenant_id, subject_type);
+
+CREATE TRIGGER update_approval_chains_updated_at
+    BEFORE UPDATE ON approval_chains
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TABLE approval_chain_steps (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    chain_id UUID NOT NULL REFERENCES approval_chains(id) ON DELETE CASCADE,
+    step_order INTEGER NOT NULL,
+    approver_role VARCHAR(20) NOT NULL,
+    UNIQUE(chain_id, step_order)
+);
+
+CREATE TABLE approval_requests (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    chain_id UUID NOT NULL REFERENCES approval_chains(id),
+    subject_type VARCHAR(20) NOT NULL,
+    subject_id UUID NOT NULL,
+    amount DECIMAL(12, 2) NOT NULL,
+    submitted_by_id UUID NOT NULL REFERENCES users(id),
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved', 'rejected')),
+    -- Index into the chain's steps of the next approval needed
+    current_step INTEGER NOT NULL DEFAULT 0,
+    -- Bumped on each resubmission; decisions from earlier rounds are history
+    round INTEGER NOT NULL DEFAULT 1,
+    rejection_reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(subject_type, subject_id)
+);
+
+CREATE INDEX idx_approval_requests_pending ON approval_requests(tenant_id, status);
+
+CREATE TRIGGER update_approval_requests_updated_at
+    BEFORE UPDATE ON approval_requests
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TABLE approval_decisions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    request_id UUID NOT NULL REFERENCES approval_requests(id) ON DELETE CASCADE,
+    round INTEGER NOT NULL,
+    step_order INTEGER NOT NULL,
+    approver_id UUID NOT NULL REFERENCES users(id),
+    decision VARCHAR(20) NOT NULL CHECK (decision IN ('approved', 'rejected')),
+    reason TEXT,
+    decided_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_approval_decisions_request ON approval_decisions(request_id);
+
+ALTER TABLE expenses ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON expenses
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
+
+ALTER TABLE approval_chains ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON approval_chains
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
+
+ALTER TABLE approval_requests ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON approval_requests
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
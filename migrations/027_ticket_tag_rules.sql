@@ -0,0 +1,33 @@
+-- Ticket tag rules
+-- Lightweight auto-tagging without full automation rules: a keyword or regex
+-- matched against a ticket's title and description adds a tag. Tags added by
+-- rules are tracked in tickets.rule_tags so they can be recomputed on every
+-- save without touching the tags users set by hand.
+
+CREATE TABLE tag_rules (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    match_type VARCHAR(20) NOT NULL DEFAULT 'keyword' CHECK (match_type IN ('keyword', 'regex')),
+    pattern VARCHAR(255) NOT NULL,
+    tag VARCHAR(50) NOT NULL,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_tag_rules_tenant ON tag_rules(tenant_id);
+
+CREATE TRIGGER update_tag_rules_updated_at
+    BEFORE UPDATE ON tag_rules
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+-- Subset of tickets.tags applied by rules
+ALTER TABLE tickets ADD COLUMN rule_tags TEXT[] NOT NULL DEFAULT '{}';
+
+ALTER TABLE tag_rules ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON tag_rules
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
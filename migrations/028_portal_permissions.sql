@@ -0,0 +1,12 @@
+-- Contact-level portal permissions
+-- Portal access used to be all-or-nothing per contact. Each portal contact now
+-- carries what they may do: open tickets, view and pay the company's invoices,
+-- and whether they see all of the company's tickets (a company admin) or only
+-- the ones they raised.
+
+ALTER TABLE contacts
+    ADD COLUMN portal_can_open_tickets BOOLEAN NOT NULL DEFAULT TRUE,
+    ADD COLUMN portal_can_view_invoices BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN portal_can_pay_invoices BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN portal_ticket_scope VARCHAR(20) NOT NULL DEFAULT 'own'
+        CHECK (portal_ticket_scope IN ('own', 'company'));
@@ -0,0 +1,32 @@
+-- Saved views
+-- Named list filters a user can come back to, for tickets, companies and
+-- contacts alike. The filter is stored as the list endpoint's query
+-- parameters in JSON. Private views are visible to their owner only; shared
+-- views to everyone in the tenant.
+
+CREATE TABLE saved_views (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    entity_type VARCHAR(20) NOT NULL CHECK (entity_type IN ('ticket', 'company', 'contact')),
+    name VARCHAR(100) NOT NULL,
+    filters JSONB NOT NULL DEFAULT '{}',
+    is_shared BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_saved_views_tenant_entity ON saved_views(tenant_id, entity_type);
+CREATE INDEX idx_saved_views_user ON saved_views(user_id);
+
+CREATE TRIGGER update_saved_views_updated_at
+    BEFORE UPDATE ON saved_views
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE saved_views ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON saved_views
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
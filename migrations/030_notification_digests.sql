@@ -0,0 +1,30 @@
+-- Notification digests
+-- Users can opt into an hourly or daily digest (users.notification_preferences
+-- -> 'digest') instead of one email per update. Non-critical events are queued
+-- here and batched into a single summary email; critical ones still go out
+-- immediately. included_at marks events already claimed by a digest so no
+-- event is sent twice.
+
+CREATE TABLE notification_digest_items (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    event_type VARCHAR(100) NOT NULL,
+    subject VARCHAR(255) NOT NULL,
+    body TEXT NOT NULL,
+    included_at TIMESTAMPTZ,
+    -- The digest email the event went out in
+    notification_id UUID REFERENCES notifications(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_notification_digest_items_pending
+    ON notification_digest_items(tenant_id, user_id, created_at)
+    WHERE included_at IS NULL;
+
+ALTER TABLE notification_digest_items ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON notification_digest_items
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
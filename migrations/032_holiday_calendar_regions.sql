@@ -0,0 +1,10 @@
+-- Imported holiday calendars
+-- A calendar filled from a bundled public holiday dataset records its region,
+-- so importing another year adds to the same calendar. One per region and tenant;
+-- hand-made calendars leave it NULL.
+
+ALTER TABLE holiday_calendars ADD COLUMN region VARCHAR(10);
+
+CREATE UNIQUE INDEX idx_holiday_calendars_region
+    ON holiday_calendars(tenant_id, region)
+    WHERE region IS NOT NULL;
@@ -0,0 +1,8 @@
+-- Notification template locales
+-- Tenants can write a template per language; sending picks the recipient's
+-- locale, then its language, then English, then the built-in default
+
+ALTER TABLE notification_templates ADD COLUMN locale VARCHAR(10) NOT NULL DEFAULT 'en';
+
+CREATE INDEX idx_notification_templates_lookup
+    ON notification_templates(tenant_id, event_type, channel_type, locale);
@@ -0,0 +1,49 @@
+-- Notification suppression list
+-- Addresses that must not be sent to, per channel. Unsubscribes, complaints
+-- and manual entries stop everything but transactional messages such as
+-- password resets; a hard bounce stops those too. Suppressed sends are still
+-- recorded in notifications, with status 'suppressed'.
+
+CREATE TABLE notification_suppressions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    -- Lowercased email address or phone number
+    address VARCHAR(255) NOT NULL,
+    channels TEXT[] NOT NULL DEFAULT '{email}',
+    reason VARCHAR(20) NOT NULL CHECK (reason IN ('unsubscribed', 'complaint', 'hard_bounce', 'manual')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (tenant_id, address)
+);
+
+CREATE TRIGGER update_notification_suppressions_updated_at
+    BEFORE UPDATE ON notification_suppressions
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE notification_suppressions ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON notification_suppressions
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
+
+-- Unsubscribe links. One per address, so every email to it carries the same link.
+CREATE TABLE unsubscribe_tokens (
+    token VARCHAR(64) PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    address VARCHAR(255) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (tenant_id, address)
+);
+
+ALTER TABLE unsubscribe_tokens ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON unsubscribe_tokens
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
+
+ALTER TABLE notifications DROP CONSTRAINT IF EXISTS notifications_status_check;
+ALTER TABLE notifications ADD CONSTRAINT notifications_status_check
+    CHECK (status IN ('pending', 'sent', 'delivered', 'failed', 'suppressed'));
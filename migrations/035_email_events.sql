@@ -0,0 +1,31 @@
+-- Bounce and complaint handling
+-- Events reported by the email provider, recorded against the address and,
+-- where it can be matched, the message that bounced. Hard bounces and
+-- complaints suppress the address; soft bounces are retried. Contacts carry
+-- the outcome as their email deliverability.
+
+CREATE TABLE email_events (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    -- Lowercased email address
+    address VARCHAR(255) NOT NULL,
+    event_type VARCHAR(20) NOT NULL CHECK (event_type IN ('hard_bounce', 'soft_bounce', 'complaint')),
+    notification_id UUID REFERENCES notifications(id) ON DELETE SET NULL,
+    reason TEXT,
+    provider_event_id VARCHAR(255),
+    occurred_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_email_events_address ON email_events(tenant_id, address);
+CREATE INDEX idx_email_events_notification ON email_events(notification_id) WHERE notification_id IS NOT NULL;
+
+ALTER TABLE email_events ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON email_events
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
+
+ALTER TABLE contacts ADD COLUMN email_deliverability VARCHAR(20) NOT NULL DEFAULT 'ok'
+    CHECK (email_deliverability IN ('ok', 'bounced', 'complained'));
@@ -0,0 +1,31 @@
+-- Tamper-evident audit log
+-- Each tenant's entries form a hash chain: an entry stores its position, the
+-- previous entry's hash and a SHA-256 of its own content plus that hash, so
+-- editing or deleting an entry breaks every link after it. The chain head
+-- records the newest entry, so losing entries from the end is noticed too.
+-- Entries written before this migration are left unchained.
+
+ALTER TABLE audit_log ADD COLUMN sequence BIGINT;
+ALTER TABLE audit_log ADD COLUMN prev_hash VARCHAR(64);
+ALTER TABLE audit_log ADD COLUMN hash VARCHAR(64);
+
+CREATE UNIQUE INDEX idx_audit_log_chain ON audit_log(tenant_id, sequence) WHERE sequence IS NOT NULL;
+
+CREATE TABLE audit_chain_heads (
+    tenant_id UUID PRIMARY KEY REFERENCES tenants(id) ON DELETE CASCADE,
+    sequence BIGINT NOT NULL,
+    hash VARCHAR(64) NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE audit_chain_heads ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON audit_chain_heads
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
+
+CREATE TRIGGER update_audit_chain_heads_updated_at
+    BEFORE UPDATE ON audit_chain_heads
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,26 @@
+-- Ticket deflection tracking
+-- The portal's new-ticket form gets a session id when it opens and reports
+-- each suggested KB article the contact views, then whether they submitted
+-- or walked away. Sessions that viewed an article and never submitted are
+-- the tickets the knowledge base deflected.
+
+CREATE TABLE portal_deflection_events (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    session_id UUID NOT NULL,
+    contact_id UUID REFERENCES contacts(id) ON DELETE SET NULL,
+    event_type VARCHAR(20) NOT NULL CHECK (event_type IN ('article_viewed', 'ticket_submitted', 'ticket_abandoned')),
+    article_id UUID REFERENCES kb_articles(id) ON DELETE SET NULL,
+    ticket_id UUID REFERENCES tickets(id) ON DELETE SET NULL,
+    occurred_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_portal_deflection_events_session ON portal_deflection_events(tenant_id, session_id);
+CREATE INDEX idx_portal_deflection_events_occurred ON portal_deflection_events(tenant_id, occurred_at);
+
+ALTER TABLE portal_deflection_events ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON portal_deflection_events
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
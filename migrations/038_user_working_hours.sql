@@ -0,0 +1,8 @@
+-- Per-technician working hours
+-- A technician's `user_availability` rows are their weekly schedule. The
+-- windows are wall-clock times in the schedule's time zone, so a shift keeps
+-- its local hours across DST. Existing rows were written in UTC.
+
+ALTER TABLE user_availability ADD COLUMN timezone VARCHAR(50) NOT NULL DEFAULT 'UTC';
+
+CREATE INDEX idx_user_availability_tenant_user ON user_availability(tenant_id, user_id);
@@ -0,0 +1,62 @@
+-- Parts inventory
+-- Spare parts kept on hand, with every receipt and use recorded as a
+-- movement. Parts used on a ticket or project reference it, and billable
+-- use links the expense raised for it. Items at or below their reorder
+-- point are flagged for restocking.
+
+CREATE TABLE stock_items (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    sku VARCHAR(100) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    description TEXT,
+    unit_cost DECIMAL(12, 2) CHECK (unit_cost >= 0),
+    -- Charged to the client per unit when billable
+    unit_price DECIMAL(12, 2) CHECK (unit_price >= 0),
+    quantity_on_hand INTEGER NOT NULL DEFAULT 0 CHECK (quantity_on_hand >= 0),
+    reorder_point INTEGER NOT NULL DEFAULT 0 CHECK (reorder_point >= 0),
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(tenant_id, sku)
+);
+
+CREATE INDEX idx_stock_items_tenant ON stock_items(tenant_id);
+
+CREATE TRIGGER update_stock_items_updated_at
+    BEFORE UPDATE ON stock_items
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TABLE stock_movements (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    stock_item_id UUID NOT NULL REFERENCES stock_items(id) ON DELETE CASCADE,
+    movement_type VARCHAR(10) NOT NULL CHECK (movement_type IN ('in', 'out')),
+    quantity INTEGER NOT NULL CHECK (quantity > 0),
+    -- Quantity on hand after the movement
+    balance INTEGER NOT NULL CHECK (balance >= 0),
+    ticket_id UUID REFERENCES tickets(id) ON DELETE SET NULL,
+    project_id UUID REFERENCES projects(id) ON DELETE SET NULL,
+    expense_id UUID REFERENCES expenses(id) ON DELETE SET NULL,
+    notes TEXT,
+    moved_by_id UUID REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_stock_movements_item ON stock_movements(stock_item_id, created_at);
+CREATE INDEX idx_stock_movements_ticket ON stock_movements(ticket_id) WHERE ticket_id IS NOT NULL;
+
+ALTER TABLE stock_items ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON stock_items
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
+
+ALTER TABLE stock_movements ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON stock_movements
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
@@ -0,0 +1,7 @@
+-- Webhook signing secrets
+-- Each subscription gets a secret when it is created, shown to the admin once
+-- and stored encrypted with ENCRYPTION_KEY. Deliveries carry an HMAC-SHA256
+-- signature of the body made with it. Subscriptions created before secrets
+-- have none and are sent unsigned.
+
+ALTER TABLE webhook_subscriptions ADD COLUMN secret_encrypted TEXT;
@@ -0,0 +1,37 @@
+-- Number sequences
+-- One counter per tenant and document kind, each with its own prefix and
+-- zero padding. Replaces ticket_sequences and invoice_sequences; their
+-- counters carry over so no number is issued twice. Rows are created on
+-- first use with the kind's default format.
+
+CREATE TABLE number_sequences (
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    kind VARCHAR(20) NOT NULL CHECK (kind IN ('ticket', 'invoice', 'quote', 'project')),
+    prefix VARCHAR(20) NOT NULL DEFAULT '',
+    padding INTEGER NOT NULL DEFAULT 6 CHECK (padding BETWEEN 1 AND 12),
+    last_number INTEGER NOT NULL DEFAULT 0 CHECK (last_number >= 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (tenant_id, kind)
+);
+
+CREATE TRIGGER update_number_sequences_updated_at
+    BEFORE UPDATE ON number_sequences
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE number_sequences ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON number_sequences
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
+
+INSERT INTO number_sequences (tenant_id, kind, prefix, padding, last_number)
+SELECT tenant_id, 'ticket', 'T', 6, last_number FROM ticket_sequences;
+
+INSERT INTO number_sequences (tenant_id, kind, prefix, padding, last_number)
+SELECT tenant_id, 'invoice', COALESCE(prefix, ''), 6, last_number FROM invoice_sequences;
+
+DROP TABLE ticket_sequences;
+DROP TABLE invoice_sequences;
@@ -0,0 +1,60 @@
+-- Quotes
+-- Priced proposals sent to a company before work is billed. A sent quote
+-- carries a token for the customer's accept/decline link and expires once
+-- valid_until passes unanswered. An accepted quote is converted to a draft
+-- invoice with the same lines, at most once.
+
+CREATE TABLE quotes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    quote_number VARCHAR(50) NOT NULL,
+    company_id UUID NOT NULL REFERENCES companies(id),
+    title VARCHAR(255) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'draft'
+        CHECK (status IN ('draft', 'sent', 'accepted', 'declined', 'expired')),
+    quote_date DATE NOT NULL,
+    valid_until DATE NOT NULL,
+    subtotal DECIMAL(12, 2) NOT NULL DEFAULT 0,
+    total DECIMAL(12, 2) NOT NULL DEFAULT 0,
+    currency VARCHAR(3) DEFAULT 'USD',
+    notes TEXT,
+    token VARCHAR(64) UNIQUE,
+    sent_at TIMESTAMPTZ,
+    responded_at TIMESTAMPTZ,
+    response_comment TEXT,
+    invoice_id UUID UNIQUE REFERENCES invoices(id) ON DELETE SET NULL,
+    created_by_id UUID REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(tenant_id, quote_number)
+);
+
+CREATE INDEX idx_quotes_company ON quotes(tenant_id, company_id);
+CREATE INDEX idx_quotes_sent_validity ON quotes(valid_until) WHERE status = 'sent';
+
+CREATE TRIGGER update_quotes_updated_at
+    BEFORE UPDATE ON quotes
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE quotes ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON quotes
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
+
+CREATE TABLE quote_lines (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    quote_id UUID NOT NULL REFERENCES quotes(id) ON DELETE CASCADE,
+    line_type VARCHAR(20) NOT NULL DEFAULT 'service'
+        CHECK (line_type IN ('service', 'product', 'adjustment', 'discount')),
+    description TEXT NOT NULL,
+    quantity DECIMAL(10, 2) NOT NULL DEFAULT 1,
+    unit_price DECIMAL(12, 2) NOT NULL,
+    total DECIMAL(12, 2) NOT NULL,
+    sort_order INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_quote_lines_quote ON quote_lines(quote_id);
@@ -0,0 +1,13 @@
+-- Contract SLA penalties
+-- Contracts can promise service credits when SLA targets are missed. The
+-- terms are a schedule of tiers by how late a first response or resolution
+-- was, each worth a percentage of the contract's billing amount, with an
+-- optional cap on the total credit for a reporting period:
+--   {"tiers": [{"late_by_minutes": 0, "credit_percent": 2},
+--              {"late_by_minutes": 240, "credit_percent": 5}],
+--    "max_credit_percent": 20}
+
+ALTER TABLE contracts
+    ADD COLUMN sla_penalty_terms JSONB;
+
+CREATE INDEX idx_tickets_contract ON tickets(tenant_id, contract_id) WHERE contract_id IS NOT NULL;
@@ -0,0 +1,13 @@
+-- Ticket snooze
+-- A technician can snooze a ticket until a later time. Snoozed tickets are
+-- left out of active ticket lists until `snoozed_until` passes, when a
+-- `tickets.unsnooze` job clears the snooze and reminds the assignee.
+-- `snoozed_at` records when the snooze began so tenants that have snoozing
+-- pause the SLA clock can move targets back by the time snoozed.
+
+ALTER TABLE tickets
+    ADD COLUMN snoozed_until TIMESTAMPTZ,
+    ADD COLUMN snoozed_at TIMESTAMPTZ,
+    ADD COLUMN snoozed_by_id UUID REFERENCES users(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_tickets_snoozed ON tickets(tenant_id, snoozed_until) WHERE snoozed_until IS NOT NULL;
@@ -0,0 +1,13 @@
+-- Attachment virus scanning
+-- Uploads are passed to the configured scanner before they are stored.
+-- Infected files (and files the scanner failed on) are kept for review but
+-- quarantined: they can't be downloaded. Attachments uploaded before
+-- scanning existed are 'not_scanned'.
+
+ALTER TABLE ticket_attachments
+    ADD COLUMN scan_status VARCHAR(20) NOT NULL DEFAULT 'not_scanned',
+    ADD COLUMN scan_detail TEXT,
+    ADD COLUMN scanned_at TIMESTAMPTZ;
+
+CREATE INDEX idx_ticket_attachments_quarantined ON ticket_attachments(tenant_id)
+    WHERE scan_status IN ('infected', 'failed');
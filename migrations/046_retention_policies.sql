@@ -0,0 +1,43 @@
+-- Data retention policies
+-- A tenant can have old data purged on a schedule: tickets closed longer
+-- than the policy's period are deleted or anonymized, audit entries are
+-- deleted. Records referenced by unbilled time or an unsettled invoice are
+-- never purged. Each run is recorded in the audit log.
+
+CREATE TABLE retention_policies (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    entity VARCHAR(30) NOT NULL CHECK (entity IN ('closed_tickets', 'audit_log')),
+    retain_days INTEGER NOT NULL CHECK (retain_days BETWEEN 1 AND 36500),
+    action VARCHAR(20) NOT NULL DEFAULT 'delete' CHECK (action IN ('delete', 'anonymize')),
+    is_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    last_run_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(tenant_id, entity),
+    -- Rewriting audit entries would break the hash chain
+    CHECK (entity <> 'audit_log' OR action = 'delete')
+);
+
+CREATE TRIGGER update_retention_policies_updated_at
+    BEFORE UPDATE ON retention_policies
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE retention_policies ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON retention_policies
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
+
+-- Anonymized tickets are kept for reporting and skipped by later runs
+ALTER TABLE tickets ADD COLUMN anonymized_at TIMESTAMPTZ;
+
+CREATE INDEX idx_tickets_closed ON tickets(tenant_id, closed_at) WHERE closed_at IS NOT NULL;
+
+-- Purging the start of an audit chain leaves the first remaining entry
+-- linking to one that is gone. The last purged entry is kept here so
+-- verification starts from it.
+ALTER TABLE audit_chain_heads ADD COLUMN base_sequence BIGINT;
+ALTER TABLE audit_chain_heads ADD COLUMN base_hash VARCHAR(64);
@@ -0,0 +1,8 @@
+-- Webhook payload versions
+-- A subscription is pinned to the payload version its endpoint understands.
+-- Existing subscriptions keep the original shape, version 1; new ones get
+-- the latest unless they ask otherwise. Deliveries store the payload as
+-- rendered for the version, so replays send exactly what was sent before.
+
+ALTER TABLE webhook_subscriptions
+    ADD COLUMN payload_version INTEGER NOT NULL DEFAULT 1 CHECK (payload_version IN (1, 2));
@@ -0,0 +1,25 @@
+-- Timestamped notes on companies and contacts
+-- Notes are appended, never edited, so the history shows who added what and
+-- when. The existing single `notes` field on companies and contacts is kept
+-- as a pinned summary above the history.
+
+CREATE TABLE crm_notes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    company_id UUID NOT NULL REFERENCES companies(id) ON DELETE CASCADE,
+    -- Set for notes on one of the company's contacts
+    contact_id UUID REFERENCES contacts(id) ON DELETE CASCADE,
+    author_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    body TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_crm_notes_company ON crm_notes(company_id, created_at DESC);
+CREATE INDEX idx_crm_notes_contact ON crm_notes(contact_id, created_at DESC) WHERE contact_id IS NOT NULL;
+
+ALTER TABLE crm_notes ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON crm_notes
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
@@ -0,0 +1,47 @@
+-- Asset maintenance schedules
+-- Each schedule raises a ticket for its asset some days ahead of every due
+-- date (filter changes, firmware updates). Raised cycles are recorded per
+-- due date so a cycle never gets a second ticket, even when runs overlap.
+
+CREATE TABLE asset_maintenance_schedules (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    asset_id UUID NOT NULL REFERENCES assets(id) ON DELETE CASCADE,
+    -- Template for the tickets raised
+    title VARCHAR(500) NOT NULL,
+    description TEXT,
+    priority_id UUID REFERENCES ticket_priorities(id),
+    queue_id UUID REFERENCES ticket_queues(id),
+    interval_count INTEGER NOT NULL CHECK (interval_count BETWEEN 1 AND 1000),
+    interval_unit VARCHAR(10) NOT NULL CHECK (interval_unit IN ('days', 'weeks', 'months')),
+    lead_days INTEGER NOT NULL DEFAULT 7 CHECK (lead_days BETWEEN 0 AND 365),
+    next_due_date DATE NOT NULL,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    -- Raised tickets are attributed to whoever set up the schedule
+    created_by_id UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_asset_maintenance_schedules_asset ON asset_maintenance_schedules(asset_id);
+CREATE INDEX idx_asset_maintenance_schedules_due ON asset_maintenance_schedules(next_due_date) WHERE is_active;
+
+CREATE TRIGGER update_asset_maintenance_schedules_updated_at
+    BEFORE UPDATE ON asset_maintenance_schedules
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TABLE asset_maintenance_tickets (
+    schedule_id UUID NOT NULL REFERENCES asset_maintenance_schedules(id) ON DELETE CASCADE,
+    due_date DATE NOT NULL,
+    ticket_id UUID REFERENCES tickets(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (schedule_id, due_date)
+);
+
+ALTER TABLE asset_maintenance_schedules ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON asset_maintenance_schedules
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
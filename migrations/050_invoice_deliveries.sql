@@ -0,0 +1,23 @@
+-- Invoice delivery
+-- Each email of an invoice to its billing contact is recorded with the
+-- notification that carried it, so the invoice's delivery history shows
+-- failures and bounces reported later by the email provider.
+
+CREATE TABLE invoice_deliveries (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    invoice_id UUID NOT NULL REFERENCES invoices(id) ON DELETE CASCADE,
+    notification_id UUID REFERENCES notifications(id) ON DELETE SET NULL,
+    recipient VARCHAR(255) NOT NULL,
+    sent_by_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_invoice_deliveries_invoice ON invoice_deliveries(invoice_id, created_at);
+
+ALTER TABLE invoice_deliveries ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON invoice_deliveries
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
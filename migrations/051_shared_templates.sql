@@ -0,0 +1,29 @@
+-- Shared template library
+-- Admins publish automation rules and KB articles to a library every tenant
+-- can import from. Content is a copy taken at publish time; ids it points
+-- at (statuses, priorities, queues, KB categories) are recorded by name so
+-- an importing tenant can resolve them to its own. The library is shared,
+-- so the table has no tenant isolation policy.
+
+CREATE TABLE shared_templates (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    kind VARCHAR(20) NOT NULL CHECK (kind IN ('automation', 'kb_article')),
+    name VARCHAR(255) NOT NULL,
+    description TEXT,
+    content JSONB NOT NULL,
+    -- Source id -> {kind, name} for each tenant record the content points at
+    reference_names JSONB NOT NULL DEFAULT '{}',
+    source_tenant_id UUID REFERENCES tenants(id) ON DELETE SET NULL,
+    published_by_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    import_count INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_shared_templates_kind ON shared_templates(kind, name);
+CREATE INDEX idx_shared_templates_source ON shared_templates(source_tenant_id);
+
+CREATE TRIGGER update_shared_templates_updated_at
+    BEFORE UPDATE ON shared_templates
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,43 @@
+-- Two-way sync reconciliation
+-- When an integration finds a record edited remotely, both copies are
+-- compared and the connection's conflict policy picks a winner. Each
+-- decision is recorded; ones flagged for review hold the record back from
+-- sync until someone picks a side.
+
+ALTER TABLE calendar_connections
+    ADD COLUMN conflict_policy VARCHAR(20) NOT NULL DEFAULT 'local_wins'
+        CHECK (conflict_policy IN ('last_write_wins', 'local_wins', 'remote_wins', 'flag_for_review'));
+
+CREATE TABLE sync_reconciliations (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    -- 'calendar' today; the integration that saw the remote edit
+    integration VARCHAR(50) NOT NULL,
+    connection_id UUID,
+    entity_type VARCHAR(50) NOT NULL,
+    local_id UUID NOT NULL,
+    external_id VARCHAR(255) NOT NULL,
+    policy VARCHAR(20) NOT NULL,
+    decision VARCHAR(20) NOT NULL CHECK (decision IN ('in_sync', 'push_local', 'pull_remote', 'flag_for_review')),
+    -- Both sides changed since the last sync
+    conflict BOOLEAN NOT NULL DEFAULT FALSE,
+    local_value JSONB NOT NULL,
+    remote_value JSONB NOT NULL,
+    local_modified_at TIMESTAMPTZ NOT NULL,
+    remote_modified_at TIMESTAMPTZ NOT NULL,
+    -- Set on flagged decisions once someone picks a side
+    resolved_at TIMESTAMPTZ,
+    resolved_by_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_sync_reconciliations_connection ON sync_reconciliations(connection_id, created_at);
+CREATE INDEX idx_sync_reconciliations_open ON sync_reconciliations(integration, local_id)
+    WHERE decision = 'flag_for_review' AND resolved_at IS NULL;
+
+ALTER TABLE sync_reconciliations ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON sync_reconciliations
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
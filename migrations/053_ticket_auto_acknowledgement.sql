@@ -0,0 +1,10 @@
+-- Ticket auto-acknowledgement
+-- Tickets opened from the portal or by email can get an immediate "we got
+-- your request" email, kept as a public note. Such notes are marked so they
+-- don't record the ticket's first response. The tenant turns it on with the
+-- `tickets.auto_acknowledge` setting; a queue can override it either way.
+
+ALTER TABLE ticket_notes ADD COLUMN counts_as_first_response BOOLEAN NOT NULL DEFAULT TRUE;
+
+-- NULL follows the tenant setting
+ALTER TABLE ticket_queues ADD COLUMN auto_acknowledge BOOLEAN;
@@ -0,0 +1,14 @@
+-- Audit log viewer indexes
+-- The viewer filters a tenant's log by actor, entity type or action, and by
+-- date, always newest first. Each composite index serves one leading filter
+-- plus the date range and the ordering; the single-column tenant, timestamp
+-- and action indexes they cover are dropped.
+
+CREATE INDEX idx_audit_log_tenant_time ON audit_log(tenant_id, timestamp DESC);
+CREATE INDEX idx_audit_log_tenant_user_time ON audit_log(tenant_id, user_id, timestamp DESC);
+CREATE INDEX idx_audit_log_tenant_entity_time ON audit_log(tenant_id, entity_type, timestamp DESC);
+CREATE INDEX idx_audit_log_tenant_action_time ON audit_log(tenant_id, action, timestamp DESC);
+
+DROP INDEX IF EXISTS idx_audit_log_tenant;
+DROP INDEX IF EXISTS idx_audit_log_timestamp;
+DROP INDEX IF EXISTS idx_audit_log_action;
@@ -0,0 +1,24 @@
+-- Ticket status workflow
+-- Each row allows tickets in one status to move to another. A status with
+-- no rows of its own is unrestricted, so tenants that never configure a
+-- workflow keep every transition. Reopening a closed ticket goes through
+-- its own endpoint and is not subject to the workflow.
+
+CREATE TABLE ticket_status_transitions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    from_status_id UUID NOT NULL REFERENCES ticket_statuses(id) ON DELETE CASCADE,
+    to_status_id UUID NOT NULL REFERENCES ticket_statuses(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (from_status_id, to_status_id),
+    CHECK (from_status_id <> to_status_id)
+);
+
+CREATE INDEX idx_ticket_status_transitions_tenant ON ticket_status_transitions(tenant_id);
+
+ALTER TABLE ticket_status_transitions ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON ticket_status_transitions
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
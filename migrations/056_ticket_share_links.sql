@@ -0,0 +1,24 @@
+-- Ticket share links
+-- A tokenized, read-only link to a ticket's public timeline for customers
+-- who ask for the full history. Links expire and can be revoked; expired
+-- and revoked links behave as if they never existed.
+
+CREATE TABLE ticket_share_links (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    ticket_id UUID NOT NULL REFERENCES tickets(id) ON DELETE CASCADE,
+    token VARCHAR(64) NOT NULL UNIQUE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_by_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_ticket_share_links_ticket ON ticket_share_links(ticket_id, created_at DESC);
+
+ALTER TABLE ticket_share_links ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON ticket_share_links
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
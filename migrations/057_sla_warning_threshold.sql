@@ -0,0 +1,9 @@
+-- SLA warning threshold
+-- Tickets turn to Warning once the share of their SLA window left falls
+-- under the policy's warning_percent, rather than a flat two hours before
+-- due. warning_hours caps how early a long window starts warning; NULL
+-- leaves it proportional.
+
+ALTER TABLE sla_policies
+    ADD COLUMN warning_percent SMALLINT NOT NULL DEFAULT 20 CHECK (warning_percent BETWEEN 0 AND 100),
+    ADD COLUMN warning_hours DECIMAL(10, 2) CHECK (warning_hours >= 0);
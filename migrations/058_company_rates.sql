@@ -0,0 +1,35 @@
+-- Company rate history
+-- Effective-dated hourly rates agreed with a client. The rate in effect on
+-- a time entry's date replaces the company and default rate cards for it,
+-- so back-dated entries bill at the rate that applied then. A contract's
+-- rate card still wins. Rates without a work type cover every work type
+-- that has no rate of its own.
+
+CREATE TABLE company_rates (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    company_id UUID NOT NULL REFERENCES companies(id) ON DELETE CASCADE,
+    work_type_id UUID REFERENCES work_types(id) ON DELETE CASCADE,
+    hourly_rate DECIMAL(10, 2) NOT NULL CHECK (hourly_rate >= 0),
+    after_hours_rate DECIMAL(10, 2) CHECK (after_hours_rate >= 0),
+    effective_from DATE NOT NULL,
+    created_by_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- One rate per company, work type and start date; NULL work types compare equal
+CREATE UNIQUE INDEX idx_company_rates_effective ON company_rates(
+    company_id,
+    COALESCE(work_type_id, '00000000-0000-0000-0000-000000000000'::UUID),
+    effective_from
+);
+
+ALTER TABLE company_rates ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON company_rates
+    USING (tenant_id = COALESCE(
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID,
+        tenant_id
+    ));
+
+ALTER TABLE time_entries
+    ADD COLUMN company_rate_id UUID REFERENCES company_rates(id) ON DELETE SET NULL;
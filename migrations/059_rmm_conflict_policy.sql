@@ -0,0 +1,12 @@
+-- RMM sync reconciliation
+-- Inventory sync settles asset details edited both in the PSA and in the
+-- RMM under the connection's conflict policy, like calendar sync. Each
+-- asset keeps the copy last pulled from the RMM so a sync can tell whether
+-- the RMM side changed since; last_sync_at now marks when the two copies
+-- last agreed.
+
+ALTER TABLE rmm_connections
+    ADD COLUMN conflict_policy VARCHAR(20) NOT NULL DEFAULT 'local_wins'
+        CHECK (conflict_policy IN ('last_write_wins', 'local_wins', 'remote_wins', 'flag_for_review'));
+
+ALTER TABLE assets ADD COLUMN rmm_snapshot JSONB;
@@ -0,0 +1,5 @@
+-- Calendar refresh tokens
+-- Provider access tokens expire within about an hour. With a refresh token
+-- stored, sync fetches a new access token before each pass.
+
+ALTER TABLE calendar_connections ADD COLUMN refresh_token_encrypted TEXT;
@@ -0,0 +1,5 @@
+-- System ticket notes
+-- Notes posted by the system, such as auto-close and snooze reminders, have
+-- no author.
+
+ALTER TABLE ticket_notes ALTER COLUMN created_by_id DROP NOT NULL;
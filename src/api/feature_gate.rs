@@ -0,0 +1,68 @@
+//! Per-tenant feature gating
+//!
+//! Cargo features decide which modules are compiled in; `module_config`
+//! decides which of those each tenant can use. A gated router answers
+//! `404 Not Found` to a tenant without the feature, as though the module
+//! wasn't there. Requests without a signed-in user pass through untouched
+//! and are left to the handlers to reject.
+
+use axum::{
+    extract::{Request, State},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    Router,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::modules::auth::AuthState;
+use crate::modules::tenants::{Feature, TenantService};
+use crate::utils::error::AppResult;
+
+/// Checks a tenant's features before a module's routes run
+#[derive(Clone)]
+pub struct FeatureGate {
+    tenant_service: Arc<TenantService>,
+}
+
+impl FeatureGate {
+    pub fn new(tenant_service: TenantService) -> Self {
+        Self {
+            tenant_service: Arc::new(tenant_service),
+        }
+    }
+
+    /// `Ok` when the tenant has `feature`, otherwise not-found
+    pub async fn check(&self, tenant_id: Uuid, feature: Feature) -> AppResult<()> {
+        self.tenant_service.get_features(tenant_id).await?.require(feature)
+    }
+
+    /// Only let `router` answer for tenants with `feature`
+    pub fn gate(&self, feature: Feature, router: Router) -> Router {
+        router.route_layer(middleware::from_fn_with_state(
+            (self.clone(), feature),
+            feature_gate_middleware,
+        ))
+    }
+}
+
+async fn feature_gate_middleware(
+    State((gate, feature)): State<(FeatureGate, Feature)>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let tenant_id = request
+        .extensions()
+        .get::<AuthState>()
+        .and_then(|state| state.user.as_ref())
+        .map(|user| user.tenant_id);
+
+    let Some(tenant_id) = tenant_id else {
+        return next.run(request).await;
+    };
+
+    match gate.check(tenant_id, feature).await {
+        Ok(()) => next.run(request).await,
+        Err(e) => e.into_response(),
+    }
+}
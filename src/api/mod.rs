@@ -1,7 +1,17 @@
 //! API module - combines all API routes
 
+#[cfg(feature = "server")]
+pub mod feature_gate;
+#[cfg(feature = "server")]
+pub mod rate_limit;
 #[cfg(feature = "server")]
 mod router;
+#[cfg(feature = "server")]
+pub mod tenant_resolution;
+#[cfg(feature = "server")]
+mod worker;
 
 #[cfg(feature = "server")]
 pub use router::create_api_router;
+#[cfg(feature = "server")]
+pub use worker::spawn_job_worker;
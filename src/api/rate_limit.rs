@@ -0,0 +1,370 @@
+//! Request rate limiting
+//!
+//! Token-bucket throttling keyed by user or client IP. Each bucket
+//! holds up to `capacity` requests and refills continuously; a request that
+//! finds it empty gets `429 Too Many Requests` with a `Retry-After` header.
+//!
+//! The client IP comes from the connection, so the server must be started
+//! with `into_make_service_with_connect_info::<SocketAddr>()`. There is no
+//! per-API-key tier: the auth middleware only accepts user tokens, and API
+//! keys get their own bucket once they can authenticate a request.
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header::RETRY_AFTER, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex, Once};
+use std::time::{Duration, Instant};
+
+use crate::modules::auth::AuthState;
+use crate::utils::error::AppError;
+
+/// Buckets kept before full (idle) ones are dropped
+const MAX_TRACKED_KEYS: usize = 10_000;
+
+/// Size and refill rate of a bucket
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quota {
+    /// Burst size
+    pub capacity: u32,
+    /// Requests regained per minute
+    pub per_minute: u32,
+}
+
+impl Quota {
+    pub fn per_minute(per_minute: u32) -> Self {
+        Self {
+            capacity: per_minute.max(1),
+            per_minute: per_minute.max(1),
+        }
+    }
+}
+
+/// Which quota a request is held to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RateLimitTier {
+    /// Signed-in users
+    Authenticated,
+    /// Everything else, including the portal's public endpoints
+    Anonymous,
+}
+
+/// Quota per tier, and the proxies whose `X-Forwarded-For` is believed
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    pub authenticated: Quota,
+    pub anonymous: Quota,
+    /// Load balancers and reverse proxies in front of the app. Any other
+    /// client could write its own `X-Forwarded-For` to get a fresh bucket.
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            authenticated: Quota::per_minute(600),
+            anonymous: Quota::per_minute(60),
+            trusted_proxies: Vec::new(),
+        }
+    }
+}
+
+impl RateLimitConfig {
+    /// `RATE_LIMIT_AUTHENTICATED_PER_MINUTE`, `RATE_LIMIT_ANONYMOUS_PER_MINUTE`
+    /// and a comma-separated `TRUSTED_PROXIES`, falling back to the defaults
+    pub fn from_env() -> Self {
+        let per_minute = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<u32>().ok());
+        let defaults = Self::default();
+
+        Self {
+            authenticated: per_minute("RATE_LIMIT_AUTHENTICATED_PER_MINUTE")
+                .map(Quota::per_minute)
+                .unwrap_or(defaults.authenticated),
+            anonymous: per_minute("RATE_LIMIT_ANONYMOUS_PER_MINUTE")
+                .map(Quota::per_minute)
+                .unwrap_or(defaults.anonymous),
+            trusted_proxies: std::env::var("TRUSTED_PROXIES")
+                .map(|proxies| proxies.split(',').filter_map(|ip| ip.trim().parse().ok()).collect())
+                .unwrap_or(defaults.trusted_proxies),
+        }
+    }
+
+    pub fn quota(&self, tier: RateLimitTier) -> Quota {
+        match tier {
+            RateLimitTier::Authenticated => self.authenticated,
+            RateLimitTier::Anonymous => self.anonymous,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn full(quota: Quota, now: Instant) -> Self {
+        Self {
+            tokens: quota.capacity as f64,
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, quota: Quota, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * quota.per_minute as f64 / 60.0).min(quota.capacity as f64);
+        self.updated = now;
+    }
+}
+
+/// Shared rate limiter state
+#[derive(Clone)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Arc<Mutex<HashMap<(RateLimitTier, String), Bucket>>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn trusted_proxies(&self) -> &[IpAddr] {
+        &self.config.trusted_proxies
+    }
+
+    /// Take one token for `key`. On `Err` the caller may retry after the
+    /// returned duration.
+    pub fn check(&self, tier: RateLimitTier, key: &str, now: Instant) -> Result<(), Duration> {
+        let quota = self.config.quota(tier);
+        let mut buckets = self.buckets.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        if buckets.len() >= MAX_TRACKED_KEYS {
+            buckets.retain(|(tier, _), bucket| {
+                let quota = self.config.quota(*tier);
+                bucket.refill(quota, now);
+                bucket.tokens < quota.capacity as f64
+            });
+        }
+
+        let bucket = buckets
+            .entry((tier, key.to_string()))
+            .or_insert_with(|| Bucket::full(quota, now));
+        bucket.refill(quota, now);
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            let missing = 1.0 - bucket.tokens;
+            Err(Duration::from_secs_f64(missing * 60.0 / quota.per_minute as f64))
+        }
+    }
+}
+
+/// Tier and bucket key for a request: the user when signed in, otherwise
+/// the client address. `None` when the server wasn't given client addresses:
+/// each such request counts as its own caller rather than every anonymous
+/// caller sharing one bucket.
+fn request_identity(request: &Request, trusted_proxies: &[IpAddr]) -> Option<(RateLimitTier, String)> {
+    if let Some(user) = request
+        .extensions()
+        .get::<AuthState>()
+        .and_then(|state| state.user.as_ref())
+    {
+        return Some((RateLimitTier::Authenticated, format!("user:{}", user.id)));
+    }
+
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    let forwarded_for = request
+        .headers()
+        .get("X-Forwarded-For")
+        .and_then(|value| value.to_str().ok());
+    let ip = client_ip(peer, forwarded_for, trusted_proxies)?;
+
+    Some((RateLimitTier::Anonymous, format!("ip:{}", ip)))
+}
+
+/// The client's address. `X-Forwarded-For` is only read when the connection
+/// comes from a trusted proxy, and then from the right, skipping the trusted
+/// hops: everything left of the first untrusted address could be made up.
+fn client_ip(peer: Option<IpAddr>, forwarded_for: Option<&str>, trusted_proxies: &[IpAddr]) -> Option<IpAddr> {
+    let peer = peer?;
+    if !trusted_proxies.contains(&peer) {
+        return Some(peer);
+    }
+
+    let hops = forwarded_for
+        .into_iter()
+        .flat_map(|value| value.split(','))
+        .map(|hop| hop.trim().parse::<IpAddr>().ok());
+    let mut client = peer;
+    for hop in hops.rev() {
+        let Some(hop) = hop else {
+            break;
+        };
+        client = hop;
+        if !trusted_proxies.contains(&hop) {
+            break;
+        }
+    }
+    Some(client)
+}
+
+/// Rate limit middleware. Mount it inside the auth middleware so signed-in
+/// users are recognised.
+pub async fn rate_limit_middleware(
+    State(limiter): State<RateLimiter>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some((tier, key)) = request_identity(&request, limiter.trusted_proxies()) else {
+        static NO_CLIENT_ADDRESS: Once = Once::new();
+        NO_CLIENT_ADDRESS.call_once(|| {
+            tracing::warn!("Client addresses are unavailable; anonymous requests are not rate limited");
+        });
+        return next.run(request).await;
+    };
+
+    match limiter.check(tier, &key, Instant::now()) {
+        Ok(()) => next.run(request).await,
+        Err(retry_after) => {
+            tracing::warn!("Rate limit exceeded for {}", key);
+            let seconds = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+            let mut response = AppError::RateLimited.into_response();
+            if let Ok(value) = HeaderValue::from_str(&seconds.to_string()) {
+                response.headers_mut().insert(RETRY_AFTER, value);
+            }
+            response
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(per_minute: u32) -> RateLimiter {
+        RateLimiter::new(RateLimitConfig {
+            authenticated: Quota::per_minute(per_minute * 10),
+            anonymous: Quota::per_minute(per_minute),
+            trusted_proxies: Vec::new(),
+        })
+    }
+
+    #[test]
+    fn test_requests_over_limit_are_rejected() {
+        let limiter = limiter(3);
+        let now = Instant::now();
+
+        for _ in 0..3 {
+            assert!(limiter.check(RateLimitTier::Anonymous, "ip:203.0.113.7", now).is_ok());
+        }
+        let retry_after = limiter
+            .check(RateLimitTier::Anonymous, "ip:203.0.113.7", now)
+            .unwrap_err();
+        // 3 per minute refills one request every 20 seconds
+        assert_eq!(retry_after.as_secs(), 20);
+
+        // Other clients and the authenticated tier have their own buckets
+        assert!(limiter.check(RateLimitTier::Anonymous, "ip:198.51.100.2", now).is_ok());
+        assert!(limiter.check(RateLimitTier::Authenticated, "ip:203.0.113.7", now).is_ok());
+    }
+
+    #[test]
+    fn test_bucket_refills_over_time() {
+        let limiter = limiter(60);
+        let start = Instant::now();
+
+        for _ in 0..60 {
+            assert!(limiter.check(RateLimitTier::Anonymous, "user", start).is_ok());
+        }
+        assert!(limiter.check(RateLimitTier::Anonymous, "user", start).is_err());
+
+        // One request per second comes back
+        let later = start + Duration::from_millis(1500);
+        assert!(limiter.check(RateLimitTier::Anonymous, "user", later).is_ok());
+        assert!(limiter.check(RateLimitTier::Anonymous, "user", later).is_err());
+
+        // Never more than the burst size
+        let much_later = start + Duration::from_secs(3600);
+        for _ in 0..60 {
+            assert!(limiter.check(RateLimitTier::Anonymous, "user", much_later).is_ok());
+        }
+        assert!(limiter.check(RateLimitTier::Anonymous, "user", much_later).is_err());
+    }
+
+    #[test]
+    fn test_forwarded_for_is_only_trusted_from_proxies() {
+        let client: IpAddr = "203.0.113.7".parse().unwrap();
+        let proxy: IpAddr = "10.0.0.2".parse().unwrap();
+        let spoofed = Some("198.51.100.99");
+
+        // Direct connections can't pick their own bucket
+        assert_eq!(client_ip(Some(client), spoofed, &[]), Some(client));
+        assert_eq!(client_ip(Some(client), spoofed, &[proxy]), Some(client));
+
+        // Through the proxy, the rightmost address it didn't add is the client,
+        // whatever the client put in front of it
+        let through_proxy = Some("198.51.100.99, 203.0.113.7");
+        assert_eq!(client_ip(Some(proxy), through_proxy, &[proxy]), Some(client));
+        assert_eq!(client_ip(Some(proxy), Some("203.0.113.7, 10.0.0.2"), &[proxy]), Some(client));
+
+        // A proxy request without the header, or with garbage, is the proxy's own
+        assert_eq!(client_ip(Some(proxy), None, &[proxy]), Some(proxy));
+        assert_eq!(client_ip(Some(proxy), Some("not-an-ip"), &[proxy]), Some(proxy));
+        assert_eq!(client_ip(None, spoofed, &[proxy]), None);
+    }
+
+    #[tokio::test]
+    async fn test_router_limits_each_client_address() {
+        use axum::body::Body;
+        use tower::ServiceExt;
+
+        // Anonymous requests never reach the database
+        let db = crate::db::Database::connect_lazy("postgres://localhost/unused").unwrap();
+        let app = crate::api::create_api_router(db, "test-secret".to_string());
+        let capacity = RateLimitConfig::from_env().anonymous.capacity;
+
+        let health = |peer: Option<&str>| {
+            let mut request = axum::http::Request::builder()
+                .uri("/api/v1/portal/health")
+                .body(Body::empty())
+                .unwrap();
+            if let Some(peer) = peer {
+                let addr: SocketAddr = peer.parse().unwrap();
+                request.extensions_mut().insert(ConnectInfo(addr));
+            }
+            request
+        };
+
+        for _ in 0..capacity {
+            let response = app.clone().oneshot(health(Some("203.0.113.7:5000"))).await.unwrap();
+            assert_eq!(response.status(), 200);
+        }
+        let response = app.clone().oneshot(health(Some("203.0.113.7:5001"))).await.unwrap();
+        assert_eq!(response.status(), 429);
+        assert!(response.headers().contains_key(RETRY_AFTER));
+
+        // Another client still gets through
+        let response = app.clone().oneshot(health(Some("198.51.100.2:5000"))).await.unwrap();
+        assert_eq!(response.status(), 200);
+
+        // Without client addresses, one looping caller can't lock out everyone
+        for _ in 0..=capacity {
+            let response = app.clone().oneshot(health(None)).await.unwrap();
+            assert_eq!(response.status(), 200);
+        }
+    }
+}
@@ -12,11 +12,37 @@ use tower_http::{
     trace::TraceLayer,
 };
 
+use super::feature_gate::FeatureGate;
+use super::rate_limit::{rate_limit_middleware, RateLimitConfig, RateLimiter};
+use super::tenant_resolution::{tenant_resolution_middleware, TenantResolver};
 use crate::db::Database;
+use crate::utils::request_id::{request_id_middleware, request_span, X_REQUEST_ID};
+use crate::modules::approvals::{approval_routes, ApprovalService};
+use crate::modules::assets::{asset_routes, AssetService};
+use crate::modules::audit::{audit_routes, AuditService};
 use crate::modules::auth::{auth_routes, AuthMiddleware, AuthService};
-use crate::modules::contacts::{contact_routes, ContactService};
-use crate::modules::tenants::{tenant_routes, TenantService};
-use crate::modules::tickets::{ticket_routes, TicketService};
+use crate::modules::billing::{billing_routes, quote_response_routes, quote_routes, BillingService, QuoteService};
+use crate::modules::calendar::{
+    booking_routes, calendar_routes, CalendarService, CalendarSyncService,
+};
+use crate::modules::contacts::{contact_routes, ContactService, PrivacyService};
+use crate::modules::dashboard::{dashboard_routes, DashboardService};
+use crate::modules::knowledge_base::{kb_article_routes, kb_category_routes, KnowledgeBaseService};
+use crate::modules::media::{media_routes, MediaService};
+use crate::modules::notifications::{email_event_routes, notification_routes, unsubscribe_routes, NotificationService};
+use crate::modules::portal::{portal_access_routes, PortalService};
+use crate::modules::reports::{report_routes, ReportService};
+use crate::modules::retention::{retention_routes, RetentionService};
+use crate::modules::saved_views::{saved_view_routes, SavedViewService};
+use crate::modules::sequences::{sequence_routes, SequenceService};
+use crate::modules::shared_templates::{shared_template_routes, SharedTemplateService};
+use crate::modules::sla::{holiday_calendar_routes, SlaCalendarService};
+use crate::modules::tenants::{tenant_routes, Feature, TenantService};
+use crate::modules::tickets::{
+    csat_routes, shared_ticket_routes, ticket_routes, AttachmentService, CsatService, InboundEmailProcessor, TicketService,
+};
+use crate::modules::time_tracking::{expense_routes, time_entry_routes, TimeTrackingService};
+use crate::modules::webhooks::{webhook_routes, WebhookService};
 
 /// Application state shared across all routes
 #[derive(Clone)]
@@ -31,11 +57,44 @@ pub fn create_api_router(db: Database, jwt_secret: String) -> Router {
     let auth_service = AuthService::new(db.clone(), jwt_secret.clone());
     let tenant_service = TenantService::new(db.clone());
     let contact_service = ContactService::new(db.clone());
+    let privacy_service = PrivacyService::new(db.clone());
     let ticket_service = TicketService::new(db.clone());
+    let attachment_service = AttachmentService::new(db.clone());
+    let inbound_email_processor = InboundEmailProcessor::new(db.clone());
+    let report_service = ReportService::new(db.clone());
+    let dashboard_service = DashboardService::new(db.clone());
+    let csat_service = CsatService::new(db.clone());
+    let kb_service = KnowledgeBaseService::new(db.clone());
+    let asset_service = AssetService::new(db.clone());
+    let calendar_service = CalendarService::new(db.clone());
+    let calendar_sync_service = CalendarSyncService::new(db.clone());
+    let time_service = TimeTrackingService::new(db.clone());
+    let approval_service = ApprovalService::new(db.clone());
+    let webhook_service = WebhookService::new(db.clone());
+    let billing_service = BillingService::new(db.clone());
+    let quote_service = QuoteService::new(db.clone());
+    let portal_service = PortalService::new(db.clone());
+    let saved_view_service = SavedViewService::new(db.clone());
+    let notification_service = NotificationService::new(db.clone());
+    let sla_calendar_service = SlaCalendarService::new(db.clone());
+    let audit_service = AuditService::new(db.clone());
+    let sequence_service = SequenceService::new(db.clone());
+    let retention_service = RetentionService::new(db.clone());
+    let media_service = MediaService::new(db.clone());
+    let shared_template_service = SharedTemplateService::new(db.clone());
+
+    // Per-tenant module switches, checked inside the auth middleware
+    let features = FeatureGate::new(tenant_service.clone());
 
     // Create auth middleware
     let auth_middleware = AuthMiddleware::new(auth_service.clone());
 
+    // Tenant from the subdomain or X-Tenant header (SaaS)
+    let tenant_resolver = TenantResolver::from_env(tenant_service.clone());
+
+    // Shared so a client cannot dodge its limit by switching between APIs
+    let rate_limiter = RateLimiter::new(RateLimitConfig::from_env());
+
     // Build API v1 routes
     let api_v1 = Router::new()
         // Health check
@@ -45,45 +104,107 @@ pub fn create_api_router(db: Database, jwt_secret: String) -> Router {
         // Tenant management (multi-tenant mode)
         .nest("/tenants", tenant_routes(tenant_service))
         // Contact management
-        .nest("/contacts", contact_routes(contact_service.clone()))
+        .nest(
+            "/contacts",
+            features.gate(
+                Feature::Contacts,
+                contact_routes(contact_service.clone(), privacy_service, saved_view_service.clone()),
+            ),
+        )
+        .nest("/portal-access", features.gate(Feature::ClientPortal, portal_access_routes(portal_service)))
         .nest("/companies", Router::new()) // Alias handled by contact routes
         // Ticketing
-        .nest("/tickets", ticket_routes(ticket_service))
-        // Time tracking (stub)
-        .nest("/time-entries", stub_routes())
+        .nest(
+            "/tickets",
+            features.gate(
+                Feature::Ticketing,
+                ticket_routes(
+                    ticket_service.clone(),
+                    attachment_service,
+                    inbound_email_processor,
+                    saved_view_service.clone(),
+                ),
+            ),
+        )
+        // Avatar and logo uploads; stored images are public
+        .nest("/media", media_routes(media_service))
+        // Public CSAT survey responses (token-authorized)
+        .nest("/csat", csat_routes(csat_service))
+        // Public read-only ticket timelines (token-authorized)
+        .nest("/shared-tickets", shared_ticket_routes(ticket_service))
+        .nest("/unsubscribe", unsubscribe_routes(notification_service.clone()))
+        .nest("/email", email_event_routes(notification_service.clone()))
+        // Time tracking
+        .nest("/time-entries", features.gate(Feature::TimeTracking, time_entry_routes(time_service.clone())))
+        .nest("/expenses", features.gate(Feature::TimeTracking, expense_routes(time_service)))
+        .nest("/approvals", features.gate(Feature::TimeTracking, approval_routes(approval_service)))
         .nest("/timesheets", stub_routes())
         // Projects (stub)
         .nest("/projects", stub_routes())
         .nest("/tasks", stub_routes())
-        // Calendar (stub)
+        // Calendar
+        .nest(
+            "/calendar",
+            features.gate(Feature::Calendar, calendar_routes(calendar_service.clone(), calendar_sync_service)),
+        )
+        // Public appointment booking (token-authorized)
+        .nest("/booking", booking_routes(calendar_service))
         .nest("/appointments", stub_routes())
         .nest("/dispatch", stub_routes())
         // Contracts (stub)
         .nest("/contracts", stub_routes())
         .nest("/rate-cards", stub_routes())
-        // SLA (stub)
+        // SLA
         .nest("/sla-policies", stub_routes())
         .nest("/business-hours", stub_routes())
+        .nest("/holiday-calendars", holiday_calendar_routes(sla_calendar_service))
         // Billing (stub)
-        .nest("/invoices", stub_routes())
+        .nest("/invoices", features.gate(Feature::Billing, billing_routes(billing_service)))
+        .nest("/quotes", features.gate(Feature::Billing, quote_routes(quote_service.clone())))
+        // Public quote accept/decline links (token-authorized)
+        .nest("/quote-response", quote_response_routes(quote_service))
         .nest("/payments", stub_routes())
-        // Assets (stub)
-        .nest("/assets", stub_routes())
+        // Assets
+        .nest("/assets", features.gate(Feature::Assets, asset_routes(asset_service)))
         .nest("/asset-types", stub_routes())
         .nest("/credentials", stub_routes())
-        // Knowledge base (stub)
-        .nest("/kb/articles", stub_routes())
-        .nest("/kb/categories", stub_routes())
+        // Knowledge base
+        .nest("/kb/articles", features.gate(Feature::KnowledgeBase, kb_article_routes(kb_service.clone())))
+        .nest("/kb/categories", features.gate(Feature::KnowledgeBase, kb_category_routes(kb_service)))
         // Notifications (stub)
-        .nest("/notifications", stub_routes())
+        .nest("/notifications", features.gate(Feature::Notifications, notification_routes(notification_service)))
         .nest("/notification-channels", stub_routes())
         // RMM (stub)
         .nest("/rmm/connections", stub_routes())
         .nest("/rmm/devices", stub_routes())
-        // Reports (stub)
-        .nest("/reports", stub_routes())
+        // Reports
+        .nest("/reports", features.gate(Feature::Reports, report_routes(report_service)))
+        // Dashboard
+        .nest("/dashboard", dashboard_routes(dashboard_service))
+        // Saved list filters
+        .nest("/saved-views", saved_view_routes(saved_view_service))
+        // Audit trail
+        .nest("/audit", audit_routes(audit_service))
+        // Webhooks
+        .nest("/settings/webhooks", webhook_routes(webhook_service))
+        // Document numbering
+        .nest("/settings/numbering", sequence_routes(sequence_service))
+        // Data retention
+        .nest("/settings/retention", retention_routes(retention_service))
+        // Template library shared between tenants
+        .nest("/shared-templates", shared_template_routes(shared_template_service))
         // Settings (stub)
         .nest("/settings", stub_routes())
+        // Throttle per user or IP; runs after auth so users are known
+        .layer(middleware::from_fn_with_state(
+            rate_limiter.clone(),
+            rate_limit_middleware,
+        ))
+        // Resolve the tenant; runs after auth to check the token's tenant
+        .layer(middleware::from_fn_with_state(
+            tenant_resolver,
+            tenant_resolution_middleware,
+        ))
         // Apply auth middleware
         .layer(middleware::from_fn_with_state(
             auth_middleware.clone(),
@@ -100,21 +221,26 @@ pub fn create_api_router(db: Database, jwt_secret: String) -> Router {
         // Portal invoices
         .nest("/invoices", stub_routes())
         // Portal KB
-        .nest("/kb", stub_routes());
+        .nest("/kb", stub_routes())
+        // Anonymous tier until portal auth is in place
+        .layer(middleware::from_fn_with_state(rate_limiter, rate_limit_middleware));
 
     // Combine everything
     Router::new()
         .nest("/api/v1", api_v1)
         .nest("/api/v1/portal", portal_api)
         // Apply global middleware
-        .layer(TraceLayer::new_for_http())
+        .layer(TraceLayer::new_for_http().make_span_with(request_span))
         .layer(CompressionLayer::new())
         .layer(
             CorsLayer::new()
                 .allow_origin(Any)
                 .allow_methods(Any)
-                .allow_headers(Any),
+                .allow_headers(Any)
+                .expose_headers([X_REQUEST_ID.clone()]),
         )
+        // Outermost, so the trace span and every error body see the id
+        .layer(middleware::from_fn(request_id_middleware))
 }
 
 /// Health check endpoint
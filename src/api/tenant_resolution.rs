@@ -0,0 +1,248 @@
+//! Per-request tenant resolution
+//!
+//! In SaaS deployments each tenant is served from its own subdomain of
+//! `TENANT_BASE_DOMAIN` (`acme.psa.example.com`), or named by an `X-Tenant`
+//! header holding its slug or id, which wins over the host. The resolved
+//! tenant is put in the request as a `TenantContext`. Unknown or inactive
+//! tenants are `404 Not Found`, and a token issued for one tenant is refused
+//! on another's host. Requests that name no tenant fall back to the signed-in
+//! user's.
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderName},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::db::TenantContext;
+use crate::modules::auth::AuthState;
+use crate::modules::tenants::{Tenant, TenantService, TenantStatus};
+use crate::utils::error::{AppError, AppResult};
+
+pub static X_TENANT: HeaderName = HeaderName::from_static("x-tenant");
+
+/// Subdomains of the base domain that aren't tenants
+const RESERVED_SUBDOMAINS: &[&str] = &["www", "api", "app"];
+
+/// How a request names its tenant
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TenantKey {
+    Id(Uuid),
+    Slug(String),
+}
+
+/// Maps requests to tenants
+#[derive(Clone)]
+pub struct TenantResolver {
+    tenant_service: Arc<TenantService>,
+    /// Tenants are looked up by subdomain of this domain; `None` turns
+    /// subdomain resolution off
+    base_domain: Option<String>,
+}
+
+impl TenantResolver {
+    pub fn new(tenant_service: TenantService, base_domain: Option<String>) -> Self {
+        Self {
+            tenant_service: Arc::new(tenant_service),
+            base_domain: base_domain
+                .map(|domain| domain.trim().trim_start_matches('.').to_ascii_lowercase())
+                .filter(|domain| !domain.is_empty()),
+        }
+    }
+
+    pub fn from_env(tenant_service: TenantService) -> Self {
+        Self::new(tenant_service, std::env::var("TENANT_BASE_DOMAIN").ok())
+    }
+
+    async fn resolve(&self, key: &TenantKey) -> AppResult<Tenant> {
+        let tenant = match key {
+            TenantKey::Id(id) => self.tenant_service.get_tenant(*id).await?,
+            TenantKey::Slug(slug) => self.tenant_service.get_tenant_by_slug(slug).await?,
+        };
+        if tenant.status != TenantStatus::Active {
+            return Err(AppError::NotFound("Tenant".to_string()));
+        }
+        Ok(tenant)
+    }
+}
+
+/// The tenant named by the `X-Tenant` header, or else by the subdomain of
+/// `base_domain` in the `Host`
+pub fn tenant_key(base_domain: Option<&str>, host: Option<&str>, tenant_header: Option<&str>) -> Option<TenantKey> {
+    if let Some(value) = tenant_header.map(str::trim).filter(|value| !value.is_empty()) {
+        return Some(match Uuid::parse_str(value) {
+            Ok(id) => TenantKey::Id(id),
+            Err(_) => TenantKey::Slug(value.to_ascii_lowercase()),
+        });
+    }
+
+    let base_domain = base_domain?;
+    let host = host?.trim().to_ascii_lowercase();
+    let host = host.split(':').next().unwrap_or(&host);
+    let subdomain = host.strip_suffix(base_domain)?.strip_suffix('.')?;
+
+    // One label only: `a.b.example.com` isn't a tenant of `example.com`
+    if subdomain.is_empty() || subdomain.contains('.') || RESERVED_SUBDOMAINS.contains(&subdomain) {
+        return None;
+    }
+    Some(TenantKey::Slug(subdomain.to_string()))
+}
+
+/// Refuse a token issued for a tenant other than the one the request is for
+pub fn ensure_token_tenant(tenant_id: Uuid, auth_state: &AuthState) -> AppResult<()> {
+    let token_tenant = auth_state.user.as_ref().map(|user| user.tenant_id).or(auth_state.tenant_id);
+    match token_tenant {
+        Some(token_tenant) if token_tenant != tenant_id => Err(AppError::Forbidden(
+            "Token was issued for a different tenant".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Resolve the request's tenant. Runs after the auth middleware so the
+/// token's tenant can be checked against it.
+pub async fn tenant_resolution_middleware(
+    State(resolver): State<TenantResolver>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let header_value = |name: &HeaderName| request.headers().get(name).and_then(|value| value.to_str().ok());
+    let key = tenant_key(
+        resolver.base_domain.as_deref(),
+        header_value(&header::HOST),
+        header_value(&X_TENANT),
+    );
+    let auth_state = request.extensions().get::<AuthState>().cloned().unwrap_or_default();
+    let user_id = auth_state.user.as_ref().map(|user| user.id);
+
+    let tenant_id = match key {
+        Some(key) => {
+            let tenant_id = match resolver.resolve(&key).await {
+                Ok(tenant) => tenant.id,
+                Err(e) => return e.into_response(),
+            };
+            if let Err(e) = ensure_token_tenant(tenant_id, &auth_state) {
+                return e.into_response();
+            }
+            Some(tenant_id)
+        }
+        None => auth_state.user.as_ref().map(|user| user.tenant_id),
+    };
+
+    if let Some(tenant_id) = tenant_id {
+        let context = match user_id {
+            Some(user_id) => TenantContext::with_user(tenant_id, user_id),
+            None => TenantContext::new(tenant_id),
+        };
+        request.extensions_mut().insert(context);
+    }
+
+    next.run(request).await
+}
+
+/// Extractor for the request's tenant. Public token routes take it as an
+/// `Option`: when the request names a tenant, tokens issued by any other
+/// tenant are not found.
+#[derive(Clone, Debug)]
+pub struct CurrentTenant(pub TenantContext);
+
+impl CurrentTenant {
+    pub fn tenant_id(&self) -> Uuid {
+        self.0.tenant_id
+    }
+}
+
+impl<S> axum::extract::FromRequestParts<S> for CurrentTenant
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<TenantContext>()
+            .cloned()
+            .map(CurrentTenant)
+            .ok_or_else(|| AppError::Tenant("No tenant for this request".to_string()))
+    }
+}
+
+impl<S> axum::extract::OptionalFromRequestParts<S> for CurrentTenant
+where
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        _state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        Ok(parts.extensions.get::<TenantContext>().cloned().map(CurrentTenant))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::auth::{CurrentUser, UserRole};
+
+    #[test]
+    fn test_subdomain_resolution() {
+        let key = |host: Option<&str>, header: Option<&str>| tenant_key(Some("psa.example.com"), host, header);
+        let slug = |s: &str| Some(TenantKey::Slug(s.to_string()));
+
+        assert_eq!(key(Some("acme.psa.example.com"), None), slug("acme"));
+        assert_eq!(key(Some("Acme.PSA.Example.com:8443"), None), slug("acme"));
+
+        // The bare domain, reserved names, nested subdomains and other
+        // domains name no tenant
+        for host in ["psa.example.com", "www.psa.example.com", "a.b.psa.example.com", "acme.example.org", "evilpsa.example.com"] {
+            assert_eq!(key(Some(host), None), None, "{}", host);
+        }
+        assert_eq!(key(None, None), None);
+
+        // The header wins, by slug or id
+        let id = Uuid::new_v4();
+        assert_eq!(key(Some("acme.psa.example.com"), Some(" Globex ")), slug("globex"));
+        assert_eq!(key(None, Some(&id.to_string())), Some(TenantKey::Id(id)));
+        assert_eq!(key(Some("acme.psa.example.com"), Some("")), slug("acme"));
+
+        // Without a base domain only the header counts
+        assert_eq!(tenant_key(None, Some("acme.psa.example.com"), None), None);
+        assert_eq!(tenant_key(None, None, Some("acme")), slug("acme"));
+    }
+
+    #[test]
+    fn test_cross_tenant_token_rejected() {
+        let tenant_a = Uuid::new_v4();
+        let tenant_b = Uuid::new_v4();
+        let user = CurrentUser {
+            id: Uuid::new_v4(),
+            tenant_id: tenant_a,
+            email: "tech@example.com".to_string(),
+            first_name: "Pat".to_string(),
+            last_name: "Lee".to_string(),
+            role: UserRole::Technician,
+            timezone: "UTC".to_string(),
+            locale: "en".to_string(),
+            avatar_url: None,
+        };
+
+        // A's token on A's host
+        let state = AuthState::authenticated(user, tenant_a);
+        assert!(ensure_token_tenant(tenant_a, &state).is_ok());
+
+        // A's token on B's host
+        assert!(matches!(ensure_token_tenant(tenant_b, &state), Err(AppError::Forbidden(_))));
+
+        // No token: nothing to compare
+        assert!(ensure_token_tenant(tenant_b, &AuthState::default()).is_ok());
+    }
+}
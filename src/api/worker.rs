@@ -0,0 +1,78 @@
+//! Background job worker
+//!
+//! Every job handler is registered here, so a job type enqueued anywhere in
+//! the app has something to run it.
+
+use std::time::Duration;
+
+use crate::db::Database;
+use crate::modules::assets::{AssetService, MAINTENANCE_TICKETS_JOB};
+use crate::modules::billing::{QuoteService, EXPIRE_QUOTES_JOB};
+use crate::modules::jobs::{Job, PgJobQueue, Worker};
+use crate::modules::notifications::{NotificationService, DELIVER_HELD_NOTIFICATION_JOB, RETRY_EMAIL_JOB};
+use crate::modules::retention::{RetentionService, RETENTION_PURGE_JOB, RETENTION_SWEEP_JOB};
+use crate::modules::tickets::{TicketService, UNSNOOZE_TICKET_JOB};
+use crate::modules::webhooks::{WebhookService, WEBHOOK_DELIVERY_JOB};
+
+/// How long the worker sleeps when the queue has nothing due
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The worker with every module's handlers and scheduled sweeps registered
+pub fn job_worker(db: Database) -> Worker<PgJobQueue> {
+    let tickets = TicketService::new(db.clone());
+    let quotes = QuoteService::new(db.clone());
+    let retention = RetentionService::new(db.clone());
+    let notifications = NotificationService::new(db.clone());
+    let assets = AssetService::new(db.clone());
+    let webhooks = WebhookService::new(db.clone());
+
+    Worker::new(PgJobQueue::new(db))
+        .register(UNSNOOZE_TICKET_JOB, move |job: Job| {
+            let tickets = tickets.clone();
+            async move { tickets.run_unsnooze_ticket_job(job).await }
+        })
+        .register(EXPIRE_QUOTES_JOB, move |job: Job| {
+            let quotes = quotes.clone();
+            async move { quotes.run_expire_quotes_job(job).await }
+        })
+        .every(EXPIRE_QUOTES_JOB, chrono::Duration::hours(1))
+        .register(RETENTION_SWEEP_JOB, {
+            let retention = retention.clone();
+            move |job: Job| {
+                let retention = retention.clone();
+                async move { retention.run_sweep_job(job).await }
+            }
+        })
+        .register(RETENTION_PURGE_JOB, move |job: Job| {
+            let retention = retention.clone();
+            async move { retention.run_purge_job(job).await }
+        })
+        .every(RETENTION_SWEEP_JOB, chrono::Duration::days(1))
+        .register(DELIVER_HELD_NOTIFICATION_JOB, {
+            let notifications = notifications.clone();
+            move |job: Job| {
+                let notifications = notifications.clone();
+                async move { notifications.run_deliver_held_notification_job(job).await }
+            }
+        })
+        .register(RETRY_EMAIL_JOB, move |job: Job| {
+            let notifications = notifications.clone();
+            async move { notifications.run_retry_email_job(job).await }
+        })
+        .register(MAINTENANCE_TICKETS_JOB, move |job: Job| {
+            let assets = assets.clone();
+            async move { assets.run_maintenance_tickets_job(job).await }
+        })
+        .every(MAINTENANCE_TICKETS_JOB, chrono::Duration::hours(1))
+        .register(WEBHOOK_DELIVERY_JOB, move |job: Job| {
+            let webhooks = webhooks.clone();
+            async move { webhooks.run_deliver_job(job).await }
+        })
+        .every(WEBHOOK_DELIVERY_JOB, chrono::Duration::minutes(5))
+}
+
+/// Run the job worker in the background for the life of the server
+pub fn spawn_job_worker(db: Database) {
+    let worker = job_worker(db);
+    tokio::spawn(async move { worker.run(POLL_INTERVAL).await });
+}
@@ -2,6 +2,8 @@
 
 use dioxus::prelude::*;
 
+use crate::hooks::use_locale;
+use crate::utils::i18n::t;
 use crate::Route;
 use super::icons::*;
 
@@ -96,42 +98,44 @@ pub fn Sidebar(props: SidebarProps) -> Element {
 
 #[component]
 fn SidebarContent() -> Element {
+    let locale = use_locale();
+
     rsx! {
         nav { class: "flex-1 px-2 py-4 space-y-1",
-            NavItem { to: Route::Dashboard {}, icon: rsx!(HomeIcon {}), label: "Dashboard" }
+            NavItem { to: Route::Dashboard {}, icon: rsx!(HomeIcon {}), label: t("nav.dashboard", &locale, &[]) }
 
-            NavSection { title: "Service Desk" }
-            NavItem { to: Route::TicketList {}, icon: rsx!(TicketIcon {}), label: "Tickets" }
-            NavItem { to: Route::TimeEntryList {}, icon: rsx!(ClockIcon {}), label: "Time Entries" }
-            NavItem { to: Route::Timesheets {}, icon: rsx!(DocumentIcon {}), label: "Timesheets" }
+            NavSection { title: t("nav.section.service_desk", &locale, &[]) }
+            NavItem { to: Route::TicketList {}, icon: rsx!(TicketIcon {}), label: t("nav.tickets", &locale, &[]) }
+            NavItem { to: Route::TimeEntryList {}, icon: rsx!(ClockIcon {}), label: t("nav.time_entries", &locale, &[]) }
+            NavItem { to: Route::Timesheets {}, icon: rsx!(DocumentIcon {}), label: t("nav.timesheets", &locale, &[]) }
 
-            NavSection { title: "Projects" }
-            NavItem { to: Route::ProjectList {}, icon: rsx!(FolderIcon {}), label: "Projects" }
+            NavSection { title: t("nav.section.projects", &locale, &[]) }
+            NavItem { to: Route::ProjectList {}, icon: rsx!(FolderIcon {}), label: t("nav.projects", &locale, &[]) }
 
-            NavSection { title: "CRM" }
-            NavItem { to: Route::CompanyList {}, icon: rsx!(BuildingIcon {}), label: "Companies" }
-            NavItem { to: Route::ContactList {}, icon: rsx!(UsersIcon {}), label: "Contacts" }
+            NavSection { title: t("nav.section.crm", &locale, &[]) }
+            NavItem { to: Route::CompanyList {}, icon: rsx!(BuildingIcon {}), label: t("nav.companies", &locale, &[]) }
+            NavItem { to: Route::ContactList {}, icon: rsx!(UsersIcon {}), label: t("nav.contacts", &locale, &[]) }
 
-            NavSection { title: "Operations" }
-            NavItem { to: Route::Calendar {}, icon: rsx!(CalendarIcon {}), label: "Calendar" }
-            NavItem { to: Route::DispatchBoard {}, icon: rsx!(CalendarIcon {}), label: "Dispatch" }
+            NavSection { title: t("nav.section.operations", &locale, &[]) }
+            NavItem { to: Route::Calendar {}, icon: rsx!(CalendarIcon {}), label: t("nav.calendar", &locale, &[]) }
+            NavItem { to: Route::DispatchBoard {}, icon: rsx!(CalendarIcon {}), label: t("nav.dispatch", &locale, &[]) }
 
-            NavSection { title: "Contracts & Billing" }
-            NavItem { to: Route::ContractList {}, icon: rsx!(DocumentIcon {}), label: "Contracts" }
-            NavItem { to: Route::InvoiceList {}, icon: rsx!(CurrencyIcon {}), label: "Invoices" }
-            NavItem { to: Route::PaymentList {}, icon: rsx!(CurrencyIcon {}), label: "Payments" }
+            NavSection { title: t("nav.section.contracts_billing", &locale, &[]) }
+            NavItem { to: Route::ContractList {}, icon: rsx!(DocumentIcon {}), label: t("nav.contracts", &locale, &[]) }
+            NavItem { to: Route::InvoiceList {}, icon: rsx!(CurrencyIcon {}), label: t("nav.invoices", &locale, &[]) }
+            NavItem { to: Route::PaymentList {}, icon: rsx!(CurrencyIcon {}), label: t("nav.payments", &locale, &[]) }
 
-            NavSection { title: "Assets" }
-            NavItem { to: Route::AssetList {}, icon: rsx!(ServerIcon {}), label: "Assets" }
+            NavSection { title: t("nav.section.assets", &locale, &[]) }
+            NavItem { to: Route::AssetList {}, icon: rsx!(ServerIcon {}), label: t("nav.assets", &locale, &[]) }
 
-            NavSection { title: "Knowledge" }
-            NavItem { to: Route::KBHome {}, icon: rsx!(BookIcon {}), label: "Knowledge Base" }
+            NavSection { title: t("nav.section.knowledge", &locale, &[]) }
+            NavItem { to: Route::KBHome {}, icon: rsx!(BookIcon {}), label: t("nav.knowledge_base", &locale, &[]) }
 
-            NavSection { title: "Analytics" }
-            NavItem { to: Route::Reports {}, icon: rsx!(ChartIcon {}), label: "Reports" }
+            NavSection { title: t("nav.section.analytics", &locale, &[]) }
+            NavItem { to: Route::Reports {}, icon: rsx!(ChartIcon {}), label: t("nav.reports", &locale, &[]) }
 
-            NavSection { title: "Configuration" }
-            NavItem { to: Route::Settings {}, icon: rsx!(CogIcon {}), label: "Settings" }
+            NavSection { title: t("nav.section.configuration", &locale, &[]) }
+            NavItem { to: Route::Settings {}, icon: rsx!(CogIcon {}), label: t("nav.settings", &locale, &[]) }
         }
     }
 }
@@ -247,6 +251,13 @@ pub struct PortalLayoutProps {
 
 #[component]
 pub fn PortalLayout(props: PortalLayoutProps) -> Element {
+    let locale = use_locale();
+    let portal_title = t("portal.title", &locale, &[]);
+    let tickets = t("nav.tickets", &locale, &[]);
+    let invoices = t("nav.invoices", &locale, &[]);
+    let knowledge_base = t("nav.knowledge_base", &locale, &[]);
+    let powered_by = t("portal.powered_by", &locale, &[("name", &t("app.name", &locale, &[]))]);
+
     rsx! {
         div { class: "min-h-screen bg-gray-50 dark:bg-gray-900",
             // Portal header
@@ -258,7 +269,7 @@ pub fn PortalLayout(props: PortalLayoutProps) -> Element {
                             to: Route::PortalHome {},
                             class: "flex items-center",
                             span { class: "text-xl font-bold text-blue-600 dark:text-blue-400",
-                                "Client Portal"
+                                "{portal_title}"
                             }
                         }
 
@@ -267,17 +278,17 @@ pub fn PortalLayout(props: PortalLayoutProps) -> Element {
                             Link {
                                 to: Route::PortalTicketList {},
                                 class: "text-gray-700 dark:text-gray-300 hover:text-blue-600",
-                                "Tickets"
+                                "{tickets}"
                             }
                             Link {
                                 to: Route::PortalInvoiceList {},
                                 class: "text-gray-700 dark:text-gray-300 hover:text-blue-600",
-                                "Invoices"
+                                "{invoices}"
                             }
                             Link {
                                 to: Route::PortalKB {},
                                 class: "text-gray-700 dark:text-gray-300 hover:text-blue-600",
-                                "Knowledge Base"
+                                "{knowledge_base}"
                             }
                         }
 
@@ -305,7 +316,7 @@ pub fn PortalLayout(props: PortalLayoutProps) -> Element {
             footer { class: "bg-white dark:bg-gray-800 border-t border-gray-200 dark:border-gray-700",
                 div { class: "max-w-7xl mx-auto px-4 sm:px-6 lg:px-8 py-6",
                     p { class: "text-sm text-gray-500 dark:text-gray-400 text-center",
-                        "Powered by PSA Platform"
+                        "{powered_by}"
                     }
                 }
             }
@@ -1,8 +1,14 @@
 //! Table components for data display
 
 use dioxus::prelude::*;
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::hooks::use_locale;
+use crate::utils::i18n::t;
 
 use super::button::Spinner;
+use super::form::SelectOption;
 use super::icons::{ChevronDownIcon, ChevronRightIcon};
 
 /// Table container props
@@ -230,19 +236,27 @@ pub fn TableLoading(props: TableLoadingProps) -> Element {
 pub struct TableEmptyProps {
     #[props(default = 5)]
     columns: usize,
-    #[props(default = "No data available".to_string())]
+    /// Defaults to "No data available" in the user's language
+    #[props(default)]
     message: String,
 }
 
 #[component]
 pub fn TableEmpty(props: TableEmptyProps) -> Element {
+    let locale = use_locale();
+    let message = if props.message.is_empty() {
+        t("table.empty", &locale, &[])
+    } else {
+        props.message.clone()
+    };
+
     rsx! {
         TableBody {
             tr {
                 td {
                     colspan: "{props.columns}",
                     class: "px-6 py-12 text-center text-gray-500 dark:text-gray-400",
-                    "{props.message}"
+                    "{message}"
                 }
             }
         }
@@ -268,10 +282,23 @@ pub fn Pagination(props: PaginationProps) -> Element {
     let start_item = (props.current_page - 1) * props.per_page + 1;
     let end_item = std::cmp::min(props.current_page * props.per_page, props.total_items);
 
+    let locale = use_locale();
     if total_pages <= 1 {
         return rsx! {};
     }
 
+    let previous = t("table.previous", &locale, &[]);
+    let next = t("table.next", &locale, &[]);
+    let showing = t(
+        "table.showing",
+        &locale,
+        &[
+            ("from", &start_item.to_string()),
+            ("to", &end_item.to_string()),
+            ("total", &props.total_items.to_string()),
+        ],
+    );
+
     let page_numbers: Vec<usize> = {
         let mut pages = Vec::new();
         let start = std::cmp::max(1, props.current_page.saturating_sub(2));
@@ -290,13 +317,13 @@ pub fn Pagination(props: PaginationProps) -> Element {
                     class: "relative inline-flex items-center rounded-md border border-gray-300 bg-white px-4 py-2 text-sm font-medium text-gray-700 hover:bg-gray-50 disabled:opacity-50 disabled:cursor-not-allowed",
                     disabled: props.current_page <= 1,
                     onclick: move |_| props.onpagechange.call(props.current_page - 1),
-                    "Previous"
+                    "{previous}"
                 }
                 button {
                     class: "relative ml-3 inline-flex items-center rounded-md border border-gray-300 bg-white px-4 py-2 text-sm font-medium text-gray-700 hover:bg-gray-50 disabled:opacity-50 disabled:cursor-not-allowed",
                     disabled: props.current_page >= total_pages,
                     onclick: move |_| props.onpagechange.call(props.current_page + 1),
-                    "Next"
+                    "{next}"
                 }
             }
 
@@ -304,13 +331,7 @@ pub fn Pagination(props: PaginationProps) -> Element {
             div { class: "hidden sm:flex sm:flex-1 sm:items-center sm:justify-between",
                 div {
                     p { class: "text-sm text-gray-700 dark:text-gray-300",
-                        "Showing "
-                        span { class: "font-medium", "{start_item}" }
-                        " to "
-                        span { class: "font-medium", "{end_item}" }
-                        " of "
-                        span { class: "font-medium", "{props.total_items}" }
-                        " results"
+                        "{showing}"
                     }
                 }
                 div {
@@ -394,6 +415,116 @@ pub fn DataTable(props: DataTableProps) -> Element {
     }
 }
 
+/// Pending save of an inline edit
+pub type SaveFuture = Pin<Box<dyn Future<Output = Result<(), String>>>>;
+
+/// An inline-edited value, shown optimistically while its save is in flight
+#[derive(Clone, Debug, PartialEq)]
+pub struct InlineEdit {
+    /// Last value the server accepted
+    pub committed: String,
+    /// What the cell shows
+    pub shown: String,
+    pub saving: bool,
+}
+
+impl InlineEdit {
+    pub fn new(value: impl Into<String>) -> Self {
+        let value = value.into();
+        Self {
+            committed: value.clone(),
+            shown: value,
+            saving: false,
+        }
+    }
+
+    /// Show `value` straight away. Returns it if it needs saving: not while
+    /// another save is in flight, and not if nothing changed.
+    pub fn begin(&mut self, value: String) -> Option<String> {
+        if self.saving || value == self.shown {
+            return None;
+        }
+        self.shown = value.clone();
+        self.saving = true;
+        Some(value)
+    }
+
+    /// Keep the new value on success; put the old one back on failure and
+    /// hand back the error to report
+    pub fn settle(&mut self, result: Result<(), String>) -> Result<(), String> {
+        self.saving = false;
+        match result {
+            Ok(()) => {
+                self.committed = self.shown.clone();
+                Ok(())
+            }
+            Err(e) => {
+                self.shown = self.committed.clone();
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Editable select cell props
+#[derive(Props, Clone, PartialEq)]
+pub struct EditableSelectCellProps {
+    value: String,
+    options: Vec<SelectOption>,
+    /// Saves a new value; the cell shows it until this fails
+    on_save: Callback<String, SaveFuture>,
+    /// Called with the error when a save fails and the cell rolls back
+    #[props(default)]
+    on_error: EventHandler<String>,
+    #[props(default)]
+    class: String,
+}
+
+/// Table cell with a dropdown that saves on change, optimistically
+#[component]
+pub fn EditableSelectCell(props: EditableSelectCellProps) -> Element {
+    let mut edit = use_signal(|| InlineEdit::new(props.value.clone()));
+    let on_save = props.on_save;
+    let on_error = props.on_error;
+
+    let class = format!(
+        "px-6 py-4 whitespace-nowrap text-sm text-gray-900 dark:text-gray-100 {}",
+        props.class
+    );
+    let shown = edit.read().shown.clone();
+    let saving = edit.read().saving;
+
+    rsx! {
+        td { class: "{class}",
+            select {
+                class: "block w-full rounded-md border-0 bg-transparent py-1 pl-2 pr-8 text-sm focus:ring-2 focus:ring-blue-500 dark:text-white disabled:opacity-50",
+                disabled: saving,
+                onclick: move |e| e.stop_propagation(),
+                onchange: move |e| {
+                    let Some(value) = edit.write().begin(e.value()) else {
+                        return;
+                    };
+                    let save = on_save.call(value);
+                    spawn(async move {
+                        let result = save.await;
+                        if let Err(e) = edit.write().settle(result) {
+                            on_error.call(e);
+                        }
+                    });
+                },
+                for option in props.options.iter() {
+                    option {
+                        value: "{option.value}",
+                        selected: option.value == shown,
+                        disabled: option.disabled,
+                        "{option.label}"
+                    }
+                }
+            }
+        }
+    }
+}
+
 /// Badge/tag component for status display
 #[derive(Clone, Copy, PartialEq, Default)]
 pub enum BadgeVariant {
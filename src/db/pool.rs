@@ -30,6 +30,15 @@ impl Database {
         Ok(Self { pool })
     }
 
+    /// A pool that connects on first use, for code that may never query
+    pub fn connect_lazy(database_url: &str) -> AppResult<Self> {
+        let pool = PgPoolOptions::new()
+            .connect_lazy(database_url)
+            .map_err(|e| AppError::Database(format!("Invalid database URL: {}", e)))?;
+
+        Ok(Self { pool })
+    }
+
     /// Get a reference to the connection pool
     pub fn pool(&self) -> &PgPool {
         &self.pool
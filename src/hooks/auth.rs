@@ -3,6 +3,7 @@
 use dioxus::prelude::*;
 
 use crate::modules::auth::{AuthState, CurrentUser};
+use crate::utils::i18n::DEFAULT_LOCALE;
 use crate::Route;
 
 /// Authentication context for the application
@@ -46,6 +47,13 @@ pub fn use_auth() -> Signal<AuthContext> {
     use_context::<Signal<AuthContext>>()
 }
 
+/// Locale for UI strings: the signed-in user's, or English before sign-in
+pub fn use_locale() -> String {
+    try_use_context::<Signal<AuthContext>>()
+        .and_then(|auth| auth.read().user.as_ref().map(|user| user.locale.clone()))
+        .unwrap_or_else(|| DEFAULT_LOCALE.to_string())
+}
+
 /// Hook to require authentication, redirects to login if not authenticated
 pub fn use_require_auth() -> Signal<AuthContext> {
     let auth = use_auth();
@@ -129,6 +137,7 @@ pub fn use_login_form() -> (Signal<LoginFormState>, impl Fn()) {
                     last_name: "User".to_string(),
                     role: crate::modules::auth::UserRole::Admin,
                     timezone: "UTC".to_string(),
+                    locale: "en".to_string(),
                     avatar_url: None,
                 });
                 auth.write().is_loading = false;
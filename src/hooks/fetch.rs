@@ -219,6 +219,32 @@ pub mod api {
         }
     }
 
+    /// Put request
+    #[cfg(feature = "web")]
+    pub async fn put<T: DeserializeOwned, B: Serialize>(
+        path: &str,
+        body: &B,
+    ) -> Result<T, String> {
+        let url = format!("{}{}", API_BASE, path);
+
+        let response = Request::put(&url)
+            .header("Content-Type", "application/json")
+            .json(body)
+            .map_err(|e| e.to_string())?
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if response.ok() {
+            response.json::<T>().await.map_err(|e| e.to_string())
+        } else {
+            Err(format!(
+                "Request failed with status: {}",
+                response.status()
+            ))
+        }
+    }
+
     /// Post request with auth token
     #[cfg(feature = "web")]
     pub async fn post_with_auth<T: DeserializeOwned, B: Serialize>(
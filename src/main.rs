@@ -102,54 +102,90 @@ fn main() {
         tracing::info!("Running in single-tenant mode");
     }
 
-    // Server-side: Use dioxus::serve with custom API routes
+    // Server-side: the Dioxus app with the API routes merged in
     #[cfg(feature = "server")]
-    dioxus::serve(|| async move {
-        use psa_platform::{api::create_api_router, Database};
-
-        // Load configuration
-        let config = AppConfig::from_env().expect("Failed to load configuration");
-
-        // Try to initialize database (optional for development)
-        let db_result = Database::new(&config.database_url).await;
-
-        // Create router based on database availability
-        let router = match db_result {
-            Ok(db) => {
-                // Run migrations if enabled and database is available
-                if config.run_migrations {
-                    if let Err(e) = db.run_migrations().await {
-                        tracing::warn!("Failed to run migrations: {}", e);
-                    } else {
-                        tracing::info!("Database migrations complete");
-                    }
+    {
+        // Debug builds keep dioxus's hot-reloading dev server. Release builds
+        // serve the router themselves so requests carry the client's address,
+        // which anonymous rate limits are keyed on.
+        #[cfg(debug_assertions)]
+        dioxus::serve(|| async move { Ok(server_router().await) });
+
+        #[cfg(not(debug_assertions))]
+        serve_with_client_addresses();
+    }
+
+    // Client-side (WASM): Use dioxus::launch
+    #[cfg(not(feature = "server"))]
+    dioxus::launch(App);
+}
+
+/// Build the server's router: the Dioxus app, plus the API when the database
+/// is reachable
+#[cfg(feature = "server")]
+async fn server_router() -> axum::Router {
+    use psa_platform::{api::{create_api_router, spawn_job_worker}, Database};
+
+    // Load configuration
+    let config = AppConfig::from_env().expect("Failed to load configuration");
+
+    // Try to initialize database (optional for development)
+    let db_result = Database::new(&config.database_url).await;
+
+    // Create router based on database availability
+    let router = match db_result {
+        Ok(db) => {
+            // Run migrations if enabled and database is available
+            if config.run_migrations {
+                if let Err(e) = db.run_migrations().await {
+                    tracing::warn!("Failed to run migrations: {}", e);
+                } else {
+                    tracing::info!("Database migrations complete");
                 }
+            }
 
-                tracing::info!("Database connected");
+            tracing::info!("Database connected");
 
-                // Create the API router with database and JWT secret
-                let api_router = create_api_router(db, config.jwt_secret);
+            // Run queued and scheduled background jobs
+            spawn_job_worker(db.clone());
 
-                // Merge with Dioxus router
-                dioxus::server::router(App).merge(api_router)
-            }
-            Err(e) => {
-                tracing::warn!("Database not available: {}. API routes will not be mounted.", e);
-                tracing::warn!("To enable API routes, start PostgreSQL and restart the server.");
+            // Create the API router with database and JWT secret
+            let api_router = create_api_router(db, config.jwt_secret);
 
-                // Return just the Dioxus router without API routes
-                dioxus::server::router(App)
-            }
-        };
+            // Merge with Dioxus router
+            dioxus::server::router(App).merge(api_router)
+        }
+        Err(e) => {
+            tracing::warn!("Database not available: {}. API routes will not be mounted.", e);
+            tracing::warn!("To enable API routes, start PostgreSQL and restart the server.");
 
-        tracing::info!("Server ready");
+            // Return just the Dioxus router without API routes
+            dioxus::server::router(App)
+        }
+    };
 
-        Ok(router)
-    });
+    tracing::info!("Server ready");
 
-    // Client-side (WASM): Use dioxus::launch
-    #[cfg(not(feature = "server"))]
-    dioxus::launch(App);
+    router
+}
+
+/// Serve on the address `dx` configures, passing each connection's peer
+/// address to the handlers
+#[cfg(all(feature = "server", not(debug_assertions)))]
+fn serve_with_client_addresses() {
+    tokio::runtime::Runtime::new()
+        .expect("Failed to start the async runtime")
+        .block_on(async {
+            let address = dioxus::cli_config::fullstack_address_or_localhost();
+            let listener = tokio::net::TcpListener::bind(address)
+                .await
+                .expect("Failed to bind the server address");
+            let router = server_router().await;
+
+            axum::serve(listener, router.into_make_service_with_connect_info::<std::net::SocketAddr>())
+                .await
+                .expect("Server failed");
+        });
 }
 
 /// Root application component
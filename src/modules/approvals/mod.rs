@@ -0,0 +1,16 @@
+//! Approvals Module
+//!
+//! Approval chains: submissions over a threshold need sign-off from a
+//! sequence of approver roles.
+
+mod models;
+#[cfg(feature = "server")]
+mod service;
+#[cfg(feature = "server")]
+mod routes;
+
+pub use models::*;
+#[cfg(feature = "server")]
+pub use service::ApprovalService;
+#[cfg(feature = "server")]
+pub use routes::approval_routes;
@@ -0,0 +1,421 @@
+//! Approval models and types
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::modules::auth::UserRole;
+use crate::utils::error::{AppError, AppResult};
+
+// ============================================================================
+// ENUMS
+// ============================================================================
+
+/// Kind of submission an approval chain applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalSubject {
+    Expense,
+}
+
+impl ApprovalSubject {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "expense" => Some(Self::Expense),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Expense => "expense",
+        }
+    }
+
+    /// Table of the submissions, which carries their `approval_status`
+    pub fn table(&self) -> &'static str {
+        match self {
+            Self::Expense => "expenses",
+        }
+    }
+}
+
+/// Where a submission is in its chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalRequestStatus {
+    #[default]
+    Pending,
+    Approved,
+    /// Sent back to the submitter
+    Rejected,
+}
+
+impl ApprovalRequestStatus {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "pending" => Some(Self::Pending),
+            "approved" => Some(Self::Approved),
+            "rejected" => Some(Self::Rejected),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Approved => "approved",
+            Self::Rejected => "rejected",
+        }
+    }
+}
+
+/// An approver's decision on one step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecisionKind {
+    Approved,
+    Rejected,
+}
+
+impl DecisionKind {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "approved" => Some(Self::Approved),
+            "rejected" => Some(Self::Rejected),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Approved => "approved",
+            Self::Rejected => "rejected",
+        }
+    }
+}
+
+// ============================================================================
+// CHAINS
+// ============================================================================
+
+/// One sign-off in a chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApprovalStep {
+    pub step_order: i32,
+    pub approver_role: UserRole,
+}
+
+/// Approver roles that must sign off, in order, on submissions of at least
+/// `min_amount`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalChain {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub subject_type: ApprovalSubject,
+    pub name: String,
+    pub min_amount: Decimal,
+    pub is_active: bool,
+    pub steps: Vec<ApprovalStep>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ApprovalChain {
+    /// The chain a submission goes through: the active chain for its subject
+    /// with the highest threshold the amount reaches. `None` means no
+    /// approval is needed.
+    pub fn select(chains: &[ApprovalChain], subject: ApprovalSubject, amount: Decimal) -> Option<&ApprovalChain> {
+        chains
+            .iter()
+            .filter(|chain| chain.is_active && chain.subject_type == subject && !chain.steps.is_empty())
+            .filter(|chain| amount >= chain.min_amount)
+            .max_by_key(|chain| chain.min_amount)
+    }
+}
+
+/// Create approval chain request
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CreateApprovalChainRequest {
+    pub subject_type: ApprovalSubject,
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    pub min_amount: Decimal,
+    /// Roles that sign off, first to last
+    #[validate(length(min = 1, max = 10))]
+    pub approver_roles: Vec<UserRole>,
+}
+
+impl CreateApprovalChainRequest {
+    pub fn check(&self) -> AppResult<()> {
+        if self.min_amount < Decimal::ZERO {
+            return Err(AppError::validation_field("min_amount", "Must not be negative"));
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// REQUESTS
+// ============================================================================
+
+/// A recorded sign-off or rejection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalDecision {
+    pub round: i32,
+    pub step_order: i32,
+    pub approver_id: Uuid,
+    pub decision: DecisionKind,
+    pub reason: Option<String>,
+    pub decided_at: DateTime<Utc>,
+}
+
+/// A submission working its way through a chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalRequest {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub chain_id: Uuid,
+    pub subject_type: ApprovalSubject,
+    pub subject_id: Uuid,
+    pub amount: Decimal,
+    pub submitted_by_id: Uuid,
+    pub status: ApprovalRequestStatus,
+    /// Index into `steps` of the next approval needed
+    pub current_step: i32,
+    /// Submission round; resubmitting after a rejection starts a new one
+    pub round: i32,
+    pub rejection_reason: Option<String>,
+    pub steps: Vec<ApprovalStep>,
+    /// Every decision, including those from earlier rounds
+    pub decisions: Vec<ApprovalDecision>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ApprovalRequest {
+    /// The step waiting for a decision, while the request is pending
+    pub fn current(&self) -> Option<&ApprovalStep> {
+        if self.status != ApprovalRequestStatus::Pending {
+            return None;
+        }
+        usize::try_from(self.current_step).ok().and_then(|i| self.steps.get(i))
+    }
+
+    /// Whether a user with `role` may decide the current step. Admins can
+    /// decide any step.
+    pub fn can_decide(&self, role: UserRole) -> bool {
+        self.current()
+            .is_some_and(|step| role == step.approver_role || role.is_admin())
+    }
+
+    fn check_approver(&self, approver_id: Uuid, role: UserRole) -> AppResult<ApprovalStep> {
+        let step = *self.current().ok_or_else(|| {
+            AppError::BadRequest(format!("Approval request is already {}", self.status.as_str()))
+        })?;
+        if approver_id == self.submitted_by_id {
+            return Err(AppError::Forbidden("You can't approve your own submission".to_string()));
+        }
+        if !self.can_decide(role) {
+            return Err(AppError::Forbidden(format!(
+                "This step needs approval from a {}",
+                step.approver_role
+            )));
+        }
+        Ok(step)
+    }
+
+    /// Sign off the current step; the request is approved once every step is
+    pub fn approve(&mut self, approver_id: Uuid, role: UserRole, now: DateTime<Utc>) -> AppResult<ApprovalDecision> {
+        let step = self.check_approver(approver_id, role)?;
+
+        let decision = ApprovalDecision {
+            round: self.round,
+            step_order: step.step_order,
+            approver_id,
+            decision: DecisionKind::Approved,
+            reason: None,
+            decided_at: now,
+        };
+        self.decisions.push(decision.clone());
+        self.current_step += 1;
+        if self.current_step as usize >= self.steps.len() {
+            self.status = ApprovalRequestStatus::Approved;
+        }
+        Ok(decision)
+    }
+
+    /// Reject at the current step, sending the submission back to the submitter
+    pub fn reject(
+        &mut self,
+        approver_id: Uuid,
+        role: UserRole,
+        reason: &str,
+        now: DateTime<Utc>,
+    ) -> AppResult<ApprovalDecision> {
+        let step = self.check_approver(approver_id, role)?;
+
+        let decision = ApprovalDecision {
+            round: self.round,
+            step_order: step.step_order,
+            approver_id,
+            decision: DecisionKind::Rejected,
+            reason: Some(reason.to_string()),
+            decided_at: now,
+        };
+        self.decisions.push(decision.clone());
+        self.status = ApprovalRequestStatus::Rejected;
+        self.current_step = 0;
+        self.rejection_reason = Some(reason.to_string());
+        Ok(decision)
+    }
+
+    /// Put a rejected submission back through the chain from the first step,
+    /// at its amount after any changes
+    pub fn resubmit(&mut self, user_id: Uuid, amount: Decimal) -> AppResult<()> {
+        if user_id != self.submitted_by_id {
+            return Err(AppError::Forbidden("Only the submitter can resubmit".to_string()));
+        }
+        if self.status != ApprovalRequestStatus::Rejected {
+            return Err(AppError::BadRequest(format!(
+                "Approval request is {}, not rejected",
+                self.status.as_str()
+            )));
+        }
+        self.status = ApprovalRequestStatus::Pending;
+        self.current_step = 0;
+        self.round += 1;
+        self.rejection_reason = None;
+        self.amount = amount;
+        Ok(())
+    }
+
+    /// Sign-offs that count towards the current round
+    pub fn current_approvals(&self) -> impl Iterator<Item = &ApprovalDecision> {
+        self.decisions
+            .iter()
+            .filter(|d| d.round == self.round && d.decision == DecisionKind::Approved)
+    }
+}
+
+/// Reject approval request
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct RejectApprovalRequest {
+    #[validate(length(min = 1, max = 1000))]
+    pub reason: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(min_amount: i64, roles: &[UserRole]) -> ApprovalChain {
+        ApprovalChain {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::nil(),
+            subject_type: ApprovalSubject::Expense,
+            name: format!("Over {}", min_amount),
+            min_amount: Decimal::from(min_amount),
+            is_active: true,
+            steps: roles
+                .iter()
+                .enumerate()
+                .map(|(i, role)| ApprovalStep {
+                    step_order: i as i32 + 1,
+                    approver_role: *role,
+                })
+                .collect(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn request(chain: &ApprovalChain, submitter: Uuid) -> ApprovalRequest {
+        ApprovalRequest {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::nil(),
+            chain_id: chain.id,
+            subject_type: ApprovalSubject::Expense,
+            subject_id: Uuid::new_v4(),
+            amount: Decimal::from(2500),
+            submitted_by_id: submitter,
+            status: ApprovalRequestStatus::Pending,
+            current_step: 0,
+            round: 1,
+            rejection_reason: None,
+            steps: chain.steps.clone(),
+            decisions: Vec::new(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_chain_selected_by_threshold() {
+        let chains = vec![
+            chain(500, &[UserRole::Manager]),
+            chain(2000, &[UserRole::Manager, UserRole::Finance]),
+        ];
+
+        assert!(ApprovalChain::select(&chains, ApprovalSubject::Expense, Decimal::from(100)).is_none());
+        let small = ApprovalChain::select(&chains, ApprovalSubject::Expense, Decimal::from(500)).unwrap();
+        assert_eq!(small.steps.len(), 1);
+        let large = ApprovalChain::select(&chains, ApprovalSubject::Expense, Decimal::from(2500)).unwrap();
+        assert_eq!(large.id, chains[1].id);
+    }
+
+    #[test]
+    fn test_two_step_chain_needs_both_approvals() {
+        let chain = chain(2000, &[UserRole::Manager, UserRole::Finance]);
+        let submitter = Uuid::new_v4();
+        let (manager, finance) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut request = request(&chain, submitter);
+        let now = Utc::now();
+
+        // Nobody approves their own submission, and finance can't take the manager's step
+        assert!(matches!(request.approve(submitter, UserRole::Manager, now), Err(AppError::Forbidden(_))));
+        assert!(matches!(request.approve(finance, UserRole::Finance, now), Err(AppError::Forbidden(_))));
+
+        request.approve(manager, UserRole::Manager, now).unwrap();
+        assert_eq!(request.status, ApprovalRequestStatus::Pending);
+        assert_eq!(request.current().unwrap().approver_role, UserRole::Finance);
+
+        request.approve(finance, UserRole::Finance, now).unwrap();
+        assert_eq!(request.status, ApprovalRequestStatus::Approved);
+        assert_eq!(request.current_approvals().count(), 2);
+        assert!(request.current().is_none());
+        assert!(request.approve(finance, UserRole::Finance, now).is_err());
+    }
+
+    #[test]
+    fn test_rejection_resets_chain() {
+        let chain = chain(2000, &[UserRole::Manager, UserRole::Finance]);
+        let submitter = Uuid::new_v4();
+        let (manager, finance) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut request = request(&chain, submitter);
+        let now = Utc::now();
+
+        request.approve(manager, UserRole::Manager, now).unwrap();
+        request.reject(finance, UserRole::Finance, "Missing receipt", now).unwrap();
+        assert_eq!(request.status, ApprovalRequestStatus::Rejected);
+        assert_eq!(request.current_step, 0);
+        assert_eq!(request.rejection_reason.as_deref(), Some("Missing receipt"));
+
+        // Back with the submitter; nobody else can resubmit it
+        assert!(matches!(request.resubmit(manager, request.amount), Err(AppError::Forbidden(_))));
+        request.resubmit(submitter, Decimal::from(2400)).unwrap();
+        assert_eq!(request.status, ApprovalRequestStatus::Pending);
+        assert_eq!(request.round, 2);
+        assert_eq!(request.rejection_reason, None);
+
+        // The earlier manager sign-off no longer counts
+        assert_eq!(request.current_approvals().count(), 0);
+        assert_eq!(request.current().unwrap().approver_role, UserRole::Manager);
+        request.approve(manager, UserRole::Manager, now).unwrap();
+        request.approve(finance, UserRole::Finance, now).unwrap();
+        assert_eq!(request.status, ApprovalRequestStatus::Approved);
+        assert_eq!(request.decisions.len(), 4);
+    }
+}
@@ -0,0 +1,144 @@
+//! Approval API routes
+
+use axum::{
+    extract::{Path, State},
+    routing::{delete, get, post},
+    Json, Router,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::{ApprovalChain, ApprovalRequest, ApprovalService, CreateApprovalChainRequest, RejectApprovalRequest};
+use crate::modules::auth::{RequireAdmin, RequireAuth};
+use crate::utils::error::AppResult;
+use crate::utils::validation::ValidatedJson;
+
+#[derive(Clone)]
+pub struct ApprovalRouterState {
+    pub approval_service: Arc<ApprovalService>,
+}
+
+/// Create the approvals router
+pub fn approval_routes(approval_service: ApprovalService) -> Router {
+    let state = ApprovalRouterState {
+        approval_service: Arc::new(approval_service),
+    };
+
+    Router::new()
+        .route("/", get(list_pending))
+        .route("/chains", get(list_chains))
+        .route("/chains", post(create_chain))
+        .route("/chains/:chain_id", delete(deactivate_chain))
+        .route("/:request_id", get(get_request))
+        .route("/:request_id/approve", post(approve_request))
+        .route("/:request_id/reject", post(reject_request))
+        .route("/:request_id/resubmit", post(resubmit_request))
+        .with_state(state)
+}
+
+// ============================================================================
+// CHAIN HANDLERS
+// ============================================================================
+
+async fn list_chains(
+    State(state): State<ApprovalRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+) -> AppResult<Json<Vec<ApprovalChain>>> {
+    let chains = state.approval_service.list_chains(user.tenant_id).await?;
+
+    Ok(Json(chains))
+}
+
+async fn create_chain(
+    State(state): State<ApprovalRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    ValidatedJson(request): ValidatedJson<CreateApprovalChainRequest>,
+) -> AppResult<Json<ApprovalChain>> {
+    let chain = state
+        .approval_service
+        .create_chain(user.tenant_id, &request)
+        .await?;
+
+    Ok(Json(chain))
+}
+
+async fn deactivate_chain(
+    State(state): State<ApprovalRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Path(chain_id): Path<Uuid>,
+) -> AppResult<()> {
+    state
+        .approval_service
+        .deactivate_chain(user.tenant_id, chain_id)
+        .await
+}
+
+// ============================================================================
+// REQUEST HANDLERS
+// ============================================================================
+
+/// Requests waiting on the caller's role
+async fn list_pending(
+    State(state): State<ApprovalRouterState>,
+    RequireAuth(user): RequireAuth,
+) -> AppResult<Json<Vec<ApprovalRequest>>> {
+    let requests = state
+        .approval_service
+        .pending_for(user.tenant_id, user.role)
+        .await?;
+
+    Ok(Json(requests))
+}
+
+async fn get_request(
+    State(state): State<ApprovalRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(request_id): Path<Uuid>,
+) -> AppResult<Json<ApprovalRequest>> {
+    let request = state
+        .approval_service
+        .get_request(user.tenant_id, request_id)
+        .await?;
+
+    Ok(Json(request))
+}
+
+async fn approve_request(
+    State(state): State<ApprovalRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(request_id): Path<Uuid>,
+) -> AppResult<Json<ApprovalRequest>> {
+    let request = state
+        .approval_service
+        .approve(user.tenant_id, request_id, user.id, user.role)
+        .await?;
+
+    Ok(Json(request))
+}
+
+async fn reject_request(
+    State(state): State<ApprovalRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(request_id): Path<Uuid>,
+    ValidatedJson(body): ValidatedJson<RejectApprovalRequest>,
+) -> AppResult<Json<ApprovalRequest>> {
+    let request = state
+        .approval_service
+        .reject(user.tenant_id, request_id, user.id, user.role, &body.reason)
+        .await?;
+
+    Ok(Json(request))
+}
+
+async fn resubmit_request(
+    State(state): State<ApprovalRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(request_id): Path<Uuid>,
+) -> AppResult<Json<ApprovalRequest>> {
+    let request = state
+        .approval_service
+        .resubmit(user.tenant_id, request_id, user.id)
+        .await?;
+
+    Ok(Json(request))
+}
@@ -0,0 +1,486 @@
+//! Approval service implementation
+
+use chrono::Utc;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::modules::auth::UserRole;
+use crate::utils::error::{AppError, AppResult};
+
+use super::models::*;
+
+const REQUEST_COLUMNS: &str = r#"
+    id, tenant_id, chain_id, subject_type, subject_id, amount, submitted_by_id, status,
+    current_step, round, rejection_reason, created_at, updated_at
+"#;
+
+/// Approval chain service
+#[derive(Clone)]
+pub struct ApprovalService {
+    db: Database,
+}
+
+impl ApprovalService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    // ========================================================================
+    // CHAINS
+    // ========================================================================
+
+    /// All of a tenant's chains with their steps
+    pub async fn list_chains(&self, tenant_id: Uuid) -> AppResult<Vec<ApprovalChain>> {
+        let rows = sqlx::query_as::<_, ChainRow>(
+            r#"
+            SELECT id, tenant_id, subject_type, name, min_amount, is_active, created_at, updated_at
+            FROM approval_chains
+            WHERE tenant_id = $1
+            ORDER BY subject_type, min_amount
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let chain_ids: Vec<Uuid> = rows.iter().map(|row| row.id).collect();
+        let steps = sqlx::query_as::<_, (Uuid, i32, String)>(
+            r#"
+            SELECT chain_id, step_order, approver_role
+            FROM approval_chain_steps
+            WHERE chain_id = ANY($1)
+            ORDER BY chain_id, step_order
+            "#,
+        )
+        .bind(&chain_ids)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let chain_steps = steps
+                    .iter()
+                    .filter(|(chain_id, _, _)| *chain_id == row.id)
+                    .filter_map(|(_, step_order, role)| {
+                        UserRole::from_str(role).map(|approver_role| ApprovalStep {
+                            step_order: *step_order,
+                            approver_role,
+                        })
+                    })
+                    .collect();
+                row.into_chain(chain_steps)
+            })
+            .collect())
+    }
+
+    /// Create a chain; its steps are the roles in the order given
+    pub async fn create_chain(
+        &self,
+        tenant_id: Uuid,
+        request: &CreateApprovalChainRequest,
+    ) -> AppResult<ApprovalChain> {
+        request.check()?;
+
+        let mut tx = self.db.pool().begin().await?;
+
+        let chain_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO approval_chains (tenant_id, subject_type, name, min_amount)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(request.subject_type.as_str())
+        .bind(&request.name)
+        .bind(request.min_amount)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        for (i, role) in request.approver_roles.iter().enumerate() {
+            sqlx::query("INSERT INTO approval_chain_steps (chain_id, step_order, approver_role) VALUES ($1, $2, $3)")
+                .bind(chain_id)
+                .bind(i as i32 + 1)
+                .bind(role.as_str())
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        self.list_chains(tenant_id)
+            .await?
+            .into_iter()
+            .find(|chain| chain.id == chain_id)
+            .ok_or_else(|| AppError::not_found("Approval chain"))
+    }
+
+    /// Deactivate a chain. Requests already on it finish under it.
+    pub async fn deactivate_chain(&self, tenant_id: Uuid, chain_id: Uuid) -> AppResult<()> {
+        let result = sqlx::query(
+            "UPDATE approval_chains SET is_active = FALSE, updated_at = NOW() WHERE id = $1 AND tenant_id = $2",
+        )
+        .bind(chain_id)
+        .bind(tenant_id)
+        .execute(self.db.pool())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::not_found("Approval chain"));
+        }
+        Ok(())
+    }
+
+    // ========================================================================
+    // REQUESTS
+    // ========================================================================
+
+    /// Start approval for a new submission. Returns `None` when no chain
+    /// applies to the amount, in which case the caller approves it outright.
+    pub async fn submit(
+        &self,
+        tenant_id: Uuid,
+        subject: ApprovalSubject,
+        subject_id: Uuid,
+        amount: Decimal,
+        submitted_by_id: Uuid,
+    ) -> AppResult<Option<ApprovalRequest>> {
+        let chains = self.list_chains(tenant_id).await?;
+        let Some(chain) = ApprovalChain::select(&chains, subject, amount) else {
+            return Ok(None);
+        };
+
+        let request_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO approval_requests (tenant_id, chain_id, subject_type, subject_id, amount, submitted_by_id)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(chain.id)
+        .bind(subject.as_str())
+        .bind(subject_id)
+        .bind(amount)
+        .bind(submitted_by_id)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        self.get_request(tenant_id, request_id).await.map(Some)
+    }
+
+    /// Get a request with its chain's steps and decision history
+    pub async fn get_request(&self, tenant_id: Uuid, request_id: Uuid) -> AppResult<ApprovalRequest> {
+        let row = sqlx::query_as::<_, RequestRow>(&format!(
+            "SELECT {} FROM approval_requests WHERE id = $1 AND tenant_id = $2",
+            REQUEST_COLUMNS
+        ))
+        .bind(request_id)
+        .bind(tenant_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::not_found("Approval request"))?;
+
+        self.load(row).await
+    }
+
+    /// The request for a submission, if it needed approval
+    pub async fn request_for(
+        &self,
+        tenant_id: Uuid,
+        subject: ApprovalSubject,
+        subject_id: Uuid,
+    ) -> AppResult<Option<ApprovalRequest>> {
+        let row = sqlx::query_as::<_, RequestRow>(&format!(
+            "SELECT {} FROM approval_requests WHERE tenant_id = $1 AND subject_type = $2 AND subject_id = $3",
+            REQUEST_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(subject.as_str())
+        .bind(subject_id)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        match row {
+            Some(row) => self.load(row).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Pending requests whose current step the role can decide, oldest first
+    pub async fn pending_for(&self, tenant_id: Uuid, role: UserRole) -> AppResult<Vec<ApprovalRequest>> {
+        let rows = sqlx::query_as::<_, RequestRow>(&format!(
+            "SELECT {} FROM approval_requests WHERE tenant_id = $1 AND status = 'pending' ORDER BY created_at",
+            REQUEST_COLUMNS
+        ))
+        .bind(tenant_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let mut pending = Vec::new();
+        for row in rows {
+            let request = self.load(row).await?;
+            if request.can_decide(role) {
+                pending.push(request);
+            }
+        }
+        Ok(pending)
+    }
+
+    /// Sign off the request's current step
+    pub async fn approve(
+        &self,
+        tenant_id: Uuid,
+        request_id: Uuid,
+        approver_id: Uuid,
+        role: UserRole,
+    ) -> AppResult<ApprovalRequest> {
+        let mut request = self.get_request(tenant_id, request_id).await?;
+        let expected = (request.current_step, request.round);
+        let decision = request.approve(approver_id, role, Utc::now())?;
+
+        self.save(&request, expected, Some(&decision)).await?;
+        self.get_request(tenant_id, request_id).await
+    }
+
+    /// Reject the request, sending the submission back to its submitter
+    pub async fn reject(
+        &self,
+        tenant_id: Uuid,
+        request_id: Uuid,
+        approver_id: Uuid,
+        role: UserRole,
+        reason: &str,
+    ) -> AppResult<ApprovalRequest> {
+        let mut request = self.get_request(tenant_id, request_id).await?;
+        let expected = (request.current_step, request.round);
+        let decision = request.reject(approver_id, role, reason, Utc::now())?;
+
+        self.save(&request, expected, Some(&decision)).await?;
+        self.get_request(tenant_id, request_id).await
+    }
+
+    /// Send a rejected submission through its chain again
+    pub async fn resubmit(&self, tenant_id: Uuid, request_id: Uuid, user_id: Uuid) -> AppResult<ApprovalRequest> {
+        let mut request = self.get_request(tenant_id, request_id).await?;
+        let expected = (request.current_step, request.round);
+
+        let amount: Decimal = sqlx::query_scalar(&format!(
+            "SELECT amount FROM {} WHERE id = $1 AND tenant_id = $2",
+            request.subject_type.table()
+        ))
+        .bind(request.subject_id)
+        .bind(tenant_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::not_found("Submission"))?;
+
+        request.resubmit(user_id, amount)?;
+
+        self.save(&request, expected, None).await?;
+        self.get_request(tenant_id, request_id).await
+    }
+
+    /// Persist a transition and mirror the outcome onto the submission.
+    /// `expected` is the `(current_step, round)` the transition started from;
+    /// a concurrent decision on the same step makes this one fail.
+    async fn save(
+        &self,
+        request: &ApprovalRequest,
+        expected: (i32, i32),
+        decision: Option<&ApprovalDecision>,
+    ) -> AppResult<()> {
+        let mut tx = self.db.pool().begin().await?;
+
+        let updated = sqlx::query(
+            r#"
+            UPDATE approval_requests
+            SET status = $1, current_step = $2, round = $3, rejection_reason = $4, amount = $5, updated_at = NOW()
+            WHERE id = $6 AND tenant_id = $7 AND current_step = $8 AND round = $9
+            "#,
+        )
+        .bind(request.status.as_str())
+        .bind(request.current_step)
+        .bind(request.round)
+        .bind(&request.rejection_reason)
+        .bind(request.amount)
+        .bind(request.id)
+        .bind(request.tenant_id)
+        .bind(expected.0)
+        .bind(expected.1)
+        .execute(&mut *tx)
+        .await?;
+
+        if updated.rows_affected() == 0 {
+            return Err(AppError::Conflict(
+                "Approval request was updated by someone else; reload and try again".to_string(),
+            ));
+        }
+
+        if let Some(decision) = decision {
+            sqlx::query(
+                r#"
+                INSERT INTO approval_decisions (request_id, round, step_order, approver_id, decision, reason, decided_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                "#,
+            )
+            .bind(request.id)
+            .bind(decision.round)
+            .bind(decision.step_order)
+            .bind(decision.approver_id)
+            .bind(decision.decision.as_str())
+            .bind(&decision.reason)
+            .bind(decision.decided_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query(&format!(
+            r#"
+            UPDATE {}
+            SET approval_status = $1,
+                approved_at = CASE WHEN $1 = 'approved' THEN NOW() END,
+                rejection_reason = $2,
+                updated_at = NOW()
+            WHERE id = $3 AND tenant_id = $4
+            "#,
+            request.subject_type.table()
+        ))
+        .bind(request.status.as_str())
+        .bind(&request.rejection_reason)
+        .bind(request.subject_id)
+        .bind(request.tenant_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn load(&self, row: RequestRow) -> AppResult<ApprovalRequest> {
+        let steps = sqlx::query_as::<_, (i32, String)>(
+            "SELECT step_order, approver_role FROM approval_chain_steps WHERE chain_id = $1 ORDER BY step_order",
+        )
+        .bind(row.chain_id)
+        .fetch_all(self.db.pool())
+        .await?
+        .into_iter()
+        .filter_map(|(step_order, role)| {
+            UserRole::from_str(&role).map(|approver_role| ApprovalStep { step_order, approver_role })
+        })
+        .collect();
+
+        let decisions = sqlx::query_as::<_, DecisionRow>(
+            r#"
+            SELECT round, step_order, approver_id, decision, reason, decided_at
+            FROM approval_decisions
+            WHERE request_id = $1
+            ORDER BY decided_at
+            "#,
+        )
+        .bind(row.id)
+        .fetch_all(self.db.pool())
+        .await?
+        .into_iter()
+        .filter_map(DecisionRow::into_decision)
+        .collect();
+
+        Ok(row.into_request(steps, decisions))
+    }
+}
+
+// ============================================================================
+// DATABASE ROW TYPES
+// ============================================================================
+
+#[derive(sqlx::FromRow)]
+struct ChainRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    subject_type: String,
+    name: String,
+    min_amount: Decimal,
+    is_active: bool,
+    created_at: chrono::DateTime<chrono::Utc>,
+    updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl ChainRow {
+    fn into_chain(self, steps: Vec<ApprovalStep>) -> ApprovalChain {
+        ApprovalChain {
+            id: self.id,
+            tenant_id: self.tenant_id,
+            subject_type: ApprovalSubject::from_str(&self.subject_type).unwrap_or(ApprovalSubject::Expense),
+            name: self.name,
+            min_amount: self.min_amount,
+            is_active: self.is_active,
+            steps,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct RequestRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    chain_id: Uuid,
+    subject_type: String,
+    subject_id: Uuid,
+    amount: Decimal,
+    submitted_by_id: Uuid,
+    status: String,
+    current_step: i32,
+    round: i32,
+    rejection_reason: Option<String>,
+    created_at: chrono::DateTime<chrono::Utc>,
+    updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl RequestRow {
+    fn into_request(self, steps: Vec<ApprovalStep>, decisions: Vec<ApprovalDecision>) -> ApprovalRequest {
+        ApprovalRequest {
+            id: self.id,
+            tenant_id: self.tenant_id,
+            chain_id: self.chain_id,
+            subject_type: ApprovalSubject::from_str(&self.subject_type).unwrap_or(ApprovalSubject::Expense),
+            subject_id: self.subject_id,
+            amount: self.amount,
+            submitted_by_id: self.submitted_by_id,
+            status: ApprovalRequestStatus::from_str(&self.status).unwrap_or_default(),
+            current_step: self.current_step,
+            round: self.round,
+            rejection_reason: self.rejection_reason,
+            steps,
+            decisions,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct DecisionRow {
+    round: i32,
+    step_order: i32,
+    approver_id: Uuid,
+    decision: String,
+    reason: Option<String>,
+    decided_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl DecisionRow {
+    fn into_decision(self) -> Option<ApprovalDecision> {
+        Some(ApprovalDecision {
+            round: self.round,
+            step_order: self.step_order,
+            approver_id: self.approver_id,
+            decision: DecisionKind::from_str(&self.decision)?,
+            reason: self.reason,
+            decided_at: self.decided_at,
+        })
+    }
+}
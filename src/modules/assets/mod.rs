@@ -1 +1,16 @@
-//! assets module placeholder
+//! Assets Module
+//!
+//! Asset inventory, lifecycle and depreciation, RMM sync, software license tracking
+//! and parts stock.
+
+mod models;
+#[cfg(feature = "server")]
+mod service;
+#[cfg(feature = "server")]
+mod routes;
+
+pub use models::*;
+#[cfg(feature = "server")]
+pub use service::AssetService;
+#[cfg(feature = "server")]
+pub use routes::asset_routes;
@@ -0,0 +1,1212 @@
+//! Asset models and types
+
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::utils::error::AppError;
+use crate::utils::reconcile::{ConflictPolicy, Reconciliation, Versioned};
+
+// ============================================================================
+// ASSETS
+// ============================================================================
+
+/// Asset lifecycle status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetStatus {
+    #[default]
+    InStock,
+    Deployed,
+    InRepair,
+    Retired,
+}
+
+impl AssetStatus {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "in_stock" => Some(Self::InStock),
+            "deployed" => Some(Self::Deployed),
+            "in_repair" => Some(Self::InRepair),
+            "retired" => Some(Self::Retired),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::InStock => "in_stock",
+            Self::Deployed => "deployed",
+            Self::InRepair => "in_repair",
+            Self::Retired => "retired",
+        }
+    }
+
+    /// Retired is terminal; every other status can move to any other
+    pub fn validate_transition(&self, to: AssetStatus) -> Result<(), AppError> {
+        if *self == to || *self == Self::Retired {
+            return Err(AppError::validation_field(
+                "status",
+                format!("Cannot move an asset from {} to {}", self.as_str(), to.as_str()),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Asset {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub asset_tag: Option<String>,
+    pub name: String,
+    pub asset_type_id: Uuid,
+    pub company_id: Uuid,
+    pub site_id: Option<Uuid>,
+    pub contact_id: Option<Uuid>,
+    pub status: AssetStatus,
+    pub manufacturer: Option<String>,
+    pub model: Option<String>,
+    pub serial_number: Option<String>,
+    pub purchase_date: Option<NaiveDate>,
+    pub purchase_price: Option<Decimal>,
+    pub useful_life_months: Option<i32>,
+    pub salvage_value: Decimal,
+    pub warranty_expiry: Option<NaiveDate>,
+    pub rmm_device_id: Option<String>,
+    pub last_seen_at: Option<DateTime<Utc>>,
+    pub possibly_decommissioned: bool,
+    pub retired_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Asset {
+    /// Straight-line book value on `as_of`, depreciating by whole months in service.
+    ///
+    /// Returns `None` when the purchase price, date or useful life is unknown.
+    pub fn book_value(&self, as_of: NaiveDate) -> Option<Decimal> {
+        let cost = self.purchase_price?;
+        let purchased = self.purchase_date?;
+        let life = self.useful_life_months.filter(|months| *months > 0)?;
+
+        let salvage = self.salvage_value.min(cost);
+        let elapsed = months_between(purchased, as_of).clamp(0, life as i64);
+        let depreciation = (cost - salvage) * Decimal::from(elapsed) / Decimal::from(life);
+
+        Some((cost - depreciation).round_dp(2))
+    }
+}
+
+/// Whole months from `from` to `to`; negative when `to` is earlier
+fn months_between(from: NaiveDate, to: NaiveDate) -> i64 {
+    let mut months = (to.year() as i64 - from.year() as i64) * 12 + to.month() as i64 - from.month() as i64;
+    if to.day() < from.day() {
+        months -= 1;
+    }
+    months
+}
+
+/// Assets that depend on another asset through `asset_relationships`
+#[derive(Debug, Clone, Serialize)]
+pub struct DependentAsset {
+    pub asset_id: Uuid,
+    pub name: String,
+    pub relationship_type: String,
+    /// 1 for direct dependents, higher for transitive ones
+    pub depth: i32,
+}
+
+/// Change an asset's lifecycle status
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct ChangeAssetStatusRequest {
+    pub status: AssetStatus,
+    /// Required to retire an asset that other assets depend on
+    #[serde(default)]
+    pub acknowledge_dependents: bool,
+}
+
+/// Asset valuation on a given date
+#[derive(Debug, Clone, Serialize)]
+pub struct BookValue {
+    pub asset_id: Uuid,
+    pub as_of: NaiveDate,
+    pub purchase_price: Option<Decimal>,
+    pub book_value: Option<Decimal>,
+}
+
+// ============================================================================
+// SOFTWARE LICENSES
+// ============================================================================
+
+/// Licenses expiring within this many days are flagged in the compliance report
+pub const LICENSE_EXPIRY_WARNING_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SoftwareLicense {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub company_id: Option<Uuid>,
+    pub product_name: String,
+    pub vendor: Option<String>,
+    pub seats_purchased: i32,
+    pub expires_on: Option<NaiveDate>,
+    pub notes: Option<String>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LicenseAssignment {
+    pub id: Uuid,
+    pub license_id: Uuid,
+    pub asset_id: Option<Uuid>,
+    pub contact_id: Option<Uuid>,
+    pub assigned_by_id: Option<Uuid>,
+    pub assigned_at: DateTime<Utc>,
+}
+
+/// Compliance state of one license
+#[derive(Debug, Clone, Serialize)]
+pub struct LicenseStatus {
+    pub license_id: Uuid,
+    pub product_name: String,
+    pub seats_purchased: i64,
+    pub seats_assigned: i64,
+    pub expires_on: Option<NaiveDate>,
+    /// More seats assigned than purchased
+    pub over_deployed: bool,
+    /// Expires within [`LICENSE_EXPIRY_WARNING_DAYS`]
+    pub expiring: bool,
+    pub expired: bool,
+}
+
+impl LicenseStatus {
+    pub fn evaluate(license: &SoftwareLicense, seats_assigned: i64, today: NaiveDate) -> Self {
+        let seats_purchased = license.seats_purchased as i64;
+        let (expiring, expired) = match license.expires_on {
+            Some(expires_on) => {
+                let days_left = (expires_on - today).num_days();
+                (
+                    (0..=LICENSE_EXPIRY_WARNING_DAYS).contains(&days_left),
+                    days_left < 0,
+                )
+            }
+            None => (false, false),
+        };
+
+        Self {
+            license_id: license.id,
+            product_name: license.product_name.clone(),
+            seats_purchased,
+            seats_assigned,
+            expires_on: license.expires_on,
+            over_deployed: seats_assigned > seats_purchased,
+            expiring,
+            expired,
+        }
+    }
+
+    pub fn seats_available(&self) -> i64 {
+        (self.seats_purchased - self.seats_assigned).max(0)
+    }
+
+    pub fn is_compliant(&self) -> bool {
+        !self.over_deployed && !self.expired
+    }
+
+    /// True when anything about the license needs attention
+    pub fn needs_attention(&self) -> bool {
+        self.over_deployed || self.expiring || self.expired
+    }
+}
+
+/// Create license request
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CreateLicenseRequest {
+    #[validate(length(min = 1, max = 255))]
+    pub product_name: String,
+    #[validate(length(max = 100))]
+    pub vendor: Option<String>,
+    pub company_id: Option<Uuid>,
+    #[validate(range(min = 0))]
+    pub seats_purchased: i32,
+    pub expires_on: Option<NaiveDate>,
+    pub notes: Option<String>,
+}
+
+/// Assign a license seat to an asset and/or contact
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct AssignSeatRequest {
+    pub asset_id: Option<Uuid>,
+    pub contact_id: Option<Uuid>,
+}
+
+/// Result of a seat assignment; over-capacity assignments are recorded but carry a warning
+#[derive(Debug, Clone, Serialize)]
+pub struct SeatAssignmentResult {
+    pub assignment: LicenseAssignment,
+    pub status: LicenseStatus,
+    pub warning: Option<String>,
+}
+
+// ============================================================================
+// STOCK
+// ============================================================================
+
+/// A spare part kept in stock
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StockItem {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub sku: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub unit_cost: Option<Decimal>,
+    /// Charged to the client per unit when billable
+    pub unit_price: Option<Decimal>,
+    pub quantity_on_hand: i32,
+    pub reorder_point: i32,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl StockItem {
+    /// At or below the reorder point
+    pub fn needs_reorder(&self) -> bool {
+        self.quantity_on_hand <= self.reorder_point
+    }
+
+    /// Take `quantity` out of stock. Returns whether this took the item to
+    /// or below its reorder point, so the alert is raised once rather than on
+    /// every use after.
+    pub fn consume(&mut self, quantity: i32) -> Result<bool, AppError> {
+        if quantity <= 0 {
+            return Err(AppError::validation_field("quantity", "Quantity must be at least 1"));
+        }
+        if quantity > self.quantity_on_hand {
+            return Err(AppError::Conflict(format!(
+                "Only {} of {} in stock",
+                self.quantity_on_hand, self.sku
+            )));
+        }
+
+        let was_above = !self.needs_reorder();
+        self.quantity_on_hand -= quantity;
+        Ok(was_above && self.needs_reorder())
+    }
+
+    /// Put `quantity` back into stock
+    pub fn receive(&mut self, quantity: i32) -> Result<(), AppError> {
+        if quantity <= 0 {
+            return Err(AppError::validation_field("quantity", "Quantity must be at least 1"));
+        }
+        self.quantity_on_hand = self
+            .quantity_on_hand
+            .checked_add(quantity)
+            .ok_or_else(|| AppError::validation_field("quantity", "Quantity is too large"))?;
+        Ok(())
+    }
+
+    pub fn reorder_alert(&self) -> String {
+        format!(
+            "{} ({}) is at or below its reorder point: {} on hand, reorder at {}",
+            self.name, self.sku, self.quantity_on_hand, self.reorder_point
+        )
+    }
+}
+
+/// Direction of a stock movement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StockMovementType {
+    In,
+    #[default]
+    Out,
+}
+
+impl StockMovementType {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "in" => Some(Self::In),
+            "out" => Some(Self::Out),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::In => "in",
+            Self::Out => "out",
+        }
+    }
+}
+
+/// A receipt or use of stock
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StockMovement {
+    pub id: Uuid,
+    pub stock_item_id: Uuid,
+    pub movement_type: StockMovementType,
+    pub quantity: i32,
+    /// Quantity on hand after the movement
+    pub balance: i32,
+    pub ticket_id: Option<Uuid>,
+    pub project_id: Option<Uuid>,
+    pub expense_id: Option<Uuid>,
+    pub notes: Option<String>,
+    pub moved_by_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Create stock item request
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CreateStockItemRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub sku: String,
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+    pub description: Option<String>,
+    pub unit_cost: Option<Decimal>,
+    pub unit_price: Option<Decimal>,
+    #[validate(range(min = 0))]
+    #[serde(default)]
+    pub quantity_on_hand: i32,
+    #[validate(range(min = 0))]
+    #[serde(default)]
+    pub reorder_point: i32,
+}
+
+impl CreateStockItemRequest {
+    pub fn check(&self) -> Result<(), AppError> {
+        for (field, value) in [("unit_cost", self.unit_cost), ("unit_price", self.unit_price)] {
+            if value.is_some_and(|value| value < Decimal::ZERO) {
+                return Err(AppError::validation_field(field, "Must not be negative"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Where a part was used
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct StockReference {
+    pub ticket_id: Option<Uuid>,
+    pub project_id: Option<Uuid>,
+    pub notes: Option<String>,
+}
+
+/// Use parts from stock
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct ConsumeStockRequest {
+    #[validate(range(min = 1))]
+    pub quantity: i32,
+    #[serde(flatten)]
+    pub reference: StockReference,
+    /// Bill the parts to the client as an expense at the item's unit price
+    #[serde(default)]
+    pub is_billable: bool,
+}
+
+/// Add parts to stock
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct ReceiveStockRequest {
+    #[validate(range(min = 1))]
+    pub quantity: i32,
+    pub notes: Option<String>,
+}
+
+/// Result of using stock; crossing the reorder point carries an alert
+#[derive(Debug, Clone, Serialize)]
+pub struct StockConsumption {
+    pub item: StockItem,
+    pub movement: StockMovement,
+    pub reorder_alert: Option<String>,
+}
+
+// ============================================================================
+// RMM INVENTORY SYNC
+// ============================================================================
+
+/// One device as reported by an RMM agent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RmmAsset {
+    /// Stable agent identifier from the RMM
+    pub agent_id: String,
+    pub hostname: String,
+    pub serial_number: Option<String>,
+    pub manufacturer: Option<String>,
+    pub model: Option<String>,
+    /// Asset type name, e.g. "Workstation" or "Server"
+    pub asset_type: Option<String>,
+    /// CPU, memory, disks, OS and the like, stored as-is
+    #[serde(default)]
+    pub specs: serde_json::Value,
+    pub last_seen: DateTime<Utc>,
+}
+
+impl RmmAsset {
+    /// Serial number suitable for matching, ignoring blanks and vendor placeholders
+    pub fn normalized_serial(&self) -> Option<String> {
+        self.serial_number.as_deref().and_then(normalize_serial)
+    }
+
+    /// The reconciled details as the RMM reports them. Details the RMM
+    /// leaves blank keep the PSA's value instead of clearing it.
+    pub fn fields_over(&self, local: &RmmAssetFields) -> RmmAssetFields {
+        RmmAssetFields {
+            serial_number: self.serial_number.clone().or_else(|| local.serial_number.clone()),
+            manufacturer: self.manufacturer.clone().or_else(|| local.manufacturer.clone()),
+            model: self.model.clone().or_else(|| local.model.clone()),
+            specs: if self.specs.is_null() { local.specs.clone() } else { self.specs.clone() },
+        }
+    }
+
+    /// Settle the device's details against the matched asset's. The RMM only
+    /// reports when the agent last checked in, so its copy counts as changed
+    /// when it differs from the one last pulled. The RMM can't be written
+    /// to, so keeping the local copy just leaves the asset as it is.
+    pub fn reconcile(&self, local: &RmmSyncState, policy: ConflictPolicy) -> Reconciliation {
+        let remote = self.fields_over(&local.fields);
+        let remote_modified_at = match (&local.snapshot, local.last_sync_at) {
+            (Some(snapshot), Some(synced_at)) if *snapshot == remote => synced_at,
+            _ => self.last_seen,
+        };
+
+        crate::utils::reconcile::reconcile(
+            &Versioned::new(local.fields.clone(), local.updated_at),
+            &Versioned::new(remote, remote_modified_at),
+            local.last_sync_at,
+            policy,
+        )
+    }
+}
+
+/// The part of an asset RMM sync reconciles
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RmmAssetFields {
+    pub serial_number: Option<String>,
+    pub manufacturer: Option<String>,
+    pub model: Option<String>,
+    pub specs: serde_json::Value,
+}
+
+/// A matched asset's details and where its sync with the RMM stands
+#[derive(Debug, Clone)]
+pub struct RmmSyncState {
+    pub asset_id: Uuid,
+    pub fields: RmmAssetFields,
+    pub updated_at: DateTime<Utc>,
+    /// When the asset and the RMM last agreed
+    pub last_sync_at: Option<DateTime<Utc>>,
+    /// The RMM's copy as last pulled
+    pub snapshot: Option<RmmAssetFields>,
+}
+
+/// Serials that BIOS vendors ship as placeholders and which must never be matched on
+const PLACEHOLDER_SERIALS: &[&str] = &[
+    "to be filled by o.e.m.",
+    "default string",
+    "system serial number",
+    "0",
+    "none",
+    "n/a",
+];
+
+pub fn normalize_serial(serial: &str) -> Option<String> {
+    let serial = serial.trim().to_lowercase();
+    if serial.is_empty() || PLACEHOLDER_SERIALS.contains(&serial.as_str()) {
+        None
+    } else {
+        Some(serial)
+    }
+}
+
+/// The identifiers of an existing asset used to match inventory against it
+#[derive(Debug, Clone)]
+pub struct AssetIdentity {
+    pub id: Uuid,
+    pub rmm_device_id: Option<String>,
+    pub serial_number: Option<String>,
+}
+
+impl AssetIdentity {
+    /// Find the existing asset for a reported device: agent ID first, then serial number
+    pub fn find_match(device: &RmmAsset, existing: &[AssetIdentity]) -> Option<Uuid> {
+        existing
+            .iter()
+            .find(|a| a.rmm_device_id.as_deref() == Some(device.agent_id.as_str()))
+            .or_else(|| {
+                let serial = device.normalized_serial()?;
+                existing.iter().find(|a| {
+                    a.serial_number.as_deref().and_then(normalize_serial).as_deref()
+                        == Some(serial.as_str())
+                })
+            })
+            .map(|a| a.id)
+    }
+
+    /// RMM-managed assets that were not part of the latest sync
+    pub fn stale(existing: &[AssetIdentity], seen: &HashSet<Uuid>) -> Vec<Uuid> {
+        existing
+            .iter()
+            .filter(|a| a.rmm_device_id.is_some() && !seen.contains(&a.id))
+            .map(|a| a.id)
+            .collect()
+    }
+}
+
+/// Outcome of an inventory sync
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SyncReport {
+    pub created: u64,
+    pub updated: u64,
+    /// Assets not seen in this sync, flagged as possibly decommissioned
+    pub flagged_stale: u64,
+    /// Agent IDs skipped because they are not mapped to a company
+    pub unmapped_agents: Vec<String>,
+    /// Assets edited on both sides since the last sync
+    pub conflicts: u64,
+}
+
+// ============================================================================
+// LABELS
+// ============================================================================
+
+/// Path, under the API root, that scanned labels resolve through
+pub const SCAN_PATH: &str = "/api/v1/assets/scan";
+
+/// Label size in SVG user units: 2" x 1" at 300 dpi
+const LABEL_WIDTH: usize = 600;
+const LABEL_HEIGHT: usize = 300;
+/// Light modules around the code, as the QR spec requires
+const QR_QUIET_ZONE: usize = 4;
+
+/// Printable asset label: a QR code linking to the asset, with its tag and name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetLabel {
+    /// Link encoded in the QR code
+    pub url: String,
+    pub asset_tag: Option<String>,
+    pub name: String,
+}
+
+impl AssetLabel {
+    pub fn for_asset(asset: &Asset, base_url: &str) -> Self {
+        Self {
+            url: Self::scan_url(base_url, asset.id),
+            asset_tag: asset.asset_tag.clone(),
+            name: asset.name.clone(),
+        }
+    }
+
+    /// Link a scanned label opens
+    pub fn scan_url(base_url: &str, asset_id: Uuid) -> String {
+        format!("{}{}/{}", base_url.trim_end_matches('/'), SCAN_PATH, asset_id)
+    }
+
+    /// Width of the QR code in modules, and whether each module is dark, row by row
+    pub fn qr_modules(&self) -> Result<(usize, Vec<bool>), AppError> {
+        let code = qrcode::QrCode::new(self.url.as_bytes())
+            .map_err(|e| AppError::Internal(format!("Failed to encode asset label: {}", e)))?;
+        let modules = code
+            .to_colors()
+            .into_iter()
+            .map(|color| color == qrcode::Color::Dark)
+            .collect();
+
+        Ok((code.width(), modules))
+    }
+
+    /// The label as an SVG document, QR code on the left and text on the right
+    pub fn to_svg(&self) -> Result<String, AppError> {
+        let (width, modules) = self.qr_modules()?;
+        let size = width + 2 * QR_QUIET_ZONE;
+        let margin = 20;
+        let qr_size = LABEL_HEIGHT - 2 * margin;
+
+        let mut path = String::new();
+        for (i, dark) in modules.iter().enumerate() {
+            if *dark {
+                let (x, y) = (i % width + QR_QUIET_ZONE, i / width + QR_QUIET_ZONE);
+                path.push_str(&format!("M{},{}h1v1h-1z", x, y));
+            }
+        }
+
+        let text_x = margin + qr_size + margin;
+        let title = self.asset_tag.as_deref().unwrap_or(&self.name);
+        let mut text = format!(
+            r#"<text x="{}" y="120" font-family="sans-serif" font-size="44" font-weight="bold">{}</text>"#,
+            text_x,
+            xml_escape(title)
+        );
+        if self.asset_tag.is_some() {
+            text.push_str(&format!(
+                r#"<text x="{}" y="180" font-family="sans-serif" font-size="28">{}</text>"#,
+                text_x,
+                xml_escape(&self.name)
+            ));
+        }
+
+        Ok(format!(
+            concat!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+                r##"<rect width="{w}" height="{h}" fill="#fff"/>"##,
+                r#"<svg x="{m}" y="{m}" width="{q}" height="{q}" viewBox="0 0 {s} {s}" shape-rendering="crispEdges">"#,
+                r##"<path d="{path}" fill="#000"/></svg>{text}</svg>"##
+            ),
+            w = LABEL_WIDTH,
+            h = LABEL_HEIGHT,
+            m = margin,
+            q = qr_size,
+            s = size,
+            path = path,
+            text = text,
+        ))
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// ============================================================================
+// MAINTENANCE
+// ============================================================================
+
+/// Job that raises tickets for maintenance coming due, across all tenants
+pub const MAINTENANCE_TICKETS_JOB: &str = "assets.maintenance_tickets";
+
+/// Tag on tickets raised by a maintenance schedule
+pub const MAINTENANCE_TAG: &str = "maintenance";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntervalUnit {
+    Days,
+    Weeks,
+    Months,
+}
+
+impl IntervalUnit {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "days" => Some(Self::Days),
+            "weeks" => Some(Self::Weeks),
+            "months" => Some(Self::Months),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Days => "days",
+            Self::Weeks => "weeks",
+            Self::Months => "months",
+        }
+    }
+}
+
+/// Periodic maintenance on an asset, raising a ticket `lead_days` before each due date
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceSchedule {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub asset_id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub priority_id: Option<Uuid>,
+    pub queue_id: Option<Uuid>,
+    pub interval_count: i32,
+    pub interval_unit: IntervalUnit,
+    pub lead_days: i32,
+    /// Due date of the next cycle without a ticket
+    pub next_due_date: NaiveDate,
+    pub is_active: bool,
+    pub created_by_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A maintenance cycle to raise a ticket for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaintenanceCycle {
+    pub due_date: NaiveDate,
+    /// Where the schedule moves on to once the ticket is raised
+    pub next_due_date: NaiveDate,
+}
+
+impl MaintenanceSchedule {
+    /// The due date one interval after `due`. Monthly schedules on the 31st
+    /// fall on the last day of shorter months.
+    pub fn due_after(&self, due: NaiveDate) -> NaiveDate {
+        let count = self.interval_count.max(1) as u32;
+        match self.interval_unit {
+            IntervalUnit::Days => due + Days::new(count.into()),
+            IntervalUnit::Weeks => due + Days::new(u64::from(count) * 7),
+            IntervalUnit::Months => due + Months::new(count),
+        }
+    }
+
+    fn raise_on(&self, due: NaiveDate) -> NaiveDate {
+        due - Days::new(self.lead_days.max(0) as u64)
+    }
+
+    /// The cycle to raise a ticket for on `today`, if its lead time has
+    /// started. Cycles missed entirely, while the schedule was paused or the
+    /// job wasn't running, are skipped for the latest one.
+    pub fn cycle_due(&self, today: NaiveDate) -> Option<MaintenanceCycle> {
+        if !self.is_active || today < self.raise_on(self.next_due_date) {
+            return None;
+        }
+
+        let mut due_date = self.next_due_date;
+        while today >= self.raise_on(self.due_after(due_date)) {
+            due_date = self.due_after(due_date);
+        }
+        Some(MaintenanceCycle {
+            due_date,
+            next_due_date: self.due_after(due_date),
+        })
+    }
+}
+
+/// Set up maintenance on an asset
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CreateMaintenanceScheduleRequest {
+    #[validate(length(min = 1, max = 500))]
+    pub title: String,
+    pub description: Option<String>,
+    pub priority_id: Option<Uuid>,
+    pub queue_id: Option<Uuid>,
+    #[validate(range(min = 1, max = 1000))]
+    pub interval_count: i32,
+    pub interval_unit: IntervalUnit,
+    #[serde(default = "default_lead_days")]
+    #[validate(range(min = 0, max = 365))]
+    pub lead_days: i32,
+    /// When maintenance is first due
+    pub first_due_date: NaiveDate,
+}
+
+fn default_lead_days() -> i32 {
+    7
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::reconcile::SyncDecision;
+
+    fn sample_license(seats: i32, expires_on: Option<NaiveDate>) -> SoftwareLicense {
+        SoftwareLicense {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            company_id: None,
+            product_name: "Microsoft 365 Business".to_string(),
+            vendor: Some("Microsoft".to_string()),
+            seats_purchased: seats,
+            expires_on,
+            notes: None,
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 6, 1).unwrap()
+    }
+
+    #[test]
+    fn test_over_deployment_detection() {
+        let license = sample_license(10, None);
+
+        let at_capacity = LicenseStatus::evaluate(&license, 10, today());
+        assert!(!at_capacity.over_deployed);
+        assert_eq!(at_capacity.seats_available(), 0);
+
+        let over = LicenseStatus::evaluate(&license, 12, today());
+        assert!(over.over_deployed);
+        assert!(!over.is_compliant());
+        assert!(over.needs_attention());
+    }
+
+    #[test]
+    fn test_expiring_license_in_report() {
+        let expiring = sample_license(5, Some(today() + chrono::Duration::days(14)));
+        let later = sample_license(5, Some(today() + chrono::Duration::days(90)));
+        let expired = sample_license(5, Some(today() - chrono::Duration::days(1)));
+
+        let report: Vec<_> = [&expiring, &later, &expired]
+            .into_iter()
+            .map(|license| LicenseStatus::evaluate(license, 1, today()))
+            .filter(LicenseStatus::needs_attention)
+            .collect();
+
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[0].license_id, expiring.id);
+        assert!(report[0].expiring && !report[0].expired);
+        assert_eq!(report[1].license_id, expired.id);
+        assert!(report[1].expired && !report[1].is_compliant());
+    }
+
+    fn device(agent_id: &str, serial: Option<&str>) -> RmmAsset {
+        RmmAsset {
+            agent_id: agent_id.to_string(),
+            hostname: format!("host-{}", agent_id),
+            serial_number: serial.map(str::to_string),
+            manufacturer: Some("Dell".to_string()),
+            model: Some("OptiPlex 7090".to_string()),
+            asset_type: None,
+            specs: serde_json::json!({ "ram_gb": 16 }),
+            last_seen: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_sync_upserts_by_serial() {
+        let manual = AssetIdentity {
+            id: Uuid::new_v4(),
+            rmm_device_id: None,
+            serial_number: Some(" ABC123 ".to_string()),
+        };
+        let existing = vec![manual.clone()];
+
+        // A manually entered asset is adopted when its serial matches
+        assert_eq!(AssetIdentity::find_match(&device("agent-1", Some("abc123")), &existing), Some(manual.id));
+        // Unknown serials and placeholder serials create new assets
+        assert_eq!(AssetIdentity::find_match(&device("agent-2", Some("XYZ")), &existing), None);
+        assert_eq!(
+            AssetIdentity::find_match(&device("agent-3", Some("To be filled by O.E.M.")), &existing),
+            None
+        );
+    }
+
+    #[test]
+    fn test_sync_prefers_agent_id_over_serial() {
+        let by_agent = AssetIdentity {
+            id: Uuid::new_v4(),
+            rmm_device_id: Some("agent-1".to_string()),
+            serial_number: None,
+        };
+        let by_serial = AssetIdentity {
+            id: Uuid::new_v4(),
+            rmm_device_id: None,
+            serial_number: Some("ABC123".to_string()),
+        };
+
+        let matched = AssetIdentity::find_match(&device("agent-1", Some("ABC123")), &[by_serial, by_agent.clone()]);
+        assert_eq!(matched, Some(by_agent.id));
+    }
+
+    #[test]
+    fn test_sync_flags_stale_assets() {
+        let seen = AssetIdentity {
+            id: Uuid::new_v4(),
+            rmm_device_id: Some("agent-1".to_string()),
+            serial_number: None,
+        };
+        let missing = AssetIdentity {
+            id: Uuid::new_v4(),
+            rmm_device_id: Some("agent-2".to_string()),
+            serial_number: None,
+        };
+        let manual = AssetIdentity {
+            id: Uuid::new_v4(),
+            rmm_device_id: None,
+            serial_number: Some("MANUAL".to_string()),
+        };
+
+        let seen_ids: HashSet<Uuid> = [seen.id].into_iter().collect();
+        let stale = AssetIdentity::stale(&[seen, missing.clone(), manual], &seen_ids);
+
+        // Manually managed assets are never flagged
+        assert_eq!(stale, vec![missing.id]);
+    }
+
+    #[test]
+    fn test_sync_reconciles_rmm_details() {
+        let synced_at = Utc::now() - chrono::Duration::hours(2);
+        let reported = device("agent-1", Some("ABC123"));
+        let pulled = RmmAssetFields {
+            serial_number: Some("ABC123".to_string()),
+            manufacturer: Some("Dell".to_string()),
+            model: Some("OptiPlex 7090".to_string()),
+            specs: serde_json::json!({ "ram_gb": 16 }),
+        };
+        let state = |fields: RmmAssetFields, updated_at| RmmSyncState {
+            asset_id: Uuid::new_v4(),
+            fields,
+            updated_at,
+            last_sync_at: Some(synced_at),
+            snapshot: Some(pulled.clone()),
+        };
+
+        // Unchanged on both sides
+        let result = reported.reconcile(&state(pulled.clone(), synced_at), ConflictPolicy::RemoteWins);
+        assert_eq!(result.decision, SyncDecision::InSync);
+
+        // A local edit survives a sync that reports the same device again
+        let edited = RmmAssetFields {
+            model: Some("OptiPlex 7090 (swapped board)".to_string()),
+            ..pulled.clone()
+        };
+        let local = state(edited.clone(), synced_at + chrono::Duration::hours(1));
+        let result = reported.reconcile(&local, ConflictPolicy::RemoteWins);
+        assert_eq!(result.decision, SyncDecision::PushLocal);
+        assert!(!result.conflict);
+
+        // A change in the RMM alone is pulled
+        let upgraded = RmmAsset {
+            specs: serde_json::json!({ "ram_gb": 32 }),
+            ..device("agent-1", Some("ABC123"))
+        };
+        let result = upgraded.reconcile(&state(pulled.clone(), synced_at), ConflictPolicy::LocalWins);
+        assert_eq!(result.decision, SyncDecision::PullRemote);
+
+        // Both changed: the policy decides
+        let result = upgraded.reconcile(&local, ConflictPolicy::LocalWins);
+        assert_eq!((result.decision, result.conflict), (SyncDecision::PushLocal, true));
+        let result = upgraded.reconcile(&local, ConflictPolicy::RemoteWins);
+        assert_eq!((result.decision, result.conflict), (SyncDecision::PullRemote, true));
+        let result = upgraded.reconcile(&local, ConflictPolicy::FlagForReview);
+        assert_eq!(result.decision, SyncDecision::FlagForReview);
+
+        // Details the RMM leaves blank keep the PSA's value
+        let blank = RmmAsset {
+            manufacturer: None,
+            ..device("agent-1", Some("ABC123"))
+        };
+        assert_eq!(blank.fields_over(&edited).manufacturer.as_deref(), Some("Dell"));
+    }
+
+    fn sample_asset() -> Asset {
+        Asset {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            asset_tag: None,
+            name: "FS-01".to_string(),
+            asset_type_id: Uuid::new_v4(),
+            company_id: Uuid::new_v4(),
+            site_id: None,
+            contact_id: None,
+            status: AssetStatus::Deployed,
+            manufacturer: None,
+            model: None,
+            serial_number: None,
+            purchase_date: NaiveDate::from_ymd_opt(2024, 1, 15),
+            purchase_price: Some(Decimal::from(1200)),
+            useful_life_months: Some(36),
+            salvage_value: Decimal::ZERO,
+            warranty_expiry: None,
+            rmm_device_id: None,
+            last_seen_at: None,
+            possibly_decommissioned: false,
+            retired_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_book_value_mid_life() {
+        let asset = sample_asset();
+
+        // 18 of 36 months elapsed
+        let mid = NaiveDate::from_ymd_opt(2025, 7, 15).unwrap();
+        assert_eq!(asset.book_value(mid), Some(Decimal::from(600)));
+
+        // A partial month does not depreciate
+        let day_before = NaiveDate::from_ymd_opt(2025, 7, 14).unwrap();
+        assert_eq!(asset.book_value(day_before), Some(Decimal::from(633) + Decimal::new(33, 2)));
+    }
+
+    #[test]
+    fn test_book_value_bounds_and_salvage() {
+        let mut asset = sample_asset();
+        asset.salvage_value = Decimal::from(300);
+
+        let before_purchase = NaiveDate::from_ymd_opt(2023, 12, 1).unwrap();
+        assert_eq!(asset.book_value(before_purchase), Some(Decimal::from(1200)));
+
+        let after_life = NaiveDate::from_ymd_opt(2030, 1, 1).unwrap();
+        assert_eq!(asset.book_value(after_life), Some(Decimal::from(300)));
+
+        asset.useful_life_months = None;
+        assert_eq!(asset.book_value(after_life), None);
+    }
+
+    #[test]
+    fn test_invalid_lifecycle_transition_rejected() {
+        assert!(AssetStatus::InStock.validate_transition(AssetStatus::Deployed).is_ok());
+        assert!(AssetStatus::Deployed.validate_transition(AssetStatus::Retired).is_ok());
+        assert!(AssetStatus::Retired.validate_transition(AssetStatus::Deployed).is_err());
+        assert!(AssetStatus::Deployed.validate_transition(AssetStatus::Deployed).is_err());
+    }
+
+    #[test]
+    fn test_label_qr_decodes_to_asset_url() {
+        let mut asset = sample_asset();
+        asset.asset_tag = Some("ACME-0042".to_string());
+        asset.name = "Front desk <printer> & scanner".to_string();
+
+        let label = AssetLabel::for_asset(&asset, "https://psa.example.com/");
+        assert_eq!(label.url, format!("https://psa.example.com/api/v1/assets/scan/{}", asset.id));
+
+        // Rasterise the modules with a quiet zone and read the code back
+        let (width, modules) = label.qr_modules().unwrap();
+        let scale = 4;
+        let size = (width + 2 * QR_QUIET_ZONE) * scale;
+        let mut image = rqrr::PreparedImage::prepare_from_greyscale(size, size, |x, y| {
+            let (mx, my) = (x / scale, y / scale);
+            let inside = (QR_QUIET_ZONE..QR_QUIET_ZONE + width).contains(&mx)
+                && (QR_QUIET_ZONE..QR_QUIET_ZONE + width).contains(&my);
+            if inside && modules[(my - QR_QUIET_ZONE) * width + (mx - QR_QUIET_ZONE)] {
+                0
+            } else {
+                255
+            }
+        });
+        let grids = image.detect_grids();
+        assert_eq!(grids.len(), 1);
+        let (_, content) = grids[0].decode().unwrap();
+        assert_eq!(content, label.url);
+
+        let svg = label.to_svg().unwrap();
+        assert!(svg.contains(">ACME-0042</text>"));
+        assert!(svg.contains("Front desk &lt;printer&gt; &amp; scanner"));
+    }
+
+    fn sample_stock_item(quantity_on_hand: i32, reorder_point: i32) -> StockItem {
+        StockItem {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            sku: "SSD-1TB".to_string(),
+            name: "1TB NVMe SSD".to_string(),
+            description: None,
+            unit_cost: Some(Decimal::new(6500, 2)),
+            unit_price: Some(Decimal::new(9900, 2)),
+            quantity_on_hand,
+            reorder_point,
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_consuming_stock_decrements_quantity() {
+        let mut item = sample_stock_item(10, 2);
+
+        assert!(!item.consume(3).unwrap());
+        assert_eq!(item.quantity_on_hand, 7);
+
+        // More than is on hand is refused and leaves the count alone
+        assert!(matches!(item.consume(8), Err(AppError::Conflict(_))));
+        assert!(item.consume(0).is_err());
+        assert_eq!(item.quantity_on_hand, 7);
+
+        item.receive(5).unwrap();
+        assert_eq!(item.quantity_on_hand, 12);
+    }
+
+    #[test]
+    fn test_reorder_alert_at_threshold() {
+        let mut item = sample_stock_item(5, 3);
+
+        // 5 -> 4 stays above the reorder point
+        assert!(!item.consume(1).unwrap());
+        // 4 -> 3 reaches it
+        assert!(item.consume(1).unwrap());
+        assert!(item.needs_reorder());
+        assert!(item.reorder_alert().contains("3 on hand"));
+
+        // Already below: no repeat alert
+        assert!(!item.consume(1).unwrap());
+
+        // Restocked above the point, the next crossing alerts again
+        item.receive(10).unwrap();
+        assert!(!item.needs_reorder());
+        assert!(item.consume(9).unwrap());
+    }
+
+    fn schedule(interval_count: i32, interval_unit: IntervalUnit, next_due_date: NaiveDate) -> MaintenanceSchedule {
+        MaintenanceSchedule {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            asset_id: Uuid::new_v4(),
+            title: "Replace air filter".to_string(),
+            description: None,
+            priority_id: None,
+            queue_id: None,
+            interval_count,
+            interval_unit,
+            lead_days: 7,
+            next_due_date,
+            is_active: true,
+            created_by_id: Uuid::new_v4(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_one_maintenance_ticket_per_interval() {
+        let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let mut schedule = schedule(3, IntervalUnit::Months, NaiveDate::from_ymd_opt(2025, 1, 15).unwrap());
+        let mut raised: Vec<NaiveDate> = Vec::new();
+
+        // Run the job daily, twice a day, for a year
+        for day in start.iter_days().take(365) {
+            for _ in 0..2 {
+                if let Some(cycle) = schedule.cycle_due(day) {
+                    assert!(!raised.contains(&cycle.due_date), "{} raised twice", cycle.due_date);
+                    // Raised a week ahead
+                    assert_eq!(cycle.due_date - day, chrono::Duration::days(7));
+                    raised.push(cycle.due_date);
+                    schedule.next_due_date = cycle.next_due_date;
+                }
+            }
+        }
+
+        let quarterly: Vec<NaiveDate> = [(1, 15), (4, 15), (7, 15), (10, 15)]
+            .iter()
+            .map(|(m, d)| NaiveDate::from_ymd_opt(2025, *m, *d).unwrap())
+            .collect();
+        assert_eq!(raised, quarterly);
+    }
+
+    #[test]
+    fn test_no_duplicate_within_a_cycle() {
+        let due = NaiveDate::from_ymd_opt(2025, 6, 10).unwrap();
+        let mut schedule = schedule(2, IntervalUnit::Weeks, due);
+
+        assert_eq!(schedule.cycle_due(NaiveDate::from_ymd_opt(2025, 6, 2).unwrap()), None);
+        let cycle = schedule.cycle_due(NaiveDate::from_ymd_opt(2025, 6, 3).unwrap()).unwrap();
+        assert_eq!(cycle.due_date, due);
+        assert_eq!(cycle.next_due_date, NaiveDate::from_ymd_opt(2025, 6, 24).unwrap());
+
+        // Once raised, nothing more until the next cycle's lead time
+        schedule.next_due_date = cycle.next_due_date;
+        for day in [4, 10, 16] {
+            assert_eq!(schedule.cycle_due(NaiveDate::from_ymd_opt(2025, 6, day).unwrap()), None);
+        }
+        assert!(schedule.cycle_due(NaiveDate::from_ymd_opt(2025, 6, 17).unwrap()).is_some());
+
+        // After a long outage only the latest cycle is raised
+        let cycle = schedule.cycle_due(NaiveDate::from_ymd_opt(2025, 9, 1).unwrap()).unwrap();
+        assert_eq!(cycle.due_date, NaiveDate::from_ymd_opt(2025, 9, 2).unwrap());
+
+        schedule.is_active = false;
+        assert_eq!(schedule.cycle_due(NaiveDate::from_ymd_opt(2025, 9, 1).unwrap()), None);
+    }
+}
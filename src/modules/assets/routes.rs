@@ -0,0 +1,421 @@
+//! Asset API routes
+
+use axum::{
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Redirect},
+    routing::{delete, get, post},
+    Json, Router,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+use super::{
+    Asset, AssetService, AssignSeatRequest, BookValue, ChangeAssetStatusRequest,
+    ConsumeStockRequest, CreateLicenseRequest, CreateMaintenanceScheduleRequest, CreateStockItemRequest,
+    DependentAsset, LicenseStatus, MaintenanceSchedule, ReceiveStockRequest, RmmAsset, SeatAssignmentResult,
+    SoftwareLicense, StockConsumption, StockItem, StockMovement, SyncReport,
+};
+use crate::modules::auth::RequireAuth;
+use crate::modules::calendar::{ResolveReconciliationRequest, SyncReconciliation};
+use crate::utils::error::AppResult;
+
+#[derive(Clone)]
+pub struct AssetRouterState {
+    pub asset_service: Arc<AssetService>,
+}
+
+/// Create the asset router
+pub fn asset_routes(asset_service: AssetService) -> Router {
+    let state = AssetRouterState {
+        asset_service: Arc::new(asset_service),
+    };
+
+    Router::new()
+        // Assets
+        .route("/:asset_id", get(get_asset))
+        .route("/:asset_id/status", post(change_status))
+        .route("/:asset_id/dependents", get(get_dependents))
+        .route("/:asset_id/book-value", get(get_book_value))
+        .route("/:asset_id/label", get(get_label))
+        .route("/:asset_id/maintenance", get(list_maintenance))
+        .route("/:asset_id/maintenance", post(create_maintenance))
+        .route("/maintenance/:schedule_id", delete(delete_maintenance))
+        // Labels link here; redirects to the asset's page
+        .route("/scan/:asset_id", get(resolve_scan))
+        // RMM inventory
+        .route("/sync", post(sync_from_rmm))
+        .route("/sync/reconciliations", get(list_rmm_reconciliations))
+        .route("/sync/reconciliations/:reconciliation_id/resolve", post(resolve_rmm_reconciliation))
+        // Software licenses
+        .route("/licenses", get(list_licenses))
+        .route("/licenses", post(create_license))
+        .route("/licenses/compliance", get(license_compliance))
+        .route("/licenses/:license_id", get(get_license))
+        .route("/licenses/:license_id/assignments", post(assign_seat))
+        .route("/licenses/assignments/:assignment_id", delete(unassign_seat))
+        // Parts stock
+        .route("/stock", get(list_stock_items))
+        .route("/stock", post(create_stock_item))
+        .route("/stock/low", get(low_stock))
+        .route("/stock/:item_id", get(get_stock_item))
+        .route("/stock/:item_id/movements", get(list_stock_movements))
+        .route("/stock/:item_id/receive", post(receive_stock))
+        .route("/stock/:item_id/consume", post(consume_stock))
+        .with_state(state)
+}
+
+#[derive(Debug, Deserialize)]
+struct BookValueQuery {
+    as_of: Option<NaiveDate>,
+}
+
+// ============================================================================
+// ASSET HANDLERS
+// ============================================================================
+
+async fn get_asset(
+    State(state): State<AssetRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(asset_id): Path<Uuid>,
+) -> AppResult<Json<Asset>> {
+    let asset = state
+        .asset_service
+        .get_asset(user.tenant_id, asset_id)
+        .await?;
+
+    Ok(Json(asset))
+}
+
+async fn change_status(
+    State(state): State<AssetRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(asset_id): Path<Uuid>,
+    Json(request): Json<ChangeAssetStatusRequest>,
+) -> AppResult<Json<Asset>> {
+    request.validate()?;
+
+    let asset = state
+        .asset_service
+        .change_status(user.tenant_id, asset_id, user.id, &request)
+        .await?;
+
+    Ok(Json(asset))
+}
+
+async fn get_dependents(
+    State(state): State<AssetRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(asset_id): Path<Uuid>,
+) -> AppResult<Json<Vec<DependentAsset>>> {
+    let dependents = state
+        .asset_service
+        .dependent_assets(user.tenant_id, asset_id)
+        .await?;
+
+    Ok(Json(dependents))
+}
+
+async fn get_book_value(
+    State(state): State<AssetRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(asset_id): Path<Uuid>,
+    Query(query): Query<BookValueQuery>,
+) -> AppResult<Json<BookValue>> {
+    let as_of = query.as_of.unwrap_or_else(|| chrono::Utc::now().date_naive());
+    let value = state
+        .asset_service
+        .current_book_value(user.tenant_id, asset_id, as_of)
+        .await?;
+
+    Ok(Json(value))
+}
+
+async fn get_label(
+    State(state): State<AssetRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(asset_id): Path<Uuid>,
+) -> AppResult<impl IntoResponse> {
+    let label = state
+        .asset_service
+        .generate_label(user.tenant_id, asset_id)
+        .await?;
+
+    Ok(([(header::CONTENT_TYPE, "image/svg+xml")], label))
+}
+
+async fn resolve_scan(
+    State(state): State<AssetRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(asset_id): Path<Uuid>,
+) -> AppResult<Redirect> {
+    let location = state
+        .asset_service
+        .resolve_scan(user.tenant_id, asset_id)
+        .await?;
+
+    Ok(Redirect::to(&location))
+}
+
+// ============================================================================
+// MAINTENANCE HANDLERS
+// ============================================================================
+
+async fn list_maintenance(
+    State(state): State<AssetRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(asset_id): Path<Uuid>,
+) -> AppResult<Json<Vec<MaintenanceSchedule>>> {
+    let schedules = state
+        .asset_service
+        .list_maintenance(user.tenant_id, asset_id)
+        .await?;
+
+    Ok(Json(schedules))
+}
+
+async fn create_maintenance(
+    State(state): State<AssetRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(asset_id): Path<Uuid>,
+    Json(request): Json<CreateMaintenanceScheduleRequest>,
+) -> AppResult<Json<MaintenanceSchedule>> {
+    request.validate()?;
+
+    let schedule = state
+        .asset_service
+        .create_maintenance(user.tenant_id, asset_id, user.id, &request)
+        .await?;
+
+    Ok(Json(schedule))
+}
+
+async fn delete_maintenance(
+    State(state): State<AssetRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(schedule_id): Path<Uuid>,
+) -> AppResult<()> {
+    state
+        .asset_service
+        .delete_maintenance(user.tenant_id, schedule_id)
+        .await
+}
+
+// ============================================================================
+// SYNC HANDLERS
+// ============================================================================
+
+async fn sync_from_rmm(
+    State(state): State<AssetRouterState>,
+    RequireAuth(user): RequireAuth,
+    Json(inventory): Json<Vec<RmmAsset>>,
+) -> AppResult<Json<SyncReport>> {
+    let report = state
+        .asset_service
+        .sync_from_rmm(user.tenant_id, inventory)
+        .await?;
+
+    Ok(Json(report))
+}
+
+async fn list_rmm_reconciliations(
+    State(state): State<AssetRouterState>,
+    RequireAuth(user): RequireAuth,
+) -> AppResult<Json<Vec<SyncReconciliation>>> {
+    let reconciliations = state
+        .asset_service
+        .list_rmm_reconciliations(user.tenant_id)
+        .await?;
+
+    Ok(Json(reconciliations))
+}
+
+async fn resolve_rmm_reconciliation(
+    State(state): State<AssetRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(reconciliation_id): Path<Uuid>,
+    Json(request): Json<ResolveReconciliationRequest>,
+) -> AppResult<Json<Asset>> {
+    let asset = state
+        .asset_service
+        .resolve_rmm_reconciliation(user.tenant_id, user.id, reconciliation_id, &request)
+        .await?;
+
+    Ok(Json(asset))
+}
+
+// ============================================================================
+// LICENSE HANDLERS
+// ============================================================================
+
+async fn list_licenses(
+    State(state): State<AssetRouterState>,
+    RequireAuth(user): RequireAuth,
+) -> AppResult<Json<Vec<SoftwareLicense>>> {
+    let licenses = state.asset_service.list_licenses(user.tenant_id).await?;
+    Ok(Json(licenses))
+}
+
+async fn create_license(
+    State(state): State<AssetRouterState>,
+    RequireAuth(user): RequireAuth,
+    Json(request): Json<CreateLicenseRequest>,
+) -> AppResult<Json<SoftwareLicense>> {
+    request.validate()?;
+
+    let license = state
+        .asset_service
+        .create_license(user.tenant_id, &request)
+        .await?;
+
+    Ok(Json(license))
+}
+
+async fn get_license(
+    State(state): State<AssetRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(license_id): Path<Uuid>,
+) -> AppResult<Json<SoftwareLicense>> {
+    let license = state
+        .asset_service
+        .get_license(user.tenant_id, license_id)
+        .await?;
+
+    Ok(Json(license))
+}
+
+async fn license_compliance(
+    State(state): State<AssetRouterState>,
+    RequireAuth(user): RequireAuth,
+) -> AppResult<Json<Vec<LicenseStatus>>> {
+    let report = state
+        .asset_service
+        .license_compliance(user.tenant_id)
+        .await?;
+
+    Ok(Json(report))
+}
+
+async fn assign_seat(
+    State(state): State<AssetRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(license_id): Path<Uuid>,
+    Json(request): Json<AssignSeatRequest>,
+) -> AppResult<Json<SeatAssignmentResult>> {
+    request.validate()?;
+
+    let result = state
+        .asset_service
+        .assign_seat(user.tenant_id, license_id, user.id, &request)
+        .await?;
+
+    Ok(Json(result))
+}
+
+async fn unassign_seat(
+    State(state): State<AssetRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(assignment_id): Path<Uuid>,
+) -> AppResult<()> {
+    state
+        .asset_service
+        .unassign_seat(user.tenant_id, assignment_id)
+        .await
+}
+
+// ============================================================================
+// STOCK HANDLERS
+// ============================================================================
+
+async fn list_stock_items(
+    State(state): State<AssetRouterState>,
+    RequireAuth(user): RequireAuth,
+) -> AppResult<Json<Vec<StockItem>>> {
+    let items = state.asset_service.list_stock_items(user.tenant_id, false).await?;
+    Ok(Json(items))
+}
+
+async fn low_stock(
+    State(state): State<AssetRouterState>,
+    RequireAuth(user): RequireAuth,
+) -> AppResult<Json<Vec<StockItem>>> {
+    let items = state.asset_service.list_stock_items(user.tenant_id, true).await?;
+    Ok(Json(items))
+}
+
+async fn create_stock_item(
+    State(state): State<AssetRouterState>,
+    RequireAuth(user): RequireAuth,
+    Json(request): Json<CreateStockItemRequest>,
+) -> AppResult<Json<StockItem>> {
+    request.validate()?;
+
+    let item = state
+        .asset_service
+        .create_stock_item(user.tenant_id, &request)
+        .await?;
+
+    Ok(Json(item))
+}
+
+async fn get_stock_item(
+    State(state): State<AssetRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(item_id): Path<Uuid>,
+) -> AppResult<Json<StockItem>> {
+    let item = state
+        .asset_service
+        .get_stock_item(user.tenant_id, item_id)
+        .await?;
+
+    Ok(Json(item))
+}
+
+async fn list_stock_movements(
+    State(state): State<AssetRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(item_id): Path<Uuid>,
+) -> AppResult<Json<Vec<StockMovement>>> {
+    let movements = state
+        .asset_service
+        .list_stock_movements(user.tenant_id, item_id)
+        .await?;
+
+    Ok(Json(movements))
+}
+
+async fn receive_stock(
+    State(state): State<AssetRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(item_id): Path<Uuid>,
+    Json(request): Json<ReceiveStockRequest>,
+) -> AppResult<Json<StockMovement>> {
+    request.validate()?;
+
+    let movement = state
+        .asset_service
+        .receive_stock(user.tenant_id, user.id, item_id, &request)
+        .await?;
+
+    Ok(Json(movement))
+}
+
+async fn consume_stock(
+    State(state): State<AssetRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(item_id): Path<Uuid>,
+    Json(request): Json<ConsumeStockRequest>,
+) -> AppResult<Json<StockConsumption>> {
+    request.validate()?;
+
+    let consumption = state
+        .asset_service
+        .consume_stock(user.tenant_id, user.id, item_id, &request)
+        .await?;
+
+    Ok(Json(consumption))
+}
@@ -0,0 +1,1538 @@
+//! Asset service implementation
+
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::modules::calendar::{
+    ResolveReconciliationRequest, SyncReconciliation, SyncReconciliationRow, RECONCILIATION_COLUMNS,
+};
+use crate::modules::jobs::Job;
+use crate::modules::tickets::{CreateTicketRequest, TicketService, TicketSource};
+use crate::modules::time_tracking::{CreateExpenseRequest, TimeTrackingService};
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::reconcile::{ConflictPolicy, Reconciliation, SyncDecision};
+use crate::utils::timezone::TenantTimezone;
+
+use super::models::*;
+
+const STOCK_ITEM_COLUMNS: &str = "id, tenant_id, sku, name, description, unit_cost, unit_price, quantity_on_hand, \
+     reorder_point, is_active, created_at, updated_at";
+
+const MAINTENANCE_SCHEDULE_COLUMNS: &str = "id, tenant_id, asset_id, title, description, priority_id, queue_id, \
+     interval_count, interval_unit, lead_days, next_due_date, is_active, created_by_id, created_at, updated_at";
+
+const STOCK_MOVEMENT_COLUMNS: &str = "id, stock_item_id, movement_type, quantity, balance, ticket_id, project_id, \
+     expense_id, notes, moved_by_id, created_at";
+
+/// Asset management service
+#[derive(Clone)]
+pub struct AssetService {
+    db: Database,
+    time_tracking: TimeTrackingService,
+    tickets: TicketService,
+    base_url: String,
+}
+
+impl AssetService {
+    pub fn new(db: Database) -> Self {
+        Self {
+            time_tracking: TimeTrackingService::new(db.clone()),
+            tickets: TicketService::new(db.clone()),
+            db,
+            base_url: std::env::var("BASE_URL")
+                .unwrap_or_else(|_| "http://localhost:8080".to_string()),
+        }
+    }
+
+    // ========================================================================
+    // ASSETS
+    // ========================================================================
+
+    /// Get asset by ID
+    pub async fn get_asset(&self, tenant_id: Uuid, asset_id: Uuid) -> AppResult<Asset> {
+        let row = sqlx::query_as::<_, AssetRow>(
+            r#"
+            SELECT id, tenant_id, asset_tag, name, asset_type_id, company_id, site_id, contact_id,
+                   status, manufacturer, model, serial_number, purchase_date, purchase_price,
+                   useful_life_months, salvage_value, warranty_expiry, rmm_device_id, last_seen_at,
+                   possibly_decommissioned, retired_at, created_at, updated_at
+            FROM assets
+            WHERE tenant_id = $1 AND id = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(asset_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Asset".to_string()))?;
+
+        Ok(row.into())
+    }
+
+    /// Assets that depend on `asset_id`, directly or transitively.
+    ///
+    /// The parent side of a relationship is the asset being depended on.
+    pub async fn dependent_assets(
+        &self,
+        tenant_id: Uuid,
+        asset_id: Uuid,
+    ) -> AppResult<Vec<DependentAsset>> {
+        let rows = sqlx::query_as::<_, (Uuid, String, String, i32)>(
+            r#"
+            WITH RECURSIVE dependents AS (
+                SELECT r.child_asset_id AS asset_id, r.relationship_type, 1 AS depth,
+                       ARRAY[r.parent_asset_id, r.child_asset_id] AS path
+                FROM asset_relationships r
+                WHERE r.tenant_id = $1 AND r.parent_asset_id = $2
+                UNION ALL
+                SELECT r.child_asset_id, r.relationship_type, d.depth + 1, d.path || r.child_asset_id
+                FROM asset_relationships r
+                JOIN dependents d ON r.parent_asset_id = d.asset_id
+                WHERE r.tenant_id = $1 AND NOT r.child_asset_id = ANY(d.path)
+            )
+            SELECT DISTINCT ON (d.asset_id) d.asset_id, a.name, d.relationship_type, d.depth
+            FROM dependents d
+            JOIN assets a ON a.id = d.asset_id
+            WHERE a.status <> 'retired'
+            ORDER BY d.asset_id, d.depth
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(asset_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let mut dependents: Vec<DependentAsset> = rows
+            .into_iter()
+            .map(|(asset_id, name, relationship_type, depth)| DependentAsset {
+                asset_id,
+                name,
+                relationship_type,
+                depth,
+            })
+            .collect();
+        dependents.sort_by(|a, b| a.depth.cmp(&b.depth).then_with(|| a.name.cmp(&b.name)));
+
+        Ok(dependents)
+    }
+
+    /// Move an asset through its lifecycle. Retiring an asset that others depend on
+    /// must be acknowledged explicitly.
+    pub async fn change_status(
+        &self,
+        tenant_id: Uuid,
+        asset_id: Uuid,
+        user_id: Uuid,
+        request: &ChangeAssetStatusRequest,
+    ) -> AppResult<Asset> {
+        let asset = self.get_asset(tenant_id, asset_id).await?;
+        asset.status.validate_transition(request.status)?;
+
+        if request.status == AssetStatus::Retired && !request.acknowledge_dependents {
+            let dependents = self.dependent_assets(tenant_id, asset_id).await?;
+            if !dependents.is_empty() {
+                let names: Vec<&str> = dependents.iter().map(|d| d.name.as_str()).collect();
+                return Err(AppError::validation_field(
+                    "acknowledge_dependents",
+                    format!(
+                        "{} dependent asset(s) rely on this asset: {}",
+                        dependents.len(),
+                        names.join(", ")
+                    ),
+                ));
+            }
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE assets
+            SET status = $1, retired_at = CASE WHEN $1 = 'retired' THEN NOW() ELSE retired_at END, updated_at = NOW()
+            WHERE tenant_id = $2 AND id = $3
+            "#,
+        )
+        .bind(request.status.as_str())
+        .bind(tenant_id)
+        .bind(asset_id)
+        .execute(self.db.pool())
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO asset_audit_log (tenant_id, asset_id, action, changes, performed_by_id)
+            VALUES ($1, $2, 'status_changed', $3, $4)
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(asset_id)
+        .bind(serde_json::json!({
+            "from": asset.status.as_str(),
+            "to": request.status.as_str(),
+        }))
+        .bind(user_id)
+        .execute(self.db.pool())
+        .await?;
+
+        self.get_asset(tenant_id, asset_id).await
+    }
+
+    /// Straight-line book value of an asset on a date
+    pub async fn current_book_value(
+        &self,
+        tenant_id: Uuid,
+        asset_id: Uuid,
+        as_of: NaiveDate,
+    ) -> AppResult<BookValue> {
+        let asset = self.get_asset(tenant_id, asset_id).await?;
+
+        Ok(BookValue {
+            asset_id,
+            as_of,
+            purchase_price: asset.purchase_price,
+            book_value: asset.book_value(as_of),
+        })
+    }
+
+    /// Printable SVG label for an asset, with a QR code that links to it
+    pub async fn generate_label(&self, tenant_id: Uuid, asset_id: Uuid) -> AppResult<Vec<u8>> {
+        let asset = self.get_asset(tenant_id, asset_id).await?;
+        let svg = AssetLabel::for_asset(&asset, &self.base_url).to_svg()?;
+
+        Ok(svg.into_bytes())
+    }
+
+    /// Where a scanned label should take the user: the asset's page in the app
+    pub async fn resolve_scan(&self, tenant_id: Uuid, asset_id: Uuid) -> AppResult<String> {
+        let asset = self.get_asset(tenant_id, asset_id).await?;
+
+        Ok(format!("{}/assets/{}", self.base_url.trim_end_matches('/'), asset.id))
+    }
+
+    // ========================================================================
+    // RMM INVENTORY SYNC
+    // ========================================================================
+
+    /// Upsert assets from an RMM inventory snapshot.
+    ///
+    /// Devices are matched by agent ID, then serial number. Devices are linked to
+    /// companies through `rmm_device_mappings`; unmapped new devices are skipped.
+    /// Details edited in the PSA are reconciled against the RMM's under the
+    /// connection's conflict policy rather than overwritten.
+    /// RMM-managed assets missing from the snapshot are flagged, never deleted.
+    pub async fn sync_from_rmm(
+        &self,
+        tenant_id: Uuid,
+        inventory: Vec<RmmAsset>,
+    ) -> AppResult<SyncReport> {
+        let rows = sqlx::query_as::<_, RmmSyncRow>(
+            r#"
+            SELECT id, rmm_device_id, serial_number, manufacturer, model, specs, updated_at, last_sync_at, rmm_snapshot
+            FROM assets
+            WHERE tenant_id = $1
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let mut existing: Vec<AssetIdentity> = rows
+            .iter()
+            .map(|row| AssetIdentity {
+                id: row.id,
+                rmm_device_id: row.rmm_device_id.clone(),
+                serial_number: row.serial_number.clone(),
+            })
+            .collect();
+        let mut states: HashMap<Uuid, RmmSyncState> = rows
+            .into_iter()
+            .map(|row| (row.id, row.into()))
+            .collect();
+
+        let mappings: HashMap<String, RmmMappingRow> = sqlx::query_as::<_, RmmMappingRow>(
+            r#"
+            SELECT m.rmm_device_id, m.rmm_connection_id, m.company_id, c.conflict_policy
+            FROM rmm_device_mappings m
+            JOIN rmm_connections c ON c.id = m.rmm_connection_id
+            WHERE m.tenant_id = $1
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(self.db.pool())
+        .await?
+        .into_iter()
+        .map(|row| (row.rmm_device_id.clone(), row))
+        .collect();
+
+        // Assets with a conflict still waiting on someone keep both copies as they are
+        let held: HashSet<Uuid> = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            SELECT local_id FROM sync_reconciliations
+            WHERE tenant_id = $1 AND integration = 'rmm' AND decision = 'flag_for_review' AND resolved_at IS NULL
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(self.db.pool())
+        .await?
+        .into_iter()
+        .collect();
+
+        let mut report = SyncReport::default();
+        let mut seen = HashSet::new();
+
+        for device in &inventory {
+            let mapping = mappings.get(&device.agent_id);
+            let company_id = mapping.and_then(|m| m.company_id);
+
+            let asset_id = match AssetIdentity::find_match(device, &existing) {
+                Some(asset_id) => {
+                    let policy = mapping
+                        .and_then(|m| ConflictPolicy::from_str(&m.conflict_policy))
+                        .unwrap_or_default();
+                    let state = &states[&asset_id];
+                    let remote = device.fields_over(&state.fields);
+                    let decision = if held.contains(&asset_id) {
+                        SyncDecision::FlagForReview
+                    } else {
+                        let result = device.reconcile(state, policy);
+                        if result.conflict {
+                            self.record_rmm_reconciliation(
+                                tenant_id,
+                                mapping.map(|m| m.rmm_connection_id),
+                                device,
+                                state,
+                                &remote,
+                                &result,
+                            )
+                            .await?;
+                            report.conflicts += 1;
+                        }
+                        result.decision
+                    };
+
+                    // Every update bumps updated_at, so one that takes the RMM's copy moves the sync
+                    // point with it; one that keeps the PSA's leaves it so the edit keeps winning
+                    match decision {
+                        SyncDecision::InSync | SyncDecision::PullRemote => {
+                            sqlx::query(
+                                r#"
+                                UPDATE assets
+                                SET rmm_device_id = $1, company_id = COALESCE($2, company_id), last_seen_at = $3,
+                                    possibly_decommissioned = FALSE, serial_number = $4, manufacturer = $5,
+                                    model = $6, specs = $7, rmm_snapshot = $8, last_sync_at = NOW()
+                                WHERE tenant_id = $9 AND id = $10
+                                "#,
+                            )
+                            .bind(&device.agent_id)
+                            .bind(company_id)
+                            .bind(device.last_seen)
+                            .bind(&remote.serial_number)
+                            .bind(&remote.manufacturer)
+                            .bind(&remote.model)
+                            .bind(&remote.specs)
+                            .bind(serde_json::to_value(&remote)?)
+                            .bind(tenant_id)
+                            .bind(asset_id)
+                            .execute(self.db.pool())
+                            .await?;
+                        }
+                        SyncDecision::PushLocal => {
+                            // The RMM can't be written to, so the asset keeps its details. One
+                            // adopted without a sync point counts as edited since it was created.
+                            sqlx::query(
+                                r#"
+                                UPDATE assets
+                                SET rmm_device_id = $1, company_id = COALESCE($2, company_id), last_seen_at = $3,
+                                    possibly_decommissioned = FALSE, rmm_snapshot = $4,
+                                    last_sync_at = COALESCE(last_sync_at, created_at)
+                                WHERE tenant_id = $5 AND id = $6
+                                "#,
+                            )
+                            .bind(&device.agent_id)
+                            .bind(company_id)
+                            .bind(device.last_seen)
+                            .bind(serde_json::to_value(&remote)?)
+                            .bind(tenant_id)
+                            .bind(asset_id)
+                            .execute(self.db.pool())
+                            .await?;
+                        }
+                        SyncDecision::FlagForReview => {
+                            sqlx::query(
+                                r#"
+                                UPDATE assets
+                                SET rmm_device_id = $1, company_id = COALESCE($2, company_id), last_seen_at = $3,
+                                    possibly_decommissioned = FALSE
+                                WHERE tenant_id = $4 AND id = $5
+                                "#,
+                            )
+                            .bind(&device.agent_id)
+                            .bind(company_id)
+                            .bind(device.last_seen)
+                            .bind(tenant_id)
+                            .bind(asset_id)
+                            .execute(self.db.pool())
+                            .await?;
+                        }
+                    }
+
+                    report.updated += 1;
+                    asset_id
+                }
+                None => {
+                    let Some(company_id) = company_id else {
+                        report.unmapped_agents.push(device.agent_id.clone());
+                        continue;
+                    };
+
+                    let asset_type_id = self
+                        .resolve_asset_type(tenant_id, device.asset_type.as_deref().unwrap_or("Workstation"))
+                        .await?;
+                    let fields = device.fields_over(&RmmAssetFields::default());
+
+                    let (asset_id, synced_at): (Uuid, DateTime<Utc>) = sqlx::query_as(
+                        r#"
+                        INSERT INTO assets (tenant_id, name, asset_type_id, company_id, status, manufacturer, model,
+                                            serial_number, rmm_device_id, specs, last_seen_at, last_sync_at,
+                                            rmm_snapshot)
+                        VALUES ($1, $2, $3, $4, 'deployed', $5, $6, $7, $8, $9, $10, NOW(), $11)
+                        RETURNING id, last_sync_at
+                        "#,
+                    )
+                    .bind(tenant_id)
+                    .bind(&device.hostname)
+                    .bind(asset_type_id)
+                    .bind(company_id)
+                    .bind(&device.manufacturer)
+                    .bind(&device.model)
+                    .bind(&device.serial_number)
+                    .bind(&device.agent_id)
+                    .bind(&device.specs)
+                    .bind(device.last_seen)
+                    .bind(serde_json::to_value(&fields)?)
+                    .fetch_one(self.db.pool())
+                    .await?;
+
+                    // Later duplicates of the same device in this snapshot update instead of inserting
+                    existing.push(AssetIdentity {
+                        id: asset_id,
+                        rmm_device_id: Some(device.agent_id.clone()),
+                        serial_number: device.serial_number.clone(),
+                    });
+                    states.insert(
+                        asset_id,
+                        RmmSyncState {
+                            asset_id,
+                            fields: fields.clone(),
+                            updated_at: synced_at,
+                            last_sync_at: Some(synced_at),
+                            snapshot: Some(fields),
+                        },
+                    );
+                    report.created += 1;
+                    asset_id
+                }
+            };
+
+            sqlx::query(
+                "UPDATE rmm_device_mappings SET asset_id = $1, last_seen = $2, sync_status = 'synced', updated_at = NOW() WHERE tenant_id = $3 AND rmm_device_id = $4",
+            )
+            .bind(asset_id)
+            .bind(device.last_seen)
+            .bind(tenant_id)
+            .bind(&device.agent_id)
+            .execute(self.db.pool())
+            .await?;
+
+            seen.insert(asset_id);
+        }
+
+        // Flagging is the sync's own bookkeeping, not an edit to reconcile later
+        let stale = AssetIdentity::stale(&existing, &seen);
+        if !stale.is_empty() {
+            let result = sqlx::query(
+                r#"
+                UPDATE assets
+                SET possibly_decommissioned = TRUE,
+                    last_sync_at = CASE WHEN updated_at > last_sync_at THEN last_sync_at ELSE NOW() END
+                WHERE tenant_id = $1 AND id = ANY($2) AND possibly_decommissioned IS NOT TRUE
+                "#,
+            )
+            .bind(tenant_id)
+            .bind(&stale)
+            .execute(self.db.pool())
+            .await?;
+
+            report.flagged_stale = result.rows_affected();
+        }
+
+        Ok(report)
+    }
+
+    async fn record_rmm_reconciliation(
+        &self,
+        tenant_id: Uuid,
+        connection_id: Option<Uuid>,
+        device: &RmmAsset,
+        local: &RmmSyncState,
+        remote: &RmmAssetFields,
+        result: &Reconciliation,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO sync_reconciliations (tenant_id, integration, connection_id, entity_type, local_id,
+                                              external_id, policy, decision, conflict, local_value, remote_value,
+                                              local_modified_at, remote_modified_at)
+            VALUES ($1, 'rmm', $2, 'asset', $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(connection_id)
+        .bind(local.asset_id)
+        .bind(&device.agent_id)
+        .bind(result.policy.as_str())
+        .bind(result.decision.as_str())
+        .bind(result.conflict)
+        .bind(serde_json::to_value(&local.fields)?)
+        .bind(serde_json::to_value(remote)?)
+        .bind(local.updated_at)
+        .bind(device.last_seen)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    /// RMM conflicts recorded for the tenant's assets, newest first
+    pub async fn list_rmm_reconciliations(&self, tenant_id: Uuid) -> AppResult<Vec<SyncReconciliation>> {
+        let rows = sqlx::query_as::<_, SyncReconciliationRow>(&format!(
+            r#"
+            SELECT {}
+            FROM sync_reconciliations r
+            WHERE r.tenant_id = $1 AND r.integration = 'rmm'
+            ORDER BY r.created_at DESC
+            LIMIT 100
+            "#,
+            RECONCILIATION_COLUMNS
+        ))
+        .bind(tenant_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Settle an RMM conflict flagged for review. Keeping the remote copy
+    /// takes the RMM's details; keeping the PSA copy leaves them as they are,
+    /// and the update counts as an edit so the next sync keeps them too.
+    pub async fn resolve_rmm_reconciliation(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        reconciliation_id: Uuid,
+        request: &ResolveReconciliationRequest,
+    ) -> AppResult<Asset> {
+        if !matches!(request.decision, SyncDecision::PushLocal | SyncDecision::PullRemote) {
+            return Err(AppError::validation_field(
+                "decision",
+                "Resolve by keeping the local or the remote copy",
+            ));
+        }
+
+        let row = sqlx::query_as::<_, SyncReconciliationRow>(&format!(
+            "SELECT {} FROM sync_reconciliations r WHERE r.tenant_id = $1 AND r.integration = 'rmm' AND r.id = $2",
+            RECONCILIATION_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(reconciliation_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::not_found("Sync reconciliation"))?;
+        let reconciliation: SyncReconciliation = row.into();
+
+        if reconciliation.decision != SyncDecision::FlagForReview || reconciliation.resolved_at.is_some() {
+            return Err(AppError::conflict("Only open conflicts flagged for review can be resolved"));
+        }
+
+        let mut tx = self.db.pool().begin().await?;
+        if request.decision == SyncDecision::PullRemote {
+            let remote: RmmAssetFields = serde_json::from_value(reconciliation.remote_value.clone())?;
+            sqlx::query(
+                r#"
+                UPDATE assets
+                SET serial_number = $1, manufacturer = $2, model = $3, specs = $4, rmm_snapshot = $5,
+                    last_sync_at = NOW()
+                WHERE tenant_id = $6 AND id = $7
+                "#,
+            )
+            .bind(&remote.serial_number)
+            .bind(&remote.manufacturer)
+            .bind(&remote.model)
+            .bind(&remote.specs)
+            .bind(&reconciliation.remote_value)
+            .bind(tenant_id)
+            .bind(reconciliation.local_id)
+            .execute(&mut *tx)
+            .await?;
+        } else {
+            sqlx::query(
+                r#"
+                UPDATE assets
+                SET rmm_snapshot = $1, last_sync_at = COALESCE(last_sync_at, created_at)
+                WHERE tenant_id = $2 AND id = $3
+                "#,
+            )
+            .bind(&reconciliation.remote_value)
+            .bind(tenant_id)
+            .bind(reconciliation.local_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query(
+            "UPDATE sync_reconciliations SET resolved_at = NOW(), resolved_by_id = $1 WHERE id = $2",
+        )
+        .bind(user_id)
+        .bind(reconciliation_id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        self.get_asset(tenant_id, reconciliation.local_id).await
+    }
+
+    /// Find an asset type by name, creating it on first use
+    async fn resolve_asset_type(&self, tenant_id: Uuid, name: &str) -> AppResult<Uuid> {
+        let existing = sqlx::query_scalar::<_, Uuid>(
+            "SELECT id FROM asset_types WHERE tenant_id = $1 AND LOWER(name) = LOWER($2) ORDER BY created_at LIMIT 1",
+        )
+        .bind(tenant_id)
+        .bind(name)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        if let Some(id) = existing {
+            return Ok(id);
+        }
+
+        let id = sqlx::query_scalar::<_, Uuid>(
+            "INSERT INTO asset_types (tenant_id, name) VALUES ($1, $2) RETURNING id",
+        )
+        .bind(tenant_id)
+        .bind(name)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(id)
+    }
+
+    // ========================================================================
+    // SOFTWARE LICENSES
+    // ========================================================================
+
+    /// Create a software license
+    pub async fn create_license(
+        &self,
+        tenant_id: Uuid,
+        request: &CreateLicenseRequest,
+    ) -> AppResult<SoftwareLicense> {
+        let license_id = Uuid::new_v4();
+
+        sqlx::query(
+            r#"
+            INSERT INTO software_licenses (id, tenant_id, company_id, product_name, vendor, seats_purchased, expires_on, notes)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(license_id)
+        .bind(tenant_id)
+        .bind(request.company_id)
+        .bind(&request.product_name)
+        .bind(&request.vendor)
+        .bind(request.seats_purchased)
+        .bind(request.expires_on)
+        .bind(&request.notes)
+        .execute(self.db.pool())
+        .await?;
+
+        self.get_license(tenant_id, license_id).await
+    }
+
+    /// Get license by ID
+    pub async fn get_license(&self, tenant_id: Uuid, license_id: Uuid) -> AppResult<SoftwareLicense> {
+        let row = sqlx::query_as::<_, SoftwareLicenseRow>(
+            r#"
+            SELECT id, tenant_id, company_id, product_name, vendor, seats_purchased, expires_on,
+                   notes, is_active, created_at, updated_at
+            FROM software_licenses
+            WHERE tenant_id = $1 AND id = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(license_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("License".to_string()))?;
+
+        Ok(row.into())
+    }
+
+    /// List active licenses
+    pub async fn list_licenses(&self, tenant_id: Uuid) -> AppResult<Vec<SoftwareLicense>> {
+        let rows = sqlx::query_as::<_, SoftwareLicenseRow>(
+            r#"
+            SELECT id, tenant_id, company_id, product_name, vendor, seats_purchased, expires_on,
+                   notes, is_active, created_at, updated_at
+            FROM software_licenses
+            WHERE tenant_id = $1 AND is_active = TRUE
+            ORDER BY product_name
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Seat usage and expiry state for every active license
+    pub async fn license_compliance(&self, tenant_id: Uuid) -> AppResult<Vec<LicenseStatus>> {
+        let licenses = self.list_licenses(tenant_id).await?;
+        let counts = sqlx::query_as::<_, (Uuid, i64)>(
+            r#"
+            SELECT license_id, COUNT(*)
+            FROM software_license_assignments
+            WHERE tenant_id = $1
+            GROUP BY license_id
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let today = Utc::now().date_naive();
+        Ok(licenses
+            .iter()
+            .map(|license| {
+                let assigned = counts
+                    .iter()
+                    .find(|(id, _)| *id == license.id)
+                    .map(|(_, count)| *count)
+                    .unwrap_or(0);
+                LicenseStatus::evaluate(license, assigned, today)
+            })
+            .collect())
+    }
+
+    /// Seat usage for one license
+    pub async fn license_status(
+        &self,
+        tenant_id: Uuid,
+        license_id: Uuid,
+        today: NaiveDate,
+    ) -> AppResult<LicenseStatus> {
+        let license = self.get_license(tenant_id, license_id).await?;
+        let assigned: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM software_license_assignments WHERE tenant_id = $1 AND license_id = $2",
+        )
+        .bind(tenant_id)
+        .bind(license_id)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(LicenseStatus::evaluate(&license, assigned, today))
+    }
+
+    /// Assign a seat. Assignments beyond capacity are still recorded, since the
+    /// software is usually already installed, but the result carries a warning.
+    pub async fn assign_seat(
+        &self,
+        tenant_id: Uuid,
+        license_id: Uuid,
+        user_id: Uuid,
+        request: &AssignSeatRequest,
+    ) -> AppResult<SeatAssignmentResult> {
+        if request.asset_id.is_none() && request.contact_id.is_none() {
+            return Err(AppError::BadRequest(
+                "A seat must be assigned to an asset or a contact".to_string(),
+            ));
+        }
+
+        let license = self.get_license(tenant_id, license_id).await?;
+
+        let row = sqlx::query_as::<_, LicenseAssignmentRow>(
+            r#"
+            INSERT INTO software_license_assignments (tenant_id, license_id, asset_id, contact_id, assigned_by_id)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, license_id, asset_id, contact_id, assigned_by_id, assigned_at
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(license_id)
+        .bind(request.asset_id)
+        .bind(request.contact_id)
+        .bind(user_id)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        let status = self
+            .license_status(tenant_id, license_id, Utc::now().date_naive())
+            .await?;
+
+        let warning = status.over_deployed.then(|| {
+            tracing::warn!(
+                "License {} over-deployed: {} of {} seats assigned",
+                license_id,
+                status.seats_assigned,
+                status.seats_purchased
+            );
+            format!(
+                "{} is over-deployed: {} of {} seats assigned",
+                license.product_name, status.seats_assigned, status.seats_purchased
+            )
+        });
+
+        Ok(SeatAssignmentResult {
+            assignment: row.into(),
+            status,
+            warning,
+        })
+    }
+
+    /// Release a seat
+    pub async fn unassign_seat(&self, tenant_id: Uuid, assignment_id: Uuid) -> AppResult<()> {
+        let result = sqlx::query("DELETE FROM software_license_assignments WHERE tenant_id = $1 AND id = $2")
+            .bind(tenant_id)
+            .bind(assignment_id)
+            .execute(self.db.pool())
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("License assignment".to_string()));
+        }
+
+        Ok(())
+    }
+
+    // ========================================================================
+    // STOCK
+    // ========================================================================
+
+    /// Create a stock item
+    pub async fn create_stock_item(&self, tenant_id: Uuid, request: &CreateStockItemRequest) -> AppResult<StockItem> {
+        request.check()?;
+
+        let row = sqlx::query_as::<_, StockItemRow>(&format!(
+            r#"
+            INSERT INTO stock_items (tenant_id, sku, name, description, unit_cost, unit_price, quantity_on_hand, reorder_point)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (tenant_id, sku) DO NOTHING
+            RETURNING {}
+            "#,
+            STOCK_ITEM_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(request.sku.trim())
+        .bind(&request.name)
+        .bind(&request.description)
+        .bind(request.unit_cost)
+        .bind(request.unit_price)
+        .bind(request.quantity_on_hand)
+        .bind(request.reorder_point)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::Conflict(format!("A stock item with SKU {} already exists", request.sku.trim())))?;
+
+        Ok(row.into())
+    }
+
+    /// Get stock item by ID
+    pub async fn get_stock_item(&self, tenant_id: Uuid, item_id: Uuid) -> AppResult<StockItem> {
+        let row = sqlx::query_as::<_, StockItemRow>(&format!(
+            "SELECT {} FROM stock_items WHERE tenant_id = $1 AND id = $2",
+            STOCK_ITEM_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(item_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Stock item".to_string()))?;
+
+        Ok(row.into())
+    }
+
+    /// List active stock items, or only those due for reordering
+    pub async fn list_stock_items(&self, tenant_id: Uuid, needs_reorder: bool) -> AppResult<Vec<StockItem>> {
+        let rows = sqlx::query_as::<_, StockItemRow>(&format!(
+            r#"
+            SELECT {} FROM stock_items
+            WHERE tenant_id = $1 AND is_active = TRUE AND (NOT $2 OR quantity_on_hand <= reorder_point)
+            ORDER BY name
+            "#,
+            STOCK_ITEM_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(needs_reorder)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Movements for a stock item, newest first
+    pub async fn list_stock_movements(&self, tenant_id: Uuid, item_id: Uuid) -> AppResult<Vec<StockMovement>> {
+        let rows = sqlx::query_as::<_, StockMovementRow>(&format!(
+            "SELECT {} FROM stock_movements WHERE tenant_id = $1 AND stock_item_id = $2 ORDER BY created_at DESC",
+            STOCK_MOVEMENT_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(item_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Add parts to stock
+    pub async fn receive_stock(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        item_id: Uuid,
+        request: &ReceiveStockRequest,
+    ) -> AppResult<StockMovement> {
+        let mut tx = self.db.pool().begin().await?;
+        let mut item = Self::lock_stock_item(&mut tx, tenant_id, item_id).await?;
+        item.receive(request.quantity)?;
+
+        let reference = StockReference {
+            notes: request.notes.clone(),
+            ..Default::default()
+        };
+        let movement = Self::record_stock_movement(
+            &mut tx,
+            &item,
+            user_id,
+            StockMovementType::In,
+            request.quantity,
+            &reference,
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(movement)
+    }
+
+    /// Take parts out of stock for a ticket or project and log the movement.
+    /// Billable use raises an expense at the item's unit price. Taking the
+    /// item to its reorder point carries an alert.
+    pub async fn consume_stock(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        item_id: Uuid,
+        request: &ConsumeStockRequest,
+    ) -> AppResult<StockConsumption> {
+        let mut tx = self.db.pool().begin().await?;
+        let mut item = Self::lock_stock_item(&mut tx, tenant_id, item_id).await?;
+        let unit_price = match (request.is_billable, item.unit_price) {
+            (true, None) => {
+                return Err(AppError::validation_field("is_billable", "The item has no unit price to bill at"));
+            }
+            (true, Some(price)) => Some(price),
+            (false, _) => None,
+        };
+        let reached_reorder_point = item.consume(request.quantity)?;
+
+        let mut movement = Self::record_stock_movement(
+            &mut tx,
+            &item,
+            user_id,
+            StockMovementType::Out,
+            request.quantity,
+            &request.reference,
+        )
+        .await?;
+        tx.commit().await?;
+
+        if let Some(unit_price) = unit_price {
+            let company_id: Option<Uuid> = sqlx::query_scalar(
+                r#"
+                SELECT COALESCE(
+                    (SELECT company_id FROM tickets WHERE tenant_id = $1 AND id = $2),
+                    (SELECT company_id FROM projects WHERE tenant_id = $1 AND id = $3)
+                )
+                "#,
+            )
+            .bind(tenant_id)
+            .bind(request.reference.ticket_id)
+            .bind(request.reference.project_id)
+            .fetch_one(self.db.pool())
+            .await?;
+
+            let expense = self
+                .time_tracking
+                .create_expense(
+                    tenant_id,
+                    user_id,
+                    &CreateExpenseRequest {
+                        expense_date: Utc::now().date_naive(),
+                        category: "parts".to_string(),
+                        description: format!("{} x {} ({})", request.quantity, item.name, item.sku),
+                        amount: unit_price * rust_decimal::Decimal::from(request.quantity),
+                        company_id,
+                        ticket_id: request.reference.ticket_id,
+                        project_id: request.reference.project_id,
+                        is_billable: true,
+                    },
+                )
+                .await?;
+
+            sqlx::query("UPDATE stock_movements SET expense_id = $1 WHERE tenant_id = $2 AND id = $3")
+                .bind(expense.id)
+                .bind(tenant_id)
+                .bind(movement.id)
+                .execute(self.db.pool())
+                .await?;
+            movement.expense_id = Some(expense.id);
+        }
+
+        let reorder_alert = reached_reorder_point.then(|| {
+            tracing::warn!(
+                "Stock item {} at reorder point: {} on hand, reorder at {}",
+                item.id,
+                item.quantity_on_hand,
+                item.reorder_point
+            );
+            item.reorder_alert()
+        });
+
+        Ok(StockConsumption {
+            item,
+            movement,
+            reorder_alert,
+        })
+    }
+
+    async fn lock_stock_item(conn: &mut sqlx::PgConnection, tenant_id: Uuid, item_id: Uuid) -> AppResult<StockItem> {
+        let row = sqlx::query_as::<_, StockItemRow>(&format!(
+            "SELECT {} FROM stock_items WHERE tenant_id = $1 AND id = $2 FOR UPDATE",
+            STOCK_ITEM_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(item_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Stock item".to_string()))?;
+
+        Ok(row.into())
+    }
+
+    /// Store the item's new quantity and the movement that led to it
+    async fn record_stock_movement(
+        conn: &mut sqlx::PgConnection,
+        item: &StockItem,
+        user_id: Uuid,
+        movement_type: StockMovementType,
+        quantity: i32,
+        reference: &StockReference,
+    ) -> AppResult<StockMovement> {
+        sqlx::query("UPDATE stock_items SET quantity_on_hand = $1 WHERE tenant_id = $2 AND id = $3")
+            .bind(item.quantity_on_hand)
+            .bind(item.tenant_id)
+            .bind(item.id)
+            .execute(&mut *conn)
+            .await?;
+
+        let row = sqlx::query_as::<_, StockMovementRow>(&format!(
+            r#"
+            INSERT INTO stock_movements (tenant_id, stock_item_id, movement_type, quantity, balance,
+                                         ticket_id, project_id, notes, moved_by_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING {}
+            "#,
+            STOCK_MOVEMENT_COLUMNS
+        ))
+        .bind(item.tenant_id)
+        .bind(item.id)
+        .bind(movement_type.as_str())
+        .bind(quantity)
+        .bind(item.quantity_on_hand)
+        .bind(reference.ticket_id)
+        .bind(reference.project_id)
+        .bind(&reference.notes)
+        .bind(user_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(row.into())
+    }
+
+    // ========================================================================
+    // MAINTENANCE
+    // ========================================================================
+
+    /// Maintenance schedules on an asset
+    pub async fn list_maintenance(&self, tenant_id: Uuid, asset_id: Uuid) -> AppResult<Vec<MaintenanceSchedule>> {
+        let rows = sqlx::query_as::<_, MaintenanceScheduleRow>(&format!(
+            "SELECT {} FROM asset_maintenance_schedules WHERE tenant_id = $1 AND asset_id = $2 ORDER BY next_due_date",
+            MAINTENANCE_SCHEDULE_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(asset_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Schedule maintenance on an asset; tickets are raised by the
+    /// `MAINTENANCE_TICKETS_JOB` as each cycle comes due
+    pub async fn create_maintenance(
+        &self,
+        tenant_id: Uuid,
+        asset_id: Uuid,
+        user_id: Uuid,
+        request: &CreateMaintenanceScheduleRequest,
+    ) -> AppResult<MaintenanceSchedule> {
+        let asset = self.get_asset(tenant_id, asset_id).await?;
+        if asset.status == AssetStatus::Retired {
+            return Err(AppError::validation_field("asset_id", "Retired assets can't be scheduled for maintenance"));
+        }
+
+        let row = sqlx::query_as::<_, MaintenanceScheduleRow>(&format!(
+            r#"
+            INSERT INTO asset_maintenance_schedules (tenant_id, asset_id, title, description, priority_id, queue_id,
+                                                     interval_count, interval_unit, lead_days, next_due_date,
+                                                     created_by_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            RETURNING {}
+            "#,
+            MAINTENANCE_SCHEDULE_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(asset.id)
+        .bind(&request.title)
+        .bind(&request.description)
+        .bind(request.priority_id)
+        .bind(request.queue_id)
+        .bind(request.interval_count)
+        .bind(request.interval_unit.as_str())
+        .bind(request.lead_days)
+        .bind(request.first_due_date)
+        .bind(user_id)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(row.into())
+    }
+
+    pub async fn delete_maintenance(&self, tenant_id: Uuid, schedule_id: Uuid) -> AppResult<()> {
+        let result = sqlx::query("DELETE FROM asset_maintenance_schedules WHERE tenant_id = $1 AND id = $2")
+            .bind(tenant_id)
+            .bind(schedule_id)
+            .execute(self.db.pool())
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Maintenance schedule".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Raise a ticket for every maintenance cycle whose lead time has started,
+    /// linking the asset, and move each schedule on to its next cycle.
+    /// Returns how many tickets were raised.
+    pub async fn raise_maintenance_tickets(&self, now: DateTime<Utc>) -> AppResult<usize> {
+        // A day's slack either way covers every tenant's time zone
+        let rows = sqlx::query_as::<_, MaintenanceScheduleRow>(&format!(
+            r#"
+            SELECT {} FROM asset_maintenance_schedules s
+            WHERE s.is_active AND s.next_due_date - s.lead_days <= $1
+              AND EXISTS (SELECT 1 FROM assets a WHERE a.id = s.asset_id AND a.status <> 'retired')
+            "#,
+            MAINTENANCE_SCHEDULE_COLUMNS
+        ))
+        .bind(now.date_naive() + Days::new(1))
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let mut today_by_tenant: HashMap<Uuid, NaiveDate> = HashMap::new();
+        let mut raised = 0;
+        for schedule in rows.into_iter().map(MaintenanceSchedule::from) {
+            let today = match today_by_tenant.get(&schedule.tenant_id) {
+                Some(today) => *today,
+                None => {
+                    let timezone = TenantTimezone::load(self.db.pool(), schedule.tenant_id).await?;
+                    let today = timezone.local_date(now);
+                    today_by_tenant.insert(schedule.tenant_id, today);
+                    today
+                }
+            };
+            let Some(cycle) = schedule.cycle_due(today) else {
+                continue;
+            };
+
+            if self.raise_maintenance_ticket(&schedule, cycle.due_date).await? {
+                raised += 1;
+            }
+
+            sqlx::query("UPDATE asset_maintenance_schedules SET next_due_date = $1 WHERE id = $2 AND next_due_date < $1")
+                .bind(cycle.next_due_date)
+                .bind(schedule.id)
+                .execute(self.db.pool())
+                .await?;
+        }
+
+        Ok(raised)
+    }
+
+    /// Raise the ticket for one cycle, unless a ticket was already raised for it
+    async fn raise_maintenance_ticket(&self, schedule: &MaintenanceSchedule, due_date: NaiveDate) -> AppResult<bool> {
+        // Claim the cycle first, so overlapping runs can't both raise it
+        let claimed = sqlx::query(
+            r#"
+            INSERT INTO asset_maintenance_tickets (schedule_id, due_date)
+            VALUES ($1, $2)
+            ON CONFLICT (schedule_id, due_date) DO NOTHING
+            "#,
+        )
+        .bind(schedule.id)
+        .bind(due_date)
+        .execute(self.db.pool())
+        .await?
+        .rows_affected()
+            > 0;
+        if !claimed {
+            return Ok(false);
+        }
+
+        let asset = self.get_asset(schedule.tenant_id, schedule.asset_id).await?;
+        let request = CreateTicketRequest {
+            title: format!("{}: {}", schedule.title, asset.name),
+            description: schedule.description.clone(),
+            priority_id: schedule.priority_id,
+            type_id: None,
+            category_id: None,
+            queue_id: schedule.queue_id,
+            source: TicketSource::Internal,
+            company_id: asset.company_id,
+            contact_id: asset.contact_id,
+            site_id: asset.site_id,
+            assigned_to_id: None,
+            team_id: None,
+            contract_id: None,
+            sla_id: None,
+            scheduled_start: None,
+            scheduled_end: None,
+            estimated_hours: None,
+            is_billable: None,
+            asset_id: Some(asset.id),
+            custom_fields: serde_json::json!({ "maintenance_due_date": due_date }),
+            tags: vec![MAINTENANCE_TAG.to_string()],
+        };
+
+        let ticket = match self
+            .tickets
+            .create_ticket(schedule.tenant_id, schedule.created_by_id, &request)
+            .await
+        {
+            Ok(ticket) => ticket,
+            Err(e) => {
+                // Release the claim so the next run tries again
+                sqlx::query("DELETE FROM asset_maintenance_tickets WHERE schedule_id = $1 AND due_date = $2")
+                    .bind(schedule.id)
+                    .bind(due_date)
+                    .execute(self.db.pool())
+                    .await?;
+                return Err(e);
+            }
+        };
+
+        sqlx::query("UPDATE asset_maintenance_tickets SET ticket_id = $1 WHERE schedule_id = $2 AND due_date = $3")
+            .bind(ticket.id)
+            .bind(schedule.id)
+            .bind(due_date)
+            .execute(self.db.pool())
+            .await?;
+
+        Ok(true)
+    }
+
+    /// Handler for `MAINTENANCE_TICKETS_JOB`, for registering with the job worker
+    pub async fn run_maintenance_tickets_job(&self, _job: Job) -> AppResult<()> {
+        let raised = self.raise_maintenance_tickets(Utc::now()).await?;
+        if raised > 0 {
+            tracing::info!("Raised {} asset maintenance tickets", raised);
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// DATABASE ROW TYPES
+// ============================================================================
+
+#[derive(sqlx::FromRow)]
+struct AssetRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    asset_tag: Option<String>,
+    name: String,
+    asset_type_id: Uuid,
+    company_id: Uuid,
+    site_id: Option<Uuid>,
+    contact_id: Option<Uuid>,
+    status: Option<String>,
+    manufacturer: Option<String>,
+    model: Option<String>,
+    serial_number: Option<String>,
+    purchase_date: Option<NaiveDate>,
+    purchase_price: Option<rust_decimal::Decimal>,
+    useful_life_months: Option<i32>,
+    salvage_value: rust_decimal::Decimal,
+    warranty_expiry: Option<NaiveDate>,
+    rmm_device_id: Option<String>,
+    last_seen_at: Option<chrono::DateTime<Utc>>,
+    possibly_decommissioned: Option<bool>,
+    retired_at: Option<chrono::DateTime<Utc>>,
+    created_at: chrono::DateTime<Utc>,
+    updated_at: chrono::DateTime<Utc>,
+}
+
+impl From<AssetRow> for Asset {
+    fn from(row: AssetRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            asset_tag: row.asset_tag,
+            name: row.name,
+            asset_type_id: row.asset_type_id,
+            company_id: row.company_id,
+            site_id: row.site_id,
+            contact_id: row.contact_id,
+            status: row
+                .status
+                .as_deref()
+                .and_then(AssetStatus::from_str)
+                .unwrap_or_default(),
+            manufacturer: row.manufacturer,
+            model: row.model,
+            serial_number: row.serial_number,
+            purchase_date: row.purchase_date,
+            purchase_price: row.purchase_price,
+            useful_life_months: row.useful_life_months,
+            salvage_value: row.salvage_value,
+            warranty_expiry: row.warranty_expiry,
+            rmm_device_id: row.rmm_device_id,
+            last_seen_at: row.last_seen_at,
+            possibly_decommissioned: row.possibly_decommissioned.unwrap_or(false),
+            retired_at: row.retired_at,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct RmmSyncRow {
+    id: Uuid,
+    rmm_device_id: Option<String>,
+    serial_number: Option<String>,
+    manufacturer: Option<String>,
+    model: Option<String>,
+    specs: serde_json::Value,
+    updated_at: DateTime<Utc>,
+    last_sync_at: Option<DateTime<Utc>>,
+    rmm_snapshot: Option<serde_json::Value>,
+}
+
+impl From<RmmSyncRow> for RmmSyncState {
+    fn from(row: RmmSyncRow) -> Self {
+        Self {
+            asset_id: row.id,
+            fields: RmmAssetFields {
+                serial_number: row.serial_number,
+                manufacturer: row.manufacturer,
+                model: row.model,
+                specs: row.specs,
+            },
+            updated_at: row.updated_at,
+            last_sync_at: row.last_sync_at,
+            snapshot: row.rmm_snapshot.and_then(|snapshot| serde_json::from_value(snapshot).ok()),
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct RmmMappingRow {
+    rmm_device_id: String,
+    rmm_connection_id: Uuid,
+    company_id: Option<Uuid>,
+    conflict_policy: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct SoftwareLicenseRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    company_id: Option<Uuid>,
+    product_name: String,
+    vendor: Option<String>,
+    seats_purchased: i32,
+    expires_on: Option<NaiveDate>,
+    notes: Option<String>,
+    is_active: Option<bool>,
+    created_at: chrono::DateTime<Utc>,
+    updated_at: chrono::DateTime<Utc>,
+}
+
+impl From<SoftwareLicenseRow> for SoftwareLicense {
+    fn from(row: SoftwareLicenseRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            company_id: row.company_id,
+            product_name: row.product_name,
+            vendor: row.vendor,
+            seats_purchased: row.seats_purchased,
+            expires_on: row.expires_on,
+            notes: row.notes,
+            is_active: row.is_active.unwrap_or(true),
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct LicenseAssignmentRow {
+    id: Uuid,
+    license_id: Uuid,
+    asset_id: Option<Uuid>,
+    contact_id: Option<Uuid>,
+    assigned_by_id: Option<Uuid>,
+    assigned_at: chrono::DateTime<Utc>,
+}
+
+impl From<LicenseAssignmentRow> for LicenseAssignment {
+    fn from(row: LicenseAssignmentRow) -> Self {
+        Self {
+            id: row.id,
+            license_id: row.license_id,
+            asset_id: row.asset_id,
+            contact_id: row.contact_id,
+            assigned_by_id: row.assigned_by_id,
+            assigned_at: row.assigned_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct StockItemRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    sku: String,
+    name: String,
+    description: Option<String>,
+    unit_cost: Option<rust_decimal::Decimal>,
+    unit_price: Option<rust_decimal::Decimal>,
+    quantity_on_hand: i32,
+    reorder_point: i32,
+    is_active: bool,
+    created_at: chrono::DateTime<Utc>,
+    updated_at: chrono::DateTime<Utc>,
+}
+
+impl From<StockItemRow> for StockItem {
+    fn from(row: StockItemRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            sku: row.sku,
+            name: row.name,
+            description: row.description,
+            unit_cost: row.unit_cost,
+            unit_price: row.unit_price,
+            quantity_on_hand: row.quantity_on_hand,
+            reorder_point: row.reorder_point,
+            is_active: row.is_active,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct StockMovementRow {
+    id: Uuid,
+    stock_item_id: Uuid,
+    movement_type: String,
+    quantity: i32,
+    balance: i32,
+    ticket_id: Option<Uuid>,
+    project_id: Option<Uuid>,
+    expense_id: Option<Uuid>,
+    notes: Option<String>,
+    moved_by_id: Option<Uuid>,
+    created_at: chrono::DateTime<Utc>,
+}
+
+impl From<StockMovementRow> for StockMovement {
+    fn from(row: StockMovementRow) -> Self {
+        Self {
+            id: row.id,
+            stock_item_id: row.stock_item_id,
+            movement_type: StockMovementType::from_str(&row.movement_type).unwrap_or_default(),
+            quantity: row.quantity,
+            balance: row.balance,
+            ticket_id: row.ticket_id,
+            project_id: row.project_id,
+            expense_id: row.expense_id,
+            notes: row.notes,
+            moved_by_id: row.moved_by_id,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct MaintenanceScheduleRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    asset_id: Uuid,
+    title: String,
+    description: Option<String>,
+    priority_id: Option<Uuid>,
+    queue_id: Option<Uuid>,
+    interval_count: i32,
+    interval_unit: String,
+    lead_days: i32,
+    next_due_date: NaiveDate,
+    is_active: bool,
+    created_by_id: Uuid,
+    created_at: chrono::DateTime<Utc>,
+    updated_at: chrono::DateTime<Utc>,
+}
+
+impl From<MaintenanceScheduleRow> for MaintenanceSchedule {
+    fn from(row: MaintenanceScheduleRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            asset_id: row.asset_id,
+            title: row.title,
+            description: row.description,
+            priority_id: row.priority_id,
+            queue_id: row.queue_id,
+            interval_count: row.interval_count,
+            interval_unit: IntervalUnit::from_str(&row.interval_unit).unwrap_or(IntervalUnit::Months),
+            lead_days: row.lead_days,
+            next_due_date: row.next_due_date,
+            is_active: row.is_active,
+            created_by_id: row.created_by_id,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
@@ -1 +1,16 @@
-//! audit module placeholder
+//! Audit Module
+//!
+//! The tenant audit trail. Entries are hash chained per tenant so an edited
+//! or deleted record can be detected.
+
+mod models;
+#[cfg(feature = "server")]
+mod service;
+#[cfg(feature = "server")]
+mod routes;
+
+pub use models::*;
+#[cfg(feature = "server")]
+pub use service::AuditService;
+#[cfg(feature = "server")]
+pub use routes::audit_routes;
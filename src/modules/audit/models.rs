@@ -0,0 +1,503 @@
+//! Audit log models and hash chain verification
+
+use chrono::{DateTime, SubsecRound, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::utils::error::{AppError, AppResult, FieldError};
+
+/// What was done
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Create,
+    #[default]
+    Update,
+    Delete,
+    View,
+    Login,
+    Logout,
+    Export,
+    Import,
+}
+
+impl AuditAction {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "create" => Some(Self::Create),
+            "update" => Some(Self::Update),
+            "delete" => Some(Self::Delete),
+            "view" => Some(Self::View),
+            "login" => Some(Self::Login),
+            "logout" => Some(Self::Logout),
+            "export" => Some(Self::Export),
+            "import" => Some(Self::Import),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Update => "update",
+            Self::Delete => "delete",
+            Self::View => "view",
+            Self::Login => "login",
+            Self::Logout => "logout",
+            Self::Export => "export",
+            Self::Import => "import",
+        }
+    }
+}
+
+/// An audit log record. `sequence` and the hashes are `None` on entries
+/// written before the log was chained.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub action: AuditAction,
+    pub entity_type: String,
+    pub entity_id: Option<Uuid>,
+    pub old_values: Option<serde_json::Value>,
+    pub new_values: Option<serde_json::Value>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub request_id: Option<String>,
+    pub timestamp: DateTime<Utc>,
+    /// Position in the tenant's chain, from 1
+    pub sequence: Option<i64>,
+    pub prev_hash: Option<String>,
+    pub hash: Option<String>,
+}
+
+impl AuditEntry {
+    /// SHA-256 over the entry's content and the previous entry's hash, hex
+    /// encoded. Any change to a recorded field changes it.
+    pub fn compute_hash(&self) -> String {
+        use sha2::{Digest, Sha256};
+
+        let content = serde_json::json!({
+            "id": self.id,
+            "tenant_id": self.tenant_id,
+            "user_id": self.user_id,
+            "action": self.action.as_str(),
+            "entity_type": self.entity_type,
+            "entity_id": self.entity_id,
+            "old_values": self.old_values,
+            "new_values": self.new_values,
+            "ip_address": self.ip_address,
+            "user_agent": self.user_agent,
+            "request_id": self.request_id,
+            "timestamp": self.timestamp.to_rfc3339_opts(chrono::SecondsFormat::Micros, true),
+            "sequence": self.sequence,
+            "prev_hash": self.prev_hash,
+        });
+
+        Sha256::digest(canonical_json(&content).as_bytes())
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
+
+/// Narrow the audit log. Every field is optional; set ones must all match.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuditFilter {
+    /// The user who acted
+    pub user_id: Option<Uuid>,
+    pub entity_type: Option<String>,
+    pub entity_id: Option<Uuid>,
+    pub action: Option<AuditAction>,
+    /// Inclusive
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl AuditFilter {
+    pub fn check(&self) -> AppResult<()> {
+        if let (Some(from), Some(to)) = (self.from, self.to) {
+            if to <= from {
+                return Err(AppError::validation(
+                    "Invalid date range",
+                    vec![FieldError::new("to", "Must be after from", "range")],
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether an entry passes the filter; the service applies the same
+    /// conditions in SQL
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        self.user_id.map_or(true, |user_id| entry.user_id == Some(user_id))
+            && self.entity_type.as_ref().map_or(true, |entity_type| &entry.entity_type == entity_type)
+            && self.entity_id.map_or(true, |entity_id| entry.entity_id == Some(entity_id))
+            && self.action.map_or(true, |action| entry.action == action)
+            && self.from.map_or(true, |from| entry.timestamp >= from)
+            && self.to.map_or(true, |to| entry.timestamp < to)
+    }
+}
+
+/// JSON with object keys sorted at every level, so a value hashes the same
+/// after a round trip through JSONB
+pub fn canonical_json(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            let fields: Vec<String> = keys
+                .into_iter()
+                .map(|key| format!("{}:{}", serde_json::Value::String(key.clone()), canonical_json(&map[key])))
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        serde_json::Value::Array(items) => {
+            format!("[{}]", items.iter().map(canonical_json).collect::<Vec<_>>().join(","))
+        }
+        other => other.to_string(),
+    }
+}
+
+/// An entry to record
+#[derive(Debug, Clone)]
+pub struct NewAuditEntry {
+    pub user_id: Option<Uuid>,
+    pub action: AuditAction,
+    pub entity_type: String,
+    pub entity_id: Option<Uuid>,
+    pub old_values: Option<serde_json::Value>,
+    pub new_values: Option<serde_json::Value>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub request_id: Option<String>,
+}
+
+impl NewAuditEntry {
+    pub fn new(action: AuditAction, entity_type: impl Into<String>, entity_id: Option<Uuid>) -> Self {
+        Self {
+            user_id: None,
+            action,
+            entity_type: entity_type.into(),
+            entity_id,
+            old_values: None,
+            new_values: None,
+            ip_address: None,
+            user_agent: None,
+            request_id: None,
+        }
+    }
+
+    pub fn by(mut self, user_id: Option<Uuid>) -> Self {
+        self.user_id = user_id;
+        self
+    }
+
+    pub fn old_values(mut self, values: serde_json::Value) -> Self {
+        self.old_values = Some(values);
+        self
+    }
+
+    pub fn new_values(mut self, values: serde_json::Value) -> Self {
+        self.new_values = Some(values);
+        self
+    }
+
+    pub fn request_id(mut self, request_id: Option<String>) -> Self {
+        self.request_id = request_id;
+        self
+    }
+
+    /// The chained entry that follows `previous`, or the first in the chain.
+    /// The timestamp is cut to microseconds, what PostgreSQL stores.
+    pub fn into_entry(self, tenant_id: Uuid, previous: Option<&ChainHead>, now: DateTime<Utc>) -> AuditEntry {
+        let mut entry = AuditEntry {
+            id: Uuid::new_v4(),
+            tenant_id,
+            user_id: self.user_id,
+            action: self.action,
+            entity_type: self.entity_type,
+            entity_id: self.entity_id,
+            old_values: self.old_values,
+            new_values: self.new_values,
+            ip_address: self.ip_address,
+            user_agent: self.user_agent,
+            request_id: self.request_id,
+            timestamp: now.trunc_subsecs(6),
+            sequence: Some(previous.map_or(1, |head| head.sequence + 1)),
+            prev_hash: previous.map(|head| head.hash.clone()),
+            hash: None,
+        };
+        entry.hash = Some(entry.compute_hash());
+        entry
+    }
+}
+
+/// The latest entry in a tenant's chain, kept beside the log so removing
+/// entries from the end is noticed too
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainHead {
+    pub sequence: i64,
+    pub hash: String,
+}
+
+/// Where a chain stops checking out
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChainBreak {
+    /// The entry's content or hash no longer matches what was recorded, or
+    /// it doesn't link to the entry before it
+    Altered { sequence: i64, entry_id: Uuid },
+    /// No entry at this position: it was deleted
+    Missing { sequence: i64 },
+}
+
+/// Result of checking a tenant's audit chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainVerification {
+    pub valid: bool,
+    /// Entries checked before the first break, or all of them
+    pub entries_checked: u64,
+    pub first_break: Option<ChainBreak>,
+}
+
+/// Walk a tenant's chained entries, ordered by sequence, and stop at the
+/// first one that was altered or is missing
+pub fn verify_chain(entries: &[AuditEntry], head: Option<&ChainHead>) -> ChainVerification {
+    verify_chain_from(entries, None, head)
+}
+
+/// [`verify_chain`] for a chain whose start was purged under a retention
+/// policy: `base` is the last entry removed, and the entry after it links to
+/// its hash
+pub fn verify_chain_from(entries: &[AuditEntry], base: Option<&ChainHead>, head: Option<&ChainHead>) -> ChainVerification {
+    let broken = |entries_checked: u64, first_break: ChainBreak| ChainVerification {
+        valid: false,
+        entries_checked,
+        first_break: Some(first_break),
+    };
+
+    let first_sequence = base.map_or(1, |base| base.sequence + 1);
+    let mut expected_sequence = first_sequence;
+    let mut previous: Option<&str> = base.map(|base| base.hash.as_str());
+    for entry in entries {
+        let Some(sequence) = entry.sequence else {
+            continue;
+        };
+        let checked = (expected_sequence - first_sequence) as u64;
+        if sequence != expected_sequence {
+            return broken(checked, ChainBreak::Missing { sequence: expected_sequence });
+        }
+        if entry.prev_hash.as_deref() != previous || entry.hash.as_deref() != Some(entry.compute_hash().as_str()) {
+            return broken(
+                checked,
+                ChainBreak::Altered {
+                    sequence,
+                    entry_id: entry.id,
+                },
+            );
+        }
+
+        previous = entry.hash.as_deref();
+        expected_sequence += 1;
+    }
+
+    let entries_checked = (expected_sequence - first_sequence) as u64;
+    if let Some(head) = head {
+        if head.sequence >= expected_sequence {
+            return broken(entries_checked, ChainBreak::Missing { sequence: expected_sequence });
+        }
+        if previous != Some(head.hash.as_str()) {
+            let last = entries.iter().rev().find(|entry| entry.sequence.is_some());
+            return broken(
+                entries_checked,
+                ChainBreak::Altered {
+                    sequence: head.sequence,
+                    entry_id: last.map_or(Uuid::nil(), |entry| entry.id),
+                },
+            );
+        }
+    }
+
+    ChainVerification {
+        valid: true,
+        entries_checked,
+        first_break: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(tenant_id: Uuid, length: usize) -> (Vec<AuditEntry>, ChainHead) {
+        let mut entries: Vec<AuditEntry> = Vec::new();
+        let mut head: Option<ChainHead> = None;
+        for i in 0..length {
+            let entry = NewAuditEntry::new(AuditAction::Update, "ticket", Some(Uuid::new_v4()))
+                .by(Some(Uuid::new_v4()))
+                .old_values(serde_json::json!({ "status": "new", "priority": i }))
+                .new_values(serde_json::json!({ "status": "in_progress", "priority": i }))
+                .into_entry(tenant_id, head.as_ref(), Utc::now());
+            head = Some(ChainHead {
+                sequence: entry.sequence.unwrap(),
+                hash: entry.hash.clone().unwrap(),
+            });
+            entries.push(entry);
+        }
+        (entries, head.unwrap())
+    }
+
+    #[test]
+    fn test_intact_chain_verifies() {
+        let (entries, head) = chain(Uuid::new_v4(), 5);
+        assert_eq!(entries[0].prev_hash, None);
+        assert_eq!(entries[1].prev_hash, entries[0].hash);
+
+        let verification = verify_chain(&entries, Some(&head));
+        assert!(verification.valid);
+        assert_eq!(verification.entries_checked, 5);
+
+        // Entries from before chaining are skipped
+        let mut with_legacy = entries.clone();
+        let mut legacy = entries[0].clone();
+        (legacy.sequence, legacy.prev_hash, legacy.hash) = (None, None, None);
+        with_legacy.insert(0, legacy);
+        assert!(verify_chain(&with_legacy, Some(&head)).valid);
+
+        // Key order doesn't matter, as after a JSONB round trip
+        let mut reordered = entries.clone();
+        reordered[2].new_values = serde_json::from_str(r#"{"priority": 2, "status": "in_progress"}"#).ok();
+        assert!(verify_chain(&reordered, Some(&head)).valid);
+
+        assert!(verify_chain(&[], None).valid);
+    }
+
+    #[test]
+    fn test_modified_entry_fails_verification() {
+        let (entries, head) = chain(Uuid::new_v4(), 5);
+
+        let mut edited = entries.clone();
+        edited[2].new_values = Some(serde_json::json!({ "status": "closed", "priority": 2 }));
+        let verification = verify_chain(&edited, Some(&head));
+        assert!(!verification.valid);
+        assert_eq!(verification.entries_checked, 2);
+        assert_eq!(
+            verification.first_break,
+            Some(ChainBreak::Altered { sequence: 3, entry_id: entries[2].id })
+        );
+
+        // Recomputing the edited entry's hash breaks the link to the next one
+        edited[2].hash = Some(edited[2].compute_hash());
+        assert_eq!(
+            verify_chain(&edited, Some(&head)).first_break,
+            Some(ChainBreak::Altered { sequence: 4, entry_id: entries[3].id })
+        );
+
+        let mut reattributed = entries.clone();
+        reattributed[0].user_id = None;
+        assert!(!verify_chain(&reattributed, Some(&head)).valid);
+    }
+
+    #[test]
+    fn test_deleted_entry_fails_verification() {
+        let (entries, head) = chain(Uuid::new_v4(), 5);
+
+        let mut removed = entries.clone();
+        removed.remove(1);
+        assert_eq!(
+            verify_chain(&removed, Some(&head)).first_break,
+            Some(ChainBreak::Missing { sequence: 2 })
+        );
+
+        // Dropping the newest entries is caught by the head
+        let truncated = &entries[..3];
+        assert_eq!(
+            verify_chain(truncated, Some(&head)).first_break,
+            Some(ChainBreak::Missing { sequence: 4 })
+        );
+        assert!(verify_chain(truncated, None).valid);
+    }
+
+    #[test]
+    fn test_chain_verifies_from_purged_base() {
+        let (entries, head) = chain(Uuid::new_v4(), 5);
+        let base = ChainHead {
+            sequence: 2,
+            hash: entries[1].hash.clone().unwrap(),
+        };
+
+        let verification = verify_chain_from(&entries[2..], Some(&base), Some(&head));
+        assert!(verification.valid);
+        assert_eq!(verification.entries_checked, 3);
+
+        // Without the base the purged start looks deleted
+        assert_eq!(
+            verify_chain(&entries[2..], Some(&head)).first_break,
+            Some(ChainBreak::Missing { sequence: 1 })
+        );
+
+        // Everything purged: the base is the head
+        assert!(verify_chain_from(&[], Some(&head), Some(&head)).valid);
+
+        // Deleting past the base is still caught
+        assert_eq!(
+            verify_chain_from(&entries[3..], Some(&base), Some(&head)).first_break,
+            Some(ChainBreak::Missing { sequence: 3 })
+        );
+    }
+
+    #[test]
+    fn test_filter_by_actor() {
+        let (entries, _) = chain(Uuid::new_v4(), 4);
+        let actor = entries[2].user_id;
+
+        let filter = AuditFilter {
+            user_id: actor,
+            ..AuditFilter::default()
+        };
+        let matched: Vec<&AuditEntry> = entries.iter().filter(|entry| filter.matches(entry)).collect();
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].id, entries[2].id);
+
+        // Combined with another field, both must match
+        let filter = AuditFilter {
+            user_id: actor,
+            action: Some(AuditAction::Delete),
+            ..AuditFilter::default()
+        };
+        assert!(!entries.iter().any(|entry| filter.matches(entry)));
+    }
+
+    #[test]
+    fn test_filter_by_date_range() {
+        let (mut entries, _) = chain(Uuid::new_v4(), 5);
+        let start = Utc::now() - chrono::Duration::days(5);
+        for (day, entry) in entries.iter_mut().enumerate() {
+            entry.timestamp = start + chrono::Duration::days(day as i64);
+        }
+
+        // From is inclusive, to is exclusive
+        let filter = AuditFilter {
+            from: Some(entries[1].timestamp),
+            to: Some(entries[3].timestamp),
+            ..AuditFilter::default()
+        };
+        assert!(filter.check().is_ok());
+        let matched: Vec<Option<i64>> = entries
+            .iter()
+            .filter(|entry| filter.matches(entry))
+            .map(|entry| entry.sequence)
+            .collect();
+        assert_eq!(matched, vec![Some(2), Some(3)]);
+
+        let backwards = AuditFilter {
+            from: filter.to,
+            to: filter.from,
+            ..AuditFilter::default()
+        };
+        assert!(backwards.check().is_err());
+    }
+}
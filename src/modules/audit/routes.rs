@@ -0,0 +1,49 @@
+//! Audit log API routes
+
+use axum::{
+    extract::{OriginalUri, Query, State},
+    routing::get,
+    Json, Router,
+};
+use std::sync::Arc;
+
+use super::{AuditEntry, AuditFilter, AuditService, ChainVerification};
+use crate::modules::auth::RequireAdmin;
+use crate::utils::error::AppResult;
+use crate::utils::pagination::{PaginatedJson, PaginationParams};
+
+#[derive(Clone)]
+pub struct AuditRouterState {
+    pub audit_service: Arc<AuditService>,
+}
+
+/// Create the audit log router
+pub fn audit_routes(audit_service: AuditService) -> Router {
+    let state = AuditRouterState {
+        audit_service: Arc::new(audit_service),
+    };
+
+    Router::new()
+        .route("/", get(list_entries))
+        .route("/verify", get(verify_chain))
+        .with_state(state)
+}
+
+async fn list_entries(
+    State(state): State<AuditRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Query(filter): Query<AuditFilter>,
+    Query(pagination): Query<PaginationParams>,
+    OriginalUri(uri): OriginalUri,
+) -> AppResult<PaginatedJson<AuditEntry>> {
+    let entries = state.audit_service.query(user.tenant_id, &filter, &pagination).await?;
+    Ok(entries.with_links(&uri))
+}
+
+async fn verify_chain(
+    State(state): State<AuditRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+) -> AppResult<Json<ChainVerification>> {
+    let verification = state.audit_service.verify_chain(user.tenant_id).await?;
+    Ok(Json(verification))
+}
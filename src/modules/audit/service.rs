@@ -0,0 +1,312 @@
+//! Audit log service implementation
+
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, Row};
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::utils::error::AppResult;
+use crate::utils::pagination::{PaginatedResponse, PaginationParams};
+
+use super::models::*;
+
+const AUDIT_ENTRY_COLUMNS: &str = "id, tenant_id, user_id, action, entity_type, entity_id, old_values, new_values, \
+     ip_address, user_agent, request_id, timestamp, sequence, prev_hash, hash";
+
+/// Writes and checks the tenant audit trail
+#[derive(Clone)]
+pub struct AuditService {
+    db: Database,
+}
+
+impl AuditService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Append an entry to the tenant's chain
+    pub async fn record(&self, tenant_id: Uuid, entry: NewAuditEntry) -> AppResult<AuditEntry> {
+        let mut tx = self.db.pool().begin().await?;
+        let entry = Self::record_in(&mut *tx, tenant_id, entry).await?;
+        tx.commit().await?;
+
+        Ok(entry)
+    }
+
+    /// Append an entry inside the caller's transaction, so it is only kept if
+    /// the change it describes is. Writers to the same tenant are serialized
+    /// until the transaction ends.
+    pub async fn record_in(conn: &mut PgConnection, tenant_id: Uuid, entry: NewAuditEntry) -> AppResult<AuditEntry> {
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext('audit_log:' || $1::text))")
+            .bind(tenant_id)
+            .execute(&mut *conn)
+            .await?;
+
+        let head = Self::chain_head_in(&mut *conn, tenant_id).await?;
+        let entry = entry.into_entry(tenant_id, head.as_ref(), Utc::now());
+
+        sqlx::query(
+            r#"
+            INSERT INTO audit_log (id, tenant_id, user_id, action, entity_type, entity_id, old_values, new_values,
+                                   ip_address, user_agent, request_id, timestamp, sequence, prev_hash, hash)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+            "#,
+        )
+        .bind(entry.id)
+        .bind(entry.tenant_id)
+        .bind(entry.user_id)
+        .bind(entry.action.as_str())
+        .bind(&entry.entity_type)
+        .bind(entry.entity_id)
+        .bind(&entry.old_values)
+        .bind(&entry.new_values)
+        .bind(&entry.ip_address)
+        .bind(&entry.user_agent)
+        .bind(&entry.request_id)
+        .bind(entry.timestamp)
+        .bind(entry.sequence)
+        .bind(&entry.prev_hash)
+        .bind(&entry.hash)
+        .execute(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO audit_chain_heads (tenant_id, sequence, hash)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (tenant_id) DO UPDATE SET sequence = EXCLUDED.sequence, hash = EXCLUDED.hash
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(entry.sequence)
+        .bind(&entry.hash)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(entry)
+    }
+
+    async fn chain_head_in(conn: &mut PgConnection, tenant_id: Uuid) -> AppResult<Option<ChainHead>> {
+        let row = sqlx::query("SELECT sequence, hash FROM audit_chain_heads WHERE tenant_id = $1")
+            .bind(tenant_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+
+        Ok(row.map(|row| ChainHead {
+            sequence: row.get("sequence"),
+            hash: row.get("hash"),
+        }))
+    }
+
+    /// The last entry purged from the start of the chain, if any were
+    async fn chain_base_in(conn: &mut PgConnection, tenant_id: Uuid) -> AppResult<Option<ChainHead>> {
+        let row: Option<(Option<i64>, Option<String>)> =
+            sqlx::query_as("SELECT base_sequence, base_hash FROM audit_chain_heads WHERE tenant_id = $1")
+                .bind(tenant_id)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+        Ok(match row {
+            Some((Some(sequence), Some(hash))) => Some(ChainHead { sequence, hash }),
+            _ => None,
+        })
+    }
+
+    /// Recompute the tenant's chain and report the first entry that was
+    /// altered or removed
+    pub async fn verify_chain(&self, tenant_id: Uuid) -> AppResult<ChainVerification> {
+        let mut conn = self.db.pool().acquire().await?;
+        let head = Self::chain_head_in(&mut conn, tenant_id).await?;
+        let base = Self::chain_base_in(&mut conn, tenant_id).await?;
+
+        let rows = sqlx::query_as::<_, AuditEntryRow>(&format!(
+            "SELECT {} FROM audit_log WHERE tenant_id = $1 AND sequence IS NOT NULL ORDER BY sequence",
+            AUDIT_ENTRY_COLUMNS
+        ))
+        .bind(tenant_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let entries: Vec<AuditEntry> = rows.into_iter().map(Into::into).collect();
+        Ok(verify_chain_from(&entries, base.as_ref(), head.as_ref()))
+    }
+
+    /// Entries written before `cutoff`, the ones [`Self::purge_before_in`]
+    /// removes
+    pub async fn count_before(&self, tenant_id: Uuid, cutoff: DateTime<Utc>) -> AppResult<u64> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM audit_log WHERE tenant_id = $1 AND timestamp < $2")
+            .bind(tenant_id)
+            .bind(cutoff)
+            .fetch_one(self.db.pool())
+            .await?;
+
+        Ok(count.max(0) as u64)
+    }
+
+    /// Delete entries written before `cutoff` inside the caller's
+    /// transaction. Chained entries go from the start of the chain only, and
+    /// the last one removed becomes the chain's base so the rest still
+    /// verifies.
+    pub async fn purge_before_in(conn: &mut PgConnection, tenant_id: Uuid, cutoff: DateTime<Utc>) -> AppResult<u64> {
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext('audit_log:' || $1::text))")
+            .bind(tenant_id)
+            .execute(&mut *conn)
+            .await?;
+
+        let last: Option<(i64, String)> = sqlx::query_as(
+            r#"
+            SELECT sequence, hash FROM audit_log
+            WHERE tenant_id = $1 AND sequence IS NOT NULL AND timestamp < $2
+            ORDER BY sequence DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(cutoff)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        let result = sqlx::query(
+            "DELETE FROM audit_log WHERE tenant_id = $1 AND (sequence <= $2 OR (sequence IS NULL AND timestamp < $3))",
+        )
+        .bind(tenant_id)
+        .bind(last.as_ref().map(|(sequence, _)| *sequence))
+        .bind(cutoff)
+        .execute(&mut *conn)
+        .await?;
+
+        if let Some((sequence, hash)) = last {
+            sqlx::query("UPDATE audit_chain_heads SET base_sequence = $1, base_hash = $2 WHERE tenant_id = $3")
+                .bind(sequence)
+                .bind(&hash)
+                .bind(tenant_id)
+                .execute(&mut *conn)
+                .await?;
+        }
+
+        Ok(result.rows_affected())
+    }
+
+    /// Entries matching `filter`, newest first
+    pub async fn query(
+        &self,
+        tenant_id: Uuid,
+        filter: &AuditFilter,
+        pagination: &PaginationParams,
+    ) -> AppResult<PaginatedResponse<AuditEntry>> {
+        filter.check()?;
+
+        // Only set filters become conditions, so each query can use the
+        // (tenant_id, <column>, timestamp) index for its leading filter
+        let mut conditions = vec!["tenant_id = $1".to_string()];
+        let mut param_idx = 2;
+        for (is_set, condition) in [
+            (filter.user_id.is_some(), "user_id = $"),
+            (filter.entity_type.is_some(), "entity_type = $"),
+            (filter.entity_id.is_some(), "entity_id = $"),
+            (filter.action.is_some(), "action = $"),
+            (filter.from.is_some(), "timestamp >= $"),
+            (filter.to.is_some(), "timestamp < $"),
+        ] {
+            if is_set {
+                conditions.push(format!("{}{}", condition, param_idx));
+                param_idx += 1;
+            }
+        }
+        let where_clause = conditions.join(" AND ");
+
+        let count_query = format!("SELECT COUNT(*) FROM audit_log WHERE {}", where_clause);
+        let query = format!(
+            "SELECT {} FROM audit_log WHERE {} ORDER BY timestamp DESC, sequence DESC NULLS LAST LIMIT ${} OFFSET ${}",
+            AUDIT_ENTRY_COLUMNS,
+            where_clause,
+            param_idx,
+            param_idx + 1
+        );
+
+        let mut q = sqlx::query_as::<_, AuditEntryRow>(&query).bind(tenant_id);
+        let mut cq = sqlx::query_scalar::<_, i64>(&count_query).bind(tenant_id);
+        if let Some(user_id) = filter.user_id {
+            q = q.bind(user_id);
+            cq = cq.bind(user_id);
+        }
+        if let Some(ref entity_type) = filter.entity_type {
+            q = q.bind(entity_type.clone());
+            cq = cq.bind(entity_type.clone());
+        }
+        if let Some(entity_id) = filter.entity_id {
+            q = q.bind(entity_id);
+            cq = cq.bind(entity_id);
+        }
+        if let Some(action) = filter.action {
+            q = q.bind(action.as_str());
+            cq = cq.bind(action.as_str());
+        }
+        if let Some(from) = filter.from {
+            q = q.bind(from);
+            cq = cq.bind(from);
+        }
+        if let Some(to) = filter.to {
+            q = q.bind(to);
+            cq = cq.bind(to);
+        }
+
+        let total = cq.fetch_one(self.db.pool()).await?;
+        let rows = q
+            .bind(pagination.limit() as i64)
+            .bind(pagination.offset() as i64)
+            .fetch_all(self.db.pool())
+            .await?;
+
+        Ok(PaginatedResponse::from_params(
+            rows.into_iter().map(Into::into).collect(),
+            pagination,
+            total as u64,
+        ))
+    }
+}
+
+// ============================================================================
+// DATABASE ROW TYPES
+// ============================================================================
+
+#[derive(sqlx::FromRow)]
+struct AuditEntryRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    user_id: Option<Uuid>,
+    action: String,
+    entity_type: String,
+    entity_id: Option<Uuid>,
+    old_values: Option<serde_json::Value>,
+    new_values: Option<serde_json::Value>,
+    ip_address: Option<String>,
+    user_agent: Option<String>,
+    request_id: Option<String>,
+    timestamp: DateTime<Utc>,
+    sequence: Option<i64>,
+    prev_hash: Option<String>,
+    hash: Option<String>,
+}
+
+impl From<AuditEntryRow> for AuditEntry {
+    fn from(row: AuditEntryRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            user_id: row.user_id,
+            action: AuditAction::from_str(&row.action).unwrap_or_default(),
+            entity_type: row.entity_type,
+            entity_id: row.entity_id,
+            old_values: row.old_values,
+            new_values: row.new_values,
+            ip_address: row.ip_address,
+            user_agent: row.user_agent,
+            request_id: row.request_id,
+            timestamp: row.timestamp,
+            sequence: row.sequence,
+            prev_hash: row.prev_hash,
+            hash: row.hash,
+        }
+    }
+}
@@ -2,7 +2,6 @@
 
 use axum::{
     extract::{Request, State},
-    http::StatusCode,
     middleware::Next,
     response::Response,
 };
@@ -72,7 +71,7 @@ pub async fn auth_middleware(
 pub async fn require_auth(
     request: Request,
     next: Next,
-) -> Result<Response, (StatusCode, String)> {
+) -> Result<Response, AppError> {
     let auth_state = request
         .extensions()
         .get::<AuthState>()
@@ -80,7 +79,7 @@ pub async fn require_auth(
         .unwrap_or_default();
 
     if !auth_state.is_authenticated {
-        return Err((StatusCode::UNAUTHORIZED, "Authentication required".to_string()));
+        return Err(AppError::Unauthorized);
     }
 
     Ok(next.run(request).await)
@@ -178,6 +178,8 @@ pub struct CurrentUser {
     pub last_name: String,
     pub role: UserRole,
     pub timezone: String,
+    /// Language for the UI and the user's email, e.g. `en` or `fr-CA`
+    pub locale: String,
     pub avatar_url: Option<String>,
 }
 
@@ -235,6 +237,7 @@ impl User {
             last_name: self.last_name.clone(),
             role: self.role,
             timezone: self.timezone.clone(),
+            locale: self.locale.clone(),
             avatar_url: self.avatar_url.clone(),
         }
     }
@@ -382,6 +385,63 @@ impl From<User> for UserResponse {
     }
 }
 
+/// A member's role within a team
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TeamRole {
+    Leader,
+    #[default]
+    Member,
+}
+
+impl TeamRole {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "leader" => Some(Self::Leader),
+            "member" => Some(Self::Member),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Leader => "leader",
+            Self::Member => "member",
+        }
+    }
+}
+
+/// A team of users; technicians see its tickets under team visibility
+#[derive(Debug, Clone, Serialize)]
+pub struct Team {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub manager_id: Option<Uuid>,
+    pub color: Option<String>,
+    pub is_active: bool,
+    pub member_count: i64,
+}
+
+/// A user's membership of a team
+#[derive(Debug, Clone, Serialize)]
+pub struct TeamMember {
+    pub team_id: Uuid,
+    pub user_id: Uuid,
+    pub full_name: String,
+    pub email: String,
+    pub role: TeamRole,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Add a user to a team, or change their role on it
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct AddTeamMemberRequest {
+    pub user_id: Uuid,
+    #[serde(default)]
+    pub role: TeamRole,
+}
+
 /// MFA setup request
 #[derive(Debug, Clone, Deserialize)]
 pub struct MfaSetupRequest {
@@ -519,6 +579,7 @@ mod tests {
             last_name: "User".to_string(),
             role: UserRole::Admin,
             timezone: "UTC".to_string(),
+            locale: "en".to_string(),
             avatar_url: None,
         };
         let tenant_id = user.tenant_id;
@@ -539,6 +600,7 @@ mod tests {
             last_name: "User".to_string(),
             role: UserRole::Admin,
             timezone: "UTC".to_string(),
+            locale: "en".to_string(),
             avatar_url: None,
         };
         let tenant_id = user.tenant_id;
@@ -559,6 +621,7 @@ mod tests {
             last_name: "Doe".to_string(),
             role: UserRole::Technician,
             timezone: "America/New_York".to_string(),
+            locale: "en".to_string(),
             avatar_url: None,
         };
 
@@ -575,6 +638,7 @@ mod tests {
             last_name: "Doe".to_string(),
             role: UserRole::Technician,
             timezone: "UTC".to_string(),
+            locale: "en".to_string(),
             avatar_url: None,
         };
 
@@ -594,6 +658,7 @@ mod tests {
             last_name: "User".to_string(),
             role: UserRole::Admin,
             timezone: "UTC".to_string(),
+            locale: "en".to_string(),
             avatar_url: None,
         };
         let tenant_id = user.tenant_id;
@@ -614,6 +679,7 @@ mod tests {
             last_name: "User".to_string(),
             role: UserRole::Admin,
             timezone: "UTC".to_string(),
+            locale: "en".to_string(),
             avatar_url: None,
         };
         let tenant_id = user.tenant_id;
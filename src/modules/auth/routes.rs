@@ -12,13 +12,14 @@ use uuid::Uuid;
 use validator::Validate;
 
 use super::{
-    AuthService, ChangePasswordRequest, CreateUserRequest, ForgotPasswordRequest, LoginRequest,
-    LoginResponse, RefreshTokenRequest, RefreshTokenResponse, ResetPasswordRequest, SessionInfo,
+    AddTeamMemberRequest, AuthService, ChangePasswordRequest, CreateUserRequest, ForgotPasswordRequest, LoginRequest,
+    LoginResponse, RefreshTokenRequest, RefreshTokenResponse, ResetPasswordRequest, SessionInfo, Team, TeamMember,
     UpdateUserRequest, UserResponse,
 };
-use crate::modules::auth::middleware::RequireAuth;
+use crate::modules::auth::middleware::{RequireAdmin, RequireAuth, RequireManager};
 use crate::utils::error::{AppError, AppResult};
 use crate::utils::pagination::{PaginatedResponse, PaginationParams};
+use crate::utils::validation::ValidatedJson;
 
 /// Application state for auth routes
 #[derive(Clone)]
@@ -50,6 +51,11 @@ pub fn auth_routes(auth_service: AuthService) -> Router {
         .route("/users", post(create_user))
         .route("/users/:user_id", get(get_user))
         .route("/users/:user_id", put(update_user))
+        // Teams (managers manage membership)
+        .route("/teams", get(list_teams))
+        .route("/teams/:team_id/members", get(list_team_members))
+        .route("/teams/:team_id/members", post(add_team_member))
+        .route("/teams/:team_id/members/:user_id", delete(remove_team_member))
         .with_state(state)
 }
 
@@ -142,10 +148,8 @@ async fn get_current_user(
 async fn update_current_user(
     State(state): State<AuthRouterState>,
     RequireAuth(user): RequireAuth,
-    Json(request): Json<UpdateUserRequest>,
+    ValidatedJson(request): ValidatedJson<UpdateUserRequest>,
 ) -> AppResult<Json<UserResponse>> {
-    request.validate()?;
-
     // Users can't change their own role or status
     let sanitized_request = UpdateUserRequest {
         role: None,
@@ -240,16 +244,9 @@ async fn list_users(
 /// Create user (admin only)
 async fn create_user(
     State(state): State<AuthRouterState>,
-    RequireAuth(user): RequireAuth,
-    Json(request): Json<CreateUserRequest>,
+    RequireAdmin(user, _): RequireAdmin,
+    ValidatedJson(request): ValidatedJson<CreateUserRequest>,
 ) -> AppResult<Json<UserResponse>> {
-    // Check admin permission
-    if !user.role.is_admin() {
-        return Err(AppError::Forbidden("Insufficient permissions".to_string()));
-    }
-
-    request.validate()?;
-
     let new_user = state
         .auth_service
         .create_user(user.tenant_id, &request)
@@ -277,18 +274,60 @@ async fn get_user(
 /// Update user (admin only)
 async fn update_user(
     State(state): State<AuthRouterState>,
-    RequireAuth(user): RequireAuth,
+    RequireAdmin(_admin, _): RequireAdmin,
     Path(user_id): Path<Uuid>,
-    Json(request): Json<UpdateUserRequest>,
+    ValidatedJson(request): ValidatedJson<UpdateUserRequest>,
 ) -> AppResult<Json<UserResponse>> {
-    // Check admin permission
-    if !user.role.is_admin() {
-        return Err(AppError::Forbidden("Insufficient permissions".to_string()));
-    }
-
-    request.validate()?;
-
     let updated = state.auth_service.update_user(user_id, &request).await?;
 
     Ok(Json(updated.into()))
 }
+
+/// List teams
+async fn list_teams(
+    State(state): State<AuthRouterState>,
+    RequireAuth(user): RequireAuth,
+) -> AppResult<Json<Vec<Team>>> {
+    let teams = state.auth_service.list_teams(user.tenant_id).await?;
+
+    Ok(Json(teams))
+}
+
+/// List a team's members
+async fn list_team_members(
+    State(state): State<AuthRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(team_id): Path<Uuid>,
+) -> AppResult<Json<Vec<TeamMember>>> {
+    let members = state.auth_service.team_members(user.tenant_id, team_id).await?;
+
+    Ok(Json(members))
+}
+
+/// Add a user to a team (manager or admin)
+async fn add_team_member(
+    State(state): State<AuthRouterState>,
+    RequireManager(user, _): RequireManager,
+    Path(team_id): Path<Uuid>,
+    ValidatedJson(request): ValidatedJson<AddTeamMemberRequest>,
+) -> AppResult<Json<TeamMember>> {
+    let member = state
+        .auth_service
+        .add_team_member(user.tenant_id, team_id, &request)
+        .await?;
+
+    Ok(Json(member))
+}
+
+/// Remove a user from a team (manager or admin)
+async fn remove_team_member(
+    State(state): State<AuthRouterState>,
+    RequireManager(user, _): RequireManager,
+    Path((team_id, user_id)): Path<(Uuid, Uuid)>,
+) -> AppResult<()> {
+    state
+        .auth_service
+        .remove_team_member(user.tenant_id, team_id, user_id)
+        .await?;
+    Ok(())
+}
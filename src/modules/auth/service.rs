@@ -12,6 +12,8 @@ use uuid::Uuid;
 #[cfg(feature = "server")]
 use crate::db::Database;
 #[cfg(feature = "server")]
+use crate::modules::notifications::{NotificationService, OutgoingEmail};
+#[cfg(feature = "server")]
 use crate::utils::crypto::{generate_token, hash_password, verify_password};
 #[cfg(feature = "server")]
 use crate::utils::error::{AppError, AppResult};
@@ -27,6 +29,9 @@ pub struct AuthService {
     jwt_secret: String,
     access_token_ttl: Duration,
     refresh_token_ttl: Duration,
+    notifications: NotificationService,
+    /// Public URL reset links point at
+    base_url: String,
 }
 
 #[cfg(feature = "server")]
@@ -34,6 +39,8 @@ impl AuthService {
     /// Create a new auth service
     pub fn new(db: Database, jwt_secret: String) -> Self {
         Self {
+            notifications: NotificationService::new(db.clone()),
+            base_url: std::env::var("BASE_URL").unwrap_or_else(|_| "http://localhost:8080".to_string()),
             db,
             jwt_secret,
             access_token_ttl: Duration::hours(1),
@@ -197,7 +204,24 @@ impl AuthService {
         .execute(self.db.pool())
         .await?;
 
-        // TODO: Send password reset email with token
+        // Transactional, so it reaches users who unsubscribed from everything else
+        let url = format!("{}/reset-password?token={}", self.base_url.trim_end_matches('/'), token);
+        let email = OutgoingEmail {
+            to: user.email.clone(),
+            subject: "Reset your password".to_string(),
+            body_text: format!(
+                "A password reset was requested for your account.\n\nReset your password: {}\n\nThis link expires in 24 hours. If you didn't ask for this, you can ignore this email.",
+                url
+            ),
+            body_html: None,
+            template_id: None,
+            from: None,
+            thread: None,
+            attachments: Vec::new(),
+        };
+        self.notifications
+            .send_transactional_email(user.tenant_id, Some(user.id), &email)
+            .await?;
         tracing::info!("Password reset requested for user {}", user.id);
 
         Ok(())
@@ -648,8 +672,118 @@ impl AuthService {
 
         Ok(())
     }
+
+    /// List the tenant's teams with their member counts
+    pub async fn list_teams(&self, tenant_id: Uuid) -> AppResult<Vec<Team>> {
+        let rows = sqlx::query_as::<_, TeamRow>(
+            r#"
+            SELECT t.id, t.name, t.description, t.manager_id, t.color, t.is_active,
+                   (SELECT COUNT(*) FROM team_members m WHERE m.team_id = t.id) AS member_count
+            FROM teams t
+            WHERE t.tenant_id = $1
+            ORDER BY t.name
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Members of a team, leaders first
+    pub async fn team_members(&self, tenant_id: Uuid, team_id: Uuid) -> AppResult<Vec<TeamMember>> {
+        self.ensure_team(tenant_id, team_id).await?;
+
+        let rows = sqlx::query_as::<_, TeamMemberRow>(&format!(
+            "SELECT {} WHERE m.tenant_id = $1 AND m.team_id = $2 ORDER BY m.role, u.last_name, u.first_name",
+            TEAM_MEMBER_SELECT
+        ))
+        .bind(tenant_id)
+        .bind(team_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Add a user to a team. A user already on it takes the new role.
+    pub async fn add_team_member(
+        &self,
+        tenant_id: Uuid,
+        team_id: Uuid,
+        request: &AddTeamMemberRequest,
+    ) -> AppResult<TeamMember> {
+        self.ensure_team(tenant_id, team_id).await?;
+
+        let added = sqlx::query(
+            r#"
+            INSERT INTO team_members (tenant_id, team_id, user_id, role)
+            SELECT $1, $2, u.id, $4 FROM users u WHERE u.tenant_id = $1 AND u.id = $3
+            ON CONFLICT (team_id, user_id) DO UPDATE SET role = EXCLUDED.role
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(team_id)
+        .bind(request.user_id)
+        .bind(request.role.as_str())
+        .execute(self.db.pool())
+        .await?
+        .rows_affected();
+        if added == 0 {
+            return Err(AppError::NotFound("User".to_string()));
+        }
+
+        let row = sqlx::query_as::<_, TeamMemberRow>(&format!(
+            "SELECT {} WHERE m.tenant_id = $1 AND m.team_id = $2 AND m.user_id = $3",
+            TEAM_MEMBER_SELECT
+        ))
+        .bind(tenant_id)
+        .bind(team_id)
+        .bind(request.user_id)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(row.into())
+    }
+
+    /// Take a user off a team
+    pub async fn remove_team_member(&self, tenant_id: Uuid, team_id: Uuid, user_id: Uuid) -> AppResult<()> {
+        let removed = sqlx::query("DELETE FROM team_members WHERE tenant_id = $1 AND team_id = $2 AND user_id = $3")
+            .bind(tenant_id)
+            .bind(team_id)
+            .bind(user_id)
+            .execute(self.db.pool())
+            .await?
+            .rows_affected();
+        if removed == 0 {
+            return Err(AppError::NotFound("Team member".to_string()));
+        }
+
+        Ok(())
+    }
+
+    async fn ensure_team(&self, tenant_id: Uuid, team_id: Uuid) -> AppResult<()> {
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM teams WHERE tenant_id = $1 AND id = $2)")
+            .bind(tenant_id)
+            .bind(team_id)
+            .fetch_one(self.db.pool())
+            .await?;
+        if !exists {
+            return Err(AppError::NotFound("Team".to_string()));
+        }
+
+        Ok(())
+    }
 }
 
+#[cfg(feature = "server")]
+const TEAM_MEMBER_SELECT: &str = r#"
+    m.team_id, m.user_id, u.first_name || ' ' || u.last_name AS full_name, u.email, m.role, m.created_at
+    FROM team_members m
+    JOIN users u ON u.id = m.user_id
+"#;
+
 // Database row types for sqlx
 #[cfg(feature = "server")]
 #[derive(sqlx::FromRow)]
@@ -717,3 +851,55 @@ struct SessionRow {
     last_activity_at: chrono::DateTime<Utc>,
     created_at: chrono::DateTime<Utc>,
 }
+
+#[cfg(feature = "server")]
+#[derive(sqlx::FromRow)]
+struct TeamRow {
+    id: Uuid,
+    name: String,
+    description: Option<String>,
+    manager_id: Option<Uuid>,
+    color: Option<String>,
+    is_active: bool,
+    member_count: i64,
+}
+
+#[cfg(feature = "server")]
+impl From<TeamRow> for Team {
+    fn from(row: TeamRow) -> Self {
+        Self {
+            id: row.id,
+            name: row.name,
+            description: row.description,
+            manager_id: row.manager_id,
+            color: row.color,
+            is_active: row.is_active,
+            member_count: row.member_count,
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+#[derive(sqlx::FromRow)]
+struct TeamMemberRow {
+    team_id: Uuid,
+    user_id: Uuid,
+    full_name: String,
+    email: String,
+    role: String,
+    created_at: chrono::DateTime<Utc>,
+}
+
+#[cfg(feature = "server")]
+impl From<TeamMemberRow> for TeamMember {
+    fn from(row: TeamMemberRow) -> Self {
+        Self {
+            team_id: row.team_id,
+            user_id: row.user_id,
+            full_name: row.full_name,
+            email: row.email,
+            role: TeamRole::from_str(&row.role).unwrap_or_default(),
+            created_at: row.created_at,
+        }
+    }
+}
@@ -1 +1,20 @@
-//! billing module placeholder
+//! Billing Module
+//!
+//! Invoices, including fixed-fee project billing by milestone or progress,
+//! and quotes that customers accept or decline before they are invoiced.
+
+mod models;
+#[cfg(feature = "server")]
+mod service;
+#[cfg(feature = "server")]
+mod quotes;
+#[cfg(feature = "server")]
+mod routes;
+
+pub use models::*;
+#[cfg(feature = "server")]
+pub use service::BillingService;
+#[cfg(feature = "server")]
+pub use quotes::QuoteService;
+#[cfg(feature = "server")]
+pub use routes::{billing_routes, quote_response_routes, quote_routes};
@@ -0,0 +1,1499 @@
+//! Billing models and types
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::modules::notifications::{EmailAttachment, NotificationStatus, OutgoingEmail};
+use crate::modules::tenants::ResolvedBranding;
+use crate::utils::error::AppError;
+use crate::utils::pagination::ViewItem;
+use crate::utils::pdf::{render_text_pdf, PdfLine};
+
+// ============================================================================
+// INVOICES
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum InvoiceStatus {
+    #[default]
+    Draft,
+    Pending,
+    Sent,
+    Paid,
+    PartiallyPaid,
+    Void,
+    WrittenOff,
+}
+
+impl InvoiceStatus {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "draft" => Some(Self::Draft),
+            "pending" => Some(Self::Pending),
+            "sent" => Some(Self::Sent),
+            "paid" => Some(Self::Paid),
+            "partially_paid" => Some(Self::PartiallyPaid),
+            "void" => Some(Self::Void),
+            "written_off" => Some(Self::WrittenOff),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Draft => "draft",
+            Self::Pending => "pending",
+            Self::Sent => "sent",
+            Self::Paid => "paid",
+            Self::PartiallyPaid => "partially_paid",
+            Self::Void => "void",
+            Self::WrittenOff => "written_off",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Invoice {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub invoice_number: String,
+    pub company_id: Uuid,
+    pub status: InvoiceStatus,
+    pub invoice_date: NaiveDate,
+    pub due_date: NaiveDate,
+    pub payment_terms: Option<String>,
+    pub subtotal: Decimal,
+    pub tax_amount: Decimal,
+    pub total: Decimal,
+    pub amount_paid: Decimal,
+    pub balance_due: Decimal,
+    pub currency: String,
+    pub notes: Option<String>,
+    pub lines: Vec<InvoiceLine>,
+    /// When the invoice was last emailed to the customer
+    pub sent_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvoiceLine {
+    pub id: Uuid,
+    pub line_type: String,
+    pub description: String,
+    pub quantity: Decimal,
+    pub unit_price: Decimal,
+    pub total: Decimal,
+    pub project_id: Option<Uuid>,
+    pub milestone_id: Option<Uuid>,
+}
+
+/// Compact invoice for `?view=summary` lists
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvoiceSummary {
+    pub id: Uuid,
+    pub invoice_number: String,
+    pub status: InvoiceStatus,
+    pub total: Decimal,
+    pub due_date: NaiveDate,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Invoice> for InvoiceSummary {
+    fn from(invoice: Invoice) -> Self {
+        Self {
+            id: invoice.id,
+            invoice_number: invoice.invoice_number,
+            status: invoice.status,
+            total: invoice.total,
+            due_date: invoice.due_date,
+            updated_at: invoice.updated_at,
+        }
+    }
+}
+
+/// Invoice list entry in the requested view
+pub type InvoiceListItem = ViewItem<Invoice, InvoiceSummary>;
+
+/// Tenant branding printed at the top of an invoice PDF
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InvoiceDocumentHeader {
+    pub company_name: String,
+    pub logo_url: Option<String>,
+    /// Hex color for the header band and table rules
+    pub accent_color: String,
+    pub support_email: Option<String>,
+    pub support_phone: Option<String>,
+}
+
+/// Everything the PDF renderer lays out for one invoice
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvoiceDocument {
+    pub header: InvoiceDocumentHeader,
+    pub invoice: Invoice,
+    pub footer: Option<String>,
+}
+
+impl InvoiceDocumentHeader {
+    pub fn new(branding: &ResolvedBranding) -> Self {
+        Self {
+            company_name: branding.company_name.clone(),
+            logo_url: branding.logo_url.clone(),
+            accent_color: branding.primary_color.clone(),
+            support_email: branding.support_email.clone(),
+            support_phone: branding.support_phone.clone(),
+        }
+    }
+}
+
+impl InvoiceDocument {
+    pub fn new(invoice: Invoice, branding: &ResolvedBranding) -> Self {
+        Self {
+            header: InvoiceDocumentHeader::new(branding),
+            invoice,
+            footer: branding.email_footer.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct InvoiceFilter {
+    pub company_id: Option<Uuid>,
+    pub status: Option<InvoiceStatus>,
+}
+
+/// Days until an invoice is due under payment terms like `net30`; anything
+/// unrecognised is treated as net 30
+pub fn payment_terms_days(terms: Option<&str>) -> i64 {
+    match terms.map(|t| t.trim().to_ascii_lowercase()) {
+        Some(t) if t == "due_on_receipt" => 0,
+        Some(t) => t
+            .strip_prefix("net")
+            .and_then(|days| days.trim_start_matches(['_', ' ']).parse().ok())
+            .unwrap_or(30),
+        None => 30,
+    }
+}
+
+/// A line for a new invoice
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewInvoiceLine {
+    pub line_type: String,
+    pub description: String,
+    pub quantity: Decimal,
+    pub unit_price: Decimal,
+    pub total: Decimal,
+    pub project_id: Option<Uuid>,
+    pub milestone_id: Option<Uuid>,
+}
+
+impl NewInvoiceLine {
+    /// A single fixed-amount service line for a project
+    pub fn project_service(description: String, amount: Decimal, project_id: Uuid, milestone_id: Option<Uuid>) -> Self {
+        Self {
+            line_type: "service".to_string(),
+            description,
+            quantity: Decimal::ONE,
+            unit_price: amount,
+            total: amount,
+            project_id: Some(project_id),
+            milestone_id,
+        }
+    }
+}
+
+// ============================================================================
+// INVOICE DELIVERY
+// ============================================================================
+
+/// Email a batch of invoices to their companies' billing contacts
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct SendInvoicesRequest {
+    #[validate(length(min = 1, max = 200))]
+    pub invoice_ids: Vec<Uuid>,
+    /// Send again even if an invoice has gone out before
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// What happened to one invoice in a bulk send
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InvoiceSendOutcome {
+    Sent,
+    /// Sent before; pass `force` to send again
+    AlreadySent,
+    /// Void and written-off invoices are never sent
+    NotSendable,
+    /// Neither the invoice nor its company has a billing contact with an email
+    NoRecipient,
+    /// The billing contact's address is on the suppression list
+    Suppressed,
+    Failed,
+    NotFound,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvoiceSendResult {
+    pub invoice_id: Uuid,
+    pub outcome: InvoiceSendOutcome,
+    pub recipient: Option<String>,
+    pub notification_id: Option<Uuid>,
+    pub error: Option<String>,
+}
+
+impl InvoiceSendResult {
+    pub fn skipped(invoice_id: Uuid, outcome: InvoiceSendOutcome) -> Self {
+        Self {
+            invoice_id,
+            outcome,
+            recipient: None,
+            notification_id: None,
+            error: None,
+        }
+    }
+}
+
+/// One email of an invoice and how its delivery went
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvoiceDelivery {
+    pub id: Uuid,
+    pub invoice_id: Uuid,
+    pub recipient: String,
+    pub notification_id: Option<Uuid>,
+    pub status: NotificationStatus,
+    pub error_message: Option<String>,
+    /// Set once the provider reports the message bounced
+    pub bounced_at: Option<DateTime<Utc>>,
+    pub sent_by_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Invoice {
+    /// Why the invoice is left out of a send, or `None` to send it
+    pub fn send_skip(&self, force: bool) -> Option<InvoiceSendOutcome> {
+        match self.status {
+            InvoiceStatus::Void | InvoiceStatus::WrittenOff => Some(InvoiceSendOutcome::NotSendable),
+            _ if !force && (self.sent_at.is_some() || self.status == InvoiceStatus::Sent) => {
+                Some(InvoiceSendOutcome::AlreadySent)
+            }
+            _ => None,
+        }
+    }
+
+    /// Record a delivery. A draft or pending invoice becomes sent; one
+    /// already part paid keeps its status.
+    pub fn mark_sent(&mut self, at: DateTime<Utc>) {
+        if matches!(self.status, InvoiceStatus::Draft | InvoiceStatus::Pending) {
+            self.status = InvoiceStatus::Sent;
+        }
+        self.sent_at = Some(at);
+    }
+}
+
+impl InvoiceDocument {
+    pub fn file_name(&self) -> String {
+        format!("{}.pdf", self.invoice.invoice_number)
+    }
+
+    /// The invoice as a plain PDF for emailing
+    pub fn to_pdf(&self) -> Vec<u8> {
+        let invoice = &self.invoice;
+        let money = |amount: Decimal| format!("{} {:.2}", invoice.currency, amount);
+
+        let mut lines = vec![
+            PdfLine::heading(self.header.company_name.clone()),
+            PdfLine::blank(),
+            PdfLine::heading(format!("Invoice {}", invoice.invoice_number)),
+            PdfLine::text(format!("Invoice date: {}", invoice.invoice_date.format("%Y-%m-%d"))),
+            PdfLine::text(format!("Due date: {}", invoice.due_date.format("%Y-%m-%d"))),
+        ];
+        if let Some(ref terms) = invoice.payment_terms {
+            lines.push(PdfLine::text(format!("Terms: {}", terms)));
+        }
+        lines.push(PdfLine::blank());
+        for line in &invoice.lines {
+            lines.push(PdfLine::text(format!(
+                "{}    {} x {} = {}",
+                line.description,
+                line.quantity.normalize(),
+                money(line.unit_price),
+                money(line.total)
+            )));
+        }
+        lines.push(PdfLine::blank());
+        lines.push(PdfLine::text(format!("Subtotal: {}", money(invoice.subtotal))));
+        lines.push(PdfLine::text(format!("Tax: {}", money(invoice.tax_amount))));
+        lines.push(PdfLine::text(format!("Total: {}", money(invoice.total))));
+        if invoice.amount_paid > Decimal::ZERO {
+            lines.push(PdfLine::text(format!("Paid: {}", money(invoice.amount_paid))));
+        }
+        lines.push(PdfLine::text(format!("Balance due: {}", money(invoice.balance_due))));
+        if let Some(ref notes) = invoice.notes {
+            lines.push(PdfLine::blank());
+            lines.extend(notes.lines().map(PdfLine::text));
+        }
+
+        let contact: Vec<&str> = [&self.header.support_email, &self.header.support_phone]
+            .into_iter()
+            .filter_map(|value| value.as_deref())
+            .collect();
+        if !contact.is_empty() || self.footer.is_some() {
+            lines.push(PdfLine::blank());
+        }
+        if !contact.is_empty() {
+            lines.push(PdfLine::text(format!("Questions? {}", contact.join(" / "))));
+        }
+        if let Some(ref footer) = self.footer {
+            lines.extend(footer.lines().map(PdfLine::text));
+        }
+
+        render_text_pdf(&invoice.invoice_number, &lines)
+    }
+
+    /// The email carrying the invoice PDF to `to`
+    pub fn email(&self, to: &str) -> OutgoingEmail {
+        let invoice = &self.invoice;
+        let mut body_text = format!(
+            "Please find attached invoice {} from {} for {} {:.2}, due {}.",
+            invoice.invoice_number,
+            self.header.company_name,
+            invoice.currency,
+            invoice.balance_due,
+            invoice.due_date.format("%Y-%m-%d")
+        );
+        if let Some(ref footer) = self.footer {
+            body_text.push_str(&format!("\n\n{}", footer));
+        }
+
+        OutgoingEmail {
+            to: to.to_string(),
+            subject: format!("Invoice {} from {}", invoice.invoice_number, self.header.company_name),
+            body_text,
+            body_html: None,
+            template_id: None,
+            from: None,
+            thread: None,
+            attachments: vec![EmailAttachment {
+                file_name: self.file_name(),
+                content_type: "application/pdf".to_string(),
+                content: self.to_pdf(),
+            }],
+        }
+    }
+}
+
+// ============================================================================
+// PAYMENTS
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentMethod {
+    Check,
+    CreditCard,
+    Ach,
+    Wire,
+    Cash,
+    Other,
+}
+
+impl PaymentMethod {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "check" => Some(Self::Check),
+            "credit_card" => Some(Self::CreditCard),
+            "ach" => Some(Self::Ach),
+            "wire" => Some(Self::Wire),
+            "cash" => Some(Self::Cash),
+            "other" => Some(Self::Other),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Check => "check",
+            Self::CreditCard => "credit_card",
+            Self::Ach => "ach",
+            Self::Wire => "wire",
+            Self::Cash => "cash",
+            Self::Other => "other",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Check => "Check",
+            Self::CreditCard => "Credit card",
+            Self::Ach => "ACH",
+            Self::Wire => "Wire transfer",
+            Self::Cash => "Cash",
+            Self::Other => "Other",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Payment {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub invoice_id: Option<Uuid>,
+    pub company_id: Uuid,
+    pub payment_date: NaiveDate,
+    pub amount: Decimal,
+    pub payment_method: PaymentMethod,
+    pub reference_number: Option<String>,
+    pub gateway_transaction_id: Option<String>,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Record a payment against an invoice
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct RecordPaymentRequest {
+    pub amount: Decimal,
+    pub payment_method: PaymentMethod,
+    /// Defaults to today in the tenant's time zone
+    pub payment_date: Option<NaiveDate>,
+    #[validate(length(max = 100))]
+    pub reference_number: Option<String>,
+    #[validate(length(max = 255))]
+    pub gateway_transaction_id: Option<String>,
+    #[validate(length(max = 2000))]
+    pub notes: Option<String>,
+}
+
+impl RecordPaymentRequest {
+    /// Apply the payment to the invoice. It must be open and the amount
+    /// positive and no more than the balance due; paying the balance in
+    /// full marks the invoice paid.
+    pub fn apply(&self, invoice: &mut Invoice) -> Result<(), AppError> {
+        if matches!(invoice.status, InvoiceStatus::Void | InvoiceStatus::WrittenOff | InvoiceStatus::Paid) {
+            return Err(AppError::Conflict(format!(
+                "Invoice {} is {} and can't take payments",
+                invoice.invoice_number,
+                invoice.status.as_str().replace('_', " ")
+            )));
+        }
+        if self.amount <= Decimal::ZERO {
+            return Err(AppError::validation_field("amount", "Payment amount must be positive"));
+        }
+        if self.amount > invoice.balance_due {
+            return Err(AppError::validation_field(
+                "amount",
+                format!("Payment exceeds the balance due of {} {:.2}", invoice.currency, invoice.balance_due),
+            ));
+        }
+
+        invoice.amount_paid += self.amount;
+        invoice.balance_due -= self.amount;
+        invoice.status = if invoice.balance_due.is_zero() {
+            InvoiceStatus::Paid
+        } else {
+            InvoiceStatus::PartiallyPaid
+        };
+        Ok(())
+    }
+}
+
+/// A payment, the invoice it paid and how emailing the receipt went
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordedPayment {
+    pub payment: Payment,
+    pub invoice: Invoice,
+    pub receipt: InvoiceSendResult,
+}
+
+/// Everything the PDF renderer lays out for one payment receipt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentReceipt {
+    pub header: InvoiceDocumentHeader,
+    pub payment: Payment,
+    pub invoice_number: String,
+    pub currency: String,
+    /// Left on the invoice after this payment
+    pub balance_due: Decimal,
+    pub footer: Option<String>,
+}
+
+impl PaymentReceipt {
+    pub fn new(payment: Payment, invoice: &Invoice, branding: &ResolvedBranding) -> Self {
+        Self {
+            header: InvoiceDocumentHeader::new(branding),
+            payment,
+            invoice_number: invoice.invoice_number.clone(),
+            currency: invoice.currency.clone(),
+            balance_due: invoice.balance_due,
+            footer: branding.email_footer.clone(),
+        }
+    }
+
+    pub fn file_name(&self) -> String {
+        format!("Receipt-{}-{}.pdf", self.invoice_number, self.payment.payment_date.format("%Y%m%d"))
+    }
+
+    /// The receipt as a plain PDF for emailing
+    pub fn to_pdf(&self) -> Vec<u8> {
+        let payment = &self.payment;
+        let money = |amount: Decimal| format!("{} {:.2}", self.currency, amount);
+
+        let mut lines = vec![
+            PdfLine::heading(self.header.company_name.clone()),
+            PdfLine::blank(),
+            PdfLine::heading("Payment Receipt"),
+            PdfLine::text(format!("Invoice: {}", self.invoice_number)),
+            PdfLine::text(format!("Payment date: {}", payment.payment_date.format("%Y-%m-%d"))),
+            PdfLine::text(format!("Method: {}", payment.payment_method.label())),
+        ];
+        if let Some(ref reference) = payment.reference_number {
+            lines.push(PdfLine::text(format!("Reference: {}", reference)));
+        }
+        lines.push(PdfLine::blank());
+        lines.push(PdfLine::text(format!("Amount paid: {}", money(payment.amount))));
+        lines.push(PdfLine::text(format!("Balance due: {}", money(self.balance_due))));
+
+        let contact: Vec<&str> = [&self.header.support_email, &self.header.support_phone]
+            .into_iter()
+            .filter_map(|value| value.as_deref())
+            .collect();
+        if !contact.is_empty() || self.footer.is_some() {
+            lines.push(PdfLine::blank());
+        }
+        if !contact.is_empty() {
+            lines.push(PdfLine::text(format!("Questions? {}", contact.join(" / "))));
+        }
+        if let Some(ref footer) = self.footer {
+            lines.extend(footer.lines().map(PdfLine::text));
+        }
+
+        render_text_pdf(&format!("Receipt {}", self.invoice_number), &lines)
+    }
+
+    /// The email carrying the receipt PDF to `to`
+    pub fn email(&self, to: &str) -> OutgoingEmail {
+        let mut body_text = format!(
+            "Thank you for your payment of {} {:.2} toward invoice {}. Your receipt is attached.",
+            self.currency, self.payment.amount, self.invoice_number
+        );
+        if let Some(ref footer) = self.footer {
+            body_text.push_str(&format!("\n\n{}", footer));
+        }
+
+        OutgoingEmail {
+            to: to.to_string(),
+            subject: format!("Payment receipt for invoice {} from {}", self.invoice_number, self.header.company_name),
+            body_text,
+            body_html: None,
+            template_id: None,
+            from: None,
+            thread: None,
+            attachments: vec![EmailAttachment {
+                file_name: self.file_name(),
+                content_type: "application/pdf".to_string(),
+                content: self.to_pdf(),
+            }],
+        }
+    }
+}
+
+// ============================================================================
+// QUOTES
+// ============================================================================
+
+/// How long a quote is valid when no date is given
+pub const QUOTE_VALIDITY_DAYS: i64 = 30;
+
+/// Job that expires sent quotes past their validity date
+pub const EXPIRE_QUOTES_JOB: &str = "billing.expire_quotes";
+
+/// Line types a quote can carry; the same as invoice lines less tax
+const QUOTE_LINE_TYPES: &[&str] = &["service", "product", "adjustment", "discount"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum QuoteStatus {
+    #[default]
+    Draft,
+    Sent,
+    Accepted,
+    Declined,
+    /// Sent but not answered before `valid_until`
+    Expired,
+}
+
+impl QuoteStatus {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "draft" => Some(Self::Draft),
+            "sent" => Some(Self::Sent),
+            "accepted" => Some(Self::Accepted),
+            "declined" => Some(Self::Declined),
+            "expired" => Some(Self::Expired),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Draft => "draft",
+            Self::Sent => "sent",
+            Self::Accepted => "accepted",
+            Self::Declined => "declined",
+            Self::Expired => "expired",
+        }
+    }
+}
+
+/// A priced proposal to a company, accepted or declined by the customer
+/// through a link and invoiced once accepted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Quote {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub quote_number: String,
+    pub company_id: Uuid,
+    pub title: String,
+    pub status: QuoteStatus,
+    pub quote_date: NaiveDate,
+    /// Last day the quote can be accepted
+    pub valid_until: NaiveDate,
+    pub subtotal: Decimal,
+    pub total: Decimal,
+    pub currency: String,
+    pub notes: Option<String>,
+    pub lines: Vec<QuoteLine>,
+    /// Customer response link token, set when the quote is sent
+    #[serde(skip_serializing, default)]
+    pub token: Option<String>,
+    pub sent_at: Option<DateTime<Utc>>,
+    pub responded_at: Option<DateTime<Utc>>,
+    pub response_comment: Option<String>,
+    /// Invoice the accepted quote was converted to
+    pub invoice_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuoteLine {
+    pub id: Uuid,
+    pub line_type: String,
+    pub description: String,
+    pub quantity: Decimal,
+    pub unit_price: Decimal,
+    pub total: Decimal,
+}
+
+impl Quote {
+    /// Whether a sent quote has gone unanswered past its validity date
+    pub fn is_past_validity(&self, today: NaiveDate) -> bool {
+        self.status == QuoteStatus::Sent && today > self.valid_until
+    }
+
+    /// Move a sent quote past its validity date to expired; returns whether
+    /// it changed
+    pub fn expire_if_due(&mut self, today: NaiveDate) -> bool {
+        let due = self.is_past_validity(today);
+        if due {
+            self.status = QuoteStatus::Expired;
+        }
+        due
+    }
+
+    /// Lines and totals can only change before the quote is sent
+    pub fn ensure_editable(&self) -> Result<(), AppError> {
+        if self.status != QuoteStatus::Draft {
+            return Err(AppError::Conflict(format!(
+                "Quote {} is {} and can no longer be edited",
+                self.quote_number,
+                self.status.as_str()
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn ensure_sendable(&self, today: NaiveDate) -> Result<(), AppError> {
+        if !matches!(self.status, QuoteStatus::Draft | QuoteStatus::Sent) {
+            return Err(AppError::Conflict(format!(
+                "Quote {} is {} and can't be sent",
+                self.quote_number,
+                self.status.as_str()
+            )));
+        }
+        if self.lines.is_empty() {
+            return Err(AppError::BadRequest(format!("Quote {} has no lines", self.quote_number)));
+        }
+        if today > self.valid_until {
+            return Err(AppError::validation_field("valid_until", "Must not be in the past"));
+        }
+        Ok(())
+    }
+
+    /// Record the customer's answer. Only a sent quote still within its
+    /// validity date can be answered, and only once.
+    pub fn respond(
+        &mut self,
+        decision: QuoteDecision,
+        comment: Option<String>,
+        now: DateTime<Utc>,
+        today: NaiveDate,
+    ) -> Result<(), AppError> {
+        if self.expire_if_due(today) {
+            return Err(AppError::BadRequest("This quote has expired".to_string()));
+        }
+        match self.status {
+            QuoteStatus::Sent => {}
+            QuoteStatus::Accepted | QuoteStatus::Declined => {
+                return Err(AppError::Conflict("Quote has already been answered".to_string()));
+            }
+            QuoteStatus::Expired => return Err(AppError::BadRequest("This quote has expired".to_string())),
+            QuoteStatus::Draft => return Err(AppError::not_found("Quote")),
+        }
+
+        self.status = match decision {
+            QuoteDecision::Accept => QuoteStatus::Accepted,
+            QuoteDecision::Decline => QuoteStatus::Declined,
+        };
+        self.responded_at = Some(now);
+        self.response_comment = comment;
+        Ok(())
+    }
+
+    /// Only accepted quotes are invoiced, and only once
+    pub fn ensure_convertible(&self) -> Result<(), AppError> {
+        if self.status != QuoteStatus::Accepted {
+            return Err(AppError::BadRequest(format!(
+                "Quote {} is {}; only accepted quotes can be invoiced",
+                self.quote_number,
+                self.status.as_str()
+            )));
+        }
+        if self.invoice_id.is_some() {
+            return Err(AppError::Conflict(format!("Quote {} has already been invoiced", self.quote_number)));
+        }
+        Ok(())
+    }
+
+    /// The quote's lines as invoice lines, unchanged
+    pub fn invoice_lines(&self) -> Vec<NewInvoiceLine> {
+        self.lines
+            .iter()
+            .map(|line| NewInvoiceLine {
+                line_type: line.line_type.clone(),
+                description: line.description.clone(),
+                quantity: line.quantity,
+                unit_price: line.unit_price,
+                total: line.total,
+                project_id: None,
+                milestone_id: None,
+            })
+            .collect()
+    }
+}
+
+/// A quote line as entered
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct QuoteLineRequest {
+    #[serde(default = "default_line_type")]
+    pub line_type: String,
+    #[validate(length(min = 1, max = 2000))]
+    pub description: String,
+    pub quantity: Decimal,
+    pub unit_price: Decimal,
+}
+
+fn default_line_type() -> String {
+    "service".to_string()
+}
+
+impl QuoteLineRequest {
+    pub fn check(&self) -> Result<(), AppError> {
+        if !QUOTE_LINE_TYPES.contains(&self.line_type.as_str()) {
+            return Err(AppError::validation_field("line_type", format!("Unknown line type '{}'", self.line_type)));
+        }
+        if self.quantity <= Decimal::ZERO {
+            return Err(AppError::validation_field("quantity", "Must be positive"));
+        }
+        if self.unit_price < Decimal::ZERO && self.line_type != "discount" {
+            return Err(AppError::validation_field("unit_price", "Must not be negative"));
+        }
+        Ok(())
+    }
+
+    /// Line total; discounts always reduce the quote
+    pub fn total(&self) -> Decimal {
+        let total = (self.quantity * self.unit_price).round_dp(2);
+        if self.line_type == "discount" {
+            -total.abs()
+        } else {
+            total
+        }
+    }
+}
+
+/// Sum of line totals
+pub fn quote_total(lines: &[QuoteLineRequest]) -> Decimal {
+    lines.iter().map(QuoteLineRequest::total).sum()
+}
+
+fn check_quote_lines(lines: &[QuoteLineRequest]) -> Result<(), AppError> {
+    lines.iter().try_for_each(QuoteLineRequest::check)?;
+    if quote_total(lines) < Decimal::ZERO {
+        return Err(AppError::validation_field("lines", "Discounts exceed the quote total"));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CreateQuoteRequest {
+    pub company_id: Uuid,
+    #[validate(length(min = 1, max = 255))]
+    pub title: String,
+    /// Defaults to `QUOTE_VALIDITY_DAYS` from today
+    pub valid_until: Option<NaiveDate>,
+    pub notes: Option<String>,
+    #[validate(length(max = 200), nested)]
+    #[serde(default)]
+    pub lines: Vec<QuoteLineRequest>,
+}
+
+impl CreateQuoteRequest {
+    pub fn check(&self) -> Result<(), AppError> {
+        check_quote_lines(&self.lines)
+    }
+}
+
+/// Update a draft quote; `lines`, when given, replaces every line
+#[derive(Debug, Clone, Deserialize, Validate, Default)]
+pub struct UpdateQuoteRequest {
+    #[validate(length(min = 1, max = 255))]
+    pub title: Option<String>,
+    pub valid_until: Option<NaiveDate>,
+    pub notes: Option<String>,
+    #[validate(length(max = 200), nested)]
+    pub lines: Option<Vec<QuoteLineRequest>>,
+}
+
+impl UpdateQuoteRequest {
+    pub fn check(&self) -> Result<(), AppError> {
+        self.lines.as_deref().map_or(Ok(()), check_quote_lines)
+    }
+}
+
+/// The customer's answer to a quote
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuoteDecision {
+    Accept,
+    Decline,
+}
+
+/// Public quote response
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct QuoteResponseRequest {
+    pub decision: QuoteDecision,
+    #[validate(length(max = 2000))]
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct QuoteFilter {
+    pub company_id: Option<Uuid>,
+    pub status: Option<QuoteStatus>,
+}
+
+/// A quote that was just sent, with the link to pass on to the customer
+#[derive(Debug, Clone, Serialize)]
+pub struct SentQuote {
+    #[serde(flatten)]
+    pub quote: Quote,
+    pub response_url: String,
+}
+
+/// Everything the PDF renderer lays out for one quote, in the invoice layout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuoteDocument {
+    pub header: InvoiceDocumentHeader,
+    pub quote: Quote,
+    pub footer: Option<String>,
+}
+
+impl QuoteDocument {
+    pub fn new(quote: Quote, branding: &ResolvedBranding) -> Self {
+        Self {
+            header: InvoiceDocumentHeader::new(branding),
+            quote,
+            footer: branding.email_footer.clone(),
+        }
+    }
+}
+
+// ============================================================================
+// PROJECT BILLING
+// ============================================================================
+
+/// How a fixed-price project is invoiced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FixedFeeBilling {
+    /// Each milestone's agreed amount once it is completed
+    #[default]
+    Milestone,
+    /// The share of the fixed fee earned by the project's completion
+    Progress,
+}
+
+impl FixedFeeBilling {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "milestone" => Some(Self::Milestone),
+            "progress" => Some(Self::Progress),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Milestone => "milestone",
+            Self::Progress => "progress",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MilestoneStatus {
+    #[default]
+    Pending,
+    Completed,
+}
+
+impl MilestoneStatus {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "pending" => Some(Self::Pending),
+            "completed" => Some(Self::Completed),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Completed => "completed",
+        }
+    }
+}
+
+/// A billable deliverable of a fixed-price project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectMilestone {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub project_id: Uuid,
+    pub name: String,
+    pub amount: Decimal,
+    pub status: MilestoneStatus,
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Invoice the milestone was billed on; set at most once
+    pub invoice_id: Option<Uuid>,
+    pub invoiced_at: Option<DateTime<Utc>>,
+}
+
+impl ProjectMilestone {
+    /// Check the milestone can be billed now: it must be completed and not
+    /// already on an invoice
+    pub fn check_invoiceable(&self) -> Result<(), AppError> {
+        if self.invoice_id.is_some() {
+            return Err(AppError::Conflict(format!(
+                "Milestone '{}' has already been invoiced",
+                self.name
+            )));
+        }
+        if self.status != MilestoneStatus::Completed {
+            return Err(AppError::BadRequest(format!(
+                "Milestone '{}' is not completed",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn line_description(&self, project_name: &str) -> String {
+        format!("{}: {}", project_name, self.name)
+    }
+}
+
+/// The billing side of a fixed-price project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectBilling {
+    pub project_id: Uuid,
+    pub name: String,
+    pub company_id: Option<Uuid>,
+    pub billing_method: String,
+    pub fixed_fee: Option<Decimal>,
+    pub fixed_fee_billing: FixedFeeBilling,
+    /// 0 to 100
+    pub percent_complete: Decimal,
+    /// Completion already covered by earlier progress invoices
+    pub progress_billed_percent: Decimal,
+}
+
+/// What the next progress invoice covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgressInvoice {
+    /// Percentage points of completion being billed
+    pub percent: Decimal,
+    pub amount: Decimal,
+    /// `progress_billed_percent` once this invoice is issued
+    pub billed_through: Decimal,
+}
+
+impl ProjectBilling {
+    fn check_fixed_fee(&self, mode: FixedFeeBilling) -> Result<Decimal, AppError> {
+        if self.billing_method != "fixed_price" {
+            return Err(AppError::BadRequest(format!("Project '{}' is not fixed-price", self.name)));
+        }
+        if self.fixed_fee_billing != mode {
+            return Err(AppError::BadRequest(format!(
+                "Project '{}' is billed by {}",
+                self.name,
+                self.fixed_fee_billing.as_str()
+            )));
+        }
+        self.fixed_fee
+            .ok_or_else(|| AppError::BadRequest(format!("Project '{}' has no fixed fee", self.name)))
+    }
+
+    /// Check the project is billed per milestone
+    pub fn check_milestone_billing(&self) -> Result<(), AppError> {
+        self.check_fixed_fee(FixedFeeBilling::Milestone).map(|_| ())
+    }
+
+    /// The fee earned since the last progress invoice
+    pub fn next_progress_invoice(&self) -> Result<ProgressInvoice, AppError> {
+        let fixed_fee = self.check_fixed_fee(FixedFeeBilling::Progress)?;
+
+        let complete = self.percent_complete.min(Decimal::ONE_HUNDRED);
+        let percent = complete - self.progress_billed_percent;
+        if percent <= Decimal::ZERO {
+            return Err(AppError::Conflict(format!(
+                "Project '{}' has been billed through {}% complete",
+                self.name,
+                self.progress_billed_percent.normalize()
+            )));
+        }
+
+        Ok(ProgressInvoice {
+            percent,
+            amount: (fixed_fee * percent / Decimal::ONE_HUNDRED).round_dp(2),
+            billed_through: complete,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn milestone(status: MilestoneStatus) -> ProjectMilestone {
+        ProjectMilestone {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::nil(),
+            project_id: Uuid::nil(),
+            name: "Network cutover".to_string(),
+            amount: Decimal::new(450000, 2),
+            status,
+            completed_at: None,
+            invoice_id: None,
+            invoiced_at: None,
+        }
+    }
+
+    fn invoice() -> Invoice {
+        Invoice {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::nil(),
+            invoice_number: "INV-000042".to_string(),
+            company_id: Uuid::nil(),
+            status: InvoiceStatus::Sent,
+            invoice_date: NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
+            due_date: NaiveDate::from_ymd_opt(2025, 3, 31).unwrap(),
+            payment_terms: Some("net30".to_string()),
+            subtotal: Decimal::from(4500),
+            tax_amount: Decimal::ZERO,
+            total: Decimal::from(4500),
+            amount_paid: Decimal::ZERO,
+            balance_due: Decimal::from(4500),
+            currency: "USD".to_string(),
+            notes: Some("Thank you for your business".to_string()),
+            lines: vec![InvoiceLine {
+                id: Uuid::new_v4(),
+                line_type: "service".to_string(),
+                description: "Office move: Network cutover".to_string(),
+                quantity: Decimal::ONE,
+                unit_price: Decimal::from(4500),
+                total: Decimal::from(4500),
+                project_id: None,
+                milestone_id: None,
+            }],
+            sent_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn project(mode: FixedFeeBilling, percent_complete: i64, billed: i64) -> ProjectBilling {
+        ProjectBilling {
+            project_id: Uuid::nil(),
+            name: "Office move".to_string(),
+            company_id: Some(Uuid::nil()),
+            billing_method: "fixed_price".to_string(),
+            fixed_fee: Some(Decimal::new(12000, 0)),
+            fixed_fee_billing: mode,
+            percent_complete: Decimal::new(percent_complete, 0),
+            progress_billed_percent: Decimal::new(billed, 0),
+        }
+    }
+
+    #[test]
+    fn test_completed_milestone_is_invoiced_once() {
+        assert!(project(FixedFeeBilling::Milestone, 0, 0).check_milestone_billing().is_ok());
+
+        let mut done = milestone(MilestoneStatus::Completed);
+        assert!(done.check_invoiceable().is_ok());
+        assert_eq!(done.line_description("Office move"), "Office move: Network cutover");
+
+        // Once billed, a second invoice is refused
+        done.invoice_id = Some(Uuid::new_v4());
+        assert!(matches!(done.check_invoiceable(), Err(AppError::Conflict(_))));
+
+        assert!(matches!(
+            milestone(MilestoneStatus::Pending).check_invoiceable(),
+            Err(AppError::BadRequest(_))
+        ));
+        assert!(project(FixedFeeBilling::Progress, 0, 0).check_milestone_billing().is_err());
+    }
+
+    #[test]
+    fn test_progress_billing_invoices_earned_share() {
+        let first = project(FixedFeeBilling::Progress, 25, 0).next_progress_invoice().unwrap();
+        assert_eq!(first.amount, Decimal::new(3000, 0));
+        assert_eq!(first.billed_through, Decimal::new(25, 0));
+
+        // Only the completion since the last invoice is billed
+        let second = project(FixedFeeBilling::Progress, 60, 25).next_progress_invoice().unwrap();
+        assert_eq!(second.percent, Decimal::new(35, 0));
+        assert_eq!(second.amount, Decimal::new(4200, 0));
+
+        // Nothing new since the last invoice
+        assert!(matches!(
+            project(FixedFeeBilling::Progress, 60, 60).next_progress_invoice(),
+            Err(AppError::Conflict(_))
+        ));
+    }
+
+    #[test]
+    fn test_invoice_document_header_uses_tenant_branding() {
+        use crate::modules::tenants::{TenantBranding, DEFAULT_PRIMARY_COLOR};
+
+        let branding = TenantBranding {
+            logo_url: Some("https://cdn.acme.test/logo.png".to_string()),
+            primary_color: Some("#ff6600".to_string()),
+            support_email: Some("billing@acme.test".to_string()),
+            email_footer: Some("Acme IT, 1 Main St".to_string()),
+            ..TenantBranding::default()
+        }
+        .resolve("Acme IT");
+
+        let document = InvoiceDocument::new(invoice(), &branding);
+        assert_eq!(document.header.company_name, "Acme IT");
+        assert_eq!(document.header.logo_url.as_deref(), Some("https://cdn.acme.test/logo.png"));
+        assert_eq!(document.header.accent_color, "#ff6600");
+        assert_eq!(document.header.support_email.as_deref(), Some("billing@acme.test"));
+        assert_eq!(document.footer.as_deref(), Some("Acme IT, 1 Main St"));
+        assert_eq!(document.invoice.invoice_number, "INV-000042");
+
+        // An unbranded tenant gets the platform look
+        let plain = InvoiceDocument::new(invoice(), &ResolvedBranding::default());
+        assert_eq!(plain.header.accent_color, DEFAULT_PRIMARY_COLOR);
+        assert_eq!(plain.header.logo_url, None);
+    }
+
+    #[test]
+    fn test_sending_marks_invoice_sent() {
+        let mut draft = Invoice {
+            status: InvoiceStatus::Draft,
+            ..invoice()
+        };
+        assert_eq!(draft.send_skip(false), None);
+
+        let at = Utc::now();
+        draft.mark_sent(at);
+        assert_eq!(draft.status, InvoiceStatus::Sent);
+        assert_eq!(draft.sent_at, Some(at));
+
+        // A part-paid invoice sent again keeps its status
+        let mut part_paid = Invoice {
+            status: InvoiceStatus::PartiallyPaid,
+            ..invoice()
+        };
+        part_paid.mark_sent(at);
+        assert_eq!(part_paid.status, InvoiceStatus::PartiallyPaid);
+        assert_eq!(part_paid.sent_at, Some(at));
+
+        let email = InvoiceDocument::new(draft, &ResolvedBranding::default()).email("ap@customer.test");
+        assert_eq!(email.to, "ap@customer.test");
+        assert!(email.subject.contains("INV-000042"));
+        assert_eq!(email.attachments.len(), 1);
+        assert_eq!(email.attachments[0].file_name, "INV-000042.pdf");
+        assert_eq!(email.attachments[0].content_type, "application/pdf");
+        assert!(email.attachments[0].content.starts_with(b"%PDF-"));
+    }
+
+    #[test]
+    fn test_previously_sent_invoice_is_skipped_unless_forced() {
+        let mut sent = Invoice {
+            status: InvoiceStatus::Draft,
+            ..invoice()
+        };
+        sent.mark_sent(Utc::now() - Duration::days(3));
+        assert_eq!(sent.send_skip(false), Some(InvoiceSendOutcome::AlreadySent));
+        assert_eq!(sent.send_skip(true), None);
+
+        // Force doesn't send a void invoice
+        let void = Invoice {
+            status: InvoiceStatus::Void,
+            ..invoice()
+        };
+        assert_eq!(void.send_skip(true), Some(InvoiceSendOutcome::NotSendable));
+    }
+
+    fn payment_request(amount: i64) -> RecordPaymentRequest {
+        RecordPaymentRequest {
+            amount: Decimal::from(amount),
+            payment_method: PaymentMethod::CreditCard,
+            payment_date: None,
+            reference_number: Some("ch_3PqR".to_string()),
+            gateway_transaction_id: None,
+            notes: None,
+        }
+    }
+
+    fn recorded(request: &RecordPaymentRequest, invoice: &Invoice) -> Payment {
+        Payment {
+            id: Uuid::new_v4(),
+            tenant_id: invoice.tenant_id,
+            invoice_id: Some(invoice.id),
+            company_id: invoice.company_id,
+            payment_date: NaiveDate::from_ymd_opt(2025, 3, 20).unwrap(),
+            amount: request.amount,
+            payment_method: request.payment_method,
+            reference_number: request.reference_number.clone(),
+            gateway_transaction_id: None,
+            notes: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_recorded_payment_produces_receipt_for_the_invoice() {
+        let mut invoice = invoice();
+        let request = payment_request(1500);
+        request.apply(&mut invoice).unwrap();
+        assert_eq!(invoice.status, InvoiceStatus::PartiallyPaid);
+        assert_eq!(invoice.amount_paid, Decimal::from(1500));
+        assert_eq!(invoice.balance_due, Decimal::from(3000));
+
+        let receipt = PaymentReceipt::new(recorded(&request, &invoice), &invoice, &ResolvedBranding::default());
+        let email = receipt.email("ap@customer.test");
+        assert_eq!(email.to, "ap@customer.test");
+        assert!(email.subject.contains("INV-000042"));
+        assert!(email.body_text.contains("USD 1500.00 toward invoice INV-000042"));
+        assert_eq!(email.attachments.len(), 1);
+        assert_eq!(email.attachments[0].file_name, "Receipt-INV-000042-20250320.pdf");
+        assert_eq!(email.attachments[0].content_type, "application/pdf");
+
+        let pdf = String::from_utf8_lossy(&email.attachments[0].content).into_owned();
+        assert!(pdf.contains("(Invoice: INV-000042) Tj"));
+        assert!(pdf.contains("(Amount paid: USD 1500.00) Tj"));
+        assert!(pdf.contains("(Method: Credit card) Tj"));
+        assert!(pdf.contains("(Balance due: USD 3000.00) Tj"));
+
+        // Paying the rest settles the invoice
+        payment_request(3000).apply(&mut invoice).unwrap();
+        assert_eq!(invoice.status, InvoiceStatus::Paid);
+        assert!(invoice.balance_due.is_zero());
+    }
+
+    #[test]
+    fn test_payment_rejected_over_balance_or_on_closed_invoice() {
+        let mut open = invoice();
+        assert!(matches!(payment_request(5000).apply(&mut open), Err(AppError::Validation { .. })));
+        assert!(matches!(payment_request(0).apply(&mut open), Err(AppError::Validation { .. })));
+        assert_eq!(open.balance_due, Decimal::from(4500));
+
+        let mut void = Invoice {
+            status: InvoiceStatus::Void,
+            ..invoice()
+        };
+        assert!(matches!(payment_request(100).apply(&mut void), Err(AppError::Conflict(_))));
+    }
+
+    #[test]
+    fn test_payment_terms_days() {
+        assert_eq!(payment_terms_days(Some("net15")), 15);
+        assert_eq!(payment_terms_days(Some("Net 45")), 45);
+        assert_eq!(payment_terms_days(Some("due_on_receipt")), 0);
+        assert_eq!(payment_terms_days(Some("whenever")), 30);
+        assert_eq!(payment_terms_days(None), 30);
+    }
+
+    #[test]
+    fn test_list_views() {
+        use crate::utils::pagination::ResponseView;
+
+        let summary: InvoiceListItem = ResponseView::Summary.item(invoice());
+        let summary = serde_json::to_value(summary).unwrap();
+        assert_eq!(summary["invoice_number"], "INV-000042");
+        assert_eq!(summary["status"], "sent");
+        assert!(summary.get("updated_at").is_some());
+        for heavy in ["lines", "notes", "payment_terms", "subtotal"] {
+            assert!(summary.get(heavy).is_none(), "summary includes {}", heavy);
+        }
+
+        let full: InvoiceListItem = ResponseView::Full.item(invoice());
+        let full = serde_json::to_value(full).unwrap();
+        assert_eq!(full["lines"].as_array().unwrap().len(), 1);
+        assert_eq!(full["notes"], "Thank you for your business");
+    }
+
+    fn sent_quote(valid_until: NaiveDate) -> Quote {
+        let requested = vec![
+            QuoteLineRequest {
+                line_type: "service".to_string(),
+                description: "Firewall replacement labor".to_string(),
+                quantity: Decimal::from(6),
+                unit_price: Decimal::new(15000, 2),
+            },
+            QuoteLineRequest {
+                line_type: "discount".to_string(),
+                description: "Loyalty discount".to_string(),
+                quantity: Decimal::ONE,
+                unit_price: Decimal::from(100),
+            },
+        ];
+        let total = quote_total(&requested);
+
+        Quote {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::nil(),
+            quote_number: "Q-000007".to_string(),
+            company_id: Uuid::nil(),
+            title: "Firewall replacement".to_string(),
+            status: QuoteStatus::Sent,
+            quote_date: valid_until - Duration::days(QUOTE_VALIDITY_DAYS),
+            valid_until,
+            subtotal: total,
+            total,
+            currency: "USD".to_string(),
+            notes: None,
+            lines: requested
+                .iter()
+                .map(|line| QuoteLine {
+                    id: Uuid::new_v4(),
+                    line_type: line.line_type.clone(),
+                    description: line.description.clone(),
+                    quantity: line.quantity,
+                    unit_price: line.unit_price,
+                    total: line.total(),
+                })
+                .collect(),
+            token: Some("token".to_string()),
+            sent_at: Some(Utc::now()),
+            responded_at: None,
+            response_comment: None,
+            invoice_id: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_accept_then_convert() {
+        let valid_until = NaiveDate::from_ymd_opt(2025, 4, 30).unwrap();
+        let today = NaiveDate::from_ymd_opt(2025, 4, 10).unwrap();
+        let mut quote = sent_quote(valid_until);
+        assert_eq!(quote.total, Decimal::from(800));
+
+        // Not accepted yet
+        assert!(quote.ensure_convertible().is_err());
+
+        quote
+            .respond(QuoteDecision::Accept, Some("Go ahead".to_string()), Utc::now(), today)
+            .unwrap();
+        assert_eq!(quote.status, QuoteStatus::Accepted);
+        assert!(quote.responded_at.is_some());
+        assert!(quote.ensure_convertible().is_ok());
+
+        // The invoice carries the same lines and total
+        let lines = quote.invoice_lines();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1].line_type, "discount");
+        assert_eq!(lines[1].total, Decimal::from(-100));
+        assert_eq!(lines.iter().map(|line| line.total).sum::<Decimal>(), quote.total);
+
+        // Only once, either way
+        assert!(matches!(
+            quote.respond(QuoteDecision::Decline, None, Utc::now(), today),
+            Err(AppError::Conflict(_))
+        ));
+        quote.invoice_id = Some(Uuid::new_v4());
+        assert!(matches!(quote.ensure_convertible(), Err(AppError::Conflict(_))));
+
+        // Declined quotes aren't invoiced
+        let mut declined = sent_quote(valid_until);
+        declined.respond(QuoteDecision::Decline, None, Utc::now(), today).unwrap();
+        assert!(declined.ensure_convertible().is_err());
+    }
+
+    #[test]
+    fn test_quote_expiry() {
+        let valid_until = NaiveDate::from_ymd_opt(2025, 4, 30).unwrap();
+        let last_day = valid_until;
+        let day_after = valid_until + Duration::days(1);
+
+        // Still answerable on its last valid day
+        let mut quote = sent_quote(valid_until);
+        assert!(!quote.expire_if_due(last_day));
+        assert!(quote.respond(QuoteDecision::Accept, None, Utc::now(), last_day).is_ok());
+
+        // Past it, the quote expires and can't be accepted
+        let mut quote = sent_quote(valid_until);
+        assert!(quote.expire_if_due(day_after));
+        assert_eq!(quote.status, QuoteStatus::Expired);
+        assert!(!quote.expire_if_due(day_after));
+        assert!(quote.respond(QuoteDecision::Accept, None, Utc::now(), day_after).is_err());
+        assert!(quote.ensure_convertible().is_err());
+        assert!(quote.ensure_sendable(day_after).is_err());
+
+        // Answering late expires it too
+        let mut quote = sent_quote(valid_until);
+        assert!(quote.respond(QuoteDecision::Accept, None, Utc::now(), day_after).is_err());
+        assert_eq!(quote.status, QuoteStatus::Expired);
+
+        // Drafts and answered quotes don't expire
+        let mut draft = sent_quote(valid_until);
+        draft.status = QuoteStatus::Draft;
+        assert!(!draft.expire_if_due(day_after));
+        let mut accepted = sent_quote(valid_until);
+        accepted.status = QuoteStatus::Accepted;
+        assert!(!accepted.expire_if_due(day_after));
+    }
+}
@@ -0,0 +1,514 @@
+//! Quotes
+//!
+//! Quotes are drafted and priced by staff, then sent with a tokenized link
+//! the customer uses to accept or decline. Accepted quotes convert to a
+//! draft invoice with the same lines. Sent quotes that pass their validity
+//! date unanswered become expired, when next read or by the expiry job.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::modules::jobs::Job;
+use crate::modules::sequences::{SequenceKind, SequenceService};
+use crate::modules::tenants::TenantService;
+use crate::utils::crypto::generate_token;
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::pagination::PaginationParams;
+use crate::utils::timezone::TenantTimezone;
+
+use super::models::*;
+use super::service::BillingService;
+
+const QUOTE_COLUMNS: &str = "id, tenant_id, quote_number, company_id, title, status, quote_date, valid_until, \
+     subtotal, total, currency, notes, token, sent_at, responded_at, response_comment, invoice_id, \
+     created_at, updated_at";
+
+/// Quote management and customer response service
+#[derive(Clone)]
+pub struct QuoteService {
+    db: Database,
+    billing: BillingService,
+    tenants: TenantService,
+    base_url: String,
+}
+
+impl QuoteService {
+    pub fn new(db: Database) -> Self {
+        Self {
+            billing: BillingService::new(db.clone()),
+            tenants: TenantService::new(db.clone()),
+            base_url: std::env::var("BASE_URL")
+                .unwrap_or_else(|_| "http://localhost:8080".to_string()),
+            db,
+        }
+    }
+
+    /// Create a draft quote
+    pub async fn create_quote(&self, tenant_id: Uuid, user_id: Uuid, request: &CreateQuoteRequest) -> AppResult<Quote> {
+        request.check()?;
+
+        let today = TenantTimezone::load(self.db.pool(), tenant_id).await?.local_date(Utc::now());
+        let valid_until = request
+            .valid_until
+            .unwrap_or(today + Duration::days(QUOTE_VALIDITY_DAYS));
+        if valid_until < today {
+            return Err(AppError::validation_field("valid_until", "Must not be in the past"));
+        }
+
+        let company_exists: bool =
+            sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM companies WHERE tenant_id = $1 AND id = $2)")
+                .bind(tenant_id)
+                .bind(request.company_id)
+                .fetch_one(self.db.pool())
+                .await?;
+        if !company_exists {
+            return Err(AppError::not_found("Company"));
+        }
+
+        let mut tx = self.db.pool().begin().await?;
+        let quote_number = SequenceService::next_in(&mut *tx, tenant_id, SequenceKind::Quote).await?;
+
+        let quote_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO quotes (tenant_id, quote_number, company_id, title, quote_date, valid_until, notes, created_by_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(&quote_number)
+        .bind(request.company_id)
+        .bind(&request.title)
+        .bind(today)
+        .bind(valid_until)
+        .bind(&request.notes)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        Self::replace_lines(&mut tx, quote_id, &request.lines).await?;
+        tx.commit().await?;
+
+        self.get_quote(tenant_id, quote_id).await
+    }
+
+    /// Replace a quote's lines and recompute its totals
+    async fn replace_lines(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        quote_id: Uuid,
+        lines: &[QuoteLineRequest],
+    ) -> AppResult<()> {
+        sqlx::query("DELETE FROM quote_lines WHERE quote_id = $1")
+            .bind(quote_id)
+            .execute(&mut **tx)
+            .await?;
+
+        for (sort_order, line) in lines.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO quote_lines (quote_id, line_type, description, quantity, unit_price, total, sort_order)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                "#,
+            )
+            .bind(quote_id)
+            .bind(&line.line_type)
+            .bind(&line.description)
+            .bind(line.quantity)
+            .bind(line.unit_price)
+            .bind(line.total())
+            .bind(sort_order as i32)
+            .execute(&mut **tx)
+            .await?;
+        }
+
+        let total = quote_total(lines);
+        sqlx::query("UPDATE quotes SET subtotal = $1, total = $1 WHERE id = $2")
+            .bind(total)
+            .bind(quote_id)
+            .execute(&mut **tx)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Get a quote with its lines, expiring it first if it is due
+    pub async fn get_quote(&self, tenant_id: Uuid, quote_id: Uuid) -> AppResult<Quote> {
+        let row = sqlx::query_as::<_, QuoteRow>(&format!(
+            "SELECT {} FROM quotes WHERE tenant_id = $1 AND id = $2",
+            QUOTE_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(quote_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::not_found("Quote"))?;
+
+        self.load_quote(row).await
+    }
+
+    async fn load_quote(&self, row: QuoteRow) -> AppResult<Quote> {
+        let lines = sqlx::query_as::<_, QuoteLineRow>(
+            r#"
+            SELECT id, line_type, description, quantity, unit_price, total
+            FROM quote_lines
+            WHERE quote_id = $1
+            ORDER BY sort_order, created_at
+            "#,
+        )
+        .bind(row.id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let mut quote = row.into_quote(lines.into_iter().map(Into::into).collect());
+        let today = TenantTimezone::load(self.db.pool(), quote.tenant_id).await?.local_date(Utc::now());
+        if quote.expire_if_due(today) {
+            self.mark_expired(&[quote.id]).await?;
+        }
+
+        Ok(quote)
+    }
+
+    /// Quotes matching `filter` with the total count, without their lines
+    pub async fn list_quotes(
+        &self,
+        tenant_id: Uuid,
+        filter: &QuoteFilter,
+        pagination: &PaginationParams,
+    ) -> AppResult<(Vec<Quote>, u64)> {
+        let where_clause = "tenant_id = $1 AND ($2::UUID IS NULL OR company_id = $2) AND ($3::TEXT IS NULL OR status = $3)";
+        let count_query = format!("SELECT COUNT(*) FROM quotes WHERE {}", where_clause);
+        let order_by = pagination.order_by("quote_date", &["quote_date", "valid_until", "quote_number", "total", "updated_at"]);
+
+        let total: i64 = sqlx::query_scalar(&count_query)
+            .bind(tenant_id)
+            .bind(filter.company_id)
+            .bind(filter.status.map(|status| status.as_str()))
+            .fetch_one(self.db.pool())
+            .await?;
+
+        let page = pagination.clamped(total as u64);
+        let rows = sqlx::query_as::<_, QuoteRow>(&format!(
+            "SELECT {} FROM quotes WHERE {} ORDER BY {}, created_at DESC LIMIT $4 OFFSET $5",
+            QUOTE_COLUMNS, where_clause, order_by
+        ))
+        .bind(tenant_id)
+        .bind(filter.company_id)
+        .bind(filter.status.map(|status| status.as_str()))
+        .bind(page.limit() as i32)
+        .bind(page.offset() as i32)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let today = TenantTimezone::load(self.db.pool(), tenant_id).await?.local_date(Utc::now());
+        let mut quotes: Vec<Quote> = rows.into_iter().map(|row| row.into_quote(Vec::new())).collect();
+        let expired: Vec<Uuid> = quotes
+            .iter_mut()
+            .filter_map(|quote| quote.expire_if_due(today).then_some(quote.id))
+            .collect();
+        self.mark_expired(&expired).await?;
+
+        Ok((quotes, total as u64))
+    }
+
+    /// Update a draft quote
+    pub async fn update_quote(&self, tenant_id: Uuid, quote_id: Uuid, request: &UpdateQuoteRequest) -> AppResult<Quote> {
+        request.check()?;
+        self.get_quote(tenant_id, quote_id).await?.ensure_editable()?;
+
+        let mut tx = self.db.pool().begin().await?;
+        let updated = sqlx::query(
+            r#"
+            UPDATE quotes SET
+                title = COALESCE($3, title),
+                valid_until = COALESCE($4, valid_until),
+                notes = COALESCE($5, notes)
+            WHERE tenant_id = $1 AND id = $2 AND status = 'draft'
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(quote_id)
+        .bind(&request.title)
+        .bind(request.valid_until)
+        .bind(&request.notes)
+        .execute(&mut *tx)
+        .await?;
+
+        // Sent in the meantime
+        if updated.rows_affected() == 0 {
+            return Err(AppError::Conflict("Quote can no longer be edited".to_string()));
+        }
+
+        if let Some(ref lines) = request.lines {
+            Self::replace_lines(&mut tx, quote_id, lines).await?;
+        }
+        tx.commit().await?;
+
+        self.get_quote(tenant_id, quote_id).await
+    }
+
+    /// Delete a draft quote
+    pub async fn delete_quote(&self, tenant_id: Uuid, quote_id: Uuid) -> AppResult<()> {
+        self.get_quote(tenant_id, quote_id).await?.ensure_editable()?;
+
+        sqlx::query("DELETE FROM quotes WHERE tenant_id = $1 AND id = $2 AND status = 'draft'")
+            .bind(tenant_id)
+            .bind(quote_id)
+            .execute(self.db.pool())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Mark a quote sent and return the customer's response link. Sending
+    /// again keeps the same link.
+    pub async fn send_quote(&self, tenant_id: Uuid, quote_id: Uuid) -> AppResult<SentQuote> {
+        let quote = self.get_quote(tenant_id, quote_id).await?;
+        let today = TenantTimezone::load(self.db.pool(), tenant_id).await?.local_date(Utc::now());
+        quote.ensure_sendable(today)?;
+
+        let token = quote.token.clone().unwrap_or_else(|| generate_token(48));
+        sqlx::query(
+            r#"
+            UPDATE quotes SET status = 'sent', token = $1, sent_at = COALESCE(sent_at, NOW())
+            WHERE tenant_id = $2 AND id = $3 AND status IN ('draft', 'sent')
+            "#,
+        )
+        .bind(&token)
+        .bind(tenant_id)
+        .bind(quote_id)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(SentQuote {
+            quote: self.get_quote(tenant_id, quote_id).await?,
+            response_url: format!("{}/api/v1/quote-response/{}", self.base_url.trim_end_matches('/'), token),
+        })
+    }
+
+    /// A quote laid out for the PDF in the invoice layout, under the tenant's
+    /// branding
+    pub async fn quote_document(&self, tenant_id: Uuid, quote_id: Uuid) -> AppResult<QuoteDocument> {
+        let quote = self.get_quote(tenant_id, quote_id).await?;
+        let branding = self.tenants.get_branding(tenant_id).await?;
+
+        Ok(QuoteDocument::new(quote, &branding))
+    }
+
+    /// The quote behind a customer response link, as the customer sees it
+    pub async fn document_by_token(&self, tenant_id: Option<Uuid>, token: &str) -> AppResult<QuoteDocument> {
+        let quote = self.get_quote_by_token(tenant_id, token).await?;
+        let branding = self.tenants.get_branding(quote.tenant_id).await?;
+
+        Ok(QuoteDocument::new(quote, &branding))
+    }
+
+    /// With `tenant_id`, links issued by other tenants are not found
+    async fn get_quote_by_token(&self, tenant_id: Option<Uuid>, token: &str) -> AppResult<Quote> {
+        let row = sqlx::query_as::<_, QuoteRow>(&format!(
+            "SELECT {} FROM quotes WHERE token = $1 AND status <> 'draft' AND ($2::UUID IS NULL OR tenant_id = $2)",
+            QUOTE_COLUMNS
+        ))
+        .bind(token)
+        .bind(tenant_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::not_found("Quote"))?;
+
+        self.load_quote(row).await
+    }
+
+    /// Record the customer's accept or decline. Each link takes one answer.
+    pub async fn respond(
+        &self,
+        tenant_id: Option<Uuid>,
+        token: &str,
+        request: &QuoteResponseRequest,
+    ) -> AppResult<Quote> {
+        let mut quote = self.get_quote_by_token(tenant_id, token).await?;
+        let today = TenantTimezone::load(self.db.pool(), quote.tenant_id).await?.local_date(Utc::now());
+        quote.respond(request.decision, request.comment.clone(), Utc::now(), today)?;
+
+        // Guard again in SQL so two concurrent answers cannot both land
+        let updated = sqlx::query(
+            r#"
+            UPDATE quotes SET status = $1, responded_at = $2, response_comment = $3
+            WHERE id = $4 AND status = 'sent'
+            "#,
+        )
+        .bind(quote.status.as_str())
+        .bind(quote.responded_at)
+        .bind(&quote.response_comment)
+        .bind(quote.id)
+        .execute(self.db.pool())
+        .await?;
+
+        if updated.rows_affected() == 0 {
+            return Err(AppError::Conflict("Quote has already been answered".to_string()));
+        }
+
+        Ok(quote)
+    }
+
+    /// Invoice an accepted quote as a draft invoice with the same lines
+    pub async fn convert_to_invoice(&self, tenant_id: Uuid, quote_id: Uuid) -> AppResult<Invoice> {
+        let quote = self.get_quote(tenant_id, quote_id).await?;
+        quote.ensure_convertible()?;
+
+        let mut tx = self.db.pool().begin().await?;
+        let invoice_id = self
+            .billing
+            .insert_draft_invoice(&mut tx, tenant_id, quote.company_id, &quote.invoice_lines())
+            .await?;
+
+        // Claim the quote for this invoice; a concurrent conversion that got
+        // there first leaves nothing to update and this invoice rolls back
+        let claimed = sqlx::query(
+            "UPDATE quotes SET invoice_id = $1 WHERE tenant_id = $2 AND id = $3 AND status = 'accepted' AND invoice_id IS NULL",
+        )
+        .bind(invoice_id)
+        .bind(tenant_id)
+        .bind(quote_id)
+        .execute(&mut *tx)
+        .await?;
+
+        if claimed.rows_affected() == 0 {
+            return Err(AppError::Conflict(format!("Quote {} has already been invoiced", quote.quote_number)));
+        }
+
+        tx.commit().await?;
+        self.billing.get_invoice(tenant_id, invoice_id).await
+    }
+
+    /// Expire every sent quote past its validity date in its tenant's time
+    /// zone, returning how many changed
+    pub async fn expire_quotes(&self, now: DateTime<Utc>) -> AppResult<usize> {
+        // Dates are a day ahead somewhere; the tenant's own date decides
+        let candidates: Vec<(Uuid, Uuid, chrono::NaiveDate)> = sqlx::query_as(
+            "SELECT id, tenant_id, valid_until FROM quotes WHERE status = 'sent' AND valid_until < $1",
+        )
+        .bind(now.date_naive())
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let mut today_by_tenant: HashMap<Uuid, chrono::NaiveDate> = HashMap::new();
+        let mut due = Vec::new();
+        for (quote_id, tenant_id, valid_until) in candidates {
+            let today = match today_by_tenant.get(&tenant_id) {
+                Some(today) => *today,
+                None => {
+                    let today = TenantTimezone::load(self.db.pool(), tenant_id).await?.local_date(now);
+                    today_by_tenant.insert(tenant_id, today);
+                    today
+                }
+            };
+            if today > valid_until {
+                due.push(quote_id);
+            }
+        }
+
+        self.mark_expired(&due).await
+    }
+
+    /// Handler for `EXPIRE_QUOTES_JOB`, for registering with the job worker
+    pub async fn run_expire_quotes_job(&self, _job: Job) -> AppResult<()> {
+        let expired = self.expire_quotes(Utc::now()).await?;
+        if expired > 0 {
+            tracing::info!("Expired {} quotes past their validity date", expired);
+        }
+        Ok(())
+    }
+
+    async fn mark_expired(&self, quote_ids: &[Uuid]) -> AppResult<usize> {
+        if quote_ids.is_empty() {
+            return Ok(0);
+        }
+
+        let result = sqlx::query("UPDATE quotes SET status = 'expired' WHERE id = ANY($1) AND status = 'sent'")
+            .bind(quote_ids)
+            .execute(self.db.pool())
+            .await?;
+
+        Ok(result.rows_affected() as usize)
+    }
+}
+
+// ============================================================================
+// DATABASE ROW TYPES
+// ============================================================================
+
+#[derive(sqlx::FromRow)]
+struct QuoteRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    quote_number: String,
+    company_id: Uuid,
+    title: String,
+    status: String,
+    quote_date: chrono::NaiveDate,
+    valid_until: chrono::NaiveDate,
+    subtotal: Decimal,
+    total: Decimal,
+    currency: Option<String>,
+    notes: Option<String>,
+    token: Option<String>,
+    sent_at: Option<DateTime<Utc>>,
+    responded_at: Option<DateTime<Utc>>,
+    response_comment: Option<String>,
+    invoice_id: Option<Uuid>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl QuoteRow {
+    fn into_quote(self, lines: Vec<QuoteLine>) -> Quote {
+        Quote {
+            id: self.id,
+            tenant_id: self.tenant_id,
+            quote_number: self.quote_number,
+            company_id: self.company_id,
+            title: self.title,
+            status: QuoteStatus::from_str(&self.status).unwrap_or_default(),
+            quote_date: self.quote_date,
+            valid_until: self.valid_until,
+            subtotal: self.subtotal,
+            total: self.total,
+            currency: self.currency.unwrap_or_else(|| "USD".to_string()),
+            notes: self.notes,
+            lines,
+            token: self.token,
+            sent_at: self.sent_at,
+            responded_at: self.responded_at,
+            response_comment: self.response_comment,
+            invoice_id: self.invoice_id,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct QuoteLineRow {
+    id: Uuid,
+    line_type: String,
+    description: String,
+    quantity: Decimal,
+    unit_price: Decimal,
+    total: Decimal,
+}
+
+impl From<QuoteLineRow> for QuoteLine {
+    fn from(row: QuoteLineRow) -> Self {
+        Self {
+            id: row.id,
+            line_type: row.line_type,
+            description: row.description,
+            quantity: row.quantity,
+            unit_price: row.unit_price,
+            total: row.total,
+        }
+    }
+}
@@ -0,0 +1,347 @@
+//! Billing API routes
+
+use axum::{
+    extract::{OriginalUri, Path, Query, State},
+    routing::{delete, get, patch, post},
+    Json, Router,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use validator::Validate;
+
+use super::{
+    BillingService, CreateQuoteRequest, Invoice, InvoiceDelivery, InvoiceDocument, InvoiceFilter, InvoiceListItem,
+    InvoiceSendResult, PaymentReceipt, Quote, QuoteDocument, QuoteFilter, QuoteResponseRequest, QuoteService,
+    RecordPaymentRequest, RecordedPayment, SendInvoicesRequest, SentQuote, UpdateQuoteRequest,
+};
+use crate::api::tenant_resolution::CurrentTenant;
+use crate::modules::auth::RequireFinance;
+use crate::utils::error::AppResult;
+use crate::utils::pagination::{PaginatedJson, PaginatedResponse, PaginationParams, ResponseView, ViewParams};
+
+#[derive(Clone)]
+pub struct BillingRouterState {
+    pub billing_service: Arc<BillingService>,
+}
+
+/// Create the invoices router
+pub fn billing_routes(billing_service: BillingService) -> Router {
+    let state = BillingRouterState {
+        billing_service: Arc::new(billing_service),
+    };
+
+    Router::new()
+        .route("/", get(list_invoices))
+        .route("/:invoice_id", get(get_invoice))
+        .route("/:invoice_id/document", get(get_invoice_document))
+        .route("/:invoice_id/deliveries", get(list_invoice_deliveries))
+        .route("/:invoice_id/payments", post(record_payment))
+        .route("/payments/:payment_id/receipt", get(get_payment_receipt))
+        .route("/send", post(send_invoices))
+        .route(
+            "/projects/:project_id/milestones/:milestone_id",
+            post(invoice_project_milestone),
+        )
+        .route("/projects/:project_id/progress", post(invoice_project_progress))
+        .with_state(state)
+}
+
+#[derive(Clone)]
+pub struct QuoteRouterState {
+    pub quote_service: Arc<QuoteService>,
+}
+
+/// Create the quotes router
+pub fn quote_routes(quote_service: QuoteService) -> Router {
+    let state = QuoteRouterState {
+        quote_service: Arc::new(quote_service),
+    };
+
+    Router::new()
+        .route("/", get(list_quotes))
+        .route("/", post(create_quote))
+        .route("/:quote_id", get(get_quote))
+        .route("/:quote_id", patch(update_quote))
+        .route("/:quote_id", delete(delete_quote))
+        .route("/:quote_id/send", post(send_quote))
+        .route("/:quote_id/document", get(get_quote_document))
+        .route("/:quote_id/invoice", post(convert_quote_to_invoice))
+        .with_state(state)
+}
+
+/// Create the public router behind customers' quote links (token-authorized)
+pub fn quote_response_routes(quote_service: QuoteService) -> Router {
+    let state = QuoteRouterState {
+        quote_service: Arc::new(quote_service),
+    };
+
+    Router::new()
+        .route("/:token", get(get_quote_for_response))
+        .route("/:token", post(respond_to_quote))
+        .with_state(state)
+}
+
+async fn list_invoices(
+    State(state): State<BillingRouterState>,
+    RequireFinance(user, _): RequireFinance,
+    Query(filter): Query<InvoiceFilter>,
+    Query(pagination): Query<PaginationParams>,
+    Query(ViewParams { view }): Query<ViewParams>,
+    OriginalUri(uri): OriginalUri,
+) -> AppResult<PaginatedJson<InvoiceListItem>> {
+    let (invoices, total) = state
+        .billing_service
+        .list_invoices(user.tenant_id, &filter, &pagination, view == ResponseView::Full)
+        .await?;
+
+    let items: Vec<InvoiceListItem> = invoices.into_iter().map(|invoice| view.item(invoice)).collect();
+    let response = PaginatedResponse::from_params(items, &pagination, total);
+
+    Ok(response.with_links(&uri))
+}
+
+async fn get_invoice(
+    State(state): State<BillingRouterState>,
+    RequireFinance(user, _): RequireFinance,
+    Path(invoice_id): Path<Uuid>,
+) -> AppResult<Json<Invoice>> {
+    let invoice = state
+        .billing_service
+        .get_invoice(user.tenant_id, invoice_id)
+        .await?;
+
+    Ok(Json(invoice))
+}
+
+async fn get_invoice_document(
+    State(state): State<BillingRouterState>,
+    RequireFinance(user, _): RequireFinance,
+    Path(invoice_id): Path<Uuid>,
+) -> AppResult<Json<InvoiceDocument>> {
+    let document = state
+        .billing_service
+        .invoice_document(user.tenant_id, invoice_id)
+        .await?;
+
+    Ok(Json(document))
+}
+
+async fn list_invoice_deliveries(
+    State(state): State<BillingRouterState>,
+    RequireFinance(user, _): RequireFinance,
+    Path(invoice_id): Path<Uuid>,
+) -> AppResult<Json<Vec<InvoiceDelivery>>> {
+    let deliveries = state
+        .billing_service
+        .invoice_deliveries(user.tenant_id, invoice_id)
+        .await?;
+
+    Ok(Json(deliveries))
+}
+
+async fn send_invoices(
+    State(state): State<BillingRouterState>,
+    RequireFinance(user, _): RequireFinance,
+    Json(request): Json<SendInvoicesRequest>,
+) -> AppResult<Json<Vec<InvoiceSendResult>>> {
+    request.validate()?;
+
+    let results = state
+        .billing_service
+        .send_invoices(user.tenant_id, user.id, &request)
+        .await?;
+
+    Ok(Json(results))
+}
+
+async fn record_payment(
+    State(state): State<BillingRouterState>,
+    RequireFinance(user, _): RequireFinance,
+    Path(invoice_id): Path<Uuid>,
+    Json(request): Json<RecordPaymentRequest>,
+) -> AppResult<Json<RecordedPayment>> {
+    request.validate()?;
+
+    let payment = state
+        .billing_service
+        .record_payment(user.tenant_id, invoice_id, &request)
+        .await?;
+
+    Ok(Json(payment))
+}
+
+async fn get_payment_receipt(
+    State(state): State<BillingRouterState>,
+    RequireFinance(user, _): RequireFinance,
+    Path(payment_id): Path<Uuid>,
+) -> AppResult<Json<PaymentReceipt>> {
+    let receipt = state
+        .billing_service
+        .payment_receipt(user.tenant_id, payment_id)
+        .await?;
+
+    Ok(Json(receipt))
+}
+
+async fn invoice_project_milestone(
+    State(state): State<BillingRouterState>,
+    RequireFinance(user, _): RequireFinance,
+    Path((project_id, milestone_id)): Path<(Uuid, Uuid)>,
+) -> AppResult<Json<Invoice>> {
+    let invoice = state
+        .billing_service
+        .invoice_project_milestone(user.tenant_id, project_id, milestone_id)
+        .await?;
+
+    Ok(Json(invoice))
+}
+
+async fn invoice_project_progress(
+    State(state): State<BillingRouterState>,
+    RequireFinance(user, _): RequireFinance,
+    Path(project_id): Path<Uuid>,
+) -> AppResult<Json<Invoice>> {
+    let invoice = state
+        .billing_service
+        .invoice_project_progress(user.tenant_id, project_id)
+        .await?;
+
+    Ok(Json(invoice))
+}
+
+async fn list_quotes(
+    State(state): State<QuoteRouterState>,
+    RequireFinance(user, _): RequireFinance,
+    Query(filter): Query<QuoteFilter>,
+    Query(pagination): Query<PaginationParams>,
+    OriginalUri(uri): OriginalUri,
+) -> AppResult<PaginatedJson<Quote>> {
+    let (quotes, total) = state
+        .quote_service
+        .list_quotes(user.tenant_id, &filter, &pagination)
+        .await?;
+
+    let response = PaginatedResponse::from_params(quotes, &pagination, total);
+
+    Ok(response.with_links(&uri))
+}
+
+async fn create_quote(
+    State(state): State<QuoteRouterState>,
+    RequireFinance(user, _): RequireFinance,
+    Json(request): Json<CreateQuoteRequest>,
+) -> AppResult<Json<Quote>> {
+    request.validate()?;
+
+    let quote = state
+        .quote_service
+        .create_quote(user.tenant_id, user.id, &request)
+        .await?;
+
+    Ok(Json(quote))
+}
+
+async fn get_quote(
+    State(state): State<QuoteRouterState>,
+    RequireFinance(user, _): RequireFinance,
+    Path(quote_id): Path<Uuid>,
+) -> AppResult<Json<Quote>> {
+    let quote = state
+        .quote_service
+        .get_quote(user.tenant_id, quote_id)
+        .await?;
+
+    Ok(Json(quote))
+}
+
+async fn update_quote(
+    State(state): State<QuoteRouterState>,
+    RequireFinance(user, _): RequireFinance,
+    Path(quote_id): Path<Uuid>,
+    Json(request): Json<UpdateQuoteRequest>,
+) -> AppResult<Json<Quote>> {
+    request.validate()?;
+
+    let quote = state
+        .quote_service
+        .update_quote(user.tenant_id, quote_id, &request)
+        .await?;
+
+    Ok(Json(quote))
+}
+
+async fn delete_quote(
+    State(state): State<QuoteRouterState>,
+    RequireFinance(user, _): RequireFinance,
+    Path(quote_id): Path<Uuid>,
+) -> AppResult<()> {
+    state
+        .quote_service
+        .delete_quote(user.tenant_id, quote_id)
+        .await
+}
+
+async fn send_quote(
+    State(state): State<QuoteRouterState>,
+    RequireFinance(user, _): RequireFinance,
+    Path(quote_id): Path<Uuid>,
+) -> AppResult<Json<SentQuote>> {
+    let sent = state
+        .quote_service
+        .send_quote(user.tenant_id, quote_id)
+        .await?;
+
+    Ok(Json(sent))
+}
+
+async fn get_quote_document(
+    State(state): State<QuoteRouterState>,
+    RequireFinance(user, _): RequireFinance,
+    Path(quote_id): Path<Uuid>,
+) -> AppResult<Json<QuoteDocument>> {
+    let document = state
+        .quote_service
+        .quote_document(user.tenant_id, quote_id)
+        .await?;
+
+    Ok(Json(document))
+}
+
+async fn convert_quote_to_invoice(
+    State(state): State<QuoteRouterState>,
+    RequireFinance(user, _): RequireFinance,
+    Path(quote_id): Path<Uuid>,
+) -> AppResult<Json<Invoice>> {
+    let invoice = state
+        .quote_service
+        .convert_to_invoice(user.tenant_id, quote_id)
+        .await?;
+
+    Ok(Json(invoice))
+}
+
+async fn get_quote_for_response(
+    State(state): State<QuoteRouterState>,
+    tenant: Option<CurrentTenant>,
+    Path(token): Path<String>,
+) -> AppResult<Json<QuoteDocument>> {
+    let tenant_id = tenant.as_ref().map(CurrentTenant::tenant_id);
+    let document = state.quote_service.document_by_token(tenant_id, &token).await?;
+
+    Ok(Json(document))
+}
+
+async fn respond_to_quote(
+    State(state): State<QuoteRouterState>,
+    tenant: Option<CurrentTenant>,
+    Path(token): Path<String>,
+    Json(request): Json<QuoteResponseRequest>,
+) -> AppResult<Json<Quote>> {
+    request.validate()?;
+
+    let tenant_id = tenant.as_ref().map(CurrentTenant::tenant_id);
+    let quote = state.quote_service.respond(tenant_id, &token, &request).await?;
+
+    Ok(Json(quote))
+}
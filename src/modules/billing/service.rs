@@ -0,0 +1,939 @@
+//! Billing service implementation
+
+use chrono::{Duration, Utc};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::modules::notifications::{NotificationService, NotificationStatus};
+use crate::modules::sequences::{SequenceKind, SequenceService};
+use crate::modules::tenants::TenantService;
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::pagination::PaginationParams;
+use crate::utils::timezone::TenantTimezone;
+
+use super::models::*;
+
+const INVOICE_COLUMNS: &str = r#"
+    id, tenant_id, invoice_number, company_id, status, invoice_date, due_date, payment_terms,
+    subtotal, tax_amount, total, amount_paid, balance_due, currency, notes, sent_at, created_at, updated_at
+"#;
+
+const PAYMENT_COLUMNS: &str = r#"
+    id, tenant_id, invoice_id, company_id, payment_date, amount, payment_method, reference_number,
+    gateway_transaction_id, notes, created_at
+"#;
+
+/// Billing service
+#[derive(Clone)]
+pub struct BillingService {
+    db: Database,
+    tenants: TenantService,
+    notifications: NotificationService,
+}
+
+impl BillingService {
+    pub fn new(db: Database) -> Self {
+        Self {
+            tenants: TenantService::new(db.clone()),
+            notifications: NotificationService::new(db.clone()),
+            db,
+        }
+    }
+
+    async fn get_project_billing(&self, tenant_id: Uuid, project_id: Uuid) -> AppResult<ProjectBilling> {
+        let row = sqlx::query_as::<_, ProjectBillingRow>(
+            r#"
+            SELECT id, name, company_id, billing_method, fixed_fee, fixed_fee_billing,
+                   percent_complete, progress_billed_percent
+            FROM projects
+            WHERE id = $1 AND tenant_id = $2
+            "#,
+        )
+        .bind(project_id)
+        .bind(tenant_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::not_found("Project"))?;
+
+        Ok(row.into())
+    }
+
+    async fn get_milestone(&self, tenant_id: Uuid, project_id: Uuid, milestone_id: Uuid) -> AppResult<ProjectMilestone> {
+        let row = sqlx::query_as::<_, MilestoneRow>(
+            r#"
+            SELECT id, tenant_id, project_id, name, amount, status, completed_at, invoice_id, invoiced_at
+            FROM project_milestones
+            WHERE id = $1 AND project_id = $2 AND tenant_id = $3
+            "#,
+        )
+        .bind(milestone_id)
+        .bind(project_id)
+        .bind(tenant_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::not_found("Milestone"))?;
+
+        Ok(row.into())
+    }
+
+    /// Get an invoice with its lines
+    pub async fn get_invoice(&self, tenant_id: Uuid, invoice_id: Uuid) -> AppResult<Invoice> {
+        let row = sqlx::query_as::<_, InvoiceRow>(&format!(
+            "SELECT {} FROM invoices WHERE id = $1 AND tenant_id = $2",
+            INVOICE_COLUMNS
+        ))
+        .bind(invoice_id)
+        .bind(tenant_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::not_found("Invoice"))?;
+
+        let lines = sqlx::query_as::<_, InvoiceLineRow>(
+            r#"
+            SELECT id, line_type, description, quantity, unit_price, total, project_id, milestone_id
+            FROM invoice_lines
+            WHERE invoice_id = $1
+            ORDER BY sort_order, created_at
+            "#,
+        )
+        .bind(invoice_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(row.into_invoice(lines.into_iter().map(Into::into).collect()))
+    }
+
+    /// An invoice laid out for the PDF, under the tenant's branding
+    pub async fn invoice_document(&self, tenant_id: Uuid, invoice_id: Uuid) -> AppResult<InvoiceDocument> {
+        let invoice = self.get_invoice(tenant_id, invoice_id).await?;
+        let branding = self.tenants.get_branding(tenant_id).await?;
+
+        Ok(InvoiceDocument::new(invoice, &branding))
+    }
+
+    // ========================================================================
+    // DELIVERY
+    // ========================================================================
+
+    /// Email each invoice's PDF to its billing contact, falling back to the
+    /// company's default billing contact. Invoices sent before are skipped
+    /// unless `force` is set. One invoice failing doesn't stop the rest.
+    pub async fn send_invoices(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        request: &SendInvoicesRequest,
+    ) -> AppResult<Vec<InvoiceSendResult>> {
+        let branding = self.tenants.get_branding(tenant_id).await?;
+
+        let mut invoice_ids = request.invoice_ids.clone();
+        let mut seen = std::collections::HashSet::new();
+        invoice_ids.retain(|id| seen.insert(*id));
+
+        let mut results = Vec::with_capacity(invoice_ids.len());
+        for invoice_id in invoice_ids {
+            let invoice = match self.get_invoice(tenant_id, invoice_id).await {
+                Ok(invoice) => invoice,
+                Err(AppError::NotFound(_)) => {
+                    results.push(InvoiceSendResult::skipped(invoice_id, InvoiceSendOutcome::NotFound));
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            if let Some(outcome) = invoice.send_skip(request.force) {
+                results.push(InvoiceSendResult::skipped(invoice_id, outcome));
+                continue;
+            }
+            let Some(recipient) = self.billing_email(tenant_id, invoice_id).await? else {
+                results.push(InvoiceSendResult::skipped(invoice_id, InvoiceSendOutcome::NoRecipient));
+                continue;
+            };
+
+            let document = InvoiceDocument::new(invoice, &branding);
+            results.push(self.send_invoice(tenant_id, user_id, &document, recipient, request.force).await?);
+        }
+
+        Ok(results)
+    }
+
+    async fn send_invoice(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        document: &InvoiceDocument,
+        recipient: String,
+        force: bool,
+    ) -> AppResult<InvoiceSendResult> {
+        let invoice = &document.invoice;
+        let previous = (invoice.status, invoice.sent_at);
+
+        // Claim the send first so a concurrent batch can't email it twice.
+        // The status moves on as in `Invoice::mark_sent`.
+        let claimed = sqlx::query(
+            r#"
+            UPDATE invoices
+            SET status = CASE WHEN status IN ('draft', 'pending') THEN 'sent' ELSE status END, sent_at = $1
+            WHERE tenant_id = $2 AND id = $3 AND status NOT IN ('void', 'written_off')
+              AND ($4 OR (sent_at IS NULL AND status <> 'sent'))
+            "#,
+        )
+        .bind(Utc::now())
+        .bind(tenant_id)
+        .bind(invoice.id)
+        .bind(force)
+        .execute(self.db.pool())
+        .await?
+        .rows_affected();
+        if claimed == 0 {
+            return Ok(InvoiceSendResult::skipped(invoice.id, InvoiceSendOutcome::AlreadySent));
+        }
+
+        let notification = match self
+            .notifications
+            .send_transactional_email(tenant_id, None, &document.email(&recipient))
+            .await
+        {
+            Ok(notification) => notification,
+            Err(e) => {
+                self.release_send(tenant_id, invoice.id, previous).await?;
+                return Err(e);
+            }
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO invoice_deliveries (tenant_id, invoice_id, notification_id, recipient, sent_by_id)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(invoice.id)
+        .bind(notification.id)
+        .bind(&recipient)
+        .bind(user_id)
+        .execute(self.db.pool())
+        .await?;
+
+        let outcome = match notification.status {
+            NotificationStatus::Suppressed => InvoiceSendOutcome::Suppressed,
+            NotificationStatus::Failed => InvoiceSendOutcome::Failed,
+            _ => InvoiceSendOutcome::Sent,
+        };
+        if outcome != InvoiceSendOutcome::Sent {
+            self.release_send(tenant_id, invoice.id, previous).await?;
+        }
+
+        Ok(InvoiceSendResult {
+            invoice_id: invoice.id,
+            outcome,
+            recipient: Some(recipient),
+            notification_id: Some(notification.id),
+            error: notification.error_message,
+        })
+    }
+
+    /// Put back the status and send time a claimed send replaced
+    async fn release_send(
+        &self,
+        tenant_id: Uuid,
+        invoice_id: Uuid,
+        (status, sent_at): (InvoiceStatus, Option<chrono::DateTime<Utc>>),
+    ) -> AppResult<()> {
+        sqlx::query("UPDATE invoices SET status = $1, sent_at = $2 WHERE tenant_id = $3 AND id = $4")
+            .bind(status.as_str())
+            .bind(sent_at)
+            .bind(tenant_id)
+            .bind(invoice_id)
+            .execute(self.db.pool())
+            .await?;
+        Ok(())
+    }
+
+    /// Email of the invoice's billing contact, or else the company's default one
+    async fn billing_email(&self, tenant_id: Uuid, invoice_id: Uuid) -> AppResult<Option<String>> {
+        let email: Option<Option<String>> = sqlx::query_scalar(
+            r#"
+            SELECT ct.email
+            FROM invoices i
+            JOIN companies c ON c.id = i.company_id
+            LEFT JOIN contacts ct ON ct.id = COALESCE(i.billing_contact_id, c.default_billing_contact_id)
+            WHERE i.tenant_id = $1 AND i.id = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(invoice_id)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        Ok(email.flatten().map(|email| email.trim().to_string()).filter(|email| !email.is_empty()))
+    }
+
+    /// Every email of an invoice, newest first, with bounces reported since
+    pub async fn invoice_deliveries(&self, tenant_id: Uuid, invoice_id: Uuid) -> AppResult<Vec<InvoiceDelivery>> {
+        self.get_invoice(tenant_id, invoice_id).await?;
+
+        let rows = sqlx::query_as::<_, InvoiceDeliveryRow>(
+            r#"
+            SELECT d.id, d.invoice_id, d.recipient, d.notification_id, n.status, n.error_message,
+                   (SELECT MAX(e.occurred_at) FROM email_events e
+                    WHERE e.notification_id = d.notification_id
+                      AND e.event_type IN ('hard_bounce', 'soft_bounce')) AS bounced_at,
+                   d.sent_by_id, d.created_at
+            FROM invoice_deliveries d
+            LEFT JOIN notifications n ON n.id = d.notification_id
+            WHERE d.tenant_id = $1 AND d.invoice_id = $2
+            ORDER BY d.created_at DESC
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(invoice_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Invoices matching `filter` with the total count. Lines are only loaded
+    /// when `with_lines` is set, so summary listings skip them.
+    pub async fn list_invoices(
+        &self,
+        tenant_id: Uuid,
+        filter: &InvoiceFilter,
+        pagination: &PaginationParams,
+        with_lines: bool,
+    ) -> AppResult<(Vec<Invoice>, u64)> {
+        let where_clause = "tenant_id = $1 AND ($2::UUID IS NULL OR company_id = $2) AND ($3::TEXT IS NULL OR status = $3)";
+        let count_query = format!("SELECT COUNT(*) FROM invoices WHERE {}", where_clause);
+        let order_by = pagination.order_by(
+            "invoice_date",
+            &["invoice_date", "due_date", "invoice_number", "total", "updated_at"],
+        );
+
+        let total: i64 = sqlx::query_scalar(&count_query)
+            .bind(tenant_id)
+            .bind(filter.company_id)
+            .bind(filter.status.map(|status| status.as_str()))
+            .fetch_one(self.db.pool())
+            .await?;
+
+        let page = pagination.clamped(total as u64);
+        let rows = sqlx::query_as::<_, InvoiceRow>(&format!(
+            "SELECT {} FROM invoices WHERE {} ORDER BY {}, created_at DESC LIMIT $4 OFFSET $5",
+            INVOICE_COLUMNS, where_clause, order_by
+        ))
+        .bind(tenant_id)
+        .bind(filter.company_id)
+        .bind(filter.status.map(|status| status.as_str()))
+        .bind(page.limit() as i32)
+        .bind(page.offset() as i32)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let mut lines: Vec<(Uuid, InvoiceLineRow)> = Vec::new();
+        if with_lines && !rows.is_empty() {
+            let invoice_ids: Vec<Uuid> = rows.iter().map(|row| row.id).collect();
+            lines = sqlx::query_as::<_, InvoiceLineWithInvoiceRow>(
+                r#"
+                SELECT invoice_id, id, line_type, description, quantity, unit_price, total, project_id, milestone_id
+                FROM invoice_lines
+                WHERE invoice_id = ANY($1)
+                ORDER BY sort_order, created_at
+                "#,
+            )
+            .bind(&invoice_ids)
+            .fetch_all(self.db.pool())
+            .await?
+            .into_iter()
+            .map(|row| (row.invoice_id, row.line))
+            .collect();
+        }
+
+        let invoices = rows
+            .into_iter()
+            .map(|row| {
+                let invoice_lines = lines
+                    .iter()
+                    .filter(|(invoice_id, _)| *invoice_id == row.id)
+                    .map(|(_, line)| line.clone().into())
+                    .collect();
+                row.into_invoice(invoice_lines)
+            })
+            .collect();
+
+        Ok((invoices, total as u64))
+    }
+
+    /// Invoice a completed milestone of a fixed-price project for its agreed
+    /// amount. A milestone is only ever billed once.
+    pub async fn invoice_project_milestone(
+        &self,
+        tenant_id: Uuid,
+        project_id: Uuid,
+        milestone_id: Uuid,
+    ) -> AppResult<Invoice> {
+        let project = self.get_project_billing(tenant_id, project_id).await?;
+        project.check_milestone_billing()?;
+        let milestone = self.get_milestone(tenant_id, project_id, milestone_id).await?;
+        milestone.check_invoiceable()?;
+
+        let line = NewInvoiceLine::project_service(
+            milestone.line_description(&project.name),
+            milestone.amount,
+            project_id,
+            Some(milestone.id),
+        );
+
+        let mut tx = self.db.pool().begin().await?;
+        let invoice_id = self.insert_invoice(&mut tx, tenant_id, &project, line).await?;
+
+        // Claim the milestone for this invoice; a concurrent request that got
+        // there first leaves nothing to update and the new invoice rolls back
+        let claimed = sqlx::query(
+            r#"
+            UPDATE project_milestones
+            SET invoice_id = $1, invoiced_at = NOW(), updated_at = NOW()
+            WHERE id = $2 AND tenant_id = $3 AND status = 'completed' AND invoice_id IS NULL
+            "#,
+        )
+        .bind(invoice_id)
+        .bind(milestone.id)
+        .bind(tenant_id)
+        .execute(&mut *tx)
+        .await?;
+
+        if claimed.rows_affected() == 0 {
+            return Err(AppError::Conflict(format!(
+                "Milestone '{}' has already been invoiced",
+                milestone.name
+            )));
+        }
+
+        tx.commit().await?;
+        self.get_invoice(tenant_id, invoice_id).await
+    }
+
+    /// Invoice the share of a progress-billed project's fixed fee earned since
+    /// its last progress invoice
+    pub async fn invoice_project_progress(&self, tenant_id: Uuid, project_id: Uuid) -> AppResult<Invoice> {
+        let project = self.get_project_billing(tenant_id, project_id).await?;
+        let progress = project.next_progress_invoice()?;
+
+        let line = NewInvoiceLine::project_service(
+            format!(
+                "{}: {}% of fixed fee ({}% complete)",
+                project.name,
+                progress.percent.normalize(),
+                progress.billed_through.normalize()
+            ),
+            progress.amount,
+            project_id,
+            None,
+        );
+
+        let mut tx = self.db.pool().begin().await?;
+        let invoice_id = self.insert_invoice(&mut tx, tenant_id, &project, line).await?;
+
+        // Only advance from the percentage this invoice was calculated from,
+        // so two concurrent requests can't bill the same progress
+        let advanced = sqlx::query(
+            r#"
+            UPDATE projects
+            SET progress_billed_percent = $1, updated_at = NOW()
+            WHERE id = $2 AND tenant_id = $3 AND progress_billed_percent = $4
+            "#,
+        )
+        .bind(progress.billed_through)
+        .bind(project_id)
+        .bind(tenant_id)
+        .bind(project.progress_billed_percent)
+        .execute(&mut *tx)
+        .await?;
+
+        if advanced.rows_affected() == 0 {
+            return Err(AppError::Conflict(format!(
+                "Progress on project '{}' has already been invoiced",
+                project.name
+            )));
+        }
+
+        tx.commit().await?;
+        self.get_invoice(tenant_id, invoice_id).await
+    }
+
+    /// Allocate the tenant's next invoice number
+    async fn next_invoice_number(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        tenant_id: Uuid,
+    ) -> AppResult<String> {
+        SequenceService::next_in(&mut **tx, tenant_id, SequenceKind::Invoice).await
+    }
+
+    /// Create a draft invoice to the project's company with a single line
+    async fn insert_invoice(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        tenant_id: Uuid,
+        project: &ProjectBilling,
+        line: NewInvoiceLine,
+    ) -> AppResult<Uuid> {
+        let company_id = project.company_id.ok_or_else(|| {
+            AppError::BadRequest(format!("Project '{}' has no company to invoice", project.name))
+        })?;
+
+        self.insert_draft_invoice(tx, tenant_id, company_id, &[line]).await
+    }
+
+    /// Create a draft invoice to a company, dated today in the tenant's time
+    /// zone and due under the company's payment terms
+    pub(super) async fn insert_draft_invoice(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        tenant_id: Uuid,
+        company_id: Uuid,
+        lines: &[NewInvoiceLine],
+    ) -> AppResult<Uuid> {
+        let payment_terms: Option<String> =
+            sqlx::query_scalar("SELECT payment_terms FROM companies WHERE id = $1 AND tenant_id = $2")
+                .bind(company_id)
+                .bind(tenant_id)
+                .fetch_optional(&mut **tx)
+                .await?
+                .flatten();
+        let payment_terms = payment_terms.unwrap_or_else(|| "net30".to_string());
+
+        let invoice_date = TenantTimezone::load(self.db.pool(), tenant_id).await?.local_date(Utc::now());
+        let due_date = invoice_date + Duration::days(payment_terms_days(Some(&payment_terms)));
+        let invoice_number = self.next_invoice_number(tx, tenant_id).await?;
+        let subtotal: Decimal = lines.iter().map(|line| line.total).sum();
+
+        let invoice_id = Uuid::new_v4();
+        sqlx::query(
+            r#"
+            INSERT INTO invoices (
+                id, tenant_id, invoice_number, company_id, status, invoice_date, due_date,
+                payment_terms, subtotal, total, balance_due
+            )
+            VALUES ($1, $2, $3, $4, 'draft', $5, $6, $7, $8, $8, $8)
+            "#,
+        )
+        .bind(invoice_id)
+        .bind(tenant_id)
+        .bind(&invoice_number)
+        .bind(company_id)
+        .bind(invoice_date)
+        .bind(due_date)
+        .bind(&payment_terms)
+        .bind(subtotal)
+        .execute(&mut **tx)
+        .await?;
+
+        for (sort_order, line) in lines.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO invoice_lines (
+                    invoice_id, line_type, description, quantity, unit_price, total, project_id, milestone_id,
+                    sort_order
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                "#,
+            )
+            .bind(invoice_id)
+            .bind(&line.line_type)
+            .bind(&line.description)
+            .bind(line.quantity)
+            .bind(line.unit_price)
+            .bind(line.total)
+            .bind(line.project_id)
+            .bind(line.milestone_id)
+            .bind(sort_order as i32)
+            .execute(&mut **tx)
+            .await?;
+        }
+
+        Ok(invoice_id)
+    }
+
+    // ========================================================================
+    // PAYMENTS
+    // ========================================================================
+
+    /// Record a payment against an invoice, then email the billing contact a
+    /// receipt. The payment stands even if the receipt can't be sent; the
+    /// outcome is reported alongside it.
+    pub async fn record_payment(
+        &self,
+        tenant_id: Uuid,
+        invoice_id: Uuid,
+        request: &RecordPaymentRequest,
+    ) -> AppResult<RecordedPayment> {
+        let mut invoice = self.get_invoice(tenant_id, invoice_id).await?;
+        let payment_date = match request.payment_date {
+            Some(date) => date,
+            None => TenantTimezone::load(self.db.pool(), tenant_id).await?.local_date(Utc::now()),
+        };
+
+        let mut tx = self.db.pool().begin().await?;
+
+        // Re-read the balance under lock so concurrent payments can't overpay
+        let (status, amount_paid, balance_due): (String, Decimal, Decimal) = sqlx::query_as(
+            "SELECT status, amount_paid, balance_due FROM invoices WHERE tenant_id = $1 AND id = $2 FOR UPDATE",
+        )
+        .bind(tenant_id)
+        .bind(invoice_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::not_found("Invoice"))?;
+        invoice.status = InvoiceStatus::from_str(&status).unwrap_or_default();
+        invoice.amount_paid = amount_paid;
+        invoice.balance_due = balance_due;
+
+        request.apply(&mut invoice)?;
+
+        let payment = sqlx::query_as::<_, PaymentRow>(&format!(
+            r#"
+            INSERT INTO payments (
+                tenant_id, invoice_id, company_id, payment_date, amount, payment_method, reference_number,
+                gateway_transaction_id, notes
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING {}
+            "#,
+            PAYMENT_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(invoice_id)
+        .bind(invoice.company_id)
+        .bind(payment_date)
+        .bind(request.amount)
+        .bind(request.payment_method.as_str())
+        .bind(&request.reference_number)
+        .bind(&request.gateway_transaction_id)
+        .bind(&request.notes)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE invoices
+            SET amount_paid = $1, balance_due = $2, status = $3, updated_at = NOW()
+            WHERE tenant_id = $4 AND id = $5
+            "#,
+        )
+        .bind(invoice.amount_paid)
+        .bind(invoice.balance_due)
+        .bind(invoice.status.as_str())
+        .bind(tenant_id)
+        .bind(invoice_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        // The payment is committed, so anything that stops the receipt is its
+        // outcome rather than the request's
+        let payment: Payment = payment.into();
+        let receipt = match self.send_receipt(tenant_id, &payment, &invoice).await {
+            Ok(receipt) => receipt,
+            Err(e) => {
+                tracing::warn!("Receipt for payment {} could not be sent: {}", payment.id, e);
+                InvoiceSendResult {
+                    invoice_id,
+                    outcome: InvoiceSendOutcome::Failed,
+                    recipient: None,
+                    notification_id: None,
+                    error: Some(e.to_string()),
+                }
+            }
+        };
+
+        Ok(RecordedPayment {
+            payment,
+            invoice,
+            receipt,
+        })
+    }
+
+    pub async fn get_payment(&self, tenant_id: Uuid, payment_id: Uuid) -> AppResult<Payment> {
+        let row = sqlx::query_as::<_, PaymentRow>(&format!(
+            "SELECT {} FROM payments WHERE tenant_id = $1 AND id = $2",
+            PAYMENT_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(payment_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::not_found("Payment"))?;
+
+        Ok(row.into())
+    }
+
+    /// A payment laid out as a receipt, under the tenant's branding. The
+    /// balance due is the invoice's current one.
+    pub async fn payment_receipt(&self, tenant_id: Uuid, payment_id: Uuid) -> AppResult<PaymentReceipt> {
+        let payment = self.get_payment(tenant_id, payment_id).await?;
+        let invoice_id = payment
+            .invoice_id
+            .ok_or_else(|| AppError::BadRequest("Payment was not made against an invoice".to_string()))?;
+        let invoice = self.get_invoice(tenant_id, invoice_id).await?;
+        let branding = self.tenants.get_branding(tenant_id).await?;
+
+        Ok(PaymentReceipt::new(payment, &invoice, &branding))
+    }
+
+    pub async fn generate_receipt_pdf(&self, tenant_id: Uuid, payment_id: Uuid) -> AppResult<Vec<u8>> {
+        Ok(self.payment_receipt(tenant_id, payment_id).await?.to_pdf())
+    }
+
+    /// Email a payment's receipt to the invoice's billing contact
+    async fn send_receipt(
+        &self,
+        tenant_id: Uuid,
+        payment: &Payment,
+        invoice: &Invoice,
+    ) -> AppResult<InvoiceSendResult> {
+        let branding = self.tenants.get_branding(tenant_id).await?;
+        let receipt = PaymentReceipt::new(payment.clone(), invoice, &branding);
+        let invoice_id = invoice.id;
+        let Some(recipient) = self.billing_email(tenant_id, invoice_id).await? else {
+            return Ok(InvoiceSendResult::skipped(invoice_id, InvoiceSendOutcome::NoRecipient));
+        };
+
+        let notification = match self
+            .notifications
+            .send_transactional_email(tenant_id, None, &receipt.email(&recipient))
+            .await
+        {
+            Ok(notification) => notification,
+            Err(e) => {
+                tracing::warn!("Receipt for payment {} could not be sent: {}", receipt.payment.id, e);
+                return Ok(InvoiceSendResult {
+                    invoice_id,
+                    outcome: InvoiceSendOutcome::Failed,
+                    recipient: Some(recipient),
+                    notification_id: None,
+                    error: Some(e.to_string()),
+                });
+            }
+        };
+
+        let outcome = match notification.status {
+            NotificationStatus::Suppressed => InvoiceSendOutcome::Suppressed,
+            NotificationStatus::Failed => InvoiceSendOutcome::Failed,
+            _ => InvoiceSendOutcome::Sent,
+        };
+
+        Ok(InvoiceSendResult {
+            invoice_id,
+            outcome,
+            recipient: Some(recipient),
+            notification_id: Some(notification.id),
+            error: notification.error_message,
+        })
+    }
+}
+
+// ============================================================================
+// DATABASE ROW TYPES
+// ============================================================================
+
+#[derive(sqlx::FromRow)]
+struct ProjectBillingRow {
+    id: Uuid,
+    name: String,
+    company_id: Option<Uuid>,
+    billing_method: Option<String>,
+    fixed_fee: Option<Decimal>,
+    fixed_fee_billing: String,
+    percent_complete: Decimal,
+    progress_billed_percent: Decimal,
+}
+
+impl From<ProjectBillingRow> for ProjectBilling {
+    fn from(row: ProjectBillingRow) -> Self {
+        Self {
+            project_id: row.id,
+            name: row.name,
+            company_id: row.company_id,
+            billing_method: row.billing_method.unwrap_or_else(|| "time_and_materials".to_string()),
+            fixed_fee: row.fixed_fee,
+            fixed_fee_billing: FixedFeeBilling::from_str(&row.fixed_fee_billing).unwrap_or_default(),
+            percent_complete: row.percent_complete,
+            progress_billed_percent: row.progress_billed_percent,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct MilestoneRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    project_id: Uuid,
+    name: String,
+    amount: Decimal,
+    status: String,
+    completed_at: Option<chrono::DateTime<chrono::Utc>>,
+    invoice_id: Option<Uuid>,
+    invoiced_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<MilestoneRow> for ProjectMilestone {
+    fn from(row: MilestoneRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            project_id: row.project_id,
+            name: row.name,
+            amount: row.amount,
+            status: MilestoneStatus::from_str(&row.status).unwrap_or_default(),
+            completed_at: row.completed_at,
+            invoice_id: row.invoice_id,
+            invoiced_at: row.invoiced_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct InvoiceRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    invoice_number: String,
+    company_id: Uuid,
+    status: String,
+    invoice_date: chrono::NaiveDate,
+    due_date: chrono::NaiveDate,
+    payment_terms: Option<String>,
+    subtotal: Decimal,
+    tax_amount: Decimal,
+    total: Decimal,
+    amount_paid: Decimal,
+    balance_due: Decimal,
+    currency: Option<String>,
+    notes: Option<String>,
+    sent_at: Option<chrono::DateTime<chrono::Utc>>,
+    created_at: chrono::DateTime<chrono::Utc>,
+    updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl InvoiceRow {
+    fn into_invoice(self, lines: Vec<InvoiceLine>) -> Invoice {
+        Invoice {
+            id: self.id,
+            tenant_id: self.tenant_id,
+            invoice_number: self.invoice_number,
+            company_id: self.company_id,
+            status: InvoiceStatus::from_str(&self.status).unwrap_or_default(),
+            invoice_date: self.invoice_date,
+            due_date: self.due_date,
+            payment_terms: self.payment_terms,
+            subtotal: self.subtotal,
+            tax_amount: self.tax_amount,
+            total: self.total,
+            amount_paid: self.amount_paid,
+            balance_due: self.balance_due,
+            currency: self.currency.unwrap_or_else(|| "USD".to_string()),
+            notes: self.notes,
+            lines,
+            sent_at: self.sent_at,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct InvoiceDeliveryRow {
+    id: Uuid,
+    invoice_id: Uuid,
+    recipient: String,
+    notification_id: Option<Uuid>,
+    status: Option<String>,
+    error_message: Option<String>,
+    bounced_at: Option<chrono::DateTime<chrono::Utc>>,
+    sent_by_id: Option<Uuid>,
+    created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<InvoiceDeliveryRow> for InvoiceDelivery {
+    fn from(row: InvoiceDeliveryRow) -> Self {
+        Self {
+            id: row.id,
+            invoice_id: row.invoice_id,
+            recipient: row.recipient,
+            notification_id: row.notification_id,
+            status: row.status.as_deref().and_then(NotificationStatus::from_str).unwrap_or_default(),
+            error_message: row.error_message,
+            bounced_at: row.bounced_at,
+            sent_by_id: row.sent_by_id,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow, Clone)]
+struct InvoiceLineRow {
+    id: Uuid,
+    line_type: String,
+    description: String,
+    quantity: Decimal,
+    unit_price: Decimal,
+    total: Decimal,
+    project_id: Option<Uuid>,
+    milestone_id: Option<Uuid>,
+}
+
+impl From<InvoiceLineRow> for InvoiceLine {
+    fn from(row: InvoiceLineRow) -> Self {
+        Self {
+            id: row.id,
+            line_type: row.line_type,
+            description: row.description,
+            quantity: row.quantity,
+            unit_price: row.unit_price,
+            total: row.total,
+            project_id: row.project_id,
+            milestone_id: row.milestone_id,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct InvoiceLineWithInvoiceRow {
+    invoice_id: Uuid,
+    #[sqlx(flatten)]
+    line: InvoiceLineRow,
+}
+
+#[derive(sqlx::FromRow)]
+struct PaymentRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    invoice_id: Option<Uuid>,
+    company_id: Uuid,
+    payment_date: chrono::NaiveDate,
+    amount: Decimal,
+    payment_method: String,
+    reference_number: Option<String>,
+    gateway_transaction_id: Option<String>,
+    notes: Option<String>,
+    created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<PaymentRow> for Payment {
+    fn from(row: PaymentRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            invoice_id: row.invoice_id,
+            company_id: row.company_id,
+            payment_date: row.payment_date,
+            amount: row.amount,
+            payment_method: PaymentMethod::from_str(&row.payment_method).unwrap_or(PaymentMethod::Other),
+            reference_number: row.reference_number,
+            gateway_transaction_id: row.gateway_transaction_id,
+            notes: row.notes,
+            created_at: row.created_at,
+        }
+    }
+}
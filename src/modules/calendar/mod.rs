@@ -1 +1,24 @@
-//! calendar module placeholder
+//! Calendar Module
+//!
+//! Technician availability, appointments, self-service booking and
+//! two-way sync with external calendars.
+
+mod models;
+#[cfg(feature = "server")]
+mod service;
+#[cfg(feature = "server")]
+mod routes;
+#[cfg(feature = "server")]
+mod sync;
+
+pub use models::*;
+#[cfg(feature = "server")]
+pub use service::CalendarService;
+#[cfg(feature = "server")]
+pub use routes::{booking_routes, calendar_routes};
+#[cfg(feature = "server")]
+pub use sync::{
+    CalendarSyncProvider, CalendarSyncService, GoogleCalendarProvider, MicrosoftGraphProvider,
+};
+#[cfg(feature = "server")]
+pub(crate) use sync::{SyncReconciliationRow, RECONCILIATION_COLUMNS};
@@ -0,0 +1,890 @@
+//! Calendar models and types
+
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::utils::error::AppError;
+use crate::utils::reconcile::{ConflictPolicy, Reconciliation, SyncDecision, Versioned};
+use crate::utils::timezone::TenantTimezone;
+
+// ============================================================================
+// AVAILABILITY
+// ============================================================================
+
+/// A weekly working window from `user_availability`, in the schedule's time zone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AvailabilityWindow {
+    /// 0 = Sunday
+    pub day_of_week: u32,
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime,
+}
+
+impl AvailabilityWindow {
+    /// Whether a single-day span, in UTC, falls entirely inside one of the windows
+    pub fn covers(windows: &[AvailabilityWindow], start: DateTime<Utc>, end: DateTime<Utc>) -> bool {
+        Self::covers_local(windows, start.naive_utc(), end.naive_utc())
+    }
+
+    /// Whether a single-day span of wall-clock time falls entirely inside one
+    /// of the windows
+    pub fn covers_local(windows: &[AvailabilityWindow], start: NaiveDateTime, end: NaiveDateTime) -> bool {
+        if end <= start || end.date() != start.date() {
+            return false;
+        }
+
+        let day = start.weekday().num_days_from_sunday();
+        windows.iter().any(|w| {
+            w.day_of_week == day && w.start_time <= start.time() && end.time() <= w.end_time
+        })
+    }
+
+    pub fn minutes(&self) -> i64 {
+        (self.end_time - self.start_time).num_minutes().max(0)
+    }
+}
+
+/// A technician's weekly schedule: the days and hours they work, in their
+/// own time zone. Part-timers and shift workers have fewer or different
+/// windows than the standard workday.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkingHours {
+    pub timezone: TenantTimezone,
+    pub windows: Vec<AvailabilityWindow>,
+}
+
+impl WorkingHours {
+    /// Monday to Friday, 09:00 to 17:00; what's assumed for a technician
+    /// without a schedule
+    pub fn standard(timezone: TenantTimezone) -> Self {
+        let (start_time, end_time) = (
+            NaiveTime::from_hms_opt(9, 0, 0).unwrap_or_default(),
+            NaiveTime::from_hms_opt(17, 0, 0).unwrap_or_default(),
+        );
+        Self {
+            timezone,
+            windows: (1..=5)
+                .map(|day_of_week| AvailabilityWindow {
+                    day_of_week,
+                    start_time,
+                    end_time,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+
+    /// The schedule, or the standard workday in its time zone when it has no
+    /// windows
+    pub fn or_standard(self) -> Self {
+        if self.is_empty() {
+            Self::standard(self.timezone)
+        } else {
+            self
+        }
+    }
+
+    /// Whether `[start, end)` falls inside the schedule
+    pub fn covers(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> bool {
+        AvailabilityWindow::covers_local(
+            &self.windows,
+            self.timezone.local_datetime(start),
+            self.timezone.local_datetime(end),
+        )
+    }
+
+    /// Whether an appointment at `[start, end)` is outside the technician's
+    /// working hours
+    pub fn is_off_shift(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> bool {
+        !self.covers(start, end)
+    }
+
+    /// Whether the technician is working at a local date and time
+    pub fn is_working_at(&self, date: NaiveDate, time: NaiveTime) -> bool {
+        let day = date.weekday().num_days_from_sunday();
+        self.windows
+            .iter()
+            .any(|w| w.day_of_week == day && w.start_time <= time && time < w.end_time)
+    }
+
+    /// Scheduled minutes across the given local days
+    pub fn available_minutes(&self, days: &[NaiveDate]) -> i64 {
+        days.iter()
+            .map(|date| {
+                let day = date.weekday().num_days_from_sunday();
+                self.windows
+                    .iter()
+                    .filter(|w| w.day_of_week == day)
+                    .map(AvailabilityWindow::minutes)
+                    .sum::<i64>()
+            })
+            .sum()
+    }
+}
+
+/// Replace a technician's weekly schedule
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct SetWorkingHoursRequest {
+    /// IANA time zone the windows are in
+    #[validate(length(min = 1, max = 50))]
+    pub timezone: String,
+    pub windows: Vec<AvailabilityWindow>,
+}
+
+impl SetWorkingHoursRequest {
+    pub fn check(&self) -> Result<WorkingHours, AppError> {
+        let timezone = TenantTimezone::parse(&self.timezone)
+            .ok_or_else(|| AppError::validation_field("timezone", "Unknown time zone"))?;
+
+        for window in &self.windows {
+            if window.day_of_week > 6 {
+                return Err(AppError::validation_field("windows", "Day of week must be 0 (Sunday) to 6"));
+            }
+            if window.end_time <= window.start_time {
+                return Err(AppError::validation_field("windows", "Each window must end after it starts"));
+            }
+        }
+
+        Ok(WorkingHours {
+            timezone,
+            windows: self.windows.clone(),
+        })
+    }
+}
+
+/// Whether an appointment fits the technician's working hours
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ShiftCheck {
+    pub technician_id: Uuid,
+    pub off_shift: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ShiftCheckQuery {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+/// Whether two half-open time ranges intersect
+pub fn overlaps(
+    a_start: DateTime<Utc>,
+    a_end: DateTime<Utc>,
+    b_start: DateTime<Utc>,
+    b_end: DateTime<Utc>,
+) -> bool {
+    a_start < b_end && b_start < a_end
+}
+
+// ============================================================================
+// BOOKING
+// ============================================================================
+
+/// Bookable slot status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SlotStatus {
+    #[default]
+    Available,
+    Booked,
+    Cancelled,
+}
+
+impl SlotStatus {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "available" => Some(Self::Available),
+            "booked" => Some(Self::Booked),
+            "cancelled" => Some(Self::Cancelled),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Available => "available",
+            Self::Booked => "booked",
+            Self::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// A technician time slot that contacts may book
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookableSlot {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub technician_id: Uuid,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub status: SlotStatus,
+    pub invitation_id: Option<Uuid>,
+    pub appointment_id: Option<Uuid>,
+    pub ticket_id: Option<Uuid>,
+    pub booked_at: Option<DateTime<Utc>>,
+}
+
+impl BookableSlot {
+    /// Claim the slot for an invitation. The service locks the slot's row
+    /// first, so only one concurrent booking can win.
+    pub fn claim(&mut self, invitation_id: Uuid, now: DateTime<Utc>) -> Result<(), AppError> {
+        if self.status != SlotStatus::Available {
+            return Err(AppError::Conflict("Booking for this slot".to_string()));
+        }
+        if self.start_time <= now {
+            return Err(AppError::BadRequest("This slot has already started".to_string()));
+        }
+
+        self.status = SlotStatus::Booked;
+        self.invitation_id = Some(invitation_id);
+        self.booked_at = Some(now);
+        Ok(())
+    }
+}
+
+/// Tokenized invitation letting a contact book a slot without logging in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookingInvitation {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub company_id: Uuid,
+    pub contact_id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub expires_at: DateTime<Utc>,
+    pub created_by_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+impl BookingInvitation {
+    pub fn ensure_valid(&self, now: DateTime<Utc>) -> Result<(), AppError> {
+        if now >= self.expires_at {
+            return Err(AppError::BadRequest("This booking link has expired".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Publish a bookable slot
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CreateSlotRequest {
+    pub technician_id: Uuid,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+}
+
+/// Invite a contact to book an appointment
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CreateBookingInvitationRequest {
+    pub contact_id: Uuid,
+    #[validate(length(min = 1, max = 255))]
+    pub title: String,
+    pub description: Option<String>,
+    /// Defaults to 14 days
+    #[validate(range(min = 1, max = 90))]
+    pub expires_in_days: Option<i64>,
+}
+
+/// Public booking or reschedule request
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct BookSlotRequest {
+    pub slot_id: Uuid,
+}
+
+/// A booking as its manage link shows it, with the slots it can move to
+#[derive(Debug, Clone, Serialize)]
+pub struct ManagedBooking {
+    pub slot: BookableSlot,
+    pub available_slots: Vec<BookableSlot>,
+}
+
+/// Returned to the contact after booking
+#[derive(Debug, Clone, Serialize)]
+pub struct BookingConfirmation {
+    pub slot: BookableSlot,
+    pub appointment_id: Uuid,
+    pub ticket_id: Option<Uuid>,
+}
+
+// ============================================================================
+// EXTERNAL CALENDAR SYNC
+// ============================================================================
+
+/// External calendar provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CalendarProvider {
+    #[default]
+    Google,
+    Microsoft,
+}
+
+impl CalendarProvider {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "google" => Some(Self::Google),
+            "microsoft" => Some(Self::Microsoft),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Google => "google",
+            Self::Microsoft => "microsoft",
+        }
+    }
+}
+
+/// A user's link to an external calendar. The access token is stored
+/// encrypted and never leaves the service.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarConnection {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub provider: CalendarProvider,
+    pub calendar_id: String,
+    #[serde(skip_serializing)]
+    pub sync_token: Option<String>,
+    pub is_active: bool,
+    pub last_sync_at: Option<DateTime<Utc>>,
+    pub sync_status: String,
+    pub last_error: Option<String>,
+    /// How an appointment edited both here and in the remote calendar is settled
+    pub conflict_policy: ConflictPolicy,
+}
+
+/// An event as reported by the remote calendar
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExternalEvent {
+    pub external_id: String,
+    pub title: Option<String>,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub etag: Option<String>,
+    /// False for events marked free/transparent, which do not block availability
+    pub busy: bool,
+    /// When the event was last edited remotely, if the provider says
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// A single change in a provider's incremental feed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExternalChange {
+    Upserted(ExternalEvent),
+    Removed(String),
+}
+
+impl ExternalChange {
+    pub fn external_id(&self) -> &str {
+        match self {
+            Self::Upserted(event) => &event.external_id,
+            Self::Removed(id) => id,
+        }
+    }
+}
+
+/// Changes since the last sync token
+#[derive(Debug, Clone)]
+pub struct SyncDelta {
+    pub changes: Vec<ExternalChange>,
+    /// Token to store for the next incremental sync
+    pub next_sync_token: String,
+    /// The stored token was rejected and this is a complete listing
+    pub full_resync: bool,
+}
+
+/// Stored link between a remote event and the PSA
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventMapping {
+    pub external_event_id: String,
+    /// Set for events pushed from a PSA appointment, empty for pulled busy blocks
+    pub appointment_id: Option<Uuid>,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub etag: Option<String>,
+    pub synced_at: DateTime<Utc>,
+}
+
+/// What to do locally with a pulled delta
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeltaPlan {
+    /// Busy blocks to insert or refresh
+    pub upsert: Vec<ExternalEvent>,
+    /// Busy blocks to drop, by external id
+    pub remove: Vec<String>,
+    /// Appointments whose pushed event was deleted remotely; the PSA calendar is
+    /// authoritative so these are pushed again
+    pub repush: Vec<Uuid>,
+    /// Appointments whose pushed event was moved remotely, to be reconciled
+    /// against the appointment under the connection's conflict policy
+    pub remote_changes: Vec<(Uuid, ExternalEvent)>,
+}
+
+impl SyncDelta {
+    /// Reconcile the delta against existing mappings.
+    ///
+    /// Repeated entries for the same event collapse to the last one, echoes of
+    /// events we pushed are ignored, and unchanged etags are skipped, so applying
+    /// the same delta twice never duplicates anything. A pushed event whose
+    /// times no longer match what we pushed was edited remotely.
+    pub fn plan(&self, mappings: &[EventMapping]) -> DeltaPlan {
+        let by_id: HashMap<&str, &EventMapping> = mappings
+            .iter()
+            .map(|m| (m.external_event_id.as_str(), m))
+            .collect();
+
+        let mut seen = HashSet::new();
+        let mut latest: Vec<&ExternalChange> = self
+            .changes
+            .iter()
+            .rev()
+            .filter(|c| seen.insert(c.external_id()))
+            .collect();
+        latest.reverse();
+
+        let mut plan = DeltaPlan::default();
+        for change in latest {
+            let mapping = by_id.get(change.external_id()).copied();
+
+            if let Some((m, appointment_id)) = mapping.and_then(|m| m.appointment_id.map(|id| (m, id))) {
+                match change {
+                    ExternalChange::Removed(_) => plan.repush.push(appointment_id),
+                    ExternalChange::Upserted(event)
+                        if (event.start_time, event.end_time) != (m.start_time, m.end_time) =>
+                    {
+                        plan.remote_changes.push((appointment_id, event.clone()));
+                    }
+                    ExternalChange::Upserted(_) => {}
+                }
+                continue;
+            }
+
+            match change {
+                ExternalChange::Upserted(event) if event.busy => {
+                    let unchanged = mapping
+                        .is_some_and(|m| m.etag.is_some() && m.etag == event.etag);
+                    if !unchanged {
+                        plan.upsert.push(event.clone());
+                    }
+                }
+                _ => {
+                    if mapping.is_some() {
+                        plan.remove.push(change.external_id().to_string());
+                    }
+                }
+            }
+        }
+
+        if self.full_resync {
+            for mapping in mappings {
+                if seen.contains(mapping.external_event_id.as_str()) {
+                    continue;
+                }
+                match mapping.appointment_id {
+                    Some(appointment_id) => plan.repush.push(appointment_id),
+                    None => plan.remove.push(mapping.external_event_id.clone()),
+                }
+            }
+        }
+
+        plan
+    }
+}
+
+/// A PSA appointment as it is pushed to the remote calendar
+#[derive(Debug, Clone)]
+pub struct OutboundEvent {
+    pub appointment_id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub cancelled: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl OutboundEvent {
+    /// Settle an appointment moved remotely against its local copy. Only the
+    /// times are compared; the title and notes are always the PSA's. A
+    /// provider that reports no edit time is taken to have edited it now.
+    pub fn reconcile(
+        &self,
+        remote: &ExternalEvent,
+        mapping: &EventMapping,
+        policy: ConflictPolicy,
+        now: DateTime<Utc>,
+    ) -> Reconciliation {
+        crate::utils::reconcile::reconcile(
+            &Versioned::new(EventTimes::of_appointment(self), self.updated_at),
+            &Versioned::new(EventTimes::of_event(remote), remote.updated_at.unwrap_or(now)),
+            Some(mapping.synced_at),
+            policy,
+        )
+    }
+}
+
+/// The part of an appointment two-way sync reconciles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventTimes {
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+}
+
+impl EventTimes {
+    pub fn of_appointment(event: &OutboundEvent) -> Self {
+        Self {
+            start_time: event.start_time,
+            end_time: event.end_time,
+        }
+    }
+
+    pub fn of_event(event: &ExternalEvent) -> Self {
+        Self {
+            start_time: event.start_time,
+            end_time: event.end_time,
+        }
+    }
+}
+
+/// A recorded decision on a record an integration found edited remotely
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncReconciliation {
+    pub id: Uuid,
+    pub integration: String,
+    pub connection_id: Option<Uuid>,
+    pub entity_type: String,
+    pub local_id: Uuid,
+    pub external_id: String,
+    pub policy: ConflictPolicy,
+    pub decision: SyncDecision,
+    /// Both sides changed since the last sync
+    pub conflict: bool,
+    pub local_value: serde_json::Value,
+    pub remote_value: serde_json::Value,
+    pub local_modified_at: DateTime<Utc>,
+    pub remote_modified_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub resolved_by_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Settle a reconciliation flagged for review by keeping one side
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResolveReconciliationRequest {
+    /// `push_local` or `pull_remote`
+    pub decision: SyncDecision,
+}
+
+/// Remote operation needed to bring an appointment in sync
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PushAction {
+    Create,
+    Update(String),
+    Delete(String),
+    Skip,
+}
+
+impl PushAction {
+    /// Mapped appointments are updated in place rather than created again
+    pub fn plan(event: &OutboundEvent, mapping: Option<&EventMapping>) -> Self {
+        match (mapping, event.cancelled) {
+            (Some(m), true) => Self::Delete(m.external_event_id.clone()),
+            (None, true) => Self::Skip,
+            (Some(m), false) if event.updated_at > m.synced_at => {
+                Self::Update(m.external_event_id.clone())
+            }
+            (Some(_), false) => Self::Skip,
+            (None, false) => Self::Create,
+        }
+    }
+}
+
+/// Counts from one sync run
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SyncSummary {
+    pub pulled: usize,
+    pub removed: usize,
+    pub pushed: usize,
+    pub deleted: usize,
+    /// Appointments edited on both sides, however they were settled
+    pub conflicts: usize,
+}
+
+/// Connect an external calendar using a token from the provider's OAuth flow
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct ConnectCalendarRequest {
+    pub provider: CalendarProvider,
+    #[validate(length(min = 1, max = 255))]
+    pub calendar_id: Option<String>,
+    #[validate(length(min = 1))]
+    pub access_token: String,
+    /// Lets sync fetch new access tokens once this one expires
+    #[validate(length(min = 1))]
+    pub refresh_token: Option<String>,
+    /// Defaults to the PSA copy winning
+    pub conflict_policy: Option<ConflictPolicy>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn sample_slot() -> BookableSlot {
+        let start = Utc::now() + Duration::days(2);
+        BookableSlot {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            technician_id: Uuid::new_v4(),
+            start_time: start,
+            end_time: start + Duration::hours(1),
+            status: SlotStatus::Available,
+            invitation_id: None,
+            appointment_id: None,
+            ticket_id: None,
+            booked_at: None,
+        }
+    }
+
+    #[test]
+    fn test_successful_booking() {
+        let mut slot = sample_slot();
+        let invitation_id = Uuid::new_v4();
+
+        slot.claim(invitation_id, Utc::now()).unwrap();
+
+        assert_eq!(slot.status, SlotStatus::Booked);
+        assert_eq!(slot.invitation_id, Some(invitation_id));
+        assert!(slot.booked_at.is_some());
+    }
+
+    #[test]
+    fn test_second_booking_of_same_slot_fails() {
+        let mut slot = sample_slot();
+        slot.claim(Uuid::new_v4(), Utc::now()).unwrap();
+
+        let second = slot.claim(Uuid::new_v4(), Utc::now());
+        assert!(matches!(second, Err(AppError::Conflict(_))));
+    }
+
+    #[test]
+    fn test_slot_within_availability() {
+        // 2024-06-03 is a Monday
+        let windows = [AvailabilityWindow {
+            day_of_week: 1,
+            start_time: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            end_time: NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+        }];
+        let at = |h, m| Utc.with_ymd_and_hms(2024, 6, 3, h, m, 0).unwrap();
+
+        assert!(AvailabilityWindow::covers(&windows, at(9, 0), at(10, 0)));
+        assert!(AvailabilityWindow::covers(&windows, at(16, 0), at(17, 0)));
+        assert!(!AvailabilityWindow::covers(&windows, at(16, 30), at(17, 30)));
+        assert!(!AvailabilityWindow::covers(&windows, at(8, 0), at(9, 0)));
+
+        // Tuesday has no window
+        let tuesday = Utc.with_ymd_and_hms(2024, 6, 4, 10, 0, 0).unwrap();
+        assert!(!AvailabilityWindow::covers(&windows, tuesday, tuesday + Duration::hours(1)));
+    }
+
+    #[test]
+    fn test_appointment_outside_working_hours_is_off_shift() {
+        // Tuesday and Thursday afternoons in Chicago
+        let afternoon = |day_of_week| AvailabilityWindow {
+            day_of_week,
+            start_time: NaiveTime::from_hms_opt(13, 0, 0).unwrap(),
+            end_time: NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+        };
+        let hours = WorkingHours {
+            timezone: TenantTimezone::parse("America/Chicago").unwrap(),
+            windows: vec![afternoon(2), afternoon(4)],
+        };
+
+        // 2024-06-04 is a Tuesday; 19:00 UTC is 14:00 CDT
+        let tuesday = |h| Utc.with_ymd_and_hms(2024, 6, 4, h, 0, 0).unwrap();
+        assert!(!hours.is_off_shift(tuesday(19), tuesday(20)));
+        // 10:00 CDT, before their shift, though inside the standard workday
+        assert!(hours.is_off_shift(tuesday(15), tuesday(16)));
+        assert!(!WorkingHours::standard(hours.timezone).is_off_shift(tuesday(15), tuesday(16)));
+        // Wednesday is not one of their days
+        let wednesday = Utc.with_ymd_and_hms(2024, 6, 5, 19, 0, 0).unwrap();
+        assert!(hours.is_off_shift(wednesday, wednesday + Duration::hours(1)));
+
+        let thursday = NaiveDate::from_ymd_opt(2024, 6, 6).unwrap();
+        assert!(hours.is_working_at(thursday, NaiveTime::from_hms_opt(17, 59, 0).unwrap()));
+        assert!(!hours.is_working_at(thursday, NaiveTime::from_hms_opt(18, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn test_overlaps() {
+        let base = Utc.with_ymd_and_hms(2024, 6, 3, 9, 0, 0).unwrap();
+        let hour = Duration::hours(1);
+
+        assert!(overlaps(base, base + hour, base + Duration::minutes(30), base + hour * 2));
+        // Back-to-back slots do not overlap
+        assert!(!overlaps(base, base + hour, base + hour, base + hour * 2));
+    }
+
+    fn busy_event(id: &str, etag: &str) -> ExternalEvent {
+        let start = Utc.with_ymd_and_hms(2024, 6, 3, 13, 0, 0).unwrap();
+        ExternalEvent {
+            external_id: id.to_string(),
+            title: Some("Dentist".to_string()),
+            start_time: start,
+            end_time: start + Duration::hours(1),
+            etag: Some(etag.to_string()),
+            busy: true,
+            updated_at: None,
+        }
+    }
+
+    fn mapping(id: &str, etag: &str, appointment_id: Option<Uuid>) -> EventMapping {
+        let event = busy_event(id, etag);
+        EventMapping {
+            external_event_id: id.to_string(),
+            appointment_id,
+            start_time: event.start_time,
+            end_time: event.end_time,
+            etag: event.etag,
+            synced_at: Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_event_mapping_dedup() {
+        let appointment_id = Uuid::new_v4();
+        let mappings = vec![mapping("pushed", "1", Some(appointment_id))];
+
+        let delta = SyncDelta {
+            changes: vec![
+                // Echo of the event we pushed
+                ExternalChange::Upserted(busy_event("pushed", "2")),
+                // Same remote event reported twice in one feed
+                ExternalChange::Upserted(busy_event("new", "1")),
+                ExternalChange::Upserted(busy_event("new", "2")),
+            ],
+            next_sync_token: "t2".to_string(),
+            full_resync: false,
+        };
+
+        let plan = delta.plan(&mappings);
+        assert_eq!(plan.upsert, vec![busy_event("new", "2")]);
+        assert!(plan.remove.is_empty());
+        assert!(plan.repush.is_empty());
+
+        // A mapped appointment is updated, never created a second time
+        let mut outbound = OutboundEvent {
+            appointment_id,
+            title: "Onsite".to_string(),
+            description: None,
+            location: None,
+            start_time: Utc::now(),
+            end_time: Utc::now() + Duration::hours(1),
+            cancelled: false,
+            updated_at: Utc.with_ymd_and_hms(2024, 5, 1, 0, 0, 0).unwrap(),
+        };
+        assert_eq!(PushAction::plan(&outbound, Some(&mappings[0])), PushAction::Skip);
+        outbound.updated_at = Utc.with_ymd_and_hms(2024, 6, 2, 0, 0, 0).unwrap();
+        assert_eq!(
+            PushAction::plan(&outbound, Some(&mappings[0])),
+            PushAction::Update("pushed".to_string())
+        );
+        assert_eq!(PushAction::plan(&outbound, None), PushAction::Create);
+    }
+
+    #[test]
+    fn test_incremental_delta_applied() {
+        let appointment_id = Uuid::new_v4();
+        let mappings = vec![
+            mapping("changed", "1", None),
+            mapping("unchanged", "1", None),
+            mapping("deleted", "1", None),
+            mapping("pushed", "1", Some(appointment_id)),
+        ];
+
+        let delta = SyncDelta {
+            changes: vec![
+                ExternalChange::Upserted(busy_event("changed", "2")),
+                ExternalChange::Upserted(busy_event("unchanged", "1")),
+                ExternalChange::Removed("deleted".to_string()),
+                ExternalChange::Removed("pushed".to_string()),
+                ExternalChange::Removed("never-seen".to_string()),
+                ExternalChange::Upserted(busy_event("added", "1")),
+            ],
+            next_sync_token: "t2".to_string(),
+            full_resync: false,
+        };
+
+        let plan = delta.plan(&mappings);
+        assert_eq!(plan.upsert, vec![busy_event("changed", "2"), busy_event("added", "1")]);
+        assert_eq!(plan.remove, vec!["deleted".to_string()]);
+        assert_eq!(plan.repush, vec![appointment_id]);
+    }
+
+    #[test]
+    fn test_remote_move_of_pushed_appointment_is_reconciled() {
+        let appointment_id = Uuid::new_v4();
+        let mappings = vec![mapping("pushed", "1", Some(appointment_id))];
+
+        let mut moved = busy_event("pushed", "2");
+        moved.start_time += Duration::hours(2);
+        moved.end_time += Duration::hours(2);
+        moved.updated_at = Some(Utc.with_ymd_and_hms(2024, 6, 2, 12, 0, 0).unwrap());
+
+        let delta = SyncDelta {
+            changes: vec![ExternalChange::Upserted(moved.clone())],
+            next_sync_token: "t2".to_string(),
+            full_resync: false,
+        };
+        let plan = delta.plan(&mappings);
+        assert!(plan.upsert.is_empty());
+        assert_eq!(plan.remote_changes, vec![(appointment_id, moved.clone())]);
+
+        // Also edited locally, earlier than the remote edit
+        let local = OutboundEvent {
+            appointment_id,
+            title: "Onsite".to_string(),
+            description: None,
+            location: None,
+            start_time: mappings[0].start_time + Duration::hours(1),
+            end_time: mappings[0].end_time + Duration::hours(1),
+            cancelled: false,
+            updated_at: Utc.with_ymd_and_hms(2024, 6, 2, 9, 0, 0).unwrap(),
+        };
+        let now = Utc.with_ymd_and_hms(2024, 6, 3, 0, 0, 0).unwrap();
+        let decide = |policy| local.reconcile(&moved, &mappings[0], policy, now).decision;
+        assert_eq!(decide(ConflictPolicy::LastWriteWins), SyncDecision::PullRemote);
+        assert_eq!(decide(ConflictPolicy::LocalWins), SyncDecision::PushLocal);
+        assert_eq!(decide(ConflictPolicy::RemoteWins), SyncDecision::PullRemote);
+        assert_eq!(decide(ConflictPolicy::FlagForReview), SyncDecision::FlagForReview);
+    }
+
+    #[test]
+    fn test_full_resync_drops_missing_events() {
+        let appointment_id = Uuid::new_v4();
+        let mappings = vec![
+            mapping("kept", "1", None),
+            mapping("gone", "1", None),
+            mapping("pushed", "1", Some(appointment_id)),
+        ];
+
+        let delta = SyncDelta {
+            changes: vec![ExternalChange::Upserted(busy_event("kept", "1"))],
+            next_sync_token: "t1".to_string(),
+            full_resync: true,
+        };
+
+        let plan = delta.plan(&mappings);
+        assert!(plan.upsert.is_empty());
+        assert_eq!(plan.remove, vec!["gone".to_string()]);
+        assert_eq!(plan.repush, vec![appointment_id]);
+    }
+}
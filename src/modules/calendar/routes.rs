@@ -0,0 +1,389 @@
+//! Calendar API routes
+
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::{Html, IntoResponse, Response},
+    routing::{get, post, put},
+    Json, Router,
+};
+use std::sync::Arc;
+use validator::Validate;
+
+use uuid::Uuid;
+
+use super::{
+    BookSlotRequest, BookableSlot, BookingInvitation, CalendarConnection,
+    CalendarService, CalendarSyncService, ConnectCalendarRequest, CreateBookingInvitationRequest,
+    CreateSlotRequest, ResolveReconciliationRequest, SetWorkingHoursRequest, ShiftCheck, ShiftCheckQuery,
+    SyncReconciliation, SyncSummary, WorkingHours,
+};
+use crate::api::tenant_resolution::CurrentTenant;
+use crate::modules::auth::{RequireAuth, RequireManager};
+use crate::utils::error::AppResult;
+use crate::utils::public_page::{self, FormOrJson};
+
+#[derive(Clone)]
+pub struct CalendarRouterState {
+    pub calendar_service: Arc<CalendarService>,
+}
+
+#[derive(Clone)]
+pub struct CalendarStaffRouterState {
+    pub calendar_service: Arc<CalendarService>,
+    pub sync_service: Arc<CalendarSyncService>,
+}
+
+/// Create the staff calendar router
+pub fn calendar_routes(
+    calendar_service: CalendarService,
+    sync_service: CalendarSyncService,
+) -> Router {
+    let state = CalendarStaffRouterState {
+        calendar_service: Arc::new(calendar_service),
+        sync_service: Arc::new(sync_service),
+    };
+
+    Router::new()
+        .route("/slots", get(list_slots))
+        .route("/slots", post(create_slot))
+        .route("/booking-invitations", post(create_invitation))
+        // Technician working hours
+        .route("/working-hours/:user_id", get(get_working_hours))
+        .route("/working-hours/:user_id", put(set_working_hours))
+        .route("/working-hours/:user_id/shift-check", get(check_shift))
+        // External calendar sync
+        .route("/connections", get(list_connections))
+        .route("/connections", post(connect_calendar))
+        .route("/connections/:connection_id/sync", post(sync_connection))
+        .route("/connections/:connection_id/reconciliations", get(list_reconciliations))
+        .route("/reconciliations/:reconciliation_id/resolve", post(resolve_reconciliation))
+        .with_state(state)
+}
+
+/// Create the public booking router. Requests are authorized by the invitation
+/// or manage token in the path rather than a session.
+pub fn booking_routes(calendar_service: CalendarService) -> Router {
+    let state = CalendarRouterState {
+        calendar_service: Arc::new(calendar_service),
+    };
+
+    // The pages linked from emails post their forms back to the JSON routes
+    Router::new()
+        .route("/:token/slots", get(invitation_slots))
+        .route("/:token", get(booking_page))
+        .route("/:token", post(book_slot))
+        .route("/manage/:token", get(manage_booking_page))
+        .route("/manage/:token/cancel", post(cancel_booking))
+        .route("/manage/:token/reschedule", post(reschedule_booking))
+        .with_state(state)
+}
+
+// ============================================================================
+// SLOT HANDLERS
+// ============================================================================
+
+async fn list_slots(
+    State(state): State<CalendarStaffRouterState>,
+    RequireAuth(user): RequireAuth,
+) -> AppResult<Json<Vec<BookableSlot>>> {
+    let slots = state
+        .calendar_service
+        .list_available_slots(user.tenant_id)
+        .await?;
+
+    Ok(Json(slots))
+}
+
+async fn create_slot(
+    State(state): State<CalendarStaffRouterState>,
+    RequireAuth(user): RequireAuth,
+    Json(request): Json<CreateSlotRequest>,
+) -> AppResult<Json<BookableSlot>> {
+    request.validate()?;
+
+    let slot = state
+        .calendar_service
+        .create_bookable_slot(user.tenant_id, user.id, &request)
+        .await?;
+
+    Ok(Json(slot))
+}
+
+async fn create_invitation(
+    State(state): State<CalendarStaffRouterState>,
+    RequireAuth(user): RequireAuth,
+    Json(request): Json<CreateBookingInvitationRequest>,
+) -> AppResult<Json<BookingInvitation>> {
+    request.validate()?;
+
+    let invitation = state
+        .calendar_service
+        .create_invitation(user.tenant_id, user.id, &request)
+        .await?;
+
+    Ok(Json(invitation))
+}
+
+// ============================================================================
+// WORKING HOURS HANDLERS
+// ============================================================================
+
+async fn get_working_hours(
+    State(state): State<CalendarStaffRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(user_id): Path<Uuid>,
+) -> AppResult<Json<WorkingHours>> {
+    let hours = state.calendar_service.working_hours(user.tenant_id, user_id).await?;
+    Ok(Json(hours))
+}
+
+async fn set_working_hours(
+    State(state): State<CalendarStaffRouterState>,
+    RequireManager(user, _): RequireManager,
+    Path(user_id): Path<Uuid>,
+    Json(request): Json<SetWorkingHoursRequest>,
+) -> AppResult<Json<WorkingHours>> {
+    request.validate()?;
+
+    let hours = state
+        .calendar_service
+        .set_working_hours(user.tenant_id, user_id, &request)
+        .await?;
+
+    Ok(Json(hours))
+}
+
+async fn check_shift(
+    State(state): State<CalendarStaffRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(user_id): Path<Uuid>,
+    Query(query): Query<ShiftCheckQuery>,
+) -> AppResult<Json<ShiftCheck>> {
+    let check = state
+        .calendar_service
+        .check_shift(user.tenant_id, user_id, query.start, query.end)
+        .await?;
+
+    Ok(Json(check))
+}
+
+// ============================================================================
+// SYNC HANDLERS
+// ============================================================================
+
+async fn list_connections(
+    State(state): State<CalendarStaffRouterState>,
+    RequireAuth(user): RequireAuth,
+) -> AppResult<Json<Vec<CalendarConnection>>> {
+    let connections = state
+        .sync_service
+        .list_connections(user.tenant_id, user.id)
+        .await?;
+
+    Ok(Json(connections))
+}
+
+async fn connect_calendar(
+    State(state): State<CalendarStaffRouterState>,
+    RequireAuth(user): RequireAuth,
+    Json(request): Json<ConnectCalendarRequest>,
+) -> AppResult<Json<CalendarConnection>> {
+    request.validate()?;
+
+    let connection = state
+        .sync_service
+        .connect(user.tenant_id, user.id, &request)
+        .await?;
+
+    Ok(Json(connection))
+}
+
+async fn sync_connection(
+    State(state): State<CalendarStaffRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(connection_id): Path<Uuid>,
+) -> AppResult<Json<SyncSummary>> {
+    let summary = state
+        .sync_service
+        .sync_connection(user.tenant_id, connection_id)
+        .await?;
+
+    Ok(Json(summary))
+}
+
+async fn list_reconciliations(
+    State(state): State<CalendarStaffRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(connection_id): Path<Uuid>,
+) -> AppResult<Json<Vec<SyncReconciliation>>> {
+    let reconciliations = state
+        .sync_service
+        .list_reconciliations(user.tenant_id, user.id, connection_id)
+        .await?;
+
+    Ok(Json(reconciliations))
+}
+
+async fn resolve_reconciliation(
+    State(state): State<CalendarStaffRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(reconciliation_id): Path<Uuid>,
+    Json(request): Json<ResolveReconciliationRequest>,
+) -> AppResult<Json<SyncReconciliation>> {
+    let reconciliation = state
+        .sync_service
+        .resolve_reconciliation(user.tenant_id, user.id, reconciliation_id, &request)
+        .await?;
+
+    Ok(Json(reconciliation))
+}
+
+// ============================================================================
+// PUBLIC BOOKING HANDLERS
+// ============================================================================
+
+async fn invitation_slots(
+    State(state): State<CalendarRouterState>,
+    tenant: Option<CurrentTenant>,
+    Path(token): Path<String>,
+) -> AppResult<Json<Vec<BookableSlot>>> {
+    let tenant_id = tenant.as_ref().map(CurrentTenant::tenant_id);
+    let slots = state.calendar_service.slots_for_invitation(tenant_id, &token).await?;
+
+    Ok(Json(slots))
+}
+
+/// Page the invitation email links to, with a button per open slot
+async fn booking_page(
+    State(state): State<CalendarRouterState>,
+    tenant: Option<CurrentTenant>,
+    Path(token): Path<String>,
+) -> AppResult<Html<String>> {
+    let tenant_id = tenant.as_ref().map(CurrentTenant::tenant_id);
+    let invitation = state.calendar_service.get_invitation_by_token(tenant_id, &token).await?;
+    let slots = state.calendar_service.slots_for_invitation(tenant_id, &token).await?;
+
+    let body = if slots.is_empty() {
+        "<p>There are no open times right now. Please reply to the email and we'll find one.</p>".to_string()
+    } else {
+        format!("<p>Pick a time that suits you.</p>{}", slot_forms("", &slots, "Book"))
+    };
+    Ok(public_page::page(&format!("Book: {}", invitation.title), &body))
+}
+
+async fn book_slot(
+    State(state): State<CalendarRouterState>,
+    tenant: Option<CurrentTenant>,
+    Path(token): Path<String>,
+    body: FormOrJson<BookSlotRequest>,
+) -> AppResult<Response> {
+    let is_form = body.is_form();
+    let request = body.into_inner();
+    request.validate()?;
+
+    let tenant_id = tenant.as_ref().map(CurrentTenant::tenant_id);
+    let confirmation = state.calendar_service.book_slot(tenant_id, &token, &request).await?;
+
+    if is_form {
+        let body = format!(
+            "<p>You're booked for {}. A confirmation with links to reschedule or cancel is on its way.</p>",
+            public_page::escape(&slot_label(&confirmation.slot))
+        );
+        return Ok(public_page::page("Appointment booked", &body).into_response());
+    }
+    Ok(Json(confirmation).into_response())
+}
+
+/// Page the confirmation email's reschedule and cancel links point to
+async fn manage_booking_page(
+    State(state): State<CalendarRouterState>,
+    tenant: Option<CurrentTenant>,
+    Path(token): Path<String>,
+) -> AppResult<Html<String>> {
+    let tenant_id = tenant.as_ref().map(CurrentTenant::tenant_id);
+    let booking = state.calendar_service.manage_booking(tenant_id, &token).await?;
+    let token = public_page::escape(&token);
+    let others: Vec<BookableSlot> = booking
+        .available_slots
+        .into_iter()
+        .filter(|slot| slot.id != booking.slot.id)
+        .collect();
+
+    let reschedule = if others.is_empty() {
+        "<p>There are no other open times right now.</p>".to_string()
+    } else {
+        slot_forms(&format!("{}/reschedule", token), &others, "Move to")
+    };
+    let body = format!(
+        r#"<p>Your appointment is booked for {}.</p>
+<h2 id="reschedule">Reschedule</h2>{}
+<h2 id="cancel">Cancel</h2>
+<form method="post" action="{}/cancel"><button type="submit">Cancel appointment</button></form>"#,
+        public_page::escape(&slot_label(&booking.slot)),
+        reschedule,
+        token
+    );
+    Ok(public_page::page("Your appointment", &body))
+}
+
+async fn cancel_booking(
+    State(state): State<CalendarRouterState>,
+    tenant: Option<CurrentTenant>,
+    Path(token): Path<String>,
+    headers: HeaderMap,
+) -> AppResult<Response> {
+    let tenant_id = tenant.as_ref().map(CurrentTenant::tenant_id);
+    let slot = state.calendar_service.cancel_booking(tenant_id, &token).await?;
+
+    if public_page::is_form(&headers) {
+        let page = public_page::page("Appointment cancelled", "<p>Your appointment has been cancelled.</p>");
+        return Ok(page.into_response());
+    }
+    Ok(Json(slot).into_response())
+}
+
+async fn reschedule_booking(
+    State(state): State<CalendarRouterState>,
+    tenant: Option<CurrentTenant>,
+    Path(token): Path<String>,
+    body: FormOrJson<BookSlotRequest>,
+) -> AppResult<Response> {
+    let is_form = body.is_form();
+    let request = body.into_inner();
+    request.validate()?;
+
+    let tenant_id = tenant.as_ref().map(CurrentTenant::tenant_id);
+    let slot = state
+        .calendar_service
+        .reschedule_booking(tenant_id, &token, &request)
+        .await?;
+
+    if is_form {
+        let body = format!("<p>Your appointment has moved to {}.</p>", public_page::escape(&slot_label(&slot)));
+        return Ok(public_page::page("Appointment rescheduled", &body).into_response());
+    }
+    Ok(Json(slot).into_response())
+}
+
+/// A slot's time as the booking pages show it
+fn slot_label(slot: &BookableSlot) -> String {
+    format!("{} to {}", slot.start_time.format("%a %Y-%m-%d %H:%M"), slot.end_time.format("%H:%M UTC"))
+}
+
+/// One form per slot, posting its id to `action`
+fn slot_forms(action: &str, slots: &[BookableSlot], verb: &str) -> String {
+    slots
+        .iter()
+        .map(|slot| {
+            format!(
+                r#"<form method="post" action="{}"><input type="hidden" name="slot_id" value="{}">
+<button type="submit">{} {}</button></form>"#,
+                action,
+                slot.id,
+                verb,
+                public_page::escape(&slot_label(slot))
+            )
+        })
+        .collect()
+}
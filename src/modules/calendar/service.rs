@@ -0,0 +1,784 @@
+//! Calendar service implementation
+
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::modules::notifications::{NotificationChannel, NotificationService, OutgoingEmail};
+use crate::modules::tickets::{CreateTicketRequest, TicketService, TicketSource};
+use crate::utils::crypto::generate_token;
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::timezone::TenantTimezone;
+
+use super::models::*;
+
+/// Default lifetime of a booking invitation
+const INVITATION_LIFETIME_DAYS: i64 = 14;
+
+/// Scheduling and appointment booking service
+#[derive(Clone)]
+pub struct CalendarService {
+    db: Database,
+    tickets: TicketService,
+    notifications: NotificationService,
+    base_url: String,
+}
+
+impl CalendarService {
+    pub fn new(db: Database) -> Self {
+        Self {
+            tickets: TicketService::new(db.clone()),
+            notifications: NotificationService::new(db.clone()),
+            base_url: std::env::var("BASE_URL")
+                .unwrap_or_else(|_| "http://localhost:8080".to_string()),
+            db,
+        }
+    }
+
+    // ========================================================================
+    // AVAILABILITY
+    // ========================================================================
+
+    /// A technician's weekly schedule. Without one the windows are empty and
+    /// the time zone is the user's own.
+    pub async fn working_hours(&self, tenant_id: Uuid, user_id: Uuid) -> AppResult<WorkingHours> {
+        let rows = sqlx::query_as::<_, (i32, NaiveTime, NaiveTime, String)>(
+            r#"
+            SELECT day_of_week, start_time, end_time, timezone
+            FROM user_availability
+            WHERE tenant_id = $1 AND user_id = $2 AND is_available = TRUE
+            ORDER BY day_of_week, start_time
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let timezone = match rows.first() {
+            Some((_, _, _, timezone)) => TenantTimezone::parse(timezone).unwrap_or_default(),
+            None => {
+                let timezone: Option<String> =
+                    sqlx::query_scalar("SELECT timezone FROM users WHERE tenant_id = $1 AND id = $2")
+                        .bind(tenant_id)
+                        .bind(user_id)
+                        .fetch_optional(self.db.pool())
+                        .await?;
+                timezone.as_deref().and_then(TenantTimezone::parse).unwrap_or_default()
+            }
+        };
+
+        Ok(WorkingHours {
+            timezone,
+            windows: rows
+                .into_iter()
+                .map(|(day_of_week, start_time, end_time, _)| AvailabilityWindow {
+                    day_of_week: day_of_week as u32,
+                    start_time,
+                    end_time,
+                })
+                .collect(),
+        })
+    }
+
+    /// Replace a technician's weekly schedule
+    pub async fn set_working_hours(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        request: &SetWorkingHoursRequest,
+    ) -> AppResult<WorkingHours> {
+        let hours = request.check()?;
+
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users WHERE tenant_id = $1 AND id = $2)")
+            .bind(tenant_id)
+            .bind(user_id)
+            .fetch_one(self.db.pool())
+            .await?;
+        if !exists {
+            return Err(AppError::not_found("User"));
+        }
+
+        let mut tx = self.db.pool().begin().await?;
+
+        sqlx::query("DELETE FROM user_availability WHERE tenant_id = $1 AND user_id = $2")
+            .bind(tenant_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for window in &hours.windows {
+            sqlx::query(
+                r#"
+                INSERT INTO user_availability (tenant_id, user_id, day_of_week, start_time, end_time, timezone)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                "#,
+            )
+            .bind(tenant_id)
+            .bind(user_id)
+            .bind(window.day_of_week as i32)
+            .bind(window.start_time)
+            .bind(window.end_time)
+            .bind(hours.timezone.name())
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(hours)
+    }
+
+    /// Whether an appointment at `[start, end)` falls outside the technician's
+    /// working hours, or the standard workday if they have no schedule
+    pub async fn check_shift(
+        &self,
+        tenant_id: Uuid,
+        technician_id: Uuid,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> AppResult<ShiftCheck> {
+        if end <= start {
+            return Err(AppError::validation_field("end", "End must be after start"));
+        }
+
+        let hours = self.working_hours(tenant_id, technician_id).await?.or_standard();
+        Ok(ShiftCheck {
+            technician_id,
+            off_shift: hours.is_off_shift(start, end),
+        })
+    }
+
+    // ========================================================================
+    // BOOKABLE SLOTS
+    // ========================================================================
+
+    /// Publish a slot, checking the technician's working hours, time off and calendar
+    pub async fn create_bookable_slot(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        request: &CreateSlotRequest,
+    ) -> AppResult<BookableSlot> {
+        if request.end_time <= request.start_time {
+            return Err(AppError::validation_field("end_time", "End time must be after start time"));
+        }
+
+        let hours = self.working_hours(tenant_id, request.technician_id).await?;
+        if !hours.covers(request.start_time, request.end_time) {
+            return Err(AppError::validation_field(
+                "start_time",
+                "Technician is not available at this time",
+            ));
+        }
+
+        let on_leave: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM time_off
+                WHERE tenant_id = $1 AND user_id = $2 AND status = 'approved'
+                  AND start_date <= $4::DATE AND end_date >= $3::DATE
+            )
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(request.technician_id)
+        .bind(request.start_time)
+        .bind(request.end_time)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        if on_leave {
+            return Err(AppError::validation_field(
+                "start_time",
+                "Technician has approved time off on this date",
+            ));
+        }
+
+        let busy: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM appointments
+                WHERE tenant_id = $1 AND assigned_to_id = $2 AND status <> 'cancelled'
+                  AND start_time < $4 AND end_time > $3
+            ) OR EXISTS (
+                SELECT 1 FROM bookable_slots
+                WHERE tenant_id = $1 AND technician_id = $2 AND status <> 'cancelled'
+                  AND start_time < $4 AND end_time > $3
+            ) OR EXISTS (
+                -- Busy blocks pulled from the technician's external calendars
+                SELECT 1 FROM calendar_event_mappings m
+                JOIN calendar_connections c ON c.id = m.connection_id
+                WHERE c.tenant_id = $1 AND c.user_id = $2 AND c.is_active = TRUE
+                  AND m.appointment_id IS NULL
+                  AND m.start_time < $4 AND m.end_time > $3
+            )
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(request.technician_id)
+        .bind(request.start_time)
+        .bind(request.end_time)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        if busy {
+            return Err(AppError::Conflict("A slot or appointment at this time".to_string()));
+        }
+
+        let row = sqlx::query_as::<_, BookableSlotRow>(
+            r#"
+            INSERT INTO bookable_slots (tenant_id, technician_id, start_time, end_time, created_by_id)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, tenant_id, technician_id, start_time, end_time, status,
+                      invitation_id, appointment_id, ticket_id, booked_at
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(request.technician_id)
+        .bind(request.start_time)
+        .bind(request.end_time)
+        .bind(user_id)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(row.into())
+    }
+
+    /// Future slots that can still be booked
+    pub async fn list_available_slots(&self, tenant_id: Uuid) -> AppResult<Vec<BookableSlot>> {
+        let rows = sqlx::query_as::<_, BookableSlotRow>(
+            r#"
+            SELECT id, tenant_id, technician_id, start_time, end_time, status,
+                   invitation_id, appointment_id, ticket_id, booked_at
+            FROM bookable_slots
+            WHERE tenant_id = $1 AND status = 'available' AND start_time > NOW()
+            ORDER BY start_time
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    // ========================================================================
+    // INVITATIONS
+    // ========================================================================
+
+    /// Create a booking invitation and email the link to the contact
+    pub async fn create_invitation(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        request: &CreateBookingInvitationRequest,
+    ) -> AppResult<BookingInvitation> {
+        let (company_id, email) = sqlx::query_as::<_, (Uuid, Option<String>)>(
+            "SELECT company_id, email FROM contacts WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(tenant_id)
+        .bind(request.contact_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Contact".to_string()))?;
+
+        let email = email.ok_or_else(|| {
+            AppError::validation_field("contact_id", "Contact has no email address")
+        })?;
+
+        let token = generate_token(48);
+        let expires_at =
+            Utc::now() + Duration::days(request.expires_in_days.unwrap_or(INVITATION_LIFETIME_DAYS));
+
+        sqlx::query(
+            r#"
+            INSERT INTO booking_invitations (tenant_id, company_id, contact_id, token, title, description, expires_at, created_by_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(company_id)
+        .bind(request.contact_id)
+        .bind(&token)
+        .bind(&request.title)
+        .bind(&request.description)
+        .bind(expires_at)
+        .bind(user_id)
+        .execute(self.db.pool())
+        .await?;
+
+        let url = format!("{}/api/v1/booking/{}", self.base_url.trim_end_matches('/'), token);
+        let outgoing = OutgoingEmail {
+            to: email,
+            subject: format!("Book an appointment: {}", request.title),
+            body_text: format!(
+                "Please pick a time that suits you for \"{}\":\n{}\n\nThis link expires on {}.",
+                request.title,
+                url,
+                expires_at.format("%Y-%m-%d")
+            ),
+            body_html: None,
+            template_id: None,
+            from: None,
+            thread: None,
+            attachments: Vec::new(),
+        };
+        self.notifications
+            .send_email(tenant_id, None, &outgoing)
+            .await?;
+
+        self.get_invitation_by_token(Some(tenant_id), &token).await
+    }
+
+    /// Look up an invitation by its public token. With `tenant_id`,
+    /// invitations sent by other tenants are not found.
+    pub async fn get_invitation_by_token(&self, tenant_id: Option<Uuid>, token: &str) -> AppResult<BookingInvitation> {
+        let row = sqlx::query_as::<_, BookingInvitationRow>(
+            r#"
+            SELECT id, tenant_id, company_id, contact_id, title, description, expires_at,
+                   created_by_id, created_at
+            FROM booking_invitations
+            WHERE token = $1 AND ($2::UUID IS NULL OR tenant_id = $2)
+            "#,
+        )
+        .bind(token)
+        .bind(tenant_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Booking invitation".to_string()))?;
+
+        Ok(row.into())
+    }
+
+    /// Slots a contact can pick from with a valid invitation
+    pub async fn slots_for_invitation(&self, tenant_id: Option<Uuid>, token: &str) -> AppResult<Vec<BookableSlot>> {
+        let invitation = self.get_invitation_by_token(tenant_id, token).await?;
+        invitation.ensure_valid(Utc::now())?;
+
+        self.list_available_slots(invitation.tenant_id).await
+    }
+
+    // ========================================================================
+    // BOOKING
+    // ========================================================================
+
+    /// Book a slot through an invitation, creating an appointment and a scheduled ticket.
+    ///
+    /// The slot row is locked while it is claimed, so when two contacts race for
+    /// the same slot exactly one wins and the other gets a conflict.
+    pub async fn book_slot(
+        &self,
+        tenant_id: Option<Uuid>,
+        token: &str,
+        request: &BookSlotRequest,
+    ) -> AppResult<BookingConfirmation> {
+        let invitation = self.get_invitation_by_token(tenant_id, token).await?;
+        invitation.ensure_valid(Utc::now())?;
+        let tenant_id = invitation.tenant_id;
+
+        let manage_token = generate_token(48);
+        let mut tx = self.db.pool().begin().await?;
+
+        let mut slot = Self::lock_slot(&mut *tx, tenant_id, request.slot_id).await?;
+        slot.claim(invitation.id, Utc::now())?;
+
+        sqlx::query(
+            r#"
+            UPDATE bookable_slots
+            SET status = $1, invitation_id = $2, manage_token = $3, booked_at = $4, updated_at = NOW()
+            WHERE id = $5
+            "#,
+        )
+        .bind(slot.status.as_str())
+        .bind(slot.invitation_id)
+        .bind(&manage_token)
+        .bind(slot.booked_at)
+        .bind(slot.id)
+        .execute(&mut *tx)
+        .await?;
+
+        let appointment_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO appointments (tenant_id, title, description, appointment_type, company_id,
+                                      contact_id, assigned_to_id, start_time, end_time)
+            VALUES ($1, $2, $3, 'ticket', $4, $5, $6, $7, $8)
+            RETURNING id
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(&invitation.title)
+        .bind(&invitation.description)
+        .bind(invitation.company_id)
+        .bind(invitation.contact_id)
+        .bind(slot.technician_id)
+        .bind(slot.start_time)
+        .bind(slot.end_time)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query("UPDATE bookable_slots SET appointment_id = $1 WHERE id = $2")
+            .bind(appointment_id)
+            .bind(slot.id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        // The booking stands even if the ticket cannot be created; staff can link one later
+        let ticket = match self.create_booking_ticket(&invitation, &slot).await {
+            Ok(ticket) => Some(ticket),
+            Err(e) => {
+                tracing::warn!("Ticket for booking {} could not be created: {}", slot.id, e);
+                None
+            }
+        };
+
+        if let Some(ref ticket) = ticket {
+            sqlx::query("UPDATE appointments SET ticket_id = $1 WHERE id = $2")
+                .bind(ticket.id)
+                .bind(appointment_id)
+                .execute(self.db.pool())
+                .await?;
+            sqlx::query("UPDATE bookable_slots SET ticket_id = $1 WHERE id = $2")
+                .bind(ticket.id)
+                .bind(slot.id)
+                .execute(self.db.pool())
+                .await?;
+        }
+
+        if let Err(e) = self
+            .send_confirmation(&invitation, &slot, ticket.as_ref(), &manage_token)
+            .await
+        {
+            tracing::warn!("Booking confirmation for slot {} failed: {}", slot.id, e);
+        }
+
+        Ok(BookingConfirmation {
+            slot: BookableSlot {
+                appointment_id: Some(appointment_id),
+                ticket_id: ticket.as_ref().map(|t| t.id),
+                ..slot
+            },
+            appointment_id,
+            ticket_id: ticket.map(|t| t.id),
+        })
+    }
+
+    /// A booking and the slots it could be rescheduled to, from the
+    /// confirmation email link
+    pub async fn manage_booking(&self, tenant_id: Option<Uuid>, manage_token: &str) -> AppResult<ManagedBooking> {
+        let slot = self.get_slot_by_manage_token(tenant_id, manage_token).await?;
+        let available_slots = self.list_available_slots(slot.tenant_id).await?;
+
+        Ok(ManagedBooking { slot, available_slots })
+    }
+
+    /// Cancel a booking from the confirmation email link
+    pub async fn cancel_booking(&self, tenant_id: Option<Uuid>, manage_token: &str) -> AppResult<BookableSlot> {
+        let slot = self.get_slot_by_manage_token(tenant_id, manage_token).await?;
+
+        let mut tx = self.db.pool().begin().await?;
+
+        if let Some(appointment_id) = slot.appointment_id {
+            sqlx::query("UPDATE appointments SET status = 'cancelled', updated_at = NOW() WHERE id = $1")
+                .bind(appointment_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        // Free the slot for someone else
+        let row = sqlx::query_as::<_, BookableSlotRow>(
+            r#"
+            UPDATE bookable_slots
+            SET status = 'available', invitation_id = NULL, appointment_id = NULL, ticket_id = NULL,
+                manage_token = NULL, booked_at = NULL, updated_at = NOW()
+            WHERE id = $1
+            RETURNING id, tenant_id, technician_id, start_time, end_time, status,
+                      invitation_id, appointment_id, ticket_id, booked_at
+            "#,
+        )
+        .bind(slot.id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(row.into())
+    }
+
+    /// Move a booking to another available slot from the confirmation email link
+    pub async fn reschedule_booking(
+        &self,
+        tenant_id: Option<Uuid>,
+        manage_token: &str,
+        request: &BookSlotRequest,
+    ) -> AppResult<BookableSlot> {
+        let old = self.get_slot_by_manage_token(tenant_id, manage_token).await?;
+        if old.id == request.slot_id {
+            return Err(AppError::BadRequest("Pick a different slot to reschedule".to_string()));
+        }
+
+        let mut tx = self.db.pool().begin().await?;
+
+        // Release the old slot first so its manage token can move to the new one;
+        // if the new slot has been taken meanwhile the transaction rolls back
+        sqlx::query(
+            r#"
+            UPDATE bookable_slots
+            SET status = 'available', invitation_id = NULL, appointment_id = NULL, ticket_id = NULL,
+                manage_token = NULL, booked_at = NULL, updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(old.id)
+        .execute(&mut *tx)
+        .await?;
+
+        let mut new_slot = Self::lock_slot(&mut *tx, old.tenant_id, request.slot_id).await?;
+        let invitation_id = old
+            .invitation_id
+            .ok_or_else(|| AppError::internal("Booked slot has no invitation"))?;
+        new_slot.claim(invitation_id, Utc::now())?;
+        new_slot.appointment_id = old.appointment_id;
+        new_slot.ticket_id = old.ticket_id;
+
+        sqlx::query(
+            r#"
+            UPDATE bookable_slots
+            SET status = $1, invitation_id = $2, appointment_id = $3, ticket_id = $4,
+                manage_token = $5, booked_at = $6, updated_at = NOW()
+            WHERE id = $7
+            "#,
+        )
+        .bind(new_slot.status.as_str())
+        .bind(new_slot.invitation_id)
+        .bind(new_slot.appointment_id)
+        .bind(new_slot.ticket_id)
+        .bind(manage_token)
+        .bind(new_slot.booked_at)
+        .bind(new_slot.id)
+        .execute(&mut *tx)
+        .await?;
+
+        if let Some(appointment_id) = old.appointment_id {
+            sqlx::query(
+                "UPDATE appointments SET assigned_to_id = $1, start_time = $2, end_time = $3, updated_at = NOW() WHERE id = $4",
+            )
+            .bind(new_slot.technician_id)
+            .bind(new_slot.start_time)
+            .bind(new_slot.end_time)
+            .bind(appointment_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        if let Some(ticket_id) = old.ticket_id {
+            sqlx::query(
+                "UPDATE tickets SET assigned_to_id = $1, scheduled_start = $2, scheduled_end = $3, updated_at = NOW() WHERE id = $4",
+            )
+            .bind(new_slot.technician_id)
+            .bind(new_slot.start_time)
+            .bind(new_slot.end_time)
+            .bind(ticket_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(new_slot)
+    }
+
+    /// Fetch a slot for update, holding its row until the transaction ends
+    async fn lock_slot(conn: &mut PgConnection, tenant_id: Uuid, slot_id: Uuid) -> AppResult<BookableSlot> {
+        let row = sqlx::query_as::<_, BookableSlotRow>(
+            r#"
+            SELECT id, tenant_id, technician_id, start_time, end_time, status,
+                   invitation_id, appointment_id, ticket_id, booked_at
+            FROM bookable_slots
+            WHERE tenant_id = $1 AND id = $2
+            FOR UPDATE
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(slot_id)
+        .fetch_optional(conn)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Slot".to_string()))?;
+
+        Ok(row.into())
+    }
+
+    /// With `tenant_id`, bookings with other tenants are not found
+    async fn get_slot_by_manage_token(&self, tenant_id: Option<Uuid>, manage_token: &str) -> AppResult<BookableSlot> {
+        let row = sqlx::query_as::<_, BookableSlotRow>(
+            r#"
+            SELECT id, tenant_id, technician_id, start_time, end_time, status,
+                   invitation_id, appointment_id, ticket_id, booked_at
+            FROM bookable_slots
+            WHERE manage_token = $1 AND status = 'booked' AND ($2::UUID IS NULL OR tenant_id = $2)
+            "#,
+        )
+        .bind(manage_token)
+        .bind(tenant_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Booking".to_string()))?;
+
+        Ok(row.into())
+    }
+
+    async fn create_booking_ticket(
+        &self,
+        invitation: &BookingInvitation,
+        slot: &BookableSlot,
+    ) -> AppResult<crate::modules::tickets::Ticket> {
+        let request = CreateTicketRequest {
+            title: invitation.title.clone(),
+            description: invitation.description.clone(),
+            priority_id: None,
+            type_id: None,
+            category_id: None,
+            queue_id: None,
+            source: TicketSource::Portal,
+            company_id: invitation.company_id,
+            contact_id: Some(invitation.contact_id),
+            site_id: None,
+            assigned_to_id: Some(slot.technician_id),
+            team_id: None,
+            contract_id: None,
+            sla_id: None,
+            scheduled_start: Some(slot.start_time),
+            scheduled_end: Some(slot.end_time),
+            estimated_hours: Some((slot.end_time - slot.start_time).num_minutes() as f64 / 60.0),
+            is_billable: None,
+            asset_id: None,
+            custom_fields: serde_json::json!({}),
+            tags: vec!["booked".to_string()],
+        };
+
+        // Portal bookings are attributed to the staff member who sent the invitation
+        self.tickets
+            .create_ticket(invitation.tenant_id, invitation.created_by_id, &request)
+            .await
+    }
+
+    async fn send_confirmation(
+        &self,
+        invitation: &BookingInvitation,
+        slot: &BookableSlot,
+        ticket: Option<&crate::modules::tickets::Ticket>,
+        manage_token: &str,
+    ) -> AppResult<()> {
+        let (email, locale, technician) = sqlx::query_as::<_, (Option<String>, String, String)>(
+            r#"
+            SELECT c.email, c.locale, u.first_name || ' ' || u.last_name
+            FROM contacts c, users u
+            WHERE c.id = $1 AND u.id = $2
+            "#,
+        )
+        .bind(invitation.contact_id)
+        .bind(slot.technician_id)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        let Some(email) = email else {
+            return Ok(());
+        };
+
+        let manage_url = format!("{}/api/v1/booking/manage/{}", self.base_url.trim_end_matches('/'), manage_token);
+        let context = serde_json::json!({
+            "appointment": {
+                "start": slot.start_time.format("%Y-%m-%d %H:%M UTC").to_string(),
+                "end": slot.end_time.format("%Y-%m-%d %H:%M UTC").to_string(),
+                "technician": technician,
+            },
+            "ticket": {
+                "number": ticket.map(|t| t.ticket_number.as_str()).unwrap_or(""),
+                "title": invitation.title,
+            },
+            "booking": {
+                "reschedule_url": format!("{}#reschedule", manage_url),
+                "cancel_url": format!("{}#cancel", manage_url),
+            },
+        });
+
+        let (template, rendered) = self
+            .notifications
+            .render_template(invitation.tenant_id, "appointment.booked", NotificationChannel::Email, &locale, &context)
+            .await?;
+        let outgoing = self.notifications.templated_email(invitation.tenant_id, email, &template, rendered).await?;
+
+        self.notifications
+            .send_email(invitation.tenant_id, None, &outgoing)
+            .await?;
+
+        Ok(())
+    }
+}
+
+// ============================================================================
+// DATABASE ROW TYPES
+// ============================================================================
+
+#[derive(sqlx::FromRow)]
+struct BookableSlotRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    technician_id: Uuid,
+    start_time: chrono::DateTime<Utc>,
+    end_time: chrono::DateTime<Utc>,
+    status: String,
+    invitation_id: Option<Uuid>,
+    appointment_id: Option<Uuid>,
+    ticket_id: Option<Uuid>,
+    booked_at: Option<chrono::DateTime<Utc>>,
+}
+
+impl From<BookableSlotRow> for BookableSlot {
+    fn from(row: BookableSlotRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            technician_id: row.technician_id,
+            start_time: row.start_time,
+            end_time: row.end_time,
+            status: SlotStatus::from_str(&row.status).unwrap_or_default(),
+            invitation_id: row.invitation_id,
+            appointment_id: row.appointment_id,
+            ticket_id: row.ticket_id,
+            booked_at: row.booked_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct BookingInvitationRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    company_id: Uuid,
+    contact_id: Uuid,
+    title: String,
+    description: Option<String>,
+    expires_at: chrono::DateTime<Utc>,
+    created_by_id: Uuid,
+    created_at: chrono::DateTime<Utc>,
+}
+
+impl From<BookingInvitationRow> for BookingInvitation {
+    fn from(row: BookingInvitationRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            company_id: row.company_id,
+            contact_id: row.contact_id,
+            title: row.title,
+            description: row.description,
+            expires_at: row.expires_at,
+            created_by_id: row.created_by_id,
+            created_at: row.created_at,
+        }
+    }
+}
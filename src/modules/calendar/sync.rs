@@ -0,0 +1,1294 @@
+//! External calendar sync
+//!
+//! Two-way sync between a technician's PSA calendar and Google Calendar or
+//! Microsoft Graph. PSA appointments are pushed to the remote calendar and
+//! remote events are pulled back as busy blocks that count against
+//! availability. Each provider exposes an incremental change feed; the token
+//! for the next call is stored on the connection. A pushed appointment moved
+//! in the remote calendar is reconciled under the connection's conflict
+//! policy and the decision recorded in `sync_reconciliations`.
+
+use std::collections::HashSet;
+use std::future::Future;
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+use reqwest::{Client, Response, StatusCode, Url};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::utils::crypto::{decrypt, encrypt, parse_encryption_key};
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::reconcile::{ConflictPolicy, Reconciliation, SyncDecision};
+
+use super::models::*;
+
+/// How far ahead Microsoft Graph calendar views are tracked
+const SYNC_WINDOW_DAYS: i64 = 180;
+
+/// Remote event created or updated by a push
+#[derive(Debug, Clone)]
+pub struct PushedEvent {
+    pub external_id: String,
+    pub etag: Option<String>,
+}
+
+/// A new access token from a refresh
+#[derive(Debug, Clone)]
+pub struct RefreshedToken {
+    pub access_token: String,
+    /// Set when the provider rotated the refresh token
+    pub refresh_token: Option<String>,
+}
+
+/// A remote calendar that supports incremental two-way sync
+pub trait CalendarSyncProvider: Send + Sync {
+    /// Changes since `connection.sync_token`, or a full listing if there is none
+    /// or the provider rejected it
+    fn list_changes(
+        &self,
+        connection: &CalendarConnection,
+        access_token: &str,
+    ) -> impl Future<Output = AppResult<SyncDelta>> + Send;
+
+    /// Create the event, or update it in place when `external_id` is given
+    fn upsert_event(
+        &self,
+        connection: &CalendarConnection,
+        access_token: &str,
+        external_id: Option<&str>,
+        event: &OutboundEvent,
+    ) -> impl Future<Output = AppResult<PushedEvent>> + Send;
+
+    /// Delete a pushed event. Already-deleted events are not an error.
+    fn delete_event(
+        &self,
+        connection: &CalendarConnection,
+        access_token: &str,
+        external_id: &str,
+    ) -> impl Future<Output = AppResult<()>> + Send;
+
+    /// Exchange a refresh token for a new access token
+    fn refresh_access_token(&self, refresh_token: &str) -> impl Future<Output = AppResult<RefreshedToken>> + Send;
+}
+
+async fn check_response(response: Response, service: &str) -> AppResult<Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let body = response.text().await.unwrap_or_default();
+    Err(AppError::external_service(service, format!("{}: {}", status, body)))
+}
+
+/// OAuth refresh-token grant against a provider's token endpoint. The app's
+/// client is read from `{prefix}_CLIENT_ID` and `{prefix}_CLIENT_SECRET`.
+async fn refresh_token_grant(
+    http: &Client,
+    token_url: &str,
+    service: &str,
+    prefix: &str,
+    refresh_token: &str,
+    scope: Option<&str>,
+) -> AppResult<RefreshedToken> {
+    let env = |name: String| std::env::var(&name).map_err(|_| AppError::Configuration(format!("{} is not set", name)));
+    let client_id = env(format!("{}_CLIENT_ID", prefix))?;
+    let client_secret = env(format!("{}_CLIENT_SECRET", prefix))?;
+
+    let mut form = vec![
+        ("grant_type", "refresh_token"),
+        ("refresh_token", refresh_token),
+        ("client_id", client_id.as_str()),
+        ("client_secret", client_secret.as_str()),
+    ];
+    if let Some(scope) = scope {
+        form.push(("scope", scope));
+    }
+
+    let response = http.post(token_url).form(&form).send().await?;
+    let body: Value = check_response(response, service).await?.json().await?;
+    Ok(RefreshedToken {
+        access_token: body["access_token"]
+            .as_str()
+            .ok_or_else(|| AppError::external_service(service, "Missing access token"))?
+            .to_string(),
+        refresh_token: body["refresh_token"].as_str().map(String::from),
+    })
+}
+
+// ============================================================================
+// GOOGLE CALENDAR
+// ============================================================================
+
+const GOOGLE_CALENDAR_API: &str = "https://www.googleapis.com/calendar/v3/calendars";
+const GOOGLE_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+
+/// Google Calendar v3 using `syncToken` incremental sync
+#[derive(Clone)]
+pub struct GoogleCalendarProvider {
+    http: Client,
+}
+
+impl GoogleCalendarProvider {
+    pub fn new(http: Client) -> Self {
+        Self { http }
+    }
+
+    fn events_url(calendar_id: &str, event_id: Option<&str>) -> AppResult<Url> {
+        let mut url = Url::parse(GOOGLE_CALENDAR_API).map_err(|e| AppError::internal(e.to_string()))?;
+        {
+            let mut segments = url
+                .path_segments_mut()
+                .map_err(|_| AppError::internal("Invalid Google Calendar URL"))?;
+            segments.push(calendar_id).push("events");
+            if let Some(id) = event_id {
+                segments.push(id);
+            }
+        }
+        Ok(url)
+    }
+
+    /// Page through the feed; `None` means Google expired the sync token
+    async fn fetch_changes(
+        &self,
+        connection: &CalendarConnection,
+        access_token: &str,
+        sync_token: Option<&str>,
+    ) -> AppResult<Option<SyncDelta>> {
+        let url = Self::events_url(&connection.calendar_id, None)?;
+        let mut changes = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut query: Vec<(&str, String)> = vec![("singleEvents", "true".to_string())];
+            if let Some(token) = sync_token {
+                query.push(("syncToken", token.to_string()));
+            }
+            if let Some(ref page) = page_token {
+                query.push(("pageToken", page.clone()));
+            }
+
+            let response = self
+                .http
+                .get(url.clone())
+                .bearer_auth(access_token)
+                .query(&query)
+                .send()
+                .await?;
+
+            if response.status() == StatusCode::GONE {
+                return Ok(None);
+            }
+
+            let body: Value = check_response(response, "Google Calendar").await?.json().await?;
+            for item in body["items"].as_array().into_iter().flatten() {
+                if let Some(change) = Self::parse_change(item) {
+                    changes.push(change);
+                }
+            }
+
+            if let Some(next) = body["nextPageToken"].as_str() {
+                page_token = Some(next.to_string());
+                continue;
+            }
+
+            let next_sync_token = body["nextSyncToken"]
+                .as_str()
+                .ok_or_else(|| AppError::external_service("Google Calendar", "Missing nextSyncToken"))?
+                .to_string();
+
+            return Ok(Some(SyncDelta {
+                changes,
+                next_sync_token,
+                full_resync: sync_token.is_none(),
+            }));
+        }
+    }
+
+    fn parse_change(item: &Value) -> Option<ExternalChange> {
+        let id = item["id"].as_str()?.to_string();
+        if item["status"].as_str() == Some("cancelled") {
+            return Some(ExternalChange::Removed(id));
+        }
+
+        Some(ExternalChange::Upserted(ExternalEvent {
+            external_id: id,
+            title: item["summary"].as_str().map(String::from),
+            start_time: Self::parse_time(&item["start"])?,
+            end_time: Self::parse_time(&item["end"])?,
+            etag: item["etag"].as_str().map(String::from),
+            busy: item["transparency"].as_str() != Some("transparent"),
+            updated_at: parse_modified(&item["updated"]),
+        }))
+    }
+
+    /// Timed events carry `dateTime`; all-day events carry a bare `date`
+    fn parse_time(value: &Value) -> Option<DateTime<Utc>> {
+        if let Some(date_time) = value["dateTime"].as_str() {
+            return DateTime::parse_from_rfc3339(date_time)
+                .ok()
+                .map(|dt| dt.with_timezone(&Utc));
+        }
+
+        NaiveDate::parse_from_str(value["date"].as_str()?, "%Y-%m-%d")
+            .ok()?
+            .and_hms_opt(0, 0, 0)
+            .map(|dt| dt.and_utc())
+    }
+
+    fn event_body(event: &OutboundEvent) -> Value {
+        json!({
+            "summary": event.title,
+            "description": event.description,
+            "location": event.location,
+            "start": { "dateTime": event.start_time.to_rfc3339() },
+            "end": { "dateTime": event.end_time.to_rfc3339() },
+            "extendedProperties": {
+                "private": { "psaAppointmentId": event.appointment_id.to_string() }
+            },
+        })
+    }
+}
+
+impl CalendarSyncProvider for GoogleCalendarProvider {
+    async fn list_changes(
+        &self,
+        connection: &CalendarConnection,
+        access_token: &str,
+    ) -> AppResult<SyncDelta> {
+        if let Some(delta) = self
+            .fetch_changes(connection, access_token, connection.sync_token.as_deref())
+            .await?
+        {
+            return Ok(delta);
+        }
+
+        tracing::info!("Google sync token expired for connection {}, resyncing", connection.id);
+        self.fetch_changes(connection, access_token, None)
+            .await?
+            .ok_or_else(|| AppError::external_service("Google Calendar", "Full sync was rejected"))
+    }
+
+    async fn upsert_event(
+        &self,
+        connection: &CalendarConnection,
+        access_token: &str,
+        external_id: Option<&str>,
+        event: &OutboundEvent,
+    ) -> AppResult<PushedEvent> {
+        let body = Self::event_body(event);
+        let request = match external_id {
+            Some(id) => self.http.put(Self::events_url(&connection.calendar_id, Some(id))?),
+            None => self.http.post(Self::events_url(&connection.calendar_id, None)?),
+        };
+
+        let mut response = request.bearer_auth(access_token).json(&body).send().await?;
+
+        // Deleted remotely since the last pull; create it again
+        if external_id.is_some()
+            && matches!(response.status(), StatusCode::NOT_FOUND | StatusCode::GONE)
+        {
+            response = self
+                .http
+                .post(Self::events_url(&connection.calendar_id, None)?)
+                .bearer_auth(access_token)
+                .json(&body)
+                .send()
+                .await?;
+        }
+
+        let created: Value = check_response(response, "Google Calendar").await?.json().await?;
+        Ok(PushedEvent {
+            external_id: created["id"]
+                .as_str()
+                .ok_or_else(|| AppError::external_service("Google Calendar", "Missing event id"))?
+                .to_string(),
+            etag: created["etag"].as_str().map(String::from),
+        })
+    }
+
+    async fn delete_event(
+        &self,
+        connection: &CalendarConnection,
+        access_token: &str,
+        external_id: &str,
+    ) -> AppResult<()> {
+        let response = self
+            .http
+            .delete(Self::events_url(&connection.calendar_id, Some(external_id))?)
+            .bearer_auth(access_token)
+            .send()
+            .await?;
+
+        if matches!(response.status(), StatusCode::NOT_FOUND | StatusCode::GONE) {
+            return Ok(());
+        }
+        check_response(response, "Google Calendar").await?;
+        Ok(())
+    }
+
+    async fn refresh_access_token(&self, refresh_token: &str) -> AppResult<RefreshedToken> {
+        refresh_token_grant(&self.http, GOOGLE_TOKEN_URL, "Google Calendar", "GOOGLE", refresh_token, None).await
+    }
+}
+
+// ============================================================================
+// MICROSOFT GRAPH
+// ============================================================================
+
+const GRAPH_API: &str = "https://graph.microsoft.com/v1.0";
+const MICROSOFT_TOKEN_URL: &str = "https://login.microsoftonline.com/common/oauth2/v2.0/token";
+/// Scopes asked for again on refresh; Microsoft issues tokens per scope
+const MICROSOFT_SCOPES: &str = "offline_access https://graph.microsoft.com/Calendars.ReadWrite";
+
+/// Microsoft Graph calendar using `calendarView/delta`; the stored sync token
+/// is the opaque `@odata.deltaLink`
+#[derive(Clone)]
+pub struct MicrosoftGraphProvider {
+    http: Client,
+}
+
+impl MicrosoftGraphProvider {
+    pub fn new(http: Client) -> Self {
+        Self { http }
+    }
+
+    fn calendar_path(connection: &CalendarConnection) -> String {
+        if connection.calendar_id == "primary" {
+            format!("{}/me", GRAPH_API)
+        } else {
+            format!("{}/me/calendars/{}", GRAPH_API, connection.calendar_id)
+        }
+    }
+
+    fn initial_delta_url(connection: &CalendarConnection) -> AppResult<Url> {
+        let now = Utc::now();
+        Url::parse_with_params(
+            &format!("{}/calendarView/delta", Self::calendar_path(connection)),
+            &[
+                ("startDateTime", (now - Duration::days(1)).to_rfc3339()),
+                ("endDateTime", (now + Duration::days(SYNC_WINDOW_DAYS)).to_rfc3339()),
+            ],
+        )
+        .map_err(|e| AppError::internal(e.to_string()))
+    }
+
+    /// Follow nextLink pages to the deltaLink; `None` means the delta token expired
+    async fn fetch_changes(
+        &self,
+        access_token: &str,
+        start: Url,
+        full_resync: bool,
+    ) -> AppResult<Option<SyncDelta>> {
+        let mut url = start;
+        let mut changes = Vec::new();
+
+        loop {
+            let response = self
+                .http
+                .get(url)
+                .bearer_auth(access_token)
+                .header("Prefer", "outlook.timezone=\"UTC\", odata.maxpagesize=100")
+                .send()
+                .await?;
+
+            if response.status() == StatusCode::GONE {
+                return Ok(None);
+            }
+
+            let body: Value = check_response(response, "Microsoft Graph").await?.json().await?;
+            for item in body["value"].as_array().into_iter().flatten() {
+                if let Some(change) = Self::parse_change(item) {
+                    changes.push(change);
+                }
+            }
+
+            if let Some(next) = body["@odata.nextLink"].as_str() {
+                url = Url::parse(next).map_err(|e| AppError::external_service("Microsoft Graph", e.to_string()))?;
+                continue;
+            }
+
+            let next_sync_token = body["@odata.deltaLink"]
+                .as_str()
+                .ok_or_else(|| AppError::external_service("Microsoft Graph", "Missing deltaLink"))?
+                .to_string();
+
+            return Ok(Some(SyncDelta {
+                changes,
+                next_sync_token,
+                full_resync,
+            }));
+        }
+    }
+
+    fn parse_change(item: &Value) -> Option<ExternalChange> {
+        let id = item["id"].as_str()?.to_string();
+        if !item["@removed"].is_null() || item["isCancelled"].as_bool() == Some(true) {
+            return Some(ExternalChange::Removed(id));
+        }
+
+        Some(ExternalChange::Upserted(ExternalEvent {
+            external_id: id,
+            title: item["subject"].as_str().map(String::from),
+            start_time: Self::parse_time(&item["start"])?,
+            end_time: Self::parse_time(&item["end"])?,
+            etag: item["changeKey"].as_str().map(String::from),
+            busy: !matches!(item["showAs"].as_str(), Some("free") | Some("workingElsewhere")),
+            updated_at: parse_modified(&item["lastModifiedDateTime"]),
+        }))
+    }
+
+    /// Graph returns local date-times without an offset; the Prefer header asks for UTC
+    fn parse_time(value: &Value) -> Option<DateTime<Utc>> {
+        NaiveDateTime::parse_from_str(value["dateTime"].as_str()?, "%Y-%m-%dT%H:%M:%S%.f")
+            .ok()
+            .map(|dt| dt.and_utc())
+    }
+
+    fn event_body(event: &OutboundEvent) -> Value {
+        json!({
+            "subject": event.title,
+            "body": { "contentType": "text", "content": event.description.clone().unwrap_or_default() },
+            "location": { "displayName": event.location.clone().unwrap_or_default() },
+            "start": { "dateTime": event.start_time.format("%Y-%m-%dT%H:%M:%S").to_string(), "timeZone": "UTC" },
+            "end": { "dateTime": event.end_time.format("%Y-%m-%dT%H:%M:%S").to_string(), "timeZone": "UTC" },
+        })
+    }
+}
+
+impl CalendarSyncProvider for MicrosoftGraphProvider {
+    async fn list_changes(
+        &self,
+        connection: &CalendarConnection,
+        access_token: &str,
+    ) -> AppResult<SyncDelta> {
+        if let Some(ref delta_link) = connection.sync_token {
+            let url = Url::parse(delta_link).map_err(|e| AppError::internal(e.to_string()))?;
+            if let Some(delta) = self.fetch_changes(access_token, url, false).await? {
+                return Ok(delta);
+            }
+            tracing::info!("Graph delta token expired for connection {}, resyncing", connection.id);
+        }
+
+        self.fetch_changes(access_token, Self::initial_delta_url(connection)?, true)
+            .await?
+            .ok_or_else(|| AppError::external_service("Microsoft Graph", "Full sync was rejected"))
+    }
+
+    async fn upsert_event(
+        &self,
+        connection: &CalendarConnection,
+        access_token: &str,
+        external_id: Option<&str>,
+        event: &OutboundEvent,
+    ) -> AppResult<PushedEvent> {
+        let body = Self::event_body(event);
+        let request = match external_id {
+            Some(id) => self.http.patch(format!("{}/me/events/{}", GRAPH_API, id)),
+            None => self.http.post(format!("{}/events", Self::calendar_path(connection))),
+        };
+
+        let mut response = request.bearer_auth(access_token).json(&body).send().await?;
+
+        // Deleted remotely since the last pull; create it again
+        if external_id.is_some() && response.status() == StatusCode::NOT_FOUND {
+            response = self
+                .http
+                .post(format!("{}/events", Self::calendar_path(connection)))
+                .bearer_auth(access_token)
+                .json(&body)
+                .send()
+                .await?;
+        }
+
+        let saved: Value = check_response(response, "Microsoft Graph").await?.json().await?;
+        Ok(PushedEvent {
+            external_id: saved["id"]
+                .as_str()
+                .ok_or_else(|| AppError::external_service("Microsoft Graph", "Missing event id"))?
+                .to_string(),
+            etag: saved["changeKey"].as_str().map(String::from),
+        })
+    }
+
+    async fn delete_event(
+        &self,
+        _connection: &CalendarConnection,
+        access_token: &str,
+        external_id: &str,
+    ) -> AppResult<()> {
+        let response = self
+            .http
+            .delete(format!("{}/me/events/{}", GRAPH_API, external_id))
+            .bearer_auth(access_token)
+            .send()
+            .await?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(());
+        }
+        check_response(response, "Microsoft Graph").await?;
+        Ok(())
+    }
+
+    async fn refresh_access_token(&self, refresh_token: &str) -> AppResult<RefreshedToken> {
+        refresh_token_grant(
+            &self.http,
+            MICROSOFT_TOKEN_URL,
+            "Microsoft Graph",
+            "MICROSOFT",
+            refresh_token,
+            Some(MICROSOFT_SCOPES),
+        )
+        .await
+    }
+}
+
+// ============================================================================
+// SYNC SERVICE
+// ============================================================================
+
+/// Manages calendar connections and runs sync passes
+#[derive(Clone)]
+pub struct CalendarSyncService {
+    db: Database,
+    google: GoogleCalendarProvider,
+    microsoft: MicrosoftGraphProvider,
+}
+
+impl CalendarSyncService {
+    pub fn new(db: Database) -> Self {
+        let http = Client::new();
+        Self {
+            db,
+            google: GoogleCalendarProvider::new(http.clone()),
+            microsoft: MicrosoftGraphProvider::new(http),
+        }
+    }
+
+    fn encryption_key() -> AppResult<[u8; 32]> {
+        let key = std::env::var("ENCRYPTION_KEY")
+            .map_err(|_| AppError::Configuration("ENCRYPTION_KEY is not set".to_string()))?;
+        parse_encryption_key(&key)
+    }
+
+    /// Connect (or reconnect) the user's external calendar.
+    ///
+    /// Reconnecting clears the sync token so the next pass does a full sync.
+    pub async fn connect(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        request: &ConnectCalendarRequest,
+    ) -> AppResult<CalendarConnection> {
+        let key = Self::encryption_key()?;
+        let token = encrypt(&request.access_token, &key)?;
+        let refresh_token = request
+            .refresh_token
+            .as_deref()
+            .map(|refresh_token| encrypt(refresh_token, &key))
+            .transpose()?;
+        let calendar_id = request.calendar_id.as_deref().unwrap_or("primary");
+
+        let row = sqlx::query_as::<_, CalendarConnectionRow>(
+            r#"
+            INSERT INTO calendar_connections (tenant_id, user_id, provider, calendar_id, access_token_encrypted,
+                                              conflict_policy, refresh_token_encrypted)
+            VALUES ($1, $2, $3, $4, $5, COALESCE($6, 'local_wins'), $7)
+            ON CONFLICT (user_id, provider) DO UPDATE
+            SET calendar_id = EXCLUDED.calendar_id,
+                access_token_encrypted = EXCLUDED.access_token_encrypted,
+                refresh_token_encrypted = EXCLUDED.refresh_token_encrypted,
+                conflict_policy = COALESCE($6, calendar_connections.conflict_policy),
+                sync_token = NULL,
+                is_active = TRUE,
+                last_error = NULL
+            RETURNING id, tenant_id, user_id, provider, calendar_id, sync_token, is_active,
+                      last_sync_at, sync_status, last_error, conflict_policy
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(request.provider.as_str())
+        .bind(calendar_id)
+        .bind(&token)
+        .bind(request.conflict_policy.map(|policy| policy.as_str()))
+        .bind(refresh_token)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(row.into())
+    }
+
+    /// The user's calendar connections
+    pub async fn list_connections(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> AppResult<Vec<CalendarConnection>> {
+        let rows = sqlx::query_as::<_, CalendarConnectionRow>(
+            r#"
+            SELECT id, tenant_id, user_id, provider, calendar_id, sync_token, is_active,
+                   last_sync_at, sync_status, last_error, conflict_policy
+            FROM calendar_connections
+            WHERE tenant_id = $1 AND user_id = $2
+            ORDER BY provider
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Run one two-way sync pass for a connection, recording the outcome on it
+    pub async fn sync_connection(
+        &self,
+        tenant_id: Uuid,
+        connection_id: Uuid,
+    ) -> AppResult<SyncSummary> {
+        let row = sqlx::query_as::<_, CalendarConnectionRow>(
+            r#"
+            SELECT id, tenant_id, user_id, provider, calendar_id, sync_token, is_active,
+                   last_sync_at, sync_status, last_error, conflict_policy
+            FROM calendar_connections
+            WHERE tenant_id = $1 AND id = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(connection_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Calendar connection".to_string()))?;
+        let connection: CalendarConnection = row.into();
+
+        if !connection.is_active {
+            return Err(AppError::BadRequest("Calendar connection is disabled".to_string()));
+        }
+
+        let result = match connection.provider {
+            CalendarProvider::Google => self.sync_with(&self.google, &connection).await,
+            CalendarProvider::Microsoft => self.sync_with(&self.microsoft, &connection).await,
+        };
+
+        if let Err(ref e) = result {
+            sqlx::query(
+                "UPDATE calendar_connections SET sync_status = 'failed', last_error = $1 WHERE id = $2",
+            )
+            .bind(e.to_string())
+            .bind(connection.id)
+            .execute(self.db.pool())
+            .await?;
+        }
+
+        result
+    }
+
+    /// The connection's access token. Access tokens only last about an hour,
+    /// so when a refresh token is stored a new one is fetched for each pass,
+    /// keeping any refresh token the provider rotated.
+    async fn access_token<P: CalendarSyncProvider>(
+        &self,
+        provider: &P,
+        connection: &CalendarConnection,
+    ) -> AppResult<String> {
+        let key = Self::encryption_key()?;
+        let (access_token, refresh_token): (String, Option<String>) = sqlx::query_as(
+            "SELECT access_token_encrypted, refresh_token_encrypted FROM calendar_connections WHERE id = $1",
+        )
+        .bind(connection.id)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        let Some(refresh_token) = refresh_token else {
+            return decrypt(&access_token, &key);
+        };
+
+        let refreshed = provider.refresh_access_token(&decrypt(&refresh_token, &key)?).await?;
+        let rotated = refreshed
+            .refresh_token
+            .as_deref()
+            .map(|refresh_token| encrypt(refresh_token, &key))
+            .transpose()?;
+        sqlx::query(
+            r#"
+            UPDATE calendar_connections
+            SET access_token_encrypted = $1, refresh_token_encrypted = COALESCE($2, refresh_token_encrypted)
+            WHERE id = $3
+            "#,
+        )
+        .bind(encrypt(&refreshed.access_token, &key)?)
+        .bind(rotated)
+        .bind(connection.id)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(refreshed.access_token)
+    }
+
+    async fn sync_with<P: CalendarSyncProvider>(
+        &self,
+        provider: &P,
+        connection: &CalendarConnection,
+    ) -> AppResult<SyncSummary> {
+        let access_token = &self.access_token(provider, connection).await?;
+        let mut summary = SyncSummary::default();
+
+        // Pull
+        let delta = provider.list_changes(connection, access_token).await?;
+        let mappings = self.mappings(connection.id).await?;
+        let plan = delta.plan(&mappings);
+
+        // Appointments to push whatever their timestamps say, and ones held
+        // back because they wait on someone to settle a conflict
+        let mut force_push = HashSet::new();
+        let mut held: HashSet<Uuid> = self.flagged_appointments(connection).await?;
+        let appointments = if plan.remote_changes.is_empty() {
+            Vec::new()
+        } else {
+            self.outbound_events(connection).await?
+        };
+
+        let mut tx = self.db.pool().begin().await?;
+
+        for (appointment_id, remote) in &plan.remote_changes {
+            let local = appointments.iter().find(|a| a.appointment_id == *appointment_id);
+            let mapping = mappings.iter().find(|m| m.appointment_id == Some(*appointment_id));
+            let (Some(local), Some(mapping)) = (local, mapping) else {
+                continue;
+            };
+            // Cancelled appointments are deleted remotely by the push below
+            if local.cancelled || held.contains(appointment_id) {
+                continue;
+            }
+
+            let result = local.reconcile(remote, mapping, connection.conflict_policy, Utc::now());
+            match result.decision {
+                SyncDecision::InSync => {}
+                SyncDecision::PushLocal => {
+                    force_push.insert(*appointment_id);
+                }
+                SyncDecision::PullRemote => {
+                    sqlx::query(
+                        "UPDATE appointments SET start_time = $1, end_time = $2, updated_at = NOW() WHERE tenant_id = $3 AND id = $4",
+                    )
+                    .bind(remote.start_time)
+                    .bind(remote.end_time)
+                    .bind(connection.tenant_id)
+                    .bind(appointment_id)
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                SyncDecision::FlagForReview => {
+                    held.insert(*appointment_id);
+                }
+            }
+
+            // Both copies now agree with the remote one; NOW() is the same
+            // within the transaction so the appointment isn't pushed back
+            if matches!(result.decision, SyncDecision::InSync | SyncDecision::PullRemote) {
+                sqlx::query(
+                    r#"
+                    UPDATE calendar_event_mappings
+                    SET start_time = $1, end_time = $2, etag = $3, synced_at = NOW()
+                    WHERE connection_id = $4 AND appointment_id = $5
+                    "#,
+                )
+                .bind(remote.start_time)
+                .bind(remote.end_time)
+                .bind(&remote.etag)
+                .bind(connection.id)
+                .bind(appointment_id)
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            if result.decision != SyncDecision::InSync {
+                Self::record_reconciliation(&mut tx, connection, local, remote, &result).await?;
+            }
+            if result.conflict {
+                summary.conflicts += 1;
+            }
+        }
+
+        for event in &plan.upsert {
+            sqlx::query(
+                r#"
+                INSERT INTO calendar_event_mappings (tenant_id, connection_id, external_event_id, title, start_time, end_time, etag)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                ON CONFLICT (connection_id, external_event_id) DO UPDATE
+                SET title = EXCLUDED.title,
+                    start_time = EXCLUDED.start_time,
+                    end_time = EXCLUDED.end_time,
+                    etag = EXCLUDED.etag,
+                    synced_at = NOW()
+                "#,
+            )
+            .bind(connection.tenant_id)
+            .bind(connection.id)
+            .bind(&event.external_id)
+            .bind(&event.title)
+            .bind(event.start_time)
+            .bind(event.end_time)
+            .bind(&event.etag)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for external_id in &plan.remove {
+            sqlx::query(
+                "DELETE FROM calendar_event_mappings WHERE connection_id = $1 AND external_event_id = $2 AND appointment_id IS NULL",
+            )
+            .bind(connection.id)
+            .bind(external_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        // Dropping the mapping makes the push below create the event again
+        for appointment_id in &plan.repush {
+            sqlx::query(
+                "DELETE FROM calendar_event_mappings WHERE connection_id = $1 AND appointment_id = $2",
+            )
+            .bind(connection.id)
+            .bind(appointment_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        summary.pulled = plan.upsert.len();
+        summary.removed = plan.remove.len();
+
+        // Push
+        let mappings = self.mappings(connection.id).await?;
+        for event in self.outbound_events(connection).await? {
+            let mapping = mappings
+                .iter()
+                .find(|m| m.appointment_id == Some(event.appointment_id));
+
+            if held.contains(&event.appointment_id) {
+                continue;
+            }
+
+            let action = match (PushAction::plan(&event, mapping), mapping) {
+                (PushAction::Skip, Some(m)) if force_push.contains(&event.appointment_id) => {
+                    PushAction::Update(m.external_event_id.clone())
+                }
+                (action, _) => action,
+            };
+
+            match action {
+                PushAction::Create | PushAction::Update(_) => {
+                    let existing = mapping.map(|m| m.external_event_id.as_str());
+                    let pushed = provider
+                        .upsert_event(connection, access_token, existing, &event)
+                        .await?;
+                    self.save_pushed(connection, &event, &pushed).await?;
+                    summary.pushed += 1;
+                }
+                PushAction::Delete(external_id) => {
+                    provider
+                        .delete_event(connection, access_token, &external_id)
+                        .await?;
+                    sqlx::query(
+                        "DELETE FROM calendar_event_mappings WHERE connection_id = $1 AND appointment_id = $2",
+                    )
+                    .bind(connection.id)
+                    .bind(event.appointment_id)
+                    .execute(self.db.pool())
+                    .await?;
+                    summary.deleted += 1;
+                }
+                PushAction::Skip => {}
+            }
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE calendar_connections
+            SET sync_token = $1, last_sync_at = NOW(), sync_status = 'success', last_error = NULL
+            WHERE id = $2
+            "#,
+        )
+        .bind(&delta.next_sync_token)
+        .bind(connection.id)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(summary)
+    }
+
+    async fn mappings(&self, connection_id: Uuid) -> AppResult<Vec<EventMapping>> {
+        let rows = sqlx::query_as::<_, EventMappingRow>(
+            r#"
+            SELECT external_event_id, appointment_id, start_time, end_time, etag, synced_at
+            FROM calendar_event_mappings
+            WHERE connection_id = $1
+            "#,
+        )
+        .bind(connection_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// The user's current and upcoming appointments, including cancelled ones
+    /// so their remote copies can be removed
+    async fn outbound_events(&self, connection: &CalendarConnection) -> AppResult<Vec<OutboundEvent>> {
+        let rows = sqlx::query_as::<_, OutboundEventRow>(
+            r#"
+            SELECT id, title, description, location, start_time, end_time,
+                   COALESCE(status = 'cancelled', FALSE) AS cancelled, updated_at
+            FROM appointments
+            WHERE tenant_id = $1 AND assigned_to_id = $2 AND end_time > NOW() - INTERVAL '1 day'
+            ORDER BY start_time
+            "#,
+        )
+        .bind(connection.tenant_id)
+        .bind(connection.user_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Recorded reconciliations for one of the user's connections, newest first
+    pub async fn list_reconciliations(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        connection_id: Uuid,
+    ) -> AppResult<Vec<SyncReconciliation>> {
+        let rows = sqlx::query_as::<_, SyncReconciliationRow>(&format!(
+            r#"
+            SELECT {}
+            FROM sync_reconciliations r
+            JOIN calendar_connections c ON c.id = r.connection_id
+            WHERE r.tenant_id = $1 AND c.user_id = $2 AND r.connection_id = $3
+            ORDER BY r.created_at DESC
+            LIMIT 100
+            "#,
+            RECONCILIATION_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(connection_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Settle a flagged conflict. Keeping the remote copy moves the
+    /// appointment to the remote times; keeping the PSA copy marks the
+    /// appointment changed so the next sync pushes it over the remote one.
+    pub async fn resolve_reconciliation(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        reconciliation_id: Uuid,
+        request: &ResolveReconciliationRequest,
+    ) -> AppResult<SyncReconciliation> {
+        if !matches!(request.decision, SyncDecision::PushLocal | SyncDecision::PullRemote) {
+            return Err(AppError::validation_field(
+                "decision",
+                "Resolve by keeping the local or the remote copy",
+            ));
+        }
+
+        let row = sqlx::query_as::<_, SyncReconciliationRow>(&format!(
+            r#"
+            SELECT {}
+            FROM sync_reconciliations r
+            JOIN calendar_connections c ON c.id = r.connection_id
+            WHERE r.tenant_id = $1 AND c.user_id = $2 AND r.id = $3
+            "#,
+            RECONCILIATION_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(reconciliation_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::not_found("Sync reconciliation"))?;
+        let reconciliation: SyncReconciliation = row.into();
+
+        if reconciliation.decision != SyncDecision::FlagForReview || reconciliation.resolved_at.is_some() {
+            return Err(AppError::conflict("Only open conflicts flagged for review can be resolved"));
+        }
+
+        let mut tx = self.db.pool().begin().await?;
+        if request.decision == SyncDecision::PullRemote {
+            let times: EventTimes = serde_json::from_value(reconciliation.remote_value.clone())?;
+            sqlx::query(
+                "UPDATE appointments SET start_time = $1, end_time = $2, updated_at = NOW() WHERE tenant_id = $3 AND id = $4",
+            )
+            .bind(times.start_time)
+            .bind(times.end_time)
+            .bind(tenant_id)
+            .bind(reconciliation.local_id)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query(
+                r#"
+                UPDATE calendar_event_mappings
+                SET start_time = $1, end_time = $2, synced_at = NOW()
+                WHERE connection_id = $3 AND appointment_id = $4
+                "#,
+            )
+            .bind(times.start_time)
+            .bind(times.end_time)
+            .bind(reconciliation.connection_id)
+            .bind(reconciliation.local_id)
+            .execute(&mut *tx)
+            .await?;
+        } else {
+            sqlx::query("UPDATE appointments SET updated_at = NOW() WHERE tenant_id = $1 AND id = $2")
+                .bind(tenant_id)
+                .bind(reconciliation.local_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        sqlx::query(
+            "UPDATE sync_reconciliations SET resolved_at = NOW(), resolved_by_id = $1 WHERE id = $2",
+        )
+        .bind(user_id)
+        .bind(reconciliation_id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(SyncReconciliation {
+            resolved_at: Some(Utc::now()),
+            resolved_by_id: Some(user_id),
+            ..reconciliation
+        })
+    }
+
+    /// Appointments with a conflict still waiting on someone, kept out of sync
+    async fn flagged_appointments(&self, connection: &CalendarConnection) -> AppResult<HashSet<Uuid>> {
+        let ids: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT local_id FROM sync_reconciliations
+            WHERE connection_id = $1 AND decision = 'flag_for_review' AND resolved_at IS NULL
+            "#,
+        )
+        .bind(connection.id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(ids.into_iter().collect())
+    }
+
+    async fn record_reconciliation(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        connection: &CalendarConnection,
+        local: &OutboundEvent,
+        remote: &ExternalEvent,
+        result: &Reconciliation,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO sync_reconciliations (tenant_id, integration, connection_id, entity_type, local_id,
+                                              external_id, policy, decision, conflict, local_value, remote_value,
+                                              local_modified_at, remote_modified_at)
+            VALUES ($1, 'calendar', $2, 'appointment', $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            "#,
+        )
+        .bind(connection.tenant_id)
+        .bind(connection.id)
+        .bind(local.appointment_id)
+        .bind(&remote.external_id)
+        .bind(result.policy.as_str())
+        .bind(result.decision.as_str())
+        .bind(result.conflict)
+        .bind(serde_json::to_value(EventTimes::of_appointment(local))?)
+        .bind(serde_json::to_value(EventTimes::of_event(remote))?)
+        .bind(local.updated_at)
+        .bind(remote.updated_at.unwrap_or_else(Utc::now))
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn save_pushed(
+        &self,
+        connection: &CalendarConnection,
+        event: &OutboundEvent,
+        pushed: &PushedEvent,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO calendar_event_mappings (tenant_id, connection_id, external_event_id, appointment_id, title, start_time, end_time, etag)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (connection_id, appointment_id) DO UPDATE
+            SET external_event_id = EXCLUDED.external_event_id,
+                title = EXCLUDED.title,
+                start_time = EXCLUDED.start_time,
+                end_time = EXCLUDED.end_time,
+                etag = EXCLUDED.etag,
+                synced_at = NOW()
+            "#,
+        )
+        .bind(connection.tenant_id)
+        .bind(connection.id)
+        .bind(&pushed.external_id)
+        .bind(event.appointment_id)
+        .bind(&event.title)
+        .bind(event.start_time)
+        .bind(event.end_time)
+        .bind(&pushed.etag)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Provider edit timestamps are RFC 3339; Graph adds up to seven fractional digits
+fn parse_modified(value: &Value) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value.as_str()?)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+// ============================================================================
+// DATABASE ROW TYPES
+// ============================================================================
+
+pub(crate) const RECONCILIATION_COLUMNS: &str = "r.id, r.integration, r.connection_id, r.entity_type, r.local_id, \
+     r.external_id, r.policy, r.decision, r.conflict, r.local_value, r.remote_value, r.local_modified_at, \
+     r.remote_modified_at, r.resolved_at, r.resolved_by_id, r.created_at";
+
+#[derive(sqlx::FromRow)]
+struct CalendarConnectionRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    provider: String,
+    calendar_id: String,
+    sync_token: Option<String>,
+    is_active: bool,
+    last_sync_at: Option<DateTime<Utc>>,
+    sync_status: String,
+    last_error: Option<String>,
+    conflict_policy: String,
+}
+
+impl From<CalendarConnectionRow> for CalendarConnection {
+    fn from(row: CalendarConnectionRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            user_id: row.user_id,
+            provider: CalendarProvider::from_str(&row.provider).unwrap_or_default(),
+            calendar_id: row.calendar_id,
+            sync_token: row.sync_token,
+            is_active: row.is_active,
+            last_sync_at: row.last_sync_at,
+            sync_status: row.sync_status,
+            last_error: row.last_error,
+            conflict_policy: ConflictPolicy::from_str(&row.conflict_policy).unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct EventMappingRow {
+    external_event_id: String,
+    appointment_id: Option<Uuid>,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+    etag: Option<String>,
+    synced_at: DateTime<Utc>,
+}
+
+impl From<EventMappingRow> for EventMapping {
+    fn from(row: EventMappingRow) -> Self {
+        Self {
+            external_event_id: row.external_event_id,
+            appointment_id: row.appointment_id,
+            start_time: row.start_time,
+            end_time: row.end_time,
+            etag: row.etag,
+            synced_at: row.synced_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct OutboundEventRow {
+    id: Uuid,
+    title: String,
+    description: Option<String>,
+    location: Option<String>,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+    cancelled: bool,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<OutboundEventRow> for OutboundEvent {
+    fn from(row: OutboundEventRow) -> Self {
+        Self {
+            appointment_id: row.id,
+            title: row.title,
+            description: row.description,
+            location: row.location,
+            start_time: row.start_time,
+            end_time: row.end_time,
+            cancelled: row.cancelled,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+pub(crate) struct SyncReconciliationRow {
+    id: Uuid,
+    integration: String,
+    connection_id: Option<Uuid>,
+    entity_type: String,
+    local_id: Uuid,
+    external_id: String,
+    policy: String,
+    decision: String,
+    conflict: bool,
+    local_value: Value,
+    remote_value: Value,
+    local_modified_at: DateTime<Utc>,
+    remote_modified_at: DateTime<Utc>,
+    resolved_at: Option<DateTime<Utc>>,
+    resolved_by_id: Option<Uuid>,
+    created_at: DateTime<Utc>,
+}
+
+impl From<SyncReconciliationRow> for SyncReconciliation {
+    fn from(row: SyncReconciliationRow) -> Self {
+        Self {
+            id: row.id,
+            integration: row.integration,
+            connection_id: row.connection_id,
+            entity_type: row.entity_type,
+            local_id: row.local_id,
+            external_id: row.external_id,
+            policy: ConflictPolicy::from_str(&row.policy).unwrap_or_default(),
+            decision: SyncDecision::from_str(&row.decision).unwrap_or(SyncDecision::FlagForReview),
+            conflict: row.conflict,
+            local_value: row.local_value,
+            remote_value: row.remote_value,
+            local_modified_at: row.local_modified_at,
+            remote_modified_at: row.remote_modified_at,
+            resolved_at: row.resolved_at,
+            resolved_by_id: row.resolved_by_id,
+            created_at: row.created_at,
+        }
+    }
+}
@@ -7,9 +7,13 @@ mod models;
 mod service;
 #[cfg(feature = "server")]
 mod routes;
+#[cfg(feature = "server")]
+mod privacy;
 
 pub use models::*;
 #[cfg(feature = "server")]
 pub use service::ContactService;
 #[cfg(feature = "server")]
 pub use routes::contact_routes;
+#[cfg(feature = "server")]
+pub use privacy::PrivacyService;
@@ -5,6 +5,8 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::utils::error::AppError;
+
 // ============================================================================
 // COMPANY TYPES
 // ============================================================================
@@ -144,6 +146,7 @@ pub struct Company {
     pub tax_exempt: bool,
     pub custom_fields: serde_json::Value,
     pub tags: Vec<String>,
+    /// Pinned summary; the history is in [`CrmNote`]s
     pub notes: Option<String>,
     pub logo_url: Option<String>,
     pub portal_enabled: bool,
@@ -285,6 +288,49 @@ pub struct CompanyDetailResponse {
     pub logo_url: Option<String>,
 }
 
+// ============================================================================
+// COMPANY HIERARCHY
+// ============================================================================
+
+/// A company within a hierarchy, `depth` levels below the root
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CompanyTreeNode {
+    pub id: Uuid,
+    pub name: String,
+    pub parent_company_id: Option<Uuid>,
+    pub depth: u32,
+}
+
+/// A company and all of its subsidiaries, root first
+#[derive(Debug, Clone, Serialize)]
+pub struct CompanyTree {
+    pub root_id: Uuid,
+    pub companies: Vec<CompanyTreeNode>,
+}
+
+impl CompanyTree {
+    /// Ids of the root and every descendant
+    pub fn ids(&self) -> Vec<Uuid> {
+        self.companies.iter().map(|c| c.id).collect()
+    }
+
+    pub fn contains(&self, company_id: Uuid) -> bool {
+        self.companies.iter().any(|c| c.id == company_id)
+    }
+
+    /// Check that `parent_id` may become the parent of this tree's root. A
+    /// company cannot sit under itself or under one of its own subsidiaries.
+    pub fn check_parent(&self, parent_id: Uuid) -> Result<(), AppError> {
+        if self.contains(parent_id) {
+            return Err(AppError::validation_field(
+                "parent_company_id",
+                "A company cannot be placed under itself or one of its subsidiaries",
+            ));
+        }
+        Ok(())
+    }
+}
+
 // ============================================================================
 // CONTACT TYPES
 // ============================================================================
@@ -361,6 +407,38 @@ impl std::str::FromStr for ContactStatus {
     }
 }
 
+/// Whether mail to a contact's address is getting through, as reported by
+/// the email provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailDeliverability {
+    #[default]
+    Ok,
+    /// Hard bounced; the address is suppressed
+    Bounced,
+    /// Reported our mail as spam; the address is suppressed
+    Complained,
+}
+
+impl EmailDeliverability {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "ok" => Some(Self::Ok),
+            "bounced" => Some(Self::Bounced),
+            "complained" => Some(Self::Complained),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Bounced => "bounced",
+            Self::Complained => "complained",
+        }
+    }
+}
+
 /// Contact database model
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Contact {
@@ -370,6 +448,7 @@ pub struct Contact {
     pub first_name: String,
     pub last_name: String,
     pub email: Option<String>,
+    pub email_deliverability: EmailDeliverability,
     pub phone: Option<String>,
     pub mobile: Option<String>,
     pub fax: Option<String>,
@@ -383,6 +462,7 @@ pub struct Contact {
     pub locale: String,
     pub custom_fields: serde_json::Value,
     pub tags: Vec<String>,
+    /// Pinned summary; the history is in [`CrmNote`]s
     pub notes: Option<String>,
     pub avatar_url: Option<String>,
     pub status: ContactStatus,
@@ -522,6 +602,142 @@ impl From<Contact> for ContactResponse {
     }
 }
 
+// ============================================================================
+// CONTACT ROLES
+// ============================================================================
+
+/// A role a contact holds at their company; a contact may hold several
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContactRole {
+    Primary,
+    Billing,
+    Technical,
+    DecisionMaker,
+}
+
+impl ContactRole {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "primary" => Some(Self::Primary),
+            "billing" => Some(Self::Billing),
+            "technical" => Some(Self::Technical),
+            "decision_maker" => Some(Self::DecisionMaker),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Primary => "primary",
+            Self::Billing => "billing",
+            Self::Technical => "technical",
+            Self::DecisionMaker => "decision_maker",
+        }
+    }
+}
+
+/// Replace the roles a contact holds
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct SetContactRolesRequest {
+    pub roles: Vec<ContactRole>,
+}
+
+impl SetContactRolesRequest {
+    /// The requested roles without duplicates, in a stable order
+    pub fn normalized(&self) -> Vec<ContactRole> {
+        let mut roles = self.roles.clone();
+        roles.sort();
+        roles.dedup();
+        roles
+    }
+}
+
+/// A contact of a company and the roles they hold there
+#[derive(Debug, Clone, Serialize)]
+pub struct CompanyContactRoles {
+    pub contact_id: Uuid,
+    pub contact_name: String,
+    pub roles: Vec<ContactRole>,
+}
+
+/// Set a company's default billing and technical contacts. Fields left out
+/// keep their current value.
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct SetDefaultContactsRequest {
+    pub default_billing_contact_id: Option<Uuid>,
+    pub default_technical_contact_id: Option<Uuid>,
+}
+
+impl SetDefaultContactsRequest {
+    /// `(field, contact, role it implies)` for each default being set
+    pub fn defaults(&self) -> Vec<(&'static str, Uuid, ContactRole)> {
+        [
+            ("default_billing_contact_id", self.default_billing_contact_id, ContactRole::Billing),
+            ("default_technical_contact_id", self.default_technical_contact_id, ContactRole::Technical),
+        ]
+        .into_iter()
+        .filter_map(|(field, contact_id, role)| contact_id.map(|id| (field, id, role)))
+        .collect()
+    }
+}
+
+/// Check that `contact` can be a default contact of `company_id`
+pub fn check_default_contact(field: &str, contact: &Contact, company_id: Uuid) -> Result<(), AppError> {
+    if contact.company_id != company_id {
+        return Err(AppError::validation_field(
+            field.to_string(),
+            format!("{} is not a contact of this company", contact.full_name()),
+        ));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// CRM NOTES
+// ============================================================================
+
+/// A note added to a company or one of its contacts. Notes are never edited.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrmNote {
+    pub id: Uuid,
+    pub company_id: Uuid,
+    /// Set for notes on a contact
+    pub contact_id: Option<Uuid>,
+    /// `None` once the author's account is deleted
+    pub author_id: Option<Uuid>,
+    pub author_name: Option<String>,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl CrmNote {
+    /// Notes newest first, the order the activity cards show them in. Notes
+    /// added at the same instant keep a stable order.
+    pub fn newest_first(mut notes: Vec<CrmNote>) -> Vec<CrmNote> {
+        notes.sort_by(|a, b| b.created_at.cmp(&a.created_at).then_with(|| b.id.cmp(&a.id)));
+        notes
+    }
+}
+
+/// Add a note to a company or contact
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CreateCrmNoteRequest {
+    #[validate(length(min = 1, max = 10000))]
+    pub body: String,
+}
+
+impl CreateCrmNoteRequest {
+    /// The note's text, trimmed; blank notes are rejected
+    pub fn body(&self) -> Result<&str, AppError> {
+        let body = self.body.trim();
+        if body.is_empty() {
+            return Err(AppError::validation_field("body", "Note can't be blank"));
+        }
+        Ok(body)
+    }
+}
+
 // ============================================================================
 // SITE TYPES
 // ============================================================================
@@ -609,7 +825,7 @@ impl From<Site> for SiteResponse {
 // ============================================================================
 
 /// Company filter parameters
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct CompanyFilter {
     pub q: Option<String>,
     pub company_type: Option<CompanyType>,
@@ -619,7 +835,7 @@ pub struct CompanyFilter {
 }
 
 /// Contact filter parameters
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct ContactFilter {
     pub q: Option<String>,
     pub company_id: Option<Uuid>,
@@ -628,3 +844,566 @@ pub struct ContactFilter {
     pub is_portal_user: Option<bool>,
     pub tags: Option<String>,
 }
+
+// ============================================================================
+// BULK OPERATIONS
+// ============================================================================
+
+
+/// What a bulk operation did to one row
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkOutcome {
+    Updated,
+    /// Already as requested
+    Unchanged,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BulkResult {
+    pub id: Uuid,
+    pub outcome: BulkOutcome,
+}
+
+/// Per-id results of a bulk operation, in request order
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkReport {
+    pub updated: usize,
+    pub unchanged: usize,
+    pub results: Vec<BulkResult>,
+    /// `false` for a dry run: `updated` is what would have changed
+    pub committed: bool,
+}
+
+impl BulkReport {
+    pub fn new(results: Vec<BulkResult>) -> Self {
+        let updated = results.iter().filter(|r| r.outcome == BulkOutcome::Updated).count();
+        Self {
+            updated,
+            unchanged: results.len() - updated,
+            results,
+            committed: true,
+        }
+    }
+
+    /// The same report for changes that were rolled back
+    pub fn preview(self) -> Self {
+        Self {
+            committed: false,
+            ..self
+        }
+    }
+}
+
+/// The requested ids without duplicates, in request order. Every one must be
+/// among `found`, the ids that exist in the tenant, or nothing is changed.
+pub fn check_bulk_ids(ids: &[Uuid], found: &[Uuid]) -> Result<Vec<Uuid>, AppError> {
+    let mut unique: Vec<Uuid> = Vec::with_capacity(ids.len());
+    for id in ids {
+        if !unique.contains(id) {
+            unique.push(*id);
+        }
+    }
+
+    let missing: Vec<String> = unique
+        .iter()
+        .filter(|id| !found.contains(id))
+        .map(|id| id.to_string())
+        .collect();
+    if !missing.is_empty() {
+        return Err(AppError::validation_field("ids", format!("Not found: {}", missing.join(", "))));
+    }
+
+    Ok(unique)
+}
+
+/// Add and remove tags on many companies or contacts at once
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct BulkTagRequest {
+    #[validate(length(min = 1, max = 500))]
+    pub ids: Vec<Uuid>,
+    #[serde(default)]
+    pub add: Vec<String>,
+    #[serde(default)]
+    pub remove: Vec<String>,
+    /// Validate and report what would change without writing it
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+impl BulkTagRequest {
+    pub fn check(&self) -> Result<(), AppError> {
+        if self.add.is_empty() && self.remove.is_empty() {
+            return Err(AppError::validation_field("add", "Nothing to add or remove"));
+        }
+        if let Some(tag) = self.add.iter().find(|tag| self.remove.contains(tag)) {
+            return Err(AppError::validation_field("remove", format!("'{}' is both added and removed", tag)));
+        }
+        if self.add.iter().any(|tag| tag.trim().is_empty()) {
+            return Err(AppError::validation_field("add", "Tags cannot be blank"));
+        }
+        Ok(())
+    }
+
+    /// New tags for each row, given each row's current tags; `None` when the
+    /// row is already as requested
+    pub fn retag(&self, current: &[String]) -> Option<Vec<String>> {
+        let mut tags: Vec<String> = current
+            .iter()
+            .filter(|tag| !self.remove.contains(tag))
+            .cloned()
+            .collect();
+        for tag in &self.add {
+            let tag = tag.trim().to_string();
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+
+        (tags != current).then_some(tags)
+    }
+
+    /// Tag changes for every requested row, failing as a whole when any id
+    /// isn't one of `rows` (the tenant's matching rows and their tags)
+    pub fn plan(&self, rows: &[(Uuid, Vec<String>)]) -> Result<Vec<(Uuid, Option<Vec<String>>)>, AppError> {
+        let found: Vec<Uuid> = rows.iter().map(|(id, _)| *id).collect();
+        let ids = check_bulk_ids(&self.ids, &found)?;
+
+        Ok(ids
+            .into_iter()
+            .map(|id| {
+                let current = rows.iter().find(|(row_id, _)| *row_id == id).map(|(_, tags)| tags.as_slice());
+                (id, self.retag(current.unwrap_or_default()))
+            })
+            .collect())
+    }
+}
+
+/// Set the status of many companies at once
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct BulkCompanyStatusRequest {
+    #[validate(length(min = 1, max = 500))]
+    pub ids: Vec<Uuid>,
+    pub status: CompanyStatus,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Set the status of many contacts at once
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct BulkContactStatusRequest {
+    #[validate(length(min = 1, max = 500))]
+    pub ids: Vec<Uuid>,
+    pub status: ContactStatus,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+// ============================================================================
+// PRIVACY
+// ============================================================================
+
+/// Replacement for personal data scrubbed from free text
+pub const REDACTED: &str = "[redacted]";
+
+/// How a contact's personal data is erased on a data deletion request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErasureMode {
+    /// Replace personal data with placeholders; the contact row and its
+    /// ticket history stay so reporting is unchanged
+    Anonymize,
+    /// Remove the contact; linked tickets, assets and appointments are kept
+    /// but no longer point at it
+    Delete,
+}
+
+impl ErasureMode {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "anonymize" => Some(Self::Anonymize),
+            "delete" => Some(Self::Delete),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Anonymize => "anonymize",
+            Self::Delete => "delete",
+        }
+    }
+
+    /// Whether linked records lose their reference to the contact
+    pub fn unlinks_records(&self) -> bool {
+        matches!(self, Self::Delete)
+    }
+
+    /// `audit_log.action` recorded for the erasure
+    pub fn audit_action(&self) -> &'static str {
+        match self {
+            Self::Anonymize => "update",
+            Self::Delete => "delete",
+        }
+    }
+}
+
+/// Erase contact request
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct EraseContactRequest {
+    pub mode: ErasureMode,
+}
+
+/// The personal data held on a contact that is searched for in free text
+#[derive(Debug, Clone, Default)]
+pub struct ContactPii {
+    pub first_name: String,
+    pub last_name: String,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub mobile: Option<String>,
+}
+
+impl ContactPii {
+    /// Strings to redact from notes and descriptions, longest first so the
+    /// full name is replaced before its parts
+    pub fn needles(&self) -> Vec<String> {
+        let full_name = format!("{} {}", self.first_name, self.last_name);
+        let mut needles: Vec<String> = [Some(full_name), self.email.clone(), self.phone.clone(), self.mobile.clone()]
+            .into_iter()
+            .flatten()
+            .map(|s| s.trim().to_string())
+            .filter(|s| s.len() >= 3)
+            .collect();
+
+        needles.sort_by_key(|s| std::cmp::Reverse(s.len()));
+        needles.dedup();
+        needles
+    }
+
+    /// Replace every case-insensitive occurrence of the contact's details in `text`
+    pub fn scrub(&self, text: &str) -> String {
+        self.needles().iter().fold(text.to_string(), |text, needle| {
+            match regex::RegexBuilder::new(&regex::escape(needle))
+                .case_insensitive(true)
+                .build()
+            {
+                Ok(pattern) => pattern.replace_all(&text, REDACTED).into_owned(),
+                Err(_) => text,
+            }
+        })
+    }
+}
+
+/// Outcome of an erasure, also written to the audit trail
+#[derive(Debug, Clone, Serialize)]
+pub struct ErasureSummary {
+    pub contact_id: Uuid,
+    pub mode: ErasureMode,
+    /// Tickets raised by the contact; kept in either mode
+    pub tickets_retained: u64,
+    /// Notes, descriptions and survey comments that had personal data redacted
+    pub records_scrubbed: u64,
+    pub erased_at: DateTime<Utc>,
+}
+
+impl ErasureSummary {
+    /// `audit_log.new_values` for the erasure. Never includes the erased data.
+    pub fn audit_values(&self) -> serde_json::Value {
+        serde_json::json!({
+            "erasure": self.mode.as_str(),
+            "tickets_retained": self.tickets_retained,
+            "records_scrubbed": self.records_scrubbed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_company_cannot_move_under_its_own_subsidiary() {
+        let (parent, child, grandchild, other) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let node = |id: Uuid, parent_company_id: Option<Uuid>, depth: u32| CompanyTreeNode {
+            id,
+            name: "Company".to_string(),
+            parent_company_id,
+            depth,
+        };
+        // The tree under the company being updated
+        let tree = CompanyTree {
+            root_id: parent,
+            companies: vec![node(parent, None, 0), node(child, Some(parent), 1), node(grandchild, Some(child), 2)],
+        };
+
+        assert!(matches!(tree.check_parent(parent), Err(AppError::Validation { .. })));
+        assert!(matches!(tree.check_parent(grandchild), Err(AppError::Validation { .. })));
+        assert!(tree.check_parent(other).is_ok());
+        assert_eq!(tree.ids(), vec![parent, child, grandchild]);
+    }
+
+    fn contact_at(company_id: Uuid) -> Contact {
+        Contact {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::nil(),
+            company_id,
+            first_name: "Priya".to_string(),
+            last_name: "Raman".to_string(),
+            email: None,
+            email_deliverability: EmailDeliverability::Ok,
+            phone: None,
+            mobile: None,
+            fax: None,
+            title: None,
+            department: None,
+            contact_type: ContactType::Other,
+            is_portal_user: false,
+            portal_user_id: None,
+            preferred_contact_method: PreferredContactMethod::Email,
+            timezone: "UTC".to_string(),
+            locale: "en".to_string(),
+            custom_fields: serde_json::json!({}),
+            tags: vec![],
+            notes: None,
+            avatar_url: None,
+            status: ContactStatus::Active,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_contact_can_hold_multiple_roles() {
+        let request: SetContactRolesRequest =
+            serde_json::from_str(r#"{"roles": ["technical", "billing", "decision_maker", "billing"]}"#).unwrap();
+        assert_eq!(
+            request.normalized(),
+            vec![ContactRole::Billing, ContactRole::Technical, ContactRole::DecisionMaker]
+        );
+
+        let defaults = SetDefaultContactsRequest {
+            default_billing_contact_id: Some(Uuid::nil()),
+            default_technical_contact_id: None,
+        };
+        assert_eq!(
+            defaults.defaults(),
+            vec![("default_billing_contact_id", Uuid::nil(), ContactRole::Billing)]
+        );
+    }
+
+    #[test]
+    fn test_default_contact_from_another_company_is_rejected() {
+        let company_id = Uuid::new_v4();
+        assert!(check_default_contact("default_billing_contact_id", &contact_at(company_id), company_id).is_ok());
+
+        let outsider = contact_at(Uuid::new_v4());
+        match check_default_contact("default_billing_contact_id", &outsider, company_id) {
+            Err(AppError::Validation { errors, .. }) => {
+                assert_eq!(errors[0].field, "default_billing_contact_id");
+                assert_eq!(errors[0].message, "Priya Raman is not a contact of this company");
+            }
+            other => panic!("expected validation error, got {:?}", other),
+        }
+    }
+
+    fn pii() -> ContactPii {
+        ContactPii {
+            first_name: "Jane".to_string(),
+            last_name: "Doe".to_string(),
+            email: Some("jane.doe@customer.com".to_string()),
+            phone: Some("555-0100".to_string()),
+            mobile: None,
+        }
+    }
+
+    #[test]
+    fn test_anonymize_removes_email_but_keeps_tickets() {
+        let mode = ErasureMode::Anonymize;
+        assert!(!mode.unlinks_records());
+        assert!(ErasureMode::Delete.unlinks_records());
+
+        let note = "Spoke with jane DOE (Jane.Doe@Customer.com, 555-0100) about the printer";
+        let scrubbed = pii().scrub(note);
+        assert!(!scrubbed.to_lowercase().contains("jane.doe@customer.com"));
+        assert!(!scrubbed.contains("555-0100"));
+        assert_eq!(
+            scrubbed,
+            "Spoke with [redacted] ([redacted], [redacted]) about the printer"
+        );
+    }
+
+    #[test]
+    fn test_erasure_is_audited_without_personal_data() {
+        let summary = ErasureSummary {
+            contact_id: Uuid::new_v4(),
+            mode: ErasureMode::Anonymize,
+            tickets_retained: 12,
+            records_scrubbed: 3,
+            erased_at: Utc::now(),
+        };
+
+        let values = summary.audit_values();
+        assert_eq!(summary.mode.audit_action(), "update");
+        assert_eq!(values["erasure"], "anonymize");
+        assert_eq!(values["tickets_retained"], 12);
+        assert!(!values.to_string().contains("jane"));
+    }
+
+    #[test]
+    fn test_bulk_tag_across_rows() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let rows = vec![
+            (a, vec!["import".to_string()]),
+            (b, vec!["import".to_string(), "vip".to_string()]),
+            (c, vec!["onboarding".to_string()]),
+        ];
+        let request = BulkTagRequest {
+            ids: vec![a, b, c, a],
+            add: vec!["onboarding".to_string()],
+            remove: vec!["import".to_string()],
+            dry_run: false,
+        };
+        assert!(request.check().is_ok());
+
+        let plan = request.plan(&rows).unwrap();
+        assert_eq!(
+            plan,
+            vec![
+                (a, Some(vec!["onboarding".to_string()])),
+                (b, Some(vec!["vip".to_string(), "onboarding".to_string()])),
+                // Already tagged and nothing to remove
+                (c, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bulk_tag_is_all_or_nothing() {
+        let (a, foreign) = (Uuid::new_v4(), Uuid::new_v4());
+        let request = BulkTagRequest {
+            ids: vec![a, foreign],
+            add: vec!["onboarding".to_string()],
+            remove: vec![],
+            dry_run: false,
+        };
+
+        // An id outside the tenant fails the whole request, not just its row
+        match request.plan(&[(a, vec![])]) {
+            Err(AppError::Validation { errors, .. }) => {
+                assert_eq!(errors[0].field, "ids");
+                assert!(errors[0].message.contains(&foreign.to_string()));
+                assert!(!errors[0].message.contains(&a.to_string()));
+            }
+            other => panic!("expected validation error, got {:?}", other),
+        }
+
+        let conflicting = BulkTagRequest {
+            ids: vec![a],
+            add: vec!["vip".to_string()],
+            remove: vec!["vip".to_string()],
+            dry_run: false,
+        };
+        assert!(conflicting.check().is_err());
+    }
+
+    #[test]
+    fn test_bulk_report_counts() {
+        let report = BulkReport::new(vec![
+            BulkResult { id: Uuid::new_v4(), outcome: BulkOutcome::Updated },
+            BulkResult { id: Uuid::new_v4(), outcome: BulkOutcome::Unchanged },
+            BulkResult { id: Uuid::new_v4(), outcome: BulkOutcome::Updated },
+        ]);
+        assert_eq!((report.updated, report.unchanged), (2, 1));
+        assert!(report.committed);
+    }
+
+    #[test]
+    fn test_dry_run_matches_real_run() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let rows = vec![(a, vec!["import".to_string()]), (b, vec!["vip".to_string()])];
+        let real = BulkTagRequest {
+            ids: vec![a, b],
+            add: vec!["vip".to_string()],
+            remove: vec![],
+            dry_run: false,
+        };
+        let dry = BulkTagRequest { dry_run: true, ..real.clone() };
+
+        // Same validation and the same planned changes
+        assert_eq!(dry.check().is_ok(), real.check().is_ok());
+        let plan = dry.plan(&rows).unwrap();
+        assert_eq!(plan, real.plan(&rows).unwrap());
+
+        let results: Vec<BulkResult> = plan
+            .iter()
+            .map(|(id, tags)| BulkResult {
+                id: *id,
+                outcome: if tags.is_some() { BulkOutcome::Updated } else { BulkOutcome::Unchanged },
+            })
+            .collect();
+        let report = BulkReport::new(results.clone()).preview();
+        assert!(!report.committed);
+        assert_eq!((report.updated, report.unchanged), (1, 1));
+        assert_eq!(report.results, BulkReport::new(results).results);
+
+        // A dry run fails for the same ids a real run would
+        let foreign = Uuid::new_v4();
+        let dry = BulkTagRequest { ids: vec![a, foreign], ..dry };
+        let real = BulkTagRequest { dry_run: false, ..dry.clone() };
+        match (dry.plan(&rows), real.plan(&rows)) {
+            (Err(AppError::Validation { errors: dry, .. }), Err(AppError::Validation { errors: real, .. })) => {
+                assert_eq!(dry[0].message, real[0].message);
+            }
+            other => panic!("expected validation errors, got {:?}", other),
+        }
+    }
+
+    fn note(author: (Uuid, &str), body: &str, created_at: DateTime<Utc>) -> CrmNote {
+        CrmNote {
+            id: Uuid::new_v4(),
+            company_id: Uuid::nil(),
+            contact_id: None,
+            author_id: Some(author.0),
+            author_name: Some(author.1.to_string()),
+            body: body.to_string(),
+            created_at,
+        }
+    }
+
+    #[test]
+    fn test_notes_newest_first_with_their_authors() {
+        let alice = (Uuid::new_v4(), "Alice Admin");
+        let bob = (Uuid::new_v4(), "Bob Tech");
+        let now = Utc::now();
+
+        let notes = CrmNote::newest_first(vec![
+            note(alice, "Signed for managed services", now - chrono::Duration::days(30)),
+            note(bob, "Prefers calls before 10am", now - chrono::Duration::hours(2)),
+            note(alice, "Renewal due next quarter", now),
+        ]);
+
+        let bodies: Vec<&str> = notes.iter().map(|n| n.body.as_str()).collect();
+        assert_eq!(
+            bodies,
+            ["Renewal due next quarter", "Prefers calls before 10am", "Signed for managed services"]
+        );
+        // Each note keeps who wrote it
+        let authors: Vec<Option<Uuid>> = notes.iter().map(|n| n.author_id).collect();
+        assert_eq!(authors, [Some(alice.0), Some(bob.0), Some(alice.0)]);
+        assert_eq!(notes[1].author_name.as_deref(), Some("Bob Tech"));
+
+        // Same instant: the order doesn't depend on the order they were loaded in
+        let (a, b) = (note(alice, "a", now), note(bob, "b", now));
+        let forward = CrmNote::newest_first(vec![a.clone(), b.clone()]);
+        let backward = CrmNote::newest_first(vec![b, a]);
+        assert_eq!(forward[0].id, backward[0].id);
+    }
+
+    #[test]
+    fn test_blank_note_is_rejected() {
+        assert!(CreateCrmNoteRequest { body: "  \n ".to_string() }.body().is_err());
+        let request = CreateCrmNoteRequest { body: " Called re: invoice \n".to_string() };
+        assert_eq!(request.body().unwrap(), "Called re: invoice");
+    }
+}
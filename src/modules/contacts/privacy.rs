@@ -0,0 +1,193 @@
+//! Contact data erasure for privacy (GDPR) requests
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::modules::audit::{AuditAction, AuditService, NewAuditEntry};
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::request_id;
+
+use super::models::*;
+
+/// Personal data erasure service
+#[derive(Clone)]
+pub struct PrivacyService {
+    db: Database,
+}
+
+impl PrivacyService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Erase a contact's personal data across CRM and ticket records.
+    ///
+    /// Both modes redact the contact's name, email and phone numbers from the
+    /// descriptions and notes of their tickets and from survey comments.
+    /// Tickets themselves are never removed, so ticket counts and SLA metrics
+    /// are unchanged.
+    pub async fn erase_contact(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        contact_id: Uuid,
+        mode: ErasureMode,
+    ) -> AppResult<ErasureSummary> {
+        let pii = sqlx::query_as::<_, (String, String, Option<String>, Option<String>, Option<String>)>(
+            "SELECT first_name, last_name, email, phone, mobile FROM contacts WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(tenant_id)
+        .bind(contact_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .map(|(first_name, last_name, email, phone, mobile)| ContactPii {
+            first_name,
+            last_name,
+            email,
+            phone,
+            mobile,
+        })
+        .ok_or_else(|| AppError::NotFound("Contact".to_string()))?;
+
+        let mut tx = self.db.pool().begin().await?;
+
+        let tickets_retained: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM tickets WHERE tenant_id = $1 AND contact_id = $2",
+        )
+        .bind(tenant_id)
+        .bind(contact_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let mut records_scrubbed: u64 = 0;
+
+        let tickets = sqlx::query_as::<_, (Uuid, String, Option<String>)>(
+            "SELECT id, title, description FROM tickets WHERE tenant_id = $1 AND contact_id = $2",
+        )
+        .bind(tenant_id)
+        .bind(contact_id)
+        .fetch_all(&mut *tx)
+        .await?;
+        for (ticket_id, title, description) in tickets {
+            let scrubbed_title = pii.scrub(&title);
+            let scrubbed_description = description.as_deref().map(|d| pii.scrub(d));
+            if scrubbed_title != title || scrubbed_description != description {
+                sqlx::query("UPDATE tickets SET title = $1, description = $2 WHERE id = $3")
+                    .bind(&scrubbed_title)
+                    .bind(&scrubbed_description)
+                    .bind(ticket_id)
+                    .execute(&mut *tx)
+                    .await?;
+                records_scrubbed += 1;
+            }
+        }
+
+        let notes = sqlx::query_as::<_, (Uuid, String, Option<String>, Option<Vec<String>>)>(
+            r#"
+            SELECT n.id, n.content, n.content_html, n.email_recipients
+            FROM ticket_notes n
+            JOIN tickets t ON t.id = n.ticket_id
+            WHERE n.tenant_id = $1 AND t.contact_id = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(contact_id)
+        .fetch_all(&mut *tx)
+        .await?;
+        for (note_id, content, content_html, recipients) in notes {
+            let scrubbed_content = pii.scrub(&content);
+            let scrubbed_html = content_html.as_deref().map(|h| pii.scrub(h));
+            let scrubbed_recipients = recipients
+                .as_ref()
+                .map(|r| r.iter().map(|address| pii.scrub(address)).collect::<Vec<_>>());
+            if scrubbed_content != content || scrubbed_html != content_html || scrubbed_recipients != recipients {
+                sqlx::query(
+                    "UPDATE ticket_notes SET content = $1, content_html = $2, email_recipients = $3 WHERE id = $4",
+                )
+                .bind(&scrubbed_content)
+                .bind(&scrubbed_html)
+                .bind(&scrubbed_recipients)
+                .bind(note_id)
+                .execute(&mut *tx)
+                .await?;
+                records_scrubbed += 1;
+            }
+        }
+
+        let comments = sqlx::query_as::<_, (Uuid, String)>(
+            "SELECT id, comment FROM csat_surveys WHERE tenant_id = $1 AND contact_id = $2 AND comment IS NOT NULL",
+        )
+        .bind(tenant_id)
+        .bind(contact_id)
+        .fetch_all(&mut *tx)
+        .await?;
+        for (survey_id, comment) in comments {
+            let scrubbed = pii.scrub(&comment);
+            if scrubbed != comment {
+                sqlx::query("UPDATE csat_surveys SET comment = $1 WHERE id = $2")
+                    .bind(&scrubbed)
+                    .bind(survey_id)
+                    .execute(&mut *tx)
+                    .await?;
+                records_scrubbed += 1;
+            }
+        }
+
+        match mode {
+            ErasureMode::Anonymize => {
+                sqlx::query(
+                    r#"
+                    UPDATE contacts
+                    SET first_name = 'Erased', last_name = 'Contact', email = NULL, phone = NULL,
+                        mobile = NULL, fax = NULL, title = NULL, department = NULL, notes = NULL,
+                        avatar_url = NULL, is_portal_user = FALSE, portal_user_id = NULL,
+                        portal_password_hash = NULL, custom_fields = '{}', tags = '{}',
+                        status = 'inactive', updated_at = NOW()
+                    WHERE tenant_id = $1 AND id = $2
+                    "#,
+                )
+                .bind(tenant_id)
+                .bind(contact_id)
+                .execute(&mut *tx)
+                .await?;
+            }
+            ErasureMode::Delete => {
+                // Keep the business records, drop their link to the person
+                for statement in [
+                    "UPDATE tickets SET contact_id = NULL WHERE tenant_id = $1 AND contact_id = $2",
+                    "UPDATE appointments SET contact_id = NULL WHERE tenant_id = $1 AND contact_id = $2",
+                    "UPDATE assets SET contact_id = NULL WHERE tenant_id = $1 AND contact_id = $2",
+                    "UPDATE contracts SET signed_by_contact_id = NULL WHERE tenant_id = $1 AND signed_by_contact_id = $2",
+                    "UPDATE invoices SET billing_contact_id = NULL WHERE tenant_id = $1 AND billing_contact_id = $2",
+                    "DELETE FROM contacts WHERE tenant_id = $1 AND id = $2",
+                ] {
+                    sqlx::query(statement)
+                        .bind(tenant_id)
+                        .bind(contact_id)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+            }
+        }
+
+        let summary = ErasureSummary {
+            contact_id,
+            mode,
+            tickets_retained: tickets_retained.max(0) as u64,
+            records_scrubbed,
+            erased_at: Utc::now(),
+        };
+
+        let action = AuditAction::from_str(mode.audit_action()).unwrap_or_default();
+        let entry = NewAuditEntry::new(action, "contact", Some(contact_id))
+            .by(Some(user_id))
+            .new_values(summary.audit_values())
+            .request_id(request_id::current());
+        AuditService::record_in(&mut *tx, tenant_id, entry).await?;
+
+        tx.commit().await?;
+
+        Ok(summary)
+    }
+}
@@ -1,7 +1,7 @@
 //! Contact API routes
 
 use axum::{
-    extract::{Path, Query, State},
+    extract::{OriginalUri, Path, Query, State},
     routing::{delete, get, post, put},
     Json, Router,
 };
@@ -10,40 +10,66 @@ use uuid::Uuid;
 use validator::Validate;
 
 use super::{
-    CompanyDetailResponse, CompanyFilter, CompanyResponse, ContactFilter, ContactResponse,
-    ContactService, CreateCompanyRequest, CreateContactRequest, CreateSiteRequest,
-    SiteResponse, UpdateCompanyRequest, UpdateContactRequest, UpdateSiteRequest,
+    BulkCompanyStatusRequest, BulkContactStatusRequest, BulkReport, BulkTagRequest,
+    CompanyContactRoles, CompanyDetailResponse, CompanyFilter, CompanyResponse, ContactFilter,
+    ContactResponse, ContactRole, ContactService, CreateCompanyRequest, CreateContactRequest,
+    CreateCrmNoteRequest, CreateSiteRequest, CrmNote, EraseContactRequest, ErasureSummary, PrivacyService, SetContactRolesRequest,
+    SetDefaultContactsRequest, SiteResponse, UpdateCompanyRequest, UpdateContactRequest,
+    UpdateSiteRequest,
 };
-use crate::modules::auth::RequireAuth;
+use crate::modules::auth::{RequireAdmin, RequireAuth};
+use crate::modules::saved_views::{SavedViewParams, SavedViewService};
 use crate::utils::error::AppResult;
-use crate::utils::pagination::{PaginatedResponse, PaginationParams};
+use crate::utils::pagination::{PaginatedJson, PaginatedResponse, PaginationParams};
+use crate::utils::validation::ValidatedJson;
 
 #[derive(Clone)]
 pub struct ContactRouterState {
     pub contact_service: Arc<ContactService>,
+    pub privacy_service: Arc<PrivacyService>,
+    pub saved_view_service: Arc<SavedViewService>,
 }
 
 /// Create the contact management router
-pub fn contact_routes(contact_service: ContactService) -> Router {
+pub fn contact_routes(
+    contact_service: ContactService,
+    privacy_service: PrivacyService,
+    saved_view_service: SavedViewService,
+) -> Router {
     let state = ContactRouterState {
         contact_service: Arc::new(contact_service),
+        privacy_service: Arc::new(privacy_service),
+        saved_view_service: Arc::new(saved_view_service),
     };
 
     Router::new()
         // Companies
         .route("/companies", get(list_companies))
         .route("/companies", post(create_company))
+        .route("/companies/bulk/tags", post(bulk_tag_companies))
+        .route("/companies/bulk/status", post(bulk_update_company_status))
         .route("/companies/:company_id", get(get_company))
         .route("/companies/:company_id", put(update_company))
         .route("/companies/:company_id", delete(delete_company))
         .route("/companies/:company_id/contacts", get(get_company_contacts))
         .route("/companies/:company_id/sites", get(get_company_sites))
+        .route("/companies/:company_id/contact-roles", get(get_company_contact_roles))
+        .route("/companies/:company_id/default-contacts", put(set_default_contacts))
+        .route("/companies/:company_id/notes", get(get_company_notes))
+        .route("/companies/:company_id/notes", post(add_company_note))
         // Contacts
         .route("/contacts", get(list_contacts))
         .route("/contacts", post(create_contact))
+        .route("/contacts/bulk/tags", post(bulk_tag_contacts))
+        .route("/contacts/bulk/status", post(bulk_update_contact_status))
         .route("/contacts/:contact_id", get(get_contact))
         .route("/contacts/:contact_id", put(update_contact))
         .route("/contacts/:contact_id", delete(delete_contact))
+        .route("/contacts/:contact_id/erase", post(erase_contact))
+        .route("/contacts/:contact_id/roles", get(get_contact_roles))
+        .route("/contacts/:contact_id/roles", put(set_contact_roles))
+        .route("/contacts/:contact_id/notes", get(get_contact_notes))
+        .route("/contacts/:contact_id/notes", post(add_contact_note))
         // Sites
         .route("/sites", post(create_site))
         .route("/sites/:site_id", get(get_site))
@@ -60,8 +86,14 @@ async fn list_companies(
     State(state): State<ContactRouterState>,
     RequireAuth(user): RequireAuth,
     Query(filter): Query<CompanyFilter>,
+    Query(saved_view): Query<SavedViewParams>,
     Query(pagination): Query<PaginationParams>,
-) -> AppResult<Json<PaginatedResponse<CompanyResponse>>> {
+    OriginalUri(uri): OriginalUri,
+) -> AppResult<PaginatedJson<CompanyResponse>> {
+    let filter = state
+        .saved_view_service
+        .resolve_filter(&user, &saved_view, filter)
+        .await?;
     let (companies, total) = state
         .contact_service
         .list_companies(user.tenant_id, &filter, &pagination)
@@ -73,16 +105,14 @@ async fn list_companies(
         total,
     );
 
-    Ok(Json(response))
+    Ok(response.with_links(&uri))
 }
 
 async fn create_company(
     State(state): State<ContactRouterState>,
     RequireAuth(user): RequireAuth,
-    Json(request): Json<CreateCompanyRequest>,
+    ValidatedJson(request): ValidatedJson<CreateCompanyRequest>,
 ) -> AppResult<Json<CompanyResponse>> {
-    request.validate()?;
-
     let company = state
         .contact_service
         .create_company(user.tenant_id, &request)
@@ -108,10 +138,8 @@ async fn update_company(
     State(state): State<ContactRouterState>,
     RequireAuth(user): RequireAuth,
     Path(company_id): Path<Uuid>,
-    Json(request): Json<UpdateCompanyRequest>,
+    ValidatedJson(request): ValidatedJson<UpdateCompanyRequest>,
 ) -> AppResult<Json<CompanyResponse>> {
-    request.validate()?;
-
     let company = state
         .contact_service
         .update_company(user.tenant_id, company_id, &request)
@@ -157,6 +185,33 @@ async fn get_company_sites(
     Ok(Json(sites.into_iter().map(SiteResponse::from).collect()))
 }
 
+async fn get_company_contact_roles(
+    State(state): State<ContactRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(company_id): Path<Uuid>,
+) -> AppResult<Json<Vec<CompanyContactRoles>>> {
+    let roles = state
+        .contact_service
+        .company_contact_roles(user.tenant_id, company_id)
+        .await?;
+
+    Ok(Json(roles))
+}
+
+async fn set_default_contacts(
+    State(state): State<ContactRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(company_id): Path<Uuid>,
+    ValidatedJson(request): ValidatedJson<SetDefaultContactsRequest>,
+) -> AppResult<Json<CompanyResponse>> {
+    let company = state
+        .contact_service
+        .set_default_contacts(user.tenant_id, company_id, &request)
+        .await?;
+
+    Ok(Json(company.into()))
+}
+
 // ============================================================================
 // CONTACT HANDLERS
 // ============================================================================
@@ -165,8 +220,14 @@ async fn list_contacts(
     State(state): State<ContactRouterState>,
     RequireAuth(user): RequireAuth,
     Query(filter): Query<ContactFilter>,
+    Query(saved_view): Query<SavedViewParams>,
     Query(pagination): Query<PaginationParams>,
-) -> AppResult<Json<PaginatedResponse<ContactResponse>>> {
+    OriginalUri(uri): OriginalUri,
+) -> AppResult<PaginatedJson<ContactResponse>> {
+    let filter = state
+        .saved_view_service
+        .resolve_filter(&user, &saved_view, filter)
+        .await?;
     let (contacts, total) = state
         .contact_service
         .list_contacts(user.tenant_id, &filter, &pagination)
@@ -178,16 +239,14 @@ async fn list_contacts(
         total,
     );
 
-    Ok(Json(response))
+    Ok(response.with_links(&uri))
 }
 
 async fn create_contact(
     State(state): State<ContactRouterState>,
     RequireAuth(user): RequireAuth,
-    Json(request): Json<CreateContactRequest>,
+    ValidatedJson(request): ValidatedJson<CreateContactRequest>,
 ) -> AppResult<Json<ContactResponse>> {
-    request.validate()?;
-
     let contact = state
         .contact_service
         .create_contact(user.tenant_id, &request)
@@ -213,10 +272,8 @@ async fn update_contact(
     State(state): State<ContactRouterState>,
     RequireAuth(user): RequireAuth,
     Path(contact_id): Path<Uuid>,
-    Json(request): Json<UpdateContactRequest>,
+    ValidatedJson(request): ValidatedJson<UpdateContactRequest>,
 ) -> AppResult<Json<ContactResponse>> {
-    request.validate()?;
-
     let contact = state
         .contact_service
         .update_contact(user.tenant_id, contact_id, &request)
@@ -236,6 +293,163 @@ async fn delete_contact(
         .await
 }
 
+async fn erase_contact(
+    State(state): State<ContactRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Path(contact_id): Path<Uuid>,
+    Json(request): Json<EraseContactRequest>,
+) -> AppResult<Json<ErasureSummary>> {
+    request.validate()?;
+
+    let summary = state
+        .privacy_service
+        .erase_contact(user.tenant_id, user.id, contact_id, request.mode)
+        .await?;
+
+    Ok(Json(summary))
+}
+
+async fn get_contact_roles(
+    State(state): State<ContactRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(contact_id): Path<Uuid>,
+) -> AppResult<Json<Vec<ContactRole>>> {
+    let roles = state
+        .contact_service
+        .contact_roles(user.tenant_id, contact_id)
+        .await?;
+
+    Ok(Json(roles))
+}
+
+async fn set_contact_roles(
+    State(state): State<ContactRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(contact_id): Path<Uuid>,
+    ValidatedJson(request): ValidatedJson<SetContactRolesRequest>,
+) -> AppResult<Json<Vec<ContactRole>>> {
+    let roles = state
+        .contact_service
+        .set_contact_roles(user.tenant_id, contact_id, &request)
+        .await?;
+
+    Ok(Json(roles))
+}
+
+// ============================================================================
+// NOTE HANDLERS
+// ============================================================================
+
+async fn get_company_notes(
+    State(state): State<ContactRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(company_id): Path<Uuid>,
+) -> AppResult<Json<Vec<CrmNote>>> {
+    let notes = state
+        .contact_service
+        .company_notes(user.tenant_id, company_id)
+        .await?;
+
+    Ok(Json(notes))
+}
+
+async fn add_company_note(
+    State(state): State<ContactRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(company_id): Path<Uuid>,
+    ValidatedJson(request): ValidatedJson<CreateCrmNoteRequest>,
+) -> AppResult<Json<CrmNote>> {
+    let note = state
+        .contact_service
+        .add_company_note(user.tenant_id, company_id, user.id, &request)
+        .await?;
+
+    Ok(Json(note))
+}
+
+async fn get_contact_notes(
+    State(state): State<ContactRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(contact_id): Path<Uuid>,
+) -> AppResult<Json<Vec<CrmNote>>> {
+    let notes = state
+        .contact_service
+        .contact_notes(user.tenant_id, contact_id)
+        .await?;
+
+    Ok(Json(notes))
+}
+
+async fn add_contact_note(
+    State(state): State<ContactRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(contact_id): Path<Uuid>,
+    ValidatedJson(request): ValidatedJson<CreateCrmNoteRequest>,
+) -> AppResult<Json<CrmNote>> {
+    let note = state
+        .contact_service
+        .add_contact_note(user.tenant_id, contact_id, user.id, &request)
+        .await?;
+
+    Ok(Json(note))
+}
+
+// ============================================================================
+// BULK HANDLERS
+// ============================================================================
+
+async fn bulk_tag_companies(
+    State(state): State<ContactRouterState>,
+    RequireAuth(user): RequireAuth,
+    ValidatedJson(request): ValidatedJson<BulkTagRequest>,
+) -> AppResult<Json<BulkReport>> {
+    let report = state
+        .contact_service
+        .bulk_tag_companies(user.tenant_id, &request)
+        .await?;
+
+    Ok(Json(report))
+}
+
+async fn bulk_update_company_status(
+    State(state): State<ContactRouterState>,
+    RequireAuth(user): RequireAuth,
+    ValidatedJson(request): ValidatedJson<BulkCompanyStatusRequest>,
+) -> AppResult<Json<BulkReport>> {
+    let report = state
+        .contact_service
+        .bulk_update_company_status(user.tenant_id, &request)
+        .await?;
+
+    Ok(Json(report))
+}
+
+async fn bulk_tag_contacts(
+    State(state): State<ContactRouterState>,
+    RequireAuth(user): RequireAuth,
+    ValidatedJson(request): ValidatedJson<BulkTagRequest>,
+) -> AppResult<Json<BulkReport>> {
+    let report = state
+        .contact_service
+        .bulk_tag_contacts(user.tenant_id, &request)
+        .await?;
+
+    Ok(Json(report))
+}
+
+async fn bulk_update_contact_status(
+    State(state): State<ContactRouterState>,
+    RequireAuth(user): RequireAuth,
+    ValidatedJson(request): ValidatedJson<BulkContactStatusRequest>,
+) -> AppResult<Json<BulkReport>> {
+    let report = state
+        .contact_service
+        .bulk_update_contact_status(user.tenant_id, &request)
+        .await?;
+
+    Ok(Json(report))
+}
+
 // ============================================================================
 // SITE HANDLERS
 // ============================================================================
@@ -243,10 +457,8 @@ async fn delete_contact(
 async fn create_site(
     State(state): State<ContactRouterState>,
     RequireAuth(user): RequireAuth,
-    Json(request): Json<CreateSiteRequest>,
+    ValidatedJson(request): ValidatedJson<CreateSiteRequest>,
 ) -> AppResult<Json<SiteResponse>> {
-    request.validate()?;
-
     let site = state
         .contact_service
         .create_site(user.tenant_id, &request)
@@ -272,10 +484,8 @@ async fn update_site(
     State(state): State<ContactRouterState>,
     RequireAuth(user): RequireAuth,
     Path(site_id): Path<Uuid>,
-    Json(request): Json<UpdateSiteRequest>,
+    ValidatedJson(request): ValidatedJson<UpdateSiteRequest>,
 ) -> AppResult<Json<SiteResponse>> {
-    request.validate()?;
-
     // TODO: Implement update_site in service
     let site = state
         .contact_service
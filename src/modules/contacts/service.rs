@@ -29,6 +29,10 @@ impl ContactService {
         tenant_id: Uuid,
         request: &CreateCompanyRequest,
     ) -> AppResult<Company> {
+        if let Some(parent_id) = request.parent_company_id {
+            self.get_company(tenant_id, parent_id).await?;
+        }
+
         let company_id = Uuid::new_v4();
         let address = request.address.clone().unwrap_or_default();
         let billing_address = request.billing_address.clone().unwrap_or_default();
@@ -122,9 +126,6 @@ impl ContactService {
         filter: &CompanyFilter,
         pagination: &PaginationParams,
     ) -> AppResult<(Vec<Company>, u64)> {
-        let offset = pagination.offset() as i32;
-        let limit = pagination.limit() as i32;
-
         // Build dynamic query
         let mut conditions = vec!["tenant_id = $1".to_string()];
         let mut param_idx = 4;
@@ -174,35 +175,44 @@ impl ContactService {
             where_clause
         );
 
-        // Execute queries
-        let mut query_builder = sqlx::query_as::<_, CompanyRow>(&query)
-            .bind(tenant_id)
-            .bind(limit)
-            .bind(offset);
-
         let mut count_builder = sqlx::query_scalar::<_, i64>(&count_query)
             .bind(tenant_id);
 
         if let Some(ref q) = filter.q {
-            let search = format!("%{}%", q);
-            query_builder = query_builder.bind(search.clone());
-            count_builder = count_builder.bind(search);
+            count_builder = count_builder.bind(format!("%{}%", q));
         }
         if let Some(ref ct) = filter.company_type {
-            query_builder = query_builder.bind(ct.as_str());
             count_builder = count_builder.bind(ct.as_str());
         }
         if let Some(ref status) = filter.status {
-            query_builder = query_builder.bind(status.as_str());
             count_builder = count_builder.bind(status.as_str());
         }
         if let Some(ref am_id) = filter.account_manager_id {
-            query_builder = query_builder.bind(am_id);
             count_builder = count_builder.bind(am_id);
         }
 
-        let rows = query_builder.fetch_all(self.db.pool()).await?;
         let total = count_builder.fetch_one(self.db.pool()).await?;
+        let page = pagination.clamped(total as u64);
+
+        let mut query_builder = sqlx::query_as::<_, CompanyRow>(&query)
+            .bind(tenant_id)
+            .bind(page.limit() as i32)
+            .bind(page.offset() as i32);
+
+        if let Some(ref q) = filter.q {
+            query_builder = query_builder.bind(format!("%{}%", q));
+        }
+        if let Some(ref ct) = filter.company_type {
+            query_builder = query_builder.bind(ct.as_str());
+        }
+        if let Some(ref status) = filter.status {
+            query_builder = query_builder.bind(status.as_str());
+        }
+        if let Some(ref am_id) = filter.account_manager_id {
+            query_builder = query_builder.bind(am_id);
+        }
+
+        let rows = query_builder.fetch_all(self.db.pool()).await?;
 
         Ok((rows.into_iter().map(Into::into).collect(), total as u64))
     }
@@ -217,6 +227,11 @@ impl ContactService {
         // Verify company exists
         self.get_company(tenant_id, company_id).await?;
 
+        if let Some(parent_id) = request.parent_company_id {
+            self.get_company(tenant_id, parent_id).await?;
+            self.company_tree(tenant_id, company_id).await?.check_parent(parent_id)?;
+        }
+
         // Build update query dynamically
         let mut updates = vec!["updated_at = NOW()".to_string()];
         let mut param_idx = 3;
@@ -233,6 +248,10 @@ impl ContactService {
             updates.push(format!("status = ${}", param_idx));
             param_idx += 1;
         }
+        if request.parent_company_id.is_some() {
+            updates.push(format!("parent_company_id = ${}", param_idx));
+            param_idx += 1;
+        }
         // Add more fields as needed...
 
         let query = format!(
@@ -253,12 +272,55 @@ impl ContactService {
         if let Some(ref status) = request.status {
             query_builder = query_builder.bind(status.as_str());
         }
+        if let Some(parent_id) = request.parent_company_id {
+            query_builder = query_builder.bind(parent_id);
+        }
 
         query_builder.execute(self.db.pool()).await?;
 
         self.get_company(tenant_id, company_id).await
     }
 
+    /// A company and all of its subsidiaries, however deep
+    pub async fn company_tree(&self, tenant_id: Uuid, root_id: Uuid) -> AppResult<CompanyTree> {
+        let rows = sqlx::query_as::<_, (Uuid, String, Option<Uuid>, i32)>(
+            r#"
+            WITH RECURSIVE tree AS (
+                SELECT id, name, parent_company_id, 0 AS depth, ARRAY[id] AS path
+                FROM companies
+                WHERE tenant_id = $1 AND id = $2
+                UNION ALL
+                SELECT c.id, c.name, c.parent_company_id, t.depth + 1, t.path || c.id
+                FROM companies c
+                JOIN tree t ON c.parent_company_id = t.id
+                WHERE c.tenant_id = $1 AND NOT c.id = ANY(t.path)
+            )
+            SELECT id, name, parent_company_id, depth FROM tree ORDER BY depth, name
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(root_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        if rows.is_empty() {
+            return Err(AppError::NotFound("Company".to_string()));
+        }
+
+        Ok(CompanyTree {
+            root_id,
+            companies: rows
+                .into_iter()
+                .map(|(id, name, parent_company_id, depth)| CompanyTreeNode {
+                    id,
+                    name,
+                    parent_company_id,
+                    depth: depth.max(0) as u32,
+                })
+                .collect(),
+        })
+    }
+
     /// Delete company
     pub async fn delete_company(&self, tenant_id: Uuid, company_id: Uuid) -> AppResult<()> {
         // Check for related records
@@ -341,7 +403,7 @@ impl ContactService {
     pub async fn get_contact(&self, tenant_id: Uuid, contact_id: Uuid) -> AppResult<Contact> {
         let row = sqlx::query_as::<_, ContactRow>(
             r#"
-            SELECT id, tenant_id, company_id, first_name, last_name, email,
+            SELECT id, tenant_id, company_id, first_name, last_name, email, email_deliverability,
                    phone, mobile, fax, title, department, contact_type,
                    is_portal_user, portal_user_id, preferred_contact_method,
                    timezone, locale, custom_fields, tags, notes, avatar_url,
@@ -366,9 +428,6 @@ impl ContactService {
         filter: &ContactFilter,
         pagination: &PaginationParams,
     ) -> AppResult<(Vec<Contact>, u64)> {
-        let offset = pagination.offset() as i32;
-        let limit = pagination.limit() as i32;
-
         let mut conditions = vec!["tenant_id = $1".to_string()];
         let mut param_idx = 4;
 
@@ -397,7 +456,7 @@ impl ContactService {
 
         let query = format!(
             r#"
-            SELECT id, tenant_id, company_id, first_name, last_name, email,
+            SELECT id, tenant_id, company_id, first_name, last_name, email, email_deliverability,
                    phone, mobile, fax, title, department, contact_type,
                    is_portal_user, portal_user_id, preferred_contact_method,
                    timezone, locale, custom_fields, tags, notes, avatar_url,
@@ -415,34 +474,44 @@ impl ContactService {
             where_clause
         );
 
-        let mut query_builder = sqlx::query_as::<_, ContactRow>(&query)
-            .bind(tenant_id)
-            .bind(limit)
-            .bind(offset);
-
         let mut count_builder = sqlx::query_scalar::<_, i64>(&count_query)
             .bind(tenant_id);
 
         if let Some(ref q) = filter.q {
-            let search = format!("%{}%", q);
-            query_builder = query_builder.bind(search.clone());
-            count_builder = count_builder.bind(search);
+            count_builder = count_builder.bind(format!("%{}%", q));
         }
         if let Some(ref company_id) = filter.company_id {
-            query_builder = query_builder.bind(company_id);
             count_builder = count_builder.bind(company_id);
         }
         if let Some(ref ct) = filter.contact_type {
-            query_builder = query_builder.bind(ct.as_str());
             count_builder = count_builder.bind(ct.as_str());
         }
         if let Some(ref status) = filter.status {
-            query_builder = query_builder.bind(status.as_str());
             count_builder = count_builder.bind(status.as_str());
         }
 
-        let rows = query_builder.fetch_all(self.db.pool()).await?;
         let total = count_builder.fetch_one(self.db.pool()).await?;
+        let page = pagination.clamped(total as u64);
+
+        let mut query_builder = sqlx::query_as::<_, ContactRow>(&query)
+            .bind(tenant_id)
+            .bind(page.limit() as i32)
+            .bind(page.offset() as i32);
+
+        if let Some(ref q) = filter.q {
+            query_builder = query_builder.bind(format!("%{}%", q));
+        }
+        if let Some(ref company_id) = filter.company_id {
+            query_builder = query_builder.bind(company_id);
+        }
+        if let Some(ref ct) = filter.contact_type {
+            query_builder = query_builder.bind(ct.as_str());
+        }
+        if let Some(ref status) = filter.status {
+            query_builder = query_builder.bind(status.as_str());
+        }
+
+        let rows = query_builder.fetch_all(self.db.pool()).await?;
 
         Ok((rows.into_iter().map(Into::into).collect(), total as u64))
     }
@@ -455,7 +524,7 @@ impl ContactService {
     ) -> AppResult<Vec<Contact>> {
         let rows = sqlx::query_as::<_, ContactRow>(
             r#"
-            SELECT id, tenant_id, company_id, first_name, last_name, email,
+            SELECT id, tenant_id, company_id, first_name, last_name, email, email_deliverability,
                    phone, mobile, fax, title, department, contact_type,
                    is_portal_user, portal_user_id, preferred_contact_method,
                    timezone, locale, custom_fields, tags, notes, avatar_url,
@@ -502,7 +571,16 @@ impl ContactService {
         }
 
         if let Some(ref email) = request.email {
-            sqlx::query("UPDATE contacts SET email = $1, updated_at = NOW() WHERE tenant_id = $2 AND id = $3")
+            // A new address starts with a clean deliverability record
+            sqlx::query(
+                r#"
+                UPDATE contacts
+                SET email = $1,
+                    email_deliverability = CASE WHEN lower(email) = lower($1) THEN email_deliverability ELSE 'ok' END,
+                    updated_at = NOW()
+                WHERE tenant_id = $2 AND id = $3
+                "#,
+            )
                 .bind(email)
                 .bind(tenant_id)
                 .bind(contact_id)
@@ -524,6 +602,375 @@ impl ContactService {
         Ok(())
     }
 
+    // ========================================================================
+    // CONTACT ROLES
+    // ========================================================================
+
+    /// Roles a contact holds at their company
+    pub async fn contact_roles(&self, tenant_id: Uuid, contact_id: Uuid) -> AppResult<Vec<ContactRole>> {
+        let roles: Vec<String> = sqlx::query_scalar(
+            "SELECT role FROM contact_roles WHERE tenant_id = $1 AND contact_id = $2 ORDER BY role",
+        )
+        .bind(tenant_id)
+        .bind(contact_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let mut roles: Vec<ContactRole> = roles.iter().filter_map(|r| ContactRole::from_str(r)).collect();
+        roles.sort();
+        Ok(roles)
+    }
+
+    /// Replace the roles a contact holds at their company
+    pub async fn set_contact_roles(
+        &self,
+        tenant_id: Uuid,
+        contact_id: Uuid,
+        request: &SetContactRolesRequest,
+    ) -> AppResult<Vec<ContactRole>> {
+        let contact = self.get_contact(tenant_id, contact_id).await?;
+        let roles: Vec<&str> = request.normalized().iter().map(ContactRole::as_str).collect();
+
+        sqlx::query(
+            "DELETE FROM contact_roles WHERE tenant_id = $1 AND contact_id = $2 AND NOT role = ANY($3)",
+        )
+        .bind(tenant_id)
+        .bind(contact_id)
+        .bind(&roles)
+        .execute(self.db.pool())
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO contact_roles (tenant_id, company_id, contact_id, role)
+            SELECT $1, $2, $3, UNNEST($4::VARCHAR[])
+            ON CONFLICT (contact_id, company_id, role) DO NOTHING
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(contact.company_id)
+        .bind(contact_id)
+        .bind(&roles)
+        .execute(self.db.pool())
+        .await?;
+
+        self.contact_roles(tenant_id, contact_id).await
+    }
+
+    /// A company's contacts that hold at least one role, with their roles
+    pub async fn company_contact_roles(
+        &self,
+        tenant_id: Uuid,
+        company_id: Uuid,
+    ) -> AppResult<Vec<CompanyContactRoles>> {
+        let rows = sqlx::query_as::<_, (Uuid, String, Vec<String>)>(
+            r#"
+            SELECT c.id, c.first_name || ' ' || c.last_name, ARRAY_AGG(r.role ORDER BY r.role)
+            FROM contact_roles r
+            JOIN contacts c ON c.id = r.contact_id
+            WHERE r.tenant_id = $1 AND r.company_id = $2
+            GROUP BY c.id, c.first_name, c.last_name
+            ORDER BY c.last_name, c.first_name
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(company_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(contact_id, contact_name, roles)| {
+                let mut roles: Vec<ContactRole> = roles.iter().filter_map(|r| ContactRole::from_str(r)).collect();
+                roles.sort();
+                CompanyContactRoles {
+                    contact_id,
+                    contact_name,
+                    roles,
+                }
+            })
+            .collect())
+    }
+
+    /// Set the company's default billing and/or technical contact. Each must
+    /// be a contact of the company, and is given the matching role.
+    pub async fn set_default_contacts(
+        &self,
+        tenant_id: Uuid,
+        company_id: Uuid,
+        request: &SetDefaultContactsRequest,
+    ) -> AppResult<Company> {
+        self.get_company(tenant_id, company_id).await?;
+
+        let defaults = request.defaults();
+        for (field, contact_id, _) in &defaults {
+            let contact = self.get_contact(tenant_id, *contact_id).await?;
+            check_default_contact(field, &contact, company_id)?;
+        }
+
+        for (field, contact_id, role) in defaults {
+            sqlx::query(&format!(
+                "UPDATE companies SET {} = $1, updated_at = NOW() WHERE tenant_id = $2 AND id = $3",
+                field
+            ))
+            .bind(contact_id)
+            .bind(tenant_id)
+            .bind(company_id)
+            .execute(self.db.pool())
+            .await?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO contact_roles (tenant_id, company_id, contact_id, role)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (contact_id, company_id, role) DO NOTHING
+                "#,
+            )
+            .bind(tenant_id)
+            .bind(company_id)
+            .bind(contact_id)
+            .bind(role.as_str())
+            .execute(self.db.pool())
+            .await?;
+        }
+
+        self.get_company(tenant_id, company_id).await
+    }
+
+    // ========================================================================
+    // CRM NOTES
+    // ========================================================================
+
+    /// Add a note to a company, attributed to `author_id`
+    pub async fn add_company_note(
+        &self,
+        tenant_id: Uuid,
+        company_id: Uuid,
+        author_id: Uuid,
+        request: &CreateCrmNoteRequest,
+    ) -> AppResult<CrmNote> {
+        let company = self.get_company(tenant_id, company_id).await?;
+        self.insert_note(tenant_id, company.id, None, author_id, request.body()?).await
+    }
+
+    /// Add a note to a contact, attributed to `author_id`
+    pub async fn add_contact_note(
+        &self,
+        tenant_id: Uuid,
+        contact_id: Uuid,
+        author_id: Uuid,
+        request: &CreateCrmNoteRequest,
+    ) -> AppResult<CrmNote> {
+        let contact = self.get_contact(tenant_id, contact_id).await?;
+        self.insert_note(tenant_id, contact.company_id, Some(contact.id), author_id, request.body()?)
+            .await
+    }
+
+    async fn insert_note(
+        &self,
+        tenant_id: Uuid,
+        company_id: Uuid,
+        contact_id: Option<Uuid>,
+        author_id: Uuid,
+        body: &str,
+    ) -> AppResult<CrmNote> {
+        let row = sqlx::query_as::<_, CrmNoteRow>(
+            r#"
+            WITH note AS (
+                INSERT INTO crm_notes (tenant_id, company_id, contact_id, author_id, body)
+                VALUES ($1, $2, $3, $4, $5)
+                RETURNING id, company_id, contact_id, author_id, body, created_at
+            )
+            SELECT note.id, note.company_id, note.contact_id, note.author_id,
+                   u.first_name || ' ' || u.last_name AS author_name,
+                   note.body, note.created_at
+            FROM note
+            LEFT JOIN users u ON u.id = note.author_id
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(company_id)
+        .bind(contact_id)
+        .bind(author_id)
+        .bind(body)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(row.into())
+    }
+
+    /// Notes on a company and on each of its contacts, newest first
+    pub async fn company_notes(&self, tenant_id: Uuid, company_id: Uuid) -> AppResult<Vec<CrmNote>> {
+        let rows = sqlx::query_as::<_, CrmNoteRow>(
+            r#"
+            SELECT n.id, n.company_id, n.contact_id, n.author_id,
+                   u.first_name || ' ' || u.last_name AS author_name,
+                   n.body, n.created_at
+            FROM crm_notes n
+            LEFT JOIN users u ON u.id = n.author_id
+            WHERE n.tenant_id = $1 AND n.company_id = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(company_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(CrmNote::newest_first(rows.into_iter().map(Into::into).collect()))
+    }
+
+    /// Notes on a contact, newest first
+    pub async fn contact_notes(&self, tenant_id: Uuid, contact_id: Uuid) -> AppResult<Vec<CrmNote>> {
+        let rows = sqlx::query_as::<_, CrmNoteRow>(
+            r#"
+            SELECT n.id, n.company_id, n.contact_id, n.author_id,
+                   u.first_name || ' ' || u.last_name AS author_name,
+                   n.body, n.created_at
+            FROM crm_notes n
+            LEFT JOIN users u ON u.id = n.author_id
+            WHERE n.tenant_id = $1 AND n.contact_id = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(contact_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(CrmNote::newest_first(rows.into_iter().map(Into::into).collect()))
+    }
+
+    // ========================================================================
+    // BULK OPERATIONS
+    // ========================================================================
+
+    /// Add and remove tags on many companies in one transaction
+    pub async fn bulk_tag_companies(&self, tenant_id: Uuid, request: &BulkTagRequest) -> AppResult<BulkReport> {
+        self.bulk_tag(tenant_id, "companies", request).await
+    }
+
+    /// Add and remove tags on many contacts in one transaction
+    pub async fn bulk_tag_contacts(&self, tenant_id: Uuid, request: &BulkTagRequest) -> AppResult<BulkReport> {
+        self.bulk_tag(tenant_id, "contacts", request).await
+    }
+
+    pub async fn bulk_update_company_status(
+        &self,
+        tenant_id: Uuid,
+        request: &BulkCompanyStatusRequest,
+    ) -> AppResult<BulkReport> {
+        self.bulk_update_status(tenant_id, "companies", &request.ids, request.status.as_str(), request.dry_run)
+            .await
+    }
+
+    pub async fn bulk_update_contact_status(
+        &self,
+        tenant_id: Uuid,
+        request: &BulkContactStatusRequest,
+    ) -> AppResult<BulkReport> {
+        self.bulk_update_status(tenant_id, "contacts", &request.ids, request.status.as_str(), request.dry_run)
+            .await
+    }
+
+    /// `table` is always a literal from the callers above. Every id must belong
+    /// to the tenant or nothing changes. Dry runs make the same writes and
+    /// roll them back, so they report exactly what a real run would.
+    async fn bulk_tag(&self, tenant_id: Uuid, table: &'static str, request: &BulkTagRequest) -> AppResult<BulkReport> {
+        request.check()?;
+
+        let mut tx = self.db.pool().begin().await?;
+
+        let rows: Vec<(Uuid, Vec<String>)> = sqlx::query_as(&format!(
+            "SELECT id, COALESCE(tags, '{{}}') FROM {} WHERE tenant_id = $1 AND id = ANY($2) FOR UPDATE",
+            table
+        ))
+        .bind(tenant_id)
+        .bind(&request.ids)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut results = Vec::with_capacity(rows.len());
+        for (id, tags) in request.plan(&rows)? {
+            let outcome = match tags {
+                Some(tags) => {
+                    sqlx::query(&format!(
+                        "UPDATE {} SET tags = $1, updated_at = NOW() WHERE tenant_id = $2 AND id = $3",
+                        table
+                    ))
+                    .bind(&tags)
+                    .bind(tenant_id)
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?;
+                    BulkOutcome::Updated
+                }
+                None => BulkOutcome::Unchanged,
+            };
+            results.push(BulkResult { id, outcome });
+        }
+
+        Self::finish_bulk(tx, BulkReport::new(results), request.dry_run).await
+    }
+
+    async fn bulk_update_status(
+        &self,
+        tenant_id: Uuid,
+        table: &'static str,
+        ids: &[Uuid],
+        status: &str,
+        dry_run: bool,
+    ) -> AppResult<BulkReport> {
+        let mut tx = self.db.pool().begin().await?;
+
+        let found: Vec<Uuid> = sqlx::query_scalar(&format!(
+            "SELECT id FROM {} WHERE tenant_id = $1 AND id = ANY($2) FOR UPDATE",
+            table
+        ))
+        .bind(tenant_id)
+        .bind(ids)
+        .fetch_all(&mut *tx)
+        .await?;
+        let ids = check_bulk_ids(ids, &found)?;
+
+        let updated: Vec<Uuid> = sqlx::query_scalar(&format!(
+            "UPDATE {} SET status = $1, updated_at = NOW() WHERE tenant_id = $2 AND id = ANY($3) AND status <> $1 RETURNING id",
+            table
+        ))
+        .bind(status)
+        .bind(tenant_id)
+        .bind(&ids)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let report = BulkReport::new(
+            ids.into_iter()
+                .map(|id| BulkResult {
+                    id,
+                    outcome: if updated.contains(&id) {
+                        BulkOutcome::Updated
+                    } else {
+                        BulkOutcome::Unchanged
+                    },
+                })
+                .collect(),
+        );
+        Self::finish_bulk(tx, report, dry_run).await
+    }
+
+    /// Commit a bulk operation, or roll it back for a dry run
+    async fn finish_bulk(
+        tx: sqlx::Transaction<'_, sqlx::Postgres>,
+        report: BulkReport,
+        dry_run: bool,
+    ) -> AppResult<BulkReport> {
+        if dry_run {
+            tx.rollback().await?;
+            return Ok(report.preview());
+        }
+
+        tx.commit().await?;
+        Ok(report)
+    }
+
     // ========================================================================
     // SITES
     // ========================================================================
@@ -745,6 +1192,7 @@ struct ContactRow {
     first_name: String,
     last_name: String,
     email: Option<String>,
+    email_deliverability: String,
     phone: Option<String>,
     mobile: Option<String>,
     fax: Option<String>,
@@ -774,6 +1222,7 @@ impl From<ContactRow> for Contact {
             first_name: row.first_name,
             last_name: row.last_name,
             email: row.email,
+            email_deliverability: EmailDeliverability::from_str(&row.email_deliverability).unwrap_or_default(),
             phone: row.phone,
             mobile: row.mobile,
             fax: row.fax,
@@ -796,6 +1245,31 @@ impl From<ContactRow> for Contact {
     }
 }
 
+#[derive(sqlx::FromRow)]
+struct CrmNoteRow {
+    id: Uuid,
+    company_id: Uuid,
+    contact_id: Option<Uuid>,
+    author_id: Option<Uuid>,
+    author_name: Option<String>,
+    body: String,
+    created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<CrmNoteRow> for CrmNote {
+    fn from(row: CrmNoteRow) -> Self {
+        Self {
+            id: row.id,
+            company_id: row.company_id,
+            contact_id: row.contact_id,
+            author_id: row.author_id,
+            author_name: row.author_name,
+            body: row.body,
+            created_at: row.created_at,
+        }
+    }
+}
+
 #[derive(sqlx::FromRow)]
 struct SiteRow {
     id: Uuid,
@@ -0,0 +1,15 @@
+//! Dashboard Module
+//!
+//! Per-user dashboard layouts and the data behind each widget.
+
+mod models;
+#[cfg(feature = "server")]
+mod service;
+#[cfg(feature = "server")]
+mod routes;
+
+pub use models::*;
+#[cfg(feature = "server")]
+pub use service::DashboardService;
+#[cfg(feature = "server")]
+pub use routes::dashboard_routes;
@@ -0,0 +1,268 @@
+//! Dashboard models and types
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::modules::auth::UserRole;
+
+// ============================================================================
+// WIDGETS
+// ============================================================================
+
+/// A dashboard widget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DashboardWidget {
+    OpenTickets,
+    SlaAtRisk,
+    MyTimesheet,
+    Revenue,
+}
+
+impl DashboardWidget {
+    /// Every widget, in the default order
+    pub const ALL: [Self; 4] = [Self::OpenTickets, Self::SlaAtRisk, Self::MyTimesheet, Self::Revenue];
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "open_tickets" => Some(Self::OpenTickets),
+            "sla_at_risk" => Some(Self::SlaAtRisk),
+            "my_timesheet" => Some(Self::MyTimesheet),
+            "revenue" => Some(Self::Revenue),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::OpenTickets => "open_tickets",
+            Self::SlaAtRisk => "sla_at_risk",
+            Self::MyTimesheet => "my_timesheet",
+            Self::Revenue => "revenue",
+        }
+    }
+
+    /// Whether a user with this role may see the widget
+    pub fn allowed_for(&self, role: UserRole) -> bool {
+        match self {
+            Self::Revenue => role.can_view_financials(),
+            _ => true,
+        }
+    }
+}
+
+// ============================================================================
+// LAYOUT
+// ============================================================================
+
+/// A user's enabled widgets in display order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DashboardLayout {
+    pub user_id: Uuid,
+    pub widgets: Vec<DashboardWidget>,
+    /// `false` when the user has never saved a layout
+    pub is_custom: bool,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl DashboardLayout {
+    /// Layout for users who have not customized their dashboard
+    pub fn default_for(user_id: Uuid, role: UserRole) -> Self {
+        Self {
+            user_id,
+            widgets: DashboardWidget::ALL
+                .into_iter()
+                .filter(|widget| widget.allowed_for(role))
+                .collect(),
+            is_custom: false,
+            updated_at: None,
+        }
+    }
+
+    /// Layout from stored widget names; names no longer known are skipped
+    pub fn from_stored(user_id: Uuid, widgets: &[String], updated_at: DateTime<Utc>) -> Self {
+        Self {
+            user_id,
+            widgets: widgets.iter().filter_map(|w| DashboardWidget::from_str(w)).collect(),
+            is_custom: true,
+            updated_at: Some(updated_at),
+        }
+    }
+
+    /// Widget names as stored in `dashboard_layouts.widgets`
+    pub fn stored_widgets(&self) -> Vec<String> {
+        self.widgets.iter().map(|w| w.as_str().to_string()).collect()
+    }
+
+    /// Widgets whose data the summary loads for a user with `role`, in order
+    pub fn visible_widgets(&self, role: UserRole) -> Vec<DashboardWidget> {
+        self.widgets
+            .iter()
+            .copied()
+            .filter(|widget| widget.allowed_for(role))
+            .collect()
+    }
+}
+
+/// Save layout request
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct SaveLayoutRequest {
+    #[validate(length(max = 20))]
+    pub widgets: Vec<DashboardWidget>,
+}
+
+impl SaveLayoutRequest {
+    /// Requested widgets in order with repeats dropped
+    pub fn normalized(&self) -> Vec<DashboardWidget> {
+        let mut widgets: Vec<DashboardWidget> = Vec::with_capacity(self.widgets.len());
+        for widget in &self.widgets {
+            if !widgets.contains(widget) {
+                widgets.push(*widget);
+            }
+        }
+        widgets
+    }
+}
+
+// ============================================================================
+// WIDGET DATA
+// ============================================================================
+
+/// Open ticket counts
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OpenTicketsData {
+    pub total: u64,
+    pub unassigned: u64,
+    pub assigned_to_me: u64,
+}
+
+/// Open tickets past or close to their SLA due date
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SlaAtRiskData {
+    pub breached: u64,
+    pub at_risk: u64,
+    /// Soonest due first
+    pub tickets: Vec<SlaAtRiskTicket>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SlaAtRiskTicket {
+    pub id: Uuid,
+    pub ticket_number: String,
+    pub title: String,
+    pub sla_due_date: DateTime<Utc>,
+}
+
+/// The current user's time this week
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MyTimesheetData {
+    pub week_start: NaiveDate,
+    pub total_minutes: i64,
+    pub billable_minutes: i64,
+}
+
+/// Invoicing for the current month
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RevenueData {
+    pub month_start: NaiveDate,
+    pub invoiced: Decimal,
+    pub collected: Decimal,
+    /// Balance due on all unpaid invoices, regardless of month
+    pub outstanding: Decimal,
+}
+
+/// Data for one widget, tagged with the widget it belongs to
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "widget", content = "data", rename_all = "snake_case")]
+pub enum WidgetData {
+    OpenTickets(OpenTicketsData),
+    SlaAtRisk(SlaAtRiskData),
+    MyTimesheet(MyTimesheetData),
+    Revenue(RevenueData),
+}
+
+impl WidgetData {
+    pub fn widget(&self) -> DashboardWidget {
+        match self {
+            Self::OpenTickets(_) => DashboardWidget::OpenTickets,
+            Self::SlaAtRisk(_) => DashboardWidget::SlaAtRisk,
+            Self::MyTimesheet(_) => DashboardWidget::MyTimesheet,
+            Self::Revenue(_) => DashboardWidget::Revenue,
+        }
+    }
+}
+
+/// Dashboard summary: data for the user's visible widgets only, in layout order
+#[derive(Debug, Clone, Serialize)]
+pub struct DashboardSummary {
+    pub widgets: Vec<WidgetData>,
+    pub generated_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_layout_round_trips_through_storage() {
+        let user_id = Uuid::new_v4();
+        let request: SaveLayoutRequest = serde_json::from_value(serde_json::json!({
+            "widgets": ["my_timesheet", "open_tickets", "my_timesheet"],
+        }))
+        .unwrap();
+        let widgets = request.normalized();
+        assert_eq!(widgets, vec![DashboardWidget::MyTimesheet, DashboardWidget::OpenTickets]);
+
+        let layout = DashboardLayout {
+            user_id,
+            widgets,
+            is_custom: true,
+            updated_at: None,
+        };
+        let mut stored = layout.stored_widgets();
+        assert_eq!(stored, vec!["my_timesheet".to_string(), "open_tickets".to_string()]);
+
+        // A widget removed in a later release is dropped on load
+        stored.push("retired_widget".to_string());
+        let loaded = DashboardLayout::from_stored(user_id, &stored, Utc::now());
+        assert_eq!(loaded.widgets, layout.widgets);
+        assert!(loaded.is_custom);
+
+        let default = DashboardLayout::default_for(user_id, UserRole::Technician);
+        assert!(!default.is_custom);
+        assert!(!default.widgets.contains(&DashboardWidget::Revenue));
+    }
+
+    #[test]
+    fn test_summary_contains_only_enabled_widgets() {
+        let layout = DashboardLayout::from_stored(
+            Uuid::new_v4(),
+            &["revenue".to_string(), "sla_at_risk".to_string()],
+            Utc::now(),
+        );
+
+        assert_eq!(
+            layout.visible_widgets(UserRole::Finance),
+            vec![DashboardWidget::Revenue, DashboardWidget::SlaAtRisk]
+        );
+        // Financial widgets stay hidden from roles without financial access
+        assert_eq!(layout.visible_widgets(UserRole::Technician), vec![DashboardWidget::SlaAtRisk]);
+
+        let summary = DashboardSummary {
+            widgets: vec![WidgetData::SlaAtRisk(SlaAtRiskData {
+                breached: 1,
+                at_risk: 2,
+                tickets: Vec::new(),
+            })],
+            generated_at: Utc::now(),
+        };
+        let json = serde_json::to_value(&summary).unwrap();
+        let widgets = json["widgets"].as_array().unwrap();
+        assert_eq!(widgets.len(), 1);
+        assert_eq!(widgets[0]["widget"], "sla_at_risk");
+        assert_eq!(widgets[0]["data"]["at_risk"], 2);
+    }
+}
@@ -0,0 +1,58 @@
+//! Dashboard API routes
+
+use axum::{
+    extract::State,
+    routing::{get, put},
+    Json, Router,
+};
+use std::sync::Arc;
+use validator::Validate;
+
+use super::{DashboardLayout, DashboardService, DashboardSummary, SaveLayoutRequest};
+use crate::modules::auth::RequireAuth;
+use crate::utils::error::AppResult;
+
+#[derive(Clone)]
+pub struct DashboardRouterState {
+    pub dashboard_service: Arc<DashboardService>,
+}
+
+/// Create the dashboard router
+pub fn dashboard_routes(dashboard_service: DashboardService) -> Router {
+    let state = DashboardRouterState {
+        dashboard_service: Arc::new(dashboard_service),
+    };
+
+    Router::new()
+        .route("/layout", get(get_layout))
+        .route("/layout", put(save_layout))
+        .route("/summary", get(get_summary))
+        .with_state(state)
+}
+
+async fn get_layout(
+    State(state): State<DashboardRouterState>,
+    RequireAuth(user): RequireAuth,
+) -> AppResult<Json<DashboardLayout>> {
+    let layout = state.dashboard_service.get_layout(&user).await?;
+    Ok(Json(layout))
+}
+
+async fn save_layout(
+    State(state): State<DashboardRouterState>,
+    RequireAuth(user): RequireAuth,
+    Json(request): Json<SaveLayoutRequest>,
+) -> AppResult<Json<DashboardLayout>> {
+    request.validate()?;
+
+    let layout = state.dashboard_service.save_layout(&user, &request).await?;
+    Ok(Json(layout))
+}
+
+async fn get_summary(
+    State(state): State<DashboardRouterState>,
+    RequireAuth(user): RequireAuth,
+) -> AppResult<Json<DashboardSummary>> {
+    let summary = state.dashboard_service.summary(&user).await?;
+    Ok(Json(summary))
+}
@@ -0,0 +1,226 @@
+//! Dashboard service implementation
+
+use chrono::{Datelike, Duration, Months, Utc};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::modules::auth::CurrentUser;
+use crate::utils::error::AppResult;
+use crate::utils::timezone::TenantTimezone;
+
+use super::models::*;
+
+/// Tickets due within this many hours count as at risk
+const SLA_AT_RISK_HOURS: i64 = 2;
+
+/// Tickets listed on the SLA at risk widget
+const SLA_AT_RISK_LIMIT: i64 = 10;
+
+/// Dashboard service
+#[derive(Clone)]
+pub struct DashboardService {
+    db: Database,
+}
+
+impl DashboardService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// The user's saved layout, or the default one for their role
+    pub async fn get_layout(&self, user: &CurrentUser) -> AppResult<DashboardLayout> {
+        let row = sqlx::query_as::<_, (Vec<String>, chrono::DateTime<Utc>)>(
+            "SELECT widgets, updated_at FROM dashboard_layouts WHERE tenant_id = $1 AND user_id = $2",
+        )
+        .bind(user.tenant_id)
+        .bind(user.id)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        Ok(match row {
+            Some((widgets, updated_at)) => DashboardLayout::from_stored(user.id, &widgets, updated_at),
+            None => DashboardLayout::default_for(user.id, user.role),
+        })
+    }
+
+    /// Save the user's widgets and their order
+    pub async fn save_layout(&self, user: &CurrentUser, request: &SaveLayoutRequest) -> AppResult<DashboardLayout> {
+        let layout = DashboardLayout {
+            user_id: user.id,
+            widgets: request.normalized(),
+            is_custom: true,
+            updated_at: None,
+        };
+
+        let updated_at: chrono::DateTime<Utc> = sqlx::query_scalar(
+            r#"
+            INSERT INTO dashboard_layouts (tenant_id, user_id, widgets)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (tenant_id, user_id) DO UPDATE SET widgets = EXCLUDED.widgets
+            RETURNING updated_at
+            "#,
+        )
+        .bind(user.tenant_id)
+        .bind(user.id)
+        .bind(layout.stored_widgets())
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(DashboardLayout {
+            updated_at: Some(updated_at),
+            ..layout
+        })
+    }
+
+    /// Data for the widgets on the user's dashboard. Widgets that are not
+    /// enabled, or that the user's role may not see, are not queried.
+    pub async fn summary(&self, user: &CurrentUser) -> AppResult<DashboardSummary> {
+        let layout = self.get_layout(user).await?;
+
+        let mut widgets = Vec::new();
+        for widget in layout.visible_widgets(user.role) {
+            let data = match widget {
+                DashboardWidget::OpenTickets => WidgetData::OpenTickets(self.open_tickets(user).await?),
+                DashboardWidget::SlaAtRisk => WidgetData::SlaAtRisk(self.sla_at_risk(user).await?),
+                DashboardWidget::MyTimesheet => WidgetData::MyTimesheet(self.my_timesheet(user).await?),
+                DashboardWidget::Revenue => WidgetData::Revenue(self.revenue(user).await?),
+            };
+            widgets.push(data);
+        }
+
+        Ok(DashboardSummary {
+            widgets,
+            generated_at: Utc::now(),
+        })
+    }
+
+    async fn open_tickets(&self, user: &CurrentUser) -> AppResult<OpenTicketsData> {
+        let (total, unassigned, assigned_to_me) = sqlx::query_as::<_, (i64, i64, i64)>(
+            r#"
+            SELECT COUNT(*),
+                   COUNT(*) FILTER (WHERE assigned_to_id IS NULL),
+                   COUNT(*) FILTER (WHERE assigned_to_id = $2)
+            FROM tickets
+            WHERE tenant_id = $1 AND closed_at IS NULL
+            "#,
+        )
+        .bind(user.tenant_id)
+        .bind(user.id)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(OpenTicketsData {
+            total: total.max(0) as u64,
+            unassigned: unassigned.max(0) as u64,
+            assigned_to_me: assigned_to_me.max(0) as u64,
+        })
+    }
+
+    async fn sla_at_risk(&self, user: &CurrentUser) -> AppResult<SlaAtRiskData> {
+        let now = Utc::now();
+        let horizon = now + Duration::hours(SLA_AT_RISK_HOURS);
+
+        let (breached, at_risk) = sqlx::query_as::<_, (i64, i64)>(
+            r#"
+            SELECT COUNT(*) FILTER (WHERE sla_due_date < $2),
+                   COUNT(*) FILTER (WHERE sla_due_date >= $2)
+            FROM tickets
+            WHERE tenant_id = $1 AND closed_at IS NULL AND sla_due_date < $3
+            "#,
+        )
+        .bind(user.tenant_id)
+        .bind(now)
+        .bind(horizon)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        let tickets = sqlx::query_as::<_, (Uuid, String, String, chrono::DateTime<Utc>)>(
+            r#"
+            SELECT id, ticket_number, title, sla_due_date
+            FROM tickets
+            WHERE tenant_id = $1 AND closed_at IS NULL AND sla_due_date < $2
+            ORDER BY sla_due_date
+            LIMIT $3
+            "#,
+        )
+        .bind(user.tenant_id)
+        .bind(horizon)
+        .bind(SLA_AT_RISK_LIMIT)
+        .fetch_all(self.db.pool())
+        .await?
+        .into_iter()
+        .map(|(id, ticket_number, title, sla_due_date)| SlaAtRiskTicket {
+            id,
+            ticket_number,
+            title,
+            sla_due_date,
+        })
+        .collect();
+
+        Ok(SlaAtRiskData {
+            breached: breached.max(0) as u64,
+            at_risk: at_risk.max(0) as u64,
+            tickets,
+        })
+    }
+
+    async fn my_timesheet(&self, user: &CurrentUser) -> AppResult<MyTimesheetData> {
+        let today = TenantTimezone::load(self.db.pool(), user.tenant_id).await?.local_date(Utc::now());
+        let week_start = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+
+        let (total_minutes, billable_minutes) = sqlx::query_as::<_, (i64, i64)>(
+            r#"
+            SELECT COALESCE(SUM(duration_minutes), 0)::BIGINT,
+                   COALESCE(SUM(duration_minutes) FILTER (WHERE is_billable), 0)::BIGINT
+            FROM time_entries
+            WHERE tenant_id = $1 AND user_id = $2 AND date >= $3 AND date < $4
+            "#,
+        )
+        .bind(user.tenant_id)
+        .bind(user.id)
+        .bind(week_start)
+        .bind(week_start + Duration::days(7))
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(MyTimesheetData {
+            week_start,
+            total_minutes,
+            billable_minutes,
+        })
+    }
+
+    async fn revenue(&self, user: &CurrentUser) -> AppResult<RevenueData> {
+        let timezone = TenantTimezone::load(self.db.pool(), user.tenant_id).await?;
+        let today = timezone.local_date(Utc::now());
+        let month_start = today - Duration::days(today.day0() as i64);
+        let next_month = month_start + Months::new(1);
+
+        let (invoiced, collected, outstanding) = sqlx::query_as::<_, (Decimal, Decimal, Decimal)>(
+            r#"
+            SELECT COALESCE(SUM(total) FILTER (
+                       WHERE invoice_date >= $2 AND invoice_date < $3 AND status NOT IN ('draft', 'void')
+                   ), 0),
+                   COALESCE(SUM(amount_paid) FILTER (WHERE paid_at >= $4 AND paid_at < $5), 0),
+                   COALESCE(SUM(balance_due) FILTER (WHERE status IN ('pending', 'sent', 'partially_paid')), 0)
+            FROM invoices
+            WHERE tenant_id = $1
+            "#,
+        )
+        .bind(user.tenant_id)
+        .bind(month_start)
+        .bind(next_month)
+        .bind(timezone.day_start(month_start))
+        .bind(timezone.day_start(next_month))
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(RevenueData {
+            month_start,
+            invoiced,
+            collected,
+            outstanding,
+        })
+    }
+}
@@ -0,0 +1,18 @@
+//! Background Jobs Module
+//!
+//! A queue for scheduled and deferred work, with a worker that runs due jobs
+//! and retries failures with backoff. The queue is pluggable: an in-process
+//! queue by default, or the `jobs` table when work must survive restarts and
+//! be shared between instances.
+
+mod models;
+#[cfg(feature = "server")]
+mod queue;
+#[cfg(feature = "server")]
+mod postgres;
+
+pub use models::*;
+#[cfg(feature = "server")]
+pub use queue::{InMemoryJobQueue, JobFuture, JobHandler, JobQueue, Worker, WorkerReport};
+#[cfg(feature = "server")]
+pub use postgres::PgJobQueue;
@@ -0,0 +1,169 @@
+//! Job models and types
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Attempts a job gets unless it asks for something else
+pub const DEFAULT_MAX_ATTEMPTS: i32 = 5;
+
+/// Job status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    /// Waiting for `run_at`, including between retries
+    #[default]
+    Pending,
+    Running,
+    Succeeded,
+    /// Out of attempts, or no handler for its type
+    Failed,
+}
+
+impl JobStatus {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "pending" => Some(Self::Pending),
+            "running" => Some(Self::Running),
+            "succeeded" => Some(Self::Succeeded),
+            "failed" => Some(Self::Failed),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Running => "running",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// A unit of background work
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: Uuid,
+    /// `None` for platform-wide work
+    pub tenant_id: Option<Uuid>,
+    /// Selects the handler, e.g. `sla.scan` or `webhooks.deliver`
+    pub job_type: String,
+    pub payload: serde_json::Value,
+    /// Earliest time the job may run
+    pub run_at: DateTime<Utc>,
+    pub status: JobStatus,
+    /// Attempts started so far, including the current one while running
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Job {
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.status == JobStatus::Pending && self.run_at <= now
+    }
+
+    /// When to try again after a failed attempt, or `None` once all
+    /// attempts are used
+    pub fn retry_at(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        (self.attempts < self.max_attempts).then(|| now + retry_backoff(self.attempts))
+    }
+}
+
+/// Wait before the next attempt: 30 seconds after the first failure,
+/// doubling each time, capped at an hour
+pub fn retry_backoff(attempts: i32) -> Duration {
+    let doublings = attempts.saturating_sub(1).clamp(0, 7) as u32;
+    Duration::seconds(30 * 2_i64.pow(doublings)).min(Duration::hours(1))
+}
+
+/// Work to enqueue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewJob {
+    pub tenant_id: Option<Uuid>,
+    pub job_type: String,
+    pub payload: serde_json::Value,
+    pub run_at: DateTime<Utc>,
+    pub max_attempts: i32,
+}
+
+impl NewJob {
+    /// A job to run as soon as a worker is free
+    pub fn new(job_type: impl Into<String>, payload: serde_json::Value) -> Self {
+        Self {
+            tenant_id: None,
+            job_type: job_type.into(),
+            payload,
+            run_at: Utc::now(),
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+        }
+    }
+
+    pub fn for_tenant(mut self, tenant_id: Uuid) -> Self {
+        self.tenant_id = Some(tenant_id);
+        self
+    }
+
+    /// Hold the job until `run_at`
+    pub fn run_at(mut self, run_at: DateTime<Utc>) -> Self {
+        self.run_at = run_at;
+        self
+    }
+
+    pub fn max_attempts(mut self, max_attempts: i32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// The pending job as a queue stores it
+    pub fn into_job(self, now: DateTime<Utc>) -> Job {
+        Job {
+            id: Uuid::new_v4(),
+            tenant_id: self.tenant_id,
+            job_type: self.job_type,
+            payload: self.payload,
+            run_at: self.run_at,
+            status: JobStatus::Pending,
+            attempts: 0,
+            max_attempts: self.max_attempts,
+            last_error: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_backoff_doubles_up_to_an_hour() {
+        assert_eq!(retry_backoff(1), Duration::seconds(30));
+        assert_eq!(retry_backoff(2), Duration::seconds(60));
+        assert_eq!(retry_backoff(4), Duration::seconds(240));
+        assert_eq!(retry_backoff(8), Duration::hours(1));
+        assert_eq!(retry_backoff(50), Duration::hours(1));
+    }
+
+    #[test]
+    fn test_job_retries_until_attempts_used() {
+        let now = Utc::now();
+        let mut job = NewJob::new("sla.scan", serde_json::json!({})).max_attempts(3).into_job(now);
+        assert!(job.is_due(now));
+
+        job.attempts = 1;
+        assert_eq!(job.retry_at(now), Some(now + Duration::seconds(30)));
+        job.attempts = 3;
+        assert_eq!(job.retry_at(now), None);
+
+        let later = NewJob::new("billing.recurring", serde_json::json!({}))
+            .run_at(now + Duration::hours(1))
+            .into_job(now);
+        assert!(!later.is_due(now));
+        assert!(later.is_due(now + Duration::hours(1)));
+    }
+}
@@ -0,0 +1,146 @@
+//! Job queue backed by the `jobs` table
+
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::utils::error::AppResult;
+
+use super::models::*;
+use super::queue::JobQueue;
+
+const JOB_COLUMNS: &str = r#"
+    id, tenant_id, job_type, payload, run_at, status, attempts, max_attempts,
+    last_error, created_at, updated_at
+"#;
+
+/// A running job untouched for this long is assumed to have lost its worker
+/// and is handed out again. Handlers must finish well within it.
+const JOB_LOCK_TIMEOUT_MINUTES: i64 = 15;
+
+/// Durable queue shared by every instance. Claims use `SKIP LOCKED`, so any
+/// number of workers can poll it at once.
+#[derive(Clone)]
+pub struct PgJobQueue {
+    db: Database,
+}
+
+impl PgJobQueue {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+}
+
+impl JobQueue for PgJobQueue {
+    async fn enqueue(&self, job: NewJob) -> AppResult<Job> {
+        let row = sqlx::query_as::<_, JobRow>(&format!(
+            r#"
+            INSERT INTO jobs (tenant_id, job_type, payload, run_at, max_attempts)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING {}
+            "#,
+            JOB_COLUMNS
+        ))
+        .bind(job.tenant_id)
+        .bind(&job.job_type)
+        .bind(&job.payload)
+        .bind(job.run_at)
+        .bind(job.max_attempts)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(row.into())
+    }
+
+    async fn claim(&self, now: DateTime<Utc>, limit: usize) -> AppResult<Vec<Job>> {
+        let rows = sqlx::query_as::<_, JobRow>(&format!(
+            r#"
+            UPDATE jobs
+            SET status = 'running', attempts = attempts + 1, locked_at = $1
+            WHERE id IN (
+                SELECT id FROM jobs
+                WHERE (status = 'pending' AND run_at <= $1)
+                   OR (status = 'running' AND locked_at < $2)
+                ORDER BY run_at
+                LIMIT $3
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING {}
+            "#,
+            JOB_COLUMNS
+        ))
+        .bind(now)
+        .bind(now - Duration::minutes(JOB_LOCK_TIMEOUT_MINUTES))
+        .bind(limit as i64)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let mut jobs: Vec<Job> = rows.into_iter().map(Into::into).collect();
+        jobs.sort_by_key(|job| job.run_at);
+        Ok(jobs)
+    }
+
+    async fn complete(&self, job_id: Uuid) -> AppResult<()> {
+        sqlx::query("UPDATE jobs SET status = 'succeeded', last_error = NULL, locked_at = NULL WHERE id = $1")
+            .bind(job_id)
+            .execute(self.db.pool())
+            .await?;
+
+        Ok(())
+    }
+
+    async fn fail(&self, job_id: Uuid, error: &str, retry_at: Option<DateTime<Utc>>) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE jobs
+            SET status = CASE WHEN $2::TIMESTAMPTZ IS NULL THEN 'failed' ELSE 'pending' END,
+                run_at = COALESCE($2, run_at), last_error = $3, locked_at = NULL
+            WHERE id = $1
+            "#,
+        )
+        .bind(job_id)
+        .bind(retry_at)
+        .bind(error)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(())
+    }
+}
+
+// ============================================================================
+// DATABASE ROW TYPES
+// ============================================================================
+
+#[derive(sqlx::FromRow)]
+struct JobRow {
+    id: Uuid,
+    tenant_id: Option<Uuid>,
+    job_type: String,
+    payload: serde_json::Value,
+    run_at: DateTime<Utc>,
+    status: String,
+    attempts: i32,
+    max_attempts: i32,
+    last_error: Option<String>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<JobRow> for Job {
+    fn from(row: JobRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            job_type: row.job_type,
+            payload: row.payload,
+            run_at: row.run_at,
+            status: JobStatus::from_str(&row.status).unwrap_or_default(),
+            attempts: row.attempts,
+            max_attempts: row.max_attempts,
+            last_error: row.last_error,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
@@ -0,0 +1,375 @@
+//! Job queue abstraction and worker
+
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+use crate::utils::error::{AppError, AppResult};
+
+use super::models::*;
+
+/// Jobs claimed per `run_once` unless the worker is told otherwise
+const DEFAULT_BATCH_SIZE: usize = 20;
+
+/// Where jobs wait to run
+pub trait JobQueue: Send + Sync {
+    /// Store a pending job
+    fn enqueue(&self, job: NewJob) -> impl Future<Output = AppResult<Job>> + Send;
+
+    /// Take up to `limit` due jobs, earliest `run_at` first, marking them
+    /// running and counting the attempt. A job is handed to one worker only.
+    fn claim(&self, now: DateTime<Utc>, limit: usize) -> impl Future<Output = AppResult<Vec<Job>>> + Send;
+
+    fn complete(&self, job_id: Uuid) -> impl Future<Output = AppResult<()>> + Send;
+
+    /// Record a failed attempt: pending again at `retry_at`, or failed for
+    /// good when `retry_at` is `None`
+    fn fail(
+        &self,
+        job_id: Uuid,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+    ) -> impl Future<Output = AppResult<()>> + Send;
+}
+
+/// Queue held in memory. The default: nothing to set up, but jobs are lost
+/// on restart and not shared between instances.
+#[derive(Clone, Default)]
+pub struct InMemoryJobQueue {
+    jobs: Arc<Mutex<Vec<Job>>>,
+}
+
+impl InMemoryJobQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A job's current state
+    pub fn get(&self, job_id: Uuid) -> Option<Job> {
+        self.lock().iter().find(|job| job.id == job_id).cloned()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Job>> {
+        self.jobs.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn update(&self, job_id: Uuid, f: impl FnOnce(&mut Job)) -> AppResult<()> {
+        let mut jobs = self.lock();
+        let job = jobs
+            .iter_mut()
+            .find(|job| job.id == job_id)
+            .ok_or_else(|| AppError::not_found("Job"))?;
+        f(job);
+        job.updated_at = Utc::now();
+        Ok(())
+    }
+}
+
+impl JobQueue for InMemoryJobQueue {
+    async fn enqueue(&self, job: NewJob) -> AppResult<Job> {
+        let job = job.into_job(Utc::now());
+        self.lock().push(job.clone());
+        Ok(job)
+    }
+
+    async fn claim(&self, now: DateTime<Utc>, limit: usize) -> AppResult<Vec<Job>> {
+        let mut jobs = self.lock();
+        let mut due: Vec<&mut Job> = jobs.iter_mut().filter(|job| job.is_due(now)).collect();
+        due.sort_by_key(|job| job.run_at);
+
+        Ok(due
+            .into_iter()
+            .take(limit)
+            .map(|job| {
+                job.status = JobStatus::Running;
+                job.attempts += 1;
+                job.updated_at = now;
+                job.clone()
+            })
+            .collect())
+    }
+
+    async fn complete(&self, job_id: Uuid) -> AppResult<()> {
+        self.update(job_id, |job| {
+            job.status = JobStatus::Succeeded;
+            job.last_error = None;
+        })
+    }
+
+    async fn fail(&self, job_id: Uuid, error: &str, retry_at: Option<DateTime<Utc>>) -> AppResult<()> {
+        self.update(job_id, |job| {
+            job.last_error = Some(error.to_string());
+            match retry_at {
+                Some(retry_at) => {
+                    job.status = JobStatus::Pending;
+                    job.run_at = retry_at;
+                }
+                None => job.status = JobStatus::Failed,
+            }
+        })
+    }
+}
+
+/// Boxed future returned by a job handler
+pub type JobFuture = Pin<Box<dyn Future<Output = AppResult<()>> + Send>>;
+
+/// Runs one type of job. Any async function or closure taking the `Job` will do.
+pub trait JobHandler: Send + Sync {
+    fn run(&self, job: Job) -> JobFuture;
+}
+
+impl<F, Fut> JobHandler for F
+where
+    F: Fn(Job) -> Fut + Send + Sync,
+    Fut: Future<Output = AppResult<()>> + Send + 'static,
+{
+    fn run(&self, job: Job) -> JobFuture {
+        Box::pin(self(job))
+    }
+}
+
+/// What one pass of the worker did
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WorkerReport {
+    pub succeeded: usize,
+    /// Failed, and scheduled to run again
+    pub retried: usize,
+    /// Failed with no attempts left, or no handler for the type
+    pub failed: usize,
+}
+
+/// Claims due jobs from a queue and runs them with the registered handlers
+pub struct Worker<Q: JobQueue> {
+    queue: Q,
+    handlers: HashMap<String, Arc<dyn JobHandler>>,
+    /// Job types enqueued on a fixed interval while `run` polls
+    schedules: Vec<(String, chrono::Duration)>,
+    batch_size: usize,
+}
+
+impl<Q: JobQueue> Worker<Q> {
+    pub fn new(queue: Q) -> Self {
+        Self {
+            queue,
+            handlers: HashMap::new(),
+            schedules: Vec::new(),
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    /// Handle jobs of `job_type` with `handler`
+    pub fn register(mut self, job_type: impl Into<String>, handler: impl JobHandler + 'static) -> Self {
+        self.handlers.insert(job_type.into(), Arc::new(handler));
+        self
+    }
+
+    /// Enqueue a `job_type` job every `interval` while the worker runs, the
+    /// first on start. For sweeps that find their own work, so a job enqueued
+    /// by each of several instances is harmless.
+    pub fn every(mut self, job_type: impl Into<String>, interval: chrono::Duration) -> Self {
+        self.schedules.push((job_type.into(), interval));
+        self
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn queue(&self) -> &Q {
+        &self.queue
+    }
+
+    /// Run every job due at `now`, up to the batch size. A failed job is
+    /// retried later with backoff until its attempts are used up.
+    pub async fn run_once(&self, now: DateTime<Utc>) -> AppResult<WorkerReport> {
+        let mut report = WorkerReport::default();
+
+        for job in self.queue.claim(now, self.batch_size).await? {
+            let Some(handler) = self.handlers.get(&job.job_type) else {
+                tracing::warn!("No handler for job {} of type {}", job.id, job.job_type);
+                self.queue
+                    .fail(job.id, &format!("No handler for job type '{}'", job.job_type), None)
+                    .await?;
+                report.failed += 1;
+                continue;
+            };
+
+            let (job_id, retry_at) = (job.id, job.retry_at(now));
+            match handler.run(job).await {
+                Ok(()) => {
+                    self.queue.complete(job_id).await?;
+                    report.succeeded += 1;
+                }
+                Err(e) => {
+                    tracing::warn!("Job {} failed: {}", job_id, e);
+                    self.queue.fail(job_id, &e.to_string(), retry_at).await?;
+                    match retry_at {
+                        Some(_) => report.retried += 1,
+                        None => report.failed += 1,
+                    }
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Enqueue the scheduled jobs due at `now`, moving each one's entry in
+    /// `next_runs` on by its interval
+    pub async fn enqueue_scheduled(&self, now: DateTime<Utc>, next_runs: &mut [DateTime<Utc>]) -> AppResult<usize> {
+        let mut enqueued = 0;
+        for ((job_type, interval), next_run) in self.schedules.iter().zip(next_runs.iter_mut()) {
+            if *next_run > now {
+                continue;
+            }
+            self.queue.enqueue(NewJob::new(job_type.clone(), serde_json::json!({}))).await?;
+            *next_run = now + *interval;
+            enqueued += 1;
+        }
+        Ok(enqueued)
+    }
+
+    /// Poll the queue until the task is dropped, sleeping `poll_interval`
+    /// whenever there was nothing to do
+    pub async fn run(&self, poll_interval: std::time::Duration) {
+        let mut next_runs = vec![Utc::now(); self.schedules.len()];
+        loop {
+            if let Err(e) = self.enqueue_scheduled(Utc::now(), &mut next_runs).await {
+                tracing::error!("Scheduled jobs could not be enqueued: {}", e);
+            }
+            match self.run_once(Utc::now()).await {
+                Ok(report) if report != WorkerReport::default() => continue,
+                Ok(_) => {}
+                Err(e) => tracing::error!("Job worker pass failed: {}", e),
+            }
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_enqueue_and_run() {
+        let queue = InMemoryJobQueue::new();
+        let job = queue
+            .enqueue(NewJob::new("sla.scan", serde_json::json!({ "queue": "helpdesk" })))
+            .await
+            .unwrap();
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorded = seen.clone();
+        let worker = Worker::new(queue.clone()).register("sla.scan", move |job: Job| {
+            let recorded = recorded.clone();
+            async move {
+                recorded.lock().unwrap().push(job.payload["queue"].clone());
+                Ok(())
+            }
+        });
+
+        let report = worker.run_once(Utc::now()).await.unwrap();
+        assert_eq!(report, WorkerReport { succeeded: 1, ..WorkerReport::default() });
+        assert_eq!(*seen.lock().unwrap(), vec![serde_json::json!("helpdesk")]);
+
+        let done = queue.get(job.id).unwrap();
+        assert_eq!(done.status, JobStatus::Succeeded);
+        assert_eq!(done.attempts, 1);
+
+        // Nothing left to claim
+        assert_eq!(worker.run_once(Utc::now()).await.unwrap(), WorkerReport::default());
+    }
+
+    #[tokio::test]
+    async fn test_failed_job_is_retried_with_backoff() {
+        let queue = InMemoryJobQueue::new();
+        let job = queue
+            .enqueue(NewJob::new("webhooks.deliver", serde_json::json!({})).max_attempts(2))
+            .await
+            .unwrap();
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let worker = Worker::new(queue.clone()).register("webhooks.deliver", move |_job: Job| {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Err(AppError::internal("endpoint returned 503"))
+            }
+        });
+
+        let now = Utc::now();
+        let report = worker.run_once(now).await.unwrap();
+        assert_eq!(report.retried, 1);
+
+        let waiting = queue.get(job.id).unwrap();
+        assert_eq!(waiting.status, JobStatus::Pending);
+        assert_eq!(waiting.run_at, now + retry_backoff(1));
+        assert!(waiting.last_error.as_deref().unwrap().contains("503"));
+
+        // Not before the backoff has passed
+        assert_eq!(worker.run_once(now + Duration::seconds(10)).await.unwrap(), WorkerReport::default());
+
+        // The last attempt fails for good
+        let report = worker.run_once(waiting.run_at).await.unwrap();
+        assert_eq!(report.failed, 1);
+        assert_eq!(queue.get(job.id).unwrap().status, JobStatus::Failed);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_jobs_wait_for_run_at() {
+        let queue = InMemoryJobQueue::new();
+        let now = Utc::now();
+        let tomorrow = queue
+            .enqueue(NewJob::new("billing.recurring", serde_json::json!({})).run_at(now + Duration::days(1)))
+            .await
+            .unwrap();
+        let today = queue
+            .enqueue(NewJob::new("billing.recurring", serde_json::json!({})).run_at(now - Duration::minutes(5)))
+            .await
+            .unwrap();
+
+        let claimed = queue.claim(now, 10).await.unwrap();
+        assert_eq!(claimed.iter().map(|job| job.id).collect::<Vec<_>>(), vec![today.id]);
+        assert_eq!(queue.get(tomorrow.id).unwrap().status, JobStatus::Pending);
+
+        // A claimed job isn't handed out twice
+        assert!(queue.claim(now, 10).await.unwrap().is_empty());
+        assert_eq!(queue.claim(now + Duration::days(1), 10).await.unwrap()[0].id, tomorrow.id);
+
+        // Unknown types fail without retrying
+        queue
+            .enqueue(NewJob::new("retired.job", serde_json::json!({})).run_at(now))
+            .await
+            .unwrap();
+        let report = Worker::new(queue.clone()).run_once(now).await.unwrap();
+        assert_eq!(report.failed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_scheduled_jobs_are_enqueued_each_interval() {
+        let queue = InMemoryJobQueue::new();
+        let worker = Worker::new(queue.clone())
+            .every("billing.expire_quotes", Duration::hours(1))
+            .register("billing.expire_quotes", |_job: Job| async { Ok(()) });
+
+        let start = Utc::now();
+        let mut next_runs = vec![start];
+        assert_eq!(worker.enqueue_scheduled(start, &mut next_runs).await.unwrap(), 1);
+        assert_eq!(next_runs, vec![start + Duration::hours(1)]);
+        assert_eq!(worker.run_once(start).await.unwrap().succeeded, 1);
+
+        // Not again until the interval has passed
+        assert_eq!(worker.enqueue_scheduled(start + Duration::minutes(30), &mut next_runs).await.unwrap(), 0);
+        assert_eq!(worker.enqueue_scheduled(start + Duration::hours(1), &mut next_runs).await.unwrap(), 1);
+        assert_eq!(worker.run_once(start + Duration::hours(1)).await.unwrap().succeeded, 1);
+    }
+}
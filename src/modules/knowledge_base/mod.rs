@@ -1 +1,15 @@
-//! knowledge base module placeholder
+//! Knowledge Base Module
+//!
+//! Internal and client-facing articles with a draft, review and publish workflow.
+
+mod models;
+#[cfg(feature = "server")]
+mod service;
+#[cfg(feature = "server")]
+mod routes;
+
+pub use models::*;
+#[cfg(feature = "server")]
+pub use service::KnowledgeBaseService;
+#[cfg(feature = "server")]
+pub use routes::{kb_article_routes, kb_category_routes};
@@ -0,0 +1,492 @@
+//! Knowledge base models and types
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::utils::error::AppError;
+use crate::utils::tenant_settings::TenantSettings;
+
+// ============================================================================
+// ARTICLE STATUS
+// ============================================================================
+
+/// Article publishing lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ArticleStatus {
+    #[default]
+    Draft,
+    InReview,
+    Published,
+    Archived,
+}
+
+impl ArticleStatus {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "draft" => Some(Self::Draft),
+            "in_review" => Some(Self::InReview),
+            "published" => Some(Self::Published),
+            "archived" => Some(Self::Archived),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Draft => "draft",
+            Self::InReview => "in_review",
+            Self::Published => "published",
+            Self::Archived => "archived",
+        }
+    }
+
+    /// Check that an article may move from this status to `to`.
+    ///
+    /// When `require_review` is set, drafts must pass through review before publishing.
+    pub fn validate_transition(&self, to: ArticleStatus, require_review: bool) -> Result<(), AppError> {
+        let allowed = match (self, to) {
+            (Self::Draft, Self::InReview) | (Self::Draft, Self::Archived) => true,
+            (Self::Draft, Self::Published) => !require_review,
+            (Self::InReview, Self::Published) | (Self::InReview, Self::Draft) => true,
+            (Self::Published, Self::Draft) | (Self::Published, Self::Archived) => true,
+            (Self::Archived, Self::Draft) => true,
+            _ => false,
+        };
+
+        if allowed {
+            Ok(())
+        } else {
+            Err(AppError::validation_field(
+                "status",
+                format!("Cannot move an article from {} to {}", self.as_str(), to.as_str()),
+            ))
+        }
+    }
+}
+
+/// Who can see an article
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum KbVisibility {
+    Public,
+    #[default]
+    Internal,
+    ClientSpecific,
+}
+
+impl KbVisibility {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "public" => Some(Self::Public),
+            "internal" => Some(Self::Internal),
+            "client_specific" => Some(Self::ClientSpecific),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Public => "public",
+            Self::Internal => "internal",
+            Self::ClientSpecific => "client_specific",
+        }
+    }
+}
+
+// ============================================================================
+// ARTICLES
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KbArticle {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub title: String,
+    pub slug: String,
+    pub content: String,
+    pub summary: Option<String>,
+    pub category_id: Option<Uuid>,
+    pub visibility: KbVisibility,
+    pub company_ids: Vec<Uuid>,
+    pub status: ArticleStatus,
+    pub author_id: Uuid,
+    pub reviewed_by_id: Option<Uuid>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub view_count: i32,
+    pub helpful_count: i32,
+    pub not_helpful_count: i32,
+    pub tags: Vec<String>,
+    /// Publish time; may be in the future for scheduled articles
+    pub published_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl KbArticle {
+    /// Whether the client portal may serve this article at `now` to a contact of `company_id`
+    pub fn is_visible_in_portal(&self, now: DateTime<Utc>, company_id: Option<Uuid>) -> bool {
+        let published = self.status == ArticleStatus::Published
+            && self.published_at.is_some_and(|at| at <= now);
+
+        let audience = match self.visibility {
+            KbVisibility::Public => true,
+            KbVisibility::Internal => false,
+            KbVisibility::ClientSpecific => {
+                company_id.is_some_and(|id| self.company_ids.contains(&id))
+            }
+        };
+
+        published && audience
+    }
+}
+
+/// Create article request. New articles always start as drafts.
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CreateArticleRequest {
+    #[validate(length(min = 1, max = 255))]
+    pub title: String,
+    #[validate(length(min = 1))]
+    pub content: String,
+    pub summary: Option<String>,
+    pub category_id: Option<Uuid>,
+    #[serde(default)]
+    pub visibility: KbVisibility,
+    #[serde(default)]
+    pub company_ids: Vec<Uuid>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Move an article through the publishing workflow
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct TransitionArticleRequest {
+    pub status: ArticleStatus,
+    /// Schedule publishing for a later time; defaults to now
+    pub publish_at: Option<DateTime<Utc>>,
+}
+
+/// Article list filter
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct ArticleFilter {
+    pub status: Option<ArticleStatus>,
+    pub category_id: Option<Uuid>,
+    pub search: Option<String>,
+}
+
+// ============================================================================
+// CATEGORIES
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KbCategory {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub parent_id: Option<Uuid>,
+    pub slug: String,
+    pub visibility: KbVisibility,
+    pub sort_order: i32,
+}
+
+/// A category with its nested subcategories
+#[derive(Debug, Clone, Serialize)]
+pub struct KbCategoryNode {
+    #[serde(flatten)]
+    pub category: KbCategory,
+    pub children: Vec<KbCategoryNode>,
+}
+
+/// One step of the path from the root category to an article
+#[derive(Debug, Clone, Serialize)]
+pub struct Breadcrumb {
+    pub id: Uuid,
+    pub name: String,
+    pub slug: String,
+}
+
+impl KbCategory {
+    /// Arrange a flat category list into a tree ordered by `sort_order`, then name.
+    ///
+    /// Categories whose parent is missing are treated as roots.
+    pub fn build_tree(categories: Vec<KbCategory>) -> Vec<KbCategoryNode> {
+        let ids: HashSet<Uuid> = categories.iter().map(|c| c.id).collect();
+        let mut by_parent: HashMap<Option<Uuid>, Vec<KbCategory>> = HashMap::new();
+
+        for category in categories {
+            let parent = category.parent_id.filter(|id| ids.contains(id));
+            by_parent.entry(parent).or_default().push(category);
+        }
+
+        fn attach(
+            parent: Option<Uuid>,
+            by_parent: &mut HashMap<Option<Uuid>, Vec<KbCategory>>,
+        ) -> Vec<KbCategoryNode> {
+            let mut siblings = by_parent.remove(&parent).unwrap_or_default();
+            siblings.sort_by(|a, b| a.sort_order.cmp(&b.sort_order).then_with(|| a.name.cmp(&b.name)));
+
+            siblings
+                .into_iter()
+                .map(|category| {
+                    let children = attach(Some(category.id), by_parent);
+                    KbCategoryNode { category, children }
+                })
+                .collect()
+        }
+
+        attach(None, &mut by_parent)
+    }
+
+    /// Path from the root down to `category_id`, inclusive
+    pub fn breadcrumbs(category_id: Uuid, categories: &[KbCategory]) -> Vec<Breadcrumb> {
+        let mut path = Vec::new();
+        let mut current = Some(category_id);
+
+        while let Some(id) = current {
+            // Stop on missing rows or a pre-existing cycle
+            let Some(category) = categories.iter().find(|c| c.id == id) else {
+                break;
+            };
+            if path.iter().any(|b: &Breadcrumb| b.id == id) {
+                break;
+            }
+
+            path.push(Breadcrumb {
+                id: category.id,
+                name: category.name.clone(),
+                slug: category.slug.clone(),
+            });
+            current = category.parent_id;
+        }
+
+        path.reverse();
+        path
+    }
+
+    /// Check that making `parent_id` the parent of `category_id` would not create a cycle
+    pub fn validate_parent(
+        category_id: Uuid,
+        parent_id: Option<Uuid>,
+        categories: &[KbCategory],
+    ) -> Result<(), AppError> {
+        let Some(parent_id) = parent_id else {
+            return Ok(());
+        };
+
+        if !categories.iter().any(|c| c.id == parent_id) {
+            return Err(AppError::validation_field("parent_id", "Parent category does not exist"));
+        }
+
+        let ancestors = Self::breadcrumbs(parent_id, categories);
+        if ancestors.iter().any(|b| b.id == category_id) {
+            return Err(AppError::validation_field(
+                "parent_id",
+                "A category cannot be its own ancestor",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Create category request
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CreateCategoryRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    pub description: Option<String>,
+    pub parent_id: Option<Uuid>,
+    #[serde(default)]
+    pub visibility: KbVisibility,
+    #[serde(default)]
+    pub sort_order: i32,
+}
+
+/// Update category request
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct UpdateCategoryRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub parent_id: Option<Uuid>,
+    /// Move the category to the root; takes precedence over `parent_id`
+    #[serde(default)]
+    pub clear_parent: bool,
+    pub visibility: Option<KbVisibility>,
+    pub sort_order: Option<i32>,
+}
+
+// ============================================================================
+// SETTINGS
+// ============================================================================
+
+/// Per-tenant KB behaviour, stored under the `knowledge_base` category of `tenant_settings`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KnowledgeBaseSettings {
+    /// Articles must be approved by someone other than the author before publishing
+    pub require_review: bool,
+}
+
+impl TenantSettings for KnowledgeBaseSettings {
+    const CATEGORY: &'static str = "knowledge_base";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn sample_article(status: ArticleStatus) -> KbArticle {
+        let now = Utc::now();
+        KbArticle {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            title: "Reset your password".to_string(),
+            slug: "reset-your-password".to_string(),
+            content: "Steps".to_string(),
+            summary: None,
+            category_id: None,
+            visibility: KbVisibility::Public,
+            company_ids: vec![],
+            status,
+            author_id: Uuid::new_v4(),
+            reviewed_by_id: None,
+            reviewed_at: None,
+            view_count: 0,
+            helpful_count: 0,
+            not_helpful_count: 0,
+            tags: vec![],
+            published_at: (status == ArticleStatus::Published).then(|| now - Duration::hours(1)),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_draft_excluded_from_portal() {
+        let now = Utc::now();
+        let articles = [
+            sample_article(ArticleStatus::Draft),
+            sample_article(ArticleStatus::InReview),
+            sample_article(ArticleStatus::Published),
+            sample_article(ArticleStatus::Archived),
+        ];
+
+        let visible: Vec<_> = articles
+            .iter()
+            .filter(|a| a.is_visible_in_portal(now, None))
+            .collect();
+
+        assert_eq!(visible.len(), 1);
+        assert_eq!(visible[0].status, ArticleStatus::Published);
+    }
+
+    #[test]
+    fn test_scheduled_article_visible_after_publish_time() {
+        let now = Utc::now();
+        let mut article = sample_article(ArticleStatus::Published);
+        article.published_at = Some(now + Duration::days(1));
+
+        assert!(!article.is_visible_in_portal(now, None));
+        assert!(article.is_visible_in_portal(now + Duration::days(1), None));
+    }
+
+    #[test]
+    fn test_portal_visibility_scoping() {
+        let now = Utc::now();
+        let company_id = Uuid::new_v4();
+
+        let mut internal = sample_article(ArticleStatus::Published);
+        internal.visibility = KbVisibility::Internal;
+        assert!(!internal.is_visible_in_portal(now, Some(company_id)));
+
+        let mut client = sample_article(ArticleStatus::Published);
+        client.visibility = KbVisibility::ClientSpecific;
+        client.company_ids = vec![company_id];
+        assert!(client.is_visible_in_portal(now, Some(company_id)));
+        assert!(!client.is_visible_in_portal(now, Some(Uuid::new_v4())));
+    }
+
+    #[test]
+    fn test_review_gate_blocks_direct_publish() {
+        assert!(ArticleStatus::Draft
+            .validate_transition(ArticleStatus::Published, false)
+            .is_ok());
+        assert!(ArticleStatus::Draft
+            .validate_transition(ArticleStatus::Published, true)
+            .is_err());
+        assert!(ArticleStatus::InReview
+            .validate_transition(ArticleStatus::Published, true)
+            .is_ok());
+        assert!(ArticleStatus::Archived
+            .validate_transition(ArticleStatus::Published, false)
+            .is_err());
+    }
+
+    fn category(name: &str, parent_id: Option<Uuid>, sort_order: i32) -> KbCategory {
+        KbCategory {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::nil(),
+            name: name.to_string(),
+            description: None,
+            parent_id,
+            slug: name.to_lowercase(),
+            visibility: KbVisibility::Public,
+            sort_order,
+        }
+    }
+
+    #[test]
+    fn test_build_two_level_category_tree() {
+        let email = category("Email", None, 2);
+        let network = category("Network", None, 1);
+        let outlook = category("Outlook", Some(email.id), 2);
+        let mobile = category("Mobile", Some(email.id), 1);
+
+        let tree = KbCategory::build_tree(vec![
+            outlook.clone(),
+            email.clone(),
+            mobile.clone(),
+            network.clone(),
+        ]);
+
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree[0].category.id, network.id);
+        assert!(tree[0].children.is_empty());
+        assert_eq!(tree[1].category.id, email.id);
+        let children: Vec<_> = tree[1].children.iter().map(|n| n.category.id).collect();
+        assert_eq!(children, vec![mobile.id, outlook.id]);
+    }
+
+    #[test]
+    fn test_category_breadcrumbs() {
+        let email = category("Email", None, 0);
+        let outlook = category("Outlook", Some(email.id), 0);
+        let categories = vec![email.clone(), outlook.clone()];
+
+        let path: Vec<_> = KbCategory::breadcrumbs(outlook.id, &categories)
+            .into_iter()
+            .map(|b| b.name)
+            .collect();
+
+        assert_eq!(path, vec!["Email", "Outlook"]);
+    }
+
+    #[test]
+    fn test_reject_category_as_own_ancestor() {
+        let root = category("Root", None, 0);
+        let child = category("Child", Some(root.id), 0);
+        let grandchild = category("Grandchild", Some(child.id), 0);
+        let categories = vec![root.clone(), child.clone(), grandchild.clone()];
+
+        assert!(KbCategory::validate_parent(root.id, Some(grandchild.id), &categories).is_err());
+        assert!(KbCategory::validate_parent(root.id, Some(root.id), &categories).is_err());
+        assert!(KbCategory::validate_parent(grandchild.id, Some(root.id), &categories).is_ok());
+        assert!(KbCategory::validate_parent(child.id, None, &categories).is_ok());
+    }
+}
@@ -0,0 +1,184 @@
+//! Knowledge base API routes
+
+use axum::{
+    extract::{OriginalUri, Path, Query, State},
+    routing::{get, post, put},
+    Json, Router,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::{
+    ArticleFilter, Breadcrumb, CreateArticleRequest, CreateCategoryRequest, KbArticle, KbCategory,
+    KbCategoryNode, KnowledgeBaseService, TransitionArticleRequest, UpdateCategoryRequest,
+};
+use crate::modules::auth::RequireAuth;
+use crate::utils::error::AppResult;
+use crate::utils::pagination::{PaginatedJson, PaginatedResponse, PaginationParams};
+
+#[derive(Clone)]
+pub struct KbRouterState {
+    pub kb_service: Arc<KnowledgeBaseService>,
+}
+
+/// Create the KB article router
+pub fn kb_article_routes(kb_service: KnowledgeBaseService) -> Router {
+    let state = KbRouterState {
+        kb_service: Arc::new(kb_service),
+    };
+
+    Router::new()
+        .route("/", get(list_articles))
+        .route("/", post(create_article))
+        .route("/:article_id", get(get_article))
+        .route("/:article_id/transition", post(transition_article))
+        .route("/:article_id/breadcrumbs", get(get_article_breadcrumbs))
+        .with_state(state)
+}
+
+/// Create the KB category router
+pub fn kb_category_routes(kb_service: KnowledgeBaseService) -> Router {
+    let state = KbRouterState {
+        kb_service: Arc::new(kb_service),
+    };
+
+    Router::new()
+        .route("/", get(get_category_tree))
+        .route("/", post(create_category))
+        .route("/:category_id", get(get_category))
+        .route("/:category_id", put(update_category))
+        .with_state(state)
+}
+
+// ============================================================================
+// ARTICLE HANDLERS
+// ============================================================================
+
+async fn list_articles(
+    State(state): State<KbRouterState>,
+    RequireAuth(user): RequireAuth,
+    Query(filter): Query<ArticleFilter>,
+    Query(pagination): Query<PaginationParams>,
+    OriginalUri(uri): OriginalUri,
+) -> AppResult<PaginatedJson<KbArticle>> {
+    let (articles, total) = state
+        .kb_service
+        .list_articles(user.tenant_id, &filter, &pagination)
+        .await?;
+
+    Ok(PaginatedResponse::from_params(articles, &pagination, total).with_links(&uri))
+}
+
+async fn create_article(
+    State(state): State<KbRouterState>,
+    RequireAuth(user): RequireAuth,
+    Json(request): Json<CreateArticleRequest>,
+) -> AppResult<Json<KbArticle>> {
+    request.validate()?;
+
+    let article = state
+        .kb_service
+        .create_article(user.tenant_id, user.id, &request)
+        .await?;
+
+    Ok(Json(article))
+}
+
+async fn get_article(
+    State(state): State<KbRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(article_id): Path<Uuid>,
+) -> AppResult<Json<KbArticle>> {
+    let article = state
+        .kb_service
+        .get_article(user.tenant_id, article_id)
+        .await?;
+
+    Ok(Json(article))
+}
+
+async fn transition_article(
+    State(state): State<KbRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(article_id): Path<Uuid>,
+    Json(request): Json<TransitionArticleRequest>,
+) -> AppResult<Json<KbArticle>> {
+    request.validate()?;
+
+    let article = state
+        .kb_service
+        .transition_article(user.tenant_id, article_id, user.id, &request)
+        .await?;
+
+    Ok(Json(article))
+}
+
+async fn get_article_breadcrumbs(
+    State(state): State<KbRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(article_id): Path<Uuid>,
+) -> AppResult<Json<Vec<Breadcrumb>>> {
+    let breadcrumbs = state
+        .kb_service
+        .article_breadcrumbs(user.tenant_id, article_id)
+        .await?;
+
+    Ok(Json(breadcrumbs))
+}
+
+// ============================================================================
+// CATEGORY HANDLERS
+// ============================================================================
+
+async fn get_category_tree(
+    State(state): State<KbRouterState>,
+    RequireAuth(user): RequireAuth,
+) -> AppResult<Json<Vec<KbCategoryNode>>> {
+    let tree = state.kb_service.category_tree(user.tenant_id).await?;
+    Ok(Json(tree))
+}
+
+async fn create_category(
+    State(state): State<KbRouterState>,
+    RequireAuth(user): RequireAuth,
+    Json(request): Json<CreateCategoryRequest>,
+) -> AppResult<Json<KbCategory>> {
+    request.validate()?;
+
+    let category = state
+        .kb_service
+        .create_category(user.tenant_id, &request)
+        .await?;
+
+    Ok(Json(category))
+}
+
+async fn get_category(
+    State(state): State<KbRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(category_id): Path<Uuid>,
+) -> AppResult<Json<KbCategory>> {
+    let category = state
+        .kb_service
+        .get_category(user.tenant_id, category_id)
+        .await?;
+
+    Ok(Json(category))
+}
+
+async fn update_category(
+    State(state): State<KbRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(category_id): Path<Uuid>,
+    Json(request): Json<UpdateCategoryRequest>,
+) -> AppResult<Json<KbCategory>> {
+    request.validate()?;
+
+    let category = state
+        .kb_service
+        .update_category(user.tenant_id, category_id, &request)
+        .await?;
+
+    Ok(Json(category))
+}
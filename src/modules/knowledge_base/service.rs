@@ -0,0 +1,445 @@
+//! Knowledge base service implementation
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::pagination::PaginationParams;
+use crate::utils::tenant_settings::TenantSettings;
+use crate::utils::validation::slugify;
+
+use super::models::*;
+
+/// Knowledge base management service
+#[derive(Clone)]
+pub struct KnowledgeBaseService {
+    db: Database,
+}
+
+impl KnowledgeBaseService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Create a new draft article
+    pub async fn create_article(
+        &self,
+        tenant_id: Uuid,
+        author_id: Uuid,
+        request: &CreateArticleRequest,
+    ) -> AppResult<KbArticle> {
+        let article_id = Uuid::new_v4();
+
+        sqlx::query(
+            r#"
+            INSERT INTO kb_articles (id, tenant_id, title, slug, content, summary, category_id,
+                                     visibility, company_ids, status, author_id, tags)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, 'draft', $10, $11)
+            "#,
+        )
+        .bind(article_id)
+        .bind(tenant_id)
+        .bind(&request.title)
+        .bind(slugify(&request.title))
+        .bind(&request.content)
+        .bind(&request.summary)
+        .bind(request.category_id)
+        .bind(request.visibility.as_str())
+        .bind(&request.company_ids)
+        .bind(author_id)
+        .bind(&request.tags)
+        .execute(self.db.pool())
+        .await?;
+
+        self.get_article(tenant_id, article_id).await
+    }
+
+    /// Get article by ID
+    pub async fn get_article(&self, tenant_id: Uuid, article_id: Uuid) -> AppResult<KbArticle> {
+        let row = sqlx::query_as::<_, KbArticleRow>(
+            r#"
+            SELECT id, tenant_id, title, slug, content, summary, category_id, visibility,
+                   company_ids, status, author_id, reviewed_by_id, reviewed_at, view_count,
+                   helpful_count, not_helpful_count, tags, published_at, created_at, updated_at
+            FROM kb_articles
+            WHERE tenant_id = $1 AND id = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(article_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Article".to_string()))?;
+
+        Ok(row.into())
+    }
+
+    /// List articles for staff, in any status
+    pub async fn list_articles(
+        &self,
+        tenant_id: Uuid,
+        filter: &ArticleFilter,
+        pagination: &PaginationParams,
+    ) -> AppResult<(Vec<KbArticle>, u64)> {
+        // Filter placeholders follow the fixed binds, which differ between the two queries
+        let where_clause = |first_param: usize| {
+            let mut conditions = vec!["tenant_id = $1".to_string()];
+            let mut param_idx = first_param;
+
+            if filter.status.is_some() {
+                conditions.push(format!("status = ${}", param_idx));
+                param_idx += 1;
+            }
+            if filter.category_id.is_some() {
+                conditions.push(format!("category_id = ${}", param_idx));
+                param_idx += 1;
+            }
+            if filter.search.is_some() {
+                conditions.push(format!("(title ILIKE ${} OR summary ILIKE ${})", param_idx, param_idx));
+            }
+
+            conditions.join(" AND ")
+        };
+
+        let order_by = pagination.order_by("updated_at", &["title", "created_at", "updated_at", "published_at"]);
+
+        let query = format!(
+            r#"
+            SELECT id, tenant_id, title, slug, content, summary, category_id, visibility,
+                   company_ids, status, author_id, reviewed_by_id, reviewed_at, view_count,
+                   helpful_count, not_helpful_count, tags, published_at, created_at, updated_at
+            FROM kb_articles
+            WHERE {}
+            ORDER BY {}
+            LIMIT $2 OFFSET $3
+            "#,
+            where_clause(4),
+            order_by,
+        );
+        let count_query = format!("SELECT COUNT(*) FROM kb_articles WHERE {}", where_clause(2));
+
+        let mut cq = sqlx::query_scalar::<_, i64>(&count_query).bind(tenant_id);
+        if let Some(status) = filter.status {
+            cq = cq.bind(status.as_str());
+        }
+        if let Some(category_id) = filter.category_id {
+            cq = cq.bind(category_id);
+        }
+        if let Some(ref search) = filter.search {
+            cq = cq.bind(format!("%{}%", search));
+        }
+        let total = cq.fetch_one(self.db.pool()).await?;
+
+        let page = pagination.clamped(total as u64);
+        let mut q = sqlx::query_as::<_, KbArticleRow>(&query)
+            .bind(tenant_id)
+            .bind(page.limit() as i32)
+            .bind(page.offset() as i32);
+        if let Some(status) = filter.status {
+            q = q.bind(status.as_str());
+        }
+        if let Some(category_id) = filter.category_id {
+            q = q.bind(category_id);
+        }
+        if let Some(ref search) = filter.search {
+            q = q.bind(format!("%{}%", search));
+        }
+        let rows = q.fetch_all(self.db.pool()).await?;
+
+        Ok((rows.into_iter().map(Into::into).collect(), total as u64))
+    }
+
+    /// Move an article through the publishing workflow
+    pub async fn transition_article(
+        &self,
+        tenant_id: Uuid,
+        article_id: Uuid,
+        user_id: Uuid,
+        request: &TransitionArticleRequest,
+    ) -> AppResult<KbArticle> {
+        let article = self.get_article(tenant_id, article_id).await?;
+        let settings = self.settings(tenant_id).await?;
+
+        article
+            .status
+            .validate_transition(request.status, settings.require_review)?;
+
+        match request.status {
+            ArticleStatus::Published => {
+                let approving = article.status == ArticleStatus::InReview;
+                if approving && settings.require_review && article.author_id == user_id {
+                    return Err(AppError::Forbidden(
+                        "Articles must be approved by someone other than the author".to_string(),
+                    ));
+                }
+
+                let publish_at = request.publish_at.unwrap_or_else(Utc::now);
+                sqlx::query(
+                    r#"
+                    UPDATE kb_articles
+                    SET status = 'published', published_at = $1,
+                        reviewed_by_id = CASE WHEN $2 THEN $3 ELSE reviewed_by_id END,
+                        reviewed_at = CASE WHEN $2 THEN NOW() ELSE reviewed_at END,
+                        updated_at = NOW()
+                    WHERE tenant_id = $4 AND id = $5
+                    "#,
+                )
+                .bind(publish_at)
+                .bind(approving)
+                .bind(user_id)
+                .bind(tenant_id)
+                .bind(article_id)
+                .execute(self.db.pool())
+                .await?;
+            }
+            ArticleStatus::Draft => {
+                sqlx::query(
+                    "UPDATE kb_articles SET status = 'draft', published_at = NULL, reviewed_by_id = NULL, reviewed_at = NULL, updated_at = NOW() WHERE tenant_id = $1 AND id = $2",
+                )
+                .bind(tenant_id)
+                .bind(article_id)
+                .execute(self.db.pool())
+                .await?;
+            }
+            status => {
+                sqlx::query("UPDATE kb_articles SET status = $1, updated_at = NOW() WHERE tenant_id = $2 AND id = $3")
+                    .bind(status.as_str())
+                    .bind(tenant_id)
+                    .bind(article_id)
+                    .execute(self.db.pool())
+                    .await?;
+            }
+        }
+
+        self.get_article(tenant_id, article_id).await
+    }
+
+    /// All categories for a tenant as a flat list
+    pub async fn list_categories(&self, tenant_id: Uuid) -> AppResult<Vec<KbCategory>> {
+        let rows = sqlx::query_as::<_, KbCategoryRow>(
+            r#"
+            SELECT id, tenant_id, name, description, parent_id, slug, visibility, sort_order
+            FROM kb_categories
+            WHERE tenant_id = $1
+            ORDER BY sort_order, name
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Category hierarchy for navigation
+    pub async fn category_tree(&self, tenant_id: Uuid) -> AppResult<Vec<KbCategoryNode>> {
+        let categories = self.list_categories(tenant_id).await?;
+        Ok(KbCategory::build_tree(categories))
+    }
+
+    /// Get category by ID
+    pub async fn get_category(&self, tenant_id: Uuid, category_id: Uuid) -> AppResult<KbCategory> {
+        let row = sqlx::query_as::<_, KbCategoryRow>(
+            r#"
+            SELECT id, tenant_id, name, description, parent_id, slug, visibility, sort_order
+            FROM kb_categories
+            WHERE tenant_id = $1 AND id = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(category_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Category".to_string()))?;
+
+        Ok(row.into())
+    }
+
+    /// Create a category
+    pub async fn create_category(
+        &self,
+        tenant_id: Uuid,
+        request: &CreateCategoryRequest,
+    ) -> AppResult<KbCategory> {
+        let category_id = Uuid::new_v4();
+
+        if let Some(parent_id) = request.parent_id {
+            let categories = self.list_categories(tenant_id).await?;
+            KbCategory::validate_parent(category_id, Some(parent_id), &categories)?;
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO kb_categories (id, tenant_id, name, description, parent_id, slug, visibility, sort_order)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(category_id)
+        .bind(tenant_id)
+        .bind(&request.name)
+        .bind(&request.description)
+        .bind(request.parent_id)
+        .bind(slugify(&request.name))
+        .bind(request.visibility.as_str())
+        .bind(request.sort_order)
+        .execute(self.db.pool())
+        .await?;
+
+        self.get_category(tenant_id, category_id).await
+    }
+
+    /// Update a category, rejecting parent changes that would create a cycle
+    pub async fn update_category(
+        &self,
+        tenant_id: Uuid,
+        category_id: Uuid,
+        request: &UpdateCategoryRequest,
+    ) -> AppResult<KbCategory> {
+        let existing = self.get_category(tenant_id, category_id).await?;
+
+        let parent_id = if request.clear_parent {
+            None
+        } else {
+            request.parent_id.or(existing.parent_id)
+        };
+
+        if parent_id != existing.parent_id {
+            let categories = self.list_categories(tenant_id).await?;
+            KbCategory::validate_parent(category_id, parent_id, &categories)?;
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE kb_categories
+            SET name = $1, description = $2, parent_id = $3, visibility = $4, sort_order = $5, updated_at = NOW()
+            WHERE tenant_id = $6 AND id = $7
+            "#,
+        )
+        .bind(request.name.as_ref().unwrap_or(&existing.name))
+        .bind(request.description.as_ref().or(existing.description.as_ref()))
+        .bind(parent_id)
+        .bind(request.visibility.unwrap_or(existing.visibility).as_str())
+        .bind(request.sort_order.unwrap_or(existing.sort_order))
+        .bind(tenant_id)
+        .bind(category_id)
+        .execute(self.db.pool())
+        .await?;
+
+        self.get_category(tenant_id, category_id).await
+    }
+
+    /// Path of categories from the root to an article's category
+    pub async fn article_breadcrumbs(
+        &self,
+        tenant_id: Uuid,
+        article_id: Uuid,
+    ) -> AppResult<Vec<Breadcrumb>> {
+        let article = self.get_article(tenant_id, article_id).await?;
+        let Some(category_id) = article.category_id else {
+            return Ok(Vec::new());
+        };
+
+        let categories = self.list_categories(tenant_id).await?;
+        Ok(KbCategory::breadcrumbs(category_id, &categories))
+    }
+
+    /// Get KB settings for a tenant
+    pub async fn settings(&self, tenant_id: Uuid) -> AppResult<KnowledgeBaseSettings> {
+        KnowledgeBaseSettings::load(self.db.pool(), tenant_id).await
+    }
+}
+
+// ============================================================================
+// DATABASE ROW TYPES
+// ============================================================================
+
+#[derive(sqlx::FromRow)]
+struct KbArticleRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    title: String,
+    slug: String,
+    content: String,
+    summary: Option<String>,
+    category_id: Option<Uuid>,
+    visibility: Option<String>,
+    company_ids: Option<Vec<Uuid>>,
+    status: Option<String>,
+    author_id: Uuid,
+    reviewed_by_id: Option<Uuid>,
+    reviewed_at: Option<chrono::DateTime<Utc>>,
+    view_count: Option<i32>,
+    helpful_count: Option<i32>,
+    not_helpful_count: Option<i32>,
+    tags: Option<Vec<String>>,
+    published_at: Option<chrono::DateTime<Utc>>,
+    created_at: chrono::DateTime<Utc>,
+    updated_at: chrono::DateTime<Utc>,
+}
+
+impl From<KbArticleRow> for KbArticle {
+    fn from(row: KbArticleRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            title: row.title,
+            slug: row.slug,
+            content: row.content,
+            summary: row.summary,
+            category_id: row.category_id,
+            visibility: row
+                .visibility
+                .as_deref()
+                .and_then(KbVisibility::from_str)
+                .unwrap_or_default(),
+            company_ids: row.company_ids.unwrap_or_default(),
+            status: row
+                .status
+                .as_deref()
+                .and_then(ArticleStatus::from_str)
+                .unwrap_or_default(),
+            author_id: row.author_id,
+            reviewed_by_id: row.reviewed_by_id,
+            reviewed_at: row.reviewed_at,
+            view_count: row.view_count.unwrap_or(0),
+            helpful_count: row.helpful_count.unwrap_or(0),
+            not_helpful_count: row.not_helpful_count.unwrap_or(0),
+            tags: row.tags.unwrap_or_default(),
+            published_at: row.published_at,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct KbCategoryRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    name: String,
+    description: Option<String>,
+    parent_id: Option<Uuid>,
+    slug: String,
+    visibility: Option<String>,
+    sort_order: Option<i32>,
+}
+
+impl From<KbCategoryRow> for KbCategory {
+    fn from(row: KbCategoryRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            name: row.name,
+            description: row.description,
+            parent_id: row.parent_id,
+            slug: row.slug,
+            visibility: row
+                .visibility
+                .as_deref()
+                .and_then(KbVisibility::from_str)
+                .unwrap_or_default(),
+            sort_order: row.sort_order.unwrap_or(0),
+        }
+    }
+}
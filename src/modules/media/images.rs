@@ -0,0 +1,187 @@
+//! Image checks, resizing and storage
+//!
+//! Every upload is decoded and re-encoded as PNG, which drops metadata such
+//! as GPS tags and anything smuggled in after the image data.
+
+use std::io::Cursor;
+
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat, ImageReader, Limits};
+use uuid::Uuid;
+
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::storage::FileStorage;
+
+use super::models::*;
+
+/// Formats accepted for upload
+const ACCEPTED_FORMATS: [ImageFormat; 4] = [ImageFormat::Png, ImageFormat::Jpeg, ImageFormat::Gif, ImageFormat::WebP];
+
+/// An upload resized for its use, with its thumbnail, both PNG encoded
+#[derive(Debug, Clone)]
+pub struct ProcessedImage {
+    pub image: Vec<u8>,
+    pub thumbnail: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Check an upload is an image within the limits, then size it for `kind`
+pub fn process_image(kind: ImageKind, content: &[u8]) -> AppResult<ProcessedImage> {
+    if content.len() > MAX_IMAGE_BYTES {
+        return Err(AppError::validation_field(
+            "file",
+            format!("Images can be at most {} MB", MAX_IMAGE_BYTES / (1024 * 1024)),
+        ));
+    }
+
+    let reader = ImageReader::new(Cursor::new(content))
+        .with_guessed_format()
+        .map_err(|e| AppError::internal(format!("Failed to read image: {}", e)))?;
+    if !reader.format().is_some_and(|format| ACCEPTED_FORMATS.contains(&format)) {
+        return Err(AppError::validation_field("file", "Upload a PNG, JPEG, GIF or WebP image"));
+    }
+
+    // Refuse oversized images before allocating for them
+    let (width, height) = reader
+        .into_dimensions()
+        .map_err(|_| AppError::validation_field("file", "The image could not be read"))?;
+    if width > MAX_IMAGE_DIMENSION || height > MAX_IMAGE_DIMENSION {
+        return Err(AppError::validation_field(
+            "file",
+            format!("Images can be at most {0}x{0} pixels", MAX_IMAGE_DIMENSION),
+        ));
+    }
+
+    let mut reader = ImageReader::new(Cursor::new(content))
+        .with_guessed_format()
+        .map_err(|e| AppError::internal(format!("Failed to read image: {}", e)))?;
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_IMAGE_DIMENSION);
+    limits.max_image_height = Some(MAX_IMAGE_DIMENSION);
+    reader.limits(limits);
+    let decoded = reader
+        .decode()
+        .map_err(|_| AppError::validation_field("file", "The image could not be read"))?;
+
+    let image = fit(square(decoded, kind), kind.max_size());
+    let (thumbnail_width, thumbnail_height) = kind.thumbnail_size();
+    let thumbnail = image.resize(thumbnail_width, thumbnail_height, FilterType::Lanczos3);
+
+    Ok(ProcessedImage {
+        width: image.width(),
+        height: image.height(),
+        image: encode_png(&image)?,
+        thumbnail: encode_png(&thumbnail)?,
+    })
+}
+
+/// Process an upload and store it with its thumbnail under a new key
+pub async fn store_image<S: FileStorage>(
+    storage: &S,
+    tenant_id: Uuid,
+    kind: ImageKind,
+    content: &[u8],
+) -> AppResult<StoredImage> {
+    let processed = process_image(kind, content)?;
+
+    let key = format!("{}/{}/{}.png", tenant_id, kind.folder(), Uuid::new_v4());
+    let thumbnail_key = thumbnail_key(&key);
+    storage.put(&key, &processed.image).await?;
+    storage.put(&thumbnail_key, &processed.thumbnail).await?;
+
+    Ok(StoredImage {
+        url: image_url(&key),
+        thumbnail_url: image_url(&thumbnail_key),
+        width: processed.width,
+        height: processed.height,
+    })
+}
+
+/// Crop the middle square out of images that should be square
+fn square(image: DynamicImage, kind: ImageKind) -> DynamicImage {
+    let side = image.width().min(image.height());
+    if !kind.is_square() || image.width() == image.height() {
+        return image;
+    }
+    image.crop_imm((image.width() - side) / 2, (image.height() - side) / 2, side, side)
+}
+
+/// Scale down to fit within `(width, height)`; smaller images are kept as
+/// they are
+fn fit(image: DynamicImage, (width, height): (u32, u32)) -> DynamicImage {
+    if image.width() <= width && image.height() <= height {
+        return image;
+    }
+    image.resize(width, height, FilterType::Lanczos3)
+}
+
+fn encode_png(image: &DynamicImage) -> AppResult<Vec<u8>> {
+    let mut content = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut content), ImageFormat::Png)
+        .map_err(|e| AppError::internal(format!("Failed to encode image: {}", e)))?;
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::storage::MemoryStorage;
+    use image::{Rgb, RgbImage};
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        encode_png(&DynamicImage::ImageRgb8(RgbImage::from_pixel(width, height, Rgb([20, 90, 160])))).unwrap()
+    }
+
+    fn error_field(result: AppResult<ProcessedImage>) -> String {
+        match result {
+            Err(AppError::Validation { errors, .. }) => errors[0].field.clone(),
+            other => panic!("expected validation error, got {:?}", other.map(|p| (p.width, p.height))),
+        }
+    }
+
+    #[test]
+    fn test_non_image_upload_is_rejected() {
+        assert_eq!(error_field(process_image(ImageKind::UserAvatar, b"%PDF-1.7 not an image")), "file");
+        assert_eq!(error_field(process_image(ImageKind::CompanyLogo, b"")), "file");
+
+        // A PNG header on a truncated file still fails to decode
+        let truncated = &png(10, 10)[..40];
+        assert_eq!(error_field(process_image(ImageKind::UserAvatar, truncated)), "file");
+
+        let huge = png(MAX_IMAGE_DIMENSION + 1, 1);
+        assert_eq!(error_field(process_image(ImageKind::CompanyLogo, &huge)), "file");
+    }
+
+    #[tokio::test]
+    async fn test_avatar_upload_is_resized_and_stored() {
+        let storage = MemoryStorage::default();
+        let tenant_id = Uuid::new_v4();
+
+        let stored = store_image(&storage, tenant_id, ImageKind::UserAvatar, &png(1200, 800)).await.unwrap();
+        assert_eq!((stored.width, stored.height), (512, 512));
+
+        let key = image_key(&stored.url).unwrap();
+        assert!(key.starts_with(&format!("{}/avatars/", tenant_id)));
+        assert_eq!(stored.thumbnail_url, image_url(&thumbnail_key(key)));
+        assert_eq!(storage.keys(), vec![key.to_string(), thumbnail_key(key)]);
+
+        let image = image::load_from_memory(&storage.get(key).await.unwrap().unwrap()).unwrap();
+        assert_eq!((image.width(), image.height()), (512, 512));
+        let thumbnail = image::load_from_memory(&storage.get(&thumbnail_key(key)).await.unwrap().unwrap()).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (64, 64));
+    }
+
+    #[test]
+    fn test_logos_keep_their_shape() {
+        let logo = process_image(ImageKind::CompanyLogo, &png(2000, 500)).unwrap();
+        assert_eq!((logo.width, logo.height), (1024, 256));
+        let thumbnail = image::load_from_memory(&logo.thumbnail).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (200, 50));
+
+        // Small images aren't scaled up
+        let small = process_image(ImageKind::CompanyLogo, &png(300, 120)).unwrap();
+        assert_eq!((small.width, small.height), (300, 120));
+    }
+}
@@ -0,0 +1,21 @@
+//! Media Module
+//!
+//! Image uploads for user and contact avatars and company logos. Uploads
+//! are checked, resized and re-encoded before they go to the file storage
+//! backend, and the resulting URL is saved on the record.
+
+mod models;
+#[cfg(feature = "server")]
+mod images;
+#[cfg(feature = "server")]
+mod service;
+#[cfg(feature = "server")]
+mod routes;
+
+pub use models::*;
+#[cfg(feature = "server")]
+pub use images::{process_image, store_image, ProcessedImage};
+#[cfg(feature = "server")]
+pub use service::MediaService;
+#[cfg(feature = "server")]
+pub use routes::media_routes;
@@ -0,0 +1,101 @@
+//! Media models
+
+use serde::{Deserialize, Serialize};
+
+/// Where stored images are served from; the storage key follows it
+pub const IMAGE_PATH: &str = "/api/v1/media/images";
+
+/// Largest image upload accepted
+pub const MAX_IMAGE_BYTES: usize = 5 * 1024 * 1024;
+
+/// Longest side of an image upload, checked before it is decoded
+pub const MAX_IMAGE_DIMENSION: u32 = 4096;
+
+/// What an image is for, which decides how it is sized
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageKind {
+    UserAvatar,
+    ContactAvatar,
+    CompanyLogo,
+}
+
+impl ImageKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::UserAvatar => "user_avatar",
+            Self::ContactAvatar => "contact_avatar",
+            Self::CompanyLogo => "company_logo",
+        }
+    }
+
+    /// Storage folder under the tenant's
+    pub fn folder(&self) -> &'static str {
+        match self {
+            Self::UserAvatar | Self::ContactAvatar => "avatars",
+            Self::CompanyLogo => "logos",
+        }
+    }
+
+    /// Avatars are cropped square; logos keep their shape
+    pub fn is_square(&self) -> bool {
+        !matches!(self, Self::CompanyLogo)
+    }
+
+    /// Box the stored image is scaled down to fit
+    pub fn max_size(&self) -> (u32, u32) {
+        match self {
+            Self::UserAvatar | Self::ContactAvatar => (512, 512),
+            Self::CompanyLogo => (1024, 512),
+        }
+    }
+
+    /// Box the thumbnail is scaled to fit
+    pub fn thumbnail_size(&self) -> (u32, u32) {
+        match self {
+            Self::UserAvatar | Self::ContactAvatar => (64, 64),
+            Self::CompanyLogo => (200, 100),
+        }
+    }
+}
+
+/// An uploaded image after processing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StoredImage {
+    pub url: String,
+    pub thumbnail_url: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// URL an image stored under `key` is served at
+pub fn image_url(key: &str) -> String {
+    format!("{}/{}", IMAGE_PATH, key)
+}
+
+/// Storage key of an image URL, or `None` for URLs set some other way
+pub fn image_key(url: &str) -> Option<&str> {
+    url.strip_prefix(IMAGE_PATH)?.strip_prefix('/')
+}
+
+/// Key of the thumbnail stored beside an image
+pub fn thumbnail_key(key: &str) -> String {
+    match key.strip_suffix(".png") {
+        Some(stem) => format!("{}_thumb.png", stem),
+        None => format!("{}_thumb", key),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_urls_round_trip() {
+        let key = "0b6c/avatars/4f1e.png";
+        assert_eq!(image_url(key), "/api/v1/media/images/0b6c/avatars/4f1e.png");
+        assert_eq!(image_key(&image_url(key)), Some(key));
+        assert_eq!(image_key("https://gravatar.example/avatar/4f1e"), None);
+        assert_eq!(thumbnail_key(key), "0b6c/avatars/4f1e_thumb.png");
+    }
+}
@@ -0,0 +1,144 @@
+//! Media API routes
+
+use axum::{
+    extract::{DefaultBodyLimit, Multipart, Path, State},
+    http::header,
+    response::IntoResponse,
+    routing::{get, put},
+    Json, Router,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::{ImageKind, MediaService, StoredImage, MAX_IMAGE_BYTES};
+use crate::modules::auth::RequireAuth;
+use crate::utils::error::{AppError, AppResult};
+
+#[derive(Clone)]
+pub struct MediaRouterState {
+    pub media_service: Arc<MediaService>,
+}
+
+/// Create the media router
+pub fn media_routes(media_service: MediaService) -> Router {
+    let state = MediaRouterState {
+        media_service: Arc::new(media_service),
+    };
+
+    Router::new()
+        .route("/me/avatar", put(upload_my_avatar).delete(remove_my_avatar))
+        .route("/contacts/:contact_id/avatar", put(upload_contact_avatar).delete(remove_contact_avatar))
+        .route("/companies/:company_id/logo", put(upload_company_logo).delete(remove_company_logo))
+        // Public so the URLs work in <img> tags; keys are unguessable
+        .route("/images/:tenant_id/:folder/:file_name", get(get_image))
+        .layer(DefaultBodyLimit::max(MAX_IMAGE_BYTES + 64 * 1024))
+        .with_state(state)
+}
+
+/// The `file` part of a multipart upload
+async fn image_upload(mut multipart: Multipart) -> AppResult<Vec<u8>> {
+    let bad_part = |e: axum::extract::multipart::MultipartError| AppError::BadRequest(e.to_string());
+
+    while let Some(field) = multipart.next_field().await.map_err(bad_part)? {
+        if field.name() == Some("file") {
+            return Ok(field.bytes().await.map_err(bad_part)?.to_vec());
+        }
+    }
+
+    Err(AppError::validation_field("file", "An image is required"))
+}
+
+async fn upload_my_avatar(
+    State(state): State<MediaRouterState>,
+    RequireAuth(user): RequireAuth,
+    multipart: Multipart,
+) -> AppResult<Json<StoredImage>> {
+    let content = image_upload(multipart).await?;
+    let image = state
+        .media_service
+        .upload_image(user.tenant_id, ImageKind::UserAvatar, user.id, &content)
+        .await?;
+
+    Ok(Json(image))
+}
+
+async fn remove_my_avatar(State(state): State<MediaRouterState>, RequireAuth(user): RequireAuth) -> AppResult<()> {
+    state
+        .media_service
+        .remove_image(user.tenant_id, ImageKind::UserAvatar, user.id)
+        .await?;
+    Ok(())
+}
+
+async fn upload_contact_avatar(
+    State(state): State<MediaRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(contact_id): Path<Uuid>,
+    multipart: Multipart,
+) -> AppResult<Json<StoredImage>> {
+    let content = image_upload(multipart).await?;
+    let image = state
+        .media_service
+        .upload_image(user.tenant_id, ImageKind::ContactAvatar, contact_id, &content)
+        .await?;
+
+    Ok(Json(image))
+}
+
+async fn remove_contact_avatar(
+    State(state): State<MediaRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(contact_id): Path<Uuid>,
+) -> AppResult<()> {
+    state
+        .media_service
+        .remove_image(user.tenant_id, ImageKind::ContactAvatar, contact_id)
+        .await?;
+    Ok(())
+}
+
+async fn upload_company_logo(
+    State(state): State<MediaRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(company_id): Path<Uuid>,
+    multipart: Multipart,
+) -> AppResult<Json<StoredImage>> {
+    let content = image_upload(multipart).await?;
+    let image = state
+        .media_service
+        .upload_image(user.tenant_id, ImageKind::CompanyLogo, company_id, &content)
+        .await?;
+
+    Ok(Json(image))
+}
+
+async fn remove_company_logo(
+    State(state): State<MediaRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(company_id): Path<Uuid>,
+) -> AppResult<()> {
+    state
+        .media_service
+        .remove_image(user.tenant_id, ImageKind::CompanyLogo, company_id)
+        .await?;
+    Ok(())
+}
+
+async fn get_image(
+    State(state): State<MediaRouterState>,
+    Path((tenant_id, folder, file_name)): Path<(Uuid, String, String)>,
+) -> AppResult<impl IntoResponse> {
+    let content = state
+        .media_service
+        .get_image(&format!("{}/{}/{}", tenant_id, folder, file_name))
+        .await?;
+
+    // A new upload gets a new key, so a key's content never changes
+    Ok((
+        [
+            (header::CONTENT_TYPE, "image/png"),
+            (header::CACHE_CONTROL, "public, max-age=31536000, immutable"),
+        ],
+        content,
+    ))
+}
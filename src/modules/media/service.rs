@@ -0,0 +1,109 @@
+//! Media service implementation
+
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::storage::{check_key, FileStorage, LocalStorage};
+
+use super::images::store_image;
+use super::models::*;
+
+/// Image uploads for avatars and logos
+#[derive(Clone)]
+pub struct MediaService<S = LocalStorage> {
+    db: Database,
+    storage: S,
+}
+
+impl MediaService {
+    pub fn new(db: Database) -> Self {
+        Self::with_storage(db, LocalStorage::from_env())
+    }
+}
+
+impl<S: FileStorage> MediaService<S> {
+    pub fn with_storage(db: Database, storage: S) -> Self {
+        Self { db, storage }
+    }
+
+    /// Store `content` as the image of the record `kind` belongs to
+    pub async fn upload_image(
+        &self,
+        tenant_id: Uuid,
+        kind: ImageKind,
+        owner_id: Uuid,
+        content: &[u8],
+    ) -> AppResult<StoredImage> {
+        self.replace_image(tenant_id, kind, owner_id, Some(content))
+            .await?
+            .ok_or_else(|| AppError::internal("Uploaded image was not stored"))
+    }
+
+    pub async fn remove_image(&self, tenant_id: Uuid, kind: ImageKind, owner_id: Uuid) -> AppResult<()> {
+        self.replace_image(tenant_id, kind, owner_id, None).await?;
+        Ok(())
+    }
+
+    /// Point the record at a newly stored image, or at none, and remove the
+    /// image it pointed at before
+    async fn replace_image(
+        &self,
+        tenant_id: Uuid,
+        kind: ImageKind,
+        owner_id: Uuid,
+        content: Option<&[u8]>,
+    ) -> AppResult<Option<StoredImage>> {
+        let (table, column, owner) = match kind {
+            ImageKind::UserAvatar => ("users", "avatar_url", "User"),
+            ImageKind::ContactAvatar => ("contacts", "avatar_url", "Contact"),
+            ImageKind::CompanyLogo => ("companies", "logo_url", "Company"),
+        };
+
+        let previous: Option<String> = sqlx::query_scalar::<_, Option<String>>(&format!(
+            "SELECT {} FROM {} WHERE tenant_id = $1 AND id = $2",
+            column, table
+        ))
+        .bind(tenant_id)
+        .bind(owner_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound(owner.to_string()))?;
+
+        let stored = match content {
+            Some(content) => Some(store_image(&self.storage, tenant_id, kind, content).await?),
+            None => None,
+        };
+
+        sqlx::query(&format!(
+            "UPDATE {} SET {} = $1, updated_at = NOW() WHERE tenant_id = $2 AND id = $3",
+            table, column
+        ))
+        .bind(stored.as_ref().map(|image| &image.url))
+        .bind(tenant_id)
+        .bind(owner_id)
+        .execute(self.db.pool())
+        .await?;
+
+        // The record no longer points at the old files, so failing to remove
+        // them only leaves them orphaned
+        if let Some(key) = previous.as_deref().and_then(image_key) {
+            for key in [key.to_string(), thumbnail_key(key)] {
+                if let Err(e) = self.storage.delete(&key).await {
+                    tracing::warn!("Failed to remove replaced image {}: {}", key, e);
+                }
+            }
+        }
+
+        Ok(stored)
+    }
+
+    /// A stored image's bytes, for serving its URL
+    pub async fn get_image(&self, key: &str) -> AppResult<Vec<u8>> {
+        check_key(key).map_err(|_| AppError::NotFound("Image".to_string()))?;
+        self.storage
+            .get(key)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Image".to_string()))
+    }
+}
@@ -8,6 +8,7 @@ pub mod tenants;
 pub mod contacts;
 pub mod tickets;
 pub mod time_tracking;
+pub mod approvals;
 pub mod projects;
 pub mod calendar;
 pub mod contracts;
@@ -19,5 +20,13 @@ pub mod notifications;
 pub mod rmm;
 pub mod portal;
 pub mod reports;
+pub mod dashboard;
+pub mod saved_views;
 pub mod settings;
 pub mod audit;
+pub mod webhooks;
+pub mod jobs;
+pub mod sequences;
+pub mod retention;
+pub mod media;
+pub mod shared_templates;
@@ -1 +1,17 @@
-//! notifications module placeholder
+//! Notifications Module
+//!
+//! Multi-channel notification delivery and history, tenant-editable
+//! templates, the suppression list with bounce and complaint handling, and
+//! optional hourly or daily digests, and per-user quiet hours.
+
+mod models;
+#[cfg(feature = "server")]
+mod service;
+#[cfg(feature = "server")]
+mod routes;
+
+pub use models::*;
+#[cfg(feature = "server")]
+pub use service::NotificationService;
+#[cfg(feature = "server")]
+pub use routes::{email_event_routes, notification_routes, unsubscribe_routes};
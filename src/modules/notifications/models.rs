@@ -0,0 +1,1378 @@
+//! Notification models and types
+
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::i18n::{self, LocaleChain};
+use crate::utils::timezone::TenantTimezone;
+
+// ============================================================================
+// CHANNELS
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationChannel {
+    #[default]
+    Email,
+    Sms,
+    Slack,
+    Teams,
+    Discord,
+    GoogleChat,
+    Mattermost,
+    InApp,
+}
+
+impl NotificationChannel {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "email" => Some(Self::Email),
+            "sms" => Some(Self::Sms),
+            "slack" => Some(Self::Slack),
+            "teams" => Some(Self::Teams),
+            "discord" => Some(Self::Discord),
+            "google_chat" => Some(Self::GoogleChat),
+            "mattermost" => Some(Self::Mattermost),
+            "in_app" => Some(Self::InApp),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Email => "email",
+            Self::Sms => "sms",
+            Self::Slack => "slack",
+            Self::Teams => "teams",
+            Self::Discord => "discord",
+            Self::GoogleChat => "google_chat",
+            Self::Mattermost => "mattermost",
+            Self::InApp => "in_app",
+        }
+    }
+}
+
+// ============================================================================
+// STATUS
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationStatus {
+    #[default]
+    Pending,
+    Sent,
+    Delivered,
+    Failed,
+    /// Not sent: the recipient is on the suppression list
+    Suppressed,
+}
+
+impl NotificationStatus {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "pending" => Some(Self::Pending),
+            "sent" => Some(Self::Sent),
+            "delivered" => Some(Self::Delivered),
+            "failed" => Some(Self::Failed),
+            "suppressed" => Some(Self::Suppressed),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Sent => "sent",
+            Self::Delivered => "delivered",
+            Self::Failed => "failed",
+            Self::Suppressed => "suppressed",
+        }
+    }
+}
+
+// ============================================================================
+// SUPPRESSIONS
+// ============================================================================
+
+/// What a message is for, which decides the suppressions it must respect
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageClass {
+    /// Ticket updates, surveys, digests and the like
+    #[default]
+    Standard,
+    /// Messages the recipient asked for and can't do without, such as a
+    /// password reset. Sent despite an unsubscribe.
+    Transactional,
+}
+
+/// Why an address is on the suppression list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SuppressionReason {
+    #[default]
+    Unsubscribed,
+    /// Reported as spam
+    Complaint,
+    /// The address doesn't exist; nothing can be delivered to it
+    HardBounce,
+    /// Added by an admin
+    Manual,
+}
+
+impl SuppressionReason {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "unsubscribed" => Some(Self::Unsubscribed),
+            "complaint" => Some(Self::Complaint),
+            "hard_bounce" => Some(Self::HardBounce),
+            "manual" => Some(Self::Manual),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Unsubscribed => "unsubscribed",
+            Self::Complaint => "complaint",
+            Self::HardBounce => "hard_bounce",
+            Self::Manual => "manual",
+        }
+    }
+}
+
+/// An address that must not be sent to on some channels
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Suppression {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    /// Email address or phone number, normalized
+    pub address: String,
+    pub channels: Vec<NotificationChannel>,
+    pub reason: SuppressionReason,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Suppression {
+    /// Whether a message of `class` on `channel` must be skipped. Only a hard
+    /// bounce stops transactional messages.
+    pub fn blocks(&self, channel: NotificationChannel, class: MessageClass) -> bool {
+        self.channels.contains(&channel)
+            && (class == MessageClass::Standard || self.reason == SuppressionReason::HardBounce)
+    }
+}
+
+/// Suppressions are matched on the trimmed, lowercased address
+pub fn normalize_address(address: &str) -> String {
+    address.trim().to_lowercase()
+}
+
+/// Suppress an address by hand
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CreateSuppressionRequest {
+    #[validate(length(min = 3, max = 255))]
+    pub address: String,
+    #[serde(default = "default_suppressed_channels")]
+    #[validate(length(min = 1))]
+    pub channels: Vec<NotificationChannel>,
+    #[serde(default = "manual_reason")]
+    pub reason: SuppressionReason,
+}
+
+fn default_suppressed_channels() -> Vec<NotificationChannel> {
+    vec![NotificationChannel::Email]
+}
+
+fn manual_reason() -> SuppressionReason {
+    SuppressionReason::Manual
+}
+
+/// Unsubscribe footer added to standard email
+pub fn unsubscribe_footer(body: &str, url: &str) -> String {
+    format!("{}\n\n--\nTo stop receiving these emails, unsubscribe: {}", body, url)
+}
+
+// ============================================================================
+// BOUNCES
+// ============================================================================
+
+/// Soft bounces of one message retried before giving up on it
+pub const SOFT_BOUNCE_RETRY_LIMIT: i64 = 3;
+
+/// Job that sends a soft-bounced email again
+pub const RETRY_EMAIL_JOB: &str = "notifications.retry_email";
+
+/// A delivery problem reported by the email provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailEventKind {
+    /// The address doesn't exist or the domain rejects it for good
+    HardBounce,
+    /// A temporary failure such as a full mailbox
+    SoftBounce,
+    /// The recipient reported the message as spam
+    Complaint,
+}
+
+impl EmailEventKind {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "hard_bounce" => Some(Self::HardBounce),
+            "soft_bounce" => Some(Self::SoftBounce),
+            "complaint" => Some(Self::Complaint),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::HardBounce => "hard_bounce",
+            Self::SoftBounce => "soft_bounce",
+            Self::Complaint => "complaint",
+        }
+    }
+}
+
+/// One event posted to `/email/events`. The aliases accept the flat event
+/// payloads of SendGrid, Postmark and Mailgun as they are sent.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EmailProviderEvent {
+    /// `bounce`, `complaint`, `spamreport`, `failed`, ...
+    #[serde(alias = "event_type", alias = "RecordType", alias = "notificationType")]
+    pub event: String,
+    #[serde(alias = "recipient", alias = "Email", alias = "address")]
+    pub email: String,
+    /// `hard`/`soft`, `permanent`/`temporary` or `HardBounce`/`SoftBounce`
+    #[serde(default, alias = "type", alias = "Type", alias = "severity", alias = "bounceType")]
+    pub bounce_type: Option<String>,
+    #[serde(default, alias = "Description", alias = "diagnostic")]
+    pub reason: Option<String>,
+    #[serde(default, alias = "sg_event_id")]
+    pub provider_event_id: Option<String>,
+    #[serde(default, alias = "BouncedAt")]
+    pub occurred_at: Option<DateTime<Utc>>,
+}
+
+impl EmailProviderEvent {
+    /// What happened, or `None` for events we don't act on (deliveries,
+    /// opens, clicks)
+    pub fn kind(&self) -> Option<EmailEventKind> {
+        let event = self.event.to_lowercase();
+        if event.contains("complain") || event.contains("spam") {
+            return Some(EmailEventKind::Complaint);
+        }
+        if !(event.contains("bounce") || event == "failed" || event == "dropped") {
+            return None;
+        }
+
+        // A bounce that doesn't say otherwise is permanent
+        let soft = self.bounce_type.as_deref().is_some_and(|bounce_type| {
+            matches!(
+                bounce_type.to_lowercase().as_str(),
+                "soft" | "softbounce" | "soft_bounce" | "temporary" | "transient" | "blocked"
+            )
+        });
+        Some(if soft { EmailEventKind::SoftBounce } else { EmailEventKind::HardBounce })
+    }
+}
+
+/// Body of `/email/events`: providers post one event or a batch
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum EmailEventBatch {
+    Many(Vec<EmailProviderEvent>),
+    One(EmailProviderEvent),
+}
+
+impl EmailEventBatch {
+    pub fn into_events(self) -> Vec<EmailProviderEvent> {
+        match self {
+            Self::Many(events) => events,
+            Self::One(event) => vec![event],
+        }
+    }
+}
+
+/// Query string of `/email/events`. The secret goes in the webhook URL,
+/// which every provider supports.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EmailEventsQuery {
+    pub secret: Option<String>,
+}
+
+/// A bounce or complaint recorded against an address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailEvent {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub address: String,
+    pub kind: EmailEventKind,
+    /// The message that bounced, when it could be matched
+    pub notification_id: Option<Uuid>,
+    pub reason: Option<String>,
+    pub provider_event_id: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// What to do about a bounced or complained-about message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BounceAction {
+    /// Stop sending to the address
+    Suppress(SuppressionReason),
+    /// Send the message again at this time
+    Retry(DateTime<Utc>),
+    /// The message keeps soft bouncing; mark it failed. The address is left
+    /// alone, the next message may get through.
+    GiveUp,
+}
+
+impl BounceAction {
+    /// The action for an event, given how many times the message has now
+    /// soft bounced including this one. Retries back off from 15 minutes.
+    pub fn for_event(kind: EmailEventKind, soft_bounces: i64, now: DateTime<Utc>) -> Self {
+        match kind {
+            EmailEventKind::HardBounce => Self::Suppress(SuppressionReason::HardBounce),
+            EmailEventKind::Complaint => Self::Suppress(SuppressionReason::Complaint),
+            EmailEventKind::SoftBounce if soft_bounces > SOFT_BOUNCE_RETRY_LIMIT => Self::GiveUp,
+            EmailEventKind::SoftBounce => {
+                let doublings = soft_bounces.clamp(1, SOFT_BOUNCE_RETRY_LIMIT) as u32 - 1;
+                Self::Retry(now + Duration::minutes(15 * 2_i64.pow(doublings)))
+            }
+        }
+    }
+}
+
+// ============================================================================
+// NOTIFICATIONS
+// ============================================================================
+
+/// Notification history record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub channel_type: NotificationChannel,
+    pub template_id: Option<Uuid>,
+    pub recipient: Option<String>,
+    pub subject: Option<String>,
+    pub body: String,
+    pub status: NotificationStatus,
+    pub error_message: Option<String>,
+    pub sent_at: Option<DateTime<Utc>>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub read_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Email ready to be delivered
+#[derive(Debug, Clone)]
+pub struct OutgoingEmail {
+    pub to: String,
+    pub subject: String,
+    pub body_text: String,
+    pub body_html: Option<String>,
+    pub template_id: Option<Uuid>,
+    /// `From` header override, e.g. a queue's brand address; defaults to `SMTP_FROM_EMAIL`
+    pub from: Option<String>,
+    /// Threading headers so replies come back on the same conversation
+    pub thread: Option<EmailThreadHeaders>,
+    pub attachments: Vec<EmailAttachment>,
+}
+
+/// File sent along with an email
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailAttachment {
+    pub file_name: String,
+    pub content_type: String,
+    pub content: Vec<u8>,
+}
+
+/// `Message-ID`, `In-Reply-To` and `References` for an outbound message.
+/// Ids are stored in their angle-bracketed form, e.g. `<note.1234@example.com>`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmailThreadHeaders {
+    pub message_id: String,
+    pub in_reply_to: Option<String>,
+    pub references: Vec<String>,
+}
+
+// ============================================================================
+// DIGESTS
+// ============================================================================
+
+/// How often a user's non-critical notifications are batched into one email
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestFrequency {
+    /// No digest; every notification is sent as it happens
+    #[default]
+    Off,
+    Hourly,
+    Daily,
+}
+
+impl DigestFrequency {
+    pub fn period(&self) -> Option<Duration> {
+        match self {
+            Self::Off => None,
+            Self::Hourly => Some(Duration::hours(1)),
+            Self::Daily => Some(Duration::days(1)),
+        }
+    }
+
+    /// Whether a digest is due, given when its oldest event was queued. No
+    /// queued event waits longer than one period.
+    pub fn is_due(&self, oldest_queued_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        self.period().is_some_and(|period| now - oldest_queued_at >= period)
+    }
+}
+
+/// A user's notification settings, stored in `users.notification_preferences`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct NotificationPreferences {
+    #[serde(default)]
+    pub digest: DigestFrequency,
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHours>,
+}
+
+impl NotificationPreferences {
+    /// Preferences from the stored JSON; unknown or missing keys take defaults
+    pub fn from_stored(value: &serde_json::Value) -> Self {
+        serde_json::from_value(value.clone()).unwrap_or_default()
+    }
+
+    pub fn delivery_for(&self, event: &NotificationEvent) -> Delivery {
+        if event.is_critical || self.digest == DigestFrequency::Off {
+            Delivery::Immediate
+        } else {
+            Delivery::Digest
+        }
+    }
+
+    /// [`Self::delivery_for`] at `now`, holding what would be sent during the
+    /// user's quiet hours until they end. Digests wait for the quiet hours
+    /// themselves, so events bound for one are queued as usual.
+    pub fn delivery_at(&self, event: &NotificationEvent, now: DateTime<Utc>, timezone: TenantTimezone) -> Delivery {
+        let delivery = self.delivery_for(event);
+        let Some(quiet_hours) = self.quiet_hours else {
+            return delivery;
+        };
+        if delivery != Delivery::Immediate || (event.is_critical && quiet_hours.critical_breaks_through) {
+            return delivery;
+        }
+
+        match quiet_hours.ends_after(now, timezone) {
+            Some(until) => Delivery::Deferred { until },
+            None => delivery,
+        }
+    }
+
+    /// Whether the user is in their quiet hours at `now`
+    pub fn is_quiet_at(&self, now: DateTime<Utc>, timezone: TenantTimezone) -> bool {
+        self.quiet_hours
+            .is_some_and(|quiet_hours| quiet_hours.ends_after(now, timezone).is_some())
+    }
+}
+
+/// A daily window, in the user's time zone, during which notifications are
+/// held. A window whose end is before its start runs past midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    /// Critical notifications, such as SLA breaches, are still sent
+    #[serde(default)]
+    pub critical_breaks_through: bool,
+}
+
+impl QuietHours {
+    pub fn check(&self) -> AppResult<()> {
+        if self.start == self.end {
+            return Err(AppError::validation_field("end", "Quiet hours must end at a different time than they start"));
+        }
+        Ok(())
+    }
+
+    /// Whether a local time of day falls in the window; the start is
+    /// included, the end isn't
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+
+    /// When the window `now` falls in ends, or `None` outside quiet hours
+    pub fn ends_after(&self, now: DateTime<Utc>, timezone: TenantTimezone) -> Option<DateTime<Utc>> {
+        let local = timezone.local_datetime(now);
+        if !self.contains(local.time()) {
+            return None;
+        }
+
+        // Past the start of an overnight window the end is tomorrow
+        let end_date = if local.time() < self.end {
+            local.date()
+        } else {
+            local.date() + Duration::days(1)
+        };
+        Some(timezone.to_utc(end_date.and_time(self.end)))
+    }
+}
+
+/// Set how notifications reach the current user
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateNotificationPreferencesRequest {
+    pub digest: DigestFrequency,
+}
+
+/// Something a user is notified about, such as a ticket update
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationEvent {
+    /// e.g. `ticket.updated`
+    pub event_type: String,
+    pub subject: String,
+    pub body: String,
+    /// Sent immediately even to users on a digest, e.g. an SLA breach
+    #[serde(default)]
+    pub is_critical: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Delivery {
+    Immediate,
+    /// Queued for the user's next digest
+    Digest,
+    /// Held through the user's quiet hours, sent when they end
+    Deferred { until: DateTime<Utc> },
+}
+
+/// An event waiting for a user's next digest
+#[derive(Debug, Clone, Serialize)]
+pub struct DigestItem {
+    pub id: Uuid,
+    pub event_type: String,
+    pub subject: String,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Job that sends a notification held through quiet hours
+pub const DELIVER_HELD_NOTIFICATION_JOB: &str = "notifications.deliver_held";
+
+/// Render one summary email covering `items`, oldest first
+pub fn digest_email(to: &str, frequency: DigestFrequency, items: &[DigestItem]) -> OutgoingEmail {
+    let heading = match frequency {
+        DigestFrequency::Daily => "Your daily summary",
+        _ => "Your hourly summary",
+    };
+
+    let mut body_text = format!("{}: {} update(s)\n", heading, items.len());
+    for item in items {
+        body_text.push_str(&format!(
+            "\n[{}] {}\n{}\n",
+            item.created_at.format("%Y-%m-%d %H:%M UTC"),
+            item.subject,
+            item.body
+        ));
+    }
+
+    OutgoingEmail {
+        to: to.to_string(),
+        subject: format!("{} ({} updates)", heading, items.len()),
+        body_text,
+        body_html: None,
+        template_id: None,
+        from: None,
+        thread: None,
+        attachments: Vec::new(),
+    }
+}
+
+// ============================================================================
+// TEMPLATES
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationTemplate {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub event_type: String,
+    pub channel_type: NotificationChannel,
+    pub subject: Option<String>,
+    pub body_text: String,
+    pub body_html: Option<String>,
+    /// Language tag such as `en` or `fr-CA`
+    pub locale: String,
+    pub is_active: bool,
+}
+
+/// Template output after variables are substituted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RenderedTemplate {
+    pub subject: Option<String>,
+    pub body_text: String,
+    pub body_html: Option<String>,
+}
+
+impl NotificationTemplate {
+    /// Render subject and bodies against a JSON context such as `{"ticket": {...}}`
+    pub fn render(&self, context: &serde_json::Value) -> AppResult<RenderedTemplate> {
+        let env = minijinja::Environment::new();
+        let render = |source: &str| {
+            env.render_str(source, context)
+                .map_err(|e| AppError::internal(format!("Template '{}' failed to render: {}", self.name, e)))
+        };
+
+        Ok(RenderedTemplate {
+            subject: self.subject.as_deref().map(render).transpose()?,
+            body_text: render(&self.body_text)?,
+            body_html: self.body_html.as_deref().map(render).transpose()?,
+        })
+    }
+
+    /// The built-in template for an event in the first of the locales
+    /// translated, used when the tenant has none
+    pub fn default_for<'a>(
+        event_type: &str,
+        channel: NotificationChannel,
+        locale: impl Into<LocaleChain<'a>>,
+    ) -> Option<Self> {
+        let template_type = TemplateType::find(event_type)?;
+        let defaults = template_type.defaults(locale);
+        Some(Self {
+            id: Uuid::nil(),
+            tenant_id: Uuid::nil(),
+            name: format!("{} (default)", template_type.event_type),
+            event_type: template_type.event_type.to_string(),
+            channel_type: channel,
+            subject: Some(defaults.default_subject),
+            body_text: defaults.default_body,
+            body_html: None,
+            locale: defaults.locale.to_string(),
+            is_active: true,
+        })
+    }
+
+    /// Check the template parses and only uses its type's placeholders
+    pub fn check(&self) -> AppResult<()> {
+        let template_type = TemplateType::find(&self.event_type).ok_or_else(|| {
+            AppError::validation_field("event_type", format!("Unknown template type '{}'", self.event_type))
+        })?;
+
+        let env = minijinja::Environment::new();
+        let fields = [
+            ("subject", self.subject.as_deref()),
+            ("body_text", Some(self.body_text.as_str())),
+            ("body_html", self.body_html.as_deref()),
+        ];
+        for (field, source) in fields {
+            let Some(source) = source else {
+                continue;
+            };
+            let parsed = env
+                .template_from_str(source)
+                .map_err(|e| AppError::validation_field(field, format!("Template syntax error: {}", e)))?;
+
+            let mut unknown: Vec<String> = parsed
+                .undeclared_variables(true)
+                .into_iter()
+                .filter(|variable| !template_type.allows(variable))
+                .collect();
+            unknown.sort();
+            if !unknown.is_empty() {
+                return Err(AppError::validation_field(
+                    field,
+                    format!("Unknown placeholders for {}: {}", template_type.event_type, unknown.join(", ")),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Dry-run render against the type's sample data, with `sample_context`
+    /// laid over it. Nothing is sent; problems come back as validation errors.
+    pub fn preview(&self, sample_context: Option<&serde_json::Value>) -> AppResult<RenderedTemplate> {
+        self.check()?;
+
+        let mut context = TemplateType::find(&self.event_type)
+            .map(TemplateType::sample_context)
+            .unwrap_or_else(|| serde_json::json!({}));
+        if let Some(sample) = sample_context {
+            merge_context(&mut context, sample);
+        }
+
+        self.render(&context).map_err(|e| AppError::validation_field("body_text", e.to_string()))
+    }
+}
+
+/// Pick the template for `locale` among a tenant's templates for one event
+/// and channel: an exact match, then the same language, then the tenant's
+/// default locale, then English. `None` leaves it to the built-in default.
+pub fn pick_template<'a>(
+    candidates: Vec<NotificationTemplate>,
+    locale: impl Into<LocaleChain<'a>>,
+) -> Option<NotificationTemplate> {
+    let preferred = locale.into().candidates();
+    let rank = |template: &NotificationTemplate| {
+        preferred
+            .iter()
+            .position(|locale| template.locale.eq_ignore_ascii_case(locale))
+    };
+
+    candidates
+        .into_iter()
+        .filter(|template| template.is_active)
+        .filter_map(|template| rank(&template).map(|rank| (rank, template)))
+        .min_by_key(|(rank, _)| *rank)
+        .map(|(_, template)| template)
+}
+
+/// Recursively lay `overlay`'s objects over `base`
+pub(crate) fn merge_context(base: &mut serde_json::Value, overlay: &serde_json::Value) {
+    match (base, overlay) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                merge_context(base.entry(key.clone()).or_insert(serde_json::Value::Null), value);
+            }
+        }
+        (base, overlay) => *base = overlay.clone(),
+    }
+}
+
+/// Locale used when a template or recipient doesn't say
+pub const DEFAULT_TEMPLATE_LOCALE: &str = i18n::DEFAULT_LOCALE;
+
+/// Placeholders every template can use, filled from the tenant's branding
+const BRANDING_PLACEHOLDERS: &[(&str, &str)] = &[
+    ("branding.company_name", "Acme IT Services"),
+    ("branding.logo_url", "https://example.com/logo.png"),
+    ("branding.primary_color", "#3b82f6"),
+    ("branding.secondary_color", "#1e40af"),
+    ("branding.support_email", "support@example.com"),
+    ("branding.support_phone", "+1 555 0100"),
+    ("branding.email_footer", "Acme IT Services, 1 Main Street"),
+];
+
+/// An event that templates can be written for. The built-in subject and
+/// body are translated in the i18n catalogs under
+/// `notifications.<event_type>.subject` and `.body`.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct TemplateType {
+    pub event_type: &'static str,
+    /// Placeholders the event is rendered with, and the sample value each
+    /// takes in a preview
+    pub placeholders: &'static [(&'static str, &'static str)],
+}
+
+/// A template type with its built-in subject and body in one locale, as
+/// shown in the template editor
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateTypeDefaults {
+    pub event_type: &'static str,
+    pub placeholders: &'static [(&'static str, &'static str)],
+    /// The catalog the defaults came from
+    pub locale: &'static str,
+    pub default_subject: String,
+    pub default_body: String,
+}
+
+impl TemplateType {
+    pub const ALL: &'static [TemplateType] = &[
+        TemplateType {
+            event_type: "ticket.created",
+            placeholders: &[
+                ("ticket.number", "T000042"),
+                ("ticket.title", "Printer offline"),
+                ("ticket.priority", "High"),
+                ("ticket.company_name", "Contoso Ltd"),
+                ("ticket.description", "The second floor printer shows as offline."),
+                ("ticket.url", "https://psa.example.com/tickets/42"),
+            ],
+        },
+        TemplateType {
+            event_type: "ticket.acknowledged",
+            placeholders: &[
+                ("ticket.number", "T000042"),
+                ("ticket.title", "Printer offline"),
+                ("ticket.response_due", "2026-03-02 17:00 America/Chicago"),
+                ("ticket.url", "https://psa.example.com/portal/tickets/42"),
+            ],
+        },
+        TemplateType {
+            event_type: "ticket.assigned",
+            placeholders: &[
+                ("ticket.number", "T000042"),
+                ("ticket.title", "Printer offline"),
+                ("ticket.priority", "High"),
+                ("ticket.company_name", "Contoso Ltd"),
+                ("ticket.url", "https://psa.example.com/tickets/42"),
+                ("user.name", "Sam Tech"),
+            ],
+        },
+        TemplateType {
+            event_type: "ticket.updated",
+            placeholders: &[
+                ("ticket.number", "T000042"),
+                ("ticket.title", "Printer offline"),
+                ("ticket.status", "In Progress"),
+                ("ticket.last_note", "Replaced the network cable; testing now."),
+                ("ticket.url", "https://psa.example.com/tickets/42"),
+            ],
+        },
+        TemplateType {
+            event_type: "ticket.sla_warning",
+            placeholders: &[
+                ("ticket.number", "T000042"),
+                ("ticket.title", "Printer offline"),
+                ("ticket.priority", "High"),
+                ("ticket.sla_due_date", "2026-03-02 17:00"),
+                ("ticket.url", "https://psa.example.com/tickets/42"),
+            ],
+        },
+        TemplateType {
+            event_type: "ticket.sla_breach",
+            placeholders: &[
+                ("ticket.number", "T000042"),
+                ("ticket.title", "Printer offline"),
+                ("ticket.priority", "High"),
+                ("ticket.sla_due_date", "2026-03-02 17:00"),
+                ("ticket.url", "https://psa.example.com/tickets/42"),
+            ],
+        },
+        TemplateType {
+            event_type: "ticket.csat_survey",
+            placeholders: &[
+                ("ticket.number", "T000042"),
+                ("ticket.title", "Printer offline"),
+                ("survey.url", "https://psa.example.com/api/v1/csat/sample"),
+                ("survey.expires_at", "2026-03-16"),
+            ],
+        },
+        TemplateType {
+            event_type: "ticket.snooze_ended",
+            placeholders: &[
+                ("ticket.number", "T000042"),
+                ("ticket.title", "Printer offline"),
+                ("ticket.url", "https://psa.example.com/tickets/42"),
+            ],
+        },
+        TemplateType {
+            event_type: "appointment.booked",
+            placeholders: &[
+                ("appointment.start", "2026-03-03 14:00 UTC"),
+                ("appointment.end", "2026-03-03 15:00 UTC"),
+                ("appointment.technician", "Sam Tech"),
+                ("ticket.number", "T000042"),
+                ("ticket.title", "Printer offline"),
+                ("booking.reschedule_url", "https://psa.example.com/api/v1/booking/manage/sample#reschedule"),
+                ("booking.cancel_url", "https://psa.example.com/api/v1/booking/manage/sample#cancel"),
+            ],
+        },
+        TemplateType {
+            event_type: "invoice.sent",
+            placeholders: &[
+                ("invoice.number", "INV-1001"),
+                ("invoice.total", "1,250.00"),
+                ("invoice.due_date", "2026-03-31"),
+                ("invoice.url", "https://psa.example.com/invoices/1001"),
+                ("tenant.name", "Acme IT Services"),
+            ],
+        },
+        TemplateType {
+            event_type: "payment.received",
+            placeholders: &[
+                ("invoice.number", "INV-1001"),
+                ("invoice.url", "https://psa.example.com/invoices/1001"),
+                ("payment.amount", "1,250.00"),
+            ],
+        },
+    ];
+
+    pub fn find(event_type: &str) -> Option<&'static TemplateType> {
+        Self::ALL.iter().find(|template_type| template_type.event_type == event_type)
+    }
+
+    /// Built-in subject and body from the first catalog in `locale`'s chain
+    /// that translates them. Both come from the same catalog.
+    pub fn defaults<'a>(&self, locale: impl Into<LocaleChain<'a>>) -> TemplateTypeDefaults {
+        let subject_key = format!("notifications.{}.subject", self.event_type);
+        let body_key = format!("notifications.{}.body", self.event_type);
+        let locale = i18n::lookup(&subject_key, locale)
+            .map(|(locale, _)| locale)
+            .unwrap_or(DEFAULT_TEMPLATE_LOCALE);
+
+        TemplateTypeDefaults {
+            event_type: self.event_type,
+            placeholders: self.placeholders,
+            locale,
+            default_subject: i18n::t(&subject_key, locale, &[]),
+            default_body: i18n::t(&body_key, locale, &[]),
+        }
+    }
+
+    /// Whether a template of this type may reference `variable`. Parents such
+    /// as `ticket` in `{% if ticket %}` are allowed too.
+    pub fn allows(&self, variable: &str) -> bool {
+        self.placeholders
+            .iter()
+            .chain(BRANDING_PLACEHOLDERS)
+            .any(|(name, _)| {
+                *name == variable || name.strip_prefix(variable).is_some_and(|rest| rest.starts_with('.'))
+            })
+    }
+
+    /// The sample values as a render context, `{"ticket": {"number": ...}}`
+    pub fn sample_context(&self) -> serde_json::Value {
+        let mut context = serde_json::json!({});
+        for (name, sample) in self.placeholders.iter().chain(BRANDING_PLACEHOLDERS) {
+            let mut node = &mut context;
+            for part in name.split('.') {
+                node = node
+                    .as_object_mut()
+                    .map(|map| map.entry(part.to_string()).or_insert_with(|| serde_json::json!({})))
+                    .expect("sample context nodes are objects");
+            }
+            *node = serde_json::Value::String(sample.to_string());
+        }
+        context
+    }
+}
+
+/// Add or change a tenant template
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct SaveNotificationTemplateRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    pub event_type: String,
+    #[serde(default)]
+    pub channel_type: NotificationChannel,
+    #[validate(length(max = 255))]
+    pub subject: Option<String>,
+    #[validate(length(min = 1))]
+    pub body_text: String,
+    pub body_html: Option<String>,
+    #[serde(default = "default_template_locale")]
+    #[validate(length(min = 2, max = 10))]
+    pub locale: String,
+    #[serde(default = "default_true")]
+    pub is_active: bool,
+}
+
+impl SaveNotificationTemplateRequest {
+    pub fn template(&self, id: Uuid, tenant_id: Uuid) -> NotificationTemplate {
+        NotificationTemplate {
+            id,
+            tenant_id,
+            name: self.name.clone(),
+            event_type: self.event_type.clone(),
+            channel_type: self.channel_type,
+            subject: self.subject.clone(),
+            body_text: self.body_text.clone(),
+            body_html: self.body_html.clone(),
+            locale: self.locale.clone(),
+            is_active: self.is_active,
+        }
+    }
+}
+
+/// A template to dry-run; nothing is saved or sent
+#[derive(Debug, Clone, Deserialize)]
+pub struct PreviewTemplateRequest {
+    pub event_type: String,
+    #[serde(default)]
+    pub channel_type: NotificationChannel,
+    pub subject: Option<String>,
+    pub body_text: String,
+    pub body_html: Option<String>,
+    /// Values laid over the type's sample data
+    pub sample_context: Option<serde_json::Value>,
+}
+
+impl PreviewTemplateRequest {
+    pub fn template(&self) -> NotificationTemplate {
+        NotificationTemplate {
+            id: Uuid::nil(),
+            tenant_id: Uuid::nil(),
+            name: "Preview".to_string(),
+            event_type: self.event_type.clone(),
+            channel_type: self.channel_type,
+            subject: self.subject.clone(),
+            body_text: self.body_text.clone(),
+            body_html: self.body_html.clone(),
+            locale: DEFAULT_TEMPLATE_LOCALE.to_string(),
+            is_active: true,
+        }
+    }
+}
+
+fn default_template_locale() -> String {
+    DEFAULT_TEMPLATE_LOCALE.to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notification_channel_from_str() {
+        assert_eq!(NotificationChannel::from_str("email"), Some(NotificationChannel::Email));
+        assert_eq!(NotificationChannel::from_str("google_chat"), Some(NotificationChannel::GoogleChat));
+        assert_eq!(NotificationChannel::from_str("fax"), None);
+        assert_eq!(NotificationChannel::InApp.as_str(), "in_app");
+    }
+
+    #[test]
+    fn test_notification_status_from_str() {
+        assert_eq!(NotificationStatus::from_str("failed"), Some(NotificationStatus::Failed));
+        assert_eq!(NotificationStatus::from_str("unknown"), None);
+        assert_eq!(NotificationStatus::Sent.as_str(), "sent");
+        assert_eq!(NotificationStatus::from_str("suppressed"), Some(NotificationStatus::Suppressed));
+    }
+
+    fn suppression(reason: SuppressionReason) -> Suppression {
+        Suppression {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::nil(),
+            address: normalize_address("  Pat@Example.com "),
+            channels: vec![NotificationChannel::Email],
+            reason,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_suppressed_address_is_skipped() {
+        let unsubscribed = suppression(SuppressionReason::Unsubscribed);
+        assert_eq!(unsubscribed.address, "pat@example.com");
+        assert!(unsubscribed.blocks(NotificationChannel::Email, MessageClass::Standard));
+        // Only the channels listed are suppressed
+        assert!(!unsubscribed.blocks(NotificationChannel::Sms, MessageClass::Standard));
+        assert!(suppression(SuppressionReason::Complaint).blocks(NotificationChannel::Email, MessageClass::Standard));
+
+        let footer = unsubscribe_footer("Ticket #T000042 was updated.", "https://psa/unsubscribe/abc");
+        assert!(footer.starts_with("Ticket #T000042 was updated."));
+        assert!(footer.ends_with("https://psa/unsubscribe/abc"));
+    }
+
+    #[test]
+    fn test_password_reset_sends_despite_unsubscribe() {
+        let unsubscribed = suppression(SuppressionReason::Unsubscribed);
+        assert!(!unsubscribed.blocks(NotificationChannel::Email, MessageClass::Transactional));
+
+        // Nothing gets through to an address that bounced
+        let bounced = suppression(SuppressionReason::HardBounce);
+        assert!(bounced.blocks(NotificationChannel::Email, MessageClass::Transactional));
+        assert!(bounced.blocks(NotificationChannel::Email, MessageClass::Standard));
+    }
+
+    fn provider_event(payload: serde_json::Value) -> EmailProviderEvent {
+        serde_json::from_value(payload).unwrap()
+    }
+
+    #[test]
+    fn test_hard_bounce_suppresses_address() {
+        let now = Utc::now();
+
+        let event = provider_event(serde_json::json!({
+            "event": "bounce",
+            "email": "gone@example.com",
+            "bounce_type": "hard",
+            "reason": "550 5.1.1 User unknown"
+        }));
+        assert_eq!(event.kind(), Some(EmailEventKind::HardBounce));
+        assert_eq!(
+            BounceAction::for_event(EmailEventKind::HardBounce, 0, now),
+            BounceAction::Suppress(SuppressionReason::HardBounce)
+        );
+
+        // Postmark's field names, and a spam complaint
+        let postmark = provider_event(serde_json::json!({ "RecordType": "Bounce", "Email": "gone@example.com", "Type": "HardBounce" }));
+        assert_eq!(postmark.kind(), Some(EmailEventKind::HardBounce));
+        let complaint = provider_event(serde_json::json!({ "event": "spamreport", "email": "pat@example.com" }));
+        assert_eq!(complaint.kind(), Some(EmailEventKind::Complaint));
+        assert_eq!(
+            BounceAction::for_event(EmailEventKind::Complaint, 0, now),
+            BounceAction::Suppress(SuppressionReason::Complaint)
+        );
+
+        // Deliveries and opens are ignored
+        assert_eq!(provider_event(serde_json::json!({ "event": "delivered", "email": "pat@example.com" })).kind(), None);
+    }
+
+    #[test]
+    fn test_soft_bounce_is_retried() {
+        let now = Utc::now();
+
+        let event = provider_event(serde_json::json!({
+            "event": "failed",
+            "recipient": "full@example.com",
+            "severity": "temporary"
+        }));
+        assert_eq!(event.kind(), Some(EmailEventKind::SoftBounce));
+
+        // Retried with backoff, never suppressed
+        assert_eq!(
+            BounceAction::for_event(EmailEventKind::SoftBounce, 1, now),
+            BounceAction::Retry(now + Duration::minutes(15))
+        );
+        assert_eq!(
+            BounceAction::for_event(EmailEventKind::SoftBounce, 2, now),
+            BounceAction::Retry(now + Duration::minutes(30))
+        );
+        assert_eq!(
+            BounceAction::for_event(EmailEventKind::SoftBounce, SOFT_BOUNCE_RETRY_LIMIT + 1, now),
+            BounceAction::GiveUp
+        );
+    }
+
+    #[test]
+    fn test_template_render() {
+        let template = NotificationTemplate {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            name: "Ticket Created - Email".to_string(),
+            event_type: "ticket.created".to_string(),
+            channel_type: NotificationChannel::Email,
+            subject: Some("New Ticket #{{ticket.number}}: {{ticket.title}}".to_string()),
+            body_text: "View ticket: {{ticket.url}}".to_string(),
+            body_html: None,
+            locale: "en".to_string(),
+            is_active: true,
+        };
+
+        let rendered = template
+            .render(&serde_json::json!({
+                "ticket": {"number": "T000042", "title": "Printer offline", "url": "https://psa/t/42"}
+            }))
+            .unwrap();
+
+        assert_eq!(rendered.subject.as_deref(), Some("New Ticket #T000042: Printer offline"));
+        assert_eq!(rendered.body_text, "View ticket: https://psa/t/42");
+        assert_eq!(rendered.body_html, None);
+    }
+
+    fn stored(locale: &str, subject: &str) -> NotificationTemplate {
+        NotificationTemplate {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            name: "Ticket Assigned - Email".to_string(),
+            event_type: "ticket.assigned".to_string(),
+            channel_type: NotificationChannel::Email,
+            subject: Some(subject.to_string()),
+            body_text: "{{user.name}}, over to you: {{ticket.url}}".to_string(),
+            body_html: None,
+            locale: locale.to_string(),
+            is_active: true,
+        }
+    }
+
+    #[test]
+    fn test_stored_template_overrides_default() {
+        let fallback = NotificationTemplate::default_for("ticket.assigned", NotificationChannel::Email, "en").unwrap();
+        assert!(fallback.check().is_ok());
+        assert!(pick_template(Vec::new(), "en-US").is_none());
+
+        let english = stored("en", "Yours now: #{{ticket.number}}");
+        let french = stored("fr", "Pour vous : #{{ticket.number}}");
+        let candidates = vec![english.clone(), french.clone()];
+
+        // Exact locale, then the language, then the default locale
+        assert_eq!(pick_template(candidates.clone(), "fr").unwrap().id, french.id);
+        assert_eq!(pick_template(candidates.clone(), "fr-CA").unwrap().id, french.id);
+        assert_eq!(pick_template(candidates.clone(), "de-DE").unwrap().id, english.id);
+        assert!(pick_template(vec![french.clone()], "de-DE").is_none());
+
+        // A disabled template falls through to the built-in one
+        let mut disabled = english.clone();
+        disabled.is_active = false;
+        assert!(pick_template(vec![disabled], "en").is_none());
+
+        let context = serde_json::json!({ "ticket": { "number": "T000042" } });
+        let chosen = pick_template(candidates, "en-US").unwrap_or(fallback.clone());
+        assert_eq!(chosen.render(&context).unwrap().subject.as_deref(), Some("Yours now: #T000042"));
+        assert_eq!(
+            fallback.render(&context).unwrap().subject.as_deref(),
+            Some("Ticket #T000042 assigned to you: ")
+        );
+
+        // Built-in defaults are translated, falling back to the tenant's locale
+        let french = NotificationTemplate::default_for("ticket.assigned", NotificationChannel::Email, "fr-CA").unwrap();
+        assert_eq!(french.locale, "fr");
+        assert!(french.subject.unwrap().starts_with("Le ticket n° {{ticket.number}}"));
+        let chain = LocaleChain::new("de").with_tenant_default("es");
+        let spanish = NotificationTemplate::default_for("ticket.assigned", NotificationChannel::Email, &chain).unwrap();
+        assert_eq!(spanish.locale, "es");
+    }
+
+    #[test]
+    fn test_template_preview() {
+        let request = PreviewTemplateRequest {
+            event_type: "ticket.created".to_string(),
+            channel_type: NotificationChannel::Email,
+            subject: Some("[{{branding.company_name}}] #{{ticket.number}}".to_string()),
+            body_text: "{{ticket.title}} for {{ticket.company_name}}".to_string(),
+            body_html: None,
+            sample_context: Some(serde_json::json!({ "ticket": { "title": "VPN down" } })),
+        };
+
+        // Sample data fills every placeholder; the request's values win
+        let preview = request.template().preview(request.sample_context.as_ref()).unwrap();
+        assert_eq!(preview.subject.as_deref(), Some("[Acme IT Services] #T000042"));
+        assert_eq!(preview.body_text, "VPN down for Contoso Ltd");
+
+        // Placeholders outside the type's set are rejected
+        let mut typo = request.template();
+        typo.body_text = "{{ticket.titel}} {{invoice.total}}".to_string();
+        let err = typo.preview(None).unwrap_err();
+        assert!(err.to_string().contains("Validation failed"));
+        assert!(matches!(err, AppError::Validation { ref errors, .. }
+            if errors[0].message.contains("invoice.total, ticket.titel")));
+
+        let mut broken = request.template();
+        broken.body_text = "{{ ticket.title ".to_string();
+        assert!(broken.check().is_err());
+
+        let mut unknown = request.template();
+        unknown.event_type = "ticket.exploded".to_string();
+        assert!(unknown.check().is_err());
+
+        // Every built-in default passes its own checks, in every language
+        for template_type in TemplateType::ALL {
+            for locale in i18n::supported_locales() {
+                let fallback = NotificationTemplate::default_for(template_type.event_type, NotificationChannel::Email, locale).unwrap();
+                assert!(fallback.preview(None).is_ok(), "{} ({})", template_type.event_type, locale);
+            }
+        }
+    }
+
+    fn event(subject: &str, is_critical: bool) -> NotificationEvent {
+        NotificationEvent {
+            event_type: "ticket.updated".to_string(),
+            subject: subject.to_string(),
+            body: "Status changed to In Progress".to_string(),
+            is_critical,
+        }
+    }
+
+    #[test]
+    fn test_digest_batches_non_critical_events() {
+        let prefs = NotificationPreferences::from_stored(&serde_json::json!({ "digest": "hourly" }));
+        let start = Utc::now();
+
+        let updates = [event("T000041 updated", false), event("T000042 updated", false)];
+        assert!(updates.iter().all(|e| prefs.delivery_for(e) == Delivery::Digest));
+
+        // A breach can't wait for the digest
+        assert_eq!(prefs.delivery_for(&event("SLA breached on T000043", true)), Delivery::Immediate);
+
+        let queued: Vec<DigestItem> = updates
+            .iter()
+            .enumerate()
+            .map(|(i, e)| DigestItem {
+                id: Uuid::new_v4(),
+                event_type: e.event_type.clone(),
+                subject: e.subject.clone(),
+                body: e.body.clone(),
+                created_at: start + Duration::minutes(i as i64 * 10),
+            })
+            .collect();
+
+        assert!(!DigestFrequency::Hourly.is_due(queued[0].created_at, start + Duration::minutes(59)));
+        assert!(DigestFrequency::Hourly.is_due(queued[0].created_at, start + Duration::minutes(60)));
+
+        let email = digest_email("tech@example.com", DigestFrequency::Hourly, &queued);
+        assert_eq!(email.subject, "Your hourly summary (2 updates)");
+        assert!(email.body_text.contains("T000041 updated"));
+        assert!(email.body_text.contains("T000042 updated"));
+    }
+
+    #[test]
+    fn test_no_digest_sends_immediately() {
+        let prefs = NotificationPreferences::from_stored(&serde_json::json!({}));
+        assert_eq!(prefs.digest, DigestFrequency::Off);
+        assert_eq!(prefs.delivery_for(&event("T000041 updated", false)), Delivery::Immediate);
+        assert!(!DigestFrequency::Off.is_due(Utc::now() - Duration::days(30), Utc::now()));
+    }
+
+    #[test]
+    fn test_quiet_hours_across_midnight_defer_until_they_end() {
+        let chicago = TenantTimezone::parse("America/Chicago").unwrap();
+        let utc = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+        let prefs = NotificationPreferences::from_stored(&serde_json::json!({
+            "quiet_hours": { "start": "22:00:00", "end": "07:00:00" }
+        }));
+        let quiet_hours = prefs.quiet_hours.unwrap();
+        assert!(quiet_hours.check().is_ok());
+        let update = event("T000041 updated", false);
+
+        // 23:30 local, before midnight: held until 07:00 the next morning
+        assert_eq!(
+            prefs.delivery_at(&update, utc("2025-03-15T04:30:00Z"), chicago),
+            Delivery::Deferred { until: utc("2025-03-15T12:00:00Z") }
+        );
+        // 02:00 local, after midnight: held until 07:00 the same morning
+        assert_eq!(
+            prefs.delivery_at(&update, utc("2025-03-15T07:00:00Z"), chicago),
+            Delivery::Deferred { until: utc("2025-03-15T12:00:00Z") }
+        );
+
+        // 07:00 and 21:59 local are outside the window: sent now
+        assert_eq!(prefs.delivery_at(&update, utc("2025-03-15T12:00:00Z"), chicago), Delivery::Immediate);
+        assert_eq!(prefs.delivery_at(&update, utc("2025-03-15T02:59:00Z"), chicago), Delivery::Immediate);
+        // The same instant is 23:30 in UTC, inside the window there
+        assert!(prefs.is_quiet_at(utc("2025-03-15T23:30:00Z"), TenantTimezone::default()));
+        assert!(!prefs.is_quiet_at(utc("2025-03-15T23:30:00Z"), chicago));
+
+        // Critical events wait too, unless the user lets them through
+        let breach = event("SLA breached on T000043", true);
+        assert!(matches!(
+            prefs.delivery_at(&breach, utc("2025-03-15T07:00:00Z"), chicago),
+            Delivery::Deferred { .. }
+        ));
+        let mut urgent = prefs;
+        urgent.quiet_hours = Some(QuietHours { critical_breaks_through: true, ..quiet_hours });
+        assert_eq!(urgent.delivery_at(&breach, utc("2025-03-15T07:00:00Z"), chicago), Delivery::Immediate);
+
+        // Digest users keep queueing; the digest waits for morning instead
+        let mut digest = prefs;
+        digest.digest = DigestFrequency::Hourly;
+        assert_eq!(digest.delivery_at(&update, utc("2025-03-15T07:00:00Z"), chicago), Delivery::Digest);
+
+        let empty = QuietHours { end: quiet_hours.start, ..quiet_hours };
+        assert!(empty.check().is_err());
+    }
+
+    #[test]
+    fn test_same_day_quiet_hours() {
+        let lunch = QuietHours {
+            start: NaiveTime::from_hms_opt(12, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(13, 0, 0).unwrap(),
+            critical_breaks_through: false,
+        };
+        assert!(lunch.contains(NaiveTime::from_hms_opt(12, 30, 0).unwrap()));
+        assert!(!lunch.contains(NaiveTime::from_hms_opt(13, 0, 0).unwrap()));
+        assert!(!lunch.contains(NaiveTime::from_hms_opt(23, 0, 0).unwrap()));
+
+        let prefs = NotificationPreferences::from_stored(&serde_json::json!({}));
+        assert_eq!(prefs.quiet_hours, None);
+        assert!(!prefs.is_quiet_at(Utc::now(), TenantTimezone::default()));
+    }
+}
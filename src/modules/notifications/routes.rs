@@ -0,0 +1,279 @@
+//! Notification API routes
+
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::{Html, IntoResponse, Response},
+    routing::{delete, get, post, put},
+    Json, Router,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::{
+    CreateSuppressionRequest, EmailEventBatch, EmailEventsQuery, NotificationPreferences, NotificationService,
+    NotificationTemplate, PreviewTemplateRequest, QuietHours, RenderedTemplate, SaveNotificationTemplateRequest,
+    Suppression, TemplateType, TemplateTypeDefaults, UpdateNotificationPreferencesRequest,
+};
+use crate::api::tenant_resolution::CurrentTenant;
+use crate::modules::auth::{RequireAdmin, RequireAuth};
+use crate::utils::error::AppResult;
+use crate::utils::public_page;
+use crate::utils::validation::ValidatedJson;
+
+#[derive(Clone)]
+pub struct NotificationRouterState {
+    pub notification_service: Arc<NotificationService>,
+}
+
+/// Create the notifications router
+pub fn notification_routes(notification_service: NotificationService) -> Router {
+    let state = NotificationRouterState {
+        notification_service: Arc::new(notification_service),
+    };
+
+    Router::new()
+        .route("/preferences", get(get_preferences))
+        .route("/preferences", put(update_preferences))
+        .route("/preferences/quiet-hours", put(set_quiet_hours))
+        .route("/preferences/quiet-hours", delete(clear_quiet_hours))
+        .route("/templates", get(list_templates))
+        .route("/templates", post(create_template))
+        .route("/templates/types", get(list_template_types))
+        .route("/templates/preview", post(preview_template))
+        .route("/templates/:template_id", put(update_template))
+        .route("/templates/:template_id", delete(delete_template))
+        .route("/suppressions", get(list_suppressions))
+        .route("/suppressions", post(create_suppression))
+        .route("/suppressions/:suppression_id", delete(delete_suppression))
+        .with_state(state)
+}
+
+/// Create the public unsubscribe router; links carry their own token
+pub fn unsubscribe_routes(notification_service: NotificationService) -> Router {
+    let state = NotificationRouterState {
+        notification_service: Arc::new(notification_service),
+    };
+
+    // GET only shows a confirmation page; the POST from its form, or the
+    // one-click POST from mail clients, unsubscribes
+    Router::new()
+        .route("/:token", get(unsubscribe_page))
+        .route("/:token", post(unsubscribe))
+        .with_state(state)
+}
+
+/// Create the public router for email provider events; requests carry the
+/// shared secret
+pub fn email_event_routes(notification_service: NotificationService) -> Router {
+    let state = NotificationRouterState {
+        notification_service: Arc::new(notification_service),
+    };
+
+    Router::new()
+        .route("/events", post(receive_email_events))
+        .with_state(state)
+}
+
+async fn get_preferences(
+    State(state): State<NotificationRouterState>,
+    RequireAuth(user): RequireAuth,
+) -> AppResult<Json<NotificationPreferences>> {
+    let preferences = state
+        .notification_service
+        .get_preferences(user.tenant_id, user.id)
+        .await?;
+
+    Ok(Json(preferences))
+}
+
+async fn update_preferences(
+    State(state): State<NotificationRouterState>,
+    RequireAuth(user): RequireAuth,
+    Json(request): Json<UpdateNotificationPreferencesRequest>,
+) -> AppResult<Json<NotificationPreferences>> {
+    let preferences = state
+        .notification_service
+        .update_preferences(user.tenant_id, user.id, &request)
+        .await?;
+
+    Ok(Json(preferences))
+}
+
+async fn set_quiet_hours(
+    State(state): State<NotificationRouterState>,
+    RequireAuth(user): RequireAuth,
+    Json(request): Json<QuietHours>,
+) -> AppResult<Json<NotificationPreferences>> {
+    let preferences = state
+        .notification_service
+        .set_quiet_hours(user.tenant_id, user.id, &request)
+        .await?;
+
+    Ok(Json(preferences))
+}
+
+async fn clear_quiet_hours(
+    State(state): State<NotificationRouterState>,
+    RequireAuth(user): RequireAuth,
+) -> AppResult<Json<NotificationPreferences>> {
+    let preferences = state
+        .notification_service
+        .clear_quiet_hours(user.tenant_id, user.id)
+        .await?;
+
+    Ok(Json(preferences))
+}
+
+async fn list_templates(
+    State(state): State<NotificationRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+) -> AppResult<Json<Vec<NotificationTemplate>>> {
+    let templates = state.notification_service.list_templates(user.tenant_id).await?;
+    Ok(Json(templates))
+}
+
+/// Events templates can be written for, with their placeholders and defaults
+async fn list_template_types(RequireAdmin(user, _): RequireAdmin) -> Json<Vec<TemplateTypeDefaults>> {
+    Json(
+        TemplateType::ALL
+            .iter()
+            .map(|template_type| template_type.defaults(&user.locale))
+            .collect(),
+    )
+}
+
+async fn create_template(
+    State(state): State<NotificationRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    ValidatedJson(request): ValidatedJson<SaveNotificationTemplateRequest>,
+) -> AppResult<Json<NotificationTemplate>> {
+    let template = state
+        .notification_service
+        .create_template(user.tenant_id, &request)
+        .await?;
+
+    Ok(Json(template))
+}
+
+async fn update_template(
+    State(state): State<NotificationRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Path(template_id): Path<Uuid>,
+    ValidatedJson(request): ValidatedJson<SaveNotificationTemplateRequest>,
+) -> AppResult<Json<NotificationTemplate>> {
+    let template = state
+        .notification_service
+        .update_template(user.tenant_id, template_id, &request)
+        .await?;
+
+    Ok(Json(template))
+}
+
+async fn delete_template(
+    State(state): State<NotificationRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Path(template_id): Path<Uuid>,
+) -> AppResult<()> {
+    state
+        .notification_service
+        .delete_template(user.tenant_id, template_id)
+        .await
+}
+
+async fn preview_template(
+    State(state): State<NotificationRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Json(request): Json<PreviewTemplateRequest>,
+) -> AppResult<Json<RenderedTemplate>> {
+    let preview = state
+        .notification_service
+        .preview_template(user.tenant_id, &request.template(), request.sample_context.as_ref())
+        .await?;
+
+    Ok(Json(preview))
+}
+
+async fn list_suppressions(
+    State(state): State<NotificationRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+) -> AppResult<Json<Vec<Suppression>>> {
+    let suppressions = state.notification_service.list_suppressions(user.tenant_id).await?;
+    Ok(Json(suppressions))
+}
+
+async fn create_suppression(
+    State(state): State<NotificationRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    ValidatedJson(request): ValidatedJson<CreateSuppressionRequest>,
+) -> AppResult<Json<Suppression>> {
+    let suppression = state
+        .notification_service
+        .suppress(user.tenant_id, &request.address, &request.channels, request.reason)
+        .await?;
+
+    Ok(Json(suppression))
+}
+
+async fn delete_suppression(
+    State(state): State<NotificationRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Path(suppression_id): Path<Uuid>,
+) -> AppResult<()> {
+    state
+        .notification_service
+        .remove_suppression(user.tenant_id, suppression_id)
+        .await
+}
+
+/// Page the unsubscribe link opens. Showing it changes nothing, so link
+/// scanners can't unsubscribe the recipient.
+async fn unsubscribe_page(
+    State(state): State<NotificationRouterState>,
+    tenant: Option<CurrentTenant>,
+    Path(token): Path<String>,
+) -> AppResult<Html<String>> {
+    let tenant_id = tenant.as_ref().map(CurrentTenant::tenant_id);
+    let address = state.notification_service.unsubscribe_address(tenant_id, &token).await?;
+
+    let body = format!(
+        r#"<p>Stop sending emails to {}?</p>
+<form method="post"><button type="submit">Unsubscribe</button></form>"#,
+        public_page::escape(&address)
+    );
+    Ok(public_page::page("Unsubscribe", &body))
+}
+
+/// Unsubscribe from the confirmation page's form, or from a mail client's
+/// one-click POST
+async fn unsubscribe(
+    State(state): State<NotificationRouterState>,
+    tenant: Option<CurrentTenant>,
+    Path(token): Path<String>,
+    headers: HeaderMap,
+) -> AppResult<Response> {
+    let tenant_id = tenant.as_ref().map(CurrentTenant::tenant_id);
+    state.notification_service.unsubscribe(tenant_id, &token).await?;
+
+    if public_page::is_form(&headers) {
+        let page = public_page::page("Unsubscribed", "<p>You won't receive these emails any more.</p>");
+        return Ok(page.into_response());
+    }
+    Ok(().into_response())
+}
+
+async fn receive_email_events(
+    State(state): State<NotificationRouterState>,
+    Query(query): Query<EmailEventsQuery>,
+    Json(batch): Json<EmailEventBatch>,
+) -> AppResult<()> {
+    state
+        .notification_service
+        .verify_email_events_secret(query.secret.as_deref())?;
+
+    for event in batch.into_events() {
+        state.notification_service.record_email_event(&event).await?;
+    }
+
+    Ok(())
+}
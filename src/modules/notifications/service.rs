@@ -0,0 +1,1331 @@
+//! Notification service implementation
+
+use chrono::Utc;
+use lettre::message::header::{Header, HeaderName, HeaderValue};
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::modules::contacts::EmailDeliverability;
+use crate::modules::jobs::{Job, JobQueue, NewJob, PgJobQueue};
+use crate::modules::tenants::{ResolvedBranding, TenantService};
+use crate::utils::crypto::generate_token;
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::i18n::LocaleChain;
+use crate::utils::timezone::TenantTimezone;
+
+use super::models::*;
+
+const EMAIL_EVENT_COLUMNS: &str =
+    "id, tenant_id, address, event_type, notification_id, reason, provider_event_id, occurred_at, created_at";
+
+const SUPPRESSION_COLUMNS: &str = "id, tenant_id, address, channels, reason, created_at, updated_at";
+
+const TEMPLATE_COLUMNS: &str =
+    "id, tenant_id, name, event_type, channel_type, subject, body_text, body_html, locale, is_active";
+
+/// SMTP settings loaded from the environment
+#[derive(Debug, Clone)]
+struct EmailConfig {
+    smtp_host: String,
+    smtp_port: u16,
+    smtp_username: String,
+    smtp_password: String,
+    from_email: String,
+    from_name: String,
+}
+
+impl EmailConfig {
+    fn from_env() -> Option<Self> {
+        Some(Self {
+            smtp_host: std::env::var("SMTP_HOST").ok()?,
+            smtp_port: std::env::var("SMTP_PORT").ok()?.parse().ok()?,
+            smtp_username: std::env::var("SMTP_USERNAME").ok()?,
+            smtp_password: std::env::var("SMTP_PASSWORD").ok()?,
+            from_email: std::env::var("SMTP_FROM_EMAIL").ok()?,
+            from_name: std::env::var("SMTP_FROM_NAME").unwrap_or_else(|_| "PSA Platform".to_string()),
+        })
+    }
+}
+
+/// Notification delivery service
+#[derive(Clone)]
+pub struct NotificationService {
+    db: Database,
+    email: Option<EmailConfig>,
+    tenants: TenantService,
+    jobs: PgJobQueue,
+    /// Public URL unsubscribe links point at
+    base_url: String,
+    /// Shared secret the email provider sends with bounce and complaint events
+    email_events_secret: Option<String>,
+}
+
+impl NotificationService {
+    pub fn new(db: Database) -> Self {
+        Self {
+            tenants: TenantService::new(db.clone()),
+            jobs: PgJobQueue::new(db.clone()),
+            db,
+            email: EmailConfig::from_env(),
+            base_url: std::env::var("BASE_URL").unwrap_or_else(|_| "http://localhost:8080".to_string()),
+            email_events_secret: std::env::var("EMAIL_EVENTS_SECRET").ok().filter(|secret| !secret.is_empty()),
+        }
+    }
+
+    /// Branding for a tenant's outgoing email. Inject it into template
+    /// contexts and add its footer to bodies built without a template.
+    pub async fn branding(&self, tenant_id: Uuid) -> AppResult<ResolvedBranding> {
+        self.tenants.get_branding(tenant_id).await
+    }
+
+    /// Get the active template for an event and channel
+    pub async fn get_template(
+        &self,
+        tenant_id: Uuid,
+        event_type: &str,
+        channel: NotificationChannel,
+    ) -> AppResult<Option<NotificationTemplate>> {
+        let row = sqlx::query_as::<_, NotificationTemplateRow>(&format!(
+            r#"
+            SELECT {}
+            FROM notification_templates
+            WHERE tenant_id = $1 AND event_type = $2 AND channel_type = $3 AND is_active = TRUE
+            ORDER BY created_at
+            LIMIT 1
+            "#,
+            TEMPLATE_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(event_type)
+        .bind(channel.as_str())
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        Ok(row.map(Into::into))
+    }
+
+    /// Tenant's default locale, stored in `tenant_settings` as `general.locale`
+    async fn tenant_locale(&self, tenant_id: Uuid) -> AppResult<String> {
+        let value: Option<serde_json::Value> = sqlx::query_scalar(
+            "SELECT value FROM tenant_settings WHERE tenant_id = $1 AND category = 'general' AND key = 'locale'",
+        )
+        .bind(tenant_id)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        Ok(value
+            .as_ref()
+            .and_then(|value| value.as_str())
+            .unwrap_or(DEFAULT_TEMPLATE_LOCALE)
+            .to_string())
+    }
+
+    /// Render the tenant's template for an event in `locale`, or the built-in
+    /// default when they haven't written one. Either falls back to the
+    /// tenant's default locale, then English. Branding is added to `context`.
+    pub async fn render_template(
+        &self,
+        tenant_id: Uuid,
+        event_type: &str,
+        channel: NotificationChannel,
+        locale: &str,
+        context: &serde_json::Value,
+    ) -> AppResult<(NotificationTemplate, RenderedTemplate)> {
+        let rows = sqlx::query_as::<_, NotificationTemplateRow>(&format!(
+            r#"
+            SELECT {}
+            FROM notification_templates
+            WHERE tenant_id = $1 AND event_type = $2 AND channel_type = $3 AND is_active = TRUE
+            ORDER BY created_at
+            "#,
+            TEMPLATE_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(event_type)
+        .bind(channel.as_str())
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let tenant_locale = self.tenant_locale(tenant_id).await?;
+        let locales = LocaleChain::new(locale).with_tenant_default(&tenant_locale);
+        let builtin = NotificationTemplate::default_for(event_type, channel, &locales);
+        let mut template = pick_template(rows.into_iter().map(Into::into).collect(), &locales)
+            .or_else(|| builtin.clone())
+            .ok_or_else(|| AppError::not_found(format!("Template for {}", event_type)))?;
+        if template.subject.is_none() {
+            template.subject = builtin.and_then(|builtin| builtin.subject);
+        }
+
+        let mut context = context.clone();
+        self.branding(tenant_id).await?.inject(&mut context);
+
+        let rendered = template.render(&context)?;
+        Ok((template, rendered))
+    }
+
+    /// Email for a rendered template. Built-in templates get the tenant's
+    /// footer; tenants' own templates add it themselves if they want it.
+    pub async fn templated_email(
+        &self,
+        tenant_id: Uuid,
+        to: String,
+        template: &NotificationTemplate,
+        rendered: RenderedTemplate,
+    ) -> AppResult<OutgoingEmail> {
+        let builtin = template.id.is_nil();
+        let body_text = match builtin {
+            true => self.branding(tenant_id).await?.with_footer(rendered.body_text),
+            false => rendered.body_text,
+        };
+
+        Ok(OutgoingEmail {
+            to,
+            subject: rendered.subject.unwrap_or_default(),
+            body_text,
+            body_html: rendered.body_html,
+            template_id: (!builtin).then_some(template.id),
+            from: None,
+            thread: None,
+            attachments: Vec::new(),
+        })
+    }
+
+    /// Dry-run a draft template against sample data and the tenant's branding
+    pub async fn preview_template(
+        &self,
+        tenant_id: Uuid,
+        template: &NotificationTemplate,
+        sample_context: Option<&serde_json::Value>,
+    ) -> AppResult<RenderedTemplate> {
+        let mut sample = serde_json::json!({});
+        self.branding(tenant_id).await?.inject(&mut sample);
+        if let Some(given) = sample_context {
+            merge_context(&mut sample, given);
+        }
+
+        template.preview(Some(&sample))
+    }
+
+    pub async fn list_templates(&self, tenant_id: Uuid) -> AppResult<Vec<NotificationTemplate>> {
+        let rows = sqlx::query_as::<_, NotificationTemplateRow>(&format!(
+            "SELECT {} FROM notification_templates WHERE tenant_id = $1 ORDER BY event_type, channel_type, locale",
+            TEMPLATE_COLUMNS
+        ))
+        .bind(tenant_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn create_template(
+        &self,
+        tenant_id: Uuid,
+        request: &SaveNotificationTemplateRequest,
+    ) -> AppResult<NotificationTemplate> {
+        request.template(Uuid::nil(), tenant_id).check()?;
+
+        let row = sqlx::query_as::<_, NotificationTemplateRow>(&format!(
+            r#"
+            INSERT INTO notification_templates
+                (tenant_id, name, event_type, channel_type, subject, body_text, body_html, locale, is_active)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING {}
+            "#,
+            TEMPLATE_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(&request.name)
+        .bind(&request.event_type)
+        .bind(request.channel_type.as_str())
+        .bind(&request.subject)
+        .bind(&request.body_text)
+        .bind(&request.body_html)
+        .bind(&request.locale)
+        .bind(request.is_active)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(row.into())
+    }
+
+    pub async fn update_template(
+        &self,
+        tenant_id: Uuid,
+        template_id: Uuid,
+        request: &SaveNotificationTemplateRequest,
+    ) -> AppResult<NotificationTemplate> {
+        request.template(template_id, tenant_id).check()?;
+
+        let row = sqlx::query_as::<_, NotificationTemplateRow>(&format!(
+            r#"
+            UPDATE notification_templates
+            SET name = $1, event_type = $2, channel_type = $3, subject = $4, body_text = $5,
+                body_html = $6, locale = $7, is_active = $8, updated_at = NOW()
+            WHERE tenant_id = $9 AND id = $10
+            RETURNING {}
+            "#,
+            TEMPLATE_COLUMNS
+        ))
+        .bind(&request.name)
+        .bind(&request.event_type)
+        .bind(request.channel_type.as_str())
+        .bind(&request.subject)
+        .bind(&request.body_text)
+        .bind(&request.body_html)
+        .bind(&request.locale)
+        .bind(request.is_active)
+        .bind(tenant_id)
+        .bind(template_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::not_found("Notification template"))?;
+
+        Ok(row.into())
+    }
+
+    /// Remove a tenant template; its event goes back to the built-in default
+    pub async fn delete_template(&self, tenant_id: Uuid, template_id: Uuid) -> AppResult<()> {
+        let result = sqlx::query("DELETE FROM notification_templates WHERE tenant_id = $1 AND id = $2")
+            .bind(tenant_id)
+            .bind(template_id)
+            .execute(self.db.pool())
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::not_found("Notification template"));
+        }
+
+        Ok(())
+    }
+
+    /// Record and deliver an email, returning the history record. Suppressed
+    /// recipients are recorded but not sent to; everything else gets an
+    /// unsubscribe link in the footer and the `List-Unsubscribe` headers.
+    pub async fn send_email(
+        &self,
+        tenant_id: Uuid,
+        user_id: Option<Uuid>,
+        email: &OutgoingEmail,
+    ) -> AppResult<Notification> {
+        self.send_email_as(tenant_id, user_id, email, MessageClass::Standard).await
+    }
+
+    /// Send an email the recipient can't opt out of, such as a password
+    /// reset. Only a hard bounce stops it.
+    pub async fn send_transactional_email(
+        &self,
+        tenant_id: Uuid,
+        user_id: Option<Uuid>,
+        email: &OutgoingEmail,
+    ) -> AppResult<Notification> {
+        self.send_email_as(tenant_id, user_id, email, MessageClass::Transactional).await
+    }
+
+    async fn send_email_as(
+        &self,
+        tenant_id: Uuid,
+        user_id: Option<Uuid>,
+        email: &OutgoingEmail,
+        class: MessageClass,
+    ) -> AppResult<Notification> {
+        let notification_id = Uuid::new_v4();
+
+        let suppression = self
+            .suppression(tenant_id, &email.to)
+            .await?
+            .filter(|suppression| suppression.blocks(NotificationChannel::Email, class));
+
+        let mut email = email.clone();
+        let unsubscribe_url = if suppression.is_none() && class == MessageClass::Standard {
+            Some(self.unsubscribe_url(tenant_id, &email.to).await?)
+        } else {
+            None
+        };
+        if let Some(ref url) = unsubscribe_url {
+            email.body_text = unsubscribe_footer(&email.body_text, url);
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO notifications (id, tenant_id, user_id, channel_type, template_id, recipient, subject, body, status, error_message)
+            VALUES ($1, $2, $3, 'email', $4, $5, $6, $7, $8, $9)
+            "#,
+        )
+        .bind(notification_id)
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(email.template_id)
+        .bind(&email.to)
+        .bind(&email.subject)
+        .bind(&email.body_text)
+        .bind(match suppression {
+            Some(_) => NotificationStatus::Suppressed.as_str(),
+            None => NotificationStatus::Pending.as_str(),
+        })
+        .bind(suppression.as_ref().map(|s| format!("Recipient suppressed: {}", s.reason.as_str())))
+        .execute(self.db.pool())
+        .await?;
+
+        if suppression.is_some() {
+            tracing::info!("Skipped email to suppressed address {}", email.to);
+            return self.get_notification(tenant_id, notification_id).await;
+        }
+
+        let email = &email;
+        match self.deliver_email(email, unsubscribe_url.as_deref()).await {
+            Ok(()) => {
+                sqlx::query("UPDATE notifications SET status = 'sent', sent_at = $1 WHERE id = $2")
+                    .bind(Utc::now())
+                    .bind(notification_id)
+                    .execute(self.db.pool())
+                    .await?;
+            }
+            Err(e) => {
+                tracing::warn!("Email to {} failed: {}", email.to, e);
+                sqlx::query("UPDATE notifications SET status = 'failed', error_message = $1 WHERE id = $2")
+                    .bind(e.to_string())
+                    .bind(notification_id)
+                    .execute(self.db.pool())
+                    .await?;
+            }
+        }
+
+        self.get_notification(tenant_id, notification_id).await
+    }
+
+    // ========================================================================
+    // SUPPRESSIONS
+    // ========================================================================
+
+    /// The suppression covering an address, if any
+    pub async fn suppression(&self, tenant_id: Uuid, address: &str) -> AppResult<Option<Suppression>> {
+        let row = sqlx::query_as::<_, SuppressionRow>(&format!(
+            "SELECT {} FROM notification_suppressions WHERE tenant_id = $1 AND address = $2",
+            SUPPRESSION_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(normalize_address(address))
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        Ok(row.map(Into::into))
+    }
+
+    /// Whether a message may go to `address`. Check before any outbound
+    /// email or SMS.
+    pub async fn is_suppressed(
+        &self,
+        tenant_id: Uuid,
+        address: &str,
+        channel: NotificationChannel,
+        class: MessageClass,
+    ) -> AppResult<bool> {
+        Ok(self
+            .suppression(tenant_id, address)
+            .await?
+            .is_some_and(|suppression| suppression.blocks(channel, class)))
+    }
+
+    pub async fn list_suppressions(&self, tenant_id: Uuid) -> AppResult<Vec<Suppression>> {
+        let rows = sqlx::query_as::<_, SuppressionRow>(&format!(
+            "SELECT {} FROM notification_suppressions WHERE tenant_id = $1 ORDER BY created_at DESC",
+            SUPPRESSION_COLUMNS
+        ))
+        .bind(tenant_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Add channels to an address's suppression. A hard bounce, once
+    /// recorded, is never downgraded by a later reason.
+    pub async fn suppress(
+        &self,
+        tenant_id: Uuid,
+        address: &str,
+        channels: &[NotificationChannel],
+        reason: SuppressionReason,
+    ) -> AppResult<Suppression> {
+        let channels: Vec<&str> = channels.iter().map(|channel| channel.as_str()).collect();
+
+        let row = sqlx::query_as::<_, SuppressionRow>(&format!(
+            r#"
+            INSERT INTO notification_suppressions (tenant_id, address, channels, reason)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (tenant_id, address) DO UPDATE
+            SET channels = ARRAY(
+                    SELECT DISTINCT unnest(notification_suppressions.channels || EXCLUDED.channels)
+                ),
+                reason = CASE
+                    WHEN notification_suppressions.reason = 'hard_bounce' THEN 'hard_bounce'
+                    ELSE EXCLUDED.reason
+                END
+            RETURNING {}
+            "#,
+            SUPPRESSION_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(normalize_address(address))
+        .bind(&channels)
+        .bind(reason.as_str())
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(row.into())
+    }
+
+    pub async fn remove_suppression(&self, tenant_id: Uuid, suppression_id: Uuid) -> AppResult<()> {
+        let result = sqlx::query("DELETE FROM notification_suppressions WHERE tenant_id = $1 AND id = $2")
+            .bind(tenant_id)
+            .bind(suppression_id)
+            .execute(self.db.pool())
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::not_found("Suppression"));
+        }
+
+        Ok(())
+    }
+
+    /// Link that unsubscribes `address` from email. The same link is reused
+    /// for every message to the address.
+    pub async fn unsubscribe_url(&self, tenant_id: Uuid, address: &str) -> AppResult<String> {
+        let token: String = sqlx::query_scalar(
+            r#"
+            INSERT INTO unsubscribe_tokens (token, tenant_id, address)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (tenant_id, address) DO UPDATE SET address = EXCLUDED.address
+            RETURNING token
+            "#,
+        )
+        .bind(generate_token(48))
+        .bind(tenant_id)
+        .bind(normalize_address(address))
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(format!("{}/api/v1/unsubscribe/{}", self.base_url.trim_end_matches('/'), token))
+    }
+
+    /// The address an unsubscribe link was issued for, to confirm before
+    /// unsubscribing. With `tenant_id`, links issued by other tenants are
+    /// not found.
+    pub async fn unsubscribe_address(&self, tenant_id: Option<Uuid>, token: &str) -> AppResult<String> {
+        let (_, address) = self.unsubscribe_token(tenant_id, token).await?;
+        Ok(address)
+    }
+
+    /// Suppress email to the address an unsubscribe link was issued for.
+    /// With `tenant_id`, links issued by other tenants are not found.
+    pub async fn unsubscribe(&self, tenant_id: Option<Uuid>, token: &str) -> AppResult<Suppression> {
+        let (tenant_id, address) = self.unsubscribe_token(tenant_id, token).await?;
+
+        self.suppress(tenant_id, &address, &[NotificationChannel::Email], SuppressionReason::Unsubscribed)
+            .await
+    }
+
+    async fn unsubscribe_token(&self, tenant_id: Option<Uuid>, token: &str) -> AppResult<(Uuid, String)> {
+        let row: (Uuid, String) = sqlx::query_as(
+            r#"
+            SELECT tenant_id, address FROM unsubscribe_tokens
+            WHERE token = $1 AND ($2::UUID IS NULL OR tenant_id = $2)
+            "#,
+        )
+        .bind(token)
+        .bind(tenant_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::not_found("Unsubscribe link"))?;
+
+        Ok(row)
+    }
+
+    // ========================================================================
+    // BOUNCES
+    // ========================================================================
+
+    /// Check the secret on a `/email/events` request. The endpoint is off
+    /// until `EMAIL_EVENTS_SECRET` is set.
+    pub fn verify_email_events_secret(&self, secret: Option<&str>) -> AppResult<()> {
+        let expected = self
+            .email_events_secret
+            .as_deref()
+            .ok_or_else(|| AppError::Configuration("Email events are not configured".to_string()))?;
+
+        if secret != Some(expected) {
+            return Err(AppError::Unauthorized);
+        }
+
+        Ok(())
+    }
+
+    /// Record a provider event and act on it: hard bounces and complaints
+    /// suppress the address and flag matching contacts, soft bounces are sent
+    /// again later. The provider is shared, so the event is recorded for
+    /// every tenant that last emailed the address, against that message.
+    pub async fn record_email_event(&self, event: &EmailProviderEvent) -> AppResult<Vec<EmailEvent>> {
+        let Some(kind) = event.kind() else {
+            return Ok(Vec::new());
+        };
+        let address = normalize_address(&event.email);
+
+        let sends: Vec<(Uuid, Uuid)> = sqlx::query_as(
+            r#"
+            SELECT DISTINCT ON (tenant_id) tenant_id, id
+            FROM notifications
+            WHERE channel_type = 'email' AND lower(recipient) = $1
+            ORDER BY tenant_id, created_at DESC
+            "#,
+        )
+        .bind(&address)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let mut recorded = Vec::new();
+        for (tenant_id, notification_id) in sends {
+            if let Some(ref provider_event_id) = event.provider_event_id {
+                let seen: bool = sqlx::query_scalar(
+                    "SELECT EXISTS(SELECT 1 FROM email_events WHERE tenant_id = $1 AND provider_event_id = $2)",
+                )
+                .bind(tenant_id)
+                .bind(provider_event_id)
+                .fetch_one(self.db.pool())
+                .await?;
+
+                // Providers retry their webhooks; act on each event once
+                if seen {
+                    continue;
+                }
+            }
+
+            let row = sqlx::query_as::<_, EmailEventRow>(&format!(
+                r#"
+                INSERT INTO email_events (tenant_id, address, event_type, notification_id, reason, provider_event_id, occurred_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                RETURNING {}
+                "#,
+                EMAIL_EVENT_COLUMNS
+            ))
+            .bind(tenant_id)
+            .bind(&address)
+            .bind(kind.as_str())
+            .bind(notification_id)
+            .bind(&event.reason)
+            .bind(&event.provider_event_id)
+            .bind(event.occurred_at.unwrap_or_else(Utc::now))
+            .fetch_one(self.db.pool())
+            .await?;
+
+            let soft_bounces: i64 = sqlx::query_scalar(
+                "SELECT COUNT(*) FROM email_events WHERE notification_id = $1 AND event_type = 'soft_bounce'",
+            )
+            .bind(notification_id)
+            .fetch_one(self.db.pool())
+            .await?;
+
+            let detail = event.reason.as_deref().unwrap_or(kind.as_str());
+            let failed = match BounceAction::for_event(kind, soft_bounces, Utc::now()) {
+                BounceAction::Suppress(reason) => {
+                    let suppression = self
+                        .suppress(tenant_id, &address, &[NotificationChannel::Email], reason)
+                        .await?;
+                    let deliverability = match suppression.reason {
+                        SuppressionReason::HardBounce => EmailDeliverability::Bounced,
+                        _ => EmailDeliverability::Complained,
+                    };
+
+                    sqlx::query(
+                        r#"
+                        UPDATE contacts SET email_deliverability = $3, updated_at = NOW()
+                        WHERE tenant_id = $1 AND lower(email) = $2
+                        "#,
+                    )
+                    .bind(tenant_id)
+                    .bind(&address)
+                    .bind(deliverability.as_str())
+                    .execute(self.db.pool())
+                    .await?;
+
+                    tracing::info!("Suppressed {} after a {}", address, kind.as_str());
+                    // A complaint means the message arrived
+                    kind == EmailEventKind::HardBounce
+                }
+                BounceAction::Retry(retry_at) => {
+                    sqlx::query("UPDATE notifications SET status = 'pending', error_message = $1 WHERE id = $2")
+                        .bind(format!("Soft bounce, retrying: {}", detail))
+                        .bind(notification_id)
+                        .execute(self.db.pool())
+                        .await?;
+
+                    self.jobs
+                        .enqueue(
+                            NewJob::new(RETRY_EMAIL_JOB, serde_json::json!({ "notification_id": notification_id }))
+                                .for_tenant(tenant_id)
+                                .run_at(retry_at),
+                        )
+                        .await?;
+                    false
+                }
+                BounceAction::GiveUp => true,
+            };
+
+            if failed {
+                sqlx::query("UPDATE notifications SET status = 'failed', error_message = $1 WHERE id = $2")
+                    .bind(format!("Bounced: {}", detail))
+                    .bind(notification_id)
+                    .execute(self.db.pool())
+                    .await?;
+            }
+
+            recorded.push(row.into());
+        }
+
+        Ok(recorded)
+    }
+
+    /// Send a soft-bounced email again, as plain text from the default
+    /// sender. Nothing is sent if the message was dealt with since, or the
+    /// address has hard bounced.
+    pub async fn retry_email(&self, tenant_id: Uuid, notification_id: Uuid) -> AppResult<Notification> {
+        let notification = self.get_notification(tenant_id, notification_id).await?;
+        if notification.status != NotificationStatus::Pending {
+            return Ok(notification);
+        }
+
+        let recipient = notification
+            .recipient
+            .clone()
+            .ok_or_else(|| AppError::internal("Notification has no recipient"))?;
+
+        if self
+            .is_suppressed(tenant_id, &recipient, NotificationChannel::Email, MessageClass::Transactional)
+            .await?
+        {
+            sqlx::query("UPDATE notifications SET status = 'suppressed' WHERE id = $1")
+                .bind(notification_id)
+                .execute(self.db.pool())
+                .await?;
+            return self.get_notification(tenant_id, notification_id).await;
+        }
+
+        let email = OutgoingEmail {
+            to: recipient,
+            subject: notification.subject.clone().unwrap_or_default(),
+            body_text: notification.body.clone(),
+            body_html: None,
+            template_id: notification.template_id,
+            from: None,
+            thread: None,
+            attachments: Vec::new(),
+        };
+
+        match self.deliver_email(&email, None).await {
+            Ok(()) => {
+                sqlx::query("UPDATE notifications SET status = 'sent', sent_at = $1, error_message = NULL WHERE id = $2")
+                    .bind(Utc::now())
+                    .bind(notification_id)
+                    .execute(self.db.pool())
+                    .await?;
+            }
+            Err(e) => {
+                tracing::warn!("Retry of email to {} failed: {}", email.to, e);
+                sqlx::query("UPDATE notifications SET status = 'failed', error_message = $1 WHERE id = $2")
+                    .bind(e.to_string())
+                    .bind(notification_id)
+                    .execute(self.db.pool())
+                    .await?;
+            }
+        }
+
+        self.get_notification(tenant_id, notification_id).await
+    }
+
+    /// Handler for `RETRY_EMAIL_JOB`, for registering with the job worker
+    pub async fn run_retry_email_job(&self, job: Job) -> AppResult<()> {
+        let tenant_id = job
+            .tenant_id
+            .ok_or_else(|| AppError::internal("Email retry job has no tenant"))?;
+        let notification_id = job.payload["notification_id"]
+            .as_str()
+            .and_then(|id| id.parse().ok())
+            .ok_or_else(|| AppError::internal("Email retry job has no notification_id"))?;
+
+        self.retry_email(tenant_id, notification_id).await?;
+        Ok(())
+    }
+
+    /// Get notification by ID
+    pub async fn get_notification(
+        &self,
+        tenant_id: Uuid,
+        notification_id: Uuid,
+    ) -> AppResult<Notification> {
+        let row = sqlx::query_as::<_, NotificationRow>(
+            r#"
+            SELECT id, tenant_id, user_id, channel_type, template_id, recipient, subject, body,
+                   status, error_message, sent_at, delivered_at, read_at, created_at
+            FROM notifications
+            WHERE tenant_id = $1 AND id = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(notification_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Notification".to_string()))?;
+
+        Ok(row.into())
+    }
+
+    // ========================================================================
+    // DIGESTS
+    // ========================================================================
+
+    pub async fn get_preferences(&self, tenant_id: Uuid, user_id: Uuid) -> AppResult<NotificationPreferences> {
+        let stored: serde_json::Value = sqlx::query_scalar(
+            "SELECT notification_preferences FROM users WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("User".to_string()))?;
+
+        Ok(NotificationPreferences::from_stored(&stored))
+    }
+
+    /// Change a user's digest setting, keeping any other stored preferences.
+    /// Events already queued go out with the next digest run.
+    pub async fn update_preferences(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        request: &UpdateNotificationPreferencesRequest,
+    ) -> AppResult<NotificationPreferences> {
+        let digest = serde_json::to_value(request.digest)?;
+
+        let stored: serde_json::Value = sqlx::query_scalar(
+            r#"
+            UPDATE users
+            SET notification_preferences = notification_preferences || jsonb_build_object('digest', $1::JSONB),
+                updated_at = NOW()
+            WHERE tenant_id = $2 AND id = $3
+            RETURNING notification_preferences
+            "#,
+        )
+        .bind(digest)
+        .bind(tenant_id)
+        .bind(user_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("User".to_string()))?;
+
+        Ok(NotificationPreferences::from_stored(&stored))
+    }
+
+    /// Set the user's quiet hours, keeping any other stored preferences.
+    /// Notifications already held are sent when the old window ends.
+    pub async fn set_quiet_hours(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        quiet_hours: &QuietHours,
+    ) -> AppResult<NotificationPreferences> {
+        quiet_hours.check()?;
+
+        let stored: serde_json::Value = sqlx::query_scalar(
+            r#"
+            UPDATE users
+            SET notification_preferences = notification_preferences || jsonb_build_object('quiet_hours', $1::JSONB),
+                updated_at = NOW()
+            WHERE tenant_id = $2 AND id = $3
+            RETURNING notification_preferences
+            "#,
+        )
+        .bind(serde_json::to_value(quiet_hours)?)
+        .bind(tenant_id)
+        .bind(user_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("User".to_string()))?;
+
+        Ok(NotificationPreferences::from_stored(&stored))
+    }
+
+    pub async fn clear_quiet_hours(&self, tenant_id: Uuid, user_id: Uuid) -> AppResult<NotificationPreferences> {
+        let stored: serde_json::Value = sqlx::query_scalar(
+            r#"
+            UPDATE users
+            SET notification_preferences = notification_preferences - 'quiet_hours',
+                updated_at = NOW()
+            WHERE tenant_id = $1 AND id = $2
+            RETURNING notification_preferences
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("User".to_string()))?;
+
+        Ok(NotificationPreferences::from_stored(&stored))
+    }
+
+    /// Notify a user by email, now or in their next digest depending on their
+    /// preferences. Critical events are always sent now, unless the user is
+    /// in quiet hours and hasn't let them through; anything that would be
+    /// sent during quiet hours is held until they end.
+    pub async fn notify_user(&self, tenant_id: Uuid, user_id: Uuid, event: &NotificationEvent) -> AppResult<Delivery> {
+        let (email, stored, timezone): (String, serde_json::Value, String) = sqlx::query_as(
+            "SELECT email, notification_preferences, timezone FROM users WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("User".to_string()))?;
+
+        let timezone = TenantTimezone::parse(&timezone).unwrap_or_default();
+        let delivery = NotificationPreferences::from_stored(&stored).delivery_at(event, Utc::now(), timezone);
+        match delivery {
+            Delivery::Immediate => {
+                let outgoing = OutgoingEmail {
+                    to: email,
+                    subject: event.subject.clone(),
+                    body_text: event.body.clone(),
+                    body_html: None,
+                    template_id: None,
+                    from: None,
+                    thread: None,
+                    attachments: Vec::new(),
+                };
+                self.send_email(tenant_id, Some(user_id), &outgoing).await?;
+            }
+            Delivery::Digest => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO notification_digest_items (tenant_id, user_id, event_type, subject, body)
+                    VALUES ($1, $2, $3, $4, $5)
+                    "#,
+                )
+                .bind(tenant_id)
+                .bind(user_id)
+                .bind(&event.event_type)
+                .bind(&event.subject)
+                .bind(&event.body)
+                .execute(self.db.pool())
+                .await?;
+            }
+            Delivery::Deferred { until } => {
+                self.jobs
+                    .enqueue(
+                        NewJob::new(
+                            DELIVER_HELD_NOTIFICATION_JOB,
+                            serde_json::json!({ "user_id": user_id, "event": event }),
+                        )
+                        .for_tenant(tenant_id)
+                        .run_at(until),
+                    )
+                    .await?;
+            }
+        }
+
+        Ok(delivery)
+    }
+
+    /// Handler for `DELIVER_HELD_NOTIFICATION_JOB`, for registering with the
+    /// job worker. The user's current preferences decide how it goes out, so
+    /// a window that was moved later holds it again.
+    pub async fn run_deliver_held_notification_job(&self, job: Job) -> AppResult<()> {
+        let tenant_id = job
+            .tenant_id
+            .ok_or_else(|| AppError::internal("Held notification job has no tenant"))?;
+        let user_id = job.payload["user_id"]
+            .as_str()
+            .and_then(|id| id.parse().ok())
+            .ok_or_else(|| AppError::internal("Held notification job has no user_id"))?;
+        let event: NotificationEvent = serde_json::from_value(job.payload["event"].clone())
+            .map_err(|e| AppError::internal(format!("Held notification job has an invalid event: {}", e)))?;
+
+        match self.notify_user(tenant_id, user_id, &event).await {
+            // The user was removed while it was held
+            Err(AppError::NotFound(_)) => Ok(()),
+            result => result.map(|_| ()),
+        }
+    }
+
+    /// Send every digest that has come due, one email per user. Users who
+    /// have since turned digests off get what was queued straight away.
+    /// Digests for users in quiet hours wait for a run after they end.
+    /// Returns the ids of the digest notifications sent.
+    pub async fn send_due_digests(&self, tenant_id: Uuid, now: chrono::DateTime<Utc>) -> AppResult<Vec<Uuid>> {
+        let pending: Vec<(Uuid, String, serde_json::Value, String, chrono::DateTime<Utc>)> = sqlx::query_as(
+            r#"
+            SELECT u.id, u.email, u.notification_preferences, u.timezone, MIN(i.created_at)
+            FROM notification_digest_items i
+            JOIN users u ON u.id = i.user_id
+            WHERE i.tenant_id = $1 AND i.included_at IS NULL
+            GROUP BY u.id, u.email, u.notification_preferences, u.timezone
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let mut sent = Vec::new();
+        for (user_id, email, stored, timezone, oldest_queued_at) in pending {
+            let preferences = NotificationPreferences::from_stored(&stored);
+            let frequency = preferences.digest;
+            if frequency != DigestFrequency::Off && !frequency.is_due(oldest_queued_at, now) {
+                continue;
+            }
+            if preferences.is_quiet_at(now, TenantTimezone::parse(&timezone).unwrap_or_default()) {
+                continue;
+            }
+
+            if let Some(notification_id) = self.send_digest(tenant_id, user_id, &email, frequency, now).await? {
+                sent.push(notification_id);
+            }
+        }
+
+        Ok(sent)
+    }
+
+    /// Claim the user's queued events and send them as one email. Claiming
+    /// first means a concurrent run can't include the same event again.
+    async fn send_digest(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        email: &str,
+        frequency: DigestFrequency,
+        now: chrono::DateTime<Utc>,
+    ) -> AppResult<Option<Uuid>> {
+        let rows = sqlx::query_as::<_, DigestItemRow>(
+            r#"
+            UPDATE notification_digest_items
+            SET included_at = $1
+            WHERE id IN (
+                SELECT id FROM notification_digest_items
+                WHERE tenant_id = $2 AND user_id = $3 AND included_at IS NULL AND created_at <= $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, event_type, subject, body, created_at
+            "#,
+        )
+        .bind(now)
+        .bind(tenant_id)
+        .bind(user_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        if rows.is_empty() {
+            return Ok(None);
+        }
+
+        let mut items: Vec<DigestItem> = rows.into_iter().map(Into::into).collect();
+        items.sort_by_key(|item| item.created_at);
+        let item_ids: Vec<Uuid> = items.iter().map(|item| item.id).collect();
+
+        let notification = self
+            .send_email(tenant_id, Some(user_id), &digest_email(email, frequency, &items))
+            .await?;
+
+        sqlx::query("UPDATE notification_digest_items SET notification_id = $1 WHERE id = ANY($2)")
+            .bind(notification.id)
+            .bind(&item_ids)
+            .execute(self.db.pool())
+            .await?;
+
+        Ok(Some(notification.id))
+    }
+
+    /// Send an email over SMTP
+    /// Send over SMTP. With `unsubscribe_url`, the message carries
+    /// `List-Unsubscribe` and the RFC 8058 one-click `List-Unsubscribe-Post`
+    /// header, so mail clients can offer their own unsubscribe button.
+    async fn deliver_email(&self, email: &OutgoingEmail, unsubscribe_url: Option<&str>) -> AppResult<()> {
+        use lettre::{
+            message::{header::ContentType, Attachment, MultiPart, SinglePart},
+            transport::smtp::authentication::Credentials,
+            AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+        };
+
+        let config = self
+            .email
+            .as_ref()
+            .ok_or_else(|| AppError::Configuration("Email is not configured".to_string()))?;
+
+        let from = email
+            .from
+            .clone()
+            .unwrap_or_else(|| format!("{} <{}>", config.from_name, config.from_email));
+
+        let builder = Message::builder()
+            .from(
+                from.parse()
+                    .map_err(|e| AppError::Email(format!("Invalid from address: {}", e)))?,
+            )
+            .to(email
+                .to
+                .parse()
+                .map_err(|e| AppError::Email(format!("Invalid recipient address: {}", e)))?)
+            .subject(&email.subject);
+
+        let builder = match email.thread {
+            Some(ref thread) => {
+                let mut builder = builder.message_id(Some(thread.message_id.clone()));
+                if let Some(ref parent) = thread.in_reply_to {
+                    builder = builder.in_reply_to(parent.clone());
+                }
+                if !thread.references.is_empty() {
+                    builder = builder.references(thread.references.join(" "));
+                }
+                builder
+            }
+            None => builder,
+        };
+
+        let builder = match unsubscribe_url {
+            Some(url) => builder
+                .header(ListUnsubscribe(url.to_string()))
+                .header(ListUnsubscribePost),
+            None => builder,
+        };
+
+        let message = if email.attachments.is_empty() {
+            match email.body_html {
+                Some(ref html) => builder.multipart(MultiPart::alternative_plain_html(
+                    email.body_text.clone(),
+                    html.clone(),
+                ))?,
+                None => builder.singlepart(SinglePart::plain(email.body_text.clone()))?,
+            }
+        } else {
+            let mut body = match email.body_html {
+                Some(ref html) => MultiPart::mixed().multipart(MultiPart::alternative_plain_html(
+                    email.body_text.clone(),
+                    html.clone(),
+                )),
+                None => MultiPart::mixed().singlepart(SinglePart::plain(email.body_text.clone())),
+            };
+            for attachment in &email.attachments {
+                let content_type = ContentType::parse(&attachment.content_type)
+                    .map_err(|e| AppError::Email(format!("Invalid attachment type: {}", e)))?;
+                body = body.singlepart(
+                    Attachment::new(attachment.file_name.clone()).body(attachment.content.clone(), content_type),
+                );
+            }
+            builder.multipart(body)?
+        };
+
+        let mailer = AsyncSmtpTransport::<Tokio1Executor>::relay(&config.smtp_host)?
+            .credentials(Credentials::new(
+                config.smtp_username.clone(),
+                config.smtp_password.clone(),
+            ))
+            .port(config.smtp_port)
+            .build();
+
+        mailer.send(message).await?;
+
+        Ok(())
+    }
+}
+
+// ============================================================================
+// EMAIL HEADERS
+// ============================================================================
+
+/// `List-Unsubscribe` (RFC 2369), pointing at the unsubscribe link
+#[derive(Clone)]
+struct ListUnsubscribe(String);
+
+impl Header for ListUnsubscribe {
+    fn name() -> HeaderName {
+        HeaderName::new_from_ascii_str("List-Unsubscribe")
+    }
+
+    fn parse(s: &str) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self(s.trim().trim_start_matches('<').trim_end_matches('>').to_string()))
+    }
+
+    fn display(&self) -> HeaderValue {
+        HeaderValue::new(Self::name(), format!("<{}>", self.0))
+    }
+}
+
+/// `List-Unsubscribe-Post` (RFC 8058): a POST to the `List-Unsubscribe` link
+/// unsubscribes without any further confirmation
+#[derive(Clone)]
+struct ListUnsubscribePost;
+
+impl Header for ListUnsubscribePost {
+    fn name() -> HeaderName {
+        HeaderName::new_from_ascii_str("List-Unsubscribe-Post")
+    }
+
+    fn parse(_s: &str) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self)
+    }
+
+    fn display(&self) -> HeaderValue {
+        HeaderValue::new(Self::name(), "List-Unsubscribe=One-Click".to_string())
+    }
+}
+
+// ============================================================================
+// DATABASE ROW TYPES
+// ============================================================================
+
+#[derive(sqlx::FromRow)]
+struct EmailEventRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    address: String,
+    event_type: String,
+    notification_id: Option<Uuid>,
+    reason: Option<String>,
+    provider_event_id: Option<String>,
+    occurred_at: chrono::DateTime<Utc>,
+    created_at: chrono::DateTime<Utc>,
+}
+
+impl From<EmailEventRow> for EmailEvent {
+    fn from(row: EmailEventRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            address: row.address,
+            kind: EmailEventKind::from_str(&row.event_type).unwrap_or(EmailEventKind::HardBounce),
+            notification_id: row.notification_id,
+            reason: row.reason,
+            provider_event_id: row.provider_event_id,
+            occurred_at: row.occurred_at,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct NotificationRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    user_id: Option<Uuid>,
+    channel_type: String,
+    template_id: Option<Uuid>,
+    recipient: Option<String>,
+    subject: Option<String>,
+    body: String,
+    status: String,
+    error_message: Option<String>,
+    sent_at: Option<chrono::DateTime<Utc>>,
+    delivered_at: Option<chrono::DateTime<Utc>>,
+    read_at: Option<chrono::DateTime<Utc>>,
+    created_at: chrono::DateTime<Utc>,
+}
+
+impl From<NotificationRow> for Notification {
+    fn from(row: NotificationRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            user_id: row.user_id,
+            channel_type: NotificationChannel::from_str(&row.channel_type).unwrap_or_default(),
+            template_id: row.template_id,
+            recipient: row.recipient,
+            subject: row.subject,
+            body: row.body,
+            status: NotificationStatus::from_str(&row.status).unwrap_or_default(),
+            error_message: row.error_message,
+            sent_at: row.sent_at,
+            delivered_at: row.delivered_at,
+            read_at: row.read_at,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct DigestItemRow {
+    id: Uuid,
+    event_type: String,
+    subject: String,
+    body: String,
+    created_at: chrono::DateTime<Utc>,
+}
+
+impl From<DigestItemRow> for DigestItem {
+    fn from(row: DigestItemRow) -> Self {
+        Self {
+            id: row.id,
+            event_type: row.event_type,
+            subject: row.subject,
+            body: row.body,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct SuppressionRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    address: String,
+    channels: Vec<String>,
+    reason: String,
+    created_at: chrono::DateTime<Utc>,
+    updated_at: chrono::DateTime<Utc>,
+}
+
+impl From<SuppressionRow> for Suppression {
+    fn from(row: SuppressionRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            address: row.address,
+            channels: row
+                .channels
+                .iter()
+                .filter_map(|channel| NotificationChannel::from_str(channel))
+                .collect(),
+            reason: SuppressionReason::from_str(&row.reason).unwrap_or_default(),
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct NotificationTemplateRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    name: String,
+    event_type: String,
+    channel_type: String,
+    subject: Option<String>,
+    body_text: String,
+    body_html: Option<String>,
+    locale: String,
+    is_active: bool,
+}
+
+impl From<NotificationTemplateRow> for NotificationTemplate {
+    fn from(row: NotificationTemplateRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            name: row.name,
+            event_type: row.event_type,
+            channel_type: NotificationChannel::from_str(&row.channel_type).unwrap_or_default(),
+            subject: row.subject,
+            body_text: row.body_text,
+            body_html: row.body_html,
+            locale: row.locale,
+            is_active: row.is_active,
+        }
+    }
+}
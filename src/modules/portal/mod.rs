@@ -1 +1,15 @@
-//! portal module placeholder
+//! Portal Module
+//!
+//! Client portal access for contacts, scoped by per-contact permissions.
+
+mod models;
+#[cfg(feature = "server")]
+mod service;
+#[cfg(feature = "server")]
+mod routes;
+
+pub use models::*;
+#[cfg(feature = "server")]
+pub use service::PortalService;
+#[cfg(feature = "server")]
+pub use routes::portal_access_routes;
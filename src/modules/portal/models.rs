@@ -0,0 +1,569 @@
+//! Portal models and types
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+use crate::modules::billing::{Invoice, InvoiceFilter};
+use crate::modules::tickets::{CreateTicketRequest, Ticket, TicketFilter};
+use crate::utils::error::{AppError, FieldError};
+
+// ============================================================================
+// PERMISSIONS
+// ============================================================================
+
+/// Which of the company's tickets a portal contact can see
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PortalTicketScope {
+    /// Only tickets the contact raised
+    #[default]
+    Own,
+    /// Every ticket for the contact's company; a company admin
+    Company,
+}
+
+impl PortalTicketScope {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "own" => Some(Self::Own),
+            "company" => Some(Self::Company),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Own => "own",
+            Self::Company => "company",
+        }
+    }
+}
+
+/// What a contact may do in the portal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortalPermissions {
+    pub can_open_tickets: bool,
+    pub can_view_invoices: bool,
+    pub can_pay_invoices: bool,
+    pub ticket_scope: PortalTicketScope,
+}
+
+impl Default for PortalPermissions {
+    /// A regular contact: opens and follows their own tickets, no billing
+    fn default() -> Self {
+        Self {
+            can_open_tickets: true,
+            can_view_invoices: false,
+            can_pay_invoices: false,
+            ticket_scope: PortalTicketScope::Own,
+        }
+    }
+}
+
+impl PortalPermissions {
+    /// Everything, across the whole company
+    pub fn company_admin() -> Self {
+        Self {
+            can_open_tickets: true,
+            can_view_invoices: true,
+            can_pay_invoices: true,
+            ticket_scope: PortalTicketScope::Company,
+        }
+    }
+
+    pub fn is_company_admin(&self) -> bool {
+        self.ticket_scope == PortalTicketScope::Company
+    }
+}
+
+/// A portal action gated by a permission
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortalAction {
+    OpenTickets,
+    ViewInvoices,
+    PayInvoices,
+}
+
+// ============================================================================
+// PORTAL CONTACT
+// ============================================================================
+
+/// A contact signed in to the portal, with what they are allowed to do
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortalContact {
+    pub contact_id: Uuid,
+    pub tenant_id: Uuid,
+    pub company_id: Uuid,
+    pub permissions: PortalPermissions,
+}
+
+impl PortalContact {
+    pub fn can(&self, action: PortalAction) -> bool {
+        match action {
+            PortalAction::OpenTickets => self.permissions.can_open_tickets,
+            PortalAction::ViewInvoices => self.permissions.can_view_invoices,
+            // Paying needs sight of the invoice too
+            PortalAction::PayInvoices => self.permissions.can_view_invoices && self.permissions.can_pay_invoices,
+        }
+    }
+
+    pub fn require(&self, action: PortalAction) -> Result<(), AppError> {
+        if self.can(action) {
+            return Ok(());
+        }
+
+        Err(AppError::forbidden(match action {
+            PortalAction::OpenTickets => "You cannot open tickets in the portal",
+            PortalAction::ViewInvoices => "You cannot view invoices in the portal",
+            PortalAction::PayInvoices => "You cannot pay invoices in the portal",
+        }))
+    }
+
+    pub fn can_view_ticket(&self, ticket: &Ticket) -> bool {
+        ticket.tenant_id == self.tenant_id
+            && ticket.company_id == self.company_id
+            && match self.permissions.ticket_scope {
+                PortalTicketScope::Company => true,
+                PortalTicketScope::Own => ticket.contact_id == Some(self.contact_id),
+            }
+    }
+
+    pub fn can_view_invoice(&self, invoice: &Invoice) -> bool {
+        self.can(PortalAction::ViewInvoices)
+            && invoice.tenant_id == self.tenant_id
+            && invoice.company_id == self.company_id
+    }
+
+    /// Narrow a ticket filter to what the contact may see, whatever it asked for
+    pub fn ticket_filter(&self, mut filter: TicketFilter) -> TicketFilter {
+        filter.company_id = Some(self.company_id);
+        if self.permissions.ticket_scope == PortalTicketScope::Own {
+            filter.contact_id = Some(self.contact_id);
+        }
+        // Snoozing is the technician's business; the customer sees the ticket
+        filter.include_snoozed = Some(true);
+        filter.is_snoozed = None;
+        filter
+    }
+
+    pub fn invoice_filter(&self, mut filter: InvoiceFilter) -> InvoiceFilter {
+        filter.company_id = Some(self.company_id);
+        filter
+    }
+}
+
+// ============================================================================
+// TICKET DEFLECTION
+// ============================================================================
+
+/// A step in a portal new-ticket session, recorded to measure how often
+/// suggested articles stop a ticket being raised
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeflectionEventKind {
+    /// The contact opened an article suggested by the form
+    ArticleViewed,
+    TicketSubmitted,
+    /// The contact left the form without submitting
+    TicketAbandoned,
+}
+
+impl DeflectionEventKind {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "article_viewed" => Some(Self::ArticleViewed),
+            "ticket_submitted" => Some(Self::TicketSubmitted),
+            "ticket_abandoned" => Some(Self::TicketAbandoned),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ArticleViewed => "article_viewed",
+            Self::TicketSubmitted => "ticket_submitted",
+            Self::TicketAbandoned => "ticket_abandoned",
+        }
+    }
+}
+
+/// Event reported by the new-ticket form. Submissions are recorded when the
+/// ticket is created, so the form only reports views and abandonment.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RecordDeflectionEventRequest {
+    /// Generated by the form when it opens
+    pub session_id: Uuid,
+    pub event: DeflectionEventKind,
+    pub article_id: Option<Uuid>,
+}
+
+impl RecordDeflectionEventRequest {
+    pub fn check(&self) -> Result<(), AppError> {
+        match (self.event, self.article_id) {
+            (DeflectionEventKind::ArticleViewed, None) => {
+                Err(AppError::validation_field("article_id", "An article view needs the article"))
+            }
+            (DeflectionEventKind::TicketSubmitted, _) => Err(AppError::validation_field(
+                "event",
+                "Submissions are recorded when the ticket is created",
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+// ============================================================================
+// TICKET FORM
+// ============================================================================
+
+/// A standard field the portal new-ticket form can offer. The title is
+/// always shown and required.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PortalFormField {
+    Description,
+    Priority,
+    Type,
+    Category,
+    Site,
+    Asset,
+}
+
+impl PortalFormField {
+    pub const ALL: [Self; 6] = [
+        Self::Description,
+        Self::Priority,
+        Self::Type,
+        Self::Category,
+        Self::Site,
+        Self::Asset,
+    ];
+
+    /// The request field it fills
+    pub fn request_field(&self) -> &'static str {
+        match self {
+            Self::Description => "description",
+            Self::Priority => "priority_id",
+            Self::Type => "type_id",
+            Self::Category => "category_id",
+            Self::Site => "site_id",
+            Self::Asset => "asset_id",
+        }
+    }
+
+    fn is_set(&self, request: &CreateTicketRequest) -> bool {
+        match self {
+            Self::Description => request.description.as_deref().is_some_and(|d| !d.trim().is_empty()),
+            Self::Priority => request.priority_id.is_some(),
+            Self::Type => request.type_id.is_some(),
+            Self::Category => request.category_id.is_some(),
+            Self::Site => request.site_id.is_some(),
+            Self::Asset => request.asset_id.is_some(),
+        }
+    }
+}
+
+/// Whether the portal form shows a field, and whether it must be filled in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldVisibility {
+    #[default]
+    Hidden,
+    Optional,
+    Required,
+}
+
+/// The tenant's portal new-ticket form, stored as the `ticket_form` portal
+/// setting. Fields and custom fields not listed are hidden.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PortalTicketForm {
+    pub fields: BTreeMap<PortalFormField, FieldVisibility>,
+    /// Custom fields customers fill in, by key
+    pub custom_fields: BTreeMap<String, FieldVisibility>,
+}
+
+impl Default for PortalTicketForm {
+    /// The form as it was before it could be configured: a description and a priority
+    fn default() -> Self {
+        Self {
+            fields: BTreeMap::from([
+                (PortalFormField::Description, FieldVisibility::Optional),
+                (PortalFormField::Priority, FieldVisibility::Optional),
+            ]),
+            custom_fields: BTreeMap::new(),
+        }
+    }
+}
+
+impl PortalTicketForm {
+    /// The stored setting, or the default form when it is missing or malformed
+    pub fn from_setting(value: Option<serde_json::Value>) -> Self {
+        value.and_then(|value| serde_json::from_value(value).ok()).unwrap_or_default()
+    }
+
+    pub fn visibility(&self, field: PortalFormField) -> FieldVisibility {
+        self.fields.get(&field).copied().unwrap_or_default()
+    }
+
+    pub fn custom_field_visibility(&self, key: &str) -> FieldVisibility {
+        self.custom_fields.get(key).copied().unwrap_or_default()
+    }
+
+    /// Check a portal submission against the form, reporting every problem
+    /// at once. Hidden fields, and fields staff set on the ticket, are
+    /// rejected if sent; required ones must be filled in.
+    pub fn check(&self, request: &CreateTicketRequest) -> Result<(), AppError> {
+        let not_offered = |field: &str| FieldError::new(field, "Not available in the portal", "not_allowed");
+        let required = |field: &str| FieldError::new(field, "Required", "required");
+
+        let mut errors: Vec<FieldError> = [
+            ("queue_id", request.queue_id.is_some()),
+            ("assigned_to_id", request.assigned_to_id.is_some()),
+            ("team_id", request.team_id.is_some()),
+            ("contract_id", request.contract_id.is_some()),
+            ("sla_id", request.sla_id.is_some()),
+            ("scheduled_start", request.scheduled_start.is_some()),
+            ("scheduled_end", request.scheduled_end.is_some()),
+            ("estimated_hours", request.estimated_hours.is_some()),
+            ("is_billable", request.is_billable.is_some()),
+            ("tags", !request.tags.is_empty()),
+        ]
+        .into_iter()
+        .filter(|(_, sent)| *sent)
+        .map(|(field, _)| not_offered(field))
+        .collect();
+
+        for field in PortalFormField::ALL {
+            match (self.visibility(field), field.is_set(request)) {
+                (FieldVisibility::Hidden, true) => errors.push(not_offered(field.request_field())),
+                (FieldVisibility::Required, false) => errors.push(required(field.request_field())),
+                _ => {}
+            }
+        }
+
+        let sent: BTreeMap<&str, &serde_json::Value> = match &request.custom_fields {
+            serde_json::Value::Object(map) => map.iter().map(|(key, value)| (key.as_str(), value)).collect(),
+            serde_json::Value::Null => BTreeMap::new(),
+            _ => return Err(AppError::validation_field("custom_fields", "Must be an object")),
+        };
+        for key in sent.keys() {
+            if self.custom_field_visibility(key) == FieldVisibility::Hidden {
+                errors.push(not_offered(&format!("custom_fields.{}", key)));
+            }
+        }
+        for (key, visibility) in &self.custom_fields {
+            let filled = match sent.get(key.as_str()) {
+                None | Some(serde_json::Value::Null) => false,
+                Some(serde_json::Value::String(value)) => !value.trim().is_empty(),
+                Some(_) => true,
+            };
+            if *visibility == FieldVisibility::Required && !filled {
+                errors.push(required(&format!("custom_fields.{}", key)));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::validation("The ticket doesn't match the portal form", errors))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::tickets::{BillingStatus, TicketSource};
+    use chrono::Utc;
+
+    fn contact(permissions: PortalPermissions) -> PortalContact {
+        PortalContact {
+            contact_id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            company_id: Uuid::new_v4(),
+            permissions,
+        }
+    }
+
+    fn ticket_for(contact: &PortalContact, contact_id: Option<Uuid>) -> Ticket {
+        Ticket {
+            id: Uuid::new_v4(),
+            tenant_id: contact.tenant_id,
+            ticket_number: "T000042".to_string(),
+            title: "Printer offline".to_string(),
+            description: None,
+            status_id: Uuid::new_v4(),
+            priority_id: Uuid::new_v4(),
+            type_id: None,
+            category_id: None,
+            subcategory_id: None,
+            queue_id: Uuid::new_v4(),
+            source: TicketSource::Portal,
+            company_id: contact.company_id,
+            contact_id,
+            site_id: None,
+            assigned_to_id: None,
+            team_id: None,
+            parent_ticket_id: None,
+            contract_id: None,
+            sla_id: None,
+            sla_due_date: None,
+            first_response_due: None,
+            first_response_at: None,
+            resolution_due: None,
+            resolved_at: None,
+            closed_at: None,
+            resolution_code: None,
+            scheduled_start: None,
+            scheduled_end: None,
+            estimated_hours: None,
+            actual_hours: 0.0,
+            is_billable: false,
+            billing_status: BillingStatus::NotBilled,
+            asset_id: None,
+            custom_fields: serde_json::json!({}),
+            tags: vec![],
+            rule_tags: vec![],
+            snoozed_until: None,
+            snoozed_at: None,
+            snoozed_by_id: None,
+            created_by_id: Uuid::new_v4(),
+            last_updated_by_id: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_restricted_contact_sees_only_own_tickets() {
+        let contact = contact(PortalPermissions::default());
+        let own = ticket_for(&contact, Some(contact.contact_id));
+        let colleagues = ticket_for(&contact, Some(Uuid::new_v4()));
+
+        assert!(contact.can_view_ticket(&own));
+        assert!(!contact.can_view_ticket(&colleagues));
+
+        let filter = contact.ticket_filter(TicketFilter::default());
+        assert_eq!(filter.contact_id, Some(contact.contact_id));
+        assert_eq!(filter.company_id, Some(contact.company_id));
+    }
+
+    #[test]
+    fn test_company_admin_sees_colleagues_tickets() {
+        let admin = contact(PortalPermissions::company_admin());
+        let colleagues = ticket_for(&admin, Some(Uuid::new_v4()));
+        assert!(admin.can_view_ticket(&colleagues));
+
+        // A company id in the request can't redirect the scope
+        let filter = admin.ticket_filter(TicketFilter {
+            company_id: Some(Uuid::new_v4()),
+            ..TicketFilter::default()
+        });
+        assert_eq!(filter.company_id, Some(admin.company_id));
+        assert_eq!(filter.contact_id, None);
+
+        // Other companies' tickets stay out of reach
+        let mut other_company = colleagues.clone();
+        other_company.company_id = Uuid::new_v4();
+        assert!(!admin.can_view_ticket(&other_company));
+    }
+
+    #[test]
+    fn test_invoice_permissions() {
+        let regular = contact(PortalPermissions::default());
+        assert!(regular.require(PortalAction::OpenTickets).is_ok());
+        assert!(regular.require(PortalAction::ViewInvoices).is_err());
+
+        // Paying without being able to see the invoice isn't allowed
+        let pay_only = contact(PortalPermissions {
+            can_pay_invoices: true,
+            ..PortalPermissions::default()
+        });
+        assert!(!pay_only.can(PortalAction::PayInvoices));
+
+        let admin = contact(PortalPermissions::company_admin());
+        assert!(admin.can(PortalAction::PayInvoices));
+        assert!(admin.permissions.is_company_admin());
+    }
+
+    fn portal_request(custom_fields: serde_json::Value) -> CreateTicketRequest {
+        serde_json::from_value(serde_json::json!({
+            "title": "Laptop won't boot",
+            "description": "Black screen after the update",
+            "company_id": Uuid::new_v4(),
+            "custom_fields": custom_fields,
+        }))
+        .unwrap()
+    }
+
+    fn error_fields(error: AppError) -> Vec<String> {
+        match error {
+            AppError::Validation { errors, .. } => errors.into_iter().map(|e| e.field).collect(),
+            other => panic!("Expected a validation error, got {:?}", other),
+        }
+    }
+
+    fn form() -> PortalTicketForm {
+        PortalTicketForm {
+            fields: BTreeMap::from([
+                (PortalFormField::Description, FieldVisibility::Required),
+                (PortalFormField::Asset, FieldVisibility::Required),
+            ]),
+            custom_fields: BTreeMap::from([
+                ("serial_number".to_string(), FieldVisibility::Required),
+                ("location".to_string(), FieldVisibility::Optional),
+                ("internal_cost_code".to_string(), FieldVisibility::Hidden),
+            ]),
+        }
+    }
+
+    #[test]
+    fn test_missing_required_form_field_is_rejected() {
+        let request = portal_request(serde_json::json!({ "location": "Front desk" }));
+        assert_eq!(error_fields(form().check(&request).unwrap_err()), ["asset_id", "custom_fields.serial_number"]);
+
+        let mut request = portal_request(serde_json::json!({ "serial_number": "SN-1234" }));
+        request.asset_id = Some(Uuid::new_v4());
+        assert!(form().check(&request).is_ok());
+
+        // A blank description doesn't count
+        request.description = Some("  ".to_string());
+        assert_eq!(error_fields(form().check(&request).unwrap_err()), ["description"]);
+    }
+
+    #[test]
+    fn test_hidden_field_submission_is_rejected() {
+        let mut request = portal_request(serde_json::json!({
+            "serial_number": "SN-1234",
+            "internal_cost_code": "CC-9",
+            "unlisted": true,
+        }));
+        request.asset_id = Some(Uuid::new_v4());
+        request.priority_id = Some(Uuid::new_v4());
+        request.assigned_to_id = Some(Uuid::new_v4());
+
+        assert_eq!(
+            error_fields(form().check(&request).unwrap_err()),
+            [
+                "assigned_to_id",
+                "priority_id",
+                "custom_fields.internal_cost_code",
+                "custom_fields.unlisted"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_default_portal_form() {
+        let form = PortalTicketForm::from_setting(None);
+        assert_eq!(form.visibility(PortalFormField::Description), FieldVisibility::Optional);
+        assert_eq!(form.visibility(PortalFormField::Asset), FieldVisibility::Hidden);
+        assert!(form.check(&portal_request(serde_json::Value::Null)).is_ok());
+
+        // Unknown settings fall back rather than locking the form
+        let form = PortalTicketForm::from_setting(Some(serde_json::json!("garbage")));
+        assert_eq!(form, PortalTicketForm::default());
+    }
+}
@@ -0,0 +1,75 @@
+//! Portal API routes
+
+use axum::{
+    extract::{Path, State},
+    routing::{get, put},
+    Json, Router,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::{PortalContact, PortalPermissions, PortalService, PortalTicketForm};
+use crate::modules::auth::{RequireAdmin, RequireAuth};
+use crate::utils::error::AppResult;
+
+#[derive(Clone)]
+pub struct PortalRouterState {
+    pub portal_service: Arc<PortalService>,
+}
+
+/// Create the staff-side router for managing contacts' portal permissions
+/// and the portal's new-ticket form.
+/// The portal's own ticket and invoice endpoints go through the same
+/// [`PortalService`] once portal sign-in is in place.
+pub fn portal_access_routes(portal_service: PortalService) -> Router {
+    let state = PortalRouterState {
+        portal_service: Arc::new(portal_service),
+    };
+
+    Router::new()
+        .route("/ticket-form", get(get_ticket_form))
+        .route("/ticket-form", put(update_ticket_form))
+        .route("/:contact_id", get(get_portal_access))
+        .route("/:contact_id", put(update_portal_access))
+        .with_state(state)
+}
+
+async fn get_portal_access(
+    State(state): State<PortalRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(contact_id): Path<Uuid>,
+) -> AppResult<Json<PortalContact>> {
+    let contact = state.portal_service.get_contact(user.tenant_id, contact_id).await?;
+    Ok(Json(contact))
+}
+
+async fn update_portal_access(
+    State(state): State<PortalRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Path(contact_id): Path<Uuid>,
+    Json(permissions): Json<PortalPermissions>,
+) -> AppResult<Json<PortalContact>> {
+    let contact = state
+        .portal_service
+        .update_permissions(user.tenant_id, contact_id, &permissions)
+        .await?;
+
+    Ok(Json(contact))
+}
+
+async fn get_ticket_form(
+    State(state): State<PortalRouterState>,
+    RequireAuth(user): RequireAuth,
+) -> AppResult<Json<PortalTicketForm>> {
+    let form = state.portal_service.ticket_form(user.tenant_id).await?;
+    Ok(Json(form))
+}
+
+async fn update_ticket_form(
+    State(state): State<PortalRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Json(form): Json<PortalTicketForm>,
+) -> AppResult<Json<PortalTicketForm>> {
+    let form = state.portal_service.update_ticket_form(user.tenant_id, &form).await?;
+    Ok(Json(form))
+}
@@ -0,0 +1,313 @@
+//! Portal service implementation
+
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::modules::billing::{BillingService, Invoice, InvoiceFilter};
+use crate::modules::tickets::{CreateTicketRequest, Ticket, TicketFilter, TicketService, TicketSource};
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::pagination::{ListTotal, PaginationParams};
+
+use super::models::*;
+
+/// Client portal service. Every portal read and write goes through the
+/// contact's permissions here.
+#[derive(Clone)]
+pub struct PortalService {
+    db: Database,
+    tickets: TicketService,
+    billing: BillingService,
+}
+
+impl PortalService {
+    pub fn new(db: Database) -> Self {
+        Self {
+            tickets: TicketService::new(db.clone()),
+            billing: BillingService::new(db.clone()),
+            db,
+        }
+    }
+
+    /// Load a portal contact. Contacts without portal access, or whose company
+    /// has the portal turned off, are refused.
+    pub async fn get_contact(&self, tenant_id: Uuid, contact_id: Uuid) -> AppResult<PortalContact> {
+        let row = sqlx::query_as::<_, PortalContactRow>(
+            r#"
+            SELECT c.id, c.tenant_id, c.company_id, c.is_portal_user, co.portal_enabled,
+                   c.portal_can_open_tickets, c.portal_can_view_invoices,
+                   c.portal_can_pay_invoices, c.portal_ticket_scope
+            FROM contacts c
+            JOIN companies co ON co.id = c.company_id
+            WHERE c.tenant_id = $1 AND c.id = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(contact_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::not_found("Contact"))?;
+
+        if !row.is_portal_user || !row.portal_enabled {
+            return Err(AppError::forbidden("Portal access is not enabled for this contact"));
+        }
+
+        Ok(row.into())
+    }
+
+    /// Change what a portal contact may do
+    pub async fn update_permissions(
+        &self,
+        tenant_id: Uuid,
+        contact_id: Uuid,
+        permissions: &PortalPermissions,
+    ) -> AppResult<PortalContact> {
+        let contact = self.get_contact(tenant_id, contact_id).await?;
+
+        sqlx::query(
+            r#"
+            UPDATE contacts
+            SET portal_can_open_tickets = $1, portal_can_view_invoices = $2,
+                portal_can_pay_invoices = $3, portal_ticket_scope = $4, updated_at = NOW()
+            WHERE tenant_id = $5 AND id = $6
+            "#,
+        )
+        .bind(permissions.can_open_tickets)
+        .bind(permissions.can_view_invoices)
+        .bind(permissions.can_pay_invoices)
+        .bind(permissions.ticket_scope.as_str())
+        .bind(tenant_id)
+        .bind(contact_id)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(PortalContact {
+            permissions: *permissions,
+            ..contact
+        })
+    }
+
+    // ========================================================================
+    // TICKETS
+    // ========================================================================
+
+    /// Tickets the contact can see: their own, or the company's for a company admin
+    pub async fn list_tickets(
+        &self,
+        contact: &PortalContact,
+        filter: TicketFilter,
+        pagination: &PaginationParams,
+    ) -> AppResult<(Vec<Ticket>, ListTotal)> {
+        self.tickets
+            .list_tickets(contact.tenant_id, &contact.ticket_filter(filter), pagination)
+            .await
+    }
+
+    /// A ticket the contact can see. Others read as missing rather than
+    /// forbidden so ticket ids can't be probed.
+    pub async fn get_ticket(&self, contact: &PortalContact, ticket_id: Uuid) -> AppResult<Ticket> {
+        let ticket = self.tickets.get_ticket(contact.tenant_id, ticket_id).await?;
+
+        if !contact.can_view_ticket(&ticket) {
+            return Err(AppError::not_found("Ticket"));
+        }
+
+        Ok(ticket)
+    }
+
+    /// Open a ticket on the contact's behalf, always for their own company,
+    /// with only the fields the tenant's portal form offers.
+    /// `deflection_session_id` is the new-ticket form's session, recorded as
+    /// submitted for deflection reporting.
+    pub async fn create_ticket(
+        &self,
+        contact: &PortalContact,
+        user_id: Uuid,
+        request: &CreateTicketRequest,
+        deflection_session_id: Option<Uuid>,
+    ) -> AppResult<Ticket> {
+        contact.require(PortalAction::OpenTickets)?;
+        self.ticket_form(contact.tenant_id).await?.check(request)?;
+
+        let request = CreateTicketRequest {
+            source: TicketSource::Portal,
+            company_id: contact.company_id,
+            contact_id: Some(contact.contact_id),
+            ..request.clone()
+        };
+
+        let ticket = self.tickets.create_ticket(contact.tenant_id, user_id, &request).await?;
+
+        if let Some(session_id) = deflection_session_id {
+            self.insert_deflection_event(contact, session_id, DeflectionEventKind::TicketSubmitted, None, Some(ticket.id))
+                .await?;
+        }
+
+        Ok(ticket)
+    }
+
+    /// The tenant's portal new-ticket form
+    pub async fn ticket_form(&self, tenant_id: Uuid) -> AppResult<PortalTicketForm> {
+        let value: Option<serde_json::Value> = sqlx::query_scalar(
+            "SELECT value FROM tenant_settings WHERE tenant_id = $1 AND category = 'portal' AND key = 'ticket_form'",
+        )
+        .bind(tenant_id)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        Ok(PortalTicketForm::from_setting(value))
+    }
+
+    /// Replace the tenant's portal new-ticket form
+    pub async fn update_ticket_form(&self, tenant_id: Uuid, form: &PortalTicketForm) -> AppResult<PortalTicketForm> {
+        if form.custom_fields.keys().any(|key| key.trim().is_empty()) {
+            return Err(AppError::validation_field("custom_fields", "Custom field keys can't be blank"));
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO tenant_settings (tenant_id, category, key, value)
+            VALUES ($1, 'portal', 'ticket_form', $2)
+            ON CONFLICT (tenant_id, category, key) DO UPDATE SET value = EXCLUDED.value
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(serde_json::to_value(form).map_err(|e| AppError::internal(e.to_string()))?)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(form.clone())
+    }
+
+    // ========================================================================
+    // TICKET DEFLECTION
+    // ========================================================================
+
+    /// Record a suggested article view or an abandoned new-ticket form
+    pub async fn record_deflection_event(
+        &self,
+        contact: &PortalContact,
+        request: &RecordDeflectionEventRequest,
+    ) -> AppResult<()> {
+        request.check()?;
+
+        let article_id = match request.article_id {
+            Some(article_id) => {
+                let exists: bool = sqlx::query_scalar(
+                    "SELECT EXISTS(SELECT 1 FROM kb_articles WHERE tenant_id = $1 AND id = $2)",
+                )
+                .bind(contact.tenant_id)
+                .bind(article_id)
+                .fetch_one(self.db.pool())
+                .await?;
+                if !exists {
+                    return Err(AppError::not_found("Article"));
+                }
+                Some(article_id)
+            }
+            None => None,
+        };
+
+        self.insert_deflection_event(contact, request.session_id, request.event, article_id, None)
+            .await
+    }
+
+    async fn insert_deflection_event(
+        &self,
+        contact: &PortalContact,
+        session_id: Uuid,
+        event: DeflectionEventKind,
+        article_id: Option<Uuid>,
+        ticket_id: Option<Uuid>,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO portal_deflection_events (tenant_id, session_id, contact_id, event_type, article_id, ticket_id)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(contact.tenant_id)
+        .bind(session_id)
+        .bind(contact.contact_id)
+        .bind(event.as_str())
+        .bind(article_id)
+        .bind(ticket_id)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    // ========================================================================
+    // INVOICES
+    // ========================================================================
+
+    /// The company's invoices, for contacts allowed to see them
+    pub async fn list_invoices(
+        &self,
+        contact: &PortalContact,
+        filter: InvoiceFilter,
+        pagination: &PaginationParams,
+    ) -> AppResult<(Vec<Invoice>, u64)> {
+        contact.require(PortalAction::ViewInvoices)?;
+
+        self.billing
+            .list_invoices(contact.tenant_id, &contact.invoice_filter(filter), pagination, true)
+            .await
+    }
+
+    pub async fn get_invoice(&self, contact: &PortalContact, invoice_id: Uuid) -> AppResult<Invoice> {
+        contact.require(PortalAction::ViewInvoices)?;
+        let invoice = self.billing.get_invoice(contact.tenant_id, invoice_id).await?;
+
+        if !contact.can_view_invoice(&invoice) {
+            return Err(AppError::not_found("Invoice"));
+        }
+
+        Ok(invoice)
+    }
+
+    /// An invoice the contact may pay, checked before a payment is taken
+    pub async fn payable_invoice(&self, contact: &PortalContact, invoice_id: Uuid) -> AppResult<Invoice> {
+        contact.require(PortalAction::PayInvoices)?;
+        let invoice = self.get_invoice(contact, invoice_id).await?;
+
+        if invoice.balance_due <= rust_decimal::Decimal::ZERO {
+            return Err(AppError::Conflict("Invoice has no balance due".to_string()));
+        }
+
+        Ok(invoice)
+    }
+}
+
+// ============================================================================
+// DATABASE ROW TYPES
+// ============================================================================
+
+#[derive(sqlx::FromRow)]
+struct PortalContactRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    company_id: Uuid,
+    is_portal_user: bool,
+    portal_enabled: bool,
+    portal_can_open_tickets: bool,
+    portal_can_view_invoices: bool,
+    portal_can_pay_invoices: bool,
+    portal_ticket_scope: String,
+}
+
+impl From<PortalContactRow> for PortalContact {
+    fn from(row: PortalContactRow) -> Self {
+        Self {
+            contact_id: row.id,
+            tenant_id: row.tenant_id,
+            company_id: row.company_id,
+            permissions: PortalPermissions {
+                can_open_tickets: row.portal_can_open_tickets,
+                can_view_invoices: row.portal_can_view_invoices,
+                can_pay_invoices: row.portal_can_pay_invoices,
+                ticket_scope: PortalTicketScope::from_str(&row.portal_ticket_scope).unwrap_or_default(),
+            },
+        }
+    }
+}
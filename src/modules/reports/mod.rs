@@ -1 +1,15 @@
-//! reports module placeholder
+//! Reports Module
+//!
+//! Aggregated service desk and business reporting.
+
+mod models;
+#[cfg(feature = "server")]
+mod service;
+#[cfg(feature = "server")]
+mod routes;
+
+pub use models::*;
+#[cfg(feature = "server")]
+pub use service::ReportService;
+#[cfg(feature = "server")]
+pub use routes::report_routes;
@@ -0,0 +1,1636 @@
+//! Report models and types
+
+use chrono::{DateTime, Datelike, Months, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, HashMap};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::modules::calendar::WorkingHours;
+use crate::modules::contacts::CompanyTree;
+use crate::modules::portal::DeflectionEventKind;
+
+// ============================================================================
+// DATE RANGE
+// ============================================================================
+
+/// Reporting window, inclusive of `from` and exclusive of `to`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct DateRange {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+impl DateRange {
+    pub fn is_valid(&self) -> bool {
+        self.from <= self.to
+    }
+}
+
+// ============================================================================
+// TICKET VOLUME
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TicketVolumeReport {
+    pub range: DateRange,
+    /// Time zone the daily rows are bucketed in
+    pub timezone: String,
+    pub created: u64,
+    pub closed: u64,
+    pub by_resolution_code: Vec<ResolutionCodeCount>,
+    pub daily: Vec<DailyTicketCount>,
+}
+
+/// Tickets created and closed on one tenant-local day
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DailyTicketCount {
+    pub date: NaiveDate,
+    pub created: u64,
+    pub closed: u64,
+}
+
+impl DailyTicketCount {
+    /// One row per day in `days`, including days without tickets, from
+    /// `(local date, count)` rows
+    pub fn series(
+        days: Vec<NaiveDate>,
+        created: Vec<(NaiveDate, i64)>,
+        closed: Vec<(NaiveDate, i64)>,
+    ) -> Vec<Self> {
+        let created: HashMap<NaiveDate, i64> = created.into_iter().collect();
+        let closed: HashMap<NaiveDate, i64> = closed.into_iter().collect();
+
+        days.into_iter()
+            .map(|date| Self {
+                date,
+                created: created.get(&date).copied().unwrap_or(0).max(0) as u64,
+                closed: closed.get(&date).copied().unwrap_or(0).max(0) as u64,
+            })
+            .collect()
+    }
+}
+
+/// Closed tickets grouped by resolution code
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolutionCodeCount {
+    /// `None` for tickets closed without a code
+    pub code: Option<String>,
+    pub name: String,
+    pub count: u64,
+    pub percentage: f64,
+}
+
+impl ResolutionCodeCount {
+    /// Build a breakdown from `(code, name, count)` rows, largest first
+    pub fn breakdown(rows: Vec<(Option<String>, Option<String>, i64)>) -> Vec<Self> {
+        let total: i64 = rows.iter().map(|(_, _, count)| *count).sum();
+
+        let mut breakdown: Vec<Self> = rows
+            .into_iter()
+            .map(|(code, name, count)| {
+                let name = name
+                    .or_else(|| code.clone())
+                    .unwrap_or_else(|| "Unspecified".to_string());
+                let percentage = if total > 0 {
+                    (count as f64 / total as f64) * 100.0
+                } else {
+                    0.0
+                };
+
+                Self {
+                    code,
+                    name,
+                    count: count.max(0) as u64,
+                    percentage,
+                }
+            })
+            .collect();
+
+        breakdown.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+        breakdown
+    }
+}
+
+// ============================================================================
+// TIME IN STATUS
+// ============================================================================
+
+/// Second dimension of the time in status report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeInStatusGroupBy {
+    /// Status only
+    #[default]
+    Status,
+    Queue,
+    Priority,
+    Assignee,
+}
+
+impl TimeInStatusGroupBy {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "status" => Some(Self::Status),
+            "queue" => Some(Self::Queue),
+            "priority" => Some(Self::Priority),
+            "assignee" => Some(Self::Assignee),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Status => "status",
+            Self::Queue => "queue",
+            Self::Priority => "priority",
+            Self::Assignee => "assignee",
+        }
+    }
+
+    /// `(group id, group name, join)` SQL fragments over `tickets t`. Groups
+    /// use the ticket's current queue, priority or assignee.
+    pub fn sql(&self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            Self::Status => ("NULL::UUID", "NULL::VARCHAR", ""),
+            Self::Queue => ("t.queue_id", "g.name", "LEFT JOIN ticket_queues g ON g.id = t.queue_id"),
+            Self::Priority => ("t.priority_id", "g.name", "LEFT JOIN ticket_priorities g ON g.id = t.priority_id"),
+            Self::Assignee => (
+                "t.assigned_to_id",
+                "g.first_name || ' ' || g.last_name",
+                "LEFT JOIN users g ON g.id = t.assigned_to_id",
+            ),
+        }
+    }
+}
+
+/// Time in status query parameters
+#[derive(Debug, Clone, Copy, Deserialize, Default)]
+pub struct TimeInStatusParams {
+    #[serde(default)]
+    pub group_by: TimeInStatusGroupBy,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimeInStatusReport {
+    pub range: DateRange,
+    pub group_by: TimeInStatusGroupBy,
+    pub rows: Vec<TimeInStatusRow>,
+}
+
+/// Time tickets spent in one status within the range, clipped to its bounds
+#[derive(Debug, Clone, Serialize)]
+pub struct TimeInStatusRow {
+    pub status_id: Uuid,
+    pub status_name: String,
+    /// Queue, priority or assignee; `None` when grouping by status only or
+    /// the ticket has no value for the group
+    pub group_id: Option<Uuid>,
+    pub group_name: Option<String>,
+    pub tickets: u64,
+    pub total_seconds: u64,
+    pub average_seconds: f64,
+}
+
+impl TimeInStatusRow {
+    /// Build a row from `(status id, status name, group id, group name, tickets, seconds)`
+    pub fn from_totals(
+        (status_id, status_name, group_id, group_name, tickets, seconds): (
+            Uuid,
+            String,
+            Option<Uuid>,
+            Option<String>,
+            i64,
+            i64,
+        ),
+    ) -> Self {
+        let tickets = tickets.max(0) as u64;
+        let total_seconds = seconds.max(0) as u64;
+        let average_seconds = if tickets > 0 {
+            total_seconds as f64 / tickets as f64
+        } else {
+            0.0
+        };
+
+        Self {
+            status_id,
+            status_name,
+            group_id,
+            group_name,
+            tickets,
+            total_seconds,
+            average_seconds,
+        }
+    }
+}
+
+// ============================================================================
+// EFFORT SUMMARY
+// ============================================================================
+
+/// Dimension of the effort summary
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EffortGroupBy {
+    #[default]
+    Queue,
+    Company,
+    Assignee,
+}
+
+impl EffortGroupBy {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "queue" => Some(Self::Queue),
+            "company" => Some(Self::Company),
+            "assignee" => Some(Self::Assignee),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Queue => "queue",
+            Self::Company => "company",
+            Self::Assignee => "assignee",
+        }
+    }
+
+    /// `(group id, group name, join)` SQL fragments over `tickets t`
+    pub fn sql(&self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            Self::Queue => ("t.queue_id", "g.name", "LEFT JOIN ticket_queues g ON g.id = t.queue_id"),
+            Self::Company => ("t.company_id", "g.name", "LEFT JOIN companies g ON g.id = t.company_id"),
+            Self::Assignee => (
+                "t.assigned_to_id",
+                "g.first_name || ' ' || g.last_name",
+                "LEFT JOIN users g ON g.id = t.assigned_to_id",
+            ),
+        }
+    }
+}
+
+/// Tickets included in the effort summary and how to group them
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EffortFilter {
+    #[serde(default)]
+    pub group_by: EffortGroupBy,
+    pub queue_id: Option<Uuid>,
+    pub company_id: Option<Uuid>,
+    pub assigned_to_id: Option<Uuid>,
+    pub is_open: Option<bool>,
+    pub created_from: Option<DateTime<Utc>>,
+    pub created_to: Option<DateTime<Utc>>,
+}
+
+/// Estimated and actual hours of one ticket
+#[derive(Debug, Clone)]
+pub struct EffortTicket {
+    pub group_id: Option<Uuid>,
+    pub group_name: Option<String>,
+    pub estimated_hours: Option<Decimal>,
+    pub actual_hours: Decimal,
+}
+
+/// Estimated against actual hours. Tickets without an estimate add to
+/// `actual_hours` but not to the estimate, and are left out of the variance
+/// so they don't read as overruns.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct EffortTotals {
+    pub tickets: u64,
+    pub estimated_tickets: u64,
+    pub estimated_hours: Decimal,
+    pub actual_hours: Decimal,
+    /// Actual hours on tickets that have an estimate
+    pub estimated_actual_hours: Decimal,
+    /// `estimated_actual_hours - estimated_hours`; positive means over estimate
+    pub variance_hours: Decimal,
+    /// Variance as a percentage of the estimate, `None` without one
+    pub variance_percent: Option<Decimal>,
+}
+
+impl EffortTotals {
+    pub fn add(&mut self, estimated_hours: Option<Decimal>, actual_hours: Decimal) {
+        self.tickets += 1;
+        self.actual_hours += actual_hours;
+        if let Some(estimated) = estimated_hours {
+            self.estimated_tickets += 1;
+            self.estimated_hours += estimated;
+            self.estimated_actual_hours += actual_hours;
+        }
+
+        self.variance_hours = self.estimated_actual_hours - self.estimated_hours;
+        self.variance_percent = if self.estimated_hours > Decimal::ZERO {
+            Some((self.variance_hours * Decimal::ONE_HUNDRED / self.estimated_hours).round_dp(1))
+        } else {
+            None
+        };
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EffortRow {
+    /// `None` for tickets with no queue, company or assignee
+    pub group_id: Option<Uuid>,
+    pub group_name: Option<String>,
+    #[serde(flatten)]
+    pub totals: EffortTotals,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EffortSummaryReport {
+    pub group_by: EffortGroupBy,
+    pub totals: EffortTotals,
+    pub rows: Vec<EffortRow>,
+}
+
+impl EffortSummaryReport {
+    /// Sum tickets into one row per group, in the order groups first appear
+    pub fn build(group_by: EffortGroupBy, tickets: Vec<EffortTicket>) -> Self {
+        let mut totals = EffortTotals::default();
+        let mut rows: Vec<EffortRow> = Vec::new();
+        let mut index: HashMap<Option<Uuid>, usize> = HashMap::new();
+
+        for ticket in tickets {
+            totals.add(ticket.estimated_hours, ticket.actual_hours);
+            let at = *index.entry(ticket.group_id).or_insert_with(|| {
+                rows.push(EffortRow {
+                    group_id: ticket.group_id,
+                    group_name: ticket.group_name.clone(),
+                    totals: EffortTotals::default(),
+                });
+                rows.len() - 1
+            });
+            rows[at].totals.add(ticket.estimated_hours, ticket.actual_hours);
+        }
+
+        Self { group_by, totals, rows }
+    }
+}
+
+// ============================================================================
+// CUSTOMER SATISFACTION
+// ============================================================================
+
+/// Thumbs-up / thumbs-down tally
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct CsatScore {
+    pub positive: u64,
+    pub negative: u64,
+    /// Share of positive responses as a percentage, `None` without responses
+    pub score: Option<f64>,
+}
+
+impl CsatScore {
+    pub fn from_counts(positive: i64, negative: i64) -> Self {
+        let positive = positive.max(0) as u64;
+        let negative = negative.max(0) as u64;
+        let total = positive + negative;
+        let score = if total > 0 {
+            Some((positive as f64 / total as f64) * 100.0)
+        } else {
+            None
+        };
+
+        Self { positive, negative, score }
+    }
+
+    pub fn responses(&self) -> u64 {
+        self.positive + self.negative
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CsatSummaryReport {
+    pub range: DateRange,
+    pub overall: CsatScore,
+    pub by_technician: Vec<TechnicianCsat>,
+    pub weekly_trend: Vec<CsatTrendPoint>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TechnicianCsat {
+    /// `None` for tickets that were closed unassigned
+    pub technician_id: Option<Uuid>,
+    pub technician_name: String,
+    pub score: CsatScore,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CsatTrendPoint {
+    pub week_start: DateTime<Utc>,
+    pub score: CsatScore,
+}
+
+// ============================================================================
+// COMPANY ROLLUP
+// ============================================================================
+
+/// Ticket, time and invoice totals for a company within the range
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CompanyActivity {
+    pub tickets_created: u64,
+    pub tickets_closed: u64,
+    pub minutes: i64,
+    pub billable_minutes: i64,
+    pub invoiced: Decimal,
+}
+
+impl CompanyActivity {
+    pub fn add(&mut self, other: &Self) {
+        self.tickets_created += other.tickets_created;
+        self.tickets_closed += other.tickets_closed;
+        self.minutes += other.minutes;
+        self.billable_minutes += other.billable_minutes;
+        self.invoiced += other.invoiced;
+    }
+}
+
+/// One company in the rollup: its own activity and that of it plus every
+/// subsidiary below it
+#[derive(Debug, Clone, Serialize)]
+pub struct CompanyRollupRow {
+    pub company_id: Uuid,
+    pub company_name: String,
+    pub parent_company_id: Option<Uuid>,
+    pub depth: u32,
+    pub own: CompanyActivity,
+    pub rolled_up: CompanyActivity,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CompanyRollupReport {
+    pub range: DateRange,
+    pub root_id: Uuid,
+    /// Whether `invoiced` figures are filled in; they are zero for users who
+    /// may not see financials
+    pub includes_revenue: bool,
+    /// Same as the root's `rolled_up`
+    pub totals: CompanyActivity,
+    pub companies: Vec<CompanyRollupRow>,
+}
+
+impl CompanyRollupReport {
+    /// Roll each company's own activity up through the tree. Companies with
+    /// no activity count as zero.
+    pub fn build(
+        range: DateRange,
+        tree: &CompanyTree,
+        activity: &HashMap<Uuid, CompanyActivity>,
+        includes_revenue: bool,
+    ) -> Self {
+        let own = |id: Uuid| activity.get(&id).cloned().unwrap_or_default();
+
+        // Deepest first, so each company is complete before it is added to its parent
+        let mut rolled_up: HashMap<Uuid, CompanyActivity> = HashMap::new();
+        for node in tree.companies.iter().rev() {
+            let mut total = rolled_up.remove(&node.id).unwrap_or_default();
+            total.add(&own(node.id));
+            if let Some(parent_id) = node.parent_company_id.filter(|_| node.id != tree.root_id) {
+                rolled_up.entry(parent_id).or_default().add(&total);
+            }
+            rolled_up.insert(node.id, total);
+        }
+
+        let companies: Vec<CompanyRollupRow> = tree
+            .companies
+            .iter()
+            .map(|node| CompanyRollupRow {
+                company_id: node.id,
+                company_name: node.name.clone(),
+                parent_company_id: node.parent_company_id,
+                depth: node.depth,
+                own: own(node.id),
+                rolled_up: rolled_up.get(&node.id).cloned().unwrap_or_default(),
+            })
+            .collect();
+
+        Self {
+            range,
+            root_id: tree.root_id,
+            includes_revenue,
+            totals: rolled_up.remove(&tree.root_id).unwrap_or_default(),
+            companies,
+        }
+    }
+}
+
+// ============================================================================
+// UTILIZATION
+// ============================================================================
+
+/// A technician's billable time against the hours they were scheduled to work
+#[derive(Debug, Clone, Serialize)]
+pub struct TechnicianUtilization {
+    pub user_id: Uuid,
+    pub name: String,
+    /// Minutes in the technician's working hours across the range
+    pub available_minutes: i64,
+    pub logged_minutes: i64,
+    pub billable_minutes: i64,
+    /// Billable minutes as a percentage of available minutes, `None` for a
+    /// technician with no hours in the range
+    pub utilization: Option<f64>,
+}
+
+impl TechnicianUtilization {
+    /// `days` are the local days of the range in the technician's time zone
+    pub fn new(
+        user_id: Uuid,
+        name: String,
+        hours: &WorkingHours,
+        days: &[NaiveDate],
+        logged_minutes: i64,
+        billable_minutes: i64,
+    ) -> Self {
+        let available_minutes = hours.available_minutes(days);
+        let utilization = if available_minutes > 0 {
+            Some((billable_minutes as f64 / available_minutes as f64) * 100.0)
+        } else {
+            None
+        };
+
+        Self {
+            user_id,
+            name,
+            available_minutes,
+            logged_minutes,
+            billable_minutes,
+            utilization,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UtilizationReport {
+    pub range: DateRange,
+    pub technicians: Vec<TechnicianUtilization>,
+}
+
+// ============================================================================
+// TICKET DEFLECTION
+// ============================================================================
+
+/// A portal new-ticket session with neither a submission nor an abandonment
+/// counts as abandoned once it has been idle this long
+pub const DEFLECTION_SESSION_TIMEOUT_MINUTES: i64 = 30;
+
+/// A recorded step of a portal new-ticket session
+#[derive(Debug, Clone)]
+pub struct DeflectionEvent {
+    pub session_id: Uuid,
+    pub kind: DeflectionEventKind,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// How often contacts who read a suggested article didn't raise a ticket
+#[derive(Debug, Clone, Serialize)]
+pub struct DeflectionReport {
+    pub range: DateRange,
+    /// Sessions that viewed a suggested article and then submitted
+    pub submitted: u64,
+    /// Sessions that viewed a suggested article and then abandoned the form
+    pub deflected: u64,
+    /// Share of those sessions that were deflected as a percentage, `None`
+    /// without any
+    pub rate: Option<f64>,
+}
+
+impl DeflectionReport {
+    /// Sessions are only counted once they viewed an article, and by what
+    /// happened after the first view. Sessions still in progress at `now`
+    /// aren't counted yet.
+    pub fn from_events(range: DateRange, events: &[DeflectionEvent], now: DateTime<Utc>) -> Self {
+        let mut sessions: HashMap<Uuid, Vec<&DeflectionEvent>> = HashMap::new();
+        for event in events {
+            sessions.entry(event.session_id).or_default().push(event);
+        }
+
+        let idle_cutoff = now - chrono::Duration::minutes(DEFLECTION_SESSION_TIMEOUT_MINUTES);
+        let (mut submitted, mut deflected) = (0, 0);
+        for mut session in sessions.into_values() {
+            session.sort_by_key(|event| event.occurred_at);
+            let Some(first_view) = session
+                .iter()
+                .position(|event| event.kind == DeflectionEventKind::ArticleViewed)
+            else {
+                continue;
+            };
+
+            let outcome = session[first_view..]
+                .iter()
+                .find(|event| event.kind != DeflectionEventKind::ArticleViewed)
+                .map(|event| event.kind);
+            match outcome {
+                Some(DeflectionEventKind::TicketSubmitted) => submitted += 1,
+                Some(_) => deflected += 1,
+                None if session.last().is_some_and(|event| event.occurred_at < idle_cutoff) => deflected += 1,
+                None => {}
+            }
+        }
+
+        let total = submitted + deflected;
+        let rate = if total > 0 {
+            Some((deflected as f64 / total as f64) * 100.0)
+        } else {
+            None
+        };
+
+        Self {
+            range,
+            submitted,
+            deflected,
+            rate,
+        }
+    }
+}
+
+// ============================================================================
+// SLA PENALTIES
+// ============================================================================
+
+/// Which SLA promise a ticket was measured against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlaTarget {
+    FirstResponse,
+    Resolution,
+}
+
+/// Credit owed for breaches at least `late_by_minutes` past due
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlaPenaltyTier {
+    pub late_by_minutes: i64,
+    pub credit_percent: Decimal,
+}
+
+/// A contract's `sla_penalty_terms`: credit per breach by how late it was,
+/// as a percentage of the contract's billing amount
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlaPenaltyTerms {
+    pub tiers: Vec<SlaPenaltyTier>,
+    /// Most the credits for one report can add up to
+    #[serde(default)]
+    pub max_credit_percent: Option<Decimal>,
+}
+
+impl SlaPenaltyTerms {
+    /// Parse the stored terms; `None` when missing, malformed or without tiers
+    pub fn from_value(value: Option<serde_json::Value>) -> Option<Self> {
+        let terms: Self = serde_json::from_value(value?).ok()?;
+        (!terms.tiers.is_empty()).then_some(terms)
+    }
+
+    /// The highest tier a breach this late reaches. A breach shorter than
+    /// every tier's threshold owes nothing.
+    pub fn tier_for(&self, late_minutes: i64) -> Option<&SlaPenaltyTier> {
+        self.tiers
+            .iter()
+            .filter(|tier| tier.late_by_minutes <= late_minutes)
+            .max_by_key(|tier| tier.late_by_minutes)
+    }
+}
+
+/// When a ticket's SLA target was due and when it was met
+#[derive(Debug, Clone)]
+pub struct SlaOutcome {
+    pub ticket_id: Uuid,
+    pub ticket_number: String,
+    pub target: SlaTarget,
+    pub due: DateTime<Utc>,
+    /// First response, or resolution (the first move to a closed status when
+    /// the ticket has no resolution time); `None` if still outstanding
+    pub met_at: Option<DateTime<Utc>>,
+}
+
+impl SlaOutcome {
+    /// Minutes past due, or `None` if met in time. Outstanding targets
+    /// count as late up to `now`.
+    pub fn late_minutes(&self, now: DateTime<Utc>) -> Option<i64> {
+        let met_at = self.met_at.unwrap_or(now);
+        (met_at > self.due).then(|| (met_at - self.due).num_minutes().max(1))
+    }
+}
+
+/// A missed SLA target and the credit it earned
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SlaBreach {
+    pub ticket_id: Uuid,
+    pub ticket_number: String,
+    pub target: SlaTarget,
+    pub due: DateTime<Utc>,
+    pub late_minutes: i64,
+    pub credit_percent: Decimal,
+}
+
+/// Credits owed on one contract
+#[derive(Debug, Clone, Serialize)]
+pub struct ContractSlaPenalty {
+    pub contract_id: Uuid,
+    pub contract_name: String,
+    pub company_id: Uuid,
+    pub billing_amount: Option<Decimal>,
+    /// SLA targets due in the range
+    pub targets_measured: u64,
+    pub targets_met: u64,
+    pub breaches: Vec<SlaBreach>,
+    /// Sum of breach credits, after the contract's cap
+    pub credit_percent: Decimal,
+    /// `credit_percent` of the billing amount
+    pub credit_amount: Decimal,
+}
+
+impl ContractSlaPenalty {
+    pub fn calculate(
+        contract_id: Uuid,
+        contract_name: String,
+        company_id: Uuid,
+        billing_amount: Option<Decimal>,
+        terms: &SlaPenaltyTerms,
+        outcomes: &[SlaOutcome],
+        now: DateTime<Utc>,
+    ) -> Self {
+        let mut breaches = Vec::new();
+        for outcome in outcomes {
+            let Some(late_minutes) = outcome.late_minutes(now) else {
+                continue;
+            };
+            breaches.push(SlaBreach {
+                ticket_id: outcome.ticket_id,
+                ticket_number: outcome.ticket_number.clone(),
+                target: outcome.target,
+                due: outcome.due,
+                late_minutes,
+                credit_percent: terms
+                    .tier_for(late_minutes)
+                    .map_or(Decimal::ZERO, |tier| tier.credit_percent),
+            });
+        }
+
+        let mut credit_percent: Decimal = breaches.iter().map(|breach| breach.credit_percent).sum();
+        if let Some(max) = terms.max_credit_percent {
+            credit_percent = credit_percent.min(max);
+        }
+        let credit_amount = billing_amount
+            .map_or(Decimal::ZERO, |amount| (amount * credit_percent / Decimal::from(100)).round_dp(2));
+
+        Self {
+            contract_id,
+            contract_name,
+            company_id,
+            billing_amount,
+            targets_measured: outcomes.len() as u64,
+            targets_met: (outcomes.len() - breaches.len()) as u64,
+            breaches,
+            credit_percent,
+            credit_amount,
+        }
+    }
+}
+
+/// SLA credits owed across contracts with penalty terms, for targets due in
+/// the range
+#[derive(Debug, Clone, Serialize)]
+pub struct SlaPenaltyReport {
+    pub range: DateRange,
+    pub contracts: Vec<ContractSlaPenalty>,
+    pub total_credit: Decimal,
+}
+
+// ============================================================================
+// AGENT SCORECARD
+// ============================================================================
+
+/// A ticket an agent resolved, as the scorecard sees it
+#[derive(Debug, Clone)]
+pub struct ResolvedTicket {
+    pub created_at: DateTime<Utc>,
+    /// Resolution time, or when the ticket was closed if it has none
+    pub resolved_at: DateTime<Utc>,
+    /// Customer-visible replies and resolution notes the agent posted
+    pub agent_touches: i64,
+    /// Whether the ticket was closed before and reopened
+    pub reopened: bool,
+    /// `None` without SLA targets, otherwise whether all of them were met
+    pub sla_met: Option<bool>,
+}
+
+impl ResolvedTicket {
+    /// Resolved with at most one reply and never reopened
+    pub fn is_first_contact_resolution(&self) -> bool {
+        !self.reopened && self.agent_touches <= 1
+    }
+}
+
+/// Met and missed SLA targets
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct SlaCompliance {
+    pub met: u64,
+    pub breached: u64,
+    /// Share of tickets that met their targets as a percentage, `None`
+    /// without tickets under an SLA
+    pub rate: Option<f64>,
+}
+
+/// Resolution, SLA and satisfaction figures for one agent's profile
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentScorecard {
+    pub user_id: Uuid,
+    pub agent_name: String,
+    pub range: DateRange,
+    pub resolved: u64,
+    /// Resolved tickets that were never reopened
+    pub resolved_first_time: u64,
+    pub first_contact_resolutions: u64,
+    /// First contact resolutions as a percentage of resolved tickets
+    pub first_contact_resolution_rate: Option<f64>,
+    pub average_resolution_hours: Option<f64>,
+    pub sla: SlaCompliance,
+    pub csat: CsatScore,
+}
+
+impl AgentScorecard {
+    pub fn build(
+        user_id: Uuid,
+        agent_name: String,
+        range: DateRange,
+        tickets: &[ResolvedTicket],
+        csat: CsatScore,
+    ) -> Self {
+        let resolved = tickets.len() as u64;
+        let percent_of_resolved = |count: u64| (resolved > 0).then(|| count as f64 / resolved as f64 * 100.0);
+
+        let first_contact_resolutions = tickets.iter().filter(|t| t.is_first_contact_resolution()).count() as u64;
+        let total_minutes: i64 = tickets
+            .iter()
+            .map(|t| (t.resolved_at - t.created_at).num_minutes().max(0))
+            .sum();
+
+        let met = tickets.iter().filter(|t| t.sla_met == Some(true)).count() as u64;
+        let breached = tickets.iter().filter(|t| t.sla_met == Some(false)).count() as u64;
+
+        Self {
+            user_id,
+            agent_name,
+            range,
+            resolved,
+            resolved_first_time: tickets.iter().filter(|t| !t.reopened).count() as u64,
+            first_contact_resolutions,
+            first_contact_resolution_rate: percent_of_resolved(first_contact_resolutions),
+            average_resolution_hours: (resolved > 0).then(|| total_minutes as f64 / 60.0 / resolved as f64),
+            sla: SlaCompliance {
+                met,
+                breached,
+                rate: (met + breached > 0).then(|| met as f64 / (met + breached) as f64 * 100.0),
+            },
+            csat,
+        }
+    }
+}
+
+// ============================================================================
+// REVENUE RECOGNITION
+// ============================================================================
+
+/// How often a contract bills, from `contracts.billing_cycle`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BillingCycle {
+    #[default]
+    Monthly,
+    Quarterly,
+    Annually,
+    OneTime,
+}
+
+impl BillingCycle {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "monthly" => Some(Self::Monthly),
+            "quarterly" => Some(Self::Quarterly),
+            "annually" => Some(Self::Annually),
+            "one_time" => Some(Self::OneTime),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Monthly => "monthly",
+            Self::Quarterly => "quarterly",
+            Self::Annually => "annually",
+            Self::OneTime => "one_time",
+        }
+    }
+
+    /// Months one billing amount pays for; `None` for a one-time fee
+    pub fn months(&self) -> Option<u32> {
+        match self {
+            Self::Monthly => Some(1),
+            Self::Quarterly => Some(3),
+            Self::Annually => Some(12),
+            Self::OneTime => None,
+        }
+    }
+}
+
+/// Revenue recognized on the days of one calendar month a contract covers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecognitionPeriod {
+    /// First day of the month
+    pub month: NaiveDate,
+    /// First and last day of the term in this month
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub amount: Decimal,
+}
+
+impl RecognitionPeriod {
+    pub fn days(&self) -> i64 {
+        (self.end - self.start).num_days() + 1
+    }
+
+    /// The part of the amount on days from `from` up to, not including, `to`
+    pub fn within(&self, from: NaiveDate, to: NaiveDate) -> Decimal {
+        let overlap = (to.min(self.end + chrono::Duration::days(1)) - from.max(self.start)).num_days();
+        if overlap <= 0 {
+            Decimal::ZERO
+        } else if overlap >= self.days() {
+            self.amount
+        } else {
+            (self.amount * Decimal::from(overlap) / Decimal::from(self.days())).round_dp(2)
+        }
+    }
+}
+
+/// Spread `value` over the term from `start` to `end`, both inclusive, by
+/// calendar month. Every full month gets the same amount and a month the
+/// term only partly covers gets the share of its days covered. Rounding is
+/// settled in the last month so the periods add up to `value`.
+pub fn revenue_schedule(value: Decimal, start: NaiveDate, end: NaiveDate) -> Vec<RecognitionPeriod> {
+    let spans = month_spans(start, end);
+    let covered: Decimal = spans.iter().map(|span| span.3).sum();
+    if covered.is_zero() {
+        return Vec::new();
+    }
+
+    let last = spans.len() - 1;
+    let mut allocated = Decimal::ZERO;
+    let mut periods = Vec::with_capacity(spans.len());
+    for (i, (month, start, end, fraction)) in spans.into_iter().enumerate() {
+        let amount = if i == last {
+            value - allocated
+        } else {
+            (value * fraction / covered).round_dp(2)
+        };
+        allocated += amount;
+        periods.push(RecognitionPeriod {
+            month,
+            start,
+            end,
+            amount,
+        });
+    }
+    periods
+}
+
+/// Each month the term touches: its first day, the term's first and last
+/// day in it, and the fraction of the month's days those cover
+fn month_spans(start: NaiveDate, end: NaiveDate) -> Vec<(NaiveDate, NaiveDate, NaiveDate, Decimal)> {
+    let mut spans = Vec::new();
+    let Some(mut month) = start.with_day(1) else {
+        return spans;
+    };
+    while month <= end {
+        let next = month + Months::new(1);
+        let first = start.max(month);
+        let last = end.min(next - chrono::Duration::days(1));
+        let days = Decimal::from((last - first).num_days() + 1);
+        spans.push((month, first, last, days / Decimal::from((next - month).num_days())));
+        month = next;
+    }
+    spans
+}
+
+/// A contract's fee and term, as revenue recognition reads them
+#[derive(Debug, Clone)]
+pub struct ContractTerm {
+    pub contract_id: Uuid,
+    pub contract_name: String,
+    pub company_id: Uuid,
+    pub billing_cycle: BillingCycle,
+    pub billing_amount: Decimal,
+    pub start_date: NaiveDate,
+    /// Last day of the term; `None` for an open-ended contract
+    pub end_date: Option<NaiveDate>,
+}
+
+/// A contract's revenue spread over its term
+#[derive(Debug, Clone, Serialize)]
+pub struct RevenueSchedule {
+    pub contract_id: Uuid,
+    pub contract_name: String,
+    pub company_id: Uuid,
+    /// Total recognized over the scheduled term
+    pub value: Decimal,
+    pub periods: Vec<RecognitionPeriod>,
+}
+
+impl RevenueSchedule {
+    /// A recurring fee is earned month by month: the billing amount over the
+    /// months it pays for, prorated in a partial first or last month. A
+    /// one-time fee is spread over the term, or earned on the start date
+    /// when the contract has no end. Open-ended contracts are scheduled
+    /// through `until`.
+    pub fn for_contract(term: &ContractTerm, until: NaiveDate) -> Self {
+        let end = match (term.end_date, term.billing_cycle.months()) {
+            (Some(end), _) => end,
+            (None, Some(_)) => until,
+            (None, None) => term.start_date,
+        };
+
+        let value = match term.billing_cycle.months() {
+            Some(months) => {
+                let covered: Decimal = month_spans(term.start_date, end).iter().map(|span| span.3).sum();
+                (term.billing_amount * covered / Decimal::from(months)).round_dp(2)
+            }
+            None => term.billing_amount,
+        };
+
+        Self {
+            contract_id: term.contract_id,
+            contract_name: term.contract_name.clone(),
+            company_id: term.company_id,
+            value,
+            periods: revenue_schedule(value, term.start_date, end),
+        }
+    }
+
+    /// Revenue recognized on days from `from` up to, not including, `to`
+    pub fn recognized(&self, from: NaiveDate, to: NaiveDate) -> Decimal {
+        self.periods.iter().map(|period| period.within(from, to)).sum()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MonthlyRevenue {
+    /// First day of the month
+    pub month: NaiveDate,
+    pub amount: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ContractRevenue {
+    pub contract_id: Uuid,
+    pub contract_name: String,
+    pub company_id: Uuid,
+    pub recognized: Decimal,
+}
+
+/// Contract revenue earned in a range on an accrual basis, by month and by
+/// contract
+#[derive(Debug, Clone, Serialize)]
+pub struct RecognizedRevenueReport {
+    pub range: DateRange,
+    pub months: Vec<MonthlyRevenue>,
+    pub contracts: Vec<ContractRevenue>,
+    pub total: Decimal,
+}
+
+impl RecognizedRevenueReport {
+    /// `from` and `to` are the range's days in the tenant's time zone, `to`
+    /// exclusive
+    pub fn build(range: DateRange, from: NaiveDate, to: NaiveDate, schedules: &[RevenueSchedule]) -> Self {
+        let mut months: BTreeMap<NaiveDate, Decimal> = BTreeMap::new();
+        let mut contracts = Vec::new();
+        for schedule in schedules {
+            let mut recognized = Decimal::ZERO;
+            for period in &schedule.periods {
+                let amount = period.within(from, to);
+                if !amount.is_zero() {
+                    *months.entry(period.month).or_default() += amount;
+                    recognized += amount;
+                }
+            }
+            if !recognized.is_zero() {
+                contracts.push(ContractRevenue {
+                    contract_id: schedule.contract_id,
+                    contract_name: schedule.contract_name.clone(),
+                    company_id: schedule.company_id,
+                    recognized,
+                });
+            }
+        }
+
+        Self {
+            range,
+            total: contracts.iter().map(|contract| contract.recognized).sum(),
+            months: months
+                .into_iter()
+                .map(|(month, amount)| MonthlyRevenue { month, amount })
+                .collect(),
+            contracts,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date_range_is_valid() {
+        let now = Utc::now();
+        assert!(DateRange { from: now, to: now }.is_valid());
+        assert!(!DateRange { from: now, to: now - chrono::Duration::days(1) }.is_valid());
+    }
+
+    #[test]
+    fn test_resolution_code_breakdown() {
+        let breakdown = ResolutionCodeCount::breakdown(vec![
+            (Some("duplicate".to_string()), Some("Duplicate".to_string()), 1),
+            (Some("resolved".to_string()), Some("Resolved".to_string()), 6),
+            (None, None, 3),
+        ]);
+
+        assert_eq!(breakdown.len(), 3);
+        assert_eq!(breakdown[0].code.as_deref(), Some("resolved"));
+        assert_eq!(breakdown[0].count, 6);
+        assert!((breakdown[0].percentage - 60.0).abs() < 0.001);
+        assert_eq!(breakdown[1].name, "Unspecified");
+        assert_eq!(breakdown[2].name, "Duplicate");
+    }
+
+    #[test]
+    fn test_resolution_code_breakdown_empty() {
+        assert!(ResolutionCodeCount::breakdown(vec![]).is_empty());
+    }
+
+    #[test]
+    fn test_daily_series_fills_empty_days() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 3, d).unwrap();
+        let series = DailyTicketCount::series(
+            vec![day(13), day(14), day(15)],
+            vec![(day(14), 4), (day(13), 1)],
+            vec![(day(14), 2)],
+        );
+
+        assert_eq!(
+            series,
+            vec![
+                DailyTicketCount { date: day(13), created: 1, closed: 0 },
+                DailyTicketCount { date: day(14), created: 4, closed: 2 },
+                DailyTicketCount { date: day(15), created: 0, closed: 0 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_company_rollup_across_two_levels() {
+        use crate::modules::contacts::CompanyTreeNode;
+
+        let node = |id: Uuid, name: &str, parent: Option<Uuid>, depth: u32| CompanyTreeNode {
+            id,
+            name: name.to_string(),
+            parent_company_id: parent,
+            depth,
+        };
+        let (holding, east, west, store) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let tree = CompanyTree {
+            root_id: holding,
+            companies: vec![
+                node(holding, "Franchise Holdings", None, 0),
+                node(east, "East Region", Some(holding), 1),
+                node(west, "West Region", Some(holding), 1),
+                node(store, "Store 12", Some(east), 2),
+            ],
+        };
+
+        let activity = |tickets: u64, minutes: i64, invoiced: i64| CompanyActivity {
+            tickets_created: tickets,
+            tickets_closed: tickets / 2,
+            minutes,
+            billable_minutes: minutes / 2,
+            invoiced: Decimal::new(invoiced, 0),
+        };
+        let mut by_company = HashMap::new();
+        by_company.insert(holding, activity(1, 60, 100));
+        by_company.insert(east, activity(2, 30, 0));
+        by_company.insert(store, activity(4, 120, 250));
+        // West had no activity at all
+
+        let now = Utc::now();
+        let range = DateRange { from: now - chrono::Duration::days(30), to: now };
+        let report = CompanyRollupReport::build(range, &tree, &by_company, true);
+
+        assert_eq!(report.totals.tickets_created, 7);
+        assert_eq!(report.totals.minutes, 210);
+        assert_eq!(report.totals.invoiced, Decimal::new(350, 0));
+
+        let row = |id: Uuid| report.companies.iter().find(|r| r.company_id == id).unwrap();
+        assert_eq!(row(east).own.tickets_created, 2);
+        assert_eq!(row(east).rolled_up.tickets_created, 6);
+        assert_eq!(row(east).rolled_up.invoiced, Decimal::new(250, 0));
+        assert_eq!(row(west).rolled_up, CompanyActivity::default());
+        assert_eq!(row(store).rolled_up, row(store).own);
+        assert_eq!(row(holding).rolled_up, report.totals);
+    }
+
+    #[test]
+    fn test_csat_score_from_counts() {
+        let score = CsatScore::from_counts(3, 1);
+        assert_eq!(score.responses(), 4);
+        assert!((score.score.unwrap() - 75.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_csat_score_without_responses() {
+        assert_eq!(CsatScore::from_counts(0, 0).score, None);
+    }
+
+    #[test]
+    fn test_time_in_status_row_average() {
+        let row = TimeInStatusRow::from_totals((Uuid::nil(), "Pending".to_string(), None, None, 4, 36_000));
+        assert_eq!(row.total_seconds, 36_000);
+        assert!((row.average_seconds - 9_000.0).abs() < 0.001);
+
+        let empty = TimeInStatusRow::from_totals((Uuid::nil(), "Open".to_string(), None, None, 0, 0));
+        assert_eq!(empty.average_seconds, 0.0);
+        assert_eq!(TimeInStatusGroupBy::from_str("queue"), Some(TimeInStatusGroupBy::Queue));
+    }
+
+    fn effort(group_id: Option<Uuid>, estimated: Option<i64>, actual: i64) -> EffortTicket {
+        EffortTicket {
+            group_id,
+            group_name: group_id.map(|_| "Service Desk".to_string()),
+            estimated_hours: estimated.map(|hours| Decimal::new(hours, 1)),
+            actual_hours: Decimal::new(actual, 1),
+        }
+    }
+
+    #[test]
+    fn test_effort_variance_against_estimate() {
+        let queue = Some(Uuid::new_v4());
+        // 4h and 6h estimated, 5h and 7.5h spent
+        let report = EffortSummaryReport::build(
+            EffortGroupBy::Queue,
+            vec![effort(queue, Some(40), 50), effort(queue, Some(60), 75)],
+        );
+
+        let totals = &report.rows[0].totals;
+        assert_eq!(totals.estimated_hours, Decimal::new(10, 0));
+        assert_eq!(totals.actual_hours, Decimal::new(125, 1));
+        assert_eq!(totals.variance_hours, Decimal::new(25, 1));
+        assert_eq!(totals.variance_percent, Some(Decimal::new(250, 1)));
+
+        // Under estimate reads as negative
+        let under = EffortSummaryReport::build(EffortGroupBy::Queue, vec![effort(queue, Some(80), 60)]);
+        assert_eq!(under.totals.variance_hours, Decimal::new(-2, 0));
+        assert_eq!(under.totals.variance_percent, Some(Decimal::new(-250, 1)));
+        assert_eq!(EffortGroupBy::from_str("company"), Some(EffortGroupBy::Company));
+    }
+
+    #[test]
+    fn test_unestimated_tickets_count_toward_actuals_only() {
+        let queue = Some(Uuid::new_v4());
+        let report = EffortSummaryReport::build(
+            EffortGroupBy::Queue,
+            vec![effort(queue, Some(20), 30), effort(queue, None, 50), effort(None, None, 10)],
+        );
+
+        assert_eq!(report.rows.len(), 2);
+        let totals = &report.rows[0].totals;
+        assert_eq!(totals.tickets, 2);
+        assert_eq!(totals.estimated_tickets, 1);
+        assert_eq!(totals.estimated_hours, Decimal::new(2, 0));
+        assert_eq!(totals.actual_hours, Decimal::new(8, 0));
+        // Only the estimated ticket's 3h counts against its 2h estimate
+        assert_eq!(totals.variance_hours, Decimal::ONE);
+        assert_eq!(totals.variance_percent, Some(Decimal::new(500, 1)));
+
+        // A group with no estimates has actuals but no variance
+        let unassigned = &report.rows[1];
+        assert_eq!(unassigned.group_id, None);
+        assert_eq!(unassigned.totals.actual_hours, Decimal::ONE);
+        assert_eq!(unassigned.totals.estimated_hours, Decimal::ZERO);
+        assert_eq!(unassigned.totals.variance_hours, Decimal::ZERO);
+        assert_eq!(unassigned.totals.variance_percent, None);
+
+        assert_eq!(report.totals.tickets, 3);
+        assert_eq!(report.totals.actual_hours, Decimal::new(9, 0));
+        assert_eq!(report.totals.estimated_hours, Decimal::new(2, 0));
+    }
+
+    #[test]
+    fn test_deflection_rate_from_sessions() {
+        let now = Utc::now();
+        let range = DateRange { from: now - chrono::Duration::days(1), to: now };
+        let minutes_ago = |minutes: i64| now - chrono::Duration::minutes(minutes);
+        let event = |session_id: Uuid, kind: DeflectionEventKind, minutes: i64| DeflectionEvent {
+            session_id,
+            kind,
+            occurred_at: minutes_ago(minutes),
+        };
+        let (abandoned, submitted, also_abandoned, idle, in_progress, no_view) = (
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+        );
+
+        let events = vec![
+            // Viewed, then walked away
+            event(abandoned, DeflectionEventKind::ArticleViewed, 120),
+            event(abandoned, DeflectionEventKind::TicketAbandoned, 118),
+            // Viewed two articles, then submitted anyway
+            event(submitted, DeflectionEventKind::ArticleViewed, 100),
+            event(submitted, DeflectionEventKind::TicketSubmitted, 95),
+            event(submitted, DeflectionEventKind::ArticleViewed, 97),
+            event(also_abandoned, DeflectionEventKind::TicketAbandoned, 80),
+            event(also_abandoned, DeflectionEventKind::ArticleViewed, 82),
+            // Never reported leaving; idle past the timeout
+            event(idle, DeflectionEventKind::ArticleViewed, 90),
+            // Still reading
+            event(in_progress, DeflectionEventKind::ArticleViewed, 5),
+            // Submitted without looking at a suggestion
+            event(no_view, DeflectionEventKind::TicketSubmitted, 60),
+        ];
+
+        let report = DeflectionReport::from_events(range, &events, now);
+        assert_eq!(report.submitted, 1);
+        assert_eq!(report.deflected, 3);
+        assert!((report.rate.unwrap() - 75.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_deflection_rate_without_views() {
+        let now = Utc::now();
+        let range = DateRange { from: now - chrono::Duration::days(1), to: now };
+        let events = vec![DeflectionEvent {
+            session_id: Uuid::new_v4(),
+            kind: DeflectionEventKind::TicketSubmitted,
+            occurred_at: now,
+        }];
+
+        let report = DeflectionReport::from_events(range, &events, now);
+        assert_eq!((report.submitted, report.deflected, report.rate), (0, 0, None));
+    }
+
+    #[test]
+    fn test_part_timer_utilization_uses_their_hours() {
+        use crate::modules::calendar::AvailabilityWindow;
+        use crate::utils::timezone::TenantTimezone;
+
+        // Monday 2025-03-10 to Sunday 2025-03-16
+        let monday = NaiveDate::from_ymd_opt(2025, 3, 10).unwrap();
+        let week: Vec<NaiveDate> = monday.iter_days().take(7).collect();
+        let morning = |day_of_week| AvailabilityWindow {
+            day_of_week,
+            start_time: chrono::NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            end_time: chrono::NaiveTime::from_hms_opt(13, 0, 0).unwrap(),
+        };
+        let part_time = WorkingHours {
+            timezone: TenantTimezone::default(),
+            windows: vec![morning(1), morning(3), morning(5)],
+        };
+        let full_time = WorkingHours::standard(TenantTimezone::default());
+
+        // Three four-hour mornings
+        let part_timer = TechnicianUtilization::new(Uuid::new_v4(), "Sam".to_string(), &part_time, &week, 600, 540);
+        assert_eq!(part_timer.available_minutes, 12 * 60);
+        assert!((part_timer.utilization.unwrap() - 75.0).abs() < 0.001);
+
+        // The same hours against a full week look like a light load
+        let full_timer = TechnicianUtilization::new(Uuid::new_v4(), "Alex".to_string(), &full_time, &week, 600, 540);
+        assert_eq!(full_timer.available_minutes, 40 * 60);
+        assert!((full_timer.utilization.unwrap() - 22.5).abs() < 0.001);
+
+        let no_hours = WorkingHours { timezone: TenantTimezone::default(), windows: vec![] };
+        assert_eq!(TechnicianUtilization::new(Uuid::nil(), String::new(), &no_hours, &week, 0, 0).utilization, None);
+    }
+
+    #[test]
+    fn test_sla_penalties() {
+        let due = DateTime::parse_from_rfc3339("2025-03-10T12:00:00Z").unwrap().with_timezone(&Utc);
+        let now = due + chrono::Duration::days(2);
+        let minutes = chrono::Duration::minutes;
+        let outcome = |number: &str, target: SlaTarget, met_at: Option<DateTime<Utc>>| SlaOutcome {
+            ticket_id: Uuid::new_v4(),
+            ticket_number: number.to_string(),
+            target,
+            due,
+            met_at,
+        };
+        let terms = SlaPenaltyTerms::from_value(Some(serde_json::json!({
+            "tiers": [
+                { "late_by_minutes": 0, "credit_percent": "2" },
+                { "late_by_minutes": 240, "credit_percent": "5" },
+            ],
+            "max_credit_percent": "10",
+        })))
+        .unwrap();
+
+        let outcomes = vec![
+            // Met, including exactly on time
+            outcome("T000001", SlaTarget::FirstResponse, Some(due - minutes(30))),
+            outcome("T000002", SlaTarget::Resolution, Some(due)),
+            // Breached in the first tier
+            outcome("T000003", SlaTarget::FirstResponse, Some(due + minutes(45))),
+            // Breached in the second tier
+            outcome("T000004", SlaTarget::Resolution, Some(due + minutes(300))),
+            // Never met: late until now
+            outcome("T000005", SlaTarget::Resolution, None),
+        ];
+
+        let penalty = ContractSlaPenalty::calculate(
+            Uuid::new_v4(),
+            "Managed services".to_string(),
+            Uuid::new_v4(),
+            Some(Decimal::from(2500)),
+            &terms,
+            &outcomes,
+            now,
+        );
+        assert_eq!(penalty.targets_measured, 5);
+        assert_eq!(penalty.targets_met, 2);
+        let credits: Vec<(&str, i64, Decimal)> = penalty
+            .breaches
+            .iter()
+            .map(|breach| (breach.ticket_number.as_str(), breach.late_minutes, breach.credit_percent))
+            .collect();
+        assert_eq!(
+            credits,
+            vec![
+                ("T000003", 45, Decimal::from(2)),
+                ("T000004", 300, Decimal::from(5)),
+                ("T000005", 2880, Decimal::from(5)),
+            ]
+        );
+
+        // 12% is capped at 10% of the billing amount
+        assert_eq!(penalty.credit_percent, Decimal::from(10));
+        assert_eq!(penalty.credit_amount, Decimal::from(250));
+
+        // Without a cap, or a first tier that forgives short breaches
+        let uncapped = SlaPenaltyTerms {
+            max_credit_percent: None,
+            ..terms.clone()
+        };
+        let penalty = ContractSlaPenalty::calculate(
+            Uuid::new_v4(),
+            "Managed services".to_string(),
+            Uuid::new_v4(),
+            Some(Decimal::from(2500)),
+            &uncapped,
+            &outcomes,
+            now,
+        );
+        assert_eq!(penalty.credit_amount, Decimal::from(300));
+
+        let lenient = SlaPenaltyTerms {
+            tiers: vec![SlaPenaltyTier { late_by_minutes: 60, credit_percent: Decimal::from(3) }],
+            max_credit_percent: None,
+        };
+        assert_eq!(lenient.tier_for(45), None);
+        assert_eq!(lenient.tier_for(60).map(|tier| tier.credit_percent), Some(Decimal::from(3)));
+
+        // Contracts without usable terms aren't reported
+        assert!(SlaPenaltyTerms::from_value(None).is_none());
+        assert!(SlaPenaltyTerms::from_value(Some(serde_json::json!({ "tiers": [] }))).is_none());
+        assert!(SlaPenaltyTerms::from_value(Some(serde_json::json!("2%"))).is_none());
+    }
+
+    fn resolved_ticket(hours: i64, agent_touches: i64, reopened: bool, sla_met: Option<bool>) -> ResolvedTicket {
+        let created_at = Utc::now() - chrono::Duration::days(3);
+        ResolvedTicket {
+            created_at,
+            resolved_at: created_at + chrono::Duration::hours(hours),
+            agent_touches,
+            reopened,
+            sla_met,
+        }
+    }
+
+    #[test]
+    fn test_first_contact_resolution_counts_one_touch_tickets() {
+        let one_touch = resolved_ticket(2, 1, false, Some(true));
+        let multi_touch = resolved_ticket(10, 3, false, Some(false));
+        let reopened = resolved_ticket(6, 1, true, None);
+        let silent = resolved_ticket(2, 0, false, Some(true));
+        assert!(one_touch.is_first_contact_resolution());
+        assert!(!multi_touch.is_first_contact_resolution());
+        // One reply, but the customer came back
+        assert!(!reopened.is_first_contact_resolution());
+        assert!(silent.is_first_contact_resolution());
+
+        let now = Utc::now();
+        let range = DateRange { from: now - chrono::Duration::days(30), to: now };
+        let scorecard = AgentScorecard::build(
+            Uuid::new_v4(),
+            "Sam Tech".to_string(),
+            range,
+            &[one_touch, multi_touch, reopened, silent],
+            CsatScore::from_counts(3, 1),
+        );
+
+        assert_eq!(scorecard.resolved, 4);
+        assert_eq!(scorecard.resolved_first_time, 3);
+        assert_eq!(scorecard.first_contact_resolutions, 2);
+        assert_eq!(scorecard.first_contact_resolution_rate, Some(50.0));
+        assert_eq!(scorecard.average_resolution_hours, Some(5.0));
+        assert_eq!(scorecard.sla.met, 2);
+        assert_eq!(scorecard.sla.breached, 1);
+        assert!((scorecard.sla.rate.unwrap() - 66.667).abs() < 0.001);
+        assert_eq!(scorecard.csat.score, Some(75.0));
+    }
+
+    #[test]
+    fn test_empty_scorecard_has_no_rates() {
+        let now = Utc::now();
+        let scorecard = AgentScorecard::build(
+            Uuid::new_v4(),
+            "Sam Tech".to_string(),
+            DateRange { from: now, to: now },
+            &[],
+            CsatScore::default(),
+        );
+
+        assert_eq!(scorecard.resolved, 0);
+        assert_eq!(scorecard.first_contact_resolution_rate, None);
+        assert_eq!(scorecard.average_resolution_hours, None);
+        assert_eq!(scorecard.sla, SlaCompliance::default());
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_revenue_recognized_evenly_by_month() {
+        let periods = revenue_schedule(Decimal::from(12000), date(2025, 1, 1), date(2025, 12, 31));
+
+        assert_eq!(periods.len(), 12);
+        assert!(periods.iter().all(|period| period.amount == Decimal::from(1000)));
+        assert_eq!(periods[1].month, date(2025, 2, 1));
+        assert_eq!(periods[1].end, date(2025, 2, 28));
+
+        // A monthly contract earns its fee each month, whatever the month's length
+        let term = ContractTerm {
+            contract_id: Uuid::new_v4(),
+            contract_name: "Managed services".to_string(),
+            company_id: Uuid::new_v4(),
+            billing_cycle: BillingCycle::Quarterly,
+            billing_amount: Decimal::from(3000),
+            start_date: date(2025, 1, 1),
+            end_date: None,
+        };
+        let schedule = RevenueSchedule::for_contract(&term, date(2025, 6, 30));
+        assert_eq!(schedule.value, Decimal::from(6000));
+        assert!(schedule.periods.iter().all(|period| period.amount == Decimal::from(1000)));
+        assert_eq!(schedule.recognized(date(2025, 2, 1), date(2025, 4, 1)), Decimal::from(2000));
+    }
+
+    #[test]
+    fn test_revenue_prorated_for_partial_first_and_last_months() {
+        // Three months from mid-January: half of January and half of April
+        let value = Decimal::from(3000);
+        let periods = revenue_schedule(value, date(2025, 1, 16), date(2025, 4, 15));
+
+        assert_eq!(periods.len(), 4);
+        assert_eq!(periods[0].start, date(2025, 1, 16));
+        assert_eq!(periods[0].days(), 16);
+        assert_eq!(periods[3].end, date(2025, 4, 15));
+
+        let full = periods[1].amount;
+        assert_eq!(periods[2].amount, full);
+        assert_eq!(periods[0].amount, (full * Decimal::from(16) / Decimal::from(31)).round_dp(2));
+        assert!((periods[3].amount - full / Decimal::from(2)).abs() <= Decimal::new(1, 2));
+        assert_eq!(periods.iter().map(|period| period.amount).sum::<Decimal>(), value);
+
+        // A monthly fee starting mid-month earns a prorated first month
+        let term = ContractTerm {
+            contract_id: Uuid::new_v4(),
+            contract_name: "Backup".to_string(),
+            company_id: Uuid::new_v4(),
+            billing_cycle: BillingCycle::Monthly,
+            billing_amount: Decimal::from(300),
+            start_date: date(2025, 6, 21),
+            end_date: Some(date(2025, 8, 31)),
+        };
+        let schedule = RevenueSchedule::for_contract(&term, date(2025, 12, 31));
+        let amounts: Vec<Decimal> = schedule.periods.iter().map(|period| period.amount).collect();
+        assert_eq!(amounts, vec![Decimal::from(100), Decimal::from(300), Decimal::from(300)]);
+
+        let now = Utc::now();
+        let report = RecognizedRevenueReport::build(
+            DateRange { from: now, to: now },
+            date(2025, 7, 1),
+            date(2025, 8, 1),
+            &[schedule],
+        );
+        assert_eq!(report.total, Decimal::from(300));
+        assert_eq!(report.months, vec![MonthlyRevenue { month: date(2025, 7, 1), amount: Decimal::from(300) }]);
+    }
+}
@@ -0,0 +1,175 @@
+//! Report API routes
+
+use axum::{
+    extract::{Path, Query, State},
+    routing::get,
+    Json, Router,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::{
+    AgentScorecard, CompanyRollupReport, CsatSummaryReport, DateRange, DeflectionReport, EffortFilter,
+    EffortSummaryReport, RecognizedRevenueReport, ReportService, SlaPenaltyReport, TicketVolumeReport,
+    TimeInStatusParams, TimeInStatusReport, UtilizationReport,
+};
+use crate::modules::auth::{RequireAuth, RequireFinance};
+use crate::utils::error::AppResult;
+
+#[derive(Clone)]
+pub struct ReportRouterState {
+    pub report_service: Arc<ReportService>,
+}
+
+/// Create the reports router
+pub fn report_routes(report_service: ReportService) -> Router {
+    let state = ReportRouterState {
+        report_service: Arc::new(report_service),
+    };
+
+    Router::new()
+        .route("/ticket-volume", get(ticket_volume))
+        .route("/csat", get(csat_summary))
+        .route("/time-in-status", get(time_in_status))
+        .route("/effort", get(effort_summary))
+        .route("/deflection", get(deflection_rate))
+        .route("/utilization", get(utilization))
+        .route("/sla-penalties", get(sla_penalties))
+        .route("/recognized-revenue", get(recognized_revenue))
+        .route("/companies/:company_id/rollup", get(company_rollup))
+        .route("/agents/:user_id/scorecard", get(agent_scorecard))
+        .with_state(state)
+}
+
+async fn ticket_volume(
+    State(state): State<ReportRouterState>,
+    RequireAuth(user): RequireAuth,
+    Query(range): Query<DateRange>,
+) -> AppResult<Json<TicketVolumeReport>> {
+    let report = state
+        .report_service
+        .ticket_volume(user.tenant_id, &range)
+        .await?;
+
+    Ok(Json(report))
+}
+
+async fn csat_summary(
+    State(state): State<ReportRouterState>,
+    RequireAuth(user): RequireAuth,
+    Query(range): Query<DateRange>,
+) -> AppResult<Json<CsatSummaryReport>> {
+    let report = state
+        .report_service
+        .csat_summary(user.tenant_id, &range)
+        .await?;
+
+    Ok(Json(report))
+}
+
+async fn time_in_status(
+    State(state): State<ReportRouterState>,
+    RequireAuth(user): RequireAuth,
+    Query(range): Query<DateRange>,
+    Query(params): Query<TimeInStatusParams>,
+) -> AppResult<Json<TimeInStatusReport>> {
+    let report = state
+        .report_service
+        .time_in_status(user.tenant_id, &range, params.group_by)
+        .await?;
+
+    Ok(Json(report))
+}
+
+async fn effort_summary(
+    State(state): State<ReportRouterState>,
+    RequireAuth(user): RequireAuth,
+    Query(filter): Query<EffortFilter>,
+) -> AppResult<Json<EffortSummaryReport>> {
+    let report = state
+        .report_service
+        .effort_summary(user.tenant_id, &filter)
+        .await?;
+
+    Ok(Json(report))
+}
+
+async fn agent_scorecard(
+    State(state): State<ReportRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(user_id): Path<Uuid>,
+    Query(range): Query<DateRange>,
+) -> AppResult<Json<AgentScorecard>> {
+    let scorecard = state
+        .report_service
+        .agent_scorecard(user.tenant_id, user_id, &range)
+        .await?;
+
+    Ok(Json(scorecard))
+}
+
+async fn utilization(
+    State(state): State<ReportRouterState>,
+    RequireAuth(user): RequireAuth,
+    Query(range): Query<DateRange>,
+) -> AppResult<Json<UtilizationReport>> {
+    let report = state
+        .report_service
+        .utilization(user.tenant_id, &range)
+        .await?;
+
+    Ok(Json(report))
+}
+
+async fn deflection_rate(
+    State(state): State<ReportRouterState>,
+    RequireAuth(user): RequireAuth,
+    Query(range): Query<DateRange>,
+) -> AppResult<Json<DeflectionReport>> {
+    let report = state
+        .report_service
+        .deflection_rate(user.tenant_id, &range)
+        .await?;
+
+    Ok(Json(report))
+}
+
+async fn sla_penalties(
+    State(state): State<ReportRouterState>,
+    RequireFinance(user, _): RequireFinance,
+    Query(range): Query<DateRange>,
+) -> AppResult<Json<SlaPenaltyReport>> {
+    let report = state
+        .report_service
+        .sla_penalties(user.tenant_id, &range)
+        .await?;
+
+    Ok(Json(report))
+}
+
+async fn recognized_revenue(
+    State(state): State<ReportRouterState>,
+    RequireFinance(user, _): RequireFinance,
+    Query(range): Query<DateRange>,
+) -> AppResult<Json<RecognizedRevenueReport>> {
+    let report = state
+        .report_service
+        .recognized_revenue(user.tenant_id, &range)
+        .await?;
+
+    Ok(Json(report))
+}
+
+async fn company_rollup(
+    State(state): State<ReportRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(company_id): Path<Uuid>,
+    Query(range): Query<DateRange>,
+) -> AppResult<Json<CompanyRollupReport>> {
+    let report = state
+        .report_service
+        .company_rollup(user.tenant_id, company_id, &range, user.role.can_view_financials())
+        .await?;
+
+    Ok(Json(report))
+}
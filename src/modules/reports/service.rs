@@ -0,0 +1,742 @@
+//! Report service implementation
+
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::modules::calendar::CalendarService;
+use crate::modules::contacts::ContactService;
+use crate::modules::portal::DeflectionEventKind;
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::timezone::TenantTimezone;
+
+use super::models::*;
+
+/// Reporting service
+#[derive(Clone)]
+pub struct ReportService {
+    db: Database,
+    calendar: CalendarService,
+}
+
+impl ReportService {
+    pub fn new(db: Database) -> Self {
+        Self {
+            calendar: CalendarService::new(db.clone()),
+            db,
+        }
+    }
+
+    /// Ticket volume for a date range, with a resolution code breakdown of closed tickets
+    pub async fn ticket_volume(
+        &self,
+        tenant_id: Uuid,
+        range: &DateRange,
+    ) -> AppResult<TicketVolumeReport> {
+        if !range.is_valid() {
+            return Err(AppError::BadRequest("Range start must be before its end".to_string()));
+        }
+
+        let created: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM tickets WHERE tenant_id = $1 AND created_at >= $2 AND created_at < $3",
+        )
+        .bind(tenant_id)
+        .bind(range.from)
+        .bind(range.to)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        let closed: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM tickets WHERE tenant_id = $1 AND closed_at >= $2 AND closed_at < $3",
+        )
+        .bind(tenant_id)
+        .bind(range.from)
+        .bind(range.to)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        let rows = sqlx::query_as::<_, (Option<String>, Option<String>, i64)>(
+            r#"
+            SELECT t.resolution_code, rc.name, COUNT(*)
+            FROM tickets t
+            LEFT JOIN ticket_resolution_codes rc
+                ON rc.tenant_id = t.tenant_id AND rc.code = t.resolution_code
+            WHERE t.tenant_id = $1 AND t.closed_at >= $2 AND t.closed_at < $3
+            GROUP BY t.resolution_code, rc.name
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(range.from)
+        .bind(range.to)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let timezone = TenantTimezone::load(self.db.pool(), tenant_id).await?;
+
+        let created_by_day = sqlx::query_as::<_, (chrono::NaiveDate, i64)>(
+            r#"
+            SELECT (created_at AT TIME ZONE $4)::date AS day, COUNT(*)
+            FROM tickets
+            WHERE tenant_id = $1 AND created_at >= $2 AND created_at < $3
+            GROUP BY day
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(range.from)
+        .bind(range.to)
+        .bind(timezone.name())
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let closed_by_day = sqlx::query_as::<_, (chrono::NaiveDate, i64)>(
+            r#"
+            SELECT (closed_at AT TIME ZONE $4)::date AS day, COUNT(*)
+            FROM tickets
+            WHERE tenant_id = $1 AND closed_at >= $2 AND closed_at < $3
+            GROUP BY day
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(range.from)
+        .bind(range.to)
+        .bind(timezone.name())
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(TicketVolumeReport {
+            range: *range,
+            timezone: timezone.name().to_string(),
+            created: created as u64,
+            closed: closed as u64,
+            by_resolution_code: ResolutionCodeCount::breakdown(rows),
+            daily: DailyTicketCount::series(
+                timezone.days_between(range.from, range.to),
+                created_by_day,
+                closed_by_day,
+            ),
+        })
+    }
+
+    /// Time tickets spent in each status during a date range. Stays that
+    /// started before or are still open after the range count only their part
+    /// inside it.
+    pub async fn time_in_status(
+        &self,
+        tenant_id: Uuid,
+        range: &DateRange,
+        group_by: TimeInStatusGroupBy,
+    ) -> AppResult<TimeInStatusReport> {
+        if !range.is_valid() {
+            return Err(AppError::BadRequest("Range start must be before its end".to_string()));
+        }
+
+        let (group_id, group_name, group_join) = group_by.sql();
+        let query = format!(
+            r#"
+            SELECT h.status_id, s.name, {group_id}, {group_name},
+                   COUNT(DISTINCT h.ticket_id),
+                   COALESCE(SUM(EXTRACT(EPOCH FROM
+                       LEAST(COALESCE(h.left_at, NOW()), $3) - GREATEST(h.entered_at, $2)
+                   )), 0)::BIGINT
+            FROM ticket_status_history h
+            JOIN ticket_statuses s ON s.id = h.status_id
+            JOIN tickets t ON t.id = h.ticket_id
+            {group_join}
+            WHERE h.tenant_id = $1 AND h.entered_at < $3 AND COALESCE(h.left_at, NOW()) > $2
+            GROUP BY h.status_id, s.name, s.sort_order, {group_id}, {group_name}
+            ORDER BY s.sort_order, s.name, {group_name}
+            "#,
+        );
+
+        let rows = sqlx::query_as::<_, (Uuid, String, Option<Uuid>, Option<String>, i64, i64)>(&query)
+            .bind(tenant_id)
+            .bind(range.from)
+            .bind(range.to)
+            .fetch_all(self.db.pool())
+            .await?;
+
+        Ok(TimeInStatusReport {
+            range: *range,
+            group_by,
+            rows: rows.into_iter().map(TimeInStatusRow::from_totals).collect(),
+        })
+    }
+
+    /// Estimated against actual hours across the filtered tickets, grouped
+    /// by their current queue, company or assignee
+    pub async fn effort_summary(&self, tenant_id: Uuid, filter: &EffortFilter) -> AppResult<EffortSummaryReport> {
+        let (group_id, group_name, group_join) = filter.group_by.sql();
+        let query = format!(
+            r#"
+            SELECT {group_id}, {group_name}, t.estimated_hours, COALESCE(t.actual_hours, 0)
+            FROM tickets t
+            {group_join}
+            WHERE t.tenant_id = $1
+              AND ($2::UUID IS NULL OR t.queue_id = $2)
+              AND ($3::UUID IS NULL OR t.company_id = $3)
+              AND ($4::UUID IS NULL OR t.assigned_to_id = $4)
+              AND ($5::BOOLEAN IS NULL OR $5 = NOT EXISTS (
+                  SELECT 1 FROM ticket_statuses s WHERE s.id = t.status_id AND s.is_closed = TRUE
+              ))
+              AND ($6::TIMESTAMPTZ IS NULL OR t.created_at >= $6)
+              AND ($7::TIMESTAMPTZ IS NULL OR t.created_at < $7)
+            ORDER BY {group_name} NULLS LAST, t.created_at
+            "#,
+        );
+
+        let tickets = sqlx::query_as::<_, (Option<Uuid>, Option<String>, Option<Decimal>, Decimal)>(&query)
+            .bind(tenant_id)
+            .bind(filter.queue_id)
+            .bind(filter.company_id)
+            .bind(filter.assigned_to_id)
+            .bind(filter.is_open)
+            .bind(filter.created_from)
+            .bind(filter.created_to)
+            .fetch_all(self.db.pool())
+            .await?
+            .into_iter()
+            .map(|(group_id, group_name, estimated_hours, actual_hours)| EffortTicket {
+                group_id,
+                group_name,
+                estimated_hours,
+                actual_hours,
+            })
+            .collect();
+
+        Ok(EffortSummaryReport::build(filter.group_by, tickets))
+    }
+
+    /// Billable time per technician against their own working hours, or the
+    /// standard workday for technicians without a schedule. Days are counted
+    /// in each technician's time zone.
+    pub async fn utilization(&self, tenant_id: Uuid, range: &DateRange) -> AppResult<UtilizationReport> {
+        if !range.is_valid() {
+            return Err(AppError::BadRequest("Range start must be before its end".to_string()));
+        }
+
+        let users = sqlx::query_as::<_, (Uuid, String)>(
+            r#"
+            SELECT u.id, u.first_name || ' ' || u.last_name
+            FROM users u
+            WHERE u.tenant_id = $1 AND u.status = 'active'
+              AND (u.role = 'technician' OR EXISTS (
+                  SELECT 1 FROM time_entries te
+                  WHERE te.tenant_id = u.tenant_id AND te.user_id = u.id
+                    AND te.date >= $2::date AND te.date <= $3::date
+              ))
+            ORDER BY u.last_name, u.first_name
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(range.from)
+        .bind(range.to)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let mut technicians = Vec::with_capacity(users.len());
+        for (user_id, name) in users {
+            let hours = self.calendar.working_hours(tenant_id, user_id).await?.or_standard();
+            let days = hours.timezone.days_between(range.from, range.to);
+            let (Some(first_day), Some(last_day)) = (days.first(), days.last()) else {
+                continue;
+            };
+
+            let (logged, billable): (i64, i64) = sqlx::query_as(
+                r#"
+                SELECT COALESCE(SUM(duration_minutes), 0)::BIGINT,
+                       COALESCE(SUM(duration_minutes) FILTER (WHERE is_billable), 0)::BIGINT
+                FROM time_entries
+                WHERE tenant_id = $1 AND user_id = $2 AND date >= $3 AND date <= $4
+                "#,
+            )
+            .bind(tenant_id)
+            .bind(user_id)
+            .bind(first_day)
+            .bind(last_day)
+            .fetch_one(self.db.pool())
+            .await?;
+
+            technicians.push(TechnicianUtilization::new(user_id, name, &hours, &days, logged, billable));
+        }
+
+        Ok(UtilizationReport {
+            range: *range,
+            technicians,
+        })
+    }
+
+    /// Share of portal new-ticket sessions where the contact read a suggested
+    /// article and then didn't submit, for sessions whose first article view
+    /// falls in the range
+    pub async fn deflection_rate(&self, tenant_id: Uuid, range: &DateRange) -> AppResult<DeflectionReport> {
+        if !range.is_valid() {
+            return Err(AppError::BadRequest("Range start must be before its end".to_string()));
+        }
+
+        let rows = sqlx::query_as::<_, (Uuid, String, chrono::DateTime<chrono::Utc>)>(
+            r#"
+            SELECT session_id, event_type, occurred_at
+            FROM portal_deflection_events
+            WHERE tenant_id = $1 AND session_id IN (
+                SELECT session_id
+                FROM portal_deflection_events
+                WHERE tenant_id = $1 AND event_type = 'article_viewed'
+                GROUP BY session_id
+                HAVING MIN(occurred_at) >= $2 AND MIN(occurred_at) < $3
+            )
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(range.from)
+        .bind(range.to)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let events: Vec<DeflectionEvent> = rows
+            .into_iter()
+            .filter_map(|(session_id, event_type, occurred_at)| {
+                Some(DeflectionEvent {
+                    session_id,
+                    kind: DeflectionEventKind::from_str(&event_type)?,
+                    occurred_at,
+                })
+            })
+            .collect();
+
+        Ok(DeflectionReport::from_events(*range, &events, chrono::Utc::now()))
+    }
+
+    /// Survey results for responses received in a date range, by technician and week
+    pub async fn csat_summary(
+        &self,
+        tenant_id: Uuid,
+        range: &DateRange,
+    ) -> AppResult<CsatSummaryReport> {
+        if !range.is_valid() {
+            return Err(AppError::BadRequest("Range start must be before its end".to_string()));
+        }
+
+        let (positive, negative) = sqlx::query_as::<_, (i64, i64)>(
+            r#"
+            SELECT COUNT(*) FILTER (WHERE rating = 'positive'),
+                   COUNT(*) FILTER (WHERE rating = 'negative')
+            FROM csat_surveys
+            WHERE tenant_id = $1 AND responded_at >= $2 AND responded_at < $3
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(range.from)
+        .bind(range.to)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        let technician_rows = sqlx::query_as::<_, (Option<Uuid>, Option<String>, i64, i64)>(
+            r#"
+            SELECT s.technician_id, u.first_name || ' ' || u.last_name,
+                   COUNT(*) FILTER (WHERE s.rating = 'positive'),
+                   COUNT(*) FILTER (WHERE s.rating = 'negative')
+            FROM csat_surveys s
+            LEFT JOIN users u ON u.id = s.technician_id
+            WHERE s.tenant_id = $1 AND s.responded_at >= $2 AND s.responded_at < $3
+            GROUP BY s.technician_id, u.first_name, u.last_name
+            ORDER BY COUNT(*) DESC
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(range.from)
+        .bind(range.to)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        // Weeks start at local midnight on Monday
+        let timezone = TenantTimezone::load(self.db.pool(), tenant_id).await?;
+        let trend_rows = sqlx::query_as::<_, (chrono::DateTime<chrono::Utc>, i64, i64)>(
+            r#"
+            SELECT date_trunc('week', responded_at AT TIME ZONE $4) AT TIME ZONE $4 AS week_start,
+                   COUNT(*) FILTER (WHERE rating = 'positive'),
+                   COUNT(*) FILTER (WHERE rating = 'negative')
+            FROM csat_surveys
+            WHERE tenant_id = $1 AND responded_at >= $2 AND responded_at < $3
+            GROUP BY week_start
+            ORDER BY week_start
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(range.from)
+        .bind(range.to)
+        .bind(timezone.name())
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(CsatSummaryReport {
+            range: *range,
+            overall: CsatScore::from_counts(positive, negative),
+            by_technician: technician_rows
+                .into_iter()
+                .map(|(technician_id, name, positive, negative)| TechnicianCsat {
+                    technician_id,
+                    technician_name: name.unwrap_or_else(|| "Unassigned".to_string()),
+                    score: CsatScore::from_counts(positive, negative),
+                })
+                .collect(),
+            weekly_trend: trend_rows
+                .into_iter()
+                .map(|(week_start, positive, negative)| CsatTrendPoint {
+                    week_start,
+                    score: CsatScore::from_counts(positive, negative),
+                })
+                .collect(),
+        })
+    }
+
+    /// Resolution, SLA and CSAT figures for tickets an agent resolved in a
+    /// date range, for their profile page. A ticket counts as a first contact
+    /// resolution when it was resolved with at most one customer-visible
+    /// reply from the agent and never reopened.
+    pub async fn agent_scorecard(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        range: &DateRange,
+    ) -> AppResult<AgentScorecard> {
+        if !range.is_valid() {
+            return Err(AppError::BadRequest("Range start must be before its end".to_string()));
+        }
+
+        let agent_name: String = sqlx::query_scalar(
+            "SELECT first_name || ' ' || last_name FROM users WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("User".to_string()))?;
+
+        // Reopened: a move to an open status after one to a closed status
+        let rows = sqlx::query_as::<_, (chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>, i64, bool, Option<bool>)>(
+            r#"
+            SELECT t.created_at, resolved.at,
+                   (SELECT COUNT(*) FROM ticket_notes n
+                    WHERE n.ticket_id = t.id AND n.created_by_id = $2
+                      AND n.note_type IN ('public', 'resolution')),
+                   EXISTS (
+                       SELECT 1
+                       FROM ticket_status_history closed
+                       JOIN ticket_statuses cs ON cs.id = closed.status_id AND cs.is_closed
+                       JOIN ticket_status_history later
+                         ON later.ticket_id = closed.ticket_id AND later.entered_at > closed.entered_at
+                       JOIN ticket_statuses ls ON ls.id = later.status_id AND NOT COALESCE(ls.is_closed, FALSE)
+                       WHERE closed.ticket_id = t.id
+                   ),
+                   CASE WHEN t.first_response_due IS NULL AND t.resolution_due IS NULL THEN NULL
+                        ELSE (t.first_response_due IS NULL OR COALESCE(t.first_response_at <= t.first_response_due, FALSE))
+                         AND (t.resolution_due IS NULL OR resolved.at <= t.resolution_due)
+                   END
+            FROM tickets t
+            CROSS JOIN LATERAL (SELECT COALESCE(t.resolved_at, t.closed_at) AS at) resolved
+            WHERE t.tenant_id = $1 AND t.assigned_to_id = $2
+              AND resolved.at >= $3 AND resolved.at < $4
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(range.from)
+        .bind(range.to)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let tickets: Vec<ResolvedTicket> = rows
+            .into_iter()
+            .map(|(created_at, resolved_at, agent_touches, reopened, sla_met)| ResolvedTicket {
+                created_at,
+                resolved_at,
+                agent_touches,
+                reopened,
+                sla_met,
+            })
+            .collect();
+
+        let (positive, negative) = sqlx::query_as::<_, (i64, i64)>(
+            r#"
+            SELECT COUNT(*) FILTER (WHERE rating = 'positive'),
+                   COUNT(*) FILTER (WHERE rating = 'negative')
+            FROM csat_surveys
+            WHERE tenant_id = $1 AND technician_id = $2 AND responded_at >= $3 AND responded_at < $4
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(range.from)
+        .bind(range.to)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(AgentScorecard::build(
+            user_id,
+            agent_name,
+            *range,
+            &tickets,
+            CsatScore::from_counts(positive, negative),
+        ))
+    }
+
+    /// Tickets, time and invoicing for a company and all of its subsidiaries,
+    /// per company and rolled up the hierarchy. Invoice totals are only
+    /// queried when `include_revenue` is set.
+    pub async fn company_rollup(
+        &self,
+        tenant_id: Uuid,
+        root_id: Uuid,
+        range: &DateRange,
+        include_revenue: bool,
+    ) -> AppResult<CompanyRollupReport> {
+        if !range.is_valid() {
+            return Err(AppError::BadRequest("Range start must be before its end".to_string()));
+        }
+
+        let tree = ContactService::new(self.db.clone())
+            .company_tree(tenant_id, root_id)
+            .await?;
+        let company_ids = tree.ids();
+        let mut activity: HashMap<Uuid, CompanyActivity> = HashMap::new();
+
+        let tickets = sqlx::query_as::<_, (Uuid, i64, i64)>(
+            r#"
+            SELECT company_id,
+                   COUNT(*) FILTER (WHERE created_at >= $3 AND created_at < $4),
+                   COUNT(*) FILTER (WHERE closed_at >= $3 AND closed_at < $4)
+            FROM tickets
+            WHERE tenant_id = $1 AND company_id = ANY($2)
+            GROUP BY company_id
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(&company_ids)
+        .bind(range.from)
+        .bind(range.to)
+        .fetch_all(self.db.pool())
+        .await?;
+        for (company_id, created, closed) in tickets {
+            let entry = activity.entry(company_id).or_default();
+            entry.tickets_created = created.max(0) as u64;
+            entry.tickets_closed = closed.max(0) as u64;
+        }
+
+        // Time entries and invoices are dated, so compare in the tenant's days
+        let timezone = TenantTimezone::load(self.db.pool(), tenant_id).await?;
+        let from_date = timezone.local_date(range.from);
+        let to_date = timezone.local_date(range.to);
+
+        let time = sqlx::query_as::<_, (Uuid, i64, i64)>(
+            r#"
+            SELECT company_id,
+                   COALESCE(SUM(duration_minutes), 0)::BIGINT,
+                   COALESCE(SUM(duration_minutes) FILTER (WHERE is_billable), 0)::BIGINT
+            FROM time_entries
+            WHERE tenant_id = $1 AND company_id = ANY($2) AND date >= $3 AND date < $4
+            GROUP BY company_id
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(&company_ids)
+        .bind(from_date)
+        .bind(to_date)
+        .fetch_all(self.db.pool())
+        .await?;
+        for (company_id, minutes, billable_minutes) in time {
+            let entry = activity.entry(company_id).or_default();
+            entry.minutes = minutes;
+            entry.billable_minutes = billable_minutes;
+        }
+
+        if include_revenue {
+            let invoiced = sqlx::query_as::<_, (Uuid, Decimal)>(
+                r#"
+                SELECT company_id, COALESCE(SUM(total), 0)
+                FROM invoices
+                WHERE tenant_id = $1 AND company_id = ANY($2) AND invoice_date >= $3 AND invoice_date < $4
+                  AND status NOT IN ('draft', 'void')
+                GROUP BY company_id
+                "#,
+            )
+            .bind(tenant_id)
+            .bind(&company_ids)
+            .bind(from_date)
+            .bind(to_date)
+            .fetch_all(self.db.pool())
+            .await?;
+            for (company_id, total) in invoiced {
+                activity.entry(company_id).or_default().invoiced = total;
+            }
+        }
+
+        Ok(CompanyRollupReport::build(*range, &tree, &activity, include_revenue))
+    }
+
+    /// Service credits owed under contracts with SLA penalty terms, for first
+    /// responses and resolutions due in the range. A ticket without a
+    /// resolution time counts as resolved when it first moved to a closed
+    /// status.
+    pub async fn sla_penalties(&self, tenant_id: Uuid, range: &DateRange) -> AppResult<SlaPenaltyReport> {
+        if !range.is_valid() {
+            return Err(AppError::BadRequest("Range start must be before its end".to_string()));
+        }
+
+        let contracts = sqlx::query_as::<_, (Uuid, String, Uuid, Option<Decimal>, Option<serde_json::Value>)>(
+            r#"
+            SELECT id, name, company_id, billing_amount, sla_penalty_terms
+            FROM contracts
+            WHERE tenant_id = $1 AND sla_penalty_terms IS NOT NULL
+            ORDER BY name
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let rows = sqlx::query_as::<_, (Uuid, Uuid, String, String, chrono::DateTime<chrono::Utc>, Option<chrono::DateTime<chrono::Utc>>)>(
+            r#"
+            SELECT t.contract_id, t.id, t.ticket_number, target.kind, target.due, target.met_at
+            FROM tickets t
+            CROSS JOIN LATERAL (
+                VALUES
+                    ('first_response', t.first_response_due, t.first_response_at),
+                    ('resolution', t.resolution_due, COALESCE(
+                        t.resolved_at,
+                        (SELECT MIN(h.entered_at)
+                         FROM ticket_status_history h
+                         JOIN ticket_statuses s ON s.id = h.status_id
+                         WHERE h.ticket_id = t.id AND s.is_closed),
+                        t.closed_at
+                    ))
+            ) AS target(kind, due, met_at)
+            WHERE t.tenant_id = $1 AND t.contract_id = ANY($2)
+              AND target.due >= $3 AND target.due < $4
+            ORDER BY target.due, t.ticket_number
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(contracts.iter().map(|contract| contract.0).collect::<Vec<Uuid>>())
+        .bind(range.from)
+        .bind(range.to)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let mut outcomes: HashMap<Uuid, Vec<SlaOutcome>> = HashMap::new();
+        for (contract_id, ticket_id, ticket_number, kind, due, met_at) in rows {
+            let target = if kind == "first_response" {
+                SlaTarget::FirstResponse
+            } else {
+                SlaTarget::Resolution
+            };
+            outcomes.entry(contract_id).or_default().push(SlaOutcome {
+                ticket_id,
+                ticket_number,
+                target,
+                due,
+                met_at,
+            });
+        }
+
+        let now = chrono::Utc::now();
+        let contracts: Vec<ContractSlaPenalty> = contracts
+            .into_iter()
+            .filter_map(|(contract_id, name, company_id, billing_amount, terms)| {
+                let terms = SlaPenaltyTerms::from_value(terms)?;
+                let outcomes = outcomes.remove(&contract_id).unwrap_or_default();
+                Some(ContractSlaPenalty::calculate(
+                    contract_id,
+                    name,
+                    company_id,
+                    billing_amount,
+                    &terms,
+                    &outcomes,
+                    now,
+                ))
+            })
+            .collect();
+
+        Ok(SlaPenaltyReport {
+            range: *range,
+            total_credit: contracts.iter().map(|contract| contract.credit_amount).sum(),
+            contracts,
+        })
+    }
+
+    /// Revenue schedules of contracts with a fee that have started before
+    /// `to` and not ended before `from`. Open-ended contracts are scheduled
+    /// up to `to`. Draft and cancelled contracts earn nothing.
+    pub async fn revenue_schedules(
+        &self,
+        tenant_id: Uuid,
+        from: chrono::NaiveDate,
+        to: chrono::NaiveDate,
+    ) -> AppResult<Vec<RevenueSchedule>> {
+        let rows = sqlx::query_as::<_, ContractTermRow>(
+            r#"
+            SELECT id, name, company_id, billing_cycle, billing_amount, start_date, end_date
+            FROM contracts
+            WHERE tenant_id = $1 AND billing_amount IS NOT NULL
+              AND COALESCE(status, 'draft') NOT IN ('draft', 'cancelled')
+              AND start_date < $3 AND (end_date IS NULL OR end_date >= $2)
+            ORDER BY name
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let until = to - chrono::Duration::days(1);
+        Ok(rows
+            .into_iter()
+            .map(|row| RevenueSchedule::for_contract(&row.into(), until))
+            .collect())
+    }
+
+    /// Contract revenue earned in the range on an accrual basis, spread over
+    /// each contract's term rather than booked when invoiced
+    pub async fn recognized_revenue(&self, tenant_id: Uuid, range: &DateRange) -> AppResult<RecognizedRevenueReport> {
+        if !range.is_valid() {
+            return Err(AppError::BadRequest("Range start must be before its end".to_string()));
+        }
+
+        let tz = TenantTimezone::load(self.db.pool(), tenant_id).await?;
+        let from = tz.local_date(range.from);
+        let to = tz.local_date(range.to);
+        let schedules = self.revenue_schedules(tenant_id, from, to).await?;
+
+        Ok(RecognizedRevenueReport::build(*range, from, to, &schedules))
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct ContractTermRow {
+    id: Uuid,
+    name: String,
+    company_id: Uuid,
+    billing_cycle: Option<String>,
+    billing_amount: Decimal,
+    start_date: chrono::NaiveDate,
+    end_date: Option<chrono::NaiveDate>,
+}
+
+impl From<ContractTermRow> for ContractTerm {
+    fn from(row: ContractTermRow) -> Self {
+        Self {
+            contract_id: row.id,
+            contract_name: row.name,
+            company_id: row.company_id,
+            billing_cycle: row
+                .billing_cycle
+                .as_deref()
+                .and_then(BillingCycle::from_str)
+                .unwrap_or_default(),
+            billing_amount: row.billing_amount,
+            start_date: row.start_date,
+            end_date: row.end_date,
+        }
+    }
+}
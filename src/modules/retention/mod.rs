@@ -0,0 +1,18 @@
+//! Retention Module
+//!
+//! Per-tenant policies that purge old data on a schedule: closed tickets
+//! past their retention period are deleted or anonymized, old audit entries
+//! deleted. Records still referenced by open financial records are held
+//! back, and every purge is written to the audit log.
+
+mod models;
+#[cfg(feature = "server")]
+mod service;
+#[cfg(feature = "server")]
+mod routes;
+
+pub use models::*;
+#[cfg(feature = "server")]
+pub use service::RetentionService;
+#[cfg(feature = "server")]
+pub use routes::retention_routes;
@@ -0,0 +1,227 @@
+//! Data retention models
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::utils::error::AppError;
+
+/// Job that applies one tenant's policy
+pub const RETENTION_PURGE_JOB: &str = "retention.purge";
+
+/// Job that queues a purge for every enabled policy
+pub const RETENTION_SWEEP_JOB: &str = "retention.sweep";
+
+/// What a policy expires
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionEntity {
+    /// Tickets closed longer ago than the retention period, with their notes
+    /// and attachments
+    ClosedTickets,
+    /// Audit log entries older than the retention period
+    AuditLog,
+}
+
+impl RetentionEntity {
+    pub const ALL: [Self; 2] = [Self::ClosedTickets, Self::AuditLog];
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "closed_tickets" => Some(Self::ClosedTickets),
+            "audit_log" => Some(Self::AuditLog),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ClosedTickets => "closed_tickets",
+            Self::AuditLog => "audit_log",
+        }
+    }
+
+    /// Audit entries can only be removed: rewriting them would break the
+    /// hash chain
+    pub fn supports(&self, action: RetentionAction) -> bool {
+        match self {
+            Self::ClosedTickets => true,
+            Self::AuditLog => action == RetentionAction::Delete,
+        }
+    }
+}
+
+/// What happens to expired records
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionAction {
+    #[default]
+    Delete,
+    /// Keep the record for reporting but blank its content
+    Anonymize,
+}
+
+impl RetentionAction {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "delete" => Some(Self::Delete),
+            "anonymize" => Some(Self::Anonymize),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Delete => "delete",
+            Self::Anonymize => "anonymize",
+        }
+    }
+}
+
+/// A tenant's retention rule for one entity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub entity: RetentionEntity,
+    /// Records older than this many days are expired
+    pub retain_days: i32,
+    pub action: RetentionAction,
+    pub is_enabled: bool,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl RetentionPolicy {
+    /// Records from before this are expired
+    pub fn cutoff(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now - Duration::days(self.retain_days as i64)
+    }
+}
+
+/// Create or replace the policy for an entity
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct SetRetentionPolicyRequest {
+    /// At least a day, at most 100 years
+    #[validate(range(min = 1, max = 36500))]
+    pub retain_days: i32,
+    #[serde(default)]
+    pub action: RetentionAction,
+    #[serde(default = "default_true")]
+    pub is_enabled: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl SetRetentionPolicyRequest {
+    pub fn check(&self, entity: RetentionEntity) -> Result<(), AppError> {
+        if !entity.supports(self.action) {
+            return Err(AppError::validation_field(
+                "action",
+                format!("{} can't be {}d", entity.as_str(), self.action.as_str()),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// An expired record and whether anything still depends on it
+#[derive(Debug, Clone)]
+pub struct PurgeCandidate {
+    pub id: Uuid,
+    /// When the record expired from: closed, or written
+    pub aged_from: DateTime<Utc>,
+    /// Referenced by unbilled time or an invoice that isn't settled
+    pub financially_referenced: bool,
+}
+
+/// The records to purge, and those held back because open financial
+/// records still point at them
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PurgeSelection {
+    pub purge: Vec<Uuid>,
+    pub held: Vec<Uuid>,
+}
+
+/// Split expired records from the rest. Records referenced by open
+/// financial records are never purged.
+pub fn select_purgeable(candidates: &[PurgeCandidate], cutoff: DateTime<Utc>) -> PurgeSelection {
+    let mut selection = PurgeSelection::default();
+    for candidate in candidates.iter().filter(|candidate| candidate.aged_from < cutoff) {
+        if candidate.financially_referenced {
+            selection.held.push(candidate.id);
+        } else {
+            selection.purge.push(candidate.id);
+        }
+    }
+    selection
+}
+
+/// What a policy run did, or would do on a dry run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PurgeReport {
+    pub entity: RetentionEntity,
+    pub action: RetentionAction,
+    pub cutoff: DateTime<Utc>,
+    pub dry_run: bool,
+    /// Records purged, or that would be
+    pub purged: u64,
+    /// Expired records kept because open financial records reference them
+    pub held: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_purges_only_expired_unreferenced_tickets() {
+        let now = Utc::now();
+        let policy = RetentionPolicy {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            entity: RetentionEntity::ClosedTickets,
+            retain_days: 365 * 7,
+            action: RetentionAction::Delete,
+            is_enabled: true,
+            last_run_at: None,
+            created_at: now,
+            updated_at: now,
+        };
+        let cutoff = policy.cutoff(now);
+        let ticket = |years_ago: i64, financially_referenced: bool| PurgeCandidate {
+            id: Uuid::new_v4(),
+            aged_from: now - Duration::days(365 * years_ago),
+            financially_referenced,
+        };
+
+        let expired = ticket(8, false);
+        let recent = ticket(2, false);
+        let invoiced = ticket(9, true);
+        let recent_invoiced = ticket(1, true);
+        let selection = select_purgeable(&[expired.clone(), recent, invoiced.clone(), recent_invoiced], cutoff);
+
+        assert_eq!(selection.purge, vec![expired.id]);
+        assert_eq!(selection.held, vec![invoiced.id]);
+
+        // Exactly at the cutoff is still retained
+        let boundary = PurgeCandidate { aged_from: cutoff, ..ticket(0, false) };
+        assert_eq!(select_purgeable(&[boundary], cutoff), PurgeSelection::default());
+    }
+
+    #[test]
+    fn test_audit_log_can_only_be_deleted() {
+        let request = |action| SetRetentionPolicyRequest {
+            retain_days: 730,
+            action,
+            is_enabled: true,
+        };
+        assert!(request(RetentionAction::Delete).check(RetentionEntity::AuditLog).is_ok());
+        assert!(request(RetentionAction::Anonymize).check(RetentionEntity::AuditLog).is_err());
+        assert!(request(RetentionAction::Anonymize).check(RetentionEntity::ClosedTickets).is_ok());
+    }
+}
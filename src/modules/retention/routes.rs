@@ -0,0 +1,94 @@
+//! Data retention API routes
+
+use axum::{
+    extract::{Path, State},
+    routing::{get, post, put},
+    Json, Router,
+};
+use std::sync::Arc;
+use validator::Validate;
+
+use super::{PurgeReport, RetentionEntity, RetentionPolicy, RetentionService, SetRetentionPolicyRequest};
+use crate::modules::auth::RequireAdmin;
+use crate::utils::error::{AppError, AppResult};
+
+#[derive(Clone)]
+pub struct RetentionRouterState {
+    pub retention_service: Arc<RetentionService>,
+}
+
+/// Create the retention policy router
+pub fn retention_routes(retention_service: RetentionService) -> Router {
+    let state = RetentionRouterState {
+        retention_service: Arc::new(retention_service),
+    };
+
+    Router::new()
+        .route("/", get(list_policies))
+        .route("/:entity", put(set_policy).delete(delete_policy))
+        .route("/:entity/preview", get(preview_purge))
+        .route("/:entity/purge", post(purge_now))
+        .with_state(state)
+}
+
+fn parse_entity(entity: &str) -> AppResult<RetentionEntity> {
+    RetentionEntity::from_str(entity).ok_or_else(|| AppError::NotFound("Retention policy".to_string()))
+}
+
+async fn list_policies(
+    State(state): State<RetentionRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+) -> AppResult<Json<Vec<RetentionPolicy>>> {
+    let policies = state.retention_service.list_policies(user.tenant_id).await?;
+    Ok(Json(policies))
+}
+
+async fn set_policy(
+    State(state): State<RetentionRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Path(entity): Path<String>,
+    Json(request): Json<SetRetentionPolicyRequest>,
+) -> AppResult<Json<RetentionPolicy>> {
+    let entity = parse_entity(&entity)?;
+    request.validate()?;
+
+    let policy = state
+        .retention_service
+        .set_policy(user.tenant_id, entity, &request)
+        .await?;
+
+    Ok(Json(policy))
+}
+
+async fn delete_policy(
+    State(state): State<RetentionRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Path(entity): Path<String>,
+) -> AppResult<()> {
+    let entity = parse_entity(&entity)?;
+    state.retention_service.delete_policy(user.tenant_id, entity).await
+}
+
+/// Dry run: what the policy would purge now
+async fn preview_purge(
+    State(state): State<RetentionRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Path(entity): Path<String>,
+) -> AppResult<Json<PurgeReport>> {
+    let entity = parse_entity(&entity)?;
+    let report = state.retention_service.preview(user.tenant_id, entity).await?;
+    Ok(Json(report))
+}
+
+async fn purge_now(
+    State(state): State<RetentionRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Path(entity): Path<String>,
+) -> AppResult<Json<PurgeReport>> {
+    let entity = parse_entity(&entity)?;
+    let report = state
+        .retention_service
+        .purge_now(user.tenant_id, entity, user.id)
+        .await?;
+    Ok(Json(report))
+}
@@ -0,0 +1,370 @@
+//! Data retention service implementation
+
+use chrono::{DateTime, Utc};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::modules::audit::{AuditAction, AuditService, NewAuditEntry};
+use crate::modules::jobs::{Job, JobQueue, NewJob, PgJobQueue};
+use crate::modules::tickets::AttachmentService;
+use crate::utils::error::{AppError, AppResult};
+
+use super::models::*;
+
+const POLICY_COLUMNS: &str =
+    "id, tenant_id, entity, retain_days, action, is_enabled, last_run_at, created_at, updated_at";
+
+/// Closed tickets not yet anonymized, and whether unbilled billable time or
+/// an unsettled invoice still points at them
+const TICKET_CANDIDATES_SQL: &str = r#"
+    SELECT t.id, t.closed_at,
+           EXISTS (
+               SELECT 1 FROM time_entries te
+               LEFT JOIN invoices i ON i.id = te.invoice_id
+               WHERE te.ticket_id = t.id AND te.is_billable
+                 AND (te.billing_status <> 'billed' OR i.status IN ('draft', 'pending', 'sent', 'partially_paid'))
+           ) OR EXISTS (
+               SELECT 1 FROM invoice_lines l
+               JOIN invoices i ON i.id = l.invoice_id
+               WHERE l.ticket_id = t.id AND i.status IN ('draft', 'pending', 'sent', 'partially_paid')
+           ) AS financially_referenced
+    FROM tickets t
+    WHERE t.tenant_id = $1 AND t.closed_at IS NOT NULL AND t.closed_at < $2 AND t.anonymized_at IS NULL
+"#;
+
+/// Applies tenants' retention policies
+#[derive(Clone)]
+pub struct RetentionService {
+    db: Database,
+    attachments: AttachmentService,
+    jobs: PgJobQueue,
+}
+
+impl RetentionService {
+    pub fn new(db: Database) -> Self {
+        Self {
+            attachments: AttachmentService::new(db.clone()),
+            jobs: PgJobQueue::new(db.clone()),
+            db,
+        }
+    }
+
+    pub async fn list_policies(&self, tenant_id: Uuid) -> AppResult<Vec<RetentionPolicy>> {
+        let rows = sqlx::query_as::<_, RetentionPolicyRow>(&format!(
+            "SELECT {} FROM retention_policies WHERE tenant_id = $1 ORDER BY entity",
+            POLICY_COLUMNS
+        ))
+        .bind(tenant_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn get_policy(&self, tenant_id: Uuid, entity: RetentionEntity) -> AppResult<RetentionPolicy> {
+        sqlx::query_as::<_, RetentionPolicyRow>(&format!(
+            "SELECT {} FROM retention_policies WHERE tenant_id = $1 AND entity = $2",
+            POLICY_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(entity.as_str())
+        .fetch_optional(self.db.pool())
+        .await?
+        .map(Into::into)
+        .ok_or_else(|| AppError::NotFound("Retention policy".to_string()))
+    }
+
+    /// Create or replace the tenant's policy for `entity`
+    pub async fn set_policy(
+        &self,
+        tenant_id: Uuid,
+        entity: RetentionEntity,
+        request: &SetRetentionPolicyRequest,
+    ) -> AppResult<RetentionPolicy> {
+        request.check(entity)?;
+
+        let row = sqlx::query_as::<_, RetentionPolicyRow>(&format!(
+            r#"
+            INSERT INTO retention_policies (id, tenant_id, entity, retain_days, action, is_enabled)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (tenant_id, entity) DO UPDATE
+            SET retain_days = EXCLUDED.retain_days, action = EXCLUDED.action, is_enabled = EXCLUDED.is_enabled
+            RETURNING {}
+            "#,
+            POLICY_COLUMNS
+        ))
+        .bind(Uuid::new_v4())
+        .bind(tenant_id)
+        .bind(entity.as_str())
+        .bind(request.retain_days)
+        .bind(request.action.as_str())
+        .bind(request.is_enabled)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(row.into())
+    }
+
+    pub async fn delete_policy(&self, tenant_id: Uuid, entity: RetentionEntity) -> AppResult<()> {
+        let result = sqlx::query("DELETE FROM retention_policies WHERE tenant_id = $1 AND entity = $2")
+            .bind(tenant_id)
+            .bind(entity.as_str())
+            .execute(self.db.pool())
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Retention policy".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Queue a purge job for every enabled policy, returning how many were
+    /// queued. Run daily by `RETENTION_SWEEP_JOB`.
+    pub async fn run(&self, now: DateTime<Utc>) -> AppResult<usize> {
+        let policies: Vec<(Uuid, Uuid)> =
+            sqlx::query_as("SELECT id, tenant_id FROM retention_policies WHERE is_enabled")
+                .fetch_all(self.db.pool())
+                .await?;
+
+        for (policy_id, tenant_id) in &policies {
+            self.jobs
+                .enqueue(
+                    NewJob::new(RETENTION_PURGE_JOB, serde_json::json!({ "policy_id": policy_id }))
+                        .for_tenant(*tenant_id)
+                        .run_at(now),
+                )
+                .await?;
+        }
+
+        Ok(policies.len())
+    }
+
+    /// Handler for `RETENTION_SWEEP_JOB`, for registering with the job worker
+    pub async fn run_sweep_job(&self, _job: Job) -> AppResult<()> {
+        let queued = self.run(Utc::now()).await?;
+        tracing::info!("Queued retention purges for {} policies", queued);
+        Ok(())
+    }
+
+    /// Handler for `RETENTION_PURGE_JOB`, for registering with the job worker
+    pub async fn run_purge_job(&self, job: Job) -> AppResult<()> {
+        let tenant_id = job
+            .tenant_id
+            .ok_or_else(|| AppError::internal("Retention job has no tenant"))?;
+        let policy_id: Uuid = job.payload["policy_id"]
+            .as_str()
+            .and_then(|id| id.parse().ok())
+            .ok_or_else(|| AppError::internal("Retention job has no policy_id"))?;
+
+        let row = sqlx::query_as::<_, RetentionPolicyRow>(&format!(
+            "SELECT {} FROM retention_policies WHERE tenant_id = $1 AND id = $2",
+            POLICY_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(policy_id)
+        .fetch_optional(self.db.pool())
+        .await?;
+        // Removed or disabled since this job was queued
+        let Some(policy) = row.map(RetentionPolicy::from).filter(|policy| policy.is_enabled) else {
+            return Ok(());
+        };
+
+        let report = self.apply(&policy, Utc::now(), false, None).await?;
+        if report.purged > 0 || report.held > 0 {
+            tracing::info!(
+                "Retention purged {} {} for tenant {} ({} held by open financial records)",
+                report.purged,
+                policy.entity.as_str(),
+                tenant_id,
+                report.held,
+            );
+        }
+        Ok(())
+    }
+
+    /// What the tenant's policy for `entity` would purge now, changing nothing
+    pub async fn preview(&self, tenant_id: Uuid, entity: RetentionEntity) -> AppResult<PurgeReport> {
+        let policy = self.get_policy(tenant_id, entity).await?;
+        self.apply(&policy, Utc::now(), true, None).await
+    }
+
+    /// Apply the tenant's policy for `entity` now rather than waiting for the
+    /// scheduled run
+    pub async fn purge_now(&self, tenant_id: Uuid, entity: RetentionEntity, user_id: Uuid) -> AppResult<PurgeReport> {
+        let policy = self.get_policy(tenant_id, entity).await?;
+        self.apply(&policy, Utc::now(), false, Some(user_id)).await
+    }
+
+    /// Apply a policy as of `now`. A dry run only counts. A real run purges
+    /// in one transaction with an audit entry of what was removed.
+    pub async fn apply(
+        &self,
+        policy: &RetentionPolicy,
+        now: DateTime<Utc>,
+        dry_run: bool,
+        user_id: Option<Uuid>,
+    ) -> AppResult<PurgeReport> {
+        let cutoff = policy.cutoff(now);
+        let mut report = PurgeReport {
+            entity: policy.entity,
+            action: policy.action,
+            cutoff,
+            dry_run,
+            purged: 0,
+            held: 0,
+        };
+
+        let mut tx = self.db.pool().begin().await?;
+        let mut removed_files = Vec::new();
+
+        match policy.entity {
+            RetentionEntity::ClosedTickets => {
+                let candidates = sqlx::query_as::<_, (Uuid, DateTime<Utc>, bool)>(TICKET_CANDIDATES_SQL)
+                    .bind(policy.tenant_id)
+                    .bind(cutoff)
+                    .fetch_all(&mut *tx)
+                    .await?
+                    .into_iter()
+                    .map(|(id, aged_from, financially_referenced)| PurgeCandidate {
+                        id,
+                        aged_from,
+                        financially_referenced,
+                    })
+                    .collect::<Vec<_>>();
+
+                let selection = select_purgeable(&candidates, cutoff);
+                report.purged = selection.purge.len() as u64;
+                report.held = selection.held.len() as u64;
+
+                if !dry_run && !selection.purge.is_empty() {
+                    removed_files =
+                        Self::purge_tickets_in(&mut *tx, policy.tenant_id, &selection.purge, policy.action).await?;
+                }
+            }
+            RetentionEntity::AuditLog => {
+                report.purged = if dry_run {
+                    AuditService::new(self.db.clone()).count_before(policy.tenant_id, cutoff).await?
+                } else {
+                    AuditService::purge_before_in(&mut *tx, policy.tenant_id, cutoff).await?
+                };
+            }
+        }
+
+        if dry_run {
+            return Ok(report);
+        }
+
+        sqlx::query("UPDATE retention_policies SET last_run_at = $1 WHERE id = $2")
+            .bind(now)
+            .bind(policy.id)
+            .execute(&mut *tx)
+            .await?;
+
+        if report.purged > 0 {
+            let entry = NewAuditEntry::new(AuditAction::Delete, "retention_policy", Some(policy.id))
+                .by(user_id)
+                .new_values(serde_json::to_value(&report).unwrap_or_default());
+            AuditService::record_in(&mut *tx, policy.tenant_id, entry).await?;
+        }
+
+        tx.commit().await?;
+
+        // Only once the rows are gone for good
+        self.attachments.remove_stored_files(&removed_files).await;
+
+        Ok(report)
+    }
+
+    /// Delete or anonymize tickets, returning the storage paths of the
+    /// attachments removed with them. Settled time and invoice lines keep
+    /// their amounts and descriptions but lose the link to the ticket.
+    async fn purge_tickets_in(
+        conn: &mut PgConnection,
+        tenant_id: Uuid,
+        ticket_ids: &[Uuid],
+        action: RetentionAction,
+    ) -> AppResult<Vec<String>> {
+        let storage_paths: Vec<String> = sqlx::query_scalar(
+            "SELECT storage_path FROM ticket_attachments WHERE tenant_id = $1 AND ticket_id = ANY($2)",
+        )
+        .bind(tenant_id)
+        .bind(ticket_ids)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        match action {
+            RetentionAction::Delete => {
+                for statement in [
+                    "UPDATE time_entries SET ticket_id = NULL WHERE tenant_id = $1 AND ticket_id = ANY($2)",
+                    "UPDATE active_timers SET ticket_id = NULL WHERE tenant_id = $1 AND ticket_id = ANY($2)",
+                    "UPDATE invoice_lines SET ticket_id = NULL WHERE ticket_id = ANY($2) \
+                     AND invoice_id IN (SELECT id FROM invoices WHERE tenant_id = $1)",
+                    "UPDATE tickets SET parent_ticket_id = NULL WHERE tenant_id = $1 AND parent_ticket_id = ANY($2)",
+                    "DELETE FROM tickets WHERE tenant_id = $1 AND id = ANY($2)",
+                ] {
+                    sqlx::query(statement)
+                        .bind(tenant_id)
+                        .bind(ticket_ids)
+                        .execute(&mut *conn)
+                        .await?;
+                }
+            }
+            RetentionAction::Anonymize => {
+                // Counts, dates and SLA figures stay for reporting
+                for statement in [
+                    "DELETE FROM ticket_notes WHERE tenant_id = $1 AND ticket_id = ANY($2)",
+                    "DELETE FROM ticket_attachments WHERE tenant_id = $1 AND ticket_id = ANY($2)",
+                    r#"
+                    UPDATE tickets
+                    SET title = 'Purged', description = NULL, contact_id = NULL,
+                        email_message_id = NULL, email_thread_id = NULL, custom_fields = '{}',
+                        anonymized_at = NOW(), updated_at = NOW()
+                    WHERE tenant_id = $1 AND id = ANY($2)
+                    "#,
+                ] {
+                    sqlx::query(statement)
+                        .bind(tenant_id)
+                        .bind(ticket_ids)
+                        .execute(&mut *conn)
+                        .await?;
+                }
+            }
+        }
+
+        Ok(storage_paths)
+    }
+}
+
+// ============================================================================
+// DATABASE ROW TYPES
+// ============================================================================
+
+#[derive(sqlx::FromRow)]
+struct RetentionPolicyRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    entity: String,
+    retain_days: i32,
+    action: String,
+    is_enabled: bool,
+    last_run_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<RetentionPolicyRow> for RetentionPolicy {
+    fn from(row: RetentionPolicyRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            entity: RetentionEntity::from_str(&row.entity).unwrap_or(RetentionEntity::ClosedTickets),
+            retain_days: row.retain_days,
+            action: RetentionAction::from_str(&row.action).unwrap_or_default(),
+            is_enabled: row.is_enabled,
+            last_run_at: row.last_run_at,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
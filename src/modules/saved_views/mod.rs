@@ -0,0 +1,15 @@
+//! Saved Views Module
+//!
+//! Named, optionally shared filters for the ticket, company and contact lists.
+
+mod models;
+#[cfg(feature = "server")]
+mod service;
+#[cfg(feature = "server")]
+mod routes;
+
+pub use models::*;
+#[cfg(feature = "server")]
+pub use service::SavedViewService;
+#[cfg(feature = "server")]
+pub use routes::saved_view_routes;
@@ -0,0 +1,268 @@
+//! Saved view models and types
+
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::modules::auth::CurrentUser;
+use crate::modules::contacts::{CompanyFilter, ContactFilter};
+use crate::modules::tickets::TicketFilter;
+use crate::utils::error::AppError;
+
+/// The list a saved view filters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SavedViewEntity {
+    Ticket,
+    Company,
+    Contact,
+}
+
+impl SavedViewEntity {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "ticket" => Some(Self::Ticket),
+            "company" => Some(Self::Company),
+            "contact" => Some(Self::Contact),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ticket => "ticket",
+            Self::Company => "company",
+            Self::Contact => "contact",
+        }
+    }
+
+    /// Check `filters` parse as this list's filter type
+    fn parse_filters(&self, filters: &serde_json::Value) -> Result<(), serde_json::Error> {
+        match self {
+            Self::Ticket => serde_json::from_value::<TicketFilter>(filters.clone()).map(|_| ()),
+            Self::Company => serde_json::from_value::<CompanyFilter>(filters.clone()).map(|_| ()),
+            Self::Contact => serde_json::from_value::<ContactFilter>(filters.clone()).map(|_| ()),
+        }
+    }
+}
+
+/// A list filter that can be saved as a view
+pub trait SavedFilter: Serialize + DeserializeOwned {
+    const ENTITY: SavedViewEntity;
+}
+
+impl SavedFilter for TicketFilter {
+    const ENTITY: SavedViewEntity = SavedViewEntity::Ticket;
+}
+
+impl SavedFilter for CompanyFilter {
+    const ENTITY: SavedViewEntity = SavedViewEntity::Company;
+}
+
+impl SavedFilter for ContactFilter {
+    const ENTITY: SavedViewEntity = SavedViewEntity::Contact;
+}
+
+/// A named filter for one of the lists
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedView {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    /// Owner; the only one who sees a private view
+    pub user_id: Uuid,
+    pub entity: SavedViewEntity,
+    pub name: String,
+    /// The list's filter query parameters
+    pub filters: serde_json::Value,
+    pub is_shared: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SavedView {
+    pub fn is_visible_to(&self, user: &CurrentUser) -> bool {
+        self.tenant_id == user.tenant_id && (self.is_shared || self.user_id == user.id)
+    }
+
+    /// Owners manage their views; admins can also tidy up shared ones
+    pub fn can_edit(&self, user: &CurrentUser) -> bool {
+        self.tenant_id == user.tenant_id
+            && (self.user_id == user.id || (self.is_shared && user.role.is_admin()))
+    }
+
+    /// The saved filter with any parameters set on the request laid over it
+    pub fn apply<F: SavedFilter>(&self, overrides: &F) -> Result<F, AppError> {
+        if self.entity != F::ENTITY {
+            return Err(AppError::BadRequest(format!(
+                "Saved view is for the {} list",
+                self.entity.as_str()
+            )));
+        }
+
+        let mut filters = match self.filters {
+            serde_json::Value::Object(ref saved) => saved.clone(),
+            _ => serde_json::Map::new(),
+        };
+        if let Ok(serde_json::Value::Object(overrides)) = serde_json::to_value(overrides) {
+            filters.extend(overrides.into_iter().filter(|(_, value)| !value.is_null()));
+        }
+
+        serde_json::from_value(serde_json::Value::Object(filters))
+            .map_err(|e| AppError::Internal(format!("Saved view {} has invalid filters: {}", self.id, e)))
+    }
+}
+
+/// Query parameter selecting a saved view on a list endpoint
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct SavedViewParams {
+    pub saved_view_id: Option<Uuid>,
+}
+
+/// Narrow the saved view list to one entity
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct SavedViewFilter {
+    pub entity: Option<SavedViewEntity>,
+}
+
+/// Save a view
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CreateSavedViewRequest {
+    pub entity: SavedViewEntity,
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    #[serde(default = "empty_filters")]
+    pub filters: serde_json::Value,
+    #[serde(default)]
+    pub is_shared: bool,
+}
+
+impl CreateSavedViewRequest {
+    pub fn check(&self) -> Result<(), AppError> {
+        check_filters(self.entity, &self.filters)
+    }
+}
+
+/// Rename, re-filter or change the sharing of a view
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct UpdateSavedViewRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: Option<String>,
+    pub filters: Option<serde_json::Value>,
+    pub is_shared: Option<bool>,
+}
+
+fn empty_filters() -> serde_json::Value {
+    serde_json::json!({})
+}
+
+/// Filters must be an object the list's filter type accepts
+pub fn check_filters(entity: SavedViewEntity, filters: &serde_json::Value) -> Result<(), AppError> {
+    if !filters.is_object() {
+        return Err(AppError::validation_field("filters", "Filters must be an object"));
+    }
+
+    entity
+        .parse_filters(filters)
+        .map_err(|e| AppError::validation_field("filters", format!("Invalid {} filter: {}", entity.as_str(), e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::auth::UserRole;
+    use crate::modules::contacts::{CompanyStatus, CompanyType};
+
+    fn user(role: UserRole) -> CurrentUser {
+        CurrentUser {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::nil(),
+            email: "tech@example.com".to_string(),
+            first_name: "Sam".to_string(),
+            last_name: "Tech".to_string(),
+            role,
+            timezone: "UTC".to_string(),
+            locale: "en".to_string(),
+            avatar_url: None,
+        }
+    }
+
+    fn view(owner: &CurrentUser, entity: SavedViewEntity, filters: serde_json::Value) -> SavedView {
+        SavedView {
+            id: Uuid::new_v4(),
+            tenant_id: owner.tenant_id,
+            user_id: owner.id,
+            entity,
+            name: "Active clients".to_string(),
+            filters,
+            is_shared: false,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_company_filter_round_trip() {
+        let owner = user(UserRole::Technician);
+        let filter = CompanyFilter {
+            q: Some("acme".to_string()),
+            company_type: Some(CompanyType::Client),
+            status: Some(CompanyStatus::Active),
+            ..CompanyFilter::default()
+        };
+
+        let request = CreateSavedViewRequest {
+            entity: SavedViewEntity::Company,
+            name: "Active clients".to_string(),
+            filters: serde_json::to_value(&filter).unwrap(),
+            is_shared: false,
+        };
+        assert!(request.check().is_ok());
+
+        // With no overrides, list_companies gets exactly the saved filter
+        let saved = view(&owner, request.entity, request.filters);
+        assert_eq!(saved.apply(&CompanyFilter::default()).unwrap(), filter);
+
+        // Parameters on the request win over the saved ones
+        let narrowed = saved
+            .apply(&CompanyFilter {
+                q: Some("acme east".to_string()),
+                ..CompanyFilter::default()
+            })
+            .unwrap();
+        assert_eq!(narrowed.q.as_deref(), Some("acme east"));
+        assert_eq!(narrowed.status, Some(CompanyStatus::Active));
+    }
+
+    #[test]
+    fn test_saved_view_entity_must_match() {
+        let owner = user(UserRole::Technician);
+        let saved = view(&owner, SavedViewEntity::Company, serde_json::json!({}));
+        assert!(saved.apply(&ContactFilter::default()).is_err());
+
+        let bad = CreateSavedViewRequest {
+            entity: SavedViewEntity::Contact,
+            name: "Portal users".to_string(),
+            filters: serde_json::json!({ "status": "not_a_status" }),
+            is_shared: false,
+        };
+        assert!(bad.check().is_err());
+    }
+
+    #[test]
+    fn test_saved_view_scoping() {
+        let owner = user(UserRole::Technician);
+        let colleague = user(UserRole::Technician);
+        let admin = user(UserRole::Admin);
+
+        let mut saved = view(&owner, SavedViewEntity::Ticket, serde_json::json!({ "is_open": true }));
+        assert!(saved.is_visible_to(&owner));
+        assert!(!saved.is_visible_to(&colleague));
+        assert!(!saved.can_edit(&admin));
+
+        saved.is_shared = true;
+        assert!(saved.is_visible_to(&colleague));
+        assert!(!saved.can_edit(&colleague));
+        assert!(saved.can_edit(&admin));
+    }
+}
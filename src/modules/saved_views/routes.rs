@@ -0,0 +1,83 @@
+//! Saved view API routes
+
+use axum::{
+    extract::{Path, Query, State},
+    routing::{delete, get, post, put},
+    Json, Router,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::{CreateSavedViewRequest, SavedView, SavedViewFilter, SavedViewService, UpdateSavedViewRequest};
+use crate::modules::auth::RequireAuth;
+use crate::utils::error::AppResult;
+use crate::utils::validation::ValidatedJson;
+
+#[derive(Clone)]
+pub struct SavedViewRouterState {
+    pub saved_view_service: Arc<SavedViewService>,
+}
+
+/// Create the saved views router
+pub fn saved_view_routes(saved_view_service: SavedViewService) -> Router {
+    let state = SavedViewRouterState {
+        saved_view_service: Arc::new(saved_view_service),
+    };
+
+    Router::new()
+        .route("/", get(list_views))
+        .route("/", post(create_view))
+        .route("/:view_id", get(get_view))
+        .route("/:view_id", put(update_view))
+        .route("/:view_id", delete(delete_view))
+        .with_state(state)
+}
+
+async fn list_views(
+    State(state): State<SavedViewRouterState>,
+    RequireAuth(user): RequireAuth,
+    Query(filter): Query<SavedViewFilter>,
+) -> AppResult<Json<Vec<SavedView>>> {
+    let views = state.saved_view_service.list_views(&user, &filter).await?;
+    Ok(Json(views))
+}
+
+async fn get_view(
+    State(state): State<SavedViewRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(view_id): Path<Uuid>,
+) -> AppResult<Json<SavedView>> {
+    let view = state.saved_view_service.get_view(&user, view_id).await?;
+    Ok(Json(view))
+}
+
+async fn create_view(
+    State(state): State<SavedViewRouterState>,
+    RequireAuth(user): RequireAuth,
+    ValidatedJson(request): ValidatedJson<CreateSavedViewRequest>,
+) -> AppResult<Json<SavedView>> {
+    let view = state.saved_view_service.create_view(&user, &request).await?;
+    Ok(Json(view))
+}
+
+async fn update_view(
+    State(state): State<SavedViewRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(view_id): Path<Uuid>,
+    ValidatedJson(request): ValidatedJson<UpdateSavedViewRequest>,
+) -> AppResult<Json<SavedView>> {
+    let view = state
+        .saved_view_service
+        .update_view(&user, view_id, &request)
+        .await?;
+
+    Ok(Json(view))
+}
+
+async fn delete_view(
+    State(state): State<SavedViewRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(view_id): Path<Uuid>,
+) -> AppResult<()> {
+    state.saved_view_service.delete_view(&user, view_id).await
+}
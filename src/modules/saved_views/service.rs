@@ -0,0 +1,186 @@
+//! Saved view service implementation
+
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::modules::auth::CurrentUser;
+use crate::utils::error::{AppError, AppResult};
+
+use super::models::*;
+
+const SAVED_VIEW_COLUMNS: &str =
+    "id, tenant_id, user_id, entity_type, name, filters, is_shared, created_at, updated_at";
+
+/// Saved view service
+#[derive(Clone)]
+pub struct SavedViewService {
+    db: Database,
+}
+
+impl SavedViewService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// The user's own views and everyone's shared ones
+    pub async fn list_views(&self, user: &CurrentUser, filter: &SavedViewFilter) -> AppResult<Vec<SavedView>> {
+        let rows = sqlx::query_as::<_, SavedViewRow>(&format!(
+            r#"
+            SELECT {}
+            FROM saved_views
+            WHERE tenant_id = $1 AND (user_id = $2 OR is_shared = TRUE)
+              AND ($3::TEXT IS NULL OR entity_type = $3)
+            ORDER BY entity_type, name
+            "#,
+            SAVED_VIEW_COLUMNS
+        ))
+        .bind(user.tenant_id)
+        .bind(user.id)
+        .bind(filter.entity.map(|entity| entity.as_str()))
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// A view the user can see; other users' private views read as missing
+    pub async fn get_view(&self, user: &CurrentUser, view_id: Uuid) -> AppResult<SavedView> {
+        let view: SavedView = sqlx::query_as::<_, SavedViewRow>(&format!(
+            "SELECT {} FROM saved_views WHERE tenant_id = $1 AND id = $2",
+            SAVED_VIEW_COLUMNS
+        ))
+        .bind(user.tenant_id)
+        .bind(view_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::not_found("Saved view"))?
+        .into();
+
+        if !view.is_visible_to(user) {
+            return Err(AppError::not_found("Saved view"));
+        }
+
+        Ok(view)
+    }
+
+    pub async fn create_view(&self, user: &CurrentUser, request: &CreateSavedViewRequest) -> AppResult<SavedView> {
+        request.check()?;
+
+        let row = sqlx::query_as::<_, SavedViewRow>(&format!(
+            r#"
+            INSERT INTO saved_views (tenant_id, user_id, entity_type, name, filters, is_shared)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING {}
+            "#,
+            SAVED_VIEW_COLUMNS
+        ))
+        .bind(user.tenant_id)
+        .bind(user.id)
+        .bind(request.entity.as_str())
+        .bind(&request.name)
+        .bind(&request.filters)
+        .bind(request.is_shared)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(row.into())
+    }
+
+    pub async fn update_view(
+        &self,
+        user: &CurrentUser,
+        view_id: Uuid,
+        request: &UpdateSavedViewRequest,
+    ) -> AppResult<SavedView> {
+        let view = self.get_view(user, view_id).await?;
+        if !view.can_edit(user) {
+            return Err(AppError::forbidden("Only the owner can change this view"));
+        }
+        if let Some(ref filters) = request.filters {
+            check_filters(view.entity, filters)?;
+        }
+
+        let row = sqlx::query_as::<_, SavedViewRow>(&format!(
+            r#"
+            UPDATE saved_views
+            SET name = COALESCE($1, name), filters = COALESCE($2, filters),
+                is_shared = COALESCE($3, is_shared), updated_at = NOW()
+            WHERE tenant_id = $4 AND id = $5
+            RETURNING {}
+            "#,
+            SAVED_VIEW_COLUMNS
+        ))
+        .bind(&request.name)
+        .bind(&request.filters)
+        .bind(request.is_shared)
+        .bind(user.tenant_id)
+        .bind(view_id)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(row.into())
+    }
+
+    pub async fn delete_view(&self, user: &CurrentUser, view_id: Uuid) -> AppResult<()> {
+        let view = self.get_view(user, view_id).await?;
+        if !view.can_edit(user) {
+            return Err(AppError::forbidden("Only the owner can delete this view"));
+        }
+
+        sqlx::query("DELETE FROM saved_views WHERE tenant_id = $1 AND id = $2")
+            .bind(user.tenant_id)
+            .bind(view_id)
+            .execute(self.db.pool())
+            .await?;
+
+        Ok(())
+    }
+
+    /// The filter a list endpoint should use: `filter` as given, or laid over
+    /// the saved view's filter when one is selected
+    pub async fn resolve_filter<F: SavedFilter>(
+        &self,
+        user: &CurrentUser,
+        params: &SavedViewParams,
+        filter: F,
+    ) -> AppResult<F> {
+        match params.saved_view_id {
+            Some(view_id) => self.get_view(user, view_id).await?.apply(&filter),
+            None => Ok(filter),
+        }
+    }
+}
+
+// ============================================================================
+// DATABASE ROW TYPES
+// ============================================================================
+
+#[derive(sqlx::FromRow)]
+struct SavedViewRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    entity_type: String,
+    name: String,
+    filters: serde_json::Value,
+    is_shared: bool,
+    created_at: chrono::DateTime<chrono::Utc>,
+    updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<SavedViewRow> for SavedView {
+    fn from(row: SavedViewRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            user_id: row.user_id,
+            // Constrained by the table's CHECK
+            entity: SavedViewEntity::from_str(&row.entity_type).unwrap_or(SavedViewEntity::Ticket),
+            name: row.name,
+            filters: row.filters,
+            is_shared: row.is_shared,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
@@ -0,0 +1,17 @@
+//! Sequences Module
+//!
+//! Per-tenant document numbering for tickets, invoices, quotes and projects.
+//! Each kind has its own counter and format, and numbers are allocated
+//! atomically.
+
+mod models;
+#[cfg(feature = "server")]
+mod service;
+#[cfg(feature = "server")]
+mod routes;
+
+pub use models::*;
+#[cfg(feature = "server")]
+pub use service::SequenceService;
+#[cfg(feature = "server")]
+pub use routes::sequence_routes;
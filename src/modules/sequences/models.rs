@@ -0,0 +1,200 @@
+//! Number sequence models
+
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::utils::error::AppError;
+
+/// What a sequence numbers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SequenceKind {
+    Ticket,
+    Invoice,
+    Quote,
+    Project,
+}
+
+impl SequenceKind {
+    pub const ALL: [Self; 4] = [Self::Ticket, Self::Invoice, Self::Quote, Self::Project];
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "ticket" => Some(Self::Ticket),
+            "invoice" => Some(Self::Invoice),
+            "quote" => Some(Self::Quote),
+            "project" => Some(Self::Project),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ticket => "ticket",
+            Self::Invoice => "invoice",
+            Self::Quote => "quote",
+            Self::Project => "project",
+        }
+    }
+
+    /// Format used until the tenant sets its own
+    pub fn default_format(&self) -> NumberFormat {
+        let prefix = match self {
+            Self::Ticket => "T",
+            Self::Invoice => "INV-",
+            Self::Quote => "Q-",
+            Self::Project => "PRJ-",
+        };
+        NumberFormat {
+            prefix: prefix.to_string(),
+            padding: 6,
+        }
+    }
+}
+
+/// How an allocated number is written: the prefix and the number zero-padded
+/// to `padding` digits, e.g. `INV-000042`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NumberFormat {
+    pub prefix: String,
+    pub padding: i32,
+}
+
+impl NumberFormat {
+    /// Numbers with more digits than `padding` are written in full
+    pub fn format(&self, number: i32) -> String {
+        format!("{}{:0width$}", self.prefix, number, width = self.padding.max(1) as usize)
+    }
+}
+
+/// A tenant's sequence for one kind
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NumberSequence {
+    pub kind: SequenceKind,
+    pub format: NumberFormat,
+    /// Last number handed out; 0 before the first
+    pub last_number: i32,
+}
+
+impl NumberSequence {
+    pub fn new(kind: SequenceKind) -> Self {
+        Self {
+            kind,
+            format: kind.default_format(),
+            last_number: 0,
+        }
+    }
+
+    /// What the next allocation will return
+    pub fn next_preview(&self) -> String {
+        self.format.format(self.last_number + 1)
+    }
+}
+
+/// Change a sequence's format, and optionally move it forward
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct SetNumberFormatRequest {
+    #[validate(length(max = 20))]
+    pub prefix: String,
+    #[validate(range(min = 1, max = 12))]
+    pub padding: i32,
+    /// Number to allocate next; must be past every number already issued
+    #[validate(range(min = 1))]
+    pub next_number: Option<i32>,
+}
+
+impl SetNumberFormatRequest {
+    pub fn check(&self) -> Result<(), AppError> {
+        if self.prefix.chars().any(|c| c.is_whitespace() || c.is_control()) {
+            return Err(AppError::validation_field("prefix", "Must not contain spaces"));
+        }
+        Ok(())
+    }
+
+    /// Apply to `sequence`. Moving a sequence back could hand out a number
+    /// twice, so `next_number` must be past the last one issued.
+    pub fn apply(&self, sequence: &NumberSequence) -> Result<NumberSequence, AppError> {
+        self.check()?;
+
+        let last_number = match self.next_number {
+            Some(next) if next <= sequence.last_number => {
+                return Err(AppError::validation_field(
+                    "next_number",
+                    format!("Must be after {}, the last number issued", sequence.last_number),
+                ));
+            }
+            Some(next) => next - 1,
+            None => sequence.last_number,
+        };
+
+        Ok(NumberSequence {
+            kind: sequence.kind,
+            format: NumberFormat {
+                prefix: self.prefix.clone(),
+                padding: self.padding,
+            },
+            last_number,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_number_formats() {
+        assert_eq!(SequenceKind::Ticket.default_format().format(42), "T000042");
+        assert_eq!(SequenceKind::Invoice.default_format().format(42), "INV-000042");
+
+        let short = NumberFormat { prefix: "Q".to_string(), padding: 2 };
+        assert_eq!(short.format(7), "Q07");
+        assert_eq!(short.format(1234), "Q1234");
+
+        for kind in SequenceKind::ALL {
+            assert_eq!(SequenceKind::from_str(kind.as_str()), Some(kind));
+        }
+    }
+
+    #[test]
+    fn test_independent_sequences_do_not_collide() {
+        // Each kind counts on its own, so the same counter value comes up in
+        // every kind; the default prefixes keep the numbers apart
+        let numbers: HashSet<String> = SequenceKind::ALL
+            .iter()
+            .flat_map(|kind| (1..=100).map(move |n| kind.default_format().format(n)))
+            .collect();
+        assert_eq!(numbers.len(), 400);
+
+        // Moving one sequence forward leaves the others where they were
+        let invoices = NumberSequence { last_number: 41, ..NumberSequence::new(SequenceKind::Invoice) };
+        let tickets = NumberSequence { last_number: 41, ..NumberSequence::new(SequenceKind::Ticket) };
+        let request = SetNumberFormatRequest {
+            prefix: "INV-2026-".to_string(),
+            padding: 4,
+            next_number: Some(1000),
+        };
+        let invoices = request.apply(&invoices).unwrap();
+        assert_eq!(invoices.next_preview(), "INV-2026-1000");
+        assert_eq!(tickets.next_preview(), "T000042");
+    }
+
+    #[test]
+    fn test_sequence_cannot_move_back() {
+        let sequence = NumberSequence { last_number: 250, ..NumberSequence::new(SequenceKind::Project) };
+        let back = SetNumberFormatRequest {
+            prefix: "PRJ-".to_string(),
+            padding: 6,
+            next_number: Some(250),
+        };
+        assert!(matches!(back.apply(&sequence), Err(AppError::Validation { .. })));
+
+        // Changing only the format keeps the counter
+        let reformat = SetNumberFormatRequest { next_number: None, ..back.clone() };
+        assert_eq!(reformat.apply(&sequence).unwrap().last_number, 250);
+
+        let spaced = SetNumberFormatRequest { prefix: "PRJ ".to_string(), ..reformat };
+        assert!(spaced.check().is_err());
+    }
+}
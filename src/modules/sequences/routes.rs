@@ -0,0 +1,55 @@
+//! Number sequence API routes
+
+use axum::{
+    extract::{Path, State},
+    routing::{get, put},
+    Json, Router,
+};
+use std::sync::Arc;
+use validator::Validate;
+
+use super::{NumberSequence, SequenceKind, SequenceService, SetNumberFormatRequest};
+use crate::modules::auth::RequireAdmin;
+use crate::utils::error::{AppError, AppResult};
+
+#[derive(Clone)]
+pub struct SequenceRouterState {
+    pub sequence_service: Arc<SequenceService>,
+}
+
+/// Create the number sequence router
+pub fn sequence_routes(sequence_service: SequenceService) -> Router {
+    let state = SequenceRouterState {
+        sequence_service: Arc::new(sequence_service),
+    };
+
+    Router::new()
+        .route("/", get(list_sequences))
+        .route("/:kind", put(set_format))
+        .with_state(state)
+}
+
+async fn list_sequences(
+    State(state): State<SequenceRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+) -> AppResult<Json<Vec<NumberSequence>>> {
+    let sequences = state.sequence_service.list(user.tenant_id).await?;
+    Ok(Json(sequences))
+}
+
+async fn set_format(
+    State(state): State<SequenceRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Path(kind): Path<String>,
+    Json(request): Json<SetNumberFormatRequest>,
+) -> AppResult<Json<NumberSequence>> {
+    let kind = SequenceKind::from_str(&kind).ok_or_else(|| AppError::NotFound("Number sequence".to_string()))?;
+    request.validate()?;
+
+    let sequence = state
+        .sequence_service
+        .set_format(user.tenant_id, kind, &request)
+        .await?;
+
+    Ok(Json(sequence))
+}
@@ -0,0 +1,127 @@
+//! Number sequence service implementation
+
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::utils::error::AppResult;
+
+use super::models::*;
+
+/// Allocates and configures document numbers
+#[derive(Clone)]
+pub struct SequenceService {
+    db: Database,
+}
+
+impl SequenceService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Allocate the tenant's next number of `kind`
+    pub async fn next(&self, tenant_id: Uuid, kind: SequenceKind) -> AppResult<String> {
+        let mut conn = self.db.pool().acquire().await?;
+        Self::next_in(&mut conn, tenant_id, kind).await
+    }
+
+    /// Allocate inside the caller's transaction, so a rolled back document
+    /// gives its number back. The counter row stays locked until the
+    /// transaction ends; concurrent allocations wait and never share a number.
+    pub async fn next_in(conn: &mut PgConnection, tenant_id: Uuid, kind: SequenceKind) -> AppResult<String> {
+        let default = kind.default_format();
+
+        let (number, prefix, padding): (i32, String, i32) = sqlx::query_as(
+            r#"
+            INSERT INTO number_sequences (tenant_id, kind, prefix, padding, last_number)
+            VALUES ($1, $2, $3, $4, 1)
+            ON CONFLICT (tenant_id, kind) DO UPDATE SET last_number = number_sequences.last_number + 1
+            RETURNING last_number, prefix, padding
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(kind.as_str())
+        .bind(&default.prefix)
+        .bind(default.padding)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(NumberFormat { prefix, padding }.format(number))
+    }
+
+    /// Every kind's sequence, with defaults for those never used
+    pub async fn list(&self, tenant_id: Uuid) -> AppResult<Vec<NumberSequence>> {
+        let rows: Vec<(String, String, i32, i32)> = sqlx::query_as(
+            "SELECT kind, prefix, padding, last_number FROM number_sequences WHERE tenant_id = $1",
+        )
+        .bind(tenant_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(SequenceKind::ALL
+            .into_iter()
+            .map(|kind| {
+                rows.iter()
+                    .find(|(row_kind, ..)| row_kind == kind.as_str())
+                    .map(|(_, prefix, padding, last_number)| NumberSequence {
+                        kind,
+                        format: NumberFormat {
+                            prefix: prefix.clone(),
+                            padding: *padding,
+                        },
+                        last_number: *last_number,
+                    })
+                    .unwrap_or_else(|| NumberSequence::new(kind))
+            })
+            .collect())
+    }
+
+    /// Change a sequence's format or move it forward. Numbers already issued
+    /// keep the format they were issued with.
+    pub async fn set_format(
+        &self,
+        tenant_id: Uuid,
+        kind: SequenceKind,
+        request: &SetNumberFormatRequest,
+    ) -> AppResult<NumberSequence> {
+        let mut tx = self.db.pool().begin().await?;
+
+        let current: Option<(String, i32, i32)> = sqlx::query_as(
+            "SELECT prefix, padding, last_number FROM number_sequences WHERE tenant_id = $1 AND kind = $2 FOR UPDATE",
+        )
+        .bind(tenant_id)
+        .bind(kind.as_str())
+        .fetch_optional(&mut *tx)
+        .await?;
+        let current = current.map_or_else(
+            || NumberSequence::new(kind),
+            |(prefix, padding, last_number)| NumberSequence {
+                kind,
+                format: NumberFormat { prefix, padding },
+                last_number,
+            },
+        );
+
+        let updated = request.apply(&current)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO number_sequences (tenant_id, kind, prefix, padding, last_number)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (tenant_id, kind) DO UPDATE
+                SET prefix = EXCLUDED.prefix, padding = EXCLUDED.padding, last_number = EXCLUDED.last_number
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(kind.as_str())
+        .bind(&updated.format.prefix)
+        .bind(updated.format.padding)
+        .bind(updated.last_number)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(updated)
+    }
+}
@@ -0,0 +1,19 @@
+//! Shared Templates Module
+//!
+//! A library across tenants: admins publish automation rules and KB
+//! articles, and other tenants import them as their own copies. References
+//! to statuses, priorities, queues and KB categories travel by name and are
+//! resolved in the importing tenant; an import that can't resolve one is
+//! refused.
+
+mod models;
+#[cfg(feature = "server")]
+mod service;
+#[cfg(feature = "server")]
+mod routes;
+
+pub use models::*;
+#[cfg(feature = "server")]
+pub use service::SharedTemplateService;
+#[cfg(feature = "server")]
+pub use routes::shared_template_routes;
@@ -0,0 +1,475 @@
+//! Shared template models and types
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::modules::knowledge_base::{CreateArticleRequest, KbArticle, KbVisibility};
+use crate::modules::tickets::{AutomationRule, AutomationTrigger};
+use crate::utils::error::AppError;
+
+// ============================================================================
+// KINDS
+// ============================================================================
+
+/// What a shared template copies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TemplateKind {
+    Automation,
+    KbArticle,
+}
+
+impl TemplateKind {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "automation" => Some(Self::Automation),
+            "kb_article" => Some(Self::KbArticle),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Automation => "automation",
+            Self::KbArticle => "kb_article",
+        }
+    }
+}
+
+// ============================================================================
+// REFERENCES
+// ============================================================================
+
+/// Tenant records a template may point at. Users and companies are
+/// specific to one customer base, so content naming them can't be shared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferenceKind {
+    TicketStatus,
+    TicketPriority,
+    TicketQueue,
+    KbCategory,
+}
+
+impl ReferenceKind {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "ticket_status" => Some(Self::TicketStatus),
+            "ticket_priority" => Some(Self::TicketPriority),
+            "ticket_queue" => Some(Self::TicketQueue),
+            "kb_category" => Some(Self::KbCategory),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::TicketStatus => "ticket_status",
+            Self::TicketPriority => "ticket_priority",
+            Self::TicketQueue => "ticket_queue",
+            Self::KbCategory => "kb_category",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::TicketStatus => "Ticket status",
+            Self::TicketPriority => "Ticket priority",
+            Self::TicketQueue => "Ticket queue",
+            Self::KbCategory => "KB category",
+        }
+    }
+}
+
+/// A record in the publishing tenant, by name so an importing tenant can
+/// find its own
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateReference {
+    pub kind: ReferenceKind,
+    pub name: String,
+}
+
+/// Every string in `value` that parses as a UUID
+fn collect_ids(value: &serde_json::Value, ids: &mut BTreeSet<Uuid>) {
+    match value {
+        serde_json::Value::String(s) => {
+            if let Ok(id) = Uuid::parse_str(s) {
+                ids.insert(id);
+            }
+        }
+        serde_json::Value::Array(items) => items.iter().for_each(|item| collect_ids(item, ids)),
+        serde_json::Value::Object(fields) => fields.values().for_each(|field| collect_ids(field, ids)),
+        _ => {}
+    }
+}
+
+/// A copy of `value` with each UUID string swapped through `ids`. Fails on
+/// the first id without a mapping.
+fn remap_ids(value: &serde_json::Value, ids: &HashMap<Uuid, Uuid>) -> Result<serde_json::Value, Uuid> {
+    Ok(match value {
+        serde_json::Value::String(s) => match Uuid::parse_str(s) {
+            Ok(id) => serde_json::Value::String(ids.get(&id).ok_or(id)?.to_string()),
+            Err(_) => value.clone(),
+        },
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.iter().map(|item| remap_ids(item, ids)).collect::<Result<_, _>>()?)
+        }
+        serde_json::Value::Object(fields) => serde_json::Value::Object(
+            fields
+                .iter()
+                .map(|(key, field)| Ok((key.clone(), remap_ids(field, ids)?)))
+                .collect::<Result<_, Uuid>>()?,
+        ),
+        _ => value.clone(),
+    })
+}
+
+// ============================================================================
+// CONTENT
+// ============================================================================
+
+/// An automation rule without its tenant, id or run history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AutomationTemplate {
+    pub name: String,
+    pub description: Option<String>,
+    pub trigger_type: AutomationTrigger,
+    pub conditions: serde_json::Value,
+    pub actions: serde_json::Value,
+    pub priority: i32,
+}
+
+impl AutomationTemplate {
+    pub fn from_rule(rule: &AutomationRule) -> Self {
+        Self {
+            name: rule.name.clone(),
+            description: rule.description.clone(),
+            trigger_type: rule.trigger_type,
+            conditions: rule.conditions.clone(),
+            actions: rule.actions.clone(),
+            priority: rule.priority,
+        }
+    }
+
+    /// A new rule for `tenant_id`. It starts inactive so the importing admin
+    /// can review it before it touches tickets.
+    pub fn into_rule(self, tenant_id: Uuid, now: DateTime<Utc>) -> AutomationRule {
+        AutomationRule {
+            id: Uuid::new_v4(),
+            tenant_id,
+            name: self.name,
+            description: self.description,
+            is_active: false,
+            trigger_type: self.trigger_type,
+            conditions: self.conditions,
+            actions: self.actions,
+            priority: self.priority,
+            last_run_at: None,
+            run_count: 0,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+/// A KB article without its tenant, author or engagement. Client-specific
+/// articles are shared as internal since their audience doesn't carry over.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KbArticleTemplate {
+    pub title: String,
+    pub content: String,
+    pub summary: Option<String>,
+    pub category_id: Option<Uuid>,
+    pub visibility: KbVisibility,
+    pub tags: Vec<String>,
+}
+
+impl KbArticleTemplate {
+    pub fn from_article(article: &KbArticle) -> Self {
+        Self {
+            title: article.title.clone(),
+            content: article.content.clone(),
+            summary: article.summary.clone(),
+            category_id: article.category_id,
+            visibility: match article.visibility {
+                KbVisibility::ClientSpecific => KbVisibility::Internal,
+                visibility => visibility,
+            },
+            tags: article.tags.clone(),
+        }
+    }
+
+    /// The request creating the article as a draft in the importing tenant
+    pub fn into_request(self) -> CreateArticleRequest {
+        CreateArticleRequest {
+            title: self.title,
+            content: self.content,
+            summary: self.summary,
+            category_id: self.category_id,
+            visibility: self.visibility,
+            company_ids: Vec::new(),
+            tags: self.tags,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "template", rename_all = "snake_case")]
+pub enum TemplateContent {
+    Automation(AutomationTemplate),
+    KbArticle(KbArticleTemplate),
+}
+
+impl TemplateContent {
+    pub fn kind(&self) -> TemplateKind {
+        match self {
+            Self::Automation(_) => TemplateKind::Automation,
+            Self::KbArticle(_) => TemplateKind::KbArticle,
+        }
+    }
+
+    /// Ids of tenant records the content points at
+    pub fn referenced_ids(&self) -> BTreeSet<Uuid> {
+        let mut ids = BTreeSet::new();
+        match self {
+            Self::Automation(template) => {
+                collect_ids(&template.conditions, &mut ids);
+                collect_ids(&template.actions, &mut ids);
+            }
+            Self::KbArticle(template) => ids.extend(template.category_id),
+        }
+        ids
+    }
+
+    /// A copy pointing at the importing tenant's records. `ids` maps each
+    /// source id to the importing tenant's; an id without one fails.
+    pub fn remap(&self, ids: &HashMap<Uuid, Uuid>) -> Result<Self, Uuid> {
+        Ok(match self {
+            Self::Automation(template) => Self::Automation(AutomationTemplate {
+                conditions: remap_ids(&template.conditions, ids)?,
+                actions: remap_ids(&template.actions, ids)?,
+                ..template.clone()
+            }),
+            Self::KbArticle(template) => Self::KbArticle(KbArticleTemplate {
+                category_id: template
+                    .category_id
+                    .map(|id| ids.get(&id).copied().ok_or(id))
+                    .transpose()?,
+                ..template.clone()
+            }),
+        })
+    }
+}
+
+// ============================================================================
+// LIBRARY
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedTemplate {
+    pub id: Uuid,
+    pub kind: TemplateKind,
+    pub name: String,
+    pub description: Option<String>,
+    pub content: TemplateContent,
+    /// What each source id in the content stood for in the publishing tenant
+    pub references: BTreeMap<Uuid, TemplateReference>,
+    pub source_tenant_id: Option<Uuid>,
+    pub published_by_id: Option<Uuid>,
+    pub import_count: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SharedTemplate {
+    /// The content for the importing tenant, given the ids its records
+    /// resolved to. Any reference without a match in `resolved` refuses the
+    /// import rather than leave the copy pointing at another tenant's data.
+    pub fn import_content(&self, resolved: &HashMap<Uuid, Uuid>) -> Result<TemplateContent, AppError> {
+        let missing: Vec<String> = self
+            .references
+            .iter()
+            .filter(|(id, _)| !resolved.contains_key(id))
+            .map(|(_, reference)| format!("{} '{}'", reference.kind.label(), reference.name))
+            .collect();
+        if !missing.is_empty() {
+            return Err(AppError::BadRequest(format!(
+                "Template references records this tenant doesn't have: {}",
+                missing.join(", ")
+            )));
+        }
+
+        self.content
+            .remap(resolved)
+            .map_err(|_| AppError::BadRequest("Template references a record that can't be resolved".to_string()))
+    }
+}
+
+/// Publish one of the tenant's automation rules or KB articles
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct PublishTemplateRequest {
+    pub kind: TemplateKind,
+    /// The rule or article to copy
+    pub source_id: Uuid,
+    /// Defaults to the rule's name or article's title
+    #[validate(length(min = 1, max = 255))]
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct SharedTemplateFilter {
+    pub kind: Option<TemplateKind>,
+}
+
+/// The record an import created in the importing tenant
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportedTemplate {
+    pub template_id: Uuid,
+    pub kind: TemplateKind,
+    pub id: Uuid,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rule(tenant_id: Uuid, status_id: Uuid, queue_id: Uuid) -> AutomationRule {
+        let now = Utc::now();
+        AutomationRule {
+            id: Uuid::new_v4(),
+            tenant_id,
+            name: "Close stale waiting tickets".to_string(),
+            description: Some("After a week waiting on the customer".to_string()),
+            is_active: true,
+            trigger_type: AutomationTrigger::OnAging,
+            conditions: json!([{"field": "status", "operator": "equals", "value": "Waiting"}]),
+            actions: json!([
+                {"action_type": "set_status", "params": {"status_id": status_id.to_string()}},
+                {"action_type": "set_queue", "params": {"queue_id": queue_id.to_string()}},
+                {"action_type": "add_note", "params": {"content": "Closed after a week without reply"}}
+            ]),
+            priority: 10,
+            last_run_at: Some(now),
+            run_count: 42,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn shared(content: TemplateContent, references: BTreeMap<Uuid, TemplateReference>) -> SharedTemplate {
+        SharedTemplate {
+            id: Uuid::new_v4(),
+            kind: content.kind(),
+            name: "Close stale waiting tickets".to_string(),
+            description: None,
+            content,
+            references,
+            source_tenant_id: Some(Uuid::new_v4()),
+            published_by_id: None,
+            import_count: 0,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_imported_automation_is_a_standalone_copy() {
+        let (source_tenant, source_status, source_queue) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let source = rule(source_tenant, source_status, source_queue);
+
+        let content = TemplateContent::Automation(AutomationTemplate::from_rule(&source));
+        assert_eq!(content.referenced_ids(), BTreeSet::from([source_status, source_queue]));
+        let template = shared(
+            content,
+            BTreeMap::from([
+                (source_status, TemplateReference { kind: ReferenceKind::TicketStatus, name: "Closed".to_string() }),
+                (source_queue, TemplateReference { kind: ReferenceKind::TicketQueue, name: "Tier 1".to_string() }),
+            ]),
+        );
+
+        // The new tenant's own "Closed" status and "Tier 1" queue
+        let (tenant, status, queue) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let resolved = HashMap::from([(source_status, status), (source_queue, queue)]);
+        let TemplateContent::Automation(imported) = template.import_content(&resolved).unwrap() else {
+            panic!("expected an automation template");
+        };
+        let copy = imported.into_rule(tenant, Utc::now());
+
+        assert_eq!(copy.tenant_id, tenant);
+        assert_ne!(copy.id, source.id);
+        assert_eq!(copy.name, source.name);
+        assert_eq!(copy.trigger_type, AutomationTrigger::OnAging);
+        assert_eq!(copy.actions[0]["params"]["status_id"], json!(status.to_string()));
+        assert_eq!(copy.actions[1]["params"]["queue_id"], json!(queue.to_string()));
+        assert_eq!(copy.actions[2], source.actions[2]);
+        assert_eq!(copy.conditions, source.conditions);
+
+        // Nothing points back at the publishing tenant, and no history comes along
+        let mut ids = BTreeSet::new();
+        collect_ids(&copy.conditions, &mut ids);
+        collect_ids(&copy.actions, &mut ids);
+        assert_eq!(ids, BTreeSet::from([status, queue]));
+        assert!(!copy.is_active);
+        assert_eq!(copy.run_count, 0);
+        assert_eq!(copy.last_run_at, None);
+
+        // The shared copy is untouched by the import
+        assert_eq!(template.content.referenced_ids(), BTreeSet::from([source_status, source_queue]));
+    }
+
+    #[test]
+    fn test_import_refused_when_a_reference_does_not_resolve() {
+        let (source_status, source_queue) = (Uuid::new_v4(), Uuid::new_v4());
+        let content = TemplateContent::Automation(AutomationTemplate::from_rule(&rule(
+            Uuid::new_v4(),
+            source_status,
+            source_queue,
+        )));
+        let template = shared(
+            content,
+            BTreeMap::from([
+                (source_status, TemplateReference { kind: ReferenceKind::TicketStatus, name: "Closed".to_string() }),
+                (source_queue, TemplateReference { kind: ReferenceKind::TicketQueue, name: "Tier 1".to_string() }),
+            ]),
+        );
+
+        // The importing tenant has no "Tier 1" queue
+        let resolved = HashMap::from([(source_status, Uuid::new_v4())]);
+        match template.import_content(&resolved) {
+            Err(AppError::BadRequest(message)) => assert!(message.contains("Ticket queue 'Tier 1'")),
+            other => panic!("expected the import to be refused, got {:?}", other.map(|content| content.kind())),
+        }
+
+        // Nor is an id that was never recorded as a reference carried over
+        let unrecorded = shared(template.content.clone(), BTreeMap::new());
+        assert!(unrecorded.import_content(&HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_imported_article_uses_the_tenants_category() {
+        let category = Uuid::new_v4();
+        let template = KbArticleTemplate {
+            title: "Reset your VPN token".to_string(),
+            content: "Open the portal and...".to_string(),
+            summary: None,
+            category_id: Some(category),
+            visibility: KbVisibility::Public,
+            tags: vec!["vpn".to_string()],
+        };
+        let content = TemplateContent::KbArticle(template);
+        assert_eq!(content.referenced_ids(), BTreeSet::from([category]));
+
+        let local = Uuid::new_v4();
+        let TemplateContent::KbArticle(imported) = content.remap(&HashMap::from([(category, local)])).unwrap() else {
+            panic!("expected a KB article template");
+        };
+        let request = imported.into_request();
+        assert_eq!(request.category_id, Some(local));
+        assert_eq!(request.visibility, KbVisibility::Public);
+    }
+}
@@ -0,0 +1,91 @@
+//! Shared template API routes
+
+use axum::{
+    extract::{Path, Query, State},
+    routing::{delete, get, post},
+    Json, Router,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::{ImportedTemplate, PublishTemplateRequest, SharedTemplate, SharedTemplateFilter, SharedTemplateService};
+use crate::modules::auth::RequireAdmin;
+use crate::utils::error::AppResult;
+
+#[derive(Clone)]
+pub struct SharedTemplateRouterState {
+    pub shared_template_service: Arc<SharedTemplateService>,
+}
+
+/// Create the shared template library router
+pub fn shared_template_routes(shared_template_service: SharedTemplateService) -> Router {
+    let state = SharedTemplateRouterState {
+        shared_template_service: Arc::new(shared_template_service),
+    };
+
+    Router::new()
+        .route("/", get(list_templates))
+        .route("/", post(publish_template))
+        .route("/:template_id", get(get_template))
+        .route("/:template_id", delete(unpublish_template))
+        .route("/:template_id/import", post(import_template))
+        .with_state(state)
+}
+
+async fn list_templates(
+    State(state): State<SharedTemplateRouterState>,
+    RequireAdmin(_user, _): RequireAdmin,
+    Query(filter): Query<SharedTemplateFilter>,
+) -> AppResult<Json<Vec<SharedTemplate>>> {
+    let templates = state.shared_template_service.list_templates(&filter).await?;
+    Ok(Json(templates))
+}
+
+async fn get_template(
+    State(state): State<SharedTemplateRouterState>,
+    RequireAdmin(_user, _): RequireAdmin,
+    Path(template_id): Path<Uuid>,
+) -> AppResult<Json<SharedTemplate>> {
+    let template = state.shared_template_service.get_template(template_id).await?;
+    Ok(Json(template))
+}
+
+async fn publish_template(
+    State(state): State<SharedTemplateRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Json(request): Json<PublishTemplateRequest>,
+) -> AppResult<Json<SharedTemplate>> {
+    request.validate()?;
+
+    let template = state
+        .shared_template_service
+        .publish(user.tenant_id, user.id, &request)
+        .await?;
+
+    Ok(Json(template))
+}
+
+async fn unpublish_template(
+    State(state): State<SharedTemplateRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Path(template_id): Path<Uuid>,
+) -> AppResult<()> {
+    state
+        .shared_template_service
+        .unpublish(user.tenant_id, template_id)
+        .await
+}
+
+async fn import_template(
+    State(state): State<SharedTemplateRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Path(template_id): Path<Uuid>,
+) -> AppResult<Json<ImportedTemplate>> {
+    let imported = state
+        .shared_template_service
+        .import(user.tenant_id, user.id, template_id)
+        .await?;
+
+    Ok(Json(imported))
+}
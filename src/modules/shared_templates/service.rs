@@ -0,0 +1,244 @@
+//! Shared template service implementation
+
+use chrono::Utc;
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::modules::knowledge_base::KnowledgeBaseService;
+use crate::modules::tickets::AutomationEngine;
+use crate::utils::error::{AppError, AppResult};
+
+use super::models::*;
+
+const TEMPLATE_COLUMNS: &str = "id, kind, name, description, content, reference_names, source_tenant_id, \
+     published_by_id, import_count, created_at, updated_at";
+
+/// Tenant records a template may reference, by id, with their names
+const SOURCE_REFERENCES_SQL: &str = r#"
+    SELECT id, 'ticket_status' AS kind, name FROM ticket_statuses WHERE tenant_id = $1 AND id = ANY($2)
+    UNION ALL
+    SELECT id, 'ticket_priority', name FROM ticket_priorities WHERE tenant_id = $1 AND id = ANY($2)
+    UNION ALL
+    SELECT id, 'ticket_queue', name FROM ticket_queues WHERE tenant_id = $1 AND id = ANY($2)
+    UNION ALL
+    SELECT id, 'kb_category', name FROM kb_categories WHERE tenant_id = $1 AND id = ANY($2)
+"#;
+
+/// The library of templates tenants publish for each other
+#[derive(Clone)]
+pub struct SharedTemplateService {
+    db: Database,
+    automation: AutomationEngine,
+    kb: KnowledgeBaseService,
+}
+
+impl SharedTemplateService {
+    pub fn new(db: Database) -> Self {
+        Self {
+            automation: AutomationEngine::new(db.clone()),
+            kb: KnowledgeBaseService::new(db.clone()),
+            db,
+        }
+    }
+
+    pub async fn list_templates(&self, filter: &SharedTemplateFilter) -> AppResult<Vec<SharedTemplate>> {
+        let rows = sqlx::query_as::<_, SharedTemplateRow>(&format!(
+            "SELECT {} FROM shared_templates WHERE ($1::TEXT IS NULL OR kind = $1) ORDER BY kind, name",
+            TEMPLATE_COLUMNS
+        ))
+        .bind(filter.kind.map(|kind| kind.as_str()))
+        .fetch_all(self.db.pool())
+        .await?;
+
+        rows.into_iter().map(TryInto::try_into).collect()
+    }
+
+    pub async fn get_template(&self, template_id: Uuid) -> AppResult<SharedTemplate> {
+        let row = sqlx::query_as::<_, SharedTemplateRow>(&format!(
+            "SELECT {} FROM shared_templates WHERE id = $1",
+            TEMPLATE_COLUMNS
+        ))
+        .bind(template_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::not_found("Shared template"))?;
+
+        row.try_into()
+    }
+
+    /// Copy one of the tenant's rules or articles into the library. Every id
+    /// it points at must be a status, priority, queue or KB category, which
+    /// are recorded by name; anything else (a user, a company) is refused.
+    pub async fn publish(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        request: &PublishTemplateRequest,
+    ) -> AppResult<SharedTemplate> {
+        let (content, default_name) = match request.kind {
+            TemplateKind::Automation => {
+                let rule = self.automation.get_rule(tenant_id, request.source_id).await?;
+                (TemplateContent::Automation(AutomationTemplate::from_rule(&rule)), rule.name)
+            }
+            TemplateKind::KbArticle => {
+                let article = self.kb.get_article(tenant_id, request.source_id).await?;
+                (TemplateContent::KbArticle(KbArticleTemplate::from_article(&article)), article.title)
+            }
+        };
+
+        let ids: Vec<Uuid> = content.referenced_ids().into_iter().collect();
+        let rows = sqlx::query_as::<_, (Uuid, String, String)>(SOURCE_REFERENCES_SQL)
+            .bind(tenant_id)
+            .bind(&ids)
+            .fetch_all(self.db.pool())
+            .await?;
+
+        let mut references = BTreeMap::new();
+        for (id, kind, name) in rows {
+            if let Some(kind) = ReferenceKind::from_str(&kind) {
+                references.insert(id, TemplateReference { kind, name });
+            }
+        }
+        if ids.iter().any(|id| !references.contains_key(id)) {
+            return Err(AppError::BadRequest(
+                "Only templates referencing statuses, priorities, queues and KB categories can be shared".to_string(),
+            ));
+        }
+
+        let template_id = Uuid::new_v4();
+        sqlx::query(
+            r#"
+            INSERT INTO shared_templates (id, kind, name, description, content, reference_names,
+                                          source_tenant_id, published_by_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(template_id)
+        .bind(request.kind.as_str())
+        .bind(request.name.clone().unwrap_or(default_name))
+        .bind(&request.description)
+        .bind(serde_json::to_value(&content)?)
+        .bind(serde_json::to_value(&references)?)
+        .bind(tenant_id)
+        .bind(user_id)
+        .execute(self.db.pool())
+        .await?;
+
+        self.get_template(template_id).await
+    }
+
+    /// Take a template out of the library. Only its publishing tenant can.
+    pub async fn unpublish(&self, tenant_id: Uuid, template_id: Uuid) -> AppResult<()> {
+        let result = sqlx::query("DELETE FROM shared_templates WHERE id = $1 AND source_tenant_id = $2")
+            .bind(template_id)
+            .bind(tenant_id)
+            .execute(self.db.pool())
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::not_found("Shared template"));
+        }
+        Ok(())
+    }
+
+    /// Copy a template into the tenant as a new rule or draft article, its
+    /// references resolved to the tenant's own records by name
+    pub async fn import(&self, tenant_id: Uuid, user_id: Uuid, template_id: Uuid) -> AppResult<ImportedTemplate> {
+        let template = self.get_template(template_id).await?;
+
+        let mut resolved = HashMap::new();
+        for (source_id, reference) in &template.references {
+            if let Some(id) = self.resolve_reference(tenant_id, reference).await? {
+                resolved.insert(*source_id, id);
+            }
+        }
+
+        let id = match template.import_content(&resolved)? {
+            TemplateContent::Automation(automation) => {
+                self.automation
+                    .insert_rule(&automation.into_rule(tenant_id, Utc::now()))
+                    .await?
+                    .id
+            }
+            TemplateContent::KbArticle(article) => {
+                self.kb
+                    .create_article(tenant_id, user_id, &article.into_request())
+                    .await?
+                    .id
+            }
+        };
+
+        sqlx::query("UPDATE shared_templates SET import_count = import_count + 1 WHERE id = $1")
+            .bind(template_id)
+            .execute(self.db.pool())
+            .await?;
+
+        Ok(ImportedTemplate {
+            template_id,
+            kind: template.kind,
+            id,
+        })
+    }
+
+    /// The tenant's record of the same kind and name, ignoring case
+    async fn resolve_reference(&self, tenant_id: Uuid, reference: &TemplateReference) -> AppResult<Option<Uuid>> {
+        let table = match reference.kind {
+            ReferenceKind::TicketStatus => "ticket_statuses",
+            ReferenceKind::TicketPriority => "ticket_priorities",
+            ReferenceKind::TicketQueue => "ticket_queues",
+            ReferenceKind::KbCategory => "kb_categories",
+        };
+
+        let id = sqlx::query_scalar(&format!(
+            "SELECT id FROM {} WHERE tenant_id = $1 AND LOWER(name) = LOWER($2) ORDER BY created_at LIMIT 1",
+            table
+        ))
+        .bind(tenant_id)
+        .bind(&reference.name)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        Ok(id)
+    }
+}
+
+// ============================================================================
+// DATABASE ROW TYPES
+// ============================================================================
+
+#[derive(sqlx::FromRow)]
+struct SharedTemplateRow {
+    id: Uuid,
+    kind: String,
+    name: String,
+    description: Option<String>,
+    content: serde_json::Value,
+    reference_names: serde_json::Value,
+    source_tenant_id: Option<Uuid>,
+    published_by_id: Option<Uuid>,
+    import_count: i32,
+    created_at: chrono::DateTime<Utc>,
+    updated_at: chrono::DateTime<Utc>,
+}
+
+impl TryFrom<SharedTemplateRow> for SharedTemplate {
+    type Error = AppError;
+
+    fn try_from(row: SharedTemplateRow) -> Result<Self, Self::Error> {
+        let content: TemplateContent = serde_json::from_value(row.content)?;
+        Ok(Self {
+            id: row.id,
+            kind: TemplateKind::from_str(&row.kind).unwrap_or(content.kind()),
+            name: row.name,
+            description: row.description,
+            content,
+            references: serde_json::from_value(row.reference_names)?,
+            source_tenant_id: row.source_tenant_id,
+            published_by_id: row.published_by_id,
+            import_count: row.import_count,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        })
+    }
+}
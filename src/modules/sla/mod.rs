@@ -1 +1,16 @@
-//! sla module placeholder
+//! SLA Module
+//!
+//! Business hours, holiday calendars and the business-time arithmetic SLA
+//! targets are measured with.
+
+mod models;
+#[cfg(feature = "server")]
+mod service;
+#[cfg(feature = "server")]
+mod routes;
+
+pub use models::*;
+#[cfg(feature = "server")]
+pub use service::SlaCalendarService;
+#[cfg(feature = "server")]
+pub use routes::holiday_calendar_routes;
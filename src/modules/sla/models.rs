@@ -0,0 +1,572 @@
+//! SLA calendar models and types
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use uuid::Uuid;
+use validator::Validate;
+
+// ============================================================================
+// BUSINESS HOURS
+// ============================================================================
+
+/// Opening hours on one day, in the schedule's local time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BusinessWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+/// A `business_hours` row: a weekly schedule in a time zone
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BusinessSchedule {
+    pub timezone: Tz,
+    /// Indexed by days from Sunday, matching the stored keys "0" to "6"
+    pub days: [Option<BusinessWindow>; 7],
+}
+
+impl BusinessSchedule {
+    /// Parse the stored schedule, `{"1": {"start": "08:00", "end": "17:00"}, ...}`.
+    /// Unknown zones fall back to UTC; missing or malformed days are closed.
+    pub fn from_config(timezone: &str, schedule: &serde_json::Value) -> Self {
+        let time = |value: &serde_json::Value| {
+            value
+                .as_str()
+                .and_then(|s| NaiveTime::parse_from_str(s, "%H:%M").ok())
+        };
+
+        let mut days = [None; 7];
+        for (index, day) in days.iter_mut().enumerate() {
+            let Some(window) = schedule.get(index.to_string()) else {
+                continue;
+            };
+            if let (Some(start), Some(end)) = (time(&window["start"]), time(&window["end"])) {
+                if start < end {
+                    *day = Some(BusinessWindow { start, end });
+                }
+            }
+        }
+
+        Self {
+            timezone: timezone.trim().parse().unwrap_or(Tz::UTC),
+            days,
+        }
+    }
+
+    fn window(&self, date: NaiveDate) -> Option<BusinessWindow> {
+        self.days[date.weekday().num_days_from_sunday() as usize]
+    }
+
+    fn local_instant(&self, date: NaiveDate, time: NaiveTime) -> Option<DateTime<Utc>> {
+        self.timezone
+            .from_local_datetime(&date.and_time(time))
+            .earliest()
+            .map(|at| at.with_timezone(&Utc))
+    }
+}
+
+/// Business hours and the holidays they close for. What business-hours SLA
+/// targets are measured against.
+#[derive(Debug, Clone)]
+pub struct BusinessCalendar {
+    pub schedule: BusinessSchedule,
+    holidays: BTreeSet<NaiveDate>,
+}
+
+/// How far ahead to look for opening hours before giving up on a schedule
+const MAX_CALENDAR_DAYS: i64 = 3660;
+
+impl BusinessCalendar {
+    pub fn new(schedule: BusinessSchedule) -> Self {
+        Self {
+            schedule,
+            holidays: BTreeSet::new(),
+        }
+    }
+
+    /// Close on these holidays as well. Calendars from several regions can be
+    /// layered for a location that observes more than one.
+    pub fn with_holidays<'a>(mut self, holidays: impl IntoIterator<Item = &'a Holiday>) -> Self {
+        self.holidays.extend(holidays.into_iter().map(|holiday| holiday.date));
+        self
+    }
+
+    pub fn is_holiday(&self, date: NaiveDate) -> bool {
+        self.holidays.contains(&date)
+    }
+
+    /// The instant `minutes` of business time after `start`. Time outside the
+    /// schedule and on holidays doesn't count. A schedule that is never open
+    /// counts round the clock.
+    pub fn add_business_minutes(&self, start: DateTime<Utc>, minutes: i64) -> DateTime<Utc> {
+        if minutes <= 0 {
+            return start;
+        }
+
+        let mut remaining = Duration::minutes(minutes);
+        let first_day = start.with_timezone(&self.schedule.timezone).date_naive();
+
+        for day in first_day.iter_days().take(MAX_CALENDAR_DAYS as usize) {
+            if self.is_holiday(day) {
+                continue;
+            }
+            let Some(window) = self.schedule.window(day) else {
+                continue;
+            };
+            let (Some(open), Some(close)) = (
+                self.schedule.local_instant(day, window.start),
+                self.schedule.local_instant(day, window.end),
+            ) else {
+                continue;
+            };
+
+            let from = open.max(start);
+            if from >= close {
+                continue;
+            }
+            if remaining <= close - from {
+                return from + remaining;
+            }
+            remaining -= close - from;
+        }
+
+        start + Duration::minutes(minutes)
+    }
+}
+
+// ============================================================================
+// HOLIDAYS
+// ============================================================================
+
+/// A day the business is closed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Holiday {
+    pub date: NaiveDate,
+    pub name: String,
+}
+
+/// Regions with a bundled public holiday dataset. National holidays only;
+/// state and provincial ones are added to a calendar by hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum HolidayRegion {
+    /// United States federal holidays
+    Us,
+    /// Canadian federal statutory holidays
+    Ca,
+    /// Bank holidays in England and Wales
+    Gb,
+    /// Australian national public holidays
+    Au,
+}
+
+impl HolidayRegion {
+    pub const ALL: [HolidayRegion; 4] = [Self::Us, Self::Ca, Self::Gb, Self::Au];
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "US" => Some(Self::Us),
+            "CA" => Some(Self::Ca),
+            "GB" => Some(Self::Gb),
+            "AU" => Some(Self::Au),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Us => "US",
+            Self::Ca => "CA",
+            Self::Gb => "GB",
+            Self::Au => "AU",
+        }
+    }
+
+    /// Name given to the region's imported calendar
+    pub fn calendar_name(&self) -> &'static str {
+        match self {
+            Self::Us => "United States federal holidays",
+            Self::Ca => "Canada statutory holidays",
+            Self::Gb => "England and Wales bank holidays",
+            Self::Au => "Australia public holidays",
+        }
+    }
+
+    fn rules(&self) -> &'static [HolidayRule] {
+        use HolidayDate::*;
+        use Observance::*;
+
+        match self {
+            Self::Us => {
+                const RULES: &[HolidayRule] = &[
+                    HolidayRule::new("New Year's Day", Fixed(1, 1, NearestWeekday)),
+                    HolidayRule::new("Martin Luther King Jr. Day", Nth(1, Weekday::Mon, 3)),
+                    HolidayRule::new("Washington's Birthday", Nth(2, Weekday::Mon, 3)),
+                    HolidayRule::new("Memorial Day", Nth(5, Weekday::Mon, -1)),
+                    HolidayRule::since("Juneteenth", Fixed(6, 19, NearestWeekday), 2021),
+                    HolidayRule::new("Independence Day", Fixed(7, 4, NearestWeekday)),
+                    HolidayRule::new("Labor Day", Nth(9, Weekday::Mon, 1)),
+                    HolidayRule::new("Columbus Day", Nth(10, Weekday::Mon, 2)),
+                    HolidayRule::new("Veterans Day", Fixed(11, 11, NearestWeekday)),
+                    HolidayRule::new("Thanksgiving Day", Nth(11, Weekday::Thu, 4)),
+                    HolidayRule::new("Christmas Day", Fixed(12, 25, NearestWeekday)),
+                ];
+                RULES
+            }
+            Self::Ca => {
+                const RULES: &[HolidayRule] = &[
+                    HolidayRule::new("New Year's Day", Fixed(1, 1, NextWeekday)),
+                    HolidayRule::new("Good Friday", Easter(-2)),
+                    HolidayRule::new("Victoria Day", OnOrBefore(5, 24, Weekday::Mon)),
+                    HolidayRule::new("Canada Day", Fixed(7, 1, NextWeekday)),
+                    HolidayRule::new("Labour Day", Nth(9, Weekday::Mon, 1)),
+                    HolidayRule::since("National Day for Truth and Reconciliation", Fixed(9, 30, NextWeekday), 2021),
+                    HolidayRule::new("Thanksgiving", Nth(10, Weekday::Mon, 2)),
+                    HolidayRule::new("Remembrance Day", Fixed(11, 11, NextWeekday)),
+                    HolidayRule::new("Christmas Day", Fixed(12, 25, NextWeekday)),
+                    HolidayRule::new("Boxing Day", Fixed(12, 26, NextWeekday)),
+                ];
+                RULES
+            }
+            Self::Gb => {
+                const RULES: &[HolidayRule] = &[
+                    HolidayRule::new("New Year's Day", Fixed(1, 1, NextWeekday)),
+                    HolidayRule::new("Good Friday", Easter(-2)),
+                    HolidayRule::new("Easter Monday", Easter(1)),
+                    HolidayRule::new("Early May bank holiday", Nth(5, Weekday::Mon, 1)),
+                    HolidayRule::new("Spring bank holiday", Nth(5, Weekday::Mon, -1)),
+                    HolidayRule::new("Summer bank holiday", Nth(8, Weekday::Mon, -1)),
+                    HolidayRule::new("Christmas Day", Fixed(12, 25, NextWeekday)),
+                    HolidayRule::new("Boxing Day", Fixed(12, 26, NextWeekday)),
+                ];
+                RULES
+            }
+            Self::Au => {
+                const RULES: &[HolidayRule] = &[
+                    HolidayRule::new("New Year's Day", Fixed(1, 1, NextWeekday)),
+                    HolidayRule::new("Australia Day", Fixed(1, 26, NextWeekday)),
+                    HolidayRule::new("Good Friday", Easter(-2)),
+                    HolidayRule::new("Easter Monday", Easter(1)),
+                    HolidayRule::new("Anzac Day", Fixed(4, 25, Unobserved)),
+                    HolidayRule::new("Christmas Day", Fixed(12, 25, NextWeekday)),
+                    HolidayRule::new("Boxing Day", Fixed(12, 26, NextWeekday)),
+                ];
+                RULES
+            }
+        }
+    }
+
+    /// The region's public holidays in `year`, in date order. A holiday that
+    /// falls on a weekend is listed on its date and again on the weekday it is
+    /// observed, so calendars open on weekends close on the actual day too.
+    pub fn holidays(&self, year: i32) -> Vec<Holiday> {
+        let dated: Vec<(&HolidayRule, NaiveDate)> = self
+            .rules()
+            .iter()
+            .filter(|rule| year >= rule.since)
+            .filter_map(|rule| rule.date.resolve(year).map(|date| (rule, date)))
+            .collect();
+
+        // Substitute days skip past dates that are already holidays, so a
+        // weekend Christmas and Boxing Day land on Monday and Tuesday
+        let mut taken: BTreeSet<NaiveDate> = dated
+            .iter()
+            .map(|(_, date)| *date)
+            .filter(|date| !is_weekend(*date))
+            .collect();
+
+        let mut holidays = Vec::new();
+        for (rule, date) in dated {
+            holidays.push(Holiday {
+                date,
+                name: rule.name.to_string(),
+            });
+
+            if let Some(observed) = rule.date.observed(date, &taken) {
+                taken.insert(observed);
+                holidays.push(Holiday {
+                    date: observed,
+                    name: format!("{} (observed)", rule.name),
+                });
+            }
+        }
+
+        holidays.sort_by_key(|holiday| holiday.date);
+        holidays
+    }
+}
+
+/// When a weekend holiday is given as a day off instead
+#[derive(Debug, Clone, Copy)]
+enum Observance {
+    /// Not moved
+    Unobserved,
+    /// Saturday to Friday, Sunday to Monday, as US federal holidays are
+    NearestWeekday,
+    /// The next weekday that isn't already a holiday
+    NextWeekday,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum HolidayDate {
+    /// Month and day
+    Fixed(u32, u32, Observance),
+    /// Nth weekday of a month; negative counts from the end
+    Nth(u32, Weekday, i32),
+    /// The weekday falling on or before a month and day
+    OnOrBefore(u32, u32, Weekday),
+    /// Days from Easter Sunday
+    Easter(i64),
+}
+
+impl HolidayDate {
+    fn resolve(&self, year: i32) -> Option<NaiveDate> {
+        match *self {
+            Self::Fixed(month, day, _) => NaiveDate::from_ymd_opt(year, month, day),
+            Self::Nth(month, weekday, n) if n > 0 => {
+                NaiveDate::from_weekday_of_month_opt(year, month, weekday, n as u8)
+            }
+            Self::Nth(month, weekday, n) => {
+                let last = last_day_of_month(year, month)?;
+                let back = (7 + last.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+                Some(last - Duration::days(back as i64 + 7 * (-n as i64 - 1)))
+            }
+            Self::OnOrBefore(month, day, weekday) => {
+                let date = NaiveDate::from_ymd_opt(year, month, day)?;
+                let back = (7 + date.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+                Some(date - Duration::days(back as i64))
+            }
+            Self::Easter(offset) => easter_sunday(year).map(|easter| easter + Duration::days(offset)),
+        }
+    }
+
+    fn observed(&self, date: NaiveDate, taken: &BTreeSet<NaiveDate>) -> Option<NaiveDate> {
+        let Self::Fixed(_, _, observance) = *self else {
+            return None;
+        };
+        if !is_weekend(date) {
+            return None;
+        }
+
+        match observance {
+            Observance::Unobserved => None,
+            Observance::NearestWeekday => Some(match date.weekday() {
+                Weekday::Sat => date - Duration::days(1),
+                _ => date + Duration::days(1),
+            }),
+            Observance::NextWeekday => date
+                .iter_days()
+                .skip(1)
+                .find(|day| !is_weekend(*day) && !taken.contains(day)),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct HolidayRule {
+    name: &'static str,
+    date: HolidayDate,
+    /// First year the holiday was observed
+    since: i32,
+}
+
+impl HolidayRule {
+    const fn new(name: &'static str, date: HolidayDate) -> Self {
+        Self::since(name, date, i32::MIN)
+    }
+
+    const fn since(name: &'static str, date: HolidayDate, since: i32) -> Self {
+        Self { name, date, since }
+    }
+}
+
+fn is_weekend(date: NaiveDate) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+fn last_day_of_month(year: i32, month: u32) -> Option<NaiveDate> {
+    let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1).and_then(|first| first.pred_opt())
+}
+
+/// Western Easter Sunday, by the anonymous Gregorian algorithm
+fn easter_sunday(year: i32) -> Option<NaiveDate> {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+}
+
+/// Merge freshly imported holidays into a calendar's list. Re-importing a year
+/// replaces the matching entries instead of duplicating them; anything
+/// entered by hand stays.
+pub fn merge_holidays(existing: Vec<Holiday>, imported: Vec<Holiday>) -> Vec<Holiday> {
+    let mut merged: Vec<Holiday> = existing
+        .into_iter()
+        .filter(|holiday| !imported.contains(holiday))
+        .chain(imported)
+        .collect();
+    merged.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.name.cmp(&b.name)));
+    merged
+}
+
+// ============================================================================
+// HOLIDAY CALENDARS
+// ============================================================================
+
+/// A named list of holidays, linked to business hours
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HolidayCalendar {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    /// Set on calendars filled from a bundled dataset
+    pub region: Option<HolidayRegion>,
+    pub holidays: Vec<Holiday>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Import a year of a region's public holidays
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct ImportHolidaysRequest {
+    pub region: HolidayRegion,
+    #[validate(range(min = 2000, max = 2100))]
+    pub year: i32,
+    /// Business hours to close on the imported holidays
+    pub business_hours_id: Option<Uuid>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    /// The seeded "Standard Business Hours"
+    fn new_york_office() -> BusinessSchedule {
+        BusinessSchedule::from_config(
+            "America/New_York",
+            &serde_json::json!({
+                "0": null,
+                "1": {"start": "08:00", "end": "17:00"},
+                "2": {"start": "08:00", "end": "17:00"},
+                "3": {"start": "08:00", "end": "17:00"},
+                "4": {"start": "08:00", "end": "17:00"},
+                "5": {"start": "08:00", "end": "17:00"},
+                "6": null
+            }),
+        )
+    }
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_bundled_holidays() {
+        let us = HolidayRegion::Us.holidays(2026);
+        let on = |holidays: &[Holiday], name: &str| {
+            holidays.iter().find(|h| h.name == name).map(|h| h.date)
+        };
+        assert_eq!(on(&us, "Thanksgiving Day"), Some(date(2026, 11, 26)));
+        assert_eq!(on(&us, "Memorial Day"), Some(date(2026, 5, 25)));
+        // July 4th 2026 is a Saturday
+        assert_eq!(on(&us, "Independence Day (observed)"), Some(date(2026, 7, 3)));
+        assert_eq!(on(&HolidayRegion::Us.holidays(2020), "Juneteenth"), None);
+
+        let gb = HolidayRegion::Gb.holidays(2026);
+        assert_eq!(on(&gb, "Good Friday"), Some(date(2026, 4, 3)));
+        assert_eq!(on(&gb, "Easter Monday"), Some(date(2026, 4, 6)));
+
+        // Weekend Christmas and Boxing Day move to Monday and Tuesday
+        let gb = HolidayRegion::Gb.holidays(2027);
+        assert_eq!(on(&gb, "Christmas Day (observed)"), Some(date(2027, 12, 27)));
+        assert_eq!(on(&gb, "Boxing Day (observed)"), Some(date(2027, 12, 28)));
+
+        // Sunday Christmas skips past a Boxing Day that is already Monday
+        let ca = HolidayRegion::Ca.holidays(2022);
+        assert_eq!(on(&ca, "Christmas Day (observed)"), Some(date(2022, 12, 27)));
+        assert_eq!(on(&ca, "Victoria Day"), Some(date(2022, 5, 23)));
+
+        for region in HolidayRegion::ALL {
+            assert_eq!(HolidayRegion::from_str(region.as_str()), Some(region));
+        }
+    }
+
+    #[test]
+    fn test_business_minutes_skip_imported_holidays() {
+        // Wednesday 16:00 in New York, two hours to go
+        let start = utc(2026, 11, 25, 21, 0);
+        let calendar = BusinessCalendar::new(new_york_office());
+        assert_eq!(calendar.add_business_minutes(start, 120), utc(2026, 11, 26, 14, 0));
+
+        // Thanksgiving is closed, so the clock runs on into Friday
+        let imported = HolidayRegion::Us.holidays(2026);
+        let calendar = calendar.with_holidays(&imported);
+        assert_eq!(calendar.add_business_minutes(start, 120), utc(2026, 11, 27, 14, 0));
+
+        // Started on the holiday itself, nothing counts until Friday opens
+        assert_eq!(
+            calendar.add_business_minutes(utc(2026, 11, 26, 15, 0), 30),
+            utc(2026, 11, 27, 13, 30)
+        );
+    }
+
+    #[test]
+    fn test_business_minutes_across_regions() {
+        // A New York office that also closes for Canadian holidays
+        let us = HolidayRegion::Us.holidays(2026);
+        let ca = HolidayRegion::Ca.holidays(2026);
+        let calendar = BusinessCalendar::new(new_york_office())
+            .with_holidays(&us)
+            .with_holidays(&ca);
+
+        // Tuesday June 30th 16:00; Canada Day on Wednesday, then the Friday
+        // before Independence Day is closed as well
+        let start = utc(2026, 6, 30, 20, 0);
+        assert_eq!(calendar.add_business_minutes(start, 120), utc(2026, 7, 2, 13, 0));
+        assert_eq!(calendar.add_business_minutes(start, 60 + 9 * 60 + 30), utc(2026, 7, 6, 12, 30));
+    }
+
+    #[test]
+    fn test_business_minutes_outside_hours() {
+        let calendar = BusinessCalendar::new(new_york_office());
+
+        // Saturday rolls to Monday's opening
+        assert_eq!(
+            calendar.add_business_minutes(utc(2026, 3, 7, 15, 0), 60),
+            utc(2026, 3, 9, 13, 0)
+        );
+        assert_eq!(calendar.add_business_minutes(utc(2026, 3, 7, 15, 0), 0), utc(2026, 3, 7, 15, 0));
+
+        // A schedule that is never open counts every minute
+        let always = BusinessCalendar::new(BusinessSchedule::from_config("UTC", &serde_json::json!({})));
+        assert_eq!(always.add_business_minutes(utc(2026, 3, 7, 15, 0), 90), utc(2026, 3, 7, 16, 30));
+
+        let merged = merge_holidays(
+            vec![Holiday {
+                date: date(2026, 8, 14),
+                name: "Company picnic".to_string(),
+            }],
+            HolidayRegion::Us.holidays(2026),
+        );
+        let again = merge_holidays(merged.clone(), HolidayRegion::Us.holidays(2026));
+        assert_eq!(merged, again);
+        assert!(merged.iter().any(|h| h.name == "Company picnic"));
+    }
+}
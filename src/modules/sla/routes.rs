@@ -0,0 +1,58 @@
+//! Holiday calendar API routes
+
+use axum::{
+    extract::State,
+    routing::{get, post},
+    Json, Router,
+};
+use std::sync::Arc;
+
+use super::{HolidayCalendar, ImportHolidaysRequest, SlaCalendarService};
+use crate::modules::auth::{RequireAdmin, RequireAuth};
+use crate::utils::error::AppResult;
+use crate::utils::validation::ValidatedJson;
+
+#[derive(Clone)]
+pub struct HolidayCalendarRouterState {
+    pub sla_calendar_service: Arc<SlaCalendarService>,
+}
+
+/// Create the holiday calendars router
+pub fn holiday_calendar_routes(sla_calendar_service: SlaCalendarService) -> Router {
+    let state = HolidayCalendarRouterState {
+        sla_calendar_service: Arc::new(sla_calendar_service),
+    };
+
+    Router::new()
+        .route("/", get(list_calendars))
+        .route("/import", post(import_holidays))
+        .with_state(state)
+}
+
+async fn list_calendars(
+    State(state): State<HolidayCalendarRouterState>,
+    RequireAuth(user): RequireAuth,
+) -> AppResult<Json<Vec<HolidayCalendar>>> {
+    let calendars = state.sla_calendar_service.list_calendars(user.tenant_id).await?;
+    Ok(Json(calendars))
+}
+
+async fn import_holidays(
+    State(state): State<HolidayCalendarRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    ValidatedJson(request): ValidatedJson<ImportHolidaysRequest>,
+) -> AppResult<Json<HolidayCalendar>> {
+    let calendar = state
+        .sla_calendar_service
+        .import_holidays(user.tenant_id, request.region, request.year)
+        .await?;
+
+    if let Some(business_hours_id) = request.business_hours_id {
+        state
+            .sla_calendar_service
+            .attach_calendar(user.tenant_id, business_hours_id, calendar.id)
+            .await?;
+    }
+
+    Ok(Json(calendar))
+}
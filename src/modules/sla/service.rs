@@ -0,0 +1,178 @@
+//! SLA calendar service implementation
+
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::utils::error::{AppError, AppResult};
+
+use super::models::*;
+
+const HOLIDAY_CALENDAR_COLUMNS: &str = "id, tenant_id, name, region, holidays, created_at, updated_at";
+
+/// Business hours and holiday calendars
+#[derive(Clone)]
+pub struct SlaCalendarService {
+    db: Database,
+}
+
+impl SlaCalendarService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    pub async fn list_calendars(&self, tenant_id: Uuid) -> AppResult<Vec<HolidayCalendar>> {
+        let rows = sqlx::query_as::<_, HolidayCalendarRow>(&format!(
+            "SELECT {} FROM holiday_calendars WHERE tenant_id = $1 ORDER BY name",
+            HOLIDAY_CALENDAR_COLUMNS
+        ))
+        .bind(tenant_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Load a year of the region's public holidays into the tenant's calendar
+    /// for that region, creating it on first import. Other years and holidays
+    /// added by hand are kept.
+    pub async fn import_holidays(
+        &self,
+        tenant_id: Uuid,
+        region: HolidayRegion,
+        year: i32,
+    ) -> AppResult<HolidayCalendar> {
+        if !(2000..=2100).contains(&year) {
+            return Err(AppError::validation_field("year", "Year must be between 2000 and 2100"));
+        }
+
+        let existing: Option<serde_json::Value> = sqlx::query_scalar(
+            "SELECT holidays FROM holiday_calendars WHERE tenant_id = $1 AND region = $2",
+        )
+        .bind(tenant_id)
+        .bind(region.as_str())
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        let existing = existing
+            .and_then(|holidays| serde_json::from_value(holidays).ok())
+            .unwrap_or_default();
+        let holidays = merge_holidays(existing, region.holidays(year));
+
+        let row = sqlx::query_as::<_, HolidayCalendarRow>(&format!(
+            r#"
+            INSERT INTO holiday_calendars (tenant_id, name, region, holidays)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (tenant_id, region) WHERE region IS NOT NULL
+            DO UPDATE SET holidays = EXCLUDED.holidays, updated_at = NOW()
+            RETURNING {}
+            "#,
+            HOLIDAY_CALENDAR_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(region.calendar_name())
+        .bind(region.as_str())
+        .bind(serde_json::to_value(&holidays).map_err(|e| AppError::internal(e.to_string()))?)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(row.into())
+    }
+
+    /// Close `business_hours_id` on the calendar's holidays. A location can
+    /// use calendars from several regions.
+    pub async fn attach_calendar(
+        &self,
+        tenant_id: Uuid,
+        business_hours_id: Uuid,
+        calendar_id: Uuid,
+    ) -> AppResult<()> {
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM holiday_calendars WHERE tenant_id = $1 AND id = $2)",
+        )
+        .bind(tenant_id)
+        .bind(calendar_id)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        if !exists {
+            return Err(AppError::not_found("Holiday calendar"));
+        }
+
+        let result = sqlx::query(
+            r#"
+            UPDATE business_hours
+            SET holidays = array_append(array_remove(COALESCE(holidays, '{}'), $3), $3), updated_at = NOW()
+            WHERE tenant_id = $1 AND id = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(business_hours_id)
+        .bind(calendar_id)
+        .execute(self.db.pool())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::not_found("Business hours"));
+        }
+
+        Ok(())
+    }
+
+    /// Business hours with every linked calendar's holidays, ready for
+    /// `add_business_minutes`
+    pub async fn business_calendar(&self, tenant_id: Uuid, business_hours_id: Uuid) -> AppResult<BusinessCalendar> {
+        let (timezone, schedule, calendar_ids) = sqlx::query_as::<_, (String, serde_json::Value, Option<Vec<Uuid>>)>(
+            "SELECT timezone, schedule, holidays FROM business_hours WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(tenant_id)
+        .bind(business_hours_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::not_found("Business hours"))?;
+
+        let lists: Vec<serde_json::Value> = sqlx::query_scalar(
+            "SELECT holidays FROM holiday_calendars WHERE tenant_id = $1 AND id = ANY($2)",
+        )
+        .bind(tenant_id)
+        .bind(calendar_ids.unwrap_or_default())
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let holidays: Vec<Holiday> = lists
+            .into_iter()
+            .filter_map(|list| serde_json::from_value::<Vec<Holiday>>(list).ok())
+            .flatten()
+            .collect();
+
+        Ok(BusinessCalendar::new(BusinessSchedule::from_config(&timezone, &schedule)).with_holidays(&holidays))
+    }
+}
+
+// ============================================================================
+// DATABASE ROW TYPES
+// ============================================================================
+
+#[derive(sqlx::FromRow)]
+struct HolidayCalendarRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    name: String,
+    region: Option<String>,
+    holidays: serde_json::Value,
+    created_at: chrono::DateTime<chrono::Utc>,
+    updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<HolidayCalendarRow> for HolidayCalendar {
+    fn from(row: HolidayCalendarRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            name: row.name,
+            region: row.region.as_deref().and_then(HolidayRegion::from_str),
+            holidays: serde_json::from_value(row.holidays).unwrap_or_default(),
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
@@ -5,6 +5,9 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::utils::error::AppError;
+use crate::utils::masking::MaskKind;
+
 /// Tenant status
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "snake_case")]
@@ -63,6 +66,95 @@ pub struct TenantBranding {
     pub support_email: Option<String>,
     pub support_phone: Option<String>,
     pub portal_domain: Option<String>,
+    /// Plain-text footer added to outgoing emails
+    pub email_footer: Option<String>,
+}
+
+/// Platform defaults used wherever a tenant hasn't set its own branding
+pub const DEFAULT_BRAND_NAME: &str = "PSA Platform";
+pub const DEFAULT_PRIMARY_COLOR: &str = "#3b82f6";
+pub const DEFAULT_SECONDARY_COLOR: &str = "#1e40af";
+
+impl TenantBranding {
+    pub fn check(&self) -> Result<(), AppError> {
+        for (field, color) in [("primary_color", &self.primary_color), ("secondary_color", &self.secondary_color)] {
+            if color.as_deref().is_some_and(|c| !c.trim().is_empty() && !is_hex_color(c)) {
+                return Err(AppError::validation_field(field, "Color must be a hex value such as #3b82f6"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Branding with anything unset, blank or invalid taken from the platform
+    /// defaults. `tenant_name` stands in for a missing company name.
+    pub fn resolve(&self, tenant_name: &str) -> ResolvedBranding {
+        let text = |value: Option<&str>| value.map(str::trim).filter(|v| !v.is_empty()).map(String::from);
+        let color = |value: &Option<String>, default: &str| {
+            text(value.as_deref()).filter(|c| is_hex_color(c)).unwrap_or_else(|| default.to_string())
+        };
+
+        ResolvedBranding {
+            company_name: text(self.company_name.as_deref())
+                .or_else(|| text(Some(tenant_name)))
+                .unwrap_or_else(|| DEFAULT_BRAND_NAME.to_string()),
+            logo_url: text(self.logo_url.as_deref()),
+            favicon_url: text(self.favicon_url.as_deref()),
+            primary_color: color(&self.primary_color, DEFAULT_PRIMARY_COLOR),
+            secondary_color: color(&self.secondary_color, DEFAULT_SECONDARY_COLOR),
+            support_email: text(self.support_email.as_deref()),
+            support_phone: text(self.support_phone.as_deref()),
+            email_footer: text(self.email_footer.as_deref()),
+        }
+    }
+}
+
+/// `#rgb` or `#rrggbb`
+fn is_hex_color(value: &str) -> bool {
+    value
+        .strip_prefix('#')
+        .is_some_and(|hex| matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// A tenant's branding with the platform defaults filled in, as applied to
+/// the UI theme, email templates and invoice documents
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolvedBranding {
+    pub company_name: String,
+    pub logo_url: Option<String>,
+    pub favicon_url: Option<String>,
+    pub primary_color: String,
+    pub secondary_color: String,
+    pub support_email: Option<String>,
+    pub support_phone: Option<String>,
+    pub email_footer: Option<String>,
+}
+
+impl Default for ResolvedBranding {
+    fn default() -> Self {
+        TenantBranding::default().resolve(DEFAULT_BRAND_NAME)
+    }
+}
+
+impl ResolvedBranding {
+    /// Expose the branding to templates as `{{ branding.company_name }}`,
+    /// `{{ branding.email_footer }}` and so on
+    pub fn inject(&self, context: &mut serde_json::Value) {
+        if let serde_json::Value::Object(map) = context {
+            map.insert(
+                "branding".to_string(),
+                serde_json::to_value(self).unwrap_or(serde_json::Value::Null),
+            );
+        }
+    }
+
+    /// A plain-text email body with the tenant's footer, if any, after it
+    pub fn with_footer(&self, body: String) -> String {
+        match self.email_footer {
+            Some(ref footer) => format!("{}\n\n--\n{}", body.trim_end(), footer),
+            None => body,
+        }
+    }
 }
 
 /// Create tenant request
@@ -147,3 +239,389 @@ pub struct ModuleConfig {
     pub is_enabled: bool,
     pub config: serde_json::Value,
 }
+
+/// A module that can be switched on or off per tenant at runtime, on top of
+/// whatever was compiled in. Stored in `module_config` under `as_str()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Feature {
+    Ticketing,
+    TimeTracking,
+    Projects,
+    Contacts,
+    Calendar,
+    Contracts,
+    Billing,
+    Assets,
+    KnowledgeBase,
+    Notifications,
+    RmmIntegration,
+    ClientPortal,
+    Reports,
+}
+
+impl Feature {
+    pub const ALL: [Feature; 13] = [
+        Self::Ticketing,
+        Self::TimeTracking,
+        Self::Projects,
+        Self::Contacts,
+        Self::Calendar,
+        Self::Contracts,
+        Self::Billing,
+        Self::Assets,
+        Self::KnowledgeBase,
+        Self::Notifications,
+        Self::RmmIntegration,
+        Self::ClientPortal,
+        Self::Reports,
+    ];
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|feature| feature.as_str() == s)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ticketing => "ticketing",
+            Self::TimeTracking => "time_tracking",
+            Self::Projects => "projects",
+            Self::Contacts => "contacts",
+            Self::Calendar => "calendar",
+            Self::Contracts => "contracts",
+            Self::Billing => "billing",
+            Self::Assets => "assets",
+            Self::KnowledgeBase => "knowledge_base",
+            Self::Notifications => "notifications",
+            Self::RmmIntegration => "rmm_integration",
+            Self::ClientPortal => "client_portal",
+            Self::Reports => "reports",
+        }
+    }
+}
+
+/// The features a tenant has switched on
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TenantFeatures {
+    enabled: std::collections::HashSet<Feature>,
+}
+
+impl TenantFeatures {
+    /// From `(module_name, is_enabled)` rows. A feature without a row is off,
+    /// as with `get_module_config`; unknown module names are ignored.
+    pub fn from_config(rows: impl IntoIterator<Item = (String, bool)>) -> Self {
+        Self {
+            enabled: rows
+                .into_iter()
+                .filter(|(_, is_enabled)| *is_enabled)
+                .filter_map(|(name, _)| Feature::from_str(&name))
+                .collect(),
+        }
+    }
+
+    pub fn is_enabled(&self, feature: Feature) -> bool {
+        self.enabled.contains(&feature)
+    }
+
+    /// A disabled feature reads as missing, the same as an unknown route
+    pub fn require(&self, feature: Feature) -> Result<(), AppError> {
+        if self.is_enabled(feature) {
+            Ok(())
+        } else {
+            Err(AppError::not_found("Resource"))
+        }
+    }
+
+    /// Every feature with whether it is on, for the UI to hide what's off
+    pub fn states(&self) -> Vec<FeatureState> {
+        Feature::ALL
+            .into_iter()
+            .map(|feature| FeatureState {
+                feature,
+                is_enabled: self.is_enabled(feature),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FeatureState {
+    pub feature: Feature,
+    pub is_enabled: bool,
+}
+
+/// Switch a feature on or off for a tenant
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateFeatureRequest {
+    pub is_enabled: bool,
+}
+
+/// A table included in a tenant data export
+#[derive(Debug, Clone, Copy)]
+pub struct ExportTable {
+    pub name: &'static str,
+    /// Columns never exported, such as password hashes
+    pub excluded_columns: &'static [&'static str],
+    /// `(foreign key, parent table)` for tables without their own `tenant_id`
+    pub parent: Option<(&'static str, &'static str)>,
+}
+
+/// Tables in a tenant data export, in dependency order
+pub const EXPORT_TABLES: &[ExportTable] = &[
+    ExportTable { name: "companies", excluded_columns: &[], parent: None },
+    ExportTable { name: "sites", excluded_columns: &[], parent: None },
+    ExportTable { name: "contacts", excluded_columns: &["portal_password_hash"], parent: None },
+    ExportTable { name: "tickets", excluded_columns: &[], parent: None },
+    ExportTable { name: "ticket_notes", excluded_columns: &[], parent: None },
+    ExportTable { name: "invoices", excluded_columns: &[], parent: None },
+    ExportTable { name: "invoice_lines", excluded_columns: &[], parent: Some(("invoice_id", "invoices")) },
+    ExportTable { name: "projects", excluded_columns: &[], parent: None },
+    ExportTable { name: "assets", excluded_columns: &[], parent: None },
+    ExportTable { name: "kb_categories", excluded_columns: &[], parent: None },
+    ExportTable { name: "kb_articles", excluded_columns: &[], parent: None },
+];
+
+impl ExportTable {
+    /// Keyset-paginated query returning `(id, row as JSON)`. `$1` is the
+    /// tenant, `$2` the last id already exported and `$3` the batch size.
+    /// Every row carries `tenant_id`, joined from the parent where needed.
+    pub fn batch_query(&self) -> String {
+        let row = self
+            .excluded_columns
+            .iter()
+            .fold("to_jsonb(t)".to_string(), |row, column| format!("({} - '{}')", row, column));
+
+        match self.parent {
+            None => format!(
+                "SELECT t.id, {} FROM {} t WHERE t.tenant_id = $1 AND t.id > $2 ORDER BY t.id LIMIT $3",
+                row, self.name
+            ),
+            Some((foreign_key, parent)) => format!(
+                "SELECT t.id, {} || jsonb_build_object('tenant_id', p.tenant_id) FROM {} t \
+                 JOIN {} p ON p.id = t.{} WHERE p.tenant_id = $1 AND t.id > $2 ORDER BY t.id LIMIT $3",
+                row, self.name, parent, foreign_key
+            ),
+        }
+    }
+
+    pub fn file_name(&self) -> String {
+        format!("{}.ndjson", self.name)
+    }
+
+    /// Personal data columns masked in exports bound for non-production
+    pub fn masked_columns(&self) -> &'static [(&'static str, MaskKind)] {
+        match self.name {
+            "companies" => &[("phone", MaskKind::Phone), ("fax", MaskKind::Phone)],
+            "sites" => &[("phone", MaskKind::Phone)],
+            "contacts" => &[
+                ("first_name", MaskKind::Name),
+                ("last_name", MaskKind::Name),
+                ("email", MaskKind::Email),
+                ("phone", MaskKind::Phone),
+                ("mobile", MaskKind::Phone),
+                ("fax", MaskKind::Phone),
+            ],
+            "ticket_notes" => &[("email_recipients", MaskKind::Email)],
+            _ => &[],
+        }
+    }
+}
+
+/// Encode exported rows as NDJSON, one object per line. Fails on any row that
+/// does not belong to `tenant_id` so a bad query can never leak another
+/// tenant's data into the archive.
+pub fn encode_export_rows(tenant_id: Uuid, rows: &[serde_json::Value]) -> Result<Vec<u8>, AppError> {
+    let expected = tenant_id.to_string();
+    let mut out = Vec::new();
+
+    for row in rows {
+        if row.get("tenant_id").and_then(|v| v.as_str()) != Some(expected.as_str()) {
+            return Err(AppError::Tenant("Export row does not belong to the tenant".to_string()));
+        }
+        serde_json::to_writer(&mut out, row)?;
+        out.push(b'\n');
+    }
+
+    Ok(out)
+}
+
+/// One NDJSON file in an export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportFile {
+    pub table: String,
+    pub file_name: String,
+    pub rows: u64,
+}
+
+/// A finished tenant data export: a directory of NDJSON files plus `manifest.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportBundle {
+    pub tenant_id: Uuid,
+    pub directory: String,
+    pub files: Vec<ExportFile>,
+    /// Personal data was masked for loading into a non-production environment
+    #[serde(default)]
+    pub masked: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Tenant export query parameters
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct ExportParams {
+    /// Mask emails, phone numbers and names with `EXPORT_MASKING_KEY`
+    #[serde(default)]
+    pub mask_personal_data: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_branding_falls_back_to_platform_defaults() {
+        let branding = TenantBranding::default().resolve("Acme IT");
+        assert_eq!(branding.company_name, "Acme IT");
+        assert_eq!(branding.primary_color, DEFAULT_PRIMARY_COLOR);
+        assert_eq!(branding.email_footer, None);
+        assert_eq!(ResolvedBranding::default().company_name, DEFAULT_BRAND_NAME);
+
+        // A bad stored color can't reach the UI or a PDF
+        let stored = TenantBranding {
+            primary_color: Some("red; background: url(x)".to_string()),
+            secondary_color: Some("#0f0".to_string()),
+            company_name: Some("  ".to_string()),
+            ..TenantBranding::default()
+        };
+        assert!(stored.check().is_err());
+        let branding = stored.resolve("Acme IT");
+        assert_eq!(branding.primary_color, DEFAULT_PRIMARY_COLOR);
+        assert_eq!(branding.secondary_color, "#0f0");
+        assert_eq!(branding.company_name, "Acme IT");
+    }
+
+    #[test]
+    fn test_branding_flows_into_email_template_context() {
+        let branding = TenantBranding {
+            logo_url: Some("https://cdn.acme.test/logo.png".to_string()),
+            primary_color: Some("#ff6600".to_string()),
+            email_footer: Some("Acme IT, 1 Main St".to_string()),
+            ..TenantBranding::default()
+        }
+        .resolve("Acme IT");
+        assert!(TenantBranding { primary_color: Some("#ff6600".to_string()), ..TenantBranding::default() }
+            .check()
+            .is_ok());
+
+        let mut context = serde_json::json!({ "ticket": { "number": "T000042" } });
+        branding.inject(&mut context);
+        assert_eq!(context["ticket"]["number"], "T000042");
+        assert_eq!(context["branding"]["logo_url"], "https://cdn.acme.test/logo.png");
+        assert_eq!(context["branding"]["primary_color"], "#ff6600");
+        assert_eq!(context["branding"]["email_footer"], "Acme IT, 1 Main St");
+
+        let rendered = minijinja::Environment::new()
+            .render_str("Ticket {{ ticket.number }}\n{{ branding.email_footer }}", &context)
+            .unwrap();
+        assert_eq!(rendered, "Ticket T000042\nAcme IT, 1 Main St");
+
+        assert_eq!(branding.with_footer("Thanks\n".to_string()), "Thanks\n\n--\nAcme IT, 1 Main St");
+        assert_eq!(ResolvedBranding::default().with_footer("Thanks".to_string()), "Thanks");
+    }
+
+    fn rows_for(tenant_id: Uuid, count: usize) -> Vec<serde_json::Value> {
+        (0..count)
+            .map(|i| serde_json::json!({ "id": Uuid::new_v4(), "tenant_id": tenant_id, "title": format!("Row {}", i) }))
+            .collect()
+    }
+
+    #[test]
+    fn test_disabled_feature_reads_as_not_found() {
+        let with_billing = TenantFeatures::from_config([
+            ("ticketing".to_string(), true),
+            ("billing".to_string(), true),
+        ]);
+        let without_billing = TenantFeatures::from_config([
+            ("ticketing".to_string(), true),
+            ("billing".to_string(), false),
+            ("retired_module".to_string(), true),
+        ]);
+
+        assert!(with_billing.require(Feature::Billing).is_ok());
+        let denied = without_billing.require(Feature::Billing).unwrap_err();
+        assert!(matches!(denied, AppError::NotFound(_)));
+        assert_eq!(denied.status_code(), 404);
+
+        // Both tenants keep the modules they do have; no row means off
+        assert!(without_billing.require(Feature::Ticketing).is_ok());
+        assert!(!with_billing.is_enabled(Feature::Assets));
+
+        let states = without_billing.states();
+        assert_eq!(states.len(), Feature::ALL.len());
+        assert!(states.contains(&FeatureState { feature: Feature::Ticketing, is_enabled: true }));
+        assert!(states.contains(&FeatureState { feature: Feature::Billing, is_enabled: false }));
+    }
+
+    #[test]
+    fn test_feature_names_match_module_config() {
+        for feature in Feature::ALL {
+            assert_eq!(Feature::from_str(feature.as_str()), Some(feature));
+        }
+        assert_eq!(Feature::RmmIntegration.as_str(), "rmm_integration");
+        assert_eq!(Feature::from_str("rmm"), None);
+    }
+
+    #[test]
+    fn test_export_contains_only_the_tenants_rows() {
+        let (tenant, other_tenant) = (Uuid::new_v4(), Uuid::new_v4());
+        let rows = rows_for(tenant, 3);
+
+        let encoded = String::from_utf8(encode_export_rows(tenant, &rows).unwrap()).unwrap();
+        let lines: Vec<serde_json::Value> =
+            encoded.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines, rows);
+        assert!(!encoded.contains(&other_tenant.to_string()));
+
+        // A row from a second tenant fails the whole batch
+        let mut mixed = rows.clone();
+        mixed.extend(rows_for(other_tenant, 1));
+        assert!(matches!(encode_export_rows(tenant, &mixed), Err(AppError::Tenant(_))));
+
+        let unscoped = vec![serde_json::json!({ "id": Uuid::new_v4() })];
+        assert!(encode_export_rows(tenant, &unscoped).is_err());
+    }
+
+    #[test]
+    fn test_export_queries_are_tenant_scoped() {
+        for table in EXPORT_TABLES {
+            let query = table.batch_query();
+            assert!(query.contains("tenant_id = $1"), "{} is not tenant scoped", table.name);
+            assert!(query.contains("ORDER BY t.id LIMIT $3"));
+        }
+
+        let contacts = EXPORT_TABLES.iter().find(|t| t.name == "contacts").unwrap();
+        assert!(contacts.batch_query().contains("(to_jsonb(t) - 'portal_password_hash')"));
+
+        let lines = EXPORT_TABLES.iter().find(|t| t.name == "invoice_lines").unwrap();
+        assert!(lines.batch_query().contains("JOIN invoices p ON p.id = t.invoice_id WHERE p.tenant_id = $1"));
+    }
+
+    #[test]
+    fn test_masked_export_rows_keep_tenant_and_ids() {
+        let tenant = Uuid::new_v4();
+        let contacts = EXPORT_TABLES.iter().find(|t| t.name == "contacts").unwrap();
+        let mut row = serde_json::json!({
+            "id": Uuid::nil(),
+            "tenant_id": tenant,
+            "company_id": Uuid::nil(),
+            "email": "jane@acme.com",
+            "first_name": "Jane",
+        });
+
+        crate::utils::masking::DataMasker::new(b"key").mask_row(contacts.masked_columns(), &mut row);
+
+        assert_ne!(row["email"], "jane@acme.com");
+        assert_ne!(row["first_name"], "Jane");
+        assert_eq!(row["company_id"], serde_json::json!(Uuid::nil()));
+        assert!(encode_export_rows(tenant, &[row]).is_ok());
+    }
+}
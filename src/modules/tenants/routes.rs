@@ -1,7 +1,7 @@
 //! Tenant API routes (Super Admin only)
 
 use axum::{
-    extract::{Path, Query, State},
+    extract::{OriginalUri, Path, Query, State},
     routing::{get, post, put},
     Json, Router,
 };
@@ -10,11 +10,12 @@ use uuid::Uuid;
 use validator::Validate;
 
 use super::{
-    CreateTenantRequest, TenantResponse, TenantService, TenantUsage, UpdateTenantRequest,
+    CreateTenantRequest, ExportBundle, ExportParams, Feature, FeatureState, ResolvedBranding, TenantBranding,
+    TenantResponse, TenantService, TenantUsage, UpdateFeatureRequest, UpdateTenantRequest,
 };
 use crate::modules::auth::{RequireAuth, UserRole};
 use crate::utils::error::{AppError, AppResult};
-use crate::utils::pagination::{PaginatedResponse, PaginationParams};
+use crate::utils::pagination::{PaginatedJson, PaginatedResponse, PaginationParams};
 
 #[derive(Clone)]
 pub struct TenantRouterState {
@@ -32,9 +33,14 @@ pub fn tenant_routes(tenant_service: TenantService) -> Router {
         .route("/", post(create_tenant))
         .route("/:tenant_id", get(get_tenant))
         .route("/:tenant_id", put(update_tenant))
+        .route("/:tenant_id/branding", get(get_branding))
+        .route("/:tenant_id/branding", put(update_branding))
+        .route("/:tenant_id/features", get(get_features))
+        .route("/:tenant_id/features/:feature", put(update_feature))
         .route("/:tenant_id/suspend", post(suspend_tenant))
         .route("/:tenant_id/activate", post(activate_tenant))
         .route("/:tenant_id/usage", get(get_tenant_usage))
+        .route("/:tenant_id/export", post(export_tenant))
         .with_state(state)
 }
 
@@ -43,7 +49,8 @@ async fn list_tenants(
     State(state): State<TenantRouterState>,
     RequireAuth(user): RequireAuth,
     Query(pagination): Query<PaginationParams>,
-) -> AppResult<Json<PaginatedResponse<TenantResponse>>> {
+    OriginalUri(uri): OriginalUri,
+) -> AppResult<PaginatedJson<TenantResponse>> {
     // Only super admins can list all tenants
     if user.role != UserRole::SuperAdmin {
         return Err(AppError::Forbidden("Super admin access required".to_string()));
@@ -60,7 +67,7 @@ async fn list_tenants(
         total,
     );
 
-    Ok(Json(response))
+    Ok(response.with_links(&uri))
 }
 
 /// Create a new tenant (super admin only)
@@ -120,6 +127,76 @@ async fn update_tenant(
     Ok(Json(tenant.into()))
 }
 
+/// Get tenant branding, with platform defaults for anything unset
+async fn get_branding(
+    State(state): State<TenantRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(tenant_id): Path<Uuid>,
+) -> AppResult<Json<ResolvedBranding>> {
+    if user.role != UserRole::SuperAdmin && user.tenant_id != tenant_id {
+        return Err(AppError::Forbidden("Access denied".to_string()));
+    }
+
+    let branding = state.tenant_service.get_branding(tenant_id).await?;
+
+    Ok(Json(branding))
+}
+
+/// Update tenant branding (super admin, or an admin of the tenant)
+async fn update_branding(
+    State(state): State<TenantRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(tenant_id): Path<Uuid>,
+    Json(request): Json<TenantBranding>,
+) -> AppResult<Json<ResolvedBranding>> {
+    if user.role != UserRole::SuperAdmin
+        && !(user.tenant_id == tenant_id && user.role.is_admin())
+    {
+        return Err(AppError::Forbidden("Access denied".to_string()));
+    }
+
+    let branding = state
+        .tenant_service
+        .update_branding(tenant_id, &request)
+        .await?;
+
+    Ok(Json(branding))
+}
+
+/// Which features a tenant has, so the UI can hide the rest
+async fn get_features(
+    State(state): State<TenantRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(tenant_id): Path<Uuid>,
+) -> AppResult<Json<Vec<FeatureState>>> {
+    if user.role != UserRole::SuperAdmin && user.tenant_id != tenant_id {
+        return Err(AppError::Forbidden("Access denied".to_string()));
+    }
+
+    let features = state.tenant_service.get_features(tenant_id).await?;
+
+    Ok(Json(features.states()))
+}
+
+/// Switch a feature on or off for a tenant (super admin only)
+async fn update_feature(
+    State(state): State<TenantRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path((tenant_id, feature)): Path<(Uuid, Feature)>,
+    Json(request): Json<UpdateFeatureRequest>,
+) -> AppResult<Json<Vec<FeatureState>>> {
+    if user.role != UserRole::SuperAdmin {
+        return Err(AppError::Forbidden("Super admin access required".to_string()));
+    }
+
+    let features = state
+        .tenant_service
+        .set_feature(tenant_id, feature, request.is_enabled)
+        .await?;
+
+    Ok(Json(features.states()))
+}
+
 /// Suspend tenant (super admin only)
 async fn suspend_tenant(
     State(state): State<TenantRouterState>,
@@ -165,3 +242,20 @@ async fn get_tenant_usage(
 
     Ok(Json(usage))
 }
+
+/// Export all of a tenant's data (super admin, or an admin of the tenant)
+async fn export_tenant(
+    State(state): State<TenantRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(tenant_id): Path<Uuid>,
+    Query(params): Query<ExportParams>,
+) -> AppResult<Json<ExportBundle>> {
+    let own_tenant_admin = user.tenant_id == tenant_id && user.role == UserRole::Admin;
+    if user.role != UserRole::SuperAdmin && !own_tenant_admin {
+        return Err(AppError::Forbidden("Access denied".to_string()));
+    }
+
+    let bundle = state.tenant_service.export_all(tenant_id, params.mask_personal_data).await?;
+
+    Ok(Json(bundle))
+}
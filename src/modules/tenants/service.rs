@@ -4,11 +4,17 @@ use chrono::{Duration, Utc};
 use uuid::Uuid;
 
 use crate::db::Database;
+use crate::modules::audit::{AuditAction, AuditService, NewAuditEntry};
 use crate::utils::error::{AppError, AppResult};
+use crate::utils::masking::DataMasker;
+use crate::utils::request_id;
 use crate::utils::validation::slugify;
 
 use super::models::*;
 
+/// Rows read per query while exporting a table
+const EXPORT_BATCH_SIZE: i64 = 500;
+
 /// Tenant management service
 #[derive(Clone)]
 pub struct TenantService {
@@ -57,21 +63,6 @@ impl TenantService {
         .execute(self.db.pool())
         .await?;
 
-        // Initialize sequences
-        sqlx::query(
-            "INSERT INTO ticket_sequences (tenant_id, last_number) VALUES ($1, 0)"
-        )
-        .bind(tenant_id)
-        .execute(self.db.pool())
-        .await?;
-
-        sqlx::query(
-            "INSERT INTO invoice_sequences (tenant_id, last_number, prefix) VALUES ($1, 0, 'INV-')"
-        )
-        .bind(tenant_id)
-        .execute(self.db.pool())
-        .await?;
-
         // Create admin user
         let admin_id = Uuid::new_v4();
         sqlx::query(
@@ -138,12 +129,14 @@ impl TenantService {
         page: u32,
         per_page: u32,
     ) -> AppResult<(Vec<Tenant>, u64)> {
-        let offset = (page.saturating_sub(1)) * per_page;
-
         let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tenants")
             .fetch_one(self.db.pool())
             .await?;
 
+        // Past-the-end pages return the last page
+        let last_page = (total as u64).div_ceil(per_page.max(1) as u64).max(1) as u32;
+        let offset = (page.clamp(1, last_page) - 1) * per_page;
+
         let rows = sqlx::query_as::<_, TenantRow>(
             r#"
             SELECT id, name, slug, status, settings, branding, billing_email,
@@ -168,6 +161,10 @@ impl TenantService {
         tenant_id: Uuid,
         request: &UpdateTenantRequest,
     ) -> AppResult<Tenant> {
+        if let Some(ref branding) = request.branding {
+            branding.check()?;
+        }
+
         let mut query = String::from("UPDATE tenants SET updated_at = NOW()");
         let mut param_idx = 2;
 
@@ -217,6 +214,42 @@ impl TenantService {
         self.get_tenant(tenant_id).await
     }
 
+    /// The tenant's branding with platform defaults filled in. Falls back to
+    /// the defaults entirely when the tenant can't be found, so an email or
+    /// invoice is never held up by branding.
+    pub async fn get_branding(&self, tenant_id: Uuid) -> AppResult<ResolvedBranding> {
+        let row = sqlx::query_as::<_, (String, serde_json::Value)>(
+            "SELECT name, branding FROM tenants WHERE id = $1",
+        )
+        .bind(tenant_id)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        Ok(match row {
+            Some((name, branding)) => serde_json::from_value::<TenantBranding>(branding)
+                .unwrap_or_default()
+                .resolve(&name),
+            None => ResolvedBranding::default(),
+        })
+    }
+
+    /// Replace the tenant's branding
+    pub async fn update_branding(&self, tenant_id: Uuid, branding: &TenantBranding) -> AppResult<ResolvedBranding> {
+        branding.check()?;
+
+        let result = sqlx::query("UPDATE tenants SET branding = $1, updated_at = NOW() WHERE id = $2")
+            .bind(serde_json::to_value(branding)?)
+            .bind(tenant_id)
+            .execute(self.db.pool())
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Tenant".to_string()));
+        }
+
+        self.get_branding(tenant_id).await
+    }
+
     /// Suspend tenant
     pub async fn suspend_tenant(&self, tenant_id: Uuid) -> AppResult<()> {
         sqlx::query("UPDATE tenants SET status = 'suspended', updated_at = NOW() WHERE id = $1")
@@ -237,6 +270,98 @@ impl TenantService {
         Ok(())
     }
 
+    /// Export all of a tenant's data as one NDJSON file per table.
+    ///
+    /// Rows are read in keyset-paginated batches and appended to disk as they
+    /// arrive, so memory use stays flat however large the tenant is. The
+    /// archive is written under `EXPORT_DIR` (default: the system temp dir).
+    ///
+    /// With `mask_personal_data`, emails, phone numbers and names are masked
+    /// with `EXPORT_MASKING_KEY` before they are written, for loading into a
+    /// non-production environment.
+    pub async fn export_all(&self, tenant_id: Uuid, mask_personal_data: bool) -> AppResult<ExportBundle> {
+        use tokio::io::AsyncWriteExt;
+
+        self.get_tenant(tenant_id).await?;
+
+        let masker = if mask_personal_data {
+            let key = std::env::var("EXPORT_MASKING_KEY")
+                .ok()
+                .filter(|key| !key.is_empty())
+                .ok_or_else(|| AppError::Configuration("EXPORT_MASKING_KEY must be set to mask exports".to_string()))?;
+            Some(DataMasker::new(key.as_bytes()))
+        } else {
+            None
+        };
+
+        let created_at = Utc::now();
+        let directory = std::env::var("EXPORT_DIR")
+            .map(std::path::PathBuf::from)
+            .unwrap_or_else(|_| std::env::temp_dir().join("psa-exports"))
+            .join(format!("{}-{}", tenant_id, created_at.format("%Y%m%dT%H%M%SZ")));
+        tokio::fs::create_dir_all(&directory).await?;
+
+        let mut files = Vec::with_capacity(EXPORT_TABLES.len());
+        for table in EXPORT_TABLES {
+            let query = table.batch_query();
+            let file_name = table.file_name();
+            let mut file = tokio::io::BufWriter::new(tokio::fs::File::create(directory.join(&file_name)).await?);
+            let mut last_id = Uuid::nil();
+            let mut rows: u64 = 0;
+
+            loop {
+                let batch = sqlx::query_as::<_, (Uuid, serde_json::Value)>(&query)
+                    .bind(tenant_id)
+                    .bind(last_id)
+                    .bind(EXPORT_BATCH_SIZE)
+                    .fetch_all(self.db.pool())
+                    .await?;
+
+                let (ids, mut values): (Vec<Uuid>, Vec<serde_json::Value>) = batch.into_iter().unzip();
+                let Some(id) = ids.last() else {
+                    break;
+                };
+                last_id = *id;
+
+                if let Some(masker) = &masker {
+                    for value in values.iter_mut() {
+                        masker.mask_row(table.masked_columns(), value);
+                    }
+                }
+
+                file.write_all(&encode_export_rows(tenant_id, &values)?).await?;
+                rows += values.len() as u64;
+
+                if (values.len() as i64) < EXPORT_BATCH_SIZE {
+                    break;
+                }
+            }
+
+            file.flush().await?;
+            files.push(ExportFile {
+                table: table.name.to_string(),
+                file_name,
+                rows,
+            });
+        }
+
+        let bundle = ExportBundle {
+            tenant_id,
+            directory: directory.to_string_lossy().into_owned(),
+            files,
+            masked: masker.is_some(),
+            created_at,
+        };
+        tokio::fs::write(directory.join("manifest.json"), serde_json::to_vec_pretty(&bundle)?).await?;
+
+        let entry = NewAuditEntry::new(AuditAction::Export, "tenant", Some(tenant_id))
+            .new_values(serde_json::json!({ "files": bundle.files.len(), "masked": bundle.masked }))
+            .request_id(request_id::current());
+        AuditService::new(self.db.clone()).record(tenant_id, entry).await?;
+
+        Ok(bundle)
+    }
+
     /// Get tenant usage statistics
     pub async fn get_tenant_usage(&self, tenant_id: Uuid) -> AppResult<TenantUsage> {
         let user_count: i64 = sqlx::query_scalar(
@@ -350,6 +475,37 @@ impl TenantService {
         Ok(())
     }
 
+    /// Which features the tenant has switched on
+    pub async fn get_features(&self, tenant_id: Uuid) -> AppResult<TenantFeatures> {
+        let rows = sqlx::query_as::<_, (String, bool)>(
+            "SELECT module_name, COALESCE(is_enabled, FALSE) FROM module_config WHERE tenant_id = $1",
+        )
+        .bind(tenant_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(TenantFeatures::from_config(rows))
+    }
+
+    /// Switch a feature on or off, keeping any module config already stored
+    pub async fn set_feature(&self, tenant_id: Uuid, feature: Feature, is_enabled: bool) -> AppResult<TenantFeatures> {
+        sqlx::query(
+            r#"
+            INSERT INTO module_config (tenant_id, module_name, is_enabled)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (tenant_id, module_name)
+            DO UPDATE SET is_enabled = $3, updated_at = NOW()
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(feature.as_str())
+        .bind(is_enabled)
+        .execute(self.db.pool())
+        .await?;
+
+        self.get_features(tenant_id).await
+    }
+
     /// Copy default configuration from default tenant
     async fn copy_default_config(&self, new_tenant_id: Uuid) -> AppResult<()> {
         let default_tenant = Uuid::parse_str("00000000-0000-0000-0000-000000000001").unwrap();
@@ -357,8 +513,8 @@ impl TenantService {
         // Copy ticket statuses
         sqlx::query(
             r#"
-            INSERT INTO ticket_statuses (tenant_id, name, color, is_closed, is_default, sort_order)
-            SELECT $1, name, color, is_closed, is_default, sort_order
+            INSERT INTO ticket_statuses (tenant_id, name, color, is_closed, is_default, sort_order, requires_resolution_code, is_final)
+            SELECT $1, name, color, is_closed, is_default, sort_order, requires_resolution_code, is_final
             FROM ticket_statuses WHERE tenant_id = $2
             "#
         )
@@ -383,8 +539,8 @@ impl TenantService {
         // Copy ticket types
         sqlx::query(
             r#"
-            INSERT INTO ticket_types (tenant_id, name, description, icon, sort_order)
-            SELECT $1, name, description, icon, sort_order
+            INSERT INTO ticket_types (tenant_id, name, description, icon, sort_order, required_fields)
+            SELECT $1, name, description, icon, sort_order, required_fields
             FROM ticket_types WHERE tenant_id = $2
             "#
         )
@@ -393,6 +549,19 @@ impl TenantService {
         .execute(self.db.pool())
         .await?;
 
+        // Copy resolution codes
+        sqlx::query(
+            r#"
+            INSERT INTO ticket_resolution_codes (tenant_id, code, name, description, is_active, sort_order)
+            SELECT $1, code, name, description, is_active, sort_order
+            FROM ticket_resolution_codes WHERE tenant_id = $2
+            "#
+        )
+        .bind(new_tenant_id)
+        .bind(default_tenant)
+        .execute(self.db.pool())
+        .await?;
+
         // Copy work types
         sqlx::query(
             r#"
@@ -0,0 +1,371 @@
+//! Ticket attachments
+//!
+//! Uploads are handed to an [`AttachmentScanner`] before they are stored.
+//! Files the scanner flags, or fails on, are still stored so they can be
+//! reviewed, but are quarantined and can't be downloaded. Without a scanner
+//! configured attachments are stored as `not_scanned`.
+
+use std::future::Future;
+use std::path::PathBuf;
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::utils::error::{AppError, AppResult};
+
+use super::models::*;
+
+/// Largest attachment accepted
+pub const MAX_ATTACHMENT_BYTES: usize = 25 * 1024 * 1024;
+
+/// A virus scanner invoked on every upload
+pub trait AttachmentScanner: Send + Sync {
+    fn scan(&self, file_name: &str, content: &[u8]) -> impl Future<Output = AppResult<ScanVerdict>> + Send;
+}
+
+/// The default scanner: accepts everything without looking
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopScanner;
+
+impl AttachmentScanner for NoopScanner {
+    async fn scan(&self, _file_name: &str, _content: &[u8]) -> AppResult<ScanVerdict> {
+        Ok(ScanVerdict::NotScanned)
+    }
+}
+
+/// Run the scanner and turn its outcome into the status to store
+pub async fn scan_attachment<S: AttachmentScanner>(
+    scanner: &S,
+    file_name: &str,
+    content: &[u8],
+) -> (AttachmentScanStatus, Option<String>) {
+    let result = scanner.scan(file_name, content).await;
+    if let Err(e) = &result {
+        tracing::warn!("Virus scan of {} failed: {}", file_name, e);
+    }
+    AttachmentScanStatus::from_scan(&result)
+}
+
+/// Attachment upload and download
+#[derive(Clone)]
+pub struct AttachmentService<S = NoopScanner> {
+    db: Database,
+    scanner: S,
+    storage_dir: PathBuf,
+}
+
+impl AttachmentService {
+    pub fn new(db: Database) -> Self {
+        Self::with_scanner(db, NoopScanner)
+    }
+}
+
+impl<S: AttachmentScanner> AttachmentService<S> {
+    pub fn with_scanner(db: Database, scanner: S) -> Self {
+        Self {
+            db,
+            scanner,
+            storage_dir: std::env::var("ATTACHMENT_STORAGE_DIR")
+                .unwrap_or_else(|_| "./data/attachments".to_string())
+                .into(),
+        }
+    }
+
+    /// Scan and store an upload. Quarantined files are stored too; the
+    /// returned attachment's `scan_status` says which it was.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn add_attachment(
+        &self,
+        tenant_id: Uuid,
+        ticket_id: Uuid,
+        note_id: Option<Uuid>,
+        uploaded_by_id: Uuid,
+        file_name: &str,
+        mime_type: &str,
+        content: &[u8],
+    ) -> AppResult<TicketAttachment> {
+        let file_name = file_name.trim();
+        if file_name.is_empty() {
+            return Err(AppError::validation_field("file", "File name is required"));
+        }
+        if content.len() > MAX_ATTACHMENT_BYTES {
+            return Err(AppError::validation_field(
+                "file",
+                format!("Attachments can be at most {} MB", MAX_ATTACHMENT_BYTES / (1024 * 1024)),
+            ));
+        }
+
+        let ticket_exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM tickets WHERE tenant_id = $1 AND id = $2)",
+        )
+        .bind(tenant_id)
+        .bind(ticket_id)
+        .fetch_one(self.db.pool())
+        .await?;
+        if !ticket_exists {
+            return Err(AppError::NotFound("Ticket".to_string()));
+        }
+
+        let (scan_status, scan_detail) = scan_attachment(&self.scanner, file_name, content).await;
+        let scanned_at = (scan_status != AttachmentScanStatus::NotScanned).then(Utc::now);
+
+        let id = Uuid::new_v4();
+        let storage_path = format!("{}/{}", tenant_id, id);
+        let path = self.storage_dir.join(&storage_path);
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir)
+                .await
+                .map_err(|e| AppError::internal(format!("Failed to create attachment directory: {}", e)))?;
+        }
+        tokio::fs::write(&path, content)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to store attachment: {}", e)))?;
+
+        let row = sqlx::query_as::<_, TicketAttachmentRow>(
+            r#"
+            INSERT INTO ticket_attachments (
+                id, tenant_id, ticket_id, note_id, file_name, file_size, mime_type,
+                storage_path, uploaded_by_id, scan_status, scan_detail, scanned_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            RETURNING id, tenant_id, ticket_id, note_id, file_name, file_size::BIGINT AS file_size,
+                      mime_type, storage_path, uploaded_by_id, scan_status, scan_detail, scanned_at,
+                      created_at
+            "#,
+        )
+        .bind(id)
+        .bind(tenant_id)
+        .bind(ticket_id)
+        .bind(note_id)
+        .bind(file_name)
+        .bind(content.len() as i32)
+        .bind(mime_type)
+        .bind(&storage_path)
+        .bind(uploaded_by_id)
+        .bind(scan_status.as_str())
+        .bind(&scan_detail)
+        .bind(scanned_at)
+        .fetch_one(self.db.pool())
+        .await;
+
+        let row = match row {
+            Ok(row) => row,
+            Err(e) => {
+                let _ = tokio::fs::remove_file(&path).await;
+                return Err(e.into());
+            }
+        };
+
+        if scan_status.is_quarantined() {
+            tracing::warn!(
+                "Quarantined attachment {} ({}) on ticket {}: {}",
+                id,
+                file_name,
+                ticket_id,
+                scan_detail.as_deref().unwrap_or("no detail"),
+            );
+        }
+
+        Ok(row.into())
+    }
+
+    /// Remove stored files whose attachment rows were deleted. Files already
+    /// gone are ignored; other failures are logged, not returned.
+    pub async fn remove_stored_files(&self, storage_paths: &[String]) {
+        for storage_path in storage_paths {
+            match tokio::fs::remove_file(self.storage_dir.join(storage_path)).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => tracing::warn!("Failed to remove attachment file {}: {}", storage_path, e),
+            }
+        }
+    }
+
+    pub async fn list_attachments(
+        &self,
+        tenant_id: Uuid,
+        ticket_id: Uuid,
+    ) -> AppResult<Vec<TicketAttachmentResponse>> {
+        let rows = sqlx::query_as::<_, TicketAttachmentResponseRow>(
+            r#"
+            SELECT a.id, a.file_name, a.file_size::BIGINT AS file_size, a.mime_type,
+                   COALESCE(u.first_name || ' ' || u.last_name, '') AS uploaded_by_name,
+                   a.scan_status, a.created_at
+            FROM ticket_attachments a
+            LEFT JOIN users u ON u.id = a.uploaded_by_id
+            WHERE a.tenant_id = $1 AND a.ticket_id = $2
+            ORDER BY a.created_at
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(ticket_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// The attachment and its content. Quarantined files are refused.
+    pub async fn download_attachment(
+        &self,
+        tenant_id: Uuid,
+        ticket_id: Uuid,
+        attachment_id: Uuid,
+    ) -> AppResult<(TicketAttachment, Vec<u8>)> {
+        let attachment: TicketAttachment = sqlx::query_as::<_, TicketAttachmentRow>(
+            r#"
+            SELECT id, tenant_id, ticket_id, note_id, file_name, file_size::BIGINT AS file_size,
+                   mime_type, storage_path, uploaded_by_id, scan_status, scan_detail, scanned_at,
+                   created_at
+            FROM ticket_attachments
+            WHERE tenant_id = $1 AND ticket_id = $2 AND id = $3
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(ticket_id)
+        .bind(attachment_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Attachment".to_string()))?
+        .into();
+
+        attachment.ensure_downloadable()?;
+
+        let content = tokio::fs::read(self.storage_dir.join(&attachment.storage_path))
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to read attachment: {}", e)))?;
+
+        Ok((attachment, content))
+    }
+}
+
+// ============================================================================
+// DATABASE ROW TYPES
+// ============================================================================
+
+#[derive(sqlx::FromRow)]
+struct TicketAttachmentRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    ticket_id: Uuid,
+    note_id: Option<Uuid>,
+    file_name: String,
+    file_size: i64,
+    mime_type: String,
+    storage_path: String,
+    uploaded_by_id: Uuid,
+    scan_status: String,
+    scan_detail: Option<String>,
+    scanned_at: Option<chrono::DateTime<Utc>>,
+    created_at: chrono::DateTime<Utc>,
+}
+
+impl From<TicketAttachmentRow> for TicketAttachment {
+    fn from(row: TicketAttachmentRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            ticket_id: row.ticket_id,
+            note_id: row.note_id,
+            file_name: row.file_name,
+            file_size: row.file_size,
+            mime_type: row.mime_type,
+            storage_path: row.storage_path,
+            uploaded_by_id: row.uploaded_by_id,
+            // An unknown status is quarantined rather than served
+            scan_status: AttachmentScanStatus::from_str(&row.scan_status).unwrap_or(AttachmentScanStatus::Failed),
+            scan_detail: row.scan_detail,
+            scanned_at: row.scanned_at,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct TicketAttachmentResponseRow {
+    id: Uuid,
+    file_name: String,
+    file_size: i64,
+    mime_type: String,
+    uploaded_by_name: String,
+    scan_status: String,
+    created_at: chrono::DateTime<Utc>,
+}
+
+impl From<TicketAttachmentResponseRow> for TicketAttachmentResponse {
+    fn from(row: TicketAttachmentResponseRow) -> Self {
+        Self {
+            id: row.id,
+            file_name: row.file_name,
+            file_size: row.file_size,
+            mime_type: row.mime_type,
+            uploaded_by_name: row.uploaded_by_name,
+            scan_status: AttachmentScanStatus::from_str(&row.scan_status).unwrap_or(AttachmentScanStatus::Failed),
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EICAR: &[u8] = br"X5O!P%@AP[4\PZX54(P^)7CC)7}$EICAR-STANDARD-ANTIVIRUS-TEST-FILE!$H+H*";
+
+    /// Flags the EICAR test string, and errors on empty files
+    struct EicarScanner;
+
+    impl AttachmentScanner for EicarScanner {
+        async fn scan(&self, _file_name: &str, content: &[u8]) -> AppResult<ScanVerdict> {
+            if content.is_empty() {
+                return Err(AppError::internal("scanner rejected empty stream"));
+            }
+            if content.windows(EICAR.len()).any(|window| window == EICAR) {
+                return Ok(ScanVerdict::Infected("EICAR-Test-File".to_string()));
+            }
+            Ok(ScanVerdict::Clean)
+        }
+    }
+
+    fn attachment(scan_status: AttachmentScanStatus) -> TicketAttachment {
+        TicketAttachment {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            ticket_id: Uuid::new_v4(),
+            note_id: None,
+            file_name: "invoice.pdf".to_string(),
+            file_size: 68,
+            mime_type: "application/pdf".to_string(),
+            storage_path: "tenant/attachment".to_string(),
+            uploaded_by_id: Uuid::new_v4(),
+            scan_status,
+            scan_detail: None,
+            scanned_at: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_infected_attachment_is_quarantined() {
+        let (status, detail) = scan_attachment(&EicarScanner, "invoice.pdf", EICAR).await;
+        assert_eq!(status, AttachmentScanStatus::Infected);
+        assert_eq!(detail.as_deref(), Some("EICAR-Test-File"));
+        assert!(matches!(attachment(status).ensure_downloadable(), Err(AppError::Forbidden(_))));
+
+        let (status, detail) = scan_attachment(&EicarScanner, "notes.txt", b"printer is jammed").await;
+        assert_eq!((status, detail), (AttachmentScanStatus::Clean, None));
+        assert!(attachment(status).ensure_downloadable().is_ok());
+
+        // A scanner error quarantines the file rather than letting it through
+        let (status, detail) = scan_attachment(&EicarScanner, "empty.txt", b"").await;
+        assert_eq!(status, AttachmentScanStatus::Failed);
+        assert!(detail.unwrap().contains("empty stream"));
+        assert!(attachment(status).ensure_downloadable().is_err());
+
+        // The default scanner stores everything as unscanned and downloadable
+        let (status, _) = scan_attachment(&NoopScanner, "invoice.pdf", EICAR).await;
+        assert_eq!(status, AttachmentScanStatus::NotScanned);
+        assert!(attachment(status).ensure_downloadable().is_ok());
+    }
+}
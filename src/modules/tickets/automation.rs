@@ -9,9 +9,10 @@
 use uuid::Uuid;
 
 use crate::db::Database;
-use crate::utils::error::AppResult;
+use crate::utils::error::{AppError, AppResult};
 
 use super::models::*;
+use super::service::TicketService;
 
 /// Automation engine for processing ticket automation rules
 #[derive(Clone)]
@@ -67,6 +68,51 @@ impl AutomationEngine {
         Ok(rows.into_iter().map(Into::into).collect())
     }
 
+    /// Get one automation rule
+    pub async fn get_rule(&self, tenant_id: Uuid, rule_id: Uuid) -> AppResult<AutomationRule> {
+        let row = sqlx::query_as::<_, AutomationRuleRow>(
+            r#"
+            SELECT id, tenant_id, name, description, is_active, trigger_type,
+                   conditions, actions, priority, last_run_at, run_count,
+                   created_at, updated_at
+            FROM ticket_automation_rules
+            WHERE tenant_id = $1 AND id = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(rule_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::not_found("Automation rule"))?;
+
+        Ok(row.into())
+    }
+
+    /// Store a new automation rule as given
+    pub async fn insert_rule(&self, rule: &AutomationRule) -> AppResult<AutomationRule> {
+        sqlx::query(
+            r#"
+            INSERT INTO ticket_automation_rules (id, tenant_id, name, description, is_active, trigger_type,
+                                                 conditions, actions, priority, run_count)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+        )
+        .bind(rule.id)
+        .bind(rule.tenant_id)
+        .bind(&rule.name)
+        .bind(&rule.description)
+        .bind(rule.is_active)
+        .bind(rule.trigger_type.as_str())
+        .bind(&rule.conditions)
+        .bind(&rule.actions)
+        .bind(rule.priority)
+        .bind(rule.run_count)
+        .execute(self.db.pool())
+        .await?;
+
+        self.get_rule(rule.tenant_id, rule.id).await
+    }
+
     /// Evaluate if rule conditions match the ticket
     async fn evaluate_conditions(
         &self,
@@ -164,6 +210,9 @@ impl AutomationEngine {
                             .bind(ticket_id)
                             .execute(self.db.pool())
                             .await?;
+                            TicketService::new(self.db.clone())
+                                .record_status_change(tenant_id, ticket_id, id, None)
+                                .await?;
                         }
                     }
                 }
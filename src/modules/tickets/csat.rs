@@ -0,0 +1,194 @@
+//! Customer satisfaction surveys
+//!
+//! A single-use survey link is emailed to the ticket contact when a ticket
+//! closes. Responses are recorded through a public, tokenized endpoint.
+
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::modules::notifications::{NotificationChannel, NotificationService};
+use crate::utils::crypto::generate_token;
+use crate::utils::error::{AppError, AppResult};
+
+use super::models::*;
+
+/// How long a survey link stays valid
+const SURVEY_LIFETIME_DAYS: i64 = 14;
+
+/// Survey issue and response service
+#[derive(Clone)]
+pub struct CsatService {
+    db: Database,
+    notifications: NotificationService,
+    base_url: String,
+}
+
+impl CsatService {
+    pub fn new(db: Database) -> Self {
+        Self {
+            notifications: NotificationService::new(db.clone()),
+            base_url: std::env::var("BASE_URL")
+                .unwrap_or_else(|_| "http://localhost:8080".to_string()),
+            db,
+        }
+    }
+
+    /// Issue a survey for a closed ticket and email it to the contact.
+    ///
+    /// Returns `None` when the ticket has no contact with an email address.
+    pub async fn send_survey(&self, ticket: &Ticket) -> AppResult<Option<CsatSurvey>> {
+        let Some(contact_id) = ticket.contact_id else {
+            return Ok(None);
+        };
+
+        let contact = sqlx::query_as::<_, (Option<String>, String)>(
+            "SELECT email, locale FROM contacts WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(ticket.tenant_id)
+        .bind(contact_id)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        let Some((Some(email), locale)) = contact else {
+            return Ok(None);
+        };
+
+        let token = generate_token(48);
+        let expires_at = Utc::now() + Duration::days(SURVEY_LIFETIME_DAYS);
+
+        let survey_id = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            INSERT INTO csat_surveys (tenant_id, ticket_id, contact_id, technician_id, token, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id
+            "#,
+        )
+        .bind(ticket.tenant_id)
+        .bind(ticket.id)
+        .bind(contact_id)
+        .bind(ticket.assigned_to_id)
+        .bind(&token)
+        .bind(expires_at)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        let url = format!("{}/api/v1/csat/{}", self.base_url.trim_end_matches('/'), token);
+        let context = serde_json::json!({
+            "ticket": {
+                "number": ticket.ticket_number,
+                "title": ticket.title,
+            },
+            "survey": {
+                "url": url,
+                "expires_at": expires_at.format("%Y-%m-%d").to_string(),
+            },
+        });
+
+        let (template, rendered) = self
+            .notifications
+            .render_template(ticket.tenant_id, "ticket.csat_survey", NotificationChannel::Email, &locale, &context)
+            .await?;
+        let outgoing = self.notifications.templated_email(ticket.tenant_id, email, &template, rendered).await?;
+
+        self.notifications
+            .send_email(ticket.tenant_id, None, &outgoing)
+            .await?;
+
+        sqlx::query("UPDATE csat_surveys SET sent_at = NOW() WHERE id = $1")
+            .bind(survey_id)
+            .execute(self.db.pool())
+            .await?;
+
+        self.get_survey_by_token(Some(ticket.tenant_id), &token).await.map(Some)
+    }
+
+    /// Look up a survey by its public token. With `tenant_id`, surveys sent
+    /// by other tenants are not found.
+    pub async fn get_survey_by_token(&self, tenant_id: Option<Uuid>, token: &str) -> AppResult<CsatSurvey> {
+        let row = sqlx::query_as::<_, CsatSurveyRow>(
+            r#"
+            SELECT id, tenant_id, ticket_id, contact_id, technician_id, token, expires_at,
+                   rating, comment, responded_at, sent_at, created_at
+            FROM csat_surveys
+            WHERE token = $1 AND ($2::UUID IS NULL OR tenant_id = $2)
+            "#,
+        )
+        .bind(token)
+        .bind(tenant_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Survey".to_string()))?;
+
+        Ok(row.into())
+    }
+
+    /// Record a survey response. Each token accepts a single response.
+    pub async fn record_response(
+        &self,
+        tenant_id: Option<Uuid>,
+        token: &str,
+        request: &CsatResponseRequest,
+    ) -> AppResult<CsatSurvey> {
+        let survey = self.get_survey_by_token(tenant_id, token).await?;
+        survey.ensure_open(Utc::now())?;
+
+        // Guard again in SQL so two concurrent submissions cannot both land
+        let row = sqlx::query_as::<_, CsatSurveyRow>(
+            r#"
+            UPDATE csat_surveys
+            SET rating = $1, comment = $2, responded_at = NOW()
+            WHERE id = $3 AND responded_at IS NULL AND expires_at > NOW()
+            RETURNING id, tenant_id, ticket_id, contact_id, technician_id, token, expires_at,
+                      rating, comment, responded_at, sent_at, created_at
+            "#,
+        )
+        .bind(request.rating.as_str())
+        .bind(&request.comment)
+        .bind(survey.id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::Conflict("Survey response".to_string()))?;
+
+        Ok(row.into())
+    }
+}
+
+// ============================================================================
+// DATABASE ROW TYPES
+// ============================================================================
+
+#[derive(sqlx::FromRow)]
+struct CsatSurveyRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    ticket_id: Uuid,
+    contact_id: Option<Uuid>,
+    technician_id: Option<Uuid>,
+    token: String,
+    expires_at: chrono::DateTime<Utc>,
+    rating: Option<String>,
+    comment: Option<String>,
+    responded_at: Option<chrono::DateTime<Utc>>,
+    sent_at: Option<chrono::DateTime<Utc>>,
+    created_at: chrono::DateTime<Utc>,
+}
+
+impl From<CsatSurveyRow> for CsatSurvey {
+    fn from(row: CsatSurveyRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            ticket_id: row.ticket_id,
+            contact_id: row.contact_id,
+            technician_id: row.technician_id,
+            token: row.token,
+            expires_at: row.expires_at,
+            rating: row.rating.as_deref().and_then(CsatRating::from_str),
+            comment: row.comment,
+            responded_at: row.responded_at,
+            sent_at: row.sent_at,
+            created_at: row.created_at,
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! Inbound email processing
+//!
+//! Turns a parsed inbound email into either a customer note on the ticket it
+//! replies to, or a new ticket in the queue its recipient address maps to.
+
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::utils::error::AppResult;
+
+use super::models::*;
+use super::service::TicketService;
+
+/// Longest ticket title taken from an email subject
+const MAX_TITLE_CHARS: usize = 500;
+
+/// Inbound email processor
+#[derive(Clone)]
+pub struct InboundEmailProcessor {
+    db: Database,
+    tickets: TicketService,
+}
+
+impl InboundEmailProcessor {
+    pub fn new(db: Database) -> Self {
+        Self {
+            tickets: TicketService::new(db.clone()),
+            db,
+        }
+    }
+
+    /// Process one inbound email. `user_id` is the account the mailbox poller
+    /// runs as and is recorded as the creator of tickets and notes.
+    pub async fn process(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        email: &InboundEmail,
+    ) -> AppResult<InboundEmailOutcome> {
+        let headers = email.thread_headers();
+
+        // Replies thread onto their ticket whoever sends them, so a Cc'd
+        // colleague's answer is not lost
+        if let Some(ticket_id) = self.tickets.resolve_reply_ticket(tenant_id, &headers).await? {
+            let note = self
+                .tickets
+                .add_customer_note(tenant_id, ticket_id, user_id, &email.body_text)
+                .await?;
+            if let Some(ref message_id) = headers.message_id {
+                self.tickets
+                    .record_note_message_id(tenant_id, note.id, message_id)
+                    .await?;
+            }
+
+            return Ok(InboundEmailOutcome::Replied { ticket_id, note_id: note.id });
+        }
+
+        let Some(sender) = normalize_email_address(&email.from) else {
+            return Ok(InboundEmailOutcome::Ignored {
+                reason: "Sender address is invalid".to_string(),
+            });
+        };
+
+        let contact: Option<(Uuid, Uuid)> = sqlx::query_as(
+            r#"
+            SELECT id, company_id FROM contacts
+            WHERE tenant_id = $1 AND LOWER(email) = $2 AND status = 'active'
+            ORDER BY created_at
+            LIMIT 1
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(&sender)
+        .fetch_optional(self.db.pool())
+        .await?;
+        let Some((contact_id, company_id)) = contact else {
+            return Ok(InboundEmailOutcome::Ignored {
+                reason: format!("No contact found for {}", sender),
+            });
+        };
+
+        let addresses = self.tickets.list_queue_addresses(tenant_id).await?;
+        let default_queue_id = self.tickets.default_queue_id(tenant_id).await?;
+        let queue_id = email.queue_for(&addresses, default_queue_id);
+
+        let title: String = match email.subject.trim() {
+            "" => "(no subject)".to_string(),
+            subject => subject.chars().take(MAX_TITLE_CHARS).collect(),
+        };
+
+        let request = CreateTicketRequest {
+            title,
+            description: Some(email.body_text.clone()),
+            priority_id: None,
+            type_id: None,
+            category_id: None,
+            queue_id: Some(queue_id),
+            source: TicketSource::Email,
+            company_id,
+            contact_id: Some(contact_id),
+            site_id: None,
+            assigned_to_id: None,
+            team_id: None,
+            contract_id: None,
+            sla_id: None,
+            scheduled_start: None,
+            scheduled_end: None,
+            estimated_hours: None,
+            is_billable: None,
+            asset_id: None,
+            custom_fields: serde_json::json!({}),
+            tags: Vec::new(),
+        };
+        let ticket = self.tickets.create_ticket(tenant_id, user_id, &request).await?;
+
+        // Replies to the original message thread onto the new ticket
+        if let Some(ref message_id) = headers.message_id {
+            sqlx::query("UPDATE tickets SET email_message_id = $1 WHERE tenant_id = $2 AND id = $3")
+                .bind(message_id)
+                .bind(tenant_id)
+                .bind(ticket.id)
+                .execute(self.db.pool())
+                .await?;
+        }
+
+        Ok(InboundEmailOutcome::Created { ticket_id: ticket.id, queue_id })
+    }
+}
@@ -8,12 +8,24 @@ mod service;
 #[cfg(feature = "server")]
 mod routes;
 #[cfg(feature = "server")]
+mod attachments;
+#[cfg(feature = "server")]
 mod automation;
+#[cfg(feature = "server")]
+mod csat;
+#[cfg(feature = "server")]
+mod inbound;
 
 pub use models::*;
 #[cfg(feature = "server")]
 pub use service::TicketService;
 #[cfg(feature = "server")]
-pub use routes::ticket_routes;
+pub use routes::{csat_routes, shared_ticket_routes, ticket_routes};
+#[cfg(feature = "server")]
+pub use attachments::{AttachmentScanner, AttachmentService, NoopScanner, MAX_ATTACHMENT_BYTES};
 #[cfg(feature = "server")]
 pub use automation::AutomationEngine;
+#[cfg(feature = "server")]
+pub use csat::CsatService;
+#[cfg(feature = "server")]
+pub use inbound::InboundEmailProcessor;
@@ -1,10 +1,21 @@
 //! Ticket models and types
 
+use std::collections::{BTreeMap, HashMap};
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::modules::auth::UserRole;
+use crate::modules::billing::InvoiceDocumentHeader;
+use crate::modules::tenants::ResolvedBranding;
+use crate::modules::webhooks::FieldChange;
+use crate::utils::error::{AppError, FieldError};
+use crate::utils::pagination::ViewItem;
+use crate::utils::tenant_settings::TenantSettings;
+use crate::utils::timezone::TenantTimezone;
+
 // ============================================================================
 // TICKET SOURCE
 // ============================================================================
@@ -115,6 +126,17 @@ impl NoteType {
             Self::TimeEntry => "time_entry",
         }
     }
+
+    /// Whether the customer sees notes of this type
+    pub fn is_customer_visible(&self) -> bool {
+        matches!(self, Self::Public | Self::Resolution)
+    }
+
+    /// Whether a note of this type stops the first-response clock. Public
+    /// notes the system posts on its own, such as acknowledgements, don't.
+    pub fn is_first_response(&self, counts_as_first_response: bool) -> bool {
+        *self == Self::Public && counts_as_first_response
+    }
 }
 
 // ============================================================================
@@ -130,6 +152,130 @@ pub struct TicketStatus {
     pub is_closed: bool,
     pub is_default: bool,
     pub sort_order: i32,
+    /// Closing into this status requires a resolution code
+    pub requires_resolution_code: bool,
+    /// Closed for good; other closed statuses are resolved and auto-close
+    /// into this one
+    pub is_final: bool,
+}
+
+impl TicketStatus {
+    /// Check a resolution code against this status and the tenant's code list
+    pub fn validate_resolution_code(
+        &self,
+        code: Option<&str>,
+        codes: &[ResolutionCode],
+    ) -> Result<(), AppError> {
+        match code {
+            Some(code) => {
+                if codes.iter().any(|c| c.is_active && c.code == code) {
+                    Ok(())
+                } else {
+                    Err(AppError::validation_field(
+                        "resolution_code",
+                        format!("Unknown resolution code '{}'", code),
+                    ))
+                }
+            }
+            None if self.is_closed && self.requires_resolution_code => Err(
+                AppError::validation_field(
+                    "resolution_code",
+                    format!("A resolution code is required to move a ticket to '{}'", self.name),
+                ),
+            ),
+            None => Ok(()),
+        }
+    }
+
+    /// Check the resolution code a ticket has after an update moves it to
+    /// this status. A code the update sets must be active; one the ticket
+    /// already has stands even if the tenant has since deactivated it.
+    pub fn validate_updated_resolution_code(
+        &self,
+        requested: Option<&str>,
+        stored: Option<&str>,
+        codes: &[ResolutionCode],
+    ) -> Result<(), AppError> {
+        match (requested, stored) {
+            (Some(code), _) => self.validate_resolution_code(Some(code), codes),
+            (None, Some(_)) => Ok(()),
+            (None, None) => self.validate_resolution_code(None, codes),
+        }
+    }
+}
+
+/// An allowed move from one status to another
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusTransition {
+    pub from_status_id: Uuid,
+    pub to_status_id: Uuid,
+}
+
+/// Replace the statuses a status may move to; empty lifts the restriction
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct SetStatusTransitionsRequest {
+    #[validate(length(max = 100))]
+    pub to_status_ids: Vec<Uuid>,
+}
+
+/// The tenant's allowed status transitions.
+///
+/// Only statuses with transitions configured are restricted; a status with
+/// none may move anywhere, so a tenant without a workflow is unaffected.
+/// Reopening a closed ticket is its own action and bypasses the workflow.
+#[derive(Debug, Clone, Default)]
+pub struct StatusWorkflow {
+    allowed: HashMap<Uuid, Vec<Uuid>>,
+}
+
+impl StatusWorkflow {
+    pub fn new(transitions: &[StatusTransition]) -> Self {
+        let mut allowed: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for transition in transitions {
+            allowed
+                .entry(transition.from_status_id)
+                .or_default()
+                .push(transition.to_status_id);
+        }
+        Self { allowed }
+    }
+
+    /// Whether a ticket may move from `from` to `to`. Staying put always is.
+    pub fn allows(&self, from: Uuid, to: Uuid) -> bool {
+        from == to || self.allowed.get(&from).map_or(true, |targets| targets.contains(&to))
+    }
+
+    /// Reject a move the workflow doesn't allow
+    pub fn check(&self, from: &TicketStatus, to: &TicketStatus) -> Result<(), AppError> {
+        if self.allows(from.id, to.id) {
+            return Ok(());
+        }
+
+        let mut message = format!("Tickets can't move from '{}' to '{}'", from.name, to.name);
+        if from.is_closed && !to.is_closed {
+            message.push_str("; reopen the ticket first");
+        }
+        Err(AppError::validation(
+            message.clone(),
+            vec![FieldError::new("status_id", message, "transition")],
+        ))
+    }
+}
+
+// ============================================================================
+// RESOLUTION CODES
+// ============================================================================
+
+/// Tenant-managed close reason recorded on a ticket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolutionCode {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub code: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub is_active: bool,
+    pub sort_order: i32,
 }
 
 // ============================================================================
@@ -143,6 +289,8 @@ pub struct TicketPriority {
     pub name: String,
     pub color: String,
     pub icon: Option<String>,
+    /// Scales an SLA policy's base targets for priorities the policy has no
+    /// targets of its own for; see [`SlaTarget::for_priority`]
     pub sla_multiplier: f64,
     pub sort_order: i32,
     pub is_default: bool,
@@ -161,6 +309,141 @@ pub struct TicketType {
     pub icon: Option<String>,
     pub is_active: bool,
     pub sort_order: i32,
+    /// Fields a ticket of this type must have, see [`RequiredTicketField`]
+    pub required_fields: Vec<String>,
+}
+
+impl TicketType {
+    /// Check a ticket's fields against this type's rules, reporting every
+    /// missing field at once
+    pub fn check_required_fields(&self, values: &TicketFieldValues<'_>) -> Result<(), AppError> {
+        let errors: Vec<FieldError> = self
+            .required_fields
+            .iter()
+            .filter_map(|rule| RequiredTicketField::parse(rule))
+            .flat_map(|field| values.missing(&field))
+            .map(|name| FieldError::new(name, format!("Required for {} tickets", self.name), "required"))
+            .collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::validation("Required fields are missing", errors))
+        }
+    }
+}
+
+/// A field a ticket type can require
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequiredTicketField {
+    Description,
+    Contact,
+    Site,
+    Category,
+    Asset,
+    /// Both `scheduled_start` and `scheduled_end`
+    ScheduledWindow,
+    EstimatedHours,
+    /// A key of `custom_fields`, written `custom_fields.<key>`
+    Custom(String),
+}
+
+impl RequiredTicketField {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "description" => Some(Self::Description),
+            "contact_id" => Some(Self::Contact),
+            "site_id" => Some(Self::Site),
+            "category_id" => Some(Self::Category),
+            "asset_id" => Some(Self::Asset),
+            "scheduled_window" => Some(Self::ScheduledWindow),
+            "estimated_hours" => Some(Self::EstimatedHours),
+            _ => s
+                .strip_prefix("custom_fields.")
+                .filter(|key| !key.is_empty())
+                .map(|key| Self::Custom(key.to_string())),
+        }
+    }
+}
+
+/// The fields required-field rules look at, taken from a create request or
+/// from a ticket with an update applied
+#[derive(Debug, Clone, Copy)]
+pub struct TicketFieldValues<'a> {
+    pub description: Option<&'a str>,
+    pub contact_id: Option<Uuid>,
+    pub site_id: Option<Uuid>,
+    pub category_id: Option<Uuid>,
+    pub asset_id: Option<Uuid>,
+    pub scheduled_start: Option<DateTime<Utc>>,
+    pub scheduled_end: Option<DateTime<Utc>>,
+    pub estimated_hours: Option<f64>,
+    pub custom_fields: &'a serde_json::Value,
+}
+
+impl<'a> TicketFieldValues<'a> {
+    pub fn from_create(request: &'a CreateTicketRequest) -> Self {
+        Self {
+            description: request.description.as_deref(),
+            contact_id: request.contact_id,
+            site_id: request.site_id,
+            category_id: request.category_id,
+            asset_id: request.asset_id,
+            scheduled_start: request.scheduled_start,
+            scheduled_end: request.scheduled_end,
+            estimated_hours: request.estimated_hours,
+            custom_fields: &request.custom_fields,
+        }
+    }
+
+    pub fn from_update(ticket: &'a Ticket, request: &'a UpdateTicketRequest) -> Self {
+        Self {
+            description: request.description.as_deref().or(ticket.description.as_deref()),
+            contact_id: request.contact_id.or(ticket.contact_id),
+            site_id: request.site_id.or(ticket.site_id),
+            category_id: request.category_id.or(ticket.category_id),
+            asset_id: request.asset_id.or(ticket.asset_id),
+            scheduled_start: request.scheduled_start.or(ticket.scheduled_start),
+            scheduled_end: request.scheduled_end.or(ticket.scheduled_end),
+            estimated_hours: request.estimated_hours.or(ticket.estimated_hours),
+            custom_fields: request.custom_fields.as_ref().unwrap_or(&ticket.custom_fields),
+        }
+    }
+
+    /// Request fields a rule finds missing, empty when it is satisfied
+    fn missing(&self, field: &RequiredTicketField) -> Vec<String> {
+        let check = |present: bool, name: &str| {
+            if present {
+                Vec::new()
+            } else {
+                vec![name.to_string()]
+            }
+        };
+
+        match field {
+            RequiredTicketField::Description => {
+                check(self.description.is_some_and(|d| !d.trim().is_empty()), "description")
+            }
+            RequiredTicketField::Contact => check(self.contact_id.is_some(), "contact_id"),
+            RequiredTicketField::Site => check(self.site_id.is_some(), "site_id"),
+            RequiredTicketField::Category => check(self.category_id.is_some(), "category_id"),
+            RequiredTicketField::Asset => check(self.asset_id.is_some(), "asset_id"),
+            RequiredTicketField::ScheduledWindow => [
+                check(self.scheduled_start.is_some(), "scheduled_start"),
+                check(self.scheduled_end.is_some(), "scheduled_end"),
+            ]
+            .concat(),
+            RequiredTicketField::EstimatedHours => check(self.estimated_hours.is_some(), "estimated_hours"),
+            RequiredTicketField::Custom(key) => {
+                let present = match self.custom_fields.get(key) {
+                    None | Some(serde_json::Value::Null) => false,
+                    Some(serde_json::Value::String(value)) => !value.trim().is_empty(),
+                    Some(_) => true,
+                };
+                check(present, &format!("custom_fields.{}", key))
+            }
+        }
+    }
 }
 
 // ============================================================================
@@ -177,6 +460,150 @@ pub struct TicketQueue {
     pub icon: Option<String>,
     pub is_default: bool,
     pub sort_order: i32,
+    pub default_sla_id: Option<Uuid>,
+    pub default_priority_id: Option<Uuid>,
+    pub default_assignee_id: Option<Uuid>,
+    pub default_team_id: Option<Uuid>,
+    /// Overrides the tenant's `auto_acknowledge` setting when set
+    pub auto_acknowledge: Option<bool>,
+}
+
+/// The ticket fields a queue can default
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct QueueFields {
+    pub sla_id: Option<Uuid>,
+    pub priority_id: Option<Uuid>,
+    pub assigned_to_id: Option<Uuid>,
+    pub team_id: Option<Uuid>,
+}
+
+impl TicketQueue {
+    /// Fields for a ticket entering this queue. Values the caller set in
+    /// `explicit` always win. The queue's SLA policy and priority replace the
+    /// ticket's `current` ones, while its assignee and team only fill a gap so
+    /// a move never takes a ticket off someone's plate.
+    pub fn entry_fields(&self, explicit: &QueueFields, current: &QueueFields) -> QueueFields {
+        QueueFields {
+            sla_id: explicit.sla_id.or(self.default_sla_id).or(current.sla_id),
+            priority_id: explicit.priority_id.or(self.default_priority_id).or(current.priority_id),
+            assigned_to_id: explicit
+                .assigned_to_id
+                .or(current.assigned_to_id)
+                .or(self.default_assignee_id),
+            team_id: explicit.team_id.or(current.team_id).or(self.default_team_id),
+        }
+    }
+}
+
+// ============================================================================
+// CONTRACT DEFAULTS
+// ============================================================================
+
+/// The contract a new ticket is opened under: the one the request names, or
+/// the company's default while it is active
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractTerms {
+    pub contract_id: Uuid,
+    pub contract_type: String,
+    pub sla_id: Option<Uuid>,
+}
+
+impl ContractTerms {
+    /// Whether the contract's fee already pays for the work, so time on its
+    /// tickets isn't billed separately. Block hours and time and materials
+    /// bill the work itself.
+    pub fn covers_work(&self) -> bool {
+        matches!(self.contract_type.as_str(), "managed_services" | "fixed_price" | "warranty")
+    }
+}
+
+/// Contract, SLA policy and billing for a new ticket
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContractFields {
+    pub contract_id: Option<Uuid>,
+    pub sla_id: Option<Uuid>,
+    pub is_billable: bool,
+}
+
+impl CreateTicketRequest {
+    /// Fields the request leaves unset come from `terms`. Without a contract
+    /// a ticket is billable.
+    pub fn contract_fields(&self, terms: Option<&ContractTerms>) -> ContractFields {
+        ContractFields {
+            contract_id: self.contract_id.or(terms.map(|t| t.contract_id)),
+            sla_id: self.sla_id.or(terms.and_then(|t| t.sla_id)),
+            is_billable: self
+                .is_billable
+                .unwrap_or_else(|| !terms.is_some_and(ContractTerms::covers_work)),
+        }
+    }
+}
+
+// ============================================================================
+// SLA TARGETS
+// ============================================================================
+
+/// An SLA policy's targets for one priority
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SlaTarget {
+    pub first_response_hours: Option<f64>,
+    pub resolution_hours: Option<f64>,
+}
+
+impl SlaTarget {
+    /// Targets for a priority under a policy. Targets the policy sets for the
+    /// priority itself are used as they are. Otherwise the policy's base
+    /// targets, those for the tenant's default priority, are scaled by the
+    /// priority's `sla_multiplier`: 0.5 halves the window, 2.0 doubles it.
+    pub fn for_priority(own: Option<SlaTarget>, base: Option<SlaTarget>, sla_multiplier: f64) -> Option<SlaTarget> {
+        own.or_else(|| base.map(|base| base.scaled(sla_multiplier)))
+    }
+
+    /// Both targets multiplied by `multiplier`, ignored unless positive
+    pub fn scaled(&self, multiplier: f64) -> Self {
+        let multiplier = if multiplier > 0.0 { multiplier } else { 1.0 };
+        Self {
+            first_response_hours: self.first_response_hours.map(|h| h * multiplier),
+            resolution_hours: self.resolution_hours.map(|h| h * multiplier),
+        }
+    }
+
+    /// First response and resolution due dates for a clock started at `start`
+    pub fn due_dates(&self, start: DateTime<Utc>) -> (Option<DateTime<Utc>>, Option<DateTime<Utc>>) {
+        let due = |hours: Option<f64>| hours.map(|h| start + chrono::Duration::minutes((h * 60.0) as i64));
+        (due(self.first_response_hours), due(self.resolution_hours))
+    }
+}
+
+/// When an SLA policy's running clock turns a ticket to Warning
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SlaWarning {
+    /// Share of the SLA window left, 0 to 1, at which the ticket is at risk
+    pub remaining_fraction: f64,
+    /// Warn no earlier than this many hours before due, so long windows
+    /// aren't flagged days ahead
+    pub max_hours: Option<f64>,
+}
+
+impl Default for SlaWarning {
+    fn default() -> Self {
+        Self {
+            remaining_fraction: 0.2,
+            max_hours: None,
+        }
+    }
+}
+
+impl SlaWarning {
+    /// How long before `due` a clock started at `start` turns to Warning
+    pub fn threshold(&self, start: DateTime<Utc>, due: DateTime<Utc>) -> chrono::Duration {
+        let window = (due - start).num_seconds().max(0) as f64;
+        let mut seconds = window * self.remaining_fraction.clamp(0.0, 1.0);
+        if let Some(hours) = self.max_hours.filter(|hours| *hours >= 0.0) {
+            seconds = seconds.min(hours * 3600.0);
+        }
+        chrono::Duration::seconds(seconds as i64)
+    }
 }
 
 // ============================================================================
@@ -211,6 +638,7 @@ pub struct Ticket {
     pub resolution_due: Option<DateTime<Utc>>,
     pub resolved_at: Option<DateTime<Utc>>,
     pub closed_at: Option<DateTime<Utc>>,
+    pub resolution_code: Option<String>,
     pub scheduled_start: Option<DateTime<Utc>>,
     pub scheduled_end: Option<DateTime<Utc>>,
     pub estimated_hours: Option<f64>,
@@ -220,6 +648,12 @@ pub struct Ticket {
     pub asset_id: Option<Uuid>,
     pub custom_fields: serde_json::Value,
     pub tags: Vec<String>,
+    /// The subset of `tags` applied by tag rules rather than by hand
+    pub rule_tags: Vec<String>,
+    /// Hidden from active views until then
+    pub snoozed_until: Option<DateTime<Utc>>,
+    pub snoozed_at: Option<DateTime<Utc>>,
+    pub snoozed_by_id: Option<Uuid>,
     pub created_by_id: Uuid,
     pub last_updated_by_id: Option<Uuid>,
     pub created_at: DateTime<Utc>,
@@ -248,8 +682,8 @@ pub struct CreateTicketRequest {
     pub scheduled_start: Option<DateTime<Utc>>,
     pub scheduled_end: Option<DateTime<Utc>>,
     pub estimated_hours: Option<f64>,
-    #[serde(default = "default_true")]
-    pub is_billable: bool,
+    /// Unset follows the ticket's contract
+    pub is_billable: Option<bool>,
     pub asset_id: Option<Uuid>,
     #[serde(default)]
     pub custom_fields: serde_json::Value,
@@ -257,10 +691,6 @@ pub struct CreateTicketRequest {
     pub tags: Vec<String>,
 }
 
-fn default_true() -> bool {
-    true
-}
-
 /// Update ticket request
 #[derive(Debug, Clone, Deserialize, Validate)]
 pub struct UpdateTicketRequest {
@@ -286,6 +716,8 @@ pub struct UpdateTicketRequest {
     pub asset_id: Option<Uuid>,
     pub custom_fields: Option<serde_json::Value>,
     pub tags: Option<Vec<String>>,
+    #[validate(length(min = 1, max = 50))]
+    pub resolution_code: Option<String>,
 }
 
 /// Ticket response for API
@@ -309,11 +741,14 @@ pub struct TicketResponse {
     pub assigned_to_name: Option<String>,
     pub sla_due_date: Option<DateTime<Utc>>,
     pub sla_status: SlaStatus,
+    pub resolution_code: Option<String>,
     pub is_billable: bool,
     pub billing_status: BillingStatus,
     pub estimated_hours: Option<f64>,
     pub actual_hours: f64,
     pub tags: Vec<String>,
+    pub rule_tags: Vec<String>,
+    pub snoozed_until: Option<DateTime<Utc>>,
     pub created_by_name: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -334,6 +769,79 @@ pub struct TicketPrioritySummary {
     pub color: String,
 }
 
+impl From<Ticket> for TicketResponse {
+    fn from(ticket: Ticket) -> Self {
+        let sla_status = ticket.sla_status();
+        Self {
+            id: ticket.id,
+            ticket_number: ticket.ticket_number,
+            title: ticket.title,
+            description: ticket.description,
+            status: TicketStatusSummary {
+                id: ticket.status_id,
+                name: String::new(), // Would be joined from DB
+                color: String::new(),
+                is_closed: false,
+            },
+            priority: TicketPrioritySummary {
+                id: ticket.priority_id,
+                name: String::new(),
+                color: String::new(),
+            },
+            type_name: None,
+            category_name: None,
+            queue_name: String::new(),
+            source: ticket.source,
+            company_id: ticket.company_id,
+            company_name: String::new(),
+            contact_id: ticket.contact_id,
+            contact_name: None,
+            assigned_to_id: ticket.assigned_to_id,
+            assigned_to_name: None,
+            sla_due_date: ticket.sla_due_date,
+            sla_status,
+            resolution_code: ticket.resolution_code,
+            is_billable: ticket.is_billable,
+            billing_status: ticket.billing_status,
+            estimated_hours: ticket.estimated_hours,
+            actual_hours: ticket.actual_hours,
+            tags: ticket.tags,
+            rule_tags: ticket.rule_tags,
+            snoozed_until: ticket.snoozed_until,
+            created_by_name: String::new(),
+            created_at: ticket.created_at,
+            updated_at: ticket.updated_at,
+        }
+    }
+}
+
+/// Compact ticket for `?view=summary` lists
+#[derive(Debug, Clone, Serialize)]
+pub struct TicketSummary {
+    pub id: Uuid,
+    pub ticket_number: String,
+    pub title: String,
+    pub status_id: Uuid,
+    pub is_closed: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Ticket> for TicketSummary {
+    fn from(ticket: Ticket) -> Self {
+        Self {
+            id: ticket.id,
+            ticket_number: ticket.ticket_number,
+            title: ticket.title,
+            status_id: ticket.status_id,
+            is_closed: ticket.closed_at.is_some(),
+            updated_at: ticket.updated_at,
+        }
+    }
+}
+
+/// Ticket list entry in the requested view
+pub type TicketListItem = ViewItem<TicketResponse, TicketSummary>;
+
 /// SLA status indicator
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -344,9 +852,34 @@ pub enum SlaStatus {
     NotApplicable,
 }
 
+/// Webhook event sent after `update_ticket` changes a ticket
+pub const TICKET_UPDATED_EVENT: &str = "ticket.updated";
+
+/// Fields kept up to date as a side effect of other changes, left out of a
+/// ticket's webhook `changes`
+const UNTRACKED_FIELDS: &[&str] = &[
+    "updated_at",
+    "last_updated_by_id",
+    "sla_due_date",
+    "first_response_due",
+    "resolution_due",
+    "rule_tags",
+];
+
 impl Ticket {
-    /// Calculate SLA status
+    /// What changed between this version of the ticket and `after`
+    pub fn changes(&self, after: &Ticket) -> Vec<FieldChange> {
+        FieldChange::diff(self, after, UNTRACKED_FIELDS)
+    }
+
+    /// Calculate SLA status with the default warning threshold
     pub fn sla_status(&self) -> SlaStatus {
+        self.sla_status_at(&SlaWarning::default(), Utc::now())
+    }
+
+    /// SLA status at `now`, turning to Warning once the time left falls under
+    /// the policy's threshold for the window from creation to due
+    pub fn sla_status_at(&self, warning: &SlaWarning, now: DateTime<Utc>) -> SlaStatus {
         if self.closed_at.is_some() {
             return SlaStatus::NotApplicable;
         }
@@ -355,361 +888,3908 @@ impl Ticket {
             return SlaStatus::NotApplicable;
         };
 
-        let now = Utc::now();
         if now > due {
             SlaStatus::Breached
-        } else if (due - now).num_hours() < 2 {
+        } else if due - now < warning.threshold(self.created_at, due) {
             SlaStatus::Warning
         } else {
             SlaStatus::OnTrack
         }
     }
+
+    /// Time left until the SLA is due at `now`, negative once breached, or
+    /// `None` for closed tickets and tickets without an SLA. While a snooze
+    /// pauses the clock the time left stays at what remained when the snooze
+    /// began, matching the targets it gets back when the snooze ends.
+    pub fn sla_time_remaining(&self, now: DateTime<Utc>, snooze_pauses_sla: bool) -> Option<chrono::Duration> {
+        if self.closed_at.is_some() {
+            return None;
+        }
+        let due = self.sla_due_date?;
+
+        let clock = match self.snoozed_at {
+            Some(snoozed_at) if snooze_pauses_sla && self.is_snoozed(now) => snoozed_at.min(now),
+            _ => now,
+        };
+        Some(due - clock)
+    }
+
+    /// Whether a resolved ticket has sat untouched for `grace` and should be
+    /// closed. Reopening clears `resolved_at`, so a ticket reopened and
+    /// resolved again starts a new grace period.
+    pub fn auto_close_due(&self, grace: chrono::Duration, now: DateTime<Utc>) -> bool {
+        let cutoff = now - grace;
+        match self.resolved_at.or(self.closed_at) {
+            Some(resolved_at) => resolved_at <= cutoff && self.updated_at <= cutoff,
+            None => false,
+        }
+    }
+
+    /// Whether the ticket is snoozed at `now`
+    pub fn is_snoozed(&self, now: DateTime<Utc>) -> bool {
+        self.snoozed_until.is_some_and(|until| until > now)
+    }
+
+    /// SLA targets pushed back by `paused`, for tenants where snoozing stops
+    /// the clock. Targets already met are left alone.
+    pub fn sla_after_snooze(&self, paused: chrono::Duration) -> SnoozedSla {
+        let shift = |due: Option<DateTime<Utc>>| due.map(|due| due + paused);
+        let resolved = self.resolved_at.is_some() || self.closed_at.is_some();
+
+        SnoozedSla {
+            first_response_due: if self.first_response_at.is_some() {
+                self.first_response_due
+            } else {
+                shift(self.first_response_due)
+            },
+            sla_due_date: if resolved { self.sla_due_date } else { shift(self.sla_due_date) },
+            resolution_due: if resolved { self.resolution_due } else { shift(self.resolution_due) },
+        }
+    }
 }
 
 // ============================================================================
-// TICKET NOTES
+// CLAIM
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TicketNote {
-    pub id: Uuid,
-    pub tenant_id: Uuid,
-    pub ticket_id: Uuid,
-    pub note_type: NoteType,
-    pub content: String,
-    pub content_html: Option<String>,
-    pub is_email_sent: bool,
-    pub email_sent_at: Option<DateTime<Utc>>,
-    pub created_by_id: Uuid,
-    pub created_by_name: Option<String>,
-    pub created_at: DateTime<Utc>,
-    pub updated_at: DateTime<Utc>,
+/// What a claim found when the conditional update did not assign the ticket
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClaimOutcome {
+    /// The update assigned the ticket to the claimant
+    Claimed,
+    /// The claimant already had it; claiming again is a no-op
+    AlreadyClaimed,
+    /// Someone else got there first
+    ClaimedBy(Uuid),
+    /// Unassigned again by the time it was re-read
+    Contended,
 }
 
-/// Create note request
-#[derive(Debug, Clone, Deserialize, Validate)]
-pub struct CreateNoteRequest {
-    #[serde(default)]
-    pub note_type: NoteType,
-    #[validate(length(min = 1))]
-    pub content: String,
-    /// Send email notification to contact
-    #[serde(default)]
-    pub send_email: bool,
-}
+impl ClaimOutcome {
+    /// Outcome from whether the `assigned_to_id IS NULL` update matched and
+    /// the assignee read back afterwards
+    pub fn from_update(updated: bool, user_id: Uuid, assignee: Option<Uuid>) -> Self {
+        match (updated, assignee) {
+            (true, _) => Self::Claimed,
+            (false, Some(id)) if id == user_id => Self::AlreadyClaimed,
+            (false, Some(id)) => Self::ClaimedBy(id),
+            (false, None) => Self::Contended,
+        }
+    }
 
-/// Note response
-#[derive(Debug, Clone, Serialize)]
-pub struct TicketNoteResponse {
-    pub id: Uuid,
-    pub note_type: NoteType,
-    pub content: String,
-    pub is_email_sent: bool,
-    pub created_by_id: Uuid,
-    pub created_by_name: String,
-    pub created_at: DateTime<Utc>,
+    /// Error for a lost claim, naming who holds the ticket
+    pub fn into_result(self, assignee_name: Option<&str>) -> Result<(), AppError> {
+        match self {
+            Self::Claimed | Self::AlreadyClaimed => Ok(()),
+            Self::ClaimedBy(_) => Err(AppError::Conflict(format!(
+                "Ticket already claimed by {}",
+                assignee_name.unwrap_or("another user")
+            ))),
+            Self::Contended => Err(AppError::Conflict(
+                "Ticket changed while claiming, please try again".to_string(),
+            )),
+        }
+    }
 }
 
 // ============================================================================
-// TICKET ATTACHMENTS
+// BULK REASSIGNMENT
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TicketAttachment {
-    pub id: Uuid,
-    pub tenant_id: Uuid,
+/// Where a departing technician's open tickets go
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ReassignTarget {
+    /// One technician takes them all
+    User { user_id: Uuid },
+    /// Unassigned in a queue, for whoever picks them up
+    Queue { queue_id: Uuid },
+    /// Dealt out in turn to these technicians
+    RoundRobin { user_ids: Vec<Uuid> },
+}
+
+/// Move a technician's open tickets, optionally only some of them
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReassignRequest {
+    pub to: ReassignTarget,
+    pub company_id: Option<Uuid>,
+    pub queue_id: Option<Uuid>,
+}
+
+/// A ticket assigned to the departing technician
+#[derive(Debug, Clone, Copy)]
+pub struct ReassignCandidate {
     pub ticket_id: Uuid,
-    pub note_id: Option<Uuid>,
-    pub file_name: String,
-    pub file_size: i64,
-    pub mime_type: String,
-    pub storage_path: String,
-    pub uploaded_by_id: Uuid,
-    pub created_at: DateTime<Utc>,
+    pub company_id: Uuid,
+    pub queue_id: Option<Uuid>,
+    pub is_closed: bool,
 }
 
-#[derive(Debug, Clone, Serialize)]
-pub struct TicketAttachmentResponse {
-    pub id: Uuid,
-    pub file_name: String,
-    pub file_size: i64,
-    pub mime_type: String,
-    pub uploaded_by_name: String,
-    pub created_at: DateTime<Utc>,
+/// Where one ticket moved to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Reassignment {
+    pub ticket_id: Uuid,
+    /// `None` when the ticket went unassigned to a queue
+    pub assigned_to_id: Option<Uuid>,
+    pub queue_id: Option<Uuid>,
+}
+
+impl ReassignRequest {
+    pub fn check(&self, from_user_id: Uuid) -> Result<(), AppError> {
+        let user_ids = match &self.to {
+            ReassignTarget::User { user_id } => std::slice::from_ref(user_id),
+            ReassignTarget::RoundRobin { user_ids } if user_ids.is_empty() => {
+                return Err(AppError::validation_field("to", "Choose at least one technician"));
+            }
+            ReassignTarget::RoundRobin { user_ids } => user_ids.as_slice(),
+            ReassignTarget::Queue { .. } => &[],
+        };
+        if user_ids.contains(&from_user_id) {
+            return Err(AppError::validation_field("to", "Tickets can't be reassigned to the technician leaving"));
+        }
+        Ok(())
+    }
+
+    /// New assignments for the open candidates that match the filters, in
+    /// the order given. Closed tickets keep their assignee for reporting.
+    pub fn plan(&self, candidates: &[ReassignCandidate]) -> Vec<Reassignment> {
+        candidates
+            .iter()
+            .filter(|c| !c.is_closed)
+            .filter(|c| self.company_id.is_none_or(|id| c.company_id == id))
+            .filter(|c| self.queue_id.is_none_or(|id| c.queue_id == Some(id)))
+            .enumerate()
+            .map(|(i, c)| match &self.to {
+                ReassignTarget::User { user_id } => Reassignment {
+                    ticket_id: c.ticket_id,
+                    assigned_to_id: Some(*user_id),
+                    queue_id: c.queue_id,
+                },
+                ReassignTarget::Queue { queue_id } => Reassignment {
+                    ticket_id: c.ticket_id,
+                    assigned_to_id: None,
+                    queue_id: Some(*queue_id),
+                },
+                ReassignTarget::RoundRobin { user_ids } => Reassignment {
+                    ticket_id: c.ticket_id,
+                    assigned_to_id: user_ids.get(i % user_ids.len().max(1)).copied(),
+                    queue_id: c.queue_id,
+                },
+            })
+            .collect()
+    }
 }
 
 // ============================================================================
-// TICKET FILTERS
+// BULK CLOSE
 // ============================================================================
 
-#[derive(Debug, Clone, Deserialize, Default)]
-pub struct TicketFilter {
-    pub q: Option<String>,
+/// Close many tickets at once, e.g. everything raised by a resolved outage
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct BulkCloseRequest {
+    #[validate(length(min = 1, max = 500))]
+    pub ticket_ids: Vec<Uuid>,
+    /// Closed status to move them to; the tenant's first closed status otherwise
     pub status_id: Option<Uuid>,
-    pub priority_id: Option<Uuid>,
-    pub type_id: Option<Uuid>,
-    pub queue_id: Option<Uuid>,
-    pub company_id: Option<Uuid>,
+    pub resolution_code: Option<String>,
+    /// Added to the internal note posted on each ticket
+    #[validate(length(max = 2000))]
+    pub note: Option<String>,
+    /// Close without emailing customers. Status history, notes and the audit
+    /// log are still recorded.
+    #[serde(default)]
+    pub suppress_notifications: bool,
+}
+
+/// A ticket picked for a bulk close
+#[derive(Debug, Clone, Copy)]
+pub struct BulkCloseCandidate {
+    pub ticket_id: Uuid,
+    pub status_id: Uuid,
+    pub is_closed: bool,
+    pub has_contact: bool,
+}
+
+/// How one ticket is closed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BulkClosure {
+    pub ticket_id: Uuid,
+    pub from_status_id: Uuid,
+    /// Whether the contact gets the usual closing email
+    pub notify_customer: bool,
+    /// Internal note recording the close on the ticket
+    pub activity: String,
+}
+
+/// A ticket left as it was, and why
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BulkCloseSkip {
+    pub ticket_id: Uuid,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BulkCloseOutcome {
+    pub closed: Vec<BulkClosure>,
+    pub skipped: Vec<BulkCloseSkip>,
+}
+
+impl BulkCloseRequest {
+    /// Work out which of the requested tickets close, in the order asked for.
+    /// Missing and already closed tickets are skipped, as are ones the status
+    /// workflow won't let move to `status`.
+    pub fn plan(
+        &self,
+        candidates: &[BulkCloseCandidate],
+        status: &TicketStatus,
+        workflow: &StatusWorkflow,
+    ) -> BulkCloseOutcome {
+        let mut activity = format!("Closed in bulk as {}", status.name);
+        if let Some(note) = self.note.as_deref().map(str::trim).filter(|note| !note.is_empty()) {
+            activity = format!("{}: {}", activity, note);
+        }
+        if self.suppress_notifications {
+            activity.push_str(" (customer notifications suppressed)");
+        }
+
+        let mut outcome = BulkCloseOutcome::default();
+        let mut seen = Vec::with_capacity(self.ticket_ids.len());
+        for ticket_id in &self.ticket_ids {
+            if seen.contains(ticket_id) {
+                continue;
+            }
+            seen.push(*ticket_id);
+
+            let skip = |reason: &str| BulkCloseSkip {
+                ticket_id: *ticket_id,
+                reason: reason.to_string(),
+            };
+            match candidates.iter().find(|c| c.ticket_id == *ticket_id) {
+                None => outcome.skipped.push(skip("Ticket not found")),
+                Some(c) if c.is_closed => outcome.skipped.push(skip("Already closed")),
+                Some(c) if !workflow.allows(c.status_id, status.id) => outcome
+                    .skipped
+                    .push(skip(&format!("The status workflow doesn't allow closing into '{}'", status.name))),
+                Some(c) => outcome.closed.push(BulkClosure {
+                    ticket_id: c.ticket_id,
+                    from_status_id: c.status_id,
+                    notify_customer: c.has_contact && !self.suppress_notifications,
+                    activity: activity.clone(),
+                }),
+            }
+        }
+        outcome
+    }
+}
+
+// ============================================================================
+// ASSIGNMENT SUGGESTIONS
+// ============================================================================
+
+/// Weight of a past resolution sharing the ticket's asset, category or
+/// company. The asset is the most specific match, the company the least.
+const ASSET_MATCH_WEIGHT: f64 = 3.0;
+const CATEGORY_MATCH_WEIGHT: f64 = 2.0;
+const COMPANY_MATCH_WEIGHT: f64 = 1.0;
+/// Each open ticket a technician holds shrinks their score by this share
+const OPEN_TICKET_PENALTY: f64 = 0.1;
+/// Similar resolutions needed before a suggestion is half confident
+const CONFIDENCE_EVIDENCE: f64 = 5.0;
+
+/// A technician's resolved tickets like the one being assigned, and their
+/// current load
+#[derive(Debug, Clone)]
+pub struct AssigneeHistory {
+    pub user_id: Uuid,
+    pub name: String,
+    /// Resolved tickets sharing the ticket's asset, category and company
+    pub same_asset: i64,
+    pub same_category: i64,
+    pub same_company: i64,
+    pub open_tickets: i64,
+}
+
+impl AssigneeHistory {
+    fn matches(&self) -> f64 {
+        self.same_asset as f64 * ASSET_MATCH_WEIGHT
+            + self.same_category as f64 * CATEGORY_MATCH_WEIGHT
+            + self.same_company as f64 * COMPANY_MATCH_WEIGHT
+    }
+
+    fn score(&self) -> f64 {
+        self.matches() / (1.0 + self.open_tickets.max(0) as f64 * OPEN_TICKET_PENALTY)
+    }
+}
+
+/// A technician suggested for a ticket
+#[derive(Debug, Clone, Serialize)]
+pub struct AssigneeSuggestion {
+    pub user_id: Uuid,
+    pub name: String,
+    pub score: f64,
+    /// 0 to 1: the technician's share of the history, discounted while there
+    /// is little of it
+    pub confidence: f64,
+    pub resolved_similar: i64,
+    pub open_tickets: i64,
+}
+
+/// Technicians with a history of tickets like this one, best first. Those
+/// with no similar resolutions aren't suggested.
+pub fn rank_assignees(history: &[AssigneeHistory]) -> Vec<AssigneeSuggestion> {
+    let candidates: Vec<&AssigneeHistory> = history.iter().filter(|h| h.matches() > 0.0).collect();
+    let total_score: f64 = candidates.iter().map(|h| h.score()).sum();
+    let evidence: f64 = candidates.iter().map(|h| h.matches()).sum();
+    let certainty = evidence / (evidence + CONFIDENCE_EVIDENCE);
+
+    let mut suggestions: Vec<AssigneeSuggestion> = candidates
+        .into_iter()
+        .map(|h| AssigneeSuggestion {
+            user_id: h.user_id,
+            name: h.name.clone(),
+            score: h.score(),
+            confidence: if total_score > 0.0 { h.score() / total_score * certainty } else { 0.0 },
+            resolved_similar: h.same_asset.max(h.same_category).max(h.same_company),
+            open_tickets: h.open_tickets,
+        })
+        .collect();
+    suggestions.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.open_tickets.cmp(&b.open_tickets)));
+    suggestions
+}
+
+// ============================================================================
+// SNOOZE
+// ============================================================================
+
+/// Job that brings a snoozed ticket back when its snooze runs out
+pub const UNSNOOZE_TICKET_JOB: &str = "tickets.unsnooze";
+
+/// Snooze ticket request
+#[derive(Debug, Clone, Deserialize)]
+pub struct SnoozeTicketRequest {
+    pub until: DateTime<Utc>,
+}
+
+impl SnoozeTicketRequest {
+    pub fn check(&self, now: DateTime<Utc>) -> Result<(), AppError> {
+        if self.until <= now {
+            return Err(AppError::validation_field("until", "Snooze must end in the future"));
+        }
+        Ok(())
+    }
+}
+
+/// Which tickets a list shows by snooze state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnoozeVisibility {
+    /// Active views: snoozed tickets stay out until the snooze ends
+    Hidden,
+    Included,
+    Only,
+}
+
+impl SnoozeVisibility {
+    pub fn from_filter(filter: &TicketFilter) -> Self {
+        match (filter.is_snoozed, filter.include_snoozed) {
+            (Some(true), _) => Self::Only,
+            (_, Some(true)) => Self::Included,
+            _ => Self::Hidden,
+        }
+    }
+
+    /// Condition on tickets aliased `t`, if any
+    pub fn sql(&self) -> Option<&'static str> {
+        match self {
+            Self::Hidden => Some("(t.snoozed_until IS NULL OR t.snoozed_until <= NOW())"),
+            Self::Included => None,
+            Self::Only => Some("t.snoozed_until > NOW()"),
+        }
+    }
+
+    /// Whether a list with this visibility shows `ticket` at `now`, as `sql` does
+    pub fn shows(&self, ticket: &Ticket, now: DateTime<Utc>) -> bool {
+        match self {
+            Self::Hidden => !ticket.is_snoozed(now),
+            Self::Included => true,
+            Self::Only => ticket.is_snoozed(now),
+        }
+    }
+}
+
+/// SLA time left for display, to the two largest units: "45 minutes
+/// remaining", "1 day 3 hours remaining" or "20 minutes overdue"
+pub fn humanize_sla_remaining(remaining: chrono::Duration) -> String {
+    let overdue = remaining < chrono::Duration::zero();
+    let minutes = remaining.num_minutes().unsigned_abs();
+    let suffix = if overdue { "overdue" } else { "remaining" };
+    if minutes == 0 {
+        return format!("less than a minute {}", suffix);
+    }
+
+    let unit = |count: u64, name: &str| format!("{} {}{}", count, name, if count == 1 { "" } else { "s" });
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    let parts = match (days, hours) {
+        (0, 0) => vec![unit(minutes, "minute")],
+        (0, _) if minutes == 0 => vec![unit(hours, "hour")],
+        (0, _) => vec![unit(hours, "hour"), unit(minutes, "minute")],
+        (_, 0) => vec![unit(days, "day")],
+        _ => vec![unit(days, "day"), unit(hours, "hour")],
+    };
+    format!("{} {}", parts.join(" "), suffix)
+}
+
+/// SLA targets moved back by the time a ticket spent snoozed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnoozedSla {
+    pub first_response_due: Option<DateTime<Utc>>,
+    pub sla_due_date: Option<DateTime<Utc>>,
+    pub resolution_due: Option<DateTime<Utc>>,
+}
+
+// ============================================================================
+// REOPEN
+// ============================================================================
+
+/// How SLA targets are handled when a closed ticket is reopened
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ReopenSlaMode {
+    /// Recalculate targets from the SLA policy as if the ticket were new
+    FreshClock,
+    /// Continue with whatever time was left when the ticket closed
+    #[default]
+    ResumeRemaining,
+    /// Drop SLA targets for the reopened ticket
+    NoSla,
+}
+
+impl ReopenSlaMode {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "fresh_clock" => Some(Self::FreshClock),
+            "resume_remaining" => Some(Self::ResumeRemaining),
+            "no_sla" => Some(Self::NoSla),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::FreshClock => "fresh_clock",
+            Self::ResumeRemaining => "resume_remaining",
+            Self::NoSla => "no_sla",
+        }
+    }
+
+    /// Work out the SLA targets for a ticket being reopened at `now`
+    pub fn plan(&self, ticket: &Ticket, now: DateTime<Utc>) -> ReopenSla {
+        match self {
+            Self::FreshClock => ReopenSla::Recalculate,
+            Self::NoSla => ReopenSla::Clear,
+            Self::ResumeRemaining => {
+                let closed_at = ticket.closed_at.unwrap_or(now);
+                let resume = |due: Option<DateTime<Utc>>| {
+                    due.map(|due| now + (due - closed_at).max(chrono::Duration::zero()))
+                };
+
+                ReopenSla::Shift {
+                    first_response_due: if ticket.first_response_at.is_some() {
+                        ticket.first_response_due
+                    } else {
+                        resume(ticket.first_response_due)
+                    },
+                    sla_due_date: resume(ticket.sla_due_date),
+                }
+            }
+        }
+    }
+}
+
+/// SLA changes to apply when reopening a ticket
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReopenSla {
+    Recalculate,
+    Shift {
+        first_response_due: Option<DateTime<Utc>>,
+        sla_due_date: Option<DateTime<Utc>>,
+    },
+    Clear,
+}
+
+/// Reopen ticket request
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct ReopenTicketRequest {
+    #[validate(length(min = 1, max = 1000))]
+    pub reason: String,
+}
+
+// ============================================================================
+// TICKET SETTINGS
+// ============================================================================
+
+/// Per-tenant ticket behaviour, stored under the `tickets` category of `tenant_settings`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TicketSettings {
+    pub reopen_sla_mode: ReopenSlaMode,
+    /// Hours after closing during which a customer reply reopens the ticket (0 disables)
+    pub auto_reopen_window_hours: i64,
+    /// Move SLA targets back by the time a ticket spends snoozed
+    pub snooze_pauses_sla: bool,
+    /// Title similarity, 0 to 1, at which an open ticket from the same
+    /// contact or asset is reported as a possible duplicate
+    pub duplicate_similarity: f64,
+    /// How far back to look for duplicates, in hours (0 disables)
+    pub duplicate_window_hours: i64,
+    /// Email the contact on tickets opened from the portal or by email
+    pub auto_acknowledge: bool,
+    /// Which tickets technicians see in their lists
+    pub visibility: TicketVisibility,
+}
+
+impl Default for TicketSettings {
+    fn default() -> Self {
+        Self {
+            reopen_sla_mode: ReopenSlaMode::default(),
+            auto_reopen_window_hours: 72,
+            snooze_pauses_sla: false,
+            duplicate_similarity: 0.5,
+            duplicate_window_hours: 72,
+            auto_acknowledge: false,
+            visibility: TicketVisibility::default(),
+        }
+    }
+}
+
+impl TenantSettings for TicketSettings {
+    const CATEGORY: &'static str = "tickets";
+}
+
+impl TicketSettings {
+    /// Whether a ticket closed at `closed_at` is still inside the auto-reopen window at `now`
+    pub fn within_reopen_window(&self, closed_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        self.auto_reopen_window_hours > 0
+            && now >= closed_at
+            && now - closed_at <= chrono::Duration::hours(self.auto_reopen_window_hours)
+    }
+}
+
+// ============================================================================
+// AUTO-ACKNOWLEDGEMENT
+// ============================================================================
+
+/// The "we got your request" email for a new ticket. It is posted as a public
+/// note that doesn't count as the first response, so the SLA clock keeps
+/// running until a person replies.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TicketAcknowledgement {
+    /// Render context for the `ticket.acknowledged` template
+    pub context: serde_json::Value,
+}
+
+impl TicketAcknowledgement {
+    /// The acknowledgement for a ticket just created, if it gets one: only
+    /// tickets from the portal or email, and only where the queue, or failing
+    /// that the tenant, has it turned on. The expected response time is the
+    /// ticket's first-response target, in the tenant's time zone.
+    pub fn for_new_ticket(
+        ticket: &Ticket,
+        settings: &TicketSettings,
+        queue: &TicketQueue,
+        timezone: TenantTimezone,
+        ticket_url: &str,
+    ) -> Option<Self> {
+        let customer_opened = matches!(ticket.source, TicketSource::Portal | TicketSource::Email);
+        if !customer_opened || !queue.auto_acknowledge.unwrap_or(settings.auto_acknowledge) {
+            return None;
+        }
+
+        let response_due = ticket
+            .first_response_due
+            .map(|due| format!("{} {}", timezone.local_datetime(due).format("%Y-%m-%d %H:%M"), timezone.name()));
+
+        Some(Self {
+            context: serde_json::json!({
+                "ticket": {
+                    "number": ticket.ticket_number,
+                    "title": ticket.title,
+                    "response_due": response_due,
+                    "url": ticket_url,
+                },
+            }),
+        })
+    }
+}
+
+// ============================================================================
+// DUPLICATE DETECTION
+// ============================================================================
+
+/// An open ticket checked against a new one for duplication
+#[derive(Debug, Clone)]
+pub struct RecentTicket {
+    pub id: Uuid,
+    pub ticket_number: String,
+    pub title: String,
     pub contact_id: Option<Uuid>,
-    pub assigned_to_id: Option<Uuid>,
-    pub team_id: Option<Uuid>,
-    pub is_unassigned: Option<bool>,
-    pub is_overdue: Option<bool>,
-    pub is_open: Option<bool>,
-    pub billing_status: Option<BillingStatus>,
-    pub created_from: Option<DateTime<Utc>>,
-    pub created_to: Option<DateTime<Utc>>,
-    pub tags: Option<String>,
+    pub asset_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An open ticket that looks like the one about to be created
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DuplicateCandidate {
+    pub ticket_id: Uuid,
+    pub ticket_number: String,
+    pub title: String,
+    pub created_at: DateTime<Utc>,
+    /// Trigram similarity of the titles, 0 to 1
+    pub similarity: f64,
+}
+
+impl CreateTicketRequest {
+    /// Recent tickets from the same contact or for the same asset whose
+    /// titles are at least as similar as the tenant's threshold, most similar
+    /// first
+    pub fn possible_duplicates(
+        &self,
+        recent: &[RecentTicket],
+        settings: &TicketSettings,
+        now: DateTime<Utc>,
+    ) -> Vec<DuplicateCandidate> {
+        if settings.duplicate_window_hours <= 0 {
+            return Vec::new();
+        }
+        let since = now - chrono::Duration::hours(settings.duplicate_window_hours);
+        let same = |mine: Option<Uuid>, theirs: Option<Uuid>| mine.is_some() && mine == theirs;
+
+        let mut candidates: Vec<DuplicateCandidate> = recent
+            .iter()
+            .filter(|t| t.created_at >= since)
+            .filter(|t| same(self.contact_id, t.contact_id) || same(self.asset_id, t.asset_id))
+            .map(|t| DuplicateCandidate {
+                ticket_id: t.id,
+                ticket_number: t.ticket_number.clone(),
+                title: t.title.clone(),
+                created_at: t.created_at,
+                similarity: title_similarity(&self.title, &t.title),
+            })
+            .filter(|c| c.similarity >= settings.duplicate_similarity)
+            .collect();
+        candidates.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+        candidates
+    }
+}
+
+/// Trigram similarity as `pg_trgm` computes it: shared trigrams over all
+/// distinct trigrams of the lowercased words, each padded with two spaces
+/// in front and one behind
+pub fn title_similarity(a: &str, b: &str) -> f64 {
+    let a = trigrams(a);
+    let b = trigrams(b);
+    let shared = a.intersection(&b).count();
+    let all = a.len() + b.len() - shared;
+    if all == 0 {
+        0.0
+    } else {
+        shared as f64 / all as f64
+    }
+}
+
+fn trigrams(text: &str) -> std::collections::HashSet<[char; 3]> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .flat_map(|word| {
+            let padded: Vec<char> = "  ".chars().chain(word.to_lowercase().chars()).chain(" ".chars()).collect();
+            padded.windows(3).map(|w| [w[0], w[1], w[2]]).collect::<Vec<_>>()
+        })
+        .collect()
 }
 
 // ============================================================================
-// TICKET ACTIVITY
+// CSAT SURVEYS
 // ============================================================================
 
+/// Thumbs-up / thumbs-down survey answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CsatRating {
+    Positive,
+    Negative,
+}
+
+impl CsatRating {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "positive" => Some(Self::Positive),
+            "negative" => Some(Self::Negative),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Positive => "positive",
+            Self::Negative => "negative",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type", rename_all = "snake_case")]
-pub enum TicketActivity {
-    Created {
-        user_id: Uuid,
-        user_name: String,
-        timestamp: DateTime<Utc>,
-    },
-    StatusChanged {
-        user_id: Uuid,
-        user_name: String,
-        from_status: String,
-        to_status: String,
-        timestamp: DateTime<Utc>,
-    },
-    Assigned {
-        user_id: Uuid,
-        user_name: String,
-        assigned_to_name: String,
-        timestamp: DateTime<Utc>,
-    },
-    NoteAdded {
-        user_id: Uuid,
-        user_name: String,
-        note_type: NoteType,
-        timestamp: DateTime<Utc>,
-    },
-    PriorityChanged {
-        user_id: Uuid,
-        user_name: String,
-        from_priority: String,
-        to_priority: String,
-        timestamp: DateTime<Utc>,
-    },
-    TimeLogged {
-        user_id: Uuid,
-        user_name: String,
-        duration_minutes: i32,
-        timestamp: DateTime<Utc>,
-    },
+pub struct CsatSurvey {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub ticket_id: Uuid,
+    pub contact_id: Option<Uuid>,
+    pub technician_id: Option<Uuid>,
+    #[serde(skip_serializing)]
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+    pub rating: Option<CsatRating>,
+    pub comment: Option<String>,
+    pub responded_at: Option<DateTime<Utc>>,
+    pub sent_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl CsatSurvey {
+    /// Surveys accept exactly one response before they expire
+    pub fn ensure_open(&self, now: DateTime<Utc>) -> Result<(), AppError> {
+        if self.responded_at.is_some() {
+            return Err(AppError::Conflict("Survey response".to_string()));
+        }
+        if now >= self.expires_at {
+            return Err(AppError::BadRequest("This survey link has expired".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Public survey response
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CsatResponseRequest {
+    pub rating: CsatRating,
+    #[validate(length(max = 2000))]
+    pub comment: Option<String>,
+}
+
+/// Query of the survey link in the email: the rating the customer clicked
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CsatLinkParams {
+    pub rating: Option<CsatRating>,
 }
 
 // ============================================================================
-// AUTOMATION TYPES
+// TICKET LINKS
 // ============================================================================
 
+/// Stored relationship between two tickets, read from `from_ticket_id` to `to_ticket_id`
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
-pub enum AutomationTrigger {
-    OnCreate,
-    OnUpdate,
-    OnSchedule,
-    OnSlaBreach,
-    OnSlaWarning,
-    OnAging,
+pub enum TicketLinkType {
+    RelatedTo,
+    DuplicateOf,
+    Blocks,
 }
 
-impl AutomationTrigger {
-    pub fn from_str(s: &str) -> Option<Self> {
-        match s {
-            "on_create" => Some(Self::OnCreate),
-            "on_update" => Some(Self::OnUpdate),
-            "on_schedule" => Some(Self::OnSchedule),
-            "on_sla_breach" => Some(Self::OnSlaBreach),
-            "on_sla_warning" => Some(Self::OnSlaWarning),
-            "on_aging" => Some(Self::OnAging),
-            _ => None,
+impl TicketLinkType {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "related_to" => Some(Self::RelatedTo),
+            "duplicate_of" => Some(Self::DuplicateOf),
+            "blocks" => Some(Self::Blocks),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::RelatedTo => "related_to",
+            Self::DuplicateOf => "duplicate_of",
+            Self::Blocks => "blocks",
+        }
+    }
+
+    /// Whether the link reads the same from both ends
+    pub fn is_symmetric(&self) -> bool {
+        matches!(self, Self::RelatedTo)
+    }
+}
+
+/// How a linked ticket relates to the ticket being viewed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TicketRelation {
+    RelatedTo,
+    /// The viewed ticket duplicates the linked one
+    DuplicateOf,
+    /// The linked ticket duplicates the viewed one
+    DuplicatedBy,
+    /// The viewed ticket blocks the linked one
+    Blocks,
+    /// The linked ticket blocks the viewed one
+    BlockedBy,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketLink {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub from_ticket_id: Uuid,
+    pub to_ticket_id: Uuid,
+    pub link_type: TicketLinkType,
+    pub created_by_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TicketLink {
+    /// Endpoints as stored. Rejects self-links, and orders symmetric links so
+    /// each pair is stored once whichever ticket the agent linked from.
+    pub fn endpoints(from: Uuid, to: Uuid, link_type: TicketLinkType) -> Result<(Uuid, Uuid), AppError> {
+        if from == to {
+            return Err(AppError::validation_field("to_ticket_id", "A ticket cannot be linked to itself"));
+        }
+
+        if link_type.is_symmetric() && to < from {
+            Ok((to, from))
+        } else {
+            Ok((from, to))
+        }
+    }
+
+    /// The other ticket and how it relates to `ticket_id`, or `None` if the
+    /// link does not involve `ticket_id`
+    pub fn relation_for(&self, ticket_id: Uuid) -> Option<(Uuid, TicketRelation)> {
+        let outgoing = if self.from_ticket_id == ticket_id {
+            true
+        } else if self.to_ticket_id == ticket_id {
+            false
+        } else {
+            return None;
+        };
+        let other = if outgoing { self.to_ticket_id } else { self.from_ticket_id };
+
+        let relation = match (self.link_type, outgoing) {
+            (TicketLinkType::RelatedTo, _) => TicketRelation::RelatedTo,
+            (TicketLinkType::DuplicateOf, true) => TicketRelation::DuplicateOf,
+            (TicketLinkType::DuplicateOf, false) => TicketRelation::DuplicatedBy,
+            (TicketLinkType::Blocks, true) => TicketRelation::Blocks,
+            (TicketLinkType::Blocks, false) => TicketRelation::BlockedBy,
+        };
+
+        Some((other, relation))
+    }
+}
+
+/// Link ticket request; the ticket in the path is the `from` end
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct LinkTicketRequest {
+    pub to_ticket_id: Uuid,
+    pub link_type: TicketLinkType,
+}
+
+/// Link ticket response
+#[derive(Debug, Clone, Serialize)]
+pub struct TicketLinkResponse {
+    #[serde(flatten)]
+    pub link: TicketLink,
+    /// Set for "duplicate of" links so the UI can offer to merge the tickets
+    pub merge_suggested: bool,
+}
+
+/// A ticket linked to the one being viewed
+#[derive(Debug, Clone, Serialize)]
+pub struct RelatedTicket {
+    pub link_id: Uuid,
+    pub ticket_id: Uuid,
+    pub ticket_number: String,
+    pub title: String,
+    pub relation: TicketRelation,
+    pub linked_at: DateTime<Utc>,
+}
+
+// ============================================================================
+// TICKET MERGE
+// ============================================================================
+
+/// Merge other tickets into the one in the path
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct MergeTicketsRequest {
+    #[validate(length(min = 1, max = 50))]
+    pub source_ticket_ids: Vec<Uuid>,
+}
+
+/// A merged field's value and the ticket it was taken from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MergedField<T> {
+    pub value: T,
+    pub from_ticket_id: Uuid,
+}
+
+/// Field values of a merged ticket, each with its source. Also the audit
+/// entry's `new_values`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TicketMerge {
+    pub target_id: Uuid,
+    pub source_ids: Vec<Uuid>,
+    pub created_at: MergedField<DateTime<Utc>>,
+    pub priority_id: MergedField<Uuid>,
+    pub tags: Vec<String>,
+    pub rule_tags: Vec<String>,
+    /// Tickets that carried each tag
+    pub tag_sources: BTreeMap<String, Vec<Uuid>>,
+}
+
+impl TicketMerge {
+    /// Take the earliest `created_at`, the highest priority (lowest
+    /// `sort_order` in `priority_ranks`; unknown ranks last) and the union
+    /// of the tags. Ties go to the target, then the lowest ticket id, so the
+    /// result doesn't depend on the order the sources were listed in.
+    pub fn plan(target: &Ticket, sources: &[Ticket], priority_ranks: &HashMap<Uuid, i32>) -> Self {
+        let mut tickets: Vec<&Ticket> = sources.iter().filter(|t| t.id != target.id).collect();
+        tickets.sort_by_key(|t| t.id);
+        tickets.insert(0, target);
+
+        let earliest = tickets
+            .iter()
+            .enumerate()
+            .min_by_key(|(i, t)| (t.created_at, *i))
+            .map(|(_, t)| *t)
+            .unwrap_or(target);
+        let highest = tickets
+            .iter()
+            .enumerate()
+            .min_by_key(|(i, t)| (priority_ranks.get(&t.priority_id).copied().unwrap_or(i32::MAX), *i))
+            .map(|(_, t)| *t)
+            .unwrap_or(target);
+
+        // The target's tags keep their order; tags new to it follow, sorted
+        let mut tag_sources: BTreeMap<String, Vec<Uuid>> = BTreeMap::new();
+        for ticket in &tickets {
+            for tag in &ticket.tags {
+                let carriers = tag_sources.entry(tag.clone()).or_default();
+                if !carriers.contains(&ticket.id) {
+                    carriers.push(ticket.id);
+                }
+            }
+        }
+        let mut tags = target.tags.clone();
+        tags.extend(tag_sources.keys().filter(|tag| !target.tags.contains(tag)).cloned());
+
+        let mut rule_tags = target.rule_tags.clone();
+        for ticket in &tickets[1..] {
+            for tag in &ticket.rule_tags {
+                if !rule_tags.contains(tag) {
+                    rule_tags.push(tag.clone());
+                }
+            }
+        }
+
+        Self {
+            target_id: target.id,
+            source_ids: tickets[1..].iter().map(|t| t.id).collect(),
+            created_at: MergedField {
+                value: earliest.created_at,
+                from_ticket_id: earliest.id,
+            },
+            priority_id: MergedField {
+                value: highest.priority_id,
+                from_ticket_id: highest.id,
+            },
+            tags,
+            rule_tags,
+            tag_sources,
+        }
+    }
+}
+
+// ============================================================================
+// TICKET NOTES
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketNote {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub ticket_id: Uuid,
+    pub note_type: NoteType,
+    pub content: String,
+    pub content_html: Option<String>,
+    pub is_email_sent: bool,
+    pub email_sent_at: Option<DateTime<Utc>>,
+    /// False for automatic public notes that leave the first-response SLA open
+    pub counts_as_first_response: bool,
+    /// `None` for notes the system posted
+    pub created_by_id: Option<Uuid>,
+    pub created_by_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Create note request
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CreateNoteRequest {
+    #[serde(default)]
+    pub note_type: NoteType,
+    #[validate(length(min = 1))]
+    pub content: String,
+    /// Send email notification to contact
+    #[serde(default)]
+    pub send_email: bool,
+}
+
+/// Note response
+#[derive(Debug, Clone, Serialize)]
+pub struct TicketNoteResponse {
+    pub id: Uuid,
+    pub note_type: NoteType,
+    pub content: String,
+    pub is_email_sent: bool,
+    pub created_by_id: Option<Uuid>,
+    pub created_by_name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+// ============================================================================
+// EMAIL THREADING
+// ============================================================================
+
+/// Most references kept on an outbound message; the thread root is always retained
+const MAX_REFERENCES: usize = 10;
+
+/// Canonical `<id@domain>` form of a message id, or `None` if it is empty
+pub fn normalize_message_id(raw: &str) -> Option<String> {
+    let inner = raw.trim().trim_start_matches('<').trim_end_matches('>').trim();
+    if inner.is_empty() {
+        None
+    } else {
+        Some(format!("<{}>", inner))
+    }
+}
+
+/// Message-ID for an outbound email about a note
+pub fn note_message_id(note_id: Uuid, domain: &str) -> String {
+    format!("<note.{}@{}>", note_id, domain)
+}
+
+/// Threading headers for an outbound note, given the earlier message ids on the
+/// ticket in order (the inbound message that opened it, then prior note emails)
+pub fn reply_thread_headers(
+    message_id: String,
+    earlier: &[String],
+) -> crate::modules::notifications::EmailThreadHeaders {
+    let references = match earlier.len() {
+        n if n > MAX_REFERENCES => std::iter::once(earlier[0].clone())
+            .chain(earlier[n - (MAX_REFERENCES - 1)..].iter().cloned())
+            .collect(),
+        _ => earlier.to_vec(),
+    };
+
+    crate::modules::notifications::EmailThreadHeaders {
+        message_id,
+        in_reply_to: earlier.last().cloned(),
+        references,
+    }
+}
+
+/// Threading information from an inbound email
+#[derive(Debug, Clone, Default)]
+pub struct InboundThreadHeaders {
+    pub message_id: Option<String>,
+    pub in_reply_to: Option<String>,
+    pub references: Vec<String>,
+    pub subject: String,
+}
+
+impl InboundThreadHeaders {
+    /// Parse raw header values; `References` is a whitespace-separated list
+    pub fn from_raw(
+        message_id: Option<&str>,
+        in_reply_to: Option<&str>,
+        references: Option<&str>,
+        subject: &str,
+    ) -> Self {
+        Self {
+            message_id: message_id.and_then(normalize_message_id),
+            in_reply_to: in_reply_to.and_then(normalize_message_id),
+            references: references
+                .unwrap_or_default()
+                .split_whitespace()
+                .filter_map(normalize_message_id)
+                .collect(),
+            subject: subject.to_string(),
+        }
+    }
+
+    /// Message ids to look up, most specific first: `In-Reply-To`, then the
+    /// `References` chain from newest to oldest
+    pub fn candidates(&self) -> Vec<String> {
+        let mut candidates: Vec<String> = Vec::new();
+        for id in self.in_reply_to.iter().chain(self.references.iter().rev()) {
+            if !candidates.contains(id) {
+                candidates.push(id.clone());
+            }
+        }
+        candidates
+    }
+
+    /// Ticket number from a `[T000123]` subject tag
+    pub fn subject_ticket_number(&self) -> Option<String> {
+        let start = self.subject.find("[T")?;
+        let rest = &self.subject[start + 1..];
+        let end = rest.find(']')?;
+        let number = &rest[..end];
+
+        (number.len() > 1 && number[1..].chars().all(|c| c.is_ascii_digit()))
+            .then(|| number.to_string())
+    }
+
+    /// Resolve the ticket from known message ids (stored note and ticket ids).
+    /// Headers win over the subject so an edited subject does not break threading.
+    pub fn resolve_ticket(&self, known: &std::collections::HashMap<String, Uuid>) -> Option<Uuid> {
+        self.candidates()
+            .iter()
+            .find_map(|id| known.get(id).copied())
+    }
+}
+
+// ============================================================================
+// INBOUND EMAIL
+// ============================================================================
+
+/// Parsed inbound email handed to the processor by the mailbox poller
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct InboundEmail {
+    #[validate(length(min = 3, max = 320))]
+    pub from: String,
+    #[serde(default)]
+    pub to: Vec<String>,
+    #[serde(default)]
+    pub cc: Vec<String>,
+    #[serde(default)]
+    pub subject: String,
+    #[serde(default)]
+    pub body_text: String,
+    pub message_id: Option<String>,
+    pub in_reply_to: Option<String>,
+    pub references: Option<String>,
+}
+
+impl InboundEmail {
+    /// Threading headers for matching the email to an existing ticket
+    pub fn thread_headers(&self) -> InboundThreadHeaders {
+        InboundThreadHeaders::from_raw(
+            self.message_id.as_deref(),
+            self.in_reply_to.as_deref(),
+            self.references.as_deref(),
+            &self.subject,
+        )
+    }
+
+    /// Queue for a new ticket: the first `To` recipient with a mapped address,
+    /// then `Cc`, otherwise the tenant's default queue
+    pub fn queue_for(&self, addresses: &[QueueEmailAddress], default_queue_id: Uuid) -> Uuid {
+        self.to
+            .iter()
+            .chain(self.cc.iter())
+            .filter_map(|recipient| normalize_email_address(recipient))
+            .find_map(|recipient| {
+                addresses
+                    .iter()
+                    .find(|address| address.email_address == recipient)
+                    .map(|address| address.queue_id)
+            })
+            .unwrap_or(default_queue_id)
+    }
+}
+
+/// What the processor did with an inbound email
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum InboundEmailOutcome {
+    /// Opened a new ticket
+    Created { ticket_id: Uuid, queue_id: Uuid },
+    /// Added a customer note to the ticket it replies to
+    Replied { ticket_id: Uuid, note_id: Uuid },
+    /// Not turned into a ticket, e.g. an unknown sender
+    Ignored { reason: String },
+}
+
+/// Bare lowercased address from a header value such as `"Brand A" <Support@Brand-A.com>`
+pub fn normalize_email_address(raw: &str) -> Option<String> {
+    let address = match (raw.rfind('<'), raw.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &raw[start + 1..end],
+        _ => raw,
+    };
+    let address = address.trim().to_lowercase();
+
+    address.contains('@').then_some(address)
+}
+
+// ============================================================================
+// QUEUE EMAIL ADDRESSES
+// ============================================================================
+
+/// Inbound address routed to a queue; also the From address for the queue's replies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueEmailAddress {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub queue_id: Uuid,
+    pub email_address: String,
+    pub from_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl QueueEmailAddress {
+    /// Value for the `From` header of outbound mail sent on the queue's behalf
+    pub fn from_header(&self) -> String {
+        match self.from_name {
+            Some(ref name) => format!("{} <{}>", name, self.email_address),
+            None => self.email_address.clone(),
+        }
+    }
+}
+
+/// Map an inbound address to a queue
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CreateQueueEmailAddressRequest {
+    pub queue_id: Uuid,
+    #[validate(email(message = "Invalid email address"))]
+    pub email_address: String,
+    #[validate(length(min = 1, max = 255))]
+    pub from_name: Option<String>,
+}
+
+// ============================================================================
+// TAG RULES
+// ============================================================================
+
+/// How a tag rule's pattern is matched against a ticket's title and description
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TagRuleMatch {
+    /// Whole word, case-insensitive
+    #[default]
+    Keyword,
+    /// Case-insensitive regular expression
+    Regex,
+}
+
+impl TagRuleMatch {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "keyword" => Some(Self::Keyword),
+            "regex" => Some(Self::Regex),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Keyword => "keyword",
+            Self::Regex => "regex",
+        }
+    }
+
+    fn compile(&self, pattern: &str) -> Result<regex::Regex, regex::Error> {
+        let source = match self {
+            Self::Keyword => format!(r"\b{}\b", regex::escape(pattern.trim())),
+            Self::Regex => pattern.to_string(),
+        };
+
+        regex::RegexBuilder::new(&source)
+            .case_insensitive(true)
+            .size_limit(1 << 20)
+            .build()
+    }
+}
+
+/// Adds a tag to tickets whose title or description matches a pattern
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagRule {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub match_type: TagRuleMatch,
+    pub pattern: String,
+    pub tag: String,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl TagRule {
+    /// Whether the rule applies to a ticket with this text. A pattern that no
+    /// longer compiles never matches.
+    pub fn matches(&self, title: &str, description: Option<&str>) -> bool {
+        match self.match_type.compile(&self.pattern) {
+            Ok(pattern) => pattern.is_match(title) || description.is_some_and(|d| pattern.is_match(d)),
+            Err(_) => false,
+        }
+    }
+}
+
+/// Create a tag rule
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CreateTagRuleRequest {
+    #[serde(default)]
+    pub match_type: TagRuleMatch,
+    #[validate(length(min = 1, max = 255))]
+    pub pattern: String,
+    #[validate(length(min = 1, max = 50))]
+    pub tag: String,
+}
+
+impl CreateTagRuleRequest {
+    /// Reject regex patterns that don't compile
+    pub fn check(&self) -> Result<(), AppError> {
+        self.match_type
+            .compile(&self.pattern)
+            .map(|_| ())
+            .map_err(|_| AppError::validation_field("pattern", "Invalid regular expression"))
+    }
+}
+
+/// A ticket's tags with the ones applied by rules tracked separately.
+///
+/// Rule tags are recomputed from the text on every save: they drop off once the
+/// text stops matching, and an edit that leaves them out (or echoes them back)
+/// neither removes them nor pins them as manual tags.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TicketTags {
+    pub tags: Vec<String>,
+    pub rule_tags: Vec<String>,
+}
+
+impl TicketTags {
+    pub fn resolve(manual: &[String], rules: &[TagRule], title: &str, description: Option<&str>) -> Self {
+        let mut rule_tags: Vec<String> = Vec::new();
+        for rule in rules.iter().filter(|rule| rule.is_active && rule.matches(title, description)) {
+            if !rule_tags.contains(&rule.tag) {
+                rule_tags.push(rule.tag.clone());
+            }
+        }
+
+        let mut tags: Vec<String> = Vec::new();
+        for tag in manual.iter().filter(|tag| !rule_tags.contains(tag)).chain(&rule_tags) {
+            if !tags.contains(tag) {
+                tags.push(tag.clone());
+            }
+        }
+
+        Self { tags, rule_tags }
+    }
+
+    /// Tags on a stored ticket that were set by hand
+    pub fn manual(ticket: &Ticket) -> Vec<String> {
+        ticket
+            .tags
+            .iter()
+            .filter(|tag| !ticket.rule_tags.contains(tag))
+            .cloned()
+            .collect()
+    }
+}
+
+// ============================================================================
+// TICKET ATTACHMENTS
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketAttachment {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub ticket_id: Uuid,
+    pub note_id: Option<Uuid>,
+    pub file_name: String,
+    pub file_size: i64,
+    pub mime_type: String,
+    pub storage_path: String,
+    pub uploaded_by_id: Uuid,
+    pub scan_status: AttachmentScanStatus,
+    pub scan_detail: Option<String>,
+    pub scanned_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TicketAttachment {
+    /// Quarantined attachments are kept for review but can't be downloaded
+    pub fn ensure_downloadable(&self) -> Result<(), AppError> {
+        if self.scan_status.is_quarantined() {
+            return Err(AppError::Forbidden(format!(
+                "{} is quarantined by the virus scanner",
+                self.file_name
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TicketAttachmentResponse {
+    pub id: Uuid,
+    pub file_name: String,
+    pub file_size: i64,
+    pub mime_type: String,
+    pub uploaded_by_name: String,
+    pub scan_status: AttachmentScanStatus,
+    pub created_at: DateTime<Utc>,
+}
+
+/// What the virus scanner made of an attachment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanVerdict {
+    Clean,
+    /// Names the signature that matched
+    Infected(String),
+    /// No scanner is configured
+    NotScanned,
+}
+
+/// Scan result recorded on an attachment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AttachmentScanStatus {
+    #[default]
+    NotScanned,
+    Clean,
+    Infected,
+    /// The scanner errored; treated as infected until rescanned
+    Failed,
+}
+
+impl AttachmentScanStatus {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "not_scanned" => Some(Self::NotScanned),
+            "clean" => Some(Self::Clean),
+            "infected" => Some(Self::Infected),
+            "failed" => Some(Self::Failed),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NotScanned => "not_scanned",
+            Self::Clean => "clean",
+            Self::Infected => "infected",
+            Self::Failed => "failed",
+        }
+    }
+
+    pub fn is_quarantined(&self) -> bool {
+        matches!(self, Self::Infected | Self::Failed)
+    }
+
+    /// Status and detail to store for a scan outcome
+    pub fn from_scan(result: &Result<ScanVerdict, AppError>) -> (Self, Option<String>) {
+        match result {
+            Ok(ScanVerdict::Clean) => (Self::Clean, None),
+            Ok(ScanVerdict::Infected(signature)) => (Self::Infected, Some(signature.clone())),
+            Ok(ScanVerdict::NotScanned) => (Self::NotScanned, None),
+            Err(e) => (Self::Failed, Some(e.to_string())),
+        }
+    }
+}
+
+// ============================================================================
+// TICKET DOCUMENT
+// ============================================================================
+
+/// Who a printed ticket is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentAudience {
+    /// Public and resolution notes only, without internal time notes
+    #[default]
+    Customer,
+    /// Everything on the ticket
+    Internal,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TicketDocumentParams {
+    #[serde(default)]
+    pub audience: DocumentAudience,
+}
+
+/// Ticket details as printed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketDocumentDetails {
+    pub ticket_number: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub status: String,
+    pub priority: String,
+    pub company_name: String,
+    pub contact_name: Option<String>,
+    pub assigned_to_name: Option<String>,
+    pub resolution_code: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub closed_at: Option<DateTime<Utc>>,
+}
+
+/// Time logged against the ticket, as printed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketDocumentTimeEntry {
+    pub date: chrono::NaiveDate,
+    pub user_name: String,
+    pub duration_minutes: i32,
+    pub is_billable: bool,
+    pub notes: Option<String>,
+    pub internal_notes: Option<String>,
+}
+
+/// Everything the PDF renderer lays out for one ticket, under the tenant's
+/// invoice header
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketDocument {
+    pub header: InvoiceDocumentHeader,
+    pub audience: DocumentAudience,
+    pub ticket: TicketDocumentDetails,
+    /// Oldest first, as the ticket unfolded
+    pub notes: Vec<TicketNote>,
+    pub time_entries: Vec<TicketDocumentTimeEntry>,
+    pub total_minutes: i64,
+    pub footer: Option<String>,
+}
+
+impl TicketDocument {
+    /// Lay out the ticket for `audience`. Customer copies leave out internal
+    /// and time-entry notes and the internal notes on time entries.
+    pub fn new(
+        ticket: TicketDocumentDetails,
+        mut notes: Vec<TicketNote>,
+        mut time_entries: Vec<TicketDocumentTimeEntry>,
+        audience: DocumentAudience,
+        branding: &ResolvedBranding,
+    ) -> Self {
+        if audience == DocumentAudience::Customer {
+            notes.retain(|note| note.note_type.is_customer_visible());
+            for entry in &mut time_entries {
+                entry.internal_notes = None;
+            }
+        }
+        notes.sort_by_key(|note| note.created_at);
+
+        Self {
+            header: InvoiceDocumentHeader::new(branding),
+            audience,
+            ticket,
+            notes,
+            total_minutes: time_entries.iter().map(|entry| entry.duration_minutes as i64).sum(),
+            time_entries,
+            footer: branding.email_footer.clone(),
+        }
+    }
+}
+
+// ============================================================================
+// SHARE LINKS
+// ============================================================================
+
+/// A tokenized, read-only link to a ticket's public timeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketShareLink {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub ticket_id: Uuid,
+    #[serde(skip_serializing)]
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+    pub created_by_id: Option<Uuid>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TicketShareLink {
+    /// Expired and revoked links look the same as ones that never existed
+    pub fn ensure_active(&self, now: DateTime<Utc>) -> Result<(), AppError> {
+        if self.revoked_at.is_some() || now >= self.expires_at {
+            return Err(AppError::NotFound("Shared ticket".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Create a share link valid for `ttl_hours`, up to 90 days
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CreateShareLinkRequest {
+    #[validate(range(min = 1, max = 2160))]
+    pub ttl_hours: i64,
+}
+
+/// A new share link with the URL to hand out; the token isn't shown again
+#[derive(Debug, Clone, Serialize)]
+pub struct CreatedShareLink {
+    #[serde(flatten)]
+    pub link: TicketShareLink,
+    pub url: String,
+}
+
+/// What happened on a shared ticket, oldest first
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TimelineEntry {
+    Note {
+        at: DateTime<Utc>,
+        author: Option<String>,
+        content: String,
+    },
+    Status {
+        at: DateTime<Utc>,
+        status: String,
+    },
+}
+
+impl TimelineEntry {
+    fn at(&self) -> DateTime<Utc> {
+        match self {
+            Self::Note { at, .. } | Self::Status { at, .. } => *at,
+        }
+    }
+}
+
+/// A ticket's history as the customer may see it
+#[derive(Debug, Clone, Serialize)]
+pub struct TicketTimeline {
+    pub ticket_number: String,
+    pub title: String,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub expires_at: DateTime<Utc>,
+    pub entries: Vec<TimelineEntry>,
+}
+
+impl TicketTimeline {
+    /// The public timeline behind a share link: status changes and the notes
+    /// a customer can see. Internal and time-entry notes are left out.
+    pub fn public(
+        ticket: &TicketDocumentDetails,
+        notes: &[TicketNote],
+        history: &[StatusHistoryEntry],
+        link: &TicketShareLink,
+    ) -> Self {
+        let mut entries: Vec<TimelineEntry> = notes
+            .iter()
+            .filter(|note| note.note_type.is_customer_visible())
+            .map(|note| TimelineEntry::Note {
+                at: note.created_at,
+                author: note.created_by_name.clone(),
+                content: note.content.clone(),
+            })
+            .chain(history.iter().map(|stay| TimelineEntry::Status {
+                at: stay.entered_at,
+                status: stay.status_name.clone(),
+            }))
+            .collect();
+        entries.sort_by_key(TimelineEntry::at);
+
+        Self {
+            ticket_number: ticket.ticket_number.clone(),
+            title: ticket.title.clone(),
+            status: ticket.status.clone(),
+            created_at: ticket.created_at,
+            closed_at: ticket.closed_at,
+            expires_at: link.expires_at,
+            entries,
+        }
+    }
+}
+
+// ============================================================================
+// TICKET FILTERS
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct TicketFilter {
+    pub q: Option<String>,
+    pub status_id: Option<Uuid>,
+    pub priority_id: Option<Uuid>,
+    pub type_id: Option<Uuid>,
+    pub queue_id: Option<Uuid>,
+    pub company_id: Option<Uuid>,
+    pub contact_id: Option<Uuid>,
+    pub assigned_to_id: Option<Uuid>,
+    pub team_id: Option<Uuid>,
+    pub is_unassigned: Option<bool>,
+    pub is_overdue: Option<bool>,
+    /// SLA due on the current day in the tenant's time zone
+    pub is_due_today: Option<bool>,
+    pub is_open: Option<bool>,
+    pub billing_status: Option<BillingStatus>,
+    pub created_from: Option<DateTime<Utc>>,
+    pub created_to: Option<DateTime<Utc>>,
+    pub tags: Option<String>,
+    /// List snoozed tickets alongside the rest
+    pub include_snoozed: Option<bool>,
+    /// List only snoozed tickets
+    pub is_snoozed: Option<bool>,
+    /// Set from the user's teams, never from the query string
+    #[serde(skip)]
+    pub scope: Option<TicketScope>,
+}
+
+/// Which tickets technicians see in their lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TicketVisibility {
+    /// Everyone sees every ticket
+    #[default]
+    All,
+    /// Technicians see their own teams' tickets and queues
+    Team,
+}
+
+impl TicketVisibility {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "all" => Some(Self::All),
+            "team" => Some(Self::Team),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::All => "all",
+            Self::Team => "team",
+        }
+    }
+}
+
+/// The tickets a technician may list under team visibility: those assigned
+/// to them, those on one of their teams and, for tickets without a team,
+/// those in a queue one of their teams works. Tickets no team owns stay
+/// visible to everyone so nothing goes unseen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TicketScope {
+    pub user_id: Uuid,
+    pub team_ids: Vec<Uuid>,
+}
+
+impl TicketScope {
+    /// The scope for a user, or `None` when they see every ticket. Managers
+    /// and admins see all teams, as does everyone under `All`.
+    pub fn for_user(visibility: TicketVisibility, role: UserRole, user_id: Uuid, team_ids: Vec<Uuid>) -> Option<Self> {
+        match (visibility, role) {
+            (TicketVisibility::Team, UserRole::Technician) => Some(Self { user_id, team_ids }),
+            _ => None,
+        }
+    }
+
+    /// Condition over `tickets t`; `$param_idx` binds the user and the next
+    /// parameter their team ids
+    pub fn sql(param_idx: usize) -> String {
+        format!(
+            "(t.assigned_to_id = ${user} OR t.team_id = ANY(${teams}) OR (t.team_id IS NULL AND NOT EXISTS (\
+             SELECT 1 FROM ticket_queues q WHERE q.id = t.queue_id AND q.assignable_to_team_id IS NOT NULL \
+             AND NOT q.assignable_to_team_id = ANY(${teams}))))",
+            user = param_idx,
+            teams = param_idx + 1
+        )
+    }
+}
+
+// ============================================================================
+// CSV EXPORT
+// ============================================================================
+
+/// Tickets fetched per query while exporting
+pub const EXPORT_BATCH_SIZE: u32 = 100;
+
+/// Most tickets in one export
+pub const MAX_EXPORT_ROWS: usize = 50_000;
+
+const TICKET_CSV_COLUMNS: [&str; 11] = [
+    "Ticket Number",
+    "Title",
+    "Status",
+    "Priority",
+    "Queue",
+    "Company",
+    "Assigned To",
+    "SLA Due",
+    "Created",
+    "Closed",
+    "Tags",
+];
+
+/// Names for the ids on exported tickets
+#[derive(Debug, Clone, Default)]
+pub struct TicketExportNames {
+    pub statuses: HashMap<Uuid, String>,
+    pub priorities: HashMap<Uuid, String>,
+    pub queues: HashMap<Uuid, String>,
+    pub companies: HashMap<Uuid, String>,
+    pub users: HashMap<Uuid, String>,
+}
+
+/// The CSV header line
+pub fn ticket_csv_header() -> String {
+    csv_line(TICKET_CSV_COLUMNS.iter().map(|column| column.to_string()))
+}
+
+/// One ticket as a CSV line, names looked up in `names`
+pub fn ticket_csv_row(ticket: &Ticket, names: &TicketExportNames) -> String {
+    let name = |names: &HashMap<Uuid, String>, id: Option<Uuid>| {
+        id.and_then(|id| names.get(&id).cloned()).unwrap_or_default()
+    };
+    let timestamp = |at: Option<DateTime<Utc>>| at.map(|at| at.to_rfc3339()).unwrap_or_default();
+
+    csv_line([
+        ticket.ticket_number.clone(),
+        ticket.title.clone(),
+        name(&names.statuses, Some(ticket.status_id)),
+        name(&names.priorities, Some(ticket.priority_id)),
+        name(&names.queues, Some(ticket.queue_id)),
+        name(&names.companies, Some(ticket.company_id)),
+        name(&names.users, ticket.assigned_to_id),
+        timestamp(ticket.sla_due_date),
+        timestamp(Some(ticket.created_at)),
+        timestamp(ticket.closed_at),
+        ticket.tags.join(", "),
+    ])
+}
+
+/// Fields joined with commas, quoted where they need it, ending in CRLF
+fn csv_line(fields: impl IntoIterator<Item = String>) -> String {
+    let fields: Vec<String> = fields
+        .into_iter()
+        .map(|field| {
+            // A leading formula character would run in a spreadsheet
+            let field = match field.chars().next() {
+                Some('=' | '+' | '-' | '@') => format!("'{}", field),
+                _ => field,
+            };
+            if field.contains([',', '"', '\r', '\n']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field
+            }
+        })
+        .collect();
+    format!("{}\r\n", fields.join(","))
+}
+
+// ============================================================================
+// STATUS HISTORY
+// ============================================================================
+
+/// One stay of a ticket in a status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusHistoryEntry {
+    pub id: Uuid,
+    pub ticket_id: Uuid,
+    pub status_id: Uuid,
+    pub status_name: String,
+    /// `None` for automation and other system changes
+    pub changed_by_id: Option<Uuid>,
+    pub entered_at: DateTime<Utc>,
+    /// `None` while the ticket is still in this status
+    pub left_at: Option<DateTime<Utc>>,
+}
+
+impl StatusHistoryEntry {
+    /// Time spent in the status, counting an open stay up to `now`
+    pub fn duration(&self, now: DateTime<Utc>) -> chrono::Duration {
+        (self.left_at.unwrap_or(now) - self.entered_at).max(chrono::Duration::zero())
+    }
+}
+
+/// Total time a ticket has spent in one status
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StatusDuration {
+    pub status_id: Uuid,
+    pub status_name: String,
+    pub seconds: i64,
+    /// Number of separate stays, e.g. 2 for Open -> Pending -> Open
+    pub visits: u32,
+    /// Whether the ticket is in this status now
+    pub is_current: bool,
+}
+
+impl StatusDuration {
+    /// Sum a ticket's history per status, in the order statuses were first entered
+    pub fn from_history(history: &[StatusHistoryEntry], now: DateTime<Utc>) -> Vec<Self> {
+        let mut entries: Vec<&StatusHistoryEntry> = history.iter().collect();
+        entries.sort_by_key(|entry| entry.entered_at);
+
+        let mut durations: Vec<Self> = Vec::new();
+        for entry in entries {
+            let seconds = entry.duration(now).num_seconds();
+            let is_current = entry.left_at.is_none();
+            match durations.iter_mut().find(|d| d.status_id == entry.status_id) {
+                Some(total) => {
+                    total.seconds += seconds;
+                    total.visits += 1;
+                    total.is_current |= is_current;
+                }
+                None => durations.push(Self {
+                    status_id: entry.status_id,
+                    status_name: entry.status_name.clone(),
+                    seconds,
+                    visits: 1,
+                    is_current,
+                }),
+            }
+        }
+
+        durations
+    }
+}
+
+// ============================================================================
+// TICKET ACTIVITY
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TicketActivity {
+    Created {
+        user_id: Uuid,
+        user_name: String,
+        timestamp: DateTime<Utc>,
+    },
+    StatusChanged {
+        user_id: Uuid,
+        user_name: String,
+        from_status: String,
+        to_status: String,
+        timestamp: DateTime<Utc>,
+    },
+    Assigned {
+        user_id: Uuid,
+        user_name: String,
+        assigned_to_name: String,
+        timestamp: DateTime<Utc>,
+    },
+    NoteAdded {
+        user_id: Uuid,
+        user_name: String,
+        note_type: NoteType,
+        timestamp: DateTime<Utc>,
+    },
+    PriorityChanged {
+        user_id: Uuid,
+        user_name: String,
+        from_priority: String,
+        to_priority: String,
+        timestamp: DateTime<Utc>,
+    },
+    TimeLogged {
+        user_id: Uuid,
+        user_name: String,
+        duration_minutes: i32,
+        timestamp: DateTime<Utc>,
+    },
+}
+
+// ============================================================================
+// AUTOMATION TYPES
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AutomationTrigger {
+    OnCreate,
+    OnUpdate,
+    OnSchedule,
+    OnSlaBreach,
+    OnSlaWarning,
+    OnAging,
+}
+
+impl AutomationTrigger {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "on_create" => Some(Self::OnCreate),
+            "on_update" => Some(Self::OnUpdate),
+            "on_schedule" => Some(Self::OnSchedule),
+            "on_sla_breach" => Some(Self::OnSlaBreach),
+            "on_sla_warning" => Some(Self::OnSlaWarning),
+            "on_aging" => Some(Self::OnAging),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::OnCreate => "on_create",
+            Self::OnUpdate => "on_update",
+            Self::OnSchedule => "on_schedule",
+            Self::OnSlaBreach => "on_sla_breach",
+            Self::OnSlaWarning => "on_sla_warning",
+            Self::OnAging => "on_aging",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutomationRule {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub is_active: bool,
+    pub trigger_type: AutomationTrigger,
+    pub conditions: serde_json::Value,
+    pub actions: serde_json::Value,
+    pub priority: i32,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub run_count: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Automation condition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutomationCondition {
+    pub field: String,
+    pub operator: String,
+    pub value: serde_json::Value,
+}
+
+/// Automation action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutomationAction {
+    pub action_type: String,
+    pub params: serde_json::Value,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_ticket_source_from_str() {
+        assert_eq!(TicketSource::from_str("portal"), Some(TicketSource::Portal));
+        assert_eq!(TicketSource::from_str("email"), Some(TicketSource::Email));
+        assert_eq!(TicketSource::from_str("phone"), Some(TicketSource::Phone));
+        assert_eq!(TicketSource::from_str("api"), Some(TicketSource::Api));
+        assert_eq!(TicketSource::from_str("rmm"), Some(TicketSource::Rmm));
+        assert_eq!(TicketSource::from_str("invalid"), None);
+    }
+
+    #[test]
+    fn test_ticket_source_as_str() {
+        assert_eq!(TicketSource::Portal.as_str(), "portal");
+        assert_eq!(TicketSource::Email.as_str(), "email");
+        assert_eq!(TicketSource::Phone.as_str(), "phone");
+        assert_eq!(TicketSource::Rmm.as_str(), "rmm");
+    }
+
+    #[test]
+    fn test_billing_status_from_str() {
+        assert_eq!(BillingStatus::from_str("not_billed"), Some(BillingStatus::NotBilled));
+        assert_eq!(BillingStatus::from_str("ready_to_bill"), Some(BillingStatus::ReadyToBill));
+        assert_eq!(BillingStatus::from_str("billed"), Some(BillingStatus::Billed));
+        assert_eq!(BillingStatus::from_str("unknown"), None);
+    }
+
+    #[test]
+    fn test_billing_status_as_str() {
+        assert_eq!(BillingStatus::NotBilled.as_str(), "not_billed");
+        assert_eq!(BillingStatus::ReadyToBill.as_str(), "ready_to_bill");
+        assert_eq!(BillingStatus::Billed.as_str(), "billed");
+    }
+
+    #[test]
+    fn test_note_type_from_str() {
+        assert_eq!(NoteType::from_str("internal"), Some(NoteType::Internal));
+        assert_eq!(NoteType::from_str("public"), Some(NoteType::Public));
+        assert_eq!(NoteType::from_str("resolution"), Some(NoteType::Resolution));
+        assert_eq!(NoteType::from_str("time_entry"), Some(NoteType::TimeEntry));
+        assert_eq!(NoteType::from_str("other"), None);
+    }
+
+    #[test]
+    fn test_note_type_as_str() {
+        assert_eq!(NoteType::Internal.as_str(), "internal");
+        assert_eq!(NoteType::Public.as_str(), "public");
+        assert_eq!(NoteType::Resolution.as_str(), "resolution");
+    }
+
+    #[test]
+    fn test_automation_trigger_from_str() {
+        assert_eq!(AutomationTrigger::from_str("on_create"), Some(AutomationTrigger::OnCreate));
+        assert_eq!(AutomationTrigger::from_str("on_update"), Some(AutomationTrigger::OnUpdate));
+        assert_eq!(AutomationTrigger::from_str("on_sla_breach"), Some(AutomationTrigger::OnSlaBreach));
+        assert_eq!(AutomationTrigger::from_str("invalid"), None);
+    }
+
+    #[test]
+    fn test_automation_trigger_as_str() {
+        assert_eq!(AutomationTrigger::OnCreate.as_str(), "on_create");
+        assert_eq!(AutomationTrigger::OnUpdate.as_str(), "on_update");
+        assert_eq!(AutomationTrigger::OnSlaBreach.as_str(), "on_sla_breach");
+    }
+
+    #[test]
+    fn test_ticket_sla_status() {
+        // Create a test ticket
+        let mut ticket = Ticket {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            ticket_number: "TKT-001".to_string(),
+            title: "Test Ticket".to_string(),
+            description: Some("Test description".to_string()),
+            status_id: Uuid::new_v4(),
+            priority_id: Uuid::new_v4(),
+            type_id: None,
+            category_id: None,
+            subcategory_id: None,
+            queue_id: Uuid::new_v4(),
+            source: TicketSource::Portal,
+            company_id: Uuid::new_v4(),
+            contact_id: None,
+            site_id: None,
+            assigned_to_id: None,
+            team_id: None,
+            parent_ticket_id: None,
+            contract_id: None,
+            sla_id: None,
+            sla_due_date: None,
+            first_response_due: None,
+            first_response_at: None,
+            resolution_due: None,
+            resolved_at: None,
+            closed_at: None,
+            resolution_code: None,
+            scheduled_start: None,
+            scheduled_end: None,
+            estimated_hours: None,
+            actual_hours: 0.0,
+            is_billable: false,
+            billing_status: BillingStatus::NotBilled,
+            asset_id: None,
+            custom_fields: serde_json::json!({}),
+            tags: vec![],
+            rule_tags: vec![],
+            snoozed_until: None,
+            snoozed_at: None,
+            snoozed_by_id: None,
+            created_by_id: Uuid::new_v4(),
+            last_updated_by_id: None,
+            created_at: Utc::now() - chrono::Duration::hours(8),
+            updated_at: Utc::now(),
+        };
+
+        // Test no SLA due date (should be NotApplicable)
+        assert_eq!(ticket.sla_status(), SlaStatus::NotApplicable);
+
+        // Test on track (due date in future, more than 20% of the window left)
+        ticket.sla_due_date = Some(Utc::now() + chrono::Duration::hours(3));
+        assert_eq!(ticket.sla_status(), SlaStatus::OnTrack);
+
+        // Test warning (less than 20% of the window left)
+        ticket.sla_due_date = Some(Utc::now() + chrono::Duration::hours(1));
+        assert_eq!(ticket.sla_status(), SlaStatus::Warning);
+
+        // Test breached (due date in past)
+        ticket.sla_due_date = Some(Utc::now() - chrono::Duration::hours(2));
+        assert_eq!(ticket.sla_status(), SlaStatus::Breached);
+
+        // Test closed ticket (should be NotApplicable)
+        ticket.closed_at = Some(Utc::now());
+        assert_eq!(ticket.sla_status(), SlaStatus::NotApplicable);
+    }
+
+    fn test_status(is_closed: bool, requires_resolution_code: bool) -> TicketStatus {
+        TicketStatus {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            name: "Closed".to_string(),
+            color: "#6B7280".to_string(),
+            is_closed,
+            is_default: false,
+            sort_order: 8,
+            requires_resolution_code,
+            is_final: is_closed,
+        }
+    }
+
+    fn test_codes() -> Vec<ResolutionCode> {
+        vec![
+            ResolutionCode {
+                id: Uuid::new_v4(),
+                tenant_id: Uuid::new_v4(),
+                code: "resolved".to_string(),
+                name: "Resolved".to_string(),
+                description: None,
+                is_active: true,
+                sort_order: 1,
+            },
+            ResolutionCode {
+                id: Uuid::new_v4(),
+                tenant_id: Uuid::new_v4(),
+                code: "legacy".to_string(),
+                name: "Legacy".to_string(),
+                description: None,
+                is_active: false,
+                sort_order: 2,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_resolution_code_required_on_close() {
+        let status = test_status(true, true);
+        let codes = test_codes();
+
+        let err = status.validate_resolution_code(None, &codes).unwrap_err();
+        match err {
+            AppError::Validation { errors, .. } => assert_eq!(errors[0].field, "resolution_code"),
+            other => panic!("expected validation error, got {:?}", other),
+        }
+
+        assert!(status.validate_resolution_code(Some("resolved"), &codes).is_ok());
+    }
+
+    #[test]
+    fn test_resolution_code_optional_when_not_required() {
+        let codes = test_codes();
+
+        assert!(test_status(true, false).validate_resolution_code(None, &codes).is_ok());
+        assert!(test_status(false, true).validate_resolution_code(None, &codes).is_ok());
+    }
+
+    #[test]
+    fn test_resolution_code_must_be_known_and_active() {
+        let status = test_status(true, true);
+        let codes = test_codes();
+
+        assert!(status.validate_resolution_code(Some("bogus"), &codes).is_err());
+        assert!(status.validate_resolution_code(Some("legacy"), &codes).is_err());
+    }
+
+    #[test]
+    fn test_stored_inactive_resolution_code_is_kept() {
+        let status = test_status(true, true);
+        let codes = test_codes();
+
+        // A ticket closed as "legacy" before it was retired can still change status
+        assert!(status.validate_updated_resolution_code(None, Some("legacy"), &codes).is_ok());
+        // but can't be given a retired code, and still needs one to close
+        assert!(status.validate_updated_resolution_code(Some("legacy"), None, &codes).is_err());
+        assert!(status.validate_updated_resolution_code(None, None, &codes).is_err());
+        assert!(status.validate_updated_resolution_code(Some("resolved"), Some("legacy"), &codes).is_ok());
+    }
+
+    fn named_status(name: &str, is_closed: bool) -> TicketStatus {
+        TicketStatus {
+            name: name.to_string(),
+            ..test_status(is_closed, false)
+        }
+    }
+
+    #[test]
+    fn test_workflow_rejects_illegal_transition() {
+        let in_progress = named_status("In Progress", false);
+        let resolved = named_status("Resolved", true);
+        let closed = named_status("Closed", true);
+        let workflow = StatusWorkflow::new(&[StatusTransition {
+            from_status_id: closed.id,
+            to_status_id: resolved.id,
+        }]);
+
+        match workflow.check(&closed, &in_progress).unwrap_err() {
+            AppError::Validation { message, errors } => {
+                assert_eq!(message, "Tickets can't move from 'Closed' to 'In Progress'; reopen the ticket first");
+                assert_eq!(errors[0].field, "status_id");
+            }
+            other => panic!("expected validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_workflow_allows_configured_and_unrestricted_transitions() {
+        let new = named_status("New", false);
+        let in_progress = named_status("In Progress", false);
+        let closed = named_status("Closed", true);
+        let workflow = StatusWorkflow::new(&[StatusTransition {
+            from_status_id: new.id,
+            to_status_id: in_progress.id,
+        }]);
+
+        assert!(workflow.check(&new, &in_progress).is_ok());
+        assert!(workflow.check(&new, &closed).is_err());
+        assert!(workflow.check(&new, &new).is_ok());
+        // In Progress has no transitions of its own, so it is unrestricted
+        assert!(workflow.check(&in_progress, &closed).is_ok());
+        assert!(StatusWorkflow::default().check(&closed, &new).is_ok());
+    }
+
+    fn sample_ticket() -> Ticket {
+        Ticket {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            ticket_number: "T000001".to_string(),
+            title: "Sample".to_string(),
+            description: None,
+            status_id: Uuid::new_v4(),
+            priority_id: Uuid::new_v4(),
+            type_id: None,
+            category_id: None,
+            subcategory_id: None,
+            queue_id: Uuid::new_v4(),
+            source: TicketSource::Email,
+            company_id: Uuid::new_v4(),
+            contact_id: None,
+            site_id: None,
+            assigned_to_id: None,
+            team_id: None,
+            parent_ticket_id: None,
+            contract_id: None,
+            sla_id: None,
+            sla_due_date: None,
+            first_response_due: None,
+            first_response_at: None,
+            resolution_due: None,
+            resolved_at: None,
+            closed_at: None,
+            resolution_code: None,
+            scheduled_start: None,
+            scheduled_end: None,
+            estimated_hours: None,
+            actual_hours: 0.0,
+            is_billable: true,
+            billing_status: BillingStatus::NotBilled,
+            asset_id: None,
+            custom_fields: serde_json::json!({}),
+            tags: vec![],
+            rule_tags: vec![],
+            snoozed_until: None,
+            snoozed_at: None,
+            snoozed_by_id: None,
+            created_by_id: Uuid::new_v4(),
+            last_updated_by_id: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_short_sla_warns_proportionally() {
+        let created_at = Utc.with_ymd_and_hms(2024, 6, 3, 9, 0, 0).unwrap();
+        let mut ticket = sample_ticket();
+        ticket.created_at = created_at;
+        ticket.sla_due_date = Some(created_at + chrono::Duration::hours(1));
+        let warning = SlaWarning::default();
+
+        // A flat two hours would have warned from the moment it opened
+        let at = |minutes: i64| created_at + chrono::Duration::minutes(minutes);
+        assert_eq!(ticket.sla_status_at(&warning, at(0)), SlaStatus::OnTrack);
+        assert_eq!(ticket.sla_status_at(&warning, at(48)), SlaStatus::OnTrack);
+        assert_eq!(ticket.sla_status_at(&warning, at(49)), SlaStatus::Warning);
+        assert_eq!(ticket.sla_status_at(&warning, at(61)), SlaStatus::Breached);
+    }
+
+    #[test]
+    fn test_long_sla_uses_configured_warning_hours() {
+        let created_at = Utc.with_ymd_and_hms(2024, 6, 3, 9, 0, 0).unwrap();
+        let due = created_at + chrono::Duration::days(5);
+        let mut ticket = sample_ticket();
+        ticket.created_at = created_at;
+        ticket.sla_due_date = Some(due);
+        let warning = SlaWarning {
+            remaining_fraction: 0.2,
+            max_hours: Some(4.0),
+        };
+
+        // 20% of five days would be a full day of warning
+        assert_eq!(warning.threshold(created_at, due), chrono::Duration::hours(4));
+        let before = |hours: i64| due - chrono::Duration::hours(hours);
+        assert_eq!(ticket.sla_status_at(&warning, before(5)), SlaStatus::OnTrack);
+        assert_eq!(ticket.sla_status_at(&warning, before(3)), SlaStatus::Warning);
+        assert_eq!(ticket.sla_status_at(&SlaWarning::default(), before(5)), SlaStatus::Warning);
+    }
+
+    #[test]
+    fn test_sla_time_remaining_counts_down_and_goes_negative() {
+        let due = Utc.with_ymd_and_hms(2024, 6, 3, 17, 0, 0).unwrap();
+        let mut ticket = sample_ticket();
+        ticket.sla_due_date = Some(due);
+
+        let remaining = ticket.sla_time_remaining(due - chrono::Duration::minutes(45), false).unwrap();
+        assert_eq!(remaining, chrono::Duration::minutes(45));
+        assert_eq!(humanize_sla_remaining(remaining), "45 minutes remaining");
+
+        let breached = ticket.sla_time_remaining(due + chrono::Duration::minutes(80), false).unwrap();
+        assert_eq!(breached, chrono::Duration::minutes(-80));
+        assert_eq!(humanize_sla_remaining(breached), "1 hour 20 minutes overdue");
+
+        ticket.closed_at = Some(due);
+        assert_eq!(ticket.sla_time_remaining(due, false), None);
+        assert_eq!(sample_ticket().sla_time_remaining(due, false), None);
+    }
+
+    #[test]
+    fn test_sla_time_remaining_frozen_while_snooze_pauses_clock() {
+        let due = Utc.with_ymd_and_hms(2024, 6, 3, 17, 0, 0).unwrap();
+        let snoozed_at = due - chrono::Duration::hours(2);
+        let mut ticket = sample_ticket();
+        ticket.sla_due_date = Some(due);
+        ticket.snoozed_at = Some(snoozed_at);
+        ticket.snoozed_until = Some(due + chrono::Duration::days(1));
+
+        // Past the original due time, but the clock stopped with two hours left
+        let later = due + chrono::Duration::hours(3);
+        assert_eq!(ticket.sla_time_remaining(later, true), Some(chrono::Duration::hours(2)));
+        // Tenants where snoozing doesn't pause the SLA keep counting
+        assert_eq!(ticket.sla_time_remaining(later, false), Some(chrono::Duration::hours(-3)));
+
+        // Once the snooze is over the clock runs again
+        ticket.snoozed_until = Some(snoozed_at + chrono::Duration::minutes(30));
+        assert_eq!(ticket.sla_time_remaining(later, true), Some(chrono::Duration::hours(-3)));
+    }
+
+    #[test]
+    fn test_humanize_sla_remaining() {
+        let minutes = chrono::Duration::minutes;
+        assert_eq!(humanize_sla_remaining(chrono::Duration::seconds(30)), "less than a minute remaining");
+        assert_eq!(humanize_sla_remaining(minutes(1)), "1 minute remaining");
+        assert_eq!(humanize_sla_remaining(minutes(120)), "2 hours remaining");
+        assert_eq!(humanize_sla_remaining(minutes(27 * 60 + 15)), "1 day 3 hours remaining");
+        assert_eq!(humanize_sla_remaining(minutes(-3 * 1440)), "3 days overdue");
+    }
+
+    #[test]
+    fn test_merged_ticket_keeps_earliest_created_at_and_highest_priority() {
+        let (critical, high, low) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let ranks = HashMap::from([(critical, 1), (high, 2), (low, 4)]);
+
+        let mut target = sample_ticket();
+        target.created_at = Utc::now() - chrono::Duration::days(1);
+        target.priority_id = low;
+        let mut oldest = sample_ticket();
+        oldest.created_at = Utc::now() - chrono::Duration::days(9);
+        oldest.priority_id = high;
+        let mut urgent = sample_ticket();
+        urgent.created_at = Utc::now() - chrono::Duration::days(3);
+        urgent.priority_id = critical;
+
+        let merge = TicketMerge::plan(&target, &[oldest.clone(), urgent.clone()], &ranks);
+        assert_eq!(merge.created_at.value, oldest.created_at);
+        assert_eq!(merge.created_at.from_ticket_id, oldest.id);
+        assert_eq!(merge.priority_id.value, critical);
+        assert_eq!(merge.priority_id.from_ticket_id, urgent.id);
+
+        // The listing order of the sources doesn't change anything
+        assert_eq!(TicketMerge::plan(&target, &[urgent, oldest], &ranks), merge);
+
+        // Ties go to the target
+        let mut twin = sample_ticket();
+        twin.created_at = target.created_at;
+        twin.priority_id = target.priority_id;
+        let merge = TicketMerge::plan(&target, &[twin], &ranks);
+        assert_eq!(merge.created_at.from_ticket_id, target.id);
+        assert_eq!(merge.priority_id.from_ticket_id, target.id);
+    }
+
+    #[test]
+    fn test_merged_ticket_unions_tags_with_their_sources() {
+        let mut target = sample_ticket();
+        target.tags = vec!["vpn".to_string(), "billing".to_string()];
+        let mut source = sample_ticket();
+        source.tags = vec!["vpn".to_string(), "auto-urgent".to_string()];
+        source.rule_tags = vec!["auto-urgent".to_string()];
+
+        let merge = TicketMerge::plan(&target, &[source.clone(), target.clone()], &HashMap::new());
+        assert_eq!(merge.source_ids, vec![source.id]);
+        assert_eq!(merge.tags, vec!["vpn", "billing", "auto-urgent"]);
+        assert_eq!(merge.rule_tags, vec!["auto-urgent"]);
+        assert_eq!(merge.tag_sources["vpn"], vec![target.id, source.id]);
+        assert_eq!(merge.tag_sources["auto-urgent"], vec![source.id]);
+    }
+
+    #[test]
+    fn test_update_changes_for_webhook() {
+        let before = sample_ticket();
+        let critical = Uuid::new_v4();
+        let technician = Uuid::new_v4();
+
+        let mut after = before.clone();
+        after.priority_id = critical;
+        after.assigned_to_id = Some(technician);
+        // Bookkeeping that comes with any update
+        after.last_updated_by_id = Some(technician);
+        after.updated_at = before.updated_at + chrono::Duration::seconds(5);
+        after.sla_due_date = Some(after.updated_at + chrono::Duration::hours(4));
+
+        let changes = before.changes(&after);
+        assert_eq!(
+            changes,
+            vec![
+                FieldChange {
+                    field: "assigned_to_id".to_string(),
+                    old: serde_json::Value::Null,
+                    new: serde_json::json!(technician),
+                },
+                FieldChange {
+                    field: "priority_id".to_string(),
+                    old: serde_json::json!(before.priority_id),
+                    new: serde_json::json!(critical),
+                },
+            ]
+        );
+
+        let payload = crate::modules::webhooks::updated_payload("ticket", &after, &changes);
+        assert_eq!(payload["ticket"]["priority_id"], serde_json::json!(critical));
+        assert_eq!(payload["changes"].as_array().map(Vec::len), Some(2));
+        assert_eq!(payload["changes"][1]["field"], "priority_id");
+
+        // Saving without changes sends nothing
+        assert!(before.changes(&before.clone()).is_empty());
+    }
+
+    fn queue(default_sla_id: Option<Uuid>) -> TicketQueue {
+        TicketQueue {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::nil(),
+            name: "Escalations".to_string(),
+            description: None,
+            color: None,
+            icon: None,
+            is_default: false,
+            sort_order: 0,
+            default_sla_id,
+            default_priority_id: None,
+            default_assignee_id: Some(Uuid::new_v4()),
+            default_team_id: Some(Uuid::new_v4()),
+            auto_acknowledge: None,
+        }
+    }
+
+    #[test]
+    fn test_new_portal_or_email_ticket_gets_one_acknowledgement() {
+        let enabled = TicketSettings {
+            auto_acknowledge: true,
+            ..TicketSettings::default()
+        };
+        let chicago = TenantTimezone::parse("America/Chicago").unwrap();
+        let mut ticket = sample_ticket();
+        ticket.source = TicketSource::Portal;
+        // 15:00 UTC is 10:00 CDT
+        ticket.first_response_due = Some(Utc.with_ymd_and_hms(2024, 6, 4, 15, 0, 0).unwrap());
+
+        let ack = TicketAcknowledgement::for_new_ticket(&ticket, &enabled, &queue(None), chicago, "https://psa/t/1")
+            .expect("portal tickets are acknowledged");
+        assert_eq!(ack.context["ticket"]["number"], "T000001");
+        assert_eq!(ack.context["ticket"]["response_due"], "2024-06-04 10:00 America/Chicago");
+
+        // Off for the tenant, or for the queue, or for tickets an agent logged
+        let ack = |ticket: &Ticket, settings: &TicketSettings, queue: &TicketQueue| {
+            TicketAcknowledgement::for_new_ticket(ticket, settings, queue, chicago, "").is_some()
+        };
+        assert!(!ack(&ticket, &TicketSettings::default(), &queue(None)));
+        let quiet_queue = TicketQueue {
+            auto_acknowledge: Some(false),
+            ..queue(None)
+        };
+        assert!(!ack(&ticket, &enabled, &quiet_queue));
+        let loud_queue = TicketQueue {
+            auto_acknowledge: Some(true),
+            ..queue(None)
+        };
+        assert!(ack(&ticket, &TicketSettings::default(), &loud_queue));
+        ticket.source = TicketSource::Phone;
+        assert!(!ack(&ticket, &enabled, &queue(None)));
+        ticket.source = TicketSource::Email;
+        assert!(ack(&ticket, &enabled, &queue(None)));
+    }
+
+    #[test]
+    fn test_acknowledgement_does_not_satisfy_first_response() {
+        // The acknowledgement is a public note marked not to count
+        assert!(!NoteType::Public.is_first_response(false));
+        assert!(NoteType::Public.is_first_response(true));
+        assert!(!NoteType::Internal.is_first_response(true));
+
+        let mut ticket = sample_ticket();
+        ticket.source = TicketSource::Email;
+        ticket.first_response_due = Some(Utc::now() + chrono::Duration::hours(1));
+        let settings = TicketSettings {
+            auto_acknowledge: true,
+            ..TicketSettings::default()
+        };
+        let ack = TicketAcknowledgement::for_new_ticket(&ticket, &settings, &queue(None), TenantTimezone::default(), "");
+        // The expected response time is still the open first-response target
+        assert!(ack.unwrap().context["ticket"]["response_due"].is_string());
+    }
+
+    #[test]
+    fn test_requeue_into_queue_with_default_sla_recalculates() {
+        let standard_sla = Uuid::new_v4();
+        let premium_sla = Uuid::new_v4();
+        let technician = Uuid::new_v4();
+        let current = QueueFields {
+            sla_id: Some(standard_sla),
+            priority_id: Some(Uuid::new_v4()),
+            assigned_to_id: Some(technician),
+            team_id: None,
+        };
+
+        let escalations = queue(Some(premium_sla));
+        let fields = escalations.entry_fields(&QueueFields::default(), &current);
+        assert_eq!(fields.sla_id, Some(premium_sla));
+        assert_eq!(fields.priority_id, current.priority_id);
+        // The assignee is kept, the empty team is filled from the queue
+        assert_eq!(fields.assigned_to_id, Some(technician));
+        assert_eq!(fields.team_id, escalations.default_team_id);
+
+        // The policy changed, so due dates restart from the move
+        assert_ne!(fields.sla_id, current.sla_id);
+        let moved_at = Utc::now();
+        let target = SlaTarget {
+            first_response_hours: Some(0.5),
+            resolution_hours: Some(4.0),
+        };
+        let (first_response_due, sla_due_date) = target.due_dates(moved_at);
+        assert_eq!(first_response_due, Some(moved_at + chrono::Duration::minutes(30)));
+        assert_eq!(sla_due_date, Some(moved_at + chrono::Duration::hours(4)));
+
+        // A queue without a default SLA leaves the policy, and the due dates, alone
+        let fields = queue(None).entry_fields(&QueueFields::default(), &current);
+        assert_eq!(fields.sla_id, current.sla_id);
+
+        // Explicit values beat queue defaults
+        let explicit = QueueFields {
+            sla_id: Some(standard_sla),
+            ..QueueFields::default()
+        };
+        assert_eq!(escalations.entry_fields(&explicit, &current).sla_id, Some(standard_sla));
+    }
+
+    #[test]
+    fn test_priority_multiplier_scales_base_targets() {
+        let base = SlaTarget {
+            first_response_hours: Some(4.0),
+            resolution_hours: Some(24.0),
+        };
+        let created_at = Utc::now();
+
+        // No targets of its own: a 0.5 priority gets half the base windows
+        let target = SlaTarget::for_priority(None, Some(base), 0.5).unwrap();
+        let (first_response_due, sla_due_date) = target.due_dates(created_at);
+        assert_eq!(first_response_due, Some(created_at + chrono::Duration::hours(2)));
+        assert_eq!(sla_due_date, Some(created_at + chrono::Duration::hours(12)));
+
+        let low = SlaTarget::for_priority(None, Some(base), 2.0).unwrap();
+        assert_eq!(low.resolution_hours, Some(48.0));
+
+        // A nonsensical multiplier leaves the base alone
+        assert_eq!(SlaTarget::for_priority(None, Some(base), 0.0), Some(base));
+    }
+
+    #[test]
+    fn test_priority_targets_take_precedence_over_multiplier() {
+        let base = SlaTarget {
+            first_response_hours: Some(4.0),
+            resolution_hours: Some(24.0),
+        };
+        let own = SlaTarget {
+            first_response_hours: Some(0.5),
+            resolution_hours: None,
+        };
+
+        assert_eq!(SlaTarget::for_priority(Some(own), Some(base), 0.25), Some(own));
+        // Nothing to scale without base targets
+        assert_eq!(SlaTarget::for_priority(None, None, 0.5), None);
+    }
+
+    #[test]
+    fn test_claim_outcomes() {
+        let user_id = Uuid::new_v4();
+        let other = Uuid::new_v4();
+
+        assert_eq!(ClaimOutcome::from_update(true, user_id, Some(user_id)), ClaimOutcome::Claimed);
+        assert_eq!(ClaimOutcome::from_update(false, user_id, Some(other)), ClaimOutcome::ClaimedBy(other));
+        assert_eq!(ClaimOutcome::from_update(false, user_id, None), ClaimOutcome::Contended);
+
+        match ClaimOutcome::ClaimedBy(other).into_result(Some("Dana Ortiz")) {
+            Err(AppError::Conflict(message)) => assert_eq!(message, "Ticket already claimed by Dana Ortiz"),
+            other => panic!("expected conflict, got {:?}", other),
+        }
+        assert!(matches!(ClaimOutcome::Contended.into_result(None), Err(AppError::Conflict(_))));
+
+        // Claiming a ticket you already hold is fine
+        assert_eq!(ClaimOutcome::from_update(false, user_id, Some(user_id)), ClaimOutcome::AlreadyClaimed);
+        assert!(ClaimOutcome::AlreadyClaimed.into_result(None).is_ok());
+        assert!(ClaimOutcome::Claimed.into_result(None).is_ok());
+    }
+
+    #[test]
+    fn test_reopen_sla_mode_from_str() {
+        assert_eq!(ReopenSlaMode::from_str("fresh_clock"), Some(ReopenSlaMode::FreshClock));
+        assert_eq!(ReopenSlaMode::from_str("resume_remaining"), Some(ReopenSlaMode::ResumeRemaining));
+        assert_eq!(ReopenSlaMode::from_str("no_sla"), Some(ReopenSlaMode::NoSla));
+        assert_eq!(ReopenSlaMode::from_str("other"), None);
+        assert_eq!(ReopenSlaMode::NoSla.as_str(), "no_sla");
+    }
+
+    #[test]
+    fn test_reopen_fresh_clock_recalculates() {
+        let mut ticket = sample_ticket();
+        ticket.closed_at = Some(Utc::now());
+        assert_eq!(ReopenSlaMode::FreshClock.plan(&ticket, Utc::now()), ReopenSla::Recalculate);
+    }
+
+    #[test]
+    fn test_reopen_resume_remaining_shifts_due_dates() {
+        let now = Utc::now();
+        let closed_at = now - chrono::Duration::hours(10);
+        let mut ticket = sample_ticket();
+        ticket.closed_at = Some(closed_at);
+        ticket.sla_due_date = Some(closed_at + chrono::Duration::hours(4));
+        ticket.first_response_due = Some(closed_at - chrono::Duration::hours(1));
+        ticket.first_response_at = Some(closed_at - chrono::Duration::hours(2));
+
+        assert_eq!(
+            ReopenSlaMode::ResumeRemaining.plan(&ticket, now),
+            ReopenSla::Shift {
+                first_response_due: ticket.first_response_due,
+                sla_due_date: Some(now + chrono::Duration::hours(4)),
+            }
+        );
+    }
+
+    #[test]
+    fn test_reopen_resume_remaining_after_breach() {
+        let now = Utc::now();
+        let closed_at = now - chrono::Duration::hours(1);
+        let mut ticket = sample_ticket();
+        ticket.closed_at = Some(closed_at);
+        ticket.sla_due_date = Some(closed_at - chrono::Duration::hours(3));
+
+        assert_eq!(
+            ReopenSlaMode::ResumeRemaining.plan(&ticket, now),
+            ReopenSla::Shift {
+                first_response_due: None,
+                sla_due_date: Some(now),
+            }
+        );
+    }
+
+    #[test]
+    fn test_reopen_no_sla_clears() {
+        let mut ticket = sample_ticket();
+        ticket.closed_at = Some(Utc::now());
+        ticket.sla_due_date = Some(Utc::now());
+        assert_eq!(ReopenSlaMode::NoSla.plan(&ticket, Utc::now()), ReopenSla::Clear);
+    }
+
+    #[test]
+    fn test_snoozed_ticket_hidden_until_snooze_ends() {
+        let now = Utc::now();
+        let mut ticket = sample_ticket();
+        ticket.snoozed_at = Some(now);
+        ticket.snoozed_until = Some(now + chrono::Duration::hours(3));
+
+        let active = SnoozeVisibility::from_filter(&TicketFilter::default());
+        assert_eq!(active, SnoozeVisibility::Hidden);
+        assert!(!active.shows(&ticket, now));
+
+        let snoozed = SnoozeVisibility::from_filter(&TicketFilter {
+            is_snoozed: Some(true),
+            ..TicketFilter::default()
+        });
+        assert!(snoozed.shows(&ticket, now));
+        assert!(SnoozeVisibility::Included.shows(&ticket, now));
+
+        // Back in the active list once the snooze runs out, even before the
+        // job has cleared it
+        let later = now + chrono::Duration::hours(3);
+        assert!(active.shows(&ticket, later));
+        assert!(!snoozed.shows(&ticket, later));
+
+        ticket.snoozed_until = None;
+        assert!(active.shows(&ticket, now));
+    }
+
+    #[test]
+    fn test_team_member_list_is_scoped_to_their_teams() {
+        let technician = Uuid::new_v4();
+        let service_desk = Uuid::new_v4();
+        let scope = TicketScope::for_user(TicketVisibility::Team, UserRole::Technician, technician, vec![service_desk])
+            .expect("technicians are scoped under team visibility");
+
+        let sql = TicketScope::sql(4);
+        // Their own tickets wherever they sit, and their teams' tickets
+        assert!(sql.starts_with("(t.assigned_to_id = $4 OR t.team_id = ANY($5) OR "));
+        // Without a team of its own, the queue's team decides; with neither,
+        // everyone sees it
+        assert!(sql.contains(
+            "(t.team_id IS NULL AND NOT EXISTS (SELECT 1 FROM ticket_queues q WHERE q.id = t.queue_id "
+        ));
+        assert!(sql.contains("q.assignable_to_team_id IS NOT NULL AND NOT q.assignable_to_team_id = ANY($5)"));
+        assert!(!sql.contains("$6"));
+        assert_eq!(sql.matches('(').count(), sql.matches(')').count());
+
+        let filter = TicketFilter {
+            scope: Some(scope),
+            ..TicketFilter::default()
+        };
+        // The scope can't be set or cleared from the query string
+        let json = serde_json::to_value(&filter).unwrap();
+        assert!(json.get("scope").is_none());
+        let parsed: TicketFilter = serde_json::from_value(serde_json::json!({ "scope": null })).unwrap();
+        assert_eq!(parsed.scope, None);
+    }
+
+    #[test]
+    fn test_managers_and_all_visibility_are_not_scoped() {
+        let scope = |visibility, role| TicketScope::for_user(visibility, role, Uuid::new_v4(), vec![Uuid::new_v4()]);
+        assert_eq!(scope(TicketVisibility::Team, UserRole::Manager), None);
+        assert_eq!(scope(TicketVisibility::Team, UserRole::Admin), None);
+        assert_eq!(scope(TicketVisibility::All, UserRole::Technician), None);
+        assert!(scope(TicketVisibility::Team, UserRole::Technician).is_some());
+        assert_eq!(TicketSettings::default().visibility, TicketVisibility::All);
+        assert_eq!(
+            TicketSettings::from_rows(vec![("visibility".to_string(), serde_json::json!("team"))]).visibility,
+            TicketVisibility::Team
+        );
+    }
+
+    #[test]
+    fn test_snooze_must_end_in_future() {
+        let now = Utc::now();
+        assert!(SnoozeTicketRequest { until: now + chrono::Duration::minutes(5) }.check(now).is_ok());
+        assert!(SnoozeTicketRequest { until: now }.check(now).is_err());
+        assert!(SnoozeTicketRequest { until: now - chrono::Duration::hours(1) }.check(now).is_err());
+    }
+
+    #[test]
+    fn test_snooze_sla_shift_skips_met_targets() {
+        let now = Utc::now();
+        let paused = chrono::Duration::hours(2);
+        let mut ticket = sample_ticket();
+        ticket.first_response_due = Some(now);
+        ticket.sla_due_date = Some(now + chrono::Duration::hours(6));
+        ticket.resolution_due = ticket.sla_due_date;
+
+        let sla = ticket.sla_after_snooze(paused);
+        assert_eq!(sla.first_response_due, Some(now + paused));
+        assert_eq!(sla.sla_due_date, Some(now + chrono::Duration::hours(8)));
+        assert_eq!(sla.resolution_due, sla.sla_due_date);
+
+        // A response already sent keeps its target
+        ticket.first_response_at = Some(now - chrono::Duration::hours(1));
+        assert_eq!(ticket.sla_after_snooze(paused).first_response_due, Some(now));
+
+        // Snoozing has no SLA effect unless the tenant turns it on
+        assert!(!TicketSettings::default().snooze_pauses_sla);
+    }
+
+    #[test]
+    fn test_resolved_ticket_past_grace_auto_closes() {
+        let now = Utc::now();
+        let grace = chrono::Duration::days(7);
+        let mut ticket = sample_ticket();
+        ticket.resolved_at = Some(now - chrono::Duration::days(8));
+        ticket.closed_at = ticket.resolved_at;
+        ticket.updated_at = now - chrono::Duration::days(8);
+
+        assert!(ticket.auto_close_due(grace, now));
+    }
+
+    #[test]
+    fn test_recently_updated_resolved_ticket_is_left_alone() {
+        let now = Utc::now();
+        let grace = chrono::Duration::days(7);
+        let mut ticket = sample_ticket();
+        ticket.resolved_at = Some(now - chrono::Duration::days(8));
+        ticket.closed_at = ticket.resolved_at;
+        // The customer replied yesterday
+        ticket.updated_at = now - chrono::Duration::days(1);
+        assert!(!ticket.auto_close_due(grace, now));
+
+        // Reopened and resolved again two days ago restarts the grace period
+        ticket.resolved_at = Some(now - chrono::Duration::days(2));
+        ticket.updated_at = now - chrono::Duration::days(2);
+        assert!(!ticket.auto_close_due(grace, now));
+
+        // Open tickets never auto-close
+        assert!(!sample_ticket().auto_close_due(grace, now));
+    }
+
+    #[test]
+    fn test_auto_reopen_window_boundary() {
+        let settings = TicketSettings {
+            auto_reopen_window_hours: 24,
+            ..Default::default()
+        };
+        let closed_at = Utc::now();
+
+        assert!(settings.within_reopen_window(closed_at, closed_at + chrono::Duration::hours(24)));
+        assert!(!settings.within_reopen_window(
+            closed_at,
+            closed_at + chrono::Duration::hours(24) + chrono::Duration::seconds(1)
+        ));
+
+        let disabled = TicketSettings {
+            auto_reopen_window_hours: 0,
+            ..Default::default()
+        };
+        assert!(!disabled.within_reopen_window(closed_at, closed_at));
+    }
+
+    #[test]
+    fn test_ticket_settings_from_rows() {
+        let settings = TicketSettings::from_rows(vec![
+            ("reopen_sla_mode".to_string(), serde_json::json!("no_sla")),
+            ("auto_reopen_window_hours".to_string(), serde_json::json!("not a number")),
+        ]);
+
+        assert_eq!(settings.reopen_sla_mode, ReopenSlaMode::NoSla);
+        assert_eq!(settings.auto_reopen_window_hours, 72);
+    }
+
+    fn sample_survey() -> CsatSurvey {
+        CsatSurvey {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            ticket_id: Uuid::new_v4(),
+            contact_id: None,
+            technician_id: None,
+            token: "token".to_string(),
+            expires_at: Utc::now() + chrono::Duration::days(7),
+            rating: None,
+            comment: None,
+            responded_at: None,
+            sent_at: Some(Utc::now()),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_csat_rating_from_str() {
+        assert_eq!(CsatRating::from_str("positive"), Some(CsatRating::Positive));
+        assert_eq!(CsatRating::from_str("negative"), Some(CsatRating::Negative));
+        assert_eq!(CsatRating::from_str("meh"), None);
+        assert_eq!(CsatRating::Positive.as_str(), "positive");
+    }
+
+    #[test]
+    fn test_csat_survey_accepts_valid_token() {
+        assert!(sample_survey().ensure_open(Utc::now()).is_ok());
+    }
+
+    #[test]
+    fn test_csat_survey_rejects_reused_token() {
+        let mut survey = sample_survey();
+        survey.rating = Some(CsatRating::Positive);
+        survey.responded_at = Some(Utc::now());
+
+        assert!(matches!(survey.ensure_open(Utc::now()), Err(AppError::Conflict(_))));
+    }
+
+    #[test]
+    fn test_csat_survey_rejects_expired_token() {
+        let survey = sample_survey();
+        let after_expiry = survey.expires_at + chrono::Duration::seconds(1);
+
+        assert!(matches!(survey.ensure_open(after_expiry), Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn test_normalize_message_id() {
+        assert_eq!(normalize_message_id(" <a@b> "), Some("<a@b>".to_string()));
+        assert_eq!(normalize_message_id("a@b"), Some("<a@b>".to_string()));
+        assert_eq!(normalize_message_id("<>"), None);
+    }
+
+    #[test]
+    fn test_reply_thread_headers() {
+        let earlier: Vec<String> = (0..15).map(|i| format!("<m{}@x>", i)).collect();
+        let headers = reply_thread_headers("<new@x>".to_string(), &earlier);
+
+        assert_eq!(headers.message_id, "<new@x>");
+        assert_eq!(headers.in_reply_to.as_deref(), Some("<m14@x>"));
+        assert_eq!(headers.references.len(), 10);
+        assert_eq!(headers.references[0], "<m0@x>");
+        assert_eq!(headers.references[9], "<m14@x>");
+
+        let first = reply_thread_headers("<new@x>".to_string(), &[]);
+        assert!(first.in_reply_to.is_none());
+        assert!(first.references.is_empty());
+    }
+
+    #[test]
+    fn test_reply_resolves_by_in_reply_to_despite_changed_subject() {
+        let ticket_id = Uuid::new_v4();
+        let other_ticket = Uuid::new_v4();
+        let note_id = Uuid::new_v4();
+        let note_message = note_message_id(note_id, "psa.example.com");
+
+        let known = std::collections::HashMap::from([
+            (note_message.clone(), ticket_id),
+            ("<older@psa.example.com>".to_string(), other_ticket),
+        ]);
+
+        // The customer rewrote the subject and dropped the ticket tag
+        let reply = InboundThreadHeaders::from_raw(
+            Some("<reply-1@mail.customer.com>"),
+            Some(&note_message),
+            Some("<older@psa.example.com> <unrelated@elsewhere>"),
+            "Still broken!!",
+        );
+
+        assert_eq!(reply.subject_ticket_number(), None);
+        assert_eq!(reply.resolve_ticket(&known), Some(ticket_id));
+    }
+
+    #[test]
+    fn test_reply_falls_back_to_references_and_subject_tag() {
+        let ticket_id = Uuid::new_v4();
+        let known =
+            std::collections::HashMap::from([("<root@psa.example.com>".to_string(), ticket_id)]);
+
+        let reply = InboundThreadHeaders::from_raw(
+            None,
+            Some("<unknown@mail.customer.com>"),
+            Some("<root@psa.example.com> <unknown@mail.customer.com>"),
+            "Re: [T000042] Printer offline",
+        );
+        assert_eq!(
+            reply.candidates(),
+            vec!["<unknown@mail.customer.com>".to_string(), "<root@psa.example.com>".to_string()]
+        );
+        assert_eq!(reply.resolve_ticket(&known), Some(ticket_id));
+        assert_eq!(reply.subject_ticket_number().as_deref(), Some("T000042"));
+
+        let untagged = InboundThreadHeaders::from_raw(None, None, None, "Re: [Tomorrow] lunch");
+        assert_eq!(untagged.subject_ticket_number(), None);
+    }
+
+    fn queue_address(queue_id: Uuid, email_address: &str) -> QueueEmailAddress {
+        QueueEmailAddress {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            queue_id,
+            email_address: email_address.to_string(),
+            from_name: Some("Brand Support".to_string()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn inbound(to: &[&str], cc: &[&str]) -> InboundEmail {
+        InboundEmail {
+            from: "jane@customer.com".to_string(),
+            to: to.iter().map(|s| s.to_string()).collect(),
+            cc: cc.iter().map(|s| s.to_string()).collect(),
+            subject: "Printer offline".to_string(),
+            body_text: "It stopped printing".to_string(),
+            message_id: None,
+            in_reply_to: None,
+            references: None,
+        }
+    }
+
+    #[test]
+    fn test_mapped_address_lands_in_queue() {
+        let (brand_a, brand_b, default_queue) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let addresses = vec![
+            queue_address(brand_a, "support@brand-a.com"),
+            queue_address(brand_b, "help@brand-b.com"),
+        ];
+
+        let email = inbound(&["\"Brand B\" <Help@Brand-B.com>"], &[]);
+        assert_eq!(email.queue_for(&addresses, default_queue), brand_b);
+
+        // A mapped Cc still routes when the To address is unknown
+        let email = inbound(&["someone@brand-c.com"], &["support@brand-a.com"]);
+        assert_eq!(email.queue_for(&addresses, default_queue), brand_a);
+
+        assert_eq!(addresses[1].from_header(), "Brand Support <help@brand-b.com>");
+    }
+
+    #[test]
+    fn test_unmapped_address_uses_default_queue() {
+        let default_queue = Uuid::new_v4();
+        let addresses = vec![queue_address(Uuid::new_v4(), "support@brand-a.com")];
+
+        let email = inbound(&["sales@brand-a.com"], &["not an address"]);
+        assert_eq!(email.queue_for(&addresses, default_queue), default_queue);
+        assert_eq!(inbound(&[], &[]).queue_for(&addresses, default_queue), default_queue);
+    }
+
+    fn link(from: Uuid, to: Uuid, link_type: TicketLinkType) -> TicketLink {
+        let (from_ticket_id, to_ticket_id) = TicketLink::endpoints(from, to, link_type).unwrap();
+        TicketLink {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            from_ticket_id,
+            to_ticket_id,
+            link_type,
+            created_by_id: Uuid::new_v4(),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_ticket_link_rejects_self_link() {
+        let ticket = Uuid::new_v4();
+        for link_type in [TicketLinkType::RelatedTo, TicketLinkType::DuplicateOf, TicketLinkType::Blocks] {
+            assert!(matches!(
+                TicketLink::endpoints(ticket, ticket, link_type),
+                Err(AppError::Validation { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_related_link_stored_once_per_pair() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+
+        // Either direction yields the same row, so the unique key catches the repeat
+        assert_eq!(
+            TicketLink::endpoints(a, b, TicketLinkType::RelatedTo).unwrap(),
+            TicketLink::endpoints(b, a, TicketLinkType::RelatedTo).unwrap()
+        );
+        // Directional links keep the order they were created in
+        assert_eq!(TicketLink::endpoints(b, a, TicketLinkType::Blocks).unwrap(), (b, a));
+    }
+
+    #[test]
+    fn test_related_tickets_from_each_end() {
+        let (original, duplicate, blocker, neighbour) =
+            (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let links = vec![
+            link(duplicate, original, TicketLinkType::DuplicateOf),
+            link(blocker, original, TicketLinkType::Blocks),
+            link(neighbour, original, TicketLinkType::RelatedTo),
+            link(duplicate, neighbour, TicketLinkType::Blocks),
+        ];
+
+        let related: Vec<_> = links.iter().filter_map(|l| l.relation_for(original)).collect();
+        assert_eq!(
+            related,
+            vec![
+                (duplicate, TicketRelation::DuplicatedBy),
+                (blocker, TicketRelation::BlockedBy),
+                (neighbour, TicketRelation::RelatedTo),
+            ]
+        );
+
+        let related: Vec<_> = links.iter().filter_map(|l| l.relation_for(duplicate)).collect();
+        assert_eq!(
+            related,
+            vec![(original, TicketRelation::DuplicateOf), (neighbour, TicketRelation::Blocks)]
+        );
+    }
+
+    fn incident_type(required_fields: &[&str]) -> TicketType {
+        TicketType {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            name: "Incident".to_string(),
+            description: None,
+            icon: None,
+            is_active: true,
+            sort_order: 0,
+            required_fields: required_fields.iter().map(|f| f.to_string()).collect(),
+        }
+    }
+
+    fn create_request(asset_id: Option<Uuid>) -> CreateTicketRequest {
+        serde_json::from_value(serde_json::json!({
+            "title": "Server down",
+            "company_id": Uuid::new_v4(),
+            "asset_id": asset_id,
+        }))
+        .unwrap()
+    }
+
+    fn contract(contract_type: &str, sla_id: Option<Uuid>) -> ContractTerms {
+        ContractTerms {
+            contract_id: Uuid::new_v4(),
+            contract_type: contract_type.to_string(),
+            sla_id,
+        }
+    }
+
+    #[test]
+    fn test_managed_services_ticket_inherits_contract() {
+        let sla_id = Uuid::new_v4();
+        let managed = contract("managed_services", Some(sla_id));
+
+        let fields = create_request(None).contract_fields(Some(&managed));
+        assert_eq!(
+            fields,
+            ContractFields { contract_id: Some(managed.contract_id), sla_id: Some(sla_id), is_billable: false }
+        );
+
+        // Anything the request sets wins over the contract
+        let mut request = create_request(None);
+        let other_sla = Uuid::new_v4();
+        request.sla_id = Some(other_sla);
+        request.is_billable = Some(true);
+        let fields = request.contract_fields(Some(&managed));
+        assert_eq!(fields.contract_id, Some(managed.contract_id));
+        assert_eq!(fields.sla_id, Some(other_sla));
+        assert!(fields.is_billable);
+    }
+
+    #[test]
+    fn test_billable_unless_contract_covers_work() {
+        let hourly = contract("time_and_materials", None);
+        let fields = create_request(None).contract_fields(Some(&hourly));
+        assert_eq!(fields.contract_id, Some(hourly.contract_id));
+        assert_eq!(fields.sla_id, None);
+        assert!(fields.is_billable);
+
+        assert!(create_request(None).contract_fields(Some(&contract("block_hours", None))).is_billable);
+        assert!(!create_request(None).contract_fields(Some(&contract("warranty", None))).is_billable);
+
+        let fields = create_request(None).contract_fields(None);
+        assert_eq!(fields, ContractFields { contract_id: None, sla_id: None, is_billable: true });
+    }
+
+    fn candidate(company_id: Uuid, is_closed: bool) -> ReassignCandidate {
+        ReassignCandidate {
+            ticket_id: Uuid::new_v4(),
+            company_id,
+            queue_id: None,
+            is_closed,
+        }
+    }
+
+    #[test]
+    fn test_reassign_moves_open_tickets_and_leaves_closed_ones() {
+        let company = Uuid::new_v4();
+        let candidates = [
+            candidate(company, false),
+            candidate(company, true),
+            candidate(Uuid::new_v4(), false),
+            candidate(company, false),
+        ];
+        let open: Vec<Uuid> = [0, 2, 3].iter().map(|&i| candidates[i].ticket_id).collect();
+        let leaving = Uuid::new_v4();
+        let successor = Uuid::new_v4();
+
+        let request = ReassignRequest {
+            to: ReassignTarget::User { user_id: successor },
+            company_id: None,
+            queue_id: None,
+        };
+        assert!(request.check(leaving).is_ok());
+        let plan = request.plan(&candidates);
+        assert_eq!(plan.iter().map(|r| r.ticket_id).collect::<Vec<_>>(), open);
+        assert!(plan.iter().all(|r| r.assigned_to_id == Some(successor)));
+
+        // Into a queue, unassigned, for one company's tickets only
+        let triage = Uuid::new_v4();
+        let request = ReassignRequest {
+            to: ReassignTarget::Queue { queue_id: triage },
+            company_id: Some(company),
+            queue_id: None,
+        };
+        let plan = request.plan(&candidates);
+        assert_eq!(
+            plan,
+            vec![
+                Reassignment { ticket_id: open[0], assigned_to_id: None, queue_id: Some(triage) },
+                Reassignment { ticket_id: open[2], assigned_to_id: None, queue_id: Some(triage) },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_round_robin_reassignment_takes_turns() {
+        let candidates: Vec<ReassignCandidate> = (0..5).map(|_| candidate(Uuid::new_v4(), false)).collect();
+        let (alex, blair) = (Uuid::new_v4(), Uuid::new_v4());
+        let request = ReassignRequest {
+            to: ReassignTarget::RoundRobin { user_ids: vec![alex, blair] },
+            company_id: None,
+            queue_id: None,
+        };
+
+        let assignees: Vec<Option<Uuid>> = request.plan(&candidates).iter().map(|r| r.assigned_to_id).collect();
+        assert_eq!(assignees, vec![Some(alex), Some(blair), Some(alex), Some(blair), Some(alex)]);
+
+        // Neither an empty rotation nor the departing technician are accepted
+        assert!(request.check(alex).is_err());
+        let empty = ReassignRequest { to: ReassignTarget::RoundRobin { user_ids: vec![] }, ..request };
+        assert!(empty.check(Uuid::new_v4()).is_err());
+    }
+
+    fn close_candidate(status_id: Uuid, is_closed: bool) -> BulkCloseCandidate {
+        BulkCloseCandidate {
+            ticket_id: Uuid::new_v4(),
+            status_id,
+            is_closed,
+            has_contact: true,
         }
     }
 
-    pub fn as_str(&self) -> &'static str {
-        match self {
-            Self::OnCreate => "on_create",
-            Self::OnUpdate => "on_update",
-            Self::OnSchedule => "on_schedule",
-            Self::OnSlaBreach => "on_sla_breach",
-            Self::OnSlaWarning => "on_sla_warning",
-            Self::OnAging => "on_aging",
+    fn bulk_close(ticket_ids: Vec<Uuid>, suppress_notifications: bool) -> BulkCloseRequest {
+        BulkCloseRequest {
+            ticket_ids,
+            status_id: None,
+            resolution_code: None,
+            note: Some("Upstream outage resolved".to_string()),
+            suppress_notifications,
         }
     }
-}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AutomationRule {
-    pub id: Uuid,
-    pub tenant_id: Uuid,
-    pub name: String,
-    pub description: Option<String>,
-    pub is_active: bool,
-    pub trigger_type: AutomationTrigger,
-    pub conditions: serde_json::Value,
-    pub actions: serde_json::Value,
-    pub priority: i32,
-    pub last_run_at: Option<DateTime<Utc>>,
-    pub run_count: i32,
-    pub created_at: DateTime<Utc>,
-    pub updated_at: DateTime<Utc>,
-}
+    #[test]
+    fn test_bulk_close_with_suppression_sends_no_emails() {
+        let open = Uuid::new_v4();
+        let closed = named_status("Resolved", true);
+        let candidates = vec![close_candidate(open, false), close_candidate(open, false), close_candidate(open, true)];
+        let mut ids: Vec<Uuid> = candidates.iter().map(|c| c.ticket_id).collect();
+        let missing = Uuid::new_v4();
+        ids.push(missing);
 
-/// Automation condition
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AutomationCondition {
-    pub field: String,
-    pub operator: String,
-    pub value: serde_json::Value,
-}
+        let outcome = bulk_close(ids, true).plan(&candidates, &closed, &StatusWorkflow::default());
 
-/// Automation action
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AutomationAction {
-    pub action_type: String,
-    pub params: serde_json::Value,
-}
+        // Both open tickets still move to the closed status, with a note
+        let closed_ids: Vec<Uuid> = outcome.closed.iter().map(|c| c.ticket_id).collect();
+        assert_eq!(closed_ids, vec![candidates[0].ticket_id, candidates[1].ticket_id]);
+        assert!(outcome.closed.iter().all(|c| !c.notify_customer && c.from_status_id == open));
+        assert_eq!(
+            outcome.closed[0].activity,
+            "Closed in bulk as Resolved: Upstream outage resolved (customer notifications suppressed)"
+        );
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let skipped: Vec<(Uuid, &str)> = outcome.skipped.iter().map(|s| (s.ticket_id, s.reason.as_str())).collect();
+        assert_eq!(skipped, vec![(candidates[2].ticket_id, "Already closed"), (missing, "Ticket not found")]);
+    }
 
     #[test]
-    fn test_ticket_source_from_str() {
-        assert_eq!(TicketSource::from_str("portal"), Some(TicketSource::Portal));
-        assert_eq!(TicketSource::from_str("email"), Some(TicketSource::Email));
-        assert_eq!(TicketSource::from_str("phone"), Some(TicketSource::Phone));
-        assert_eq!(TicketSource::from_str("api"), Some(TicketSource::Api));
-        assert_eq!(TicketSource::from_str("rmm"), Some(TicketSource::Rmm));
-        assert_eq!(TicketSource::from_str("invalid"), None);
+    fn test_bulk_close_notifies_contacts_unless_suppressed() {
+        let open = Uuid::new_v4();
+        let closed = named_status("Resolved", true);
+        let mut no_contact = close_candidate(open, false);
+        no_contact.has_contact = false;
+        let candidates = vec![close_candidate(open, false), no_contact];
+        let ids: Vec<Uuid> = candidates.iter().map(|c| c.ticket_id).collect();
+
+        let outcome = bulk_close(ids.clone(), false).plan(&candidates, &closed, &StatusWorkflow::default());
+        let notified: Vec<bool> = outcome.closed.iter().map(|c| c.notify_customer).collect();
+        assert_eq!(notified, vec![true, false]);
+        assert_eq!(outcome.closed[0].activity, "Closed in bulk as Resolved: Upstream outage resolved");
+
+        // A workflow that doesn't lead from the open status to the closed one
+        let workflow = StatusWorkflow::new(&[StatusTransition {
+            from_status_id: open,
+            to_status_id: Uuid::new_v4(),
+        }]);
+        let outcome = bulk_close(ids, false).plan(&candidates, &closed, &workflow);
+        assert!(outcome.closed.is_empty());
+        assert_eq!(outcome.skipped.len(), 2);
+    }
+
+    fn recent_ticket(title: &str, contact_id: Option<Uuid>, hours_ago: i64) -> RecentTicket {
+        RecentTicket {
+            id: Uuid::new_v4(),
+            ticket_number: "T000120".to_string(),
+            title: title.to_string(),
+            contact_id,
+            asset_id: None,
+            created_at: Utc::now() - chrono::Duration::hours(hours_ago),
+        }
+    }
+
+    fn history(same_category: i64, same_company: i64, open_tickets: i64) -> AssigneeHistory {
+        AssigneeHistory {
+            user_id: Uuid::new_v4(),
+            name: "Tech".to_string(),
+            same_asset: 0,
+            same_category,
+            same_company,
+            open_tickets,
+        }
     }
 
     #[test]
-    fn test_ticket_source_as_str() {
-        assert_eq!(TicketSource::Portal.as_str(), "portal");
-        assert_eq!(TicketSource::Email.as_str(), "email");
-        assert_eq!(TicketSource::Phone.as_str(), "phone");
-        assert_eq!(TicketSource::Rmm.as_str(), "rmm");
+    fn test_tech_with_similar_resolutions_ranks_first() {
+        let veteran = history(12, 6, 3);
+        let occasional = history(1, 1, 0);
+        let newcomer = history(0, 0, 0);
+
+        let ranked = rank_assignees(&[newcomer.clone(), occasional.clone(), veteran.clone()]);
+        let order: Vec<Uuid> = ranked.iter().map(|s| s.user_id).collect();
+        assert_eq!(order, [veteran.user_id, occasional.user_id]);
+        // Nothing to go on for the newcomer
+        assert!(!order.contains(&newcomer.user_id));
+
+        assert!(ranked[0].confidence > ranked[1].confidence);
+        assert!(ranked.iter().all(|s| (0.0..=1.0).contains(&s.confidence)));
+        assert_eq!(ranked[0].resolved_similar, 12);
     }
 
     #[test]
-    fn test_billing_status_from_str() {
-        assert_eq!(BillingStatus::from_str("not_billed"), Some(BillingStatus::NotBilled));
-        assert_eq!(BillingStatus::from_str("ready_to_bill"), Some(BillingStatus::ReadyToBill));
-        assert_eq!(BillingStatus::from_str("billed"), Some(BillingStatus::Billed));
-        assert_eq!(BillingStatus::from_str("unknown"), None);
+    fn test_current_load_breaks_near_ties() {
+        let busy = history(5, 0, 20);
+        let free = history(4, 0, 0);
+        let ranked = rank_assignees(&[busy.clone(), free.clone()]);
+        assert_eq!(ranked[0].user_id, free.user_id);
+
+        // Thin history gives low confidence even to the only candidate
+        let ranked = rank_assignees(&[history(0, 1, 0)]);
+        assert_eq!(ranked.len(), 1);
+        assert!(ranked[0].confidence < 0.5);
+        assert!(rank_assignees(&[]).is_empty());
     }
 
     #[test]
-    fn test_billing_status_as_str() {
-        assert_eq!(BillingStatus::NotBilled.as_str(), "not_billed");
-        assert_eq!(BillingStatus::ReadyToBill.as_str(), "ready_to_bill");
-        assert_eq!(BillingStatus::Billed.as_str(), "billed");
+    fn test_near_identical_recent_ticket_is_a_possible_duplicate() {
+        let contact_id = Uuid::new_v4();
+        let mut request = create_request(None);
+        request.title = "Printer on 2nd floor not printing".to_string();
+        request.contact_id = Some(contact_id);
+
+        let same = recent_ticket("Printer on 2nd floor is not printing", Some(contact_id), 2);
+        let different = recent_ticket("Password reset for new hire", Some(contact_id), 2);
+        let other_contact = recent_ticket("Printer on 2nd floor not printing", Some(Uuid::new_v4()), 2);
+        let stale = recent_ticket("Printer on 2nd floor not printing", Some(contact_id), 24 * 7);
+        let recent = [different, same.clone(), other_contact, stale];
+
+        let settings = TicketSettings::default();
+        let duplicates = request.possible_duplicates(&recent, &settings, Utc::now());
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].ticket_id, same.id);
+        assert!(duplicates[0].similarity > 0.8);
+
+        // A strict enough threshold lets it through, and a zero window turns
+        // detection off
+        let strict = TicketSettings { duplicate_similarity: 0.95, ..TicketSettings::default() };
+        assert!(request.possible_duplicates(&recent, &strict, Utc::now()).is_empty());
+        let off = TicketSettings { duplicate_window_hours: 0, ..TicketSettings::default() };
+        assert!(request.possible_duplicates(&recent, &off, Utc::now()).is_empty());
     }
 
     #[test]
-    fn test_note_type_from_str() {
-        assert_eq!(NoteType::from_str("internal"), Some(NoteType::Internal));
-        assert_eq!(NoteType::from_str("public"), Some(NoteType::Public));
-        assert_eq!(NoteType::from_str("resolution"), Some(NoteType::Resolution));
-        assert_eq!(NoteType::from_str("time_entry"), Some(NoteType::TimeEntry));
-        assert_eq!(NoteType::from_str("other"), None);
+    fn test_title_similarity_matches_pg_trgm() {
+        assert_eq!(title_similarity("word", "word"), 1.0);
+        assert_eq!(title_similarity("Word!", "word"), 1.0);
+        // pg_trgm: similarity('word', 'two words') = 0.36363637
+        assert!((title_similarity("word", "two words") - 4.0 / 11.0).abs() < 1e-6);
+        assert_eq!(title_similarity("VPN down", "Invoice question"), 0.0);
+        assert_eq!(title_similarity("", ""), 0.0);
+    }
+
+    fn error_fields(result: Result<(), AppError>) -> Vec<String> {
+        match result {
+            Err(AppError::Validation { errors, .. }) => errors.into_iter().map(|e| e.field).collect(),
+            other => panic!("expected validation error, got {:?}", other),
+        }
     }
 
     #[test]
-    fn test_note_type_as_str() {
-        assert_eq!(NoteType::Internal.as_str(), "internal");
-        assert_eq!(NoteType::Public.as_str(), "public");
-        assert_eq!(NoteType::Resolution.as_str(), "resolution");
+    fn test_incident_without_asset_fails_when_required() {
+        let incident = incident_type(&["asset_id"]);
+        let request = create_request(None);
+
+        let result = incident.check_required_fields(&TicketFieldValues::from_create(&request));
+        assert_eq!(error_fields(result), vec!["asset_id".to_string()]);
     }
 
     #[test]
-    fn test_automation_trigger_from_str() {
-        assert_eq!(AutomationTrigger::from_str("on_create"), Some(AutomationTrigger::OnCreate));
-        assert_eq!(AutomationTrigger::from_str("on_update"), Some(AutomationTrigger::OnUpdate));
-        assert_eq!(AutomationTrigger::from_str("on_sla_breach"), Some(AutomationTrigger::OnSlaBreach));
-        assert_eq!(AutomationTrigger::from_str("invalid"), None);
+    fn test_incident_passes_with_asset_or_without_rule() {
+        let request = create_request(Some(Uuid::new_v4()));
+        assert!(incident_type(&["asset_id"])
+            .check_required_fields(&TicketFieldValues::from_create(&request))
+            .is_ok());
+
+        let request = create_request(None);
+        assert!(incident_type(&[])
+            .check_required_fields(&TicketFieldValues::from_create(&request))
+            .is_ok());
     }
 
     #[test]
-    fn test_automation_trigger_as_str() {
-        assert_eq!(AutomationTrigger::OnCreate.as_str(), "on_create");
-        assert_eq!(AutomationTrigger::OnUpdate.as_str(), "on_update");
-        assert_eq!(AutomationTrigger::OnSlaBreach.as_str(), "on_sla_breach");
+    fn test_required_fields_on_update_and_custom_fields() {
+        let change = incident_type(&["scheduled_window", "custom_fields.change_number", "unknown"]);
+        let mut ticket = sample_ticket();
+        ticket.scheduled_start = Some(Utc::now());
+
+        let update: UpdateTicketRequest = serde_json::from_value(serde_json::json!({})).unwrap();
+        assert_eq!(
+            error_fields(change.check_required_fields(&TicketFieldValues::from_update(&ticket, &update))),
+            vec!["scheduled_end".to_string(), "custom_fields.change_number".to_string()]
+        );
+
+        let update: UpdateTicketRequest = serde_json::from_value(serde_json::json!({
+            "scheduled_end": Utc::now() + chrono::Duration::hours(2),
+            "custom_fields": { "change_number": "CHG-1042" },
+        }))
+        .unwrap();
+        assert!(change
+            .check_required_fields(&TicketFieldValues::from_update(&ticket, &update))
+            .is_ok());
     }
 
     #[test]
-    fn test_ticket_sla_status() {
-        // Create a test ticket
-        let mut ticket = Ticket {
+    fn test_status_durations_accumulate_across_visits() {
+        let start = Utc::now() - chrono::Duration::hours(10);
+        let hours = |h: i64| start + chrono::Duration::hours(h);
+        let ticket_id = Uuid::new_v4();
+        let open = Uuid::new_v4();
+        let pending = Uuid::new_v4();
+        let stay = |status_id: Uuid, name: &str, entered: i64, left: Option<i64>| StatusHistoryEntry {
+            id: Uuid::new_v4(),
+            ticket_id,
+            status_id,
+            status_name: name.to_string(),
+            changed_by_id: None,
+            entered_at: hours(entered),
+            left_at: left.map(hours),
+        };
+
+        // Open 2h -> Pending Customer 5h -> Open again, still open after 3h
+        let history = vec![
+            stay(open, "Open", 7, None),
+            stay(open, "Open", 0, Some(2)),
+            stay(pending, "Pending Customer", 2, Some(7)),
+        ];
+
+        let durations = StatusDuration::from_history(&history, hours(10));
+        assert_eq!(durations.len(), 2);
+        assert_eq!(durations[0].status_name, "Open");
+        assert_eq!(durations[0].seconds, 5 * 3600);
+        assert_eq!(durations[0].visits, 2);
+        assert!(durations[0].is_current);
+        assert_eq!(durations[1].status_name, "Pending Customer");
+        assert_eq!(durations[1].seconds, 5 * 3600);
+        assert_eq!(durations[1].visits, 1);
+        assert!(!durations[1].is_current);
+    }
+
+    #[test]
+    fn test_list_views() {
+        use crate::utils::pagination::ResponseView;
+
+        let mut ticket = sample_ticket();
+        ticket.description = Some("Printer on the 3rd floor jams on every job".to_string());
+        ticket.tags = vec!["printer".to_string()];
+
+        let summary: TicketListItem = ResponseView::Summary.item(ticket.clone());
+        let summary = serde_json::to_value(summary).unwrap();
+        let keys: Vec<&str> = summary.as_object().unwrap().keys().map(String::as_str).collect();
+        assert_eq!(keys.len(), 6);
+        assert_eq!(summary["ticket_number"], "T000001");
+        assert_eq!(summary["title"], "Sample");
+        assert!(summary.get("updated_at").is_some());
+        for heavy in ["description", "tags", "custom_fields", "priority", "sla_status"] {
+            assert!(summary.get(heavy).is_none(), "summary includes {}", heavy);
+        }
+
+        let full: TicketListItem = ResponseView::Full.item(ticket);
+        let full = serde_json::to_value(full).unwrap();
+        assert_eq!(full["description"], "Printer on the 3rd floor jams on every job");
+        assert_eq!(full["tags"], serde_json::json!(["printer"]));
+        assert!(full.get("priority").is_some());
+        assert!(full.get("sla_status").is_some());
+    }
+
+    fn tag_rule(match_type: TagRuleMatch, pattern: &str, tag: &str) -> TagRule {
+        TagRule {
             id: Uuid::new_v4(),
             tenant_id: Uuid::new_v4(),
-            ticket_number: "TKT-001".to_string(),
-            title: "Test Ticket".to_string(),
-            description: Some("Test description".to_string()),
-            status_id: Uuid::new_v4(),
-            priority_id: Uuid::new_v4(),
-            type_id: None,
-            category_id: None,
-            subcategory_id: None,
-            queue_id: Uuid::new_v4(),
-            source: TicketSource::Portal,
-            company_id: Uuid::new_v4(),
-            contact_id: None,
-            site_id: None,
-            assigned_to_id: None,
-            team_id: None,
-            parent_ticket_id: None,
-            contract_id: None,
-            sla_id: None,
-            sla_due_date: None,
-            first_response_due: None,
-            first_response_at: None,
-            resolution_due: None,
-            resolved_at: None,
-            closed_at: None,
-            scheduled_start: None,
-            scheduled_end: None,
-            estimated_hours: None,
-            actual_hours: 0.0,
-            is_billable: false,
-            billing_status: BillingStatus::NotBilled,
-            asset_id: None,
-            custom_fields: serde_json::json!({}),
-            tags: vec![],
-            created_by_id: Uuid::new_v4(),
-            last_updated_by_id: None,
+            match_type,
+            pattern: pattern.to_string(),
+            tag: tag.to_string(),
+            is_active: true,
             created_at: Utc::now(),
             updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_tag_rules() {
+        let rules = vec![
+            tag_rule(TagRuleMatch::Keyword, "printer", "printer"),
+            tag_rule(TagRuleMatch::Regex, r"vpn|remote access", "vpn"),
+        ];
+
+        let tagged = TicketTags::resolve(&[], &rules, "Printer on 3rd floor jammed", None);
+        assert_eq!(tagged.tags, vec!["printer".to_string()]);
+        assert_eq!(tagged.rule_tags, vec!["printer".to_string()]);
+
+        let untagged = TicketTags::resolve(&[], &rules, "Password reset", Some("Locked out of email"));
+        assert!(untagged.tags.is_empty());
+
+        // Keywords match whole words only
+        assert!(!rules[0].matches("Printers are fine", None));
+        // The description counts too
+        assert!(rules[1].matches("Can't connect", Some("The VPN drops every hour")));
+
+        let mut inactive = tag_rule(TagRuleMatch::Keyword, "outage", "outage");
+        inactive.is_active = false;
+        assert!(TicketTags::resolve(&[], &[inactive], "Outage", None).tags.is_empty());
+    }
+
+    #[test]
+    fn test_rule_tags_kept_apart_from_manual_tags() {
+        let rules = vec![tag_rule(TagRuleMatch::Keyword, "printer", "printer")];
+        let manual = vec!["vip".to_string(), "printer".to_string()];
+
+        // Echoing a rule tag back in an edit doesn't make it a manual tag
+        let tags = TicketTags::resolve(&manual, &rules, "Printer offline", None);
+        assert_eq!(tags.tags, vec!["vip".to_string(), "printer".to_string()]);
+        assert_eq!(tags.rule_tags, vec!["printer".to_string()]);
+
+        let mut ticket = sample_ticket();
+        ticket.tags = tags.tags;
+        ticket.rule_tags = tags.rule_tags;
+        assert_eq!(TicketTags::manual(&ticket), vec!["vip".to_string()]);
+
+        // Once the text no longer matches, the rule tag drops off
+        let retitled = TicketTags::resolve(&TicketTags::manual(&ticket), &rules, "Scanner offline", None);
+        assert_eq!(retitled.tags, vec!["vip".to_string()]);
+        assert!(retitled.rule_tags.is_empty());
+    }
+
+    #[test]
+    fn test_create_tag_rule_check() {
+        let mut request = CreateTagRuleRequest {
+            match_type: TagRuleMatch::Regex,
+            pattern: "(unclosed".to_string(),
+            tag: "broken".to_string(),
         };
+        assert!(request.check().is_err());
 
-        // Test no SLA due date (should be NotApplicable)
-        assert_eq!(ticket.sla_status(), SlaStatus::NotApplicable);
+        // Keywords are escaped, so the same text is fine
+        request.match_type = TagRuleMatch::Keyword;
+        assert!(request.check().is_ok());
+    }
 
-        // Test on track (due date in future, more than 2 hours)
-        ticket.sla_due_date = Some(Utc::now() + chrono::Duration::hours(3));
-        assert_eq!(ticket.sla_status(), SlaStatus::OnTrack);
+    /// A resolved ticket's details, as printed documents and share links use them
+    fn sample_document_details(now: DateTime<Utc>) -> TicketDocumentDetails {
+        TicketDocumentDetails {
+            ticket_number: "T000042".to_string(),
+            title: "Printer offline".to_string(),
+            description: None,
+            status: "Resolved".to_string(),
+            priority: "High".to_string(),
+            company_name: "Contoso Ltd".to_string(),
+            contact_name: None,
+            assigned_to_name: Some("Sam Tech".to_string()),
+            resolution_code: None,
+            created_at: now - chrono::Duration::hours(1),
+            resolved_at: Some(now),
+            closed_at: None,
+        }
+    }
 
-        // Test warning (due date in less than 2 hours)
-        ticket.sla_due_date = Some(Utc::now() + chrono::Duration::hours(1));
-        assert_eq!(ticket.sla_status(), SlaStatus::Warning);
+    /// One note of each kind, newest first as get_ticket_notes returns them:
+    /// resolution, time entry, internal and public, 10 to 40 minutes ago
+    fn sample_document_notes(now: DateTime<Utc>) -> Vec<TicketNote> {
+        let note = |note_type: NoteType, content: &str, minutes_ago: i64| TicketNote {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::nil(),
+            ticket_id: Uuid::nil(),
+            note_type,
+            content: content.to_string(),
+            content_html: None,
+            is_email_sent: false,
+            email_sent_at: None,
+            counts_as_first_response: true,
+            created_by_id: Some(Uuid::nil()),
+            created_by_name: Some("Sam Tech".to_string()),
+            created_at: now - chrono::Duration::minutes(minutes_ago),
+            updated_at: now,
+        };
+        vec![
+            note(NoteType::Resolution, "Replaced the toner", 10),
+            note(NoteType::TimeEntry, "30 minutes on site", 20),
+            note(NoteType::Internal, "Customer keeps unplugging it", 30),
+            note(NoteType::Public, "On our way", 40),
+        ]
+    }
 
-        // Test breached (due date in past)
-        ticket.sla_due_date = Some(Utc::now() - chrono::Duration::hours(2));
-        assert_eq!(ticket.sla_status(), SlaStatus::Breached);
+    #[test]
+    fn test_customer_document_excludes_internal_notes() {
+        let now = Utc::now();
+        let notes = sample_document_notes(now);
+        let details = sample_document_details(now);
+        let time_entries = vec![TicketDocumentTimeEntry {
+            date: now.date_naive(),
+            user_name: "Sam Tech".to_string(),
+            duration_minutes: 30,
+            is_billable: true,
+            notes: Some("Toner replacement".to_string()),
+            internal_notes: Some("Used the spare from stock".to_string()),
+        }];
+        let branding = ResolvedBranding::default();
 
-        // Test closed ticket (should be NotApplicable)
-        ticket.closed_at = Some(Utc::now());
-        assert_eq!(ticket.sla_status(), SlaStatus::NotApplicable);
+        let customer = TicketDocument::new(
+            details.clone(),
+            notes.clone(),
+            time_entries.clone(),
+            DocumentAudience::Customer,
+            &branding,
+        );
+        let contents: Vec<&str> = customer.notes.iter().map(|note| note.content.as_str()).collect();
+        assert_eq!(contents, ["On our way", "Replaced the toner"]);
+        assert_eq!(customer.time_entries[0].internal_notes, None);
+        assert_eq!(customer.time_entries[0].notes.as_deref(), Some("Toner replacement"));
+        assert_eq!(customer.total_minutes, 30);
+
+        // Nothing internal leaks through the serialized document either
+        let printed = serde_json::to_string(&customer).unwrap();
+        assert!(!printed.contains("unplugging"));
+        assert!(!printed.contains("spare from stock"));
+
+        let internal = TicketDocument::new(details, notes, time_entries, DocumentAudience::Internal, &branding);
+        assert_eq!(internal.notes.len(), 4);
+        assert_eq!(internal.notes[0].content, "On our way");
+        assert!(internal.time_entries[0].internal_notes.is_some());
+    }
+
+    fn sample_share_link(expires_in: chrono::Duration) -> TicketShareLink {
+        let now = Utc::now();
+        TicketShareLink {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::nil(),
+            ticket_id: Uuid::nil(),
+            token: "token".to_string(),
+            expires_at: now + expires_in,
+            created_by_id: None,
+            revoked_at: None,
+            created_at: now,
+        }
+    }
+
+    #[test]
+    fn test_share_link_renders_public_timeline() {
+        let now = Utc::now();
+        let link = sample_share_link(chrono::Duration::hours(24));
+        assert!(link.ensure_active(now).is_ok());
+
+        let notes = sample_document_notes(now);
+        let history = vec![StatusHistoryEntry {
+            id: Uuid::new_v4(),
+            ticket_id: Uuid::nil(),
+            status_id: Uuid::new_v4(),
+            status_name: "Resolved".to_string(),
+            changed_by_id: None,
+            entered_at: now - chrono::Duration::minutes(5),
+            left_at: None,
+        }];
+        let details = sample_document_details(now);
+
+        let timeline = TicketTimeline::public(&details, &notes, &history, &link);
+        assert_eq!(timeline.ticket_number, "T000042");
+        assert_eq!(timeline.expires_at, link.expires_at);
+        assert_eq!(
+            timeline.entries,
+            vec![
+                TimelineEntry::Note {
+                    at: now - chrono::Duration::minutes(40),
+                    author: Some("Sam Tech".to_string()),
+                    content: "On our way".to_string(),
+                },
+                TimelineEntry::Note {
+                    at: now - chrono::Duration::minutes(10),
+                    author: Some("Sam Tech".to_string()),
+                    content: "Replaced the toner".to_string(),
+                },
+                TimelineEntry::Status {
+                    at: now - chrono::Duration::minutes(5),
+                    status: "Resolved".to_string(),
+                },
+            ]
+        );
+
+        let shared = serde_json::to_string(&timeline).unwrap();
+        assert!(!shared.contains("unplugging"));
+        assert!(!shared.contains("minutes on site"));
+    }
+
+    #[test]
+    fn test_expired_or_revoked_share_link_is_not_found() {
+        let now = Utc::now();
+
+        let expired = sample_share_link(chrono::Duration::hours(-1));
+        assert!(matches!(expired.ensure_active(now), Err(AppError::NotFound(_))));
+
+        let revoked = TicketShareLink {
+            revoked_at: Some(now),
+            ..sample_share_link(chrono::Duration::hours(24))
+        };
+        assert!(matches!(revoked.ensure_active(now), Err(AppError::NotFound(_))));
+
+        // The token never leaves the server in a serialized link
+        let link = serde_json::to_string(&sample_share_link(chrono::Duration::hours(24))).unwrap();
+        assert!(!link.contains("token"));
+    }
+
+    #[test]
+    fn test_saved_view_export_has_the_views_tickets() {
+        use crate::modules::saved_views::{SavedView, SavedViewEntity};
+
+        let now = Utc::now();
+        let technician = Uuid::new_v4();
+        let view = SavedView {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::nil(),
+            user_id: technician,
+            entity: SavedViewEntity::Ticket,
+            name: "My overdue tickets".to_string(),
+            filters: serde_json::json!({ "assigned_to_id": technician, "is_overdue": true }),
+            is_shared: false,
+            created_at: now,
+            updated_at: now,
+        };
+        let filter = view.apply(&TicketFilter::default()).unwrap();
+
+        let fixture = |number: &str, assigned_to_id: Option<Uuid>, due_in_hours: i64, closed: bool| Ticket {
+            ticket_number: number.to_string(),
+            title: format!("Printer on floor {}", number),
+            assigned_to_id,
+            sla_due_date: Some(now + chrono::Duration::hours(due_in_hours)),
+            closed_at: closed.then_some(now),
+            ..sample_ticket()
+        };
+        let tickets = vec![
+            fixture("T000001", Some(technician), -2, false),
+            fixture("T000002", Some(technician), 4, false),
+            fixture("T000003", Some(Uuid::new_v4()), -2, false),
+            fixture("T000004", Some(technician), -2, true),
+            fixture("T000005", Some(technician), -30, false),
+            fixture("T000006", None, -2, false),
+        ];
+
+        // What list_tickets' WHERE clause selects for these fields
+        let matches = |ticket: &Ticket| {
+            filter.assigned_to_id.map_or(true, |id| ticket.assigned_to_id == Some(id))
+                && (filter.is_overdue != Some(true)
+                    || (ticket.closed_at.is_none() && ticket.sla_due_date.is_some_and(|due| due < now)))
+        };
+
+        let mut names = TicketExportNames::default();
+        names.users.insert(technician, "Sam Tech".to_string());
+        let mut csv = ticket_csv_header();
+        for ticket in tickets.iter().filter(|ticket| matches(ticket)) {
+            csv.push_str(&ticket_csv_row(ticket, &names));
+        }
+
+        let lines: Vec<&str> = csv.split_terminator("\r\n").collect();
+        assert_eq!(lines[0].split(',').count(), TICKET_CSV_COLUMNS.len());
+        let numbers: Vec<&str> = lines[1..].iter().map(|line| line.split(',').next().unwrap()).collect();
+        assert_eq!(numbers, vec!["T000001", "T000005"]);
+        assert!(lines[1].contains(",Sam Tech,"));
+    }
+
+    #[test]
+    fn test_csv_fields_are_quoted_and_defused() {
+        let ticket = Ticket {
+            title: "Outlook says \"no\", again\nand again".to_string(),
+            tags: vec!["=HYPERLINK(\"x\")".to_string()],
+            ..sample_ticket()
+        };
+        let row = ticket_csv_row(&ticket, &TicketExportNames::default());
+        assert!(row.contains("\"Outlook says \"\"no\"\", again\nand again\""));
+        assert!(row.ends_with("\"'=HYPERLINK(\"\"x\"\")\"\r\n"));
     }
 }
@@ -1,7 +1,9 @@
 //! Ticket API routes
 
 use axum::{
-    extract::{Path, Query, State},
+    extract::{DefaultBodyLimit, Multipart, OriginalUri, Path, Query, State},
+    http::header,
+    response::{Html, IntoResponse, Response},
     routing::{delete, get, post, put},
     Json, Router,
 };
@@ -10,153 +12,216 @@ use uuid::Uuid;
 use validator::Validate;
 
 use super::{
-    CreateNoteRequest, CreateTicketRequest, TicketFilter, TicketNoteResponse, TicketPriority,
-    TicketQueue, TicketResponse, TicketService, TicketStatus, TicketType, UpdateTicketRequest,
+    AssigneeSuggestion, AttachmentService, BulkCloseOutcome, BulkCloseRequest, CreateNoteRequest,
+    CreateQueueEmailAddressRequest, CreateShareLinkRequest, CreateTagRuleRequest, CreateTicketRequest, CreatedShareLink,
+    CsatLinkParams, CsatRating, CsatResponseRequest, CsatService, DuplicateCandidate, InboundEmail, InboundEmailOutcome,
+    InboundEmailProcessor, LinkTicketRequest, MergeTicketsRequest, QueueEmailAddress, ReassignRequest, Reassignment,
+    RelatedTicket, ReopenTicketRequest, ResolutionCode, SetStatusTransitionsRequest, SnoozeTicketRequest,
+    StatusDuration, StatusTransition, TagRule, TicketAttachment, TicketAttachmentResponse, TicketDocument,
+    TicketDocumentParams, TicketFilter, TicketLinkResponse, TicketLinkType, TicketListItem, TicketNoteResponse,
+    TicketPriority, TicketQueue, TicketResponse, TicketService, TicketShareLink, TicketStatus, TicketTimeline,
+    TicketType, UpdateTicketRequest, MAX_ATTACHMENT_BYTES,
 };
-use crate::modules::auth::RequireAuth;
-use crate::utils::error::AppResult;
-use crate::utils::pagination::{PaginatedResponse, PaginationParams};
+use crate::api::tenant_resolution::CurrentTenant;
+use crate::modules::auth::{RequireAdmin, RequireAuth};
+use crate::modules::saved_views::{SavedViewParams, SavedViewService};
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::pagination::{PaginatedJson, PaginatedResponse, PaginationParams, ViewParams};
+use crate::utils::public_page::{self, FormOrJson};
+use crate::utils::validation::ValidatedJson;
 
 #[derive(Clone)]
 pub struct TicketRouterState {
     pub ticket_service: Arc<TicketService>,
+    pub attachment_service: Arc<AttachmentService>,
+    pub inbound_email_processor: Arc<InboundEmailProcessor>,
+    pub saved_view_service: Arc<SavedViewService>,
 }
 
 /// Create the ticket router
-pub fn ticket_routes(ticket_service: TicketService) -> Router {
+pub fn ticket_routes(
+    ticket_service: TicketService,
+    attachment_service: AttachmentService,
+    inbound_email_processor: InboundEmailProcessor,
+    saved_view_service: SavedViewService,
+) -> Router {
     let state = TicketRouterState {
         ticket_service: Arc::new(ticket_service),
+        attachment_service: Arc::new(attachment_service),
+        inbound_email_processor: Arc::new(inbound_email_processor),
+        saved_view_service: Arc::new(saved_view_service),
     };
 
     Router::new()
         // Tickets
         .route("/", get(list_tickets))
         .route("/", post(create_ticket))
+        .route("/duplicates", post(find_possible_duplicates))
+        .route("/bulk-close", post(bulk_close))
+        .route("/views/:view_id/export", get(export_saved_view))
         .route("/:ticket_id", get(get_ticket))
         .route("/:ticket_id", put(update_ticket))
         .route("/:ticket_id/assign", post(assign_ticket))
+        .route("/:ticket_id/claim", post(claim_ticket))
+        .route("/:ticket_id/assignee-suggestions", get(suggest_assignee))
+        .route("/:ticket_id/reopen", post(reopen_ticket))
+        .route("/:ticket_id/snooze", post(snooze_ticket))
+        .route("/:ticket_id/snooze", delete(unsnooze_ticket))
         .route("/:ticket_id/notes", get(get_ticket_notes))
         .route("/:ticket_id/notes", post(add_note))
+        .route("/:ticket_id/attachments", get(list_attachments))
+        .route(
+            "/:ticket_id/attachments",
+            post(upload_attachment).layer(DefaultBodyLimit::max(MAX_ATTACHMENT_BYTES + 64 * 1024)),
+        )
+        .route("/:ticket_id/attachments/:attachment_id", get(download_attachment))
+        .route("/:ticket_id/status-durations", get(get_status_durations))
+        .route("/:ticket_id/document", get(get_ticket_document))
+        .route("/:ticket_id/links", get(get_related_tickets))
+        .route("/:ticket_id/links", post(link_ticket))
+        .route("/:ticket_id/links/:link_id", delete(unlink_ticket))
+        .route("/:ticket_id/merge", post(merge_tickets))
+        .route("/:ticket_id/share-links", get(list_share_links))
+        .route("/:ticket_id/share-links", post(create_share_link))
+        .route("/:ticket_id/share-links/:link_id", delete(revoke_share_link))
+        // Offboarding: move everything off a departing technician
+        .route("/assignees/:user_id/reassign", post(reassign_all))
         // Configuration
         .route("/statuses", get(get_statuses))
+        .route("/status-transitions", get(list_status_transitions))
+        .route("/statuses/:status_id/transitions", put(set_status_transitions))
         .route("/priorities", get(get_priorities))
         .route("/queues", get(get_queues))
         .route("/types", get(get_types))
+        .route("/resolution-codes", get(get_resolution_codes))
+        .route("/queue-addresses", get(list_queue_addresses))
+        .route("/queue-addresses", post(create_queue_address))
+        .route("/queue-addresses/:address_id", delete(delete_queue_address))
+        .route("/tag-rules", get(list_tag_rules))
+        .route("/tag-rules", post(create_tag_rule))
+        .route("/tag-rules/:rule_id", delete(delete_tag_rule))
+        // Inbound email, posted by the mailbox poller
+        .route("/inbound-email", post(process_inbound_email))
         .with_state(state)
 }
 
+#[derive(Clone)]
+pub struct CsatRouterState {
+    pub csat_service: Arc<CsatService>,
+}
+
+/// Create the public CSAT survey router. Requests are authorized by the
+/// survey token, not a login.
+pub fn csat_routes(csat_service: CsatService) -> Router {
+    let state = CsatRouterState {
+        csat_service: Arc::new(csat_service),
+    };
+
+    Router::new()
+        .route("/:token", get(csat_response_page))
+        .route("/:token", post(submit_csat_response))
+        .with_state(state)
+}
+
+/// Create the public shared-ticket router. Requests are authorized by the
+/// share link token, not a login.
+pub fn shared_ticket_routes(ticket_service: TicketService) -> Router {
+    let state = SharedTicketRouterState {
+        ticket_service: Arc::new(ticket_service),
+    };
+
+    Router::new()
+        .route("/:token", get(get_shared_ticket))
+        .with_state(state)
+}
+
+#[derive(Clone)]
+pub struct SharedTicketRouterState {
+    pub ticket_service: Arc<TicketService>,
+}
+
 async fn list_tickets(
     State(state): State<TicketRouterState>,
     RequireAuth(user): RequireAuth,
     Query(filter): Query<TicketFilter>,
+    Query(saved_view): Query<SavedViewParams>,
     Query(pagination): Query<PaginationParams>,
-) -> AppResult<Json<PaginatedResponse<TicketResponse>>> {
+    Query(ViewParams { view }): Query<ViewParams>,
+    OriginalUri(uri): OriginalUri,
+) -> AppResult<PaginatedJson<TicketListItem>> {
+    let mut filter = state
+        .saved_view_service
+        .resolve_filter(&user, &saved_view, filter)
+        .await?;
+    filter.scope = state.ticket_service.ticket_scope(&user).await?;
     let (tickets, total) = state
         .ticket_service
         .list_tickets(user.tenant_id, &filter, &pagination)
         .await?;
 
-    // Convert to response format with related data
-    let responses: Vec<TicketResponse> = tickets
-        .into_iter()
-        .map(|t| {
-            let sla_status = t.sla_status();
-            TicketResponse {
-                id: t.id,
-                ticket_number: t.ticket_number,
-                title: t.title,
-                description: t.description,
-                status: super::TicketStatusSummary {
-                    id: t.status_id,
-                    name: String::new(), // Would be joined from DB
-                    color: String::new(),
-                    is_closed: false,
-                },
-                priority: super::TicketPrioritySummary {
-                    id: t.priority_id,
-                    name: String::new(),
-                    color: String::new(),
-                },
-                type_name: None,
-                category_name: None,
-                queue_name: String::new(),
-                source: t.source,
-                company_id: t.company_id,
-                company_name: String::new(),
-                contact_id: t.contact_id,
-                contact_name: None,
-                assigned_to_id: t.assigned_to_id,
-                assigned_to_name: None,
-                sla_due_date: t.sla_due_date,
-                sla_status,
-                is_billable: t.is_billable,
-                billing_status: t.billing_status,
-                estimated_hours: t.estimated_hours,
-                actual_hours: t.actual_hours,
-                tags: t.tags,
-                created_by_name: String::new(),
-                created_at: t.created_at,
-                updated_at: t.updated_at,
-            }
-        })
-        .collect();
+    let responses: Vec<TicketListItem> = tickets.into_iter().map(|ticket| view.item(ticket)).collect();
 
-    let response = PaginatedResponse::from_params(responses, &pagination, total);
+    let response = PaginatedResponse::from_total(responses, &pagination, total);
 
-    Ok(Json(response))
+    Ok(response.with_links(&uri))
+}
+
+/// Every ticket in a saved view as a CSV download. Views the user can't
+/// see read as missing.
+async fn export_saved_view(
+    State(state): State<TicketRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(view_id): Path<Uuid>,
+) -> AppResult<impl IntoResponse> {
+    let view = state.saved_view_service.get_view(&user, view_id).await?;
+    let mut filter = view.apply(&TicketFilter::default())?;
+    filter.scope = state.ticket_service.ticket_scope(&user).await?;
+    let csv = state
+        .ticket_service
+        .export_tickets_csv(user.tenant_id, &filter)
+        .await?;
+
+    let disposition = format!(
+        "attachment; filename=\"{}.csv\"",
+        view.name.replace(['"', '\\', '\r', '\n', '/'], "_")
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        csv,
+    ))
 }
 
 async fn create_ticket(
     State(state): State<TicketRouterState>,
     RequireAuth(user): RequireAuth,
-    Json(request): Json<CreateTicketRequest>,
+    ValidatedJson(request): ValidatedJson<CreateTicketRequest>,
 ) -> AppResult<Json<TicketResponse>> {
-    request.validate()?;
-
     let ticket = state
         .ticket_service
         .create_ticket(user.tenant_id, user.id, &request)
         .await?;
 
-    // Convert to response
-    let sla_status = ticket.sla_status();
-    Ok(Json(TicketResponse {
-        id: ticket.id,
-        ticket_number: ticket.ticket_number,
-        title: ticket.title,
-        description: ticket.description,
-        status: super::TicketStatusSummary {
-            id: ticket.status_id,
-            name: String::new(),
-            color: String::new(),
-            is_closed: false,
-        },
-        priority: super::TicketPrioritySummary {
-            id: ticket.priority_id,
-            name: String::new(),
-            color: String::new(),
-        },
-        type_name: None,
-        category_name: None,
-        queue_name: String::new(),
-        source: ticket.source,
-        company_id: ticket.company_id,
-        company_name: String::new(),
-        contact_id: ticket.contact_id,
-        contact_name: None,
-        assigned_to_id: ticket.assigned_to_id,
-        assigned_to_name: None,
-        sla_due_date: ticket.sla_due_date,
-        sla_status,
-        is_billable: ticket.is_billable,
-        billing_status: ticket.billing_status,
-        estimated_hours: ticket.estimated_hours,
-        actual_hours: ticket.actual_hours,
-        tags: ticket.tags,
-        created_by_name: user.full_name(),
-        created_at: ticket.created_at,
-        updated_at: ticket.updated_at,
-    }))
+    let mut response = state.ticket_service.ticket_response(ticket).await?;
+    response.created_by_name = user.full_name();
+    Ok(Json(response))
+}
+
+/// Open tickets that look like the one about to be created, to warn before
+/// creating it
+async fn find_possible_duplicates(
+    State(state): State<TicketRouterState>,
+    RequireAuth(user): RequireAuth,
+    ValidatedJson(request): ValidatedJson<CreateTicketRequest>,
+) -> AppResult<Json<Vec<DuplicateCandidate>>> {
+    let duplicates = state
+        .ticket_service
+        .find_possible_duplicates(user.tenant_id, &request)
+        .await?;
+
+    Ok(Json(duplicates))
 }
 
 async fn get_ticket(
@@ -169,97 +234,21 @@ async fn get_ticket(
         .get_ticket(user.tenant_id, ticket_id)
         .await?;
 
-    let sla_status = ticket.sla_status();
-    Ok(Json(TicketResponse {
-        id: ticket.id,
-        ticket_number: ticket.ticket_number,
-        title: ticket.title,
-        description: ticket.description,
-        status: super::TicketStatusSummary {
-            id: ticket.status_id,
-            name: String::new(),
-            color: String::new(),
-            is_closed: false,
-        },
-        priority: super::TicketPrioritySummary {
-            id: ticket.priority_id,
-            name: String::new(),
-            color: String::new(),
-        },
-        type_name: None,
-        category_name: None,
-        queue_name: String::new(),
-        source: ticket.source,
-        company_id: ticket.company_id,
-        company_name: String::new(),
-        contact_id: ticket.contact_id,
-        contact_name: None,
-        assigned_to_id: ticket.assigned_to_id,
-        assigned_to_name: None,
-        sla_due_date: ticket.sla_due_date,
-        sla_status,
-        is_billable: ticket.is_billable,
-        billing_status: ticket.billing_status,
-        estimated_hours: ticket.estimated_hours,
-        actual_hours: ticket.actual_hours,
-        tags: ticket.tags,
-        created_by_name: String::new(),
-        created_at: ticket.created_at,
-        updated_at: ticket.updated_at,
-    }))
+    Ok(Json(state.ticket_service.ticket_response(ticket).await?))
 }
 
 async fn update_ticket(
     State(state): State<TicketRouterState>,
     RequireAuth(user): RequireAuth,
     Path(ticket_id): Path<Uuid>,
-    Json(request): Json<UpdateTicketRequest>,
+    ValidatedJson(request): ValidatedJson<UpdateTicketRequest>,
 ) -> AppResult<Json<TicketResponse>> {
-    request.validate()?;
-
     let ticket = state
         .ticket_service
         .update_ticket(user.tenant_id, ticket_id, user.id, &request)
         .await?;
 
-    let sla_status = ticket.sla_status();
-    Ok(Json(TicketResponse {
-        id: ticket.id,
-        ticket_number: ticket.ticket_number,
-        title: ticket.title,
-        description: ticket.description,
-        status: super::TicketStatusSummary {
-            id: ticket.status_id,
-            name: String::new(),
-            color: String::new(),
-            is_closed: false,
-        },
-        priority: super::TicketPrioritySummary {
-            id: ticket.priority_id,
-            name: String::new(),
-            color: String::new(),
-        },
-        type_name: None,
-        category_name: None,
-        queue_name: String::new(),
-        source: ticket.source,
-        company_id: ticket.company_id,
-        company_name: String::new(),
-        contact_id: ticket.contact_id,
-        contact_name: None,
-        assigned_to_id: ticket.assigned_to_id,
-        assigned_to_name: None,
-        sla_due_date: ticket.sla_due_date,
-        sla_status,
-        is_billable: ticket.is_billable,
-        billing_status: ticket.billing_status,
-        estimated_hours: ticket.estimated_hours,
-        actual_hours: ticket.actual_hours,
-        tags: ticket.tags,
-        created_by_name: String::new(),
-        created_at: ticket.created_at,
-        updated_at: ticket.updated_at,
-    }))
+    Ok(Json(state.ticket_service.ticket_response(ticket).await?))
 }
 
 #[derive(serde::Deserialize)]
@@ -278,44 +267,188 @@ async fn assign_ticket(
         .assign_ticket(user.tenant_id, ticket_id, request.assigned_to_id, user.id)
         .await?;
 
-    let sla_status = ticket.sla_status();
-    Ok(Json(TicketResponse {
-        id: ticket.id,
-        ticket_number: ticket.ticket_number,
-        title: ticket.title,
-        description: ticket.description,
-        status: super::TicketStatusSummary {
-            id: ticket.status_id,
-            name: String::new(),
-            color: String::new(),
-            is_closed: false,
-        },
-        priority: super::TicketPrioritySummary {
-            id: ticket.priority_id,
-            name: String::new(),
-            color: String::new(),
-        },
-        type_name: None,
-        category_name: None,
-        queue_name: String::new(),
-        source: ticket.source,
-        company_id: ticket.company_id,
-        company_name: String::new(),
-        contact_id: ticket.contact_id,
-        contact_name: None,
-        assigned_to_id: ticket.assigned_to_id,
-        assigned_to_name: None,
-        sla_due_date: ticket.sla_due_date,
-        sla_status,
-        is_billable: ticket.is_billable,
-        billing_status: ticket.billing_status,
-        estimated_hours: ticket.estimated_hours,
-        actual_hours: ticket.actual_hours,
-        tags: ticket.tags,
-        created_by_name: String::new(),
-        created_at: ticket.created_at,
-        updated_at: ticket.updated_at,
-    }))
+    Ok(Json(state.ticket_service.ticket_response(ticket).await?))
+}
+
+/// Assign the ticket to the caller if nobody has it yet
+async fn claim_ticket(
+    State(state): State<TicketRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(ticket_id): Path<Uuid>,
+) -> AppResult<Json<TicketResponse>> {
+    let ticket = state
+        .ticket_service
+        .claim(user.tenant_id, ticket_id, user.id)
+        .await?;
+
+    Ok(Json(state.ticket_service.ticket_response(ticket).await?))
+}
+
+/// Technicians who usually handle tickets like this one, best first
+async fn suggest_assignee(
+    State(state): State<TicketRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(ticket_id): Path<Uuid>,
+) -> AppResult<Json<Vec<AssigneeSuggestion>>> {
+    let ticket = state.ticket_service.get_ticket(user.tenant_id, ticket_id).await?;
+    let suggestions = state
+        .ticket_service
+        .suggest_assignee(user.tenant_id, &ticket)
+        .await?;
+
+    Ok(Json(suggestions))
+}
+
+async fn reopen_ticket(
+    State(state): State<TicketRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(ticket_id): Path<Uuid>,
+    Json(request): Json<ReopenTicketRequest>,
+) -> AppResult<Json<TicketResponse>> {
+    request.validate()?;
+
+    let ticket = state
+        .ticket_service
+        .reopen(user.tenant_id, ticket_id, user.id, &request.reason)
+        .await?;
+
+    Ok(Json(state.ticket_service.ticket_response(ticket).await?))
+}
+
+async fn snooze_ticket(
+    State(state): State<TicketRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(ticket_id): Path<Uuid>,
+    Json(request): Json<SnoozeTicketRequest>,
+) -> AppResult<Json<TicketResponse>> {
+    request.check(chrono::Utc::now())?;
+
+    let ticket = state
+        .ticket_service
+        .snooze(user.tenant_id, ticket_id, request.until, user.id)
+        .await?;
+
+    Ok(Json(state.ticket_service.ticket_response(ticket).await?))
+}
+
+async fn unsnooze_ticket(
+    State(state): State<TicketRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(ticket_id): Path<Uuid>,
+) -> AppResult<Json<TicketResponse>> {
+    let ticket = state
+        .ticket_service
+        .unsnooze(user.tenant_id, ticket_id, user.id)
+        .await?;
+
+    Ok(Json(state.ticket_service.ticket_response(ticket).await?))
+}
+
+async fn list_attachments(
+    State(state): State<TicketRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(ticket_id): Path<Uuid>,
+) -> AppResult<Json<Vec<TicketAttachmentResponse>>> {
+    let attachments = state
+        .attachment_service
+        .list_attachments(user.tenant_id, ticket_id)
+        .await?;
+
+    Ok(Json(attachments))
+}
+
+/// Multipart upload: a `file` part and an optional `note_id`. Infected files
+/// are stored quarantined; check `scan_status` in the response.
+async fn upload_attachment(
+    State(state): State<TicketRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(ticket_id): Path<Uuid>,
+    mut multipart: Multipart,
+) -> AppResult<Json<TicketAttachment>> {
+    let bad_part = |e: axum::extract::multipart::MultipartError| AppError::BadRequest(e.to_string());
+    let mut note_id = None;
+    let mut file = None;
+
+    while let Some(field) = multipart.next_field().await.map_err(bad_part)? {
+        match field.name() {
+            Some("note_id") => {
+                let value = field.text().await.map_err(bad_part)?;
+                note_id = Some(
+                    Uuid::parse_str(value.trim())
+                        .map_err(|_| AppError::validation_field("note_id", "Invalid note id"))?,
+                );
+            }
+            Some("file") => {
+                let file_name = field.file_name().unwrap_or_default().to_string();
+                let mime_type = field
+                    .content_type()
+                    .unwrap_or("application/octet-stream")
+                    .to_string();
+                let content = field.bytes().await.map_err(bad_part)?;
+                file = Some((file_name, mime_type, content));
+            }
+            _ => {}
+        }
+    }
+
+    let (file_name, mime_type, content) =
+        file.ok_or_else(|| AppError::validation_field("file", "A file is required"))?;
+
+    let attachment = state
+        .attachment_service
+        .add_attachment(user.tenant_id, ticket_id, note_id, user.id, &file_name, &mime_type, &content)
+        .await?;
+
+    Ok(Json(attachment))
+}
+
+/// `403 Forbidden` for quarantined attachments
+async fn download_attachment(
+    State(state): State<TicketRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path((ticket_id, attachment_id)): Path<(Uuid, Uuid)>,
+) -> AppResult<impl IntoResponse> {
+    let (attachment, content) = state
+        .attachment_service
+        .download_attachment(user.tenant_id, ticket_id, attachment_id)
+        .await?;
+
+    let disposition = format!(
+        "attachment; filename=\"{}\"",
+        attachment.file_name.replace(['"', '\\', '\r', '\n'], "_")
+    );
+    Ok((
+        [(header::CONTENT_TYPE, attachment.mime_type), (header::CONTENT_DISPOSITION, disposition)],
+        content,
+    ))
+}
+
+/// Printable ticket record; `?audience=internal` includes internal notes
+async fn get_ticket_document(
+    State(state): State<TicketRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(ticket_id): Path<Uuid>,
+    Query(params): Query<TicketDocumentParams>,
+) -> AppResult<Json<TicketDocument>> {
+    let document = state
+        .ticket_service
+        .ticket_document(user.tenant_id, ticket_id, params.audience)
+        .await?;
+
+    Ok(Json(document))
+}
+
+async fn get_status_durations(
+    State(state): State<TicketRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(ticket_id): Path<Uuid>,
+) -> AppResult<Json<Vec<StatusDuration>>> {
+    let durations = state
+        .ticket_service
+        .status_durations(user.tenant_id, ticket_id)
+        .await?;
+
+    Ok(Json(durations))
 }
 
 async fn get_ticket_notes(
@@ -368,6 +501,58 @@ async fn add_note(
     }))
 }
 
+async fn get_related_tickets(
+    State(state): State<TicketRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(ticket_id): Path<Uuid>,
+) -> AppResult<Json<Vec<RelatedTicket>>> {
+    let related = state.ticket_service.related(user.tenant_id, ticket_id).await?;
+    Ok(Json(related))
+}
+
+async fn link_ticket(
+    State(state): State<TicketRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(ticket_id): Path<Uuid>,
+    Json(request): Json<LinkTicketRequest>,
+) -> AppResult<Json<TicketLinkResponse>> {
+    request.validate()?;
+
+    let link = state
+        .ticket_service
+        .link_tickets(user.tenant_id, user.id, ticket_id, request.to_ticket_id, request.link_type)
+        .await?;
+
+    Ok(Json(TicketLinkResponse {
+        merge_suggested: link.link_type == TicketLinkType::DuplicateOf,
+        link,
+    }))
+}
+
+async fn merge_tickets(
+    State(state): State<TicketRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(ticket_id): Path<Uuid>,
+    Json(request): Json<MergeTicketsRequest>,
+) -> AppResult<Json<TicketResponse>> {
+    request.validate()?;
+
+    let ticket = state
+        .ticket_service
+        .merge_tickets(user.tenant_id, user.id, ticket_id, &request)
+        .await?;
+
+    Ok(Json(state.ticket_service.ticket_response(ticket).await?))
+}
+
+async fn unlink_ticket(
+    State(state): State<TicketRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path((ticket_id, link_id)): Path<(Uuid, Uuid)>,
+) -> AppResult<()> {
+    state.ticket_service.unlink(user.tenant_id, ticket_id, link_id).await
+}
+
 async fn get_statuses(
     State(state): State<TicketRouterState>,
     RequireAuth(user): RequireAuth,
@@ -376,6 +561,32 @@ async fn get_statuses(
     Ok(Json(statuses))
 }
 
+async fn list_status_transitions(
+    State(state): State<TicketRouterState>,
+    RequireAuth(user): RequireAuth,
+) -> AppResult<Json<Vec<StatusTransition>>> {
+    let transitions = state
+        .ticket_service
+        .list_status_transitions(user.tenant_id)
+        .await?;
+    Ok(Json(transitions))
+}
+
+async fn set_status_transitions(
+    State(state): State<TicketRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Path(status_id): Path<Uuid>,
+    Json(request): Json<SetStatusTransitionsRequest>,
+) -> AppResult<Json<Vec<StatusTransition>>> {
+    request.validate()?;
+
+    let transitions = state
+        .ticket_service
+        .set_status_transitions(user.tenant_id, status_id, &request)
+        .await?;
+    Ok(Json(transitions))
+}
+
 async fn get_priorities(
     State(state): State<TicketRouterState>,
     RequireAuth(user): RequireAuth,
@@ -399,3 +610,251 @@ async fn get_types(
     let types = state.ticket_service.get_types(user.tenant_id).await?;
     Ok(Json(types))
 }
+
+async fn get_resolution_codes(
+    State(state): State<TicketRouterState>,
+    RequireAuth(user): RequireAuth,
+) -> AppResult<Json<Vec<ResolutionCode>>> {
+    let codes = state.ticket_service.get_resolution_codes(user.tenant_id).await?;
+    Ok(Json(codes))
+}
+
+async fn list_queue_addresses(
+    State(state): State<TicketRouterState>,
+    RequireAuth(user): RequireAuth,
+) -> AppResult<Json<Vec<QueueEmailAddress>>> {
+    let addresses = state.ticket_service.list_queue_addresses(user.tenant_id).await?;
+    Ok(Json(addresses))
+}
+
+async fn bulk_close(
+    State(state): State<TicketRouterState>,
+    RequireAuth(user): RequireAuth,
+    Json(request): Json<BulkCloseRequest>,
+) -> AppResult<Json<BulkCloseOutcome>> {
+    request.validate()?;
+
+    let outcome = state
+        .ticket_service
+        .bulk_close(user.tenant_id, user.id, &request)
+        .await?;
+
+    Ok(Json(outcome))
+}
+
+async fn reassign_all(
+    State(state): State<TicketRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Path(from_user_id): Path<Uuid>,
+    Json(request): Json<ReassignRequest>,
+) -> AppResult<Json<Vec<Reassignment>>> {
+    let reassignments = state
+        .ticket_service
+        .reassign_all(user.tenant_id, from_user_id, &request, user.id)
+        .await?;
+
+    Ok(Json(reassignments))
+}
+
+async fn create_queue_address(
+    State(state): State<TicketRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Json(request): Json<CreateQueueEmailAddressRequest>,
+) -> AppResult<Json<QueueEmailAddress>> {
+    request.validate()?;
+
+    let address = state
+        .ticket_service
+        .create_queue_address(user.tenant_id, &request)
+        .await?;
+
+    Ok(Json(address))
+}
+
+async fn delete_queue_address(
+    State(state): State<TicketRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Path(address_id): Path<Uuid>,
+) -> AppResult<()> {
+    state
+        .ticket_service
+        .delete_queue_address(user.tenant_id, address_id)
+        .await
+}
+
+async fn list_tag_rules(
+    State(state): State<TicketRouterState>,
+    RequireAuth(user): RequireAuth,
+) -> AppResult<Json<Vec<TagRule>>> {
+    let rules = state.ticket_service.list_tag_rules(user.tenant_id).await?;
+    Ok(Json(rules))
+}
+
+async fn create_tag_rule(
+    State(state): State<TicketRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Json(request): Json<CreateTagRuleRequest>,
+) -> AppResult<Json<TagRule>> {
+    request.validate()?;
+
+    let rule = state
+        .ticket_service
+        .create_tag_rule(user.tenant_id, &request)
+        .await?;
+
+    Ok(Json(rule))
+}
+
+async fn delete_tag_rule(
+    State(state): State<TicketRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Path(rule_id): Path<Uuid>,
+) -> AppResult<()> {
+    state
+        .ticket_service
+        .delete_tag_rule(user.tenant_id, rule_id)
+        .await
+}
+
+// ============================================================================
+// INBOUND EMAIL HANDLERS
+// ============================================================================
+
+async fn process_inbound_email(
+    State(state): State<TicketRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Json(email): Json<InboundEmail>,
+) -> AppResult<Json<InboundEmailOutcome>> {
+    email.validate()?;
+
+    let outcome = state
+        .inbound_email_processor
+        .process(user.tenant_id, user.id, &email)
+        .await?;
+
+    Ok(Json(outcome))
+}
+
+// ============================================================================
+// SHARE LINK HANDLERS
+// ============================================================================
+
+async fn create_share_link(
+    State(state): State<TicketRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(ticket_id): Path<Uuid>,
+    Json(request): Json<CreateShareLinkRequest>,
+) -> AppResult<Json<CreatedShareLink>> {
+    request.validate()?;
+
+    let link = state
+        .ticket_service
+        .create_share_link(user.tenant_id, ticket_id, user.id, chrono::Duration::hours(request.ttl_hours))
+        .await?;
+
+    Ok(Json(link))
+}
+
+async fn list_share_links(
+    State(state): State<TicketRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(ticket_id): Path<Uuid>,
+) -> AppResult<Json<Vec<TicketShareLink>>> {
+    let links = state
+        .ticket_service
+        .list_share_links(user.tenant_id, ticket_id)
+        .await?;
+
+    Ok(Json(links))
+}
+
+async fn revoke_share_link(
+    State(state): State<TicketRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path((ticket_id, link_id)): Path<(Uuid, Uuid)>,
+) -> AppResult<()> {
+    state
+        .ticket_service
+        .revoke_share_link(user.tenant_id, ticket_id, link_id)
+        .await
+}
+
+async fn get_shared_ticket(
+    State(state): State<SharedTicketRouterState>,
+    tenant: Option<CurrentTenant>,
+    Path(token): Path<String>,
+) -> AppResult<Json<TicketTimeline>> {
+    let tenant_id = tenant.as_ref().map(CurrentTenant::tenant_id);
+    let timeline = state.ticket_service.shared_timeline(tenant_id, &token).await?;
+    Ok(Json(timeline))
+}
+
+// ============================================================================
+// CSAT HANDLERS
+// ============================================================================
+
+/// Page the survey email links to. Showing it records nothing; the rating is
+/// sent by the form so link scanners can't answer for the customer.
+async fn csat_response_page(
+    State(state): State<CsatRouterState>,
+    tenant: Option<CurrentTenant>,
+    Path(token): Path<String>,
+    Query(params): Query<CsatLinkParams>,
+) -> AppResult<Html<String>> {
+    let tenant_id = tenant.as_ref().map(CurrentTenant::tenant_id);
+    let survey = state.csat_service.get_survey_by_token(tenant_id, &token).await?;
+    if survey.responded_at.is_some() {
+        return Ok(public_page::page("Thanks for your feedback", "<p>We've already received your response.</p>"));
+    }
+    survey.ensure_open(chrono::Utc::now())?;
+
+    let buttons = match params.rating {
+        Some(rating) => format!(
+            r#"<input type="hidden" name="rating" value="{}"><button type="submit">Send</button>"#,
+            rating.as_str()
+        ),
+        None => concat!(
+            r#"<button type="submit" name="rating" value="positive">&#128077; Good</button> "#,
+            r#"<button type="submit" name="rating" value="negative">&#128078; Bad</button>"#
+        )
+        .to_string(),
+    };
+    let heading = match params.rating {
+        Some(CsatRating::Positive) => "<p>You rated our support &#128077; Good.</p>",
+        Some(CsatRating::Negative) => "<p>You rated our support &#128078; Bad.</p>",
+        None => "<p>How did we do?</p>",
+    };
+
+    Ok(public_page::page(
+        "How did we do?",
+        &format!(
+            r#"{}<form method="post"><p><label for="comment">Anything to add? (optional)</label></p>
+<p><textarea id="comment" name="comment" maxlength="2000"></textarea></p><p>{}</p></form>"#,
+            heading, buttons
+        ),
+    ))
+}
+
+/// Record a survey response: JSON from API clients, or the form on the
+/// survey page
+async fn submit_csat_response(
+    State(state): State<CsatRouterState>,
+    tenant: Option<CurrentTenant>,
+    Path(token): Path<String>,
+    body: FormOrJson<CsatResponseRequest>,
+) -> AppResult<Response> {
+    let is_form = body.is_form();
+    let mut request = body.into_inner();
+    // An empty textarea still posts the field
+    request.comment = request.comment.filter(|comment| !comment.trim().is_empty());
+    request.validate()?;
+
+    let tenant_id = tenant.as_ref().map(CurrentTenant::tenant_id);
+    let survey = state.csat_service.record_response(tenant_id, &token, &request).await?;
+
+    if is_form {
+        let thanks = public_page::page("Thanks for your feedback", "<p>Your response has been recorded.</p>");
+        return Ok(thanks.into_response());
+    }
+    Ok(Json(survey).into_response())
+}
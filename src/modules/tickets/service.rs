@@ -1,40 +1,69 @@
 //! Ticket service implementation
 
 use chrono::Utc;
+use std::collections::HashMap;
 use uuid::Uuid;
 
 use crate::db::Database;
+use crate::modules::audit::{AuditAction, AuditService, NewAuditEntry};
+use crate::modules::auth::CurrentUser;
+use crate::modules::jobs::{Job, JobQueue, NewJob, PgJobQueue};
+use crate::modules::notifications::{NotificationChannel, NotificationService, OutgoingEmail};
+use crate::modules::sequences::{SequenceKind, SequenceService};
+use crate::modules::webhooks::{updated_payload, WebhookService};
+use crate::utils::crypto::generate_token;
 use crate::utils::error::{AppError, AppResult};
-use crate::utils::pagination::PaginationParams;
+use crate::utils::pagination::{planner_row_estimate, CountMode, ListTotal, PaginationParams};
+use crate::utils::request_id;
+use crate::utils::tenant_settings::TenantSettings;
+use crate::utils::timezone::TenantTimezone;
 
+use super::csat::CsatService;
 use super::models::*;
 
+const SHARE_LINK_COLUMNS: &str = "id, tenant_id, ticket_id, token, expires_at, created_by_id, revoked_at, created_at";
+
+const QUEUE_COLUMNS: &str = "id, tenant_id, name, description, color, icon, is_default, sort_order, \
+    default_sla_id, default_priority_id, default_assignee_id, default_team_id, auto_acknowledge";
+
 /// Ticket management service
 #[derive(Clone)]
 pub struct TicketService {
     db: Database,
+    csat: CsatService,
+    jobs: PgJobQueue,
+    notifications: NotificationService,
+    sequences: SequenceService,
+    webhooks: WebhookService,
+    /// Right-hand side of generated Message-IDs
+    message_id_domain: String,
+    /// Public base URL for links in emails
+    base_url: String,
 }
 
 impl TicketService {
     pub fn new(db: Database) -> Self {
-        Self { db }
+        let message_id_domain = std::env::var("SMTP_FROM_EMAIL")
+            .ok()
+            .and_then(|from| from.rsplit_once('@').map(|(_, domain)| domain.to_string()))
+            .unwrap_or_else(|| "psa.localhost".to_string());
+
+        Self {
+            csat: CsatService::new(db.clone()),
+            jobs: PgJobQueue::new(db.clone()),
+            notifications: NotificationService::new(db.clone()),
+            sequences: SequenceService::new(db.clone()),
+            webhooks: WebhookService::new(db.clone()),
+            message_id_domain,
+            base_url: std::env::var("BASE_URL")
+                .unwrap_or_else(|_| "http://localhost:8080".to_string()),
+            db,
+        }
     }
 
     /// Generate next ticket number for tenant
     async fn next_ticket_number(&self, tenant_id: Uuid) -> AppResult<String> {
-        let row = sqlx::query_as::<_, (i32,)>(
-            r#"
-            UPDATE ticket_sequences
-            SET last_number = last_number + 1
-            WHERE tenant_id = $1
-            RETURNING last_number
-            "#,
-        )
-        .bind(tenant_id)
-        .fetch_one(self.db.pool())
-        .await?;
-
-        Ok(format!("T{:06}", row.0))
+        self.sequences.next(tenant_id, SequenceKind::Ticket).await
     }
 
     /// Create a new ticket
@@ -44,6 +73,12 @@ impl TicketService {
         user_id: Uuid,
         request: &CreateTicketRequest,
     ) -> AppResult<Ticket> {
+        if let Some(type_id) = request.type_id {
+            self.get_type(tenant_id, type_id)
+                .await?
+                .check_required_fields(&TicketFieldValues::from_create(request))?;
+        }
+
         let ticket_id = Uuid::new_v4();
         let ticket_number = self.next_ticket_number(tenant_id).await?;
 
@@ -56,8 +91,29 @@ impl TicketService {
         .await?
         .ok_or_else(|| AppError::Configuration("No default ticket status configured".to_string()))?;
 
-        // Get default or specified priority
-        let priority_id = match request.priority_id {
+        // Get default or specified queue
+        let queue_id = match request.queue_id {
+            Some(id) => id,
+            None => self.default_queue_id(tenant_id).await?,
+        };
+
+        // The contract's SLA policy outranks the queue's
+        let terms = self.contract_terms(tenant_id, request).await?;
+        let contract = request.contract_fields(terms.as_ref());
+
+        // Queue defaults fill whatever the request leaves unset
+        let fields = self.get_queue(tenant_id, queue_id).await?.entry_fields(
+            &QueueFields {
+                sla_id: contract.sla_id,
+                priority_id: request.priority_id,
+                assigned_to_id: request.assigned_to_id,
+                team_id: request.team_id,
+            },
+            &QueueFields::default(),
+        );
+
+        // Fall back to the tenant's default priority
+        let priority_id = match fields.priority_id {
             Some(id) => id,
             None => sqlx::query_scalar(
                 "SELECT id FROM ticket_priorities WHERE tenant_id = $1 AND is_default = TRUE LIMIT 1",
@@ -68,17 +124,12 @@ impl TicketService {
             .ok_or_else(|| AppError::Configuration("No default priority configured".to_string()))?,
         };
 
-        // Get default or specified queue
-        let queue_id = match request.queue_id {
-            Some(id) => id,
-            None => sqlx::query_scalar(
-                "SELECT id FROM ticket_queues WHERE tenant_id = $1 AND is_default = TRUE LIMIT 1",
-            )
-            .bind(tenant_id)
-            .fetch_optional(self.db.pool())
-            .await?
-            .ok_or_else(|| AppError::Configuration("No default queue configured".to_string()))?,
-        };
+        let tags = TicketTags::resolve(
+            &request.tags,
+            &self.tag_rules(tenant_id).await?,
+            &request.title,
+            request.description.as_deref(),
+        );
 
         sqlx::query(
             r#"
@@ -88,10 +139,10 @@ impl TicketService {
                 company_id, contact_id, site_id, assigned_to_id, team_id,
                 contract_id, sla_id, scheduled_start, scheduled_end,
                 estimated_hours, is_billable, asset_id, custom_fields, tags,
-                created_by_id
+                rule_tags, created_by_id
             ) VALUES (
                 $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14,
-                $15, $16, $17, $18, $19, $20, $21, $22, $23, $24, $25, $26
+                $15, $16, $17, $18, $19, $20, $21, $22, $23, $24, $25, $26, $27
             )
             "#,
         )
@@ -109,27 +160,170 @@ impl TicketService {
         .bind(request.company_id)
         .bind(request.contact_id)
         .bind(request.site_id)
-        .bind(request.assigned_to_id)
-        .bind(request.team_id)
-        .bind(request.contract_id)
-        .bind(request.sla_id)
+        .bind(fields.assigned_to_id)
+        .bind(fields.team_id)
+        .bind(contract.contract_id)
+        .bind(fields.sla_id)
         .bind(request.scheduled_start)
         .bind(request.scheduled_end)
         .bind(request.estimated_hours)
-        .bind(request.is_billable)
+        .bind(contract.is_billable)
         .bind(request.asset_id)
         .bind(&request.custom_fields)
-        .bind(&request.tags)
+        .bind(&tags.tags)
+        .bind(&tags.rule_tags)
         .bind(user_id)
         .execute(self.db.pool())
         .await?;
 
+        self.record_status_change(tenant_id, ticket_id, default_status_id, Some(user_id))
+            .await?;
+
         // Calculate and set SLA due dates
         self.calculate_sla_dates(tenant_id, ticket_id).await?;
 
         // TODO: Run automation rules for on_create trigger
 
-        self.get_ticket(tenant_id, ticket_id).await
+        let ticket = self.get_ticket(tenant_id, ticket_id).await?;
+        if let Err(e) = self.acknowledge(&ticket, user_id).await {
+            tracing::warn!("Acknowledgement for ticket {} could not be sent: {}", ticket.ticket_number, e);
+        }
+
+        Ok(ticket)
+    }
+
+    /// Open tickets that look like the one `request` would create: recent,
+    /// from the same contact or for the same asset, with a similar title.
+    /// Nothing is blocked; callers warn and let the user decide.
+    pub async fn find_possible_duplicates(
+        &self,
+        tenant_id: Uuid,
+        request: &CreateTicketRequest,
+    ) -> AppResult<Vec<DuplicateCandidate>> {
+        let settings = self.ticket_settings(tenant_id).await?;
+        if (request.contact_id.is_none() && request.asset_id.is_none()) || settings.duplicate_window_hours <= 0 {
+            return Ok(Vec::new());
+        }
+
+        let now = Utc::now();
+        let rows = sqlx::query_as::<_, (Uuid, String, String, Option<Uuid>, Option<Uuid>, chrono::DateTime<Utc>)>(
+            r#"
+            SELECT t.id, t.ticket_number, t.title, t.contact_id, t.asset_id, t.created_at
+            FROM tickets t
+            JOIN ticket_statuses s ON s.id = t.status_id
+            WHERE t.tenant_id = $1 AND COALESCE(s.is_closed, FALSE) = FALSE
+              AND (t.contact_id = $2 OR t.asset_id = $3)
+              AND t.created_at >= $4
+            ORDER BY t.created_at DESC
+            LIMIT 200
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(request.contact_id)
+        .bind(request.asset_id)
+        .bind(now - chrono::Duration::hours(settings.duplicate_window_hours))
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let recent: Vec<RecentTicket> = rows
+            .into_iter()
+            .map(|(id, ticket_number, title, contact_id, asset_id, created_at)| RecentTicket {
+                id,
+                ticket_number,
+                title,
+                contact_id,
+                asset_id,
+                created_at,
+            })
+            .collect();
+
+        Ok(request.possible_duplicates(&recent, &settings, now))
+    }
+
+    /// Technicians who have resolved tickets like `ticket` in the last year,
+    /// for the same asset, category or company, ranked by how often and by
+    /// how many open tickets they hold now
+    pub async fn suggest_assignee(&self, tenant_id: Uuid, ticket: &Ticket) -> AppResult<Vec<AssigneeSuggestion>> {
+        let rows = sqlx::query_as::<_, (Uuid, String, i64, i64, i64, i64)>(
+            r#"
+            SELECT u.id, u.first_name || ' ' || u.last_name,
+                   COUNT(*) FILTER (WHERE t.asset_id = $3),
+                   COUNT(*) FILTER (WHERE t.category_id = $4),
+                   COUNT(*) FILTER (WHERE t.company_id = $5),
+                   (
+                       SELECT COUNT(*)
+                       FROM tickets o
+                       JOIN ticket_statuses os ON os.id = o.status_id
+                       WHERE o.tenant_id = $1 AND o.assigned_to_id = u.id
+                         AND COALESCE(os.is_closed, FALSE) = FALSE
+                   )
+            FROM tickets t
+            JOIN users u ON u.id = t.assigned_to_id
+            WHERE t.tenant_id = $1 AND t.id <> $2
+              AND t.resolved_at >= NOW() - INTERVAL '365 days'
+              AND u.status = 'active'
+              AND (t.asset_id = $3 OR t.category_id = $4 OR t.company_id = $5)
+            GROUP BY u.id, u.first_name, u.last_name
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(ticket.id)
+        .bind(ticket.asset_id)
+        .bind(ticket.category_id)
+        .bind(ticket.company_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let history: Vec<AssigneeHistory> = rows
+            .into_iter()
+            .map(|(user_id, name, same_asset, same_category, same_company, open_tickets)| AssigneeHistory {
+                user_id,
+                name,
+                same_asset,
+                same_category,
+                same_company,
+                open_tickets,
+            })
+            .collect();
+
+        Ok(rank_assignees(&history))
+    }
+
+    /// The contract a new ticket falls under: the one the request names, or
+    /// else the company's default contract if it is active today
+    async fn contract_terms(&self, tenant_id: Uuid, request: &CreateTicketRequest) -> AppResult<Option<ContractTerms>> {
+        let row: Option<(Uuid, String, Option<Uuid>)> = match request.contract_id {
+            Some(contract_id) => {
+                sqlx::query_as("SELECT id, contract_type, sla_id FROM contracts WHERE tenant_id = $1 AND id = $2")
+                    .bind(tenant_id)
+                    .bind(contract_id)
+                    .fetch_optional(self.db.pool())
+                    .await?
+            }
+            None => {
+                sqlx::query_as(
+                    r#"
+                    SELECT c.id, c.contract_type, c.sla_id
+                    FROM companies co
+                    JOIN contracts c ON c.id = co.default_contract_id AND c.tenant_id = co.tenant_id
+                    WHERE co.tenant_id = $1 AND co.id = $2
+                      AND c.status = 'active'
+                      AND c.start_date <= CURRENT_DATE
+                      AND (c.end_date IS NULL OR c.end_date >= CURRENT_DATE)
+                    "#,
+                )
+                .bind(tenant_id)
+                .bind(request.company_id)
+                .fetch_optional(self.db.pool())
+                .await?
+            }
+        };
+
+        Ok(row.map(|(contract_id, contract_type, sla_id)| ContractTerms {
+            contract_id,
+            contract_type,
+            sla_id,
+        }))
     }
 
     /// Get ticket by ID
@@ -141,9 +335,10 @@ impl TicketService {
                    queue_id, source, company_id, contact_id, site_id,
                    assigned_to_id, team_id, parent_ticket_id, contract_id, sla_id,
                    sla_due_date, first_response_due, first_response_at,
-                   resolution_due, resolved_at, closed_at,
+                   resolution_due, resolved_at, closed_at, resolution_code,
                    scheduled_start, scheduled_end, estimated_hours, actual_hours,
-                   is_billable, billing_status, asset_id, custom_fields, tags,
+                   is_billable, billing_status, asset_id, custom_fields, tags, rule_tags,
+                   snoozed_until, snoozed_at, snoozed_by_id,
                    created_by_id, last_updated_by_id, created_at, updated_at
             FROM tickets
             WHERE tenant_id = $1 AND id = $2
@@ -171,9 +366,10 @@ impl TicketService {
                    queue_id, source, company_id, contact_id, site_id,
                    assigned_to_id, team_id, parent_ticket_id, contract_id, sla_id,
                    sla_due_date, first_response_due, first_response_at,
-                   resolution_due, resolved_at, closed_at,
+                   resolution_due, resolved_at, closed_at, resolution_code,
                    scheduled_start, scheduled_end, estimated_hours, actual_hours,
-                   is_billable, billing_status, asset_id, custom_fields, tags,
+                   is_billable, billing_status, asset_id, custom_fields, tags, rule_tags,
+                   snoozed_until, snoozed_at, snoozed_by_id,
                    created_by_id, last_updated_by_id, created_at, updated_at
             FROM tickets
             WHERE tenant_id = $1 AND ticket_number = $2
@@ -194,10 +390,7 @@ impl TicketService {
         tenant_id: Uuid,
         filter: &TicketFilter,
         pagination: &PaginationParams,
-    ) -> AppResult<(Vec<Ticket>, u64)> {
-        let offset = pagination.offset() as i32;
-        let limit = pagination.limit() as i32;
-
+    ) -> AppResult<(Vec<Ticket>, ListTotal)> {
         // Build query with filters
         let mut conditions = vec!["t.tenant_id = $1".to_string()];
         let mut param_idx = 4;
@@ -225,6 +418,10 @@ impl TicketService {
             conditions.push(format!("t.company_id = ${}", param_idx));
             param_idx += 1;
         }
+        if filter.contact_id.is_some() {
+            conditions.push(format!("t.contact_id = ${}", param_idx));
+            param_idx += 1;
+        }
         if filter.assigned_to_id.is_some() {
             conditions.push(format!("t.assigned_to_id = ${}", param_idx));
             param_idx += 1;
@@ -232,21 +429,55 @@ impl TicketService {
         if filter.is_unassigned == Some(true) {
             conditions.push("t.assigned_to_id IS NULL".to_string());
         }
+        // Overdue is a point-in-time comparison and needs no time zone
         if filter.is_overdue == Some(true) {
             conditions.push("t.sla_due_date < NOW() AND t.closed_at IS NULL".to_string());
         }
+        let due_today = match filter.is_due_today {
+            Some(true) => {
+                let timezone = TenantTimezone::load(self.db.pool(), tenant_id).await?;
+                Some(timezone.day_range(timezone.local_date(Utc::now())))
+            }
+            _ => None,
+        };
+        if due_today.is_some() {
+            conditions.push(format!(
+                "t.sla_due_date >= ${} AND t.sla_due_date < ${} AND t.closed_at IS NULL",
+                param_idx,
+                param_idx + 1
+            ));
+            param_idx += 2;
+        }
         if filter.is_open == Some(true) {
             conditions.push(
                 "NOT EXISTS (SELECT 1 FROM ticket_statuses s WHERE s.id = t.status_id AND s.is_closed = TRUE)".to_string()
             );
         }
+        let snooze = SnoozeVisibility::from_filter(filter);
+        if let Some(condition) = snooze.sql() {
+            conditions.push(condition.to_string());
+        }
+        if filter.scope.is_some() {
+            conditions.push(TicketScope::sql(param_idx));
+            param_idx += 2;
+        }
+
+        // Only the tenant condition, plus the default hiding of the few
+        // snoozed tickets: a candidate for an estimated total
+        let unfiltered = if snooze == SnoozeVisibility::Hidden { 2 } else { 1 };
+        let is_filtered = conditions.len() > unfiltered;
+        let estimate = if is_filtered || pagination.count == CountMode::Exact {
+            None
+        } else {
+            self.estimated_ticket_rows(tenant_id).await?
+        }
+        .filter(|&rows| pagination.count.use_estimate(is_filtered, rows));
 
         let where_clause = conditions.join(" AND ");
         let order_by = pagination.order_by(
             "t.created_at",
             &["created_at", "updated_at", "sla_due_date", "priority_id"],
         );
-
         let query = format!(
             r#"
             SELECT t.id, t.tenant_id, t.ticket_number, t.title, t.description,
@@ -254,9 +485,10 @@ impl TicketService {
                    t.queue_id, t.source, t.company_id, t.contact_id, t.site_id,
                    t.assigned_to_id, t.team_id, t.parent_ticket_id, t.contract_id, t.sla_id,
                    t.sla_due_date, t.first_response_due, t.first_response_at,
-                   t.resolution_due, t.resolved_at, t.closed_at,
+                   t.resolution_due, t.resolved_at, t.closed_at, t.resolution_code,
                    t.scheduled_start, t.scheduled_end, t.estimated_hours, t.actual_hours,
-                   t.is_billable, t.billing_status, t.asset_id, t.custom_fields, t.tags,
+                   t.is_billable, t.billing_status, t.asset_id, t.custom_fields, t.tags, t.rule_tags,
+                   t.snoozed_until, t.snoozed_at, t.snoozed_by_id,
                    t.created_by_id, t.last_updated_by_id, t.created_at, t.updated_at
             FROM tickets t
             WHERE {}
@@ -271,47 +503,96 @@ impl TicketService {
             where_clause
         );
 
-        // Build queries with dynamic parameters
+        let mut count_builder = sqlx::query_scalar::<_, i64>(&count_query).bind(tenant_id);
+
+        if let Some(ref q) = filter.q {
+            count_builder = count_builder.bind(format!("%{}%", q));
+        }
+        if let Some(ref status_id) = filter.status_id {
+            count_builder = count_builder.bind(status_id);
+        }
+        if let Some(ref priority_id) = filter.priority_id {
+            count_builder = count_builder.bind(priority_id);
+        }
+        if let Some(ref queue_id) = filter.queue_id {
+            count_builder = count_builder.bind(queue_id);
+        }
+        if let Some(ref company_id) = filter.company_id {
+            count_builder = count_builder.bind(company_id);
+        }
+        if let Some(ref contact_id) = filter.contact_id {
+            count_builder = count_builder.bind(contact_id);
+        }
+        if let Some(ref assigned_to_id) = filter.assigned_to_id {
+            count_builder = count_builder.bind(assigned_to_id);
+        }
+        if let Some((day_start, day_end)) = due_today {
+            count_builder = count_builder.bind(day_start).bind(day_end);
+        }
+        if let Some(ref scope) = filter.scope {
+            count_builder = count_builder.bind(scope.user_id).bind(&scope.team_ids);
+        }
+
+        let total = match estimate {
+            Some(rows) => ListTotal::estimated(rows),
+            None => ListTotal::exact(count_builder.fetch_one(self.db.pool()).await? as u64),
+        };
+        let page = pagination.clamped_to(total);
+
         let mut query_builder = sqlx::query_as::<_, TicketRow>(&query)
             .bind(tenant_id)
-            .bind(limit)
-            .bind(offset);
-
-        let mut count_builder = sqlx::query_scalar::<_, i64>(&count_query).bind(tenant_id);
+            .bind(page.limit() as i32)
+            .bind(page.offset() as i32);
 
         if let Some(ref q) = filter.q {
-            let search = format!("%{}%", q);
-            query_builder = query_builder.bind(search.clone());
-            count_builder = count_builder.bind(search);
+            query_builder = query_builder.bind(format!("%{}%", q));
         }
         if let Some(ref status_id) = filter.status_id {
             query_builder = query_builder.bind(status_id);
-            count_builder = count_builder.bind(status_id);
         }
         if let Some(ref priority_id) = filter.priority_id {
             query_builder = query_builder.bind(priority_id);
-            count_builder = count_builder.bind(priority_id);
         }
         if let Some(ref queue_id) = filter.queue_id {
             query_builder = query_builder.bind(queue_id);
-            count_builder = count_builder.bind(queue_id);
         }
         if let Some(ref company_id) = filter.company_id {
             query_builder = query_builder.bind(company_id);
-            count_builder = count_builder.bind(company_id);
+        }
+        if let Some(ref contact_id) = filter.contact_id {
+            query_builder = query_builder.bind(contact_id);
         }
         if let Some(ref assigned_to_id) = filter.assigned_to_id {
             query_builder = query_builder.bind(assigned_to_id);
-            count_builder = count_builder.bind(assigned_to_id);
+        }
+        if let Some((day_start, day_end)) = due_today {
+            query_builder = query_builder.bind(day_start).bind(day_end);
+        }
+        if let Some(ref scope) = filter.scope {
+            query_builder = query_builder.bind(scope.user_id).bind(&scope.team_ids);
         }
 
         let rows = query_builder.fetch_all(self.db.pool()).await?;
-        let total = count_builder.fetch_one(self.db.pool()).await?;
 
-        Ok((rows.into_iter().map(Into::into).collect(), total as u64))
+        Ok((rows.into_iter().map(Into::into).collect(), total))
+    }
+
+    /// The planner's estimate of a tenant's ticket count, from table
+    /// statistics rather than a scan
+    async fn estimated_ticket_rows(&self, tenant_id: Uuid) -> AppResult<Option<u64>> {
+        let plan = sqlx::query_scalar::<_, serde_json::Value>(
+            "EXPLAIN (FORMAT JSON) SELECT 1 FROM tickets t WHERE t.tenant_id = $1",
+        )
+        .bind(tenant_id)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(planner_row_estimate(&plan))
     }
 
-    /// Update ticket
+    /// Update ticket. The checks run before anything is written and the
+    /// fields are written in one transaction, so a rejected update leaves
+    /// the ticket as it was.
     pub async fn update_ticket(
         &self,
         tenant_id: Uuid,
@@ -322,6 +603,96 @@ impl TicketService {
         let ticket = self.get_ticket(tenant_id, ticket_id).await?;
         let old_status_id = ticket.status_id;
 
+        if let Some(type_id) = request.type_id.or(ticket.type_id) {
+            self.get_type(tenant_id, type_id)
+                .await?
+                .check_required_fields(&TicketFieldValues::from_update(&ticket, request))?;
+        }
+
+        let new_status = match request.status_id {
+            Some(status_id) => {
+                let status = self.get_status(tenant_id, status_id).await?;
+                if status_id != old_status_id {
+                    let current = self.get_status(tenant_id, old_status_id).await?;
+                    self.status_workflow(tenant_id).await?.check(&current, &status)?;
+                }
+                Some(status)
+            }
+            None => None,
+        };
+
+        if request.status_id.is_some() || request.resolution_code.is_some() {
+            let status = match new_status {
+                Some(ref status) => status.clone(),
+                None => self.get_status(tenant_id, old_status_id).await?,
+            };
+            let codes = self.get_resolution_codes(tenant_id).await?;
+            status.validate_updated_resolution_code(
+                request.resolution_code.as_deref(),
+                ticket.resolution_code.as_deref(),
+                &codes,
+            )?;
+        }
+
+        // Rule tags follow the text, so re-apply the rules whenever it or the
+        // manual tags change
+        let tags = if request.title.is_some() || request.description.is_some() || request.tags.is_some() {
+            let manual = request.tags.clone().unwrap_or_else(|| TicketTags::manual(&ticket));
+            Some(TicketTags::resolve(
+                &manual,
+                &self.tag_rules(tenant_id).await?,
+                request.title.as_deref().unwrap_or(&ticket.title),
+                request.description.as_deref().or(ticket.description.as_deref()),
+            ))
+        } else {
+            None
+        };
+
+        let mut tx = self.db.pool().begin().await?;
+
+        // Fields the type rules look at; unset fields keep their value
+        let sets_rule_fields = request.type_id.is_some()
+            || request.category_id.is_some()
+            || request.contact_id.is_some()
+            || request.site_id.is_some()
+            || request.asset_id.is_some()
+            || request.scheduled_start.is_some()
+            || request.scheduled_end.is_some()
+            || request.estimated_hours.is_some()
+            || request.custom_fields.is_some();
+        if sets_rule_fields {
+            sqlx::query(
+                r#"
+                UPDATE tickets
+                SET type_id = COALESCE($1, type_id),
+                    category_id = COALESCE($2, category_id),
+                    contact_id = COALESCE($3, contact_id),
+                    site_id = COALESCE($4, site_id),
+                    asset_id = COALESCE($5, asset_id),
+                    scheduled_start = COALESCE($6, scheduled_start),
+                    scheduled_end = COALESCE($7, scheduled_end),
+                    estimated_hours = COALESCE($8, estimated_hours),
+                    custom_fields = COALESCE($9, custom_fields),
+                    last_updated_by_id = $10, updated_at = NOW()
+                WHERE tenant_id = $11 AND id = $12
+                "#,
+            )
+            .bind(request.type_id)
+            .bind(request.category_id)
+            .bind(request.contact_id)
+            .bind(request.site_id)
+            .bind(request.asset_id)
+            .bind(request.scheduled_start)
+            .bind(request.scheduled_end)
+            .bind(request.estimated_hours)
+            .bind(&request.custom_fields)
+            .bind(user_id)
+            .bind(tenant_id)
+            .bind(ticket_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
         // Build update
         if let Some(ref title) = request.title {
             sqlx::query("UPDATE tickets SET title = $1, last_updated_by_id = $2, updated_at = NOW() WHERE tenant_id = $3 AND id = $4")
@@ -329,7 +700,7 @@ impl TicketService {
                 .bind(user_id)
                 .bind(tenant_id)
                 .bind(ticket_id)
-                .execute(self.db.pool())
+                .execute(&mut *tx)
                 .await?;
         }
 
@@ -339,40 +710,65 @@ impl TicketService {
                 .bind(user_id)
                 .bind(tenant_id)
                 .bind(ticket_id)
-                .execute(self.db.pool())
+                .execute(&mut *tx)
                 .await?;
         }
 
-        if let Some(status_id) = request.status_id {
-            // Check if status is closing the ticket
-            let is_closed: bool = sqlx::query_scalar(
-                "SELECT is_closed FROM ticket_statuses WHERE id = $1",
-            )
-            .bind(status_id)
-            .fetch_one(self.db.pool())
-            .await?;
+        if let Some(ref tags) = tags {
+            sqlx::query("UPDATE tickets SET tags = $1, rule_tags = $2, last_updated_by_id = $3, updated_at = NOW() WHERE tenant_id = $4 AND id = $5")
+                .bind(&tags.tags)
+                .bind(&tags.rule_tags)
+                .bind(user_id)
+                .bind(tenant_id)
+                .bind(ticket_id)
+                .execute(&mut *tx)
+                .await?;
+        }
 
-            if is_closed && ticket.closed_at.is_none() {
+        let mut closing = false;
+        if let Some(ref status) = new_status {
+            let status_id = status.id;
+            let resolution_code = request
+                .resolution_code
+                .as_deref()
+                .or(ticket.resolution_code.as_deref());
+
+            if status.is_closed && ticket.closed_at.is_none() {
+                closing = true;
                 sqlx::query(
-                    "UPDATE tickets SET status_id = $1, closed_at = NOW(), resolved_at = COALESCE(resolved_at, NOW()), last_updated_by_id = $2, updated_at = NOW() WHERE tenant_id = $3 AND id = $4",
+                    "UPDATE tickets SET status_id = $1, closed_at = NOW(), resolved_at = COALESCE(resolved_at, NOW()), resolution_code = $2, last_updated_by_id = $3, updated_at = NOW() WHERE tenant_id = $4 AND id = $5",
                 )
                 .bind(status_id)
+                .bind(resolution_code)
                 .bind(user_id)
                 .bind(tenant_id)
                 .bind(ticket_id)
-                .execute(self.db.pool())
+                .execute(&mut *tx)
                 .await?;
             } else {
                 sqlx::query(
-                    "UPDATE tickets SET status_id = $1, last_updated_by_id = $2, updated_at = NOW() WHERE tenant_id = $3 AND id = $4",
+                    "UPDATE tickets SET status_id = $1, resolution_code = $2, last_updated_by_id = $3, updated_at = NOW() WHERE tenant_id = $4 AND id = $5",
                 )
                 .bind(status_id)
+                .bind(resolution_code)
                 .bind(user_id)
                 .bind(tenant_id)
                 .bind(ticket_id)
-                .execute(self.db.pool())
+                .execute(&mut *tx)
                 .await?;
             }
+
+            if status_id != old_status_id {
+                Self::record_status_change_in(&mut tx, tenant_id, ticket_id, status_id, Some(user_id)).await?;
+            }
+        } else if let Some(ref resolution_code) = request.resolution_code {
+            sqlx::query("UPDATE tickets SET resolution_code = $1, last_updated_by_id = $2, updated_at = NOW() WHERE tenant_id = $3 AND id = $4")
+                .bind(resolution_code)
+                .bind(user_id)
+                .bind(tenant_id)
+                .bind(ticket_id)
+                .execute(&mut *tx)
+                .await?;
         }
 
         if let Some(priority_id) = request.priority_id {
@@ -381,11 +777,8 @@ impl TicketService {
                 .bind(user_id)
                 .bind(tenant_id)
                 .bind(ticket_id)
-                .execute(self.db.pool())
+                .execute(&mut *tx)
                 .await?;
-
-            // Recalculate SLA when priority changes
-            self.calculate_sla_dates(tenant_id, ticket_id).await?;
         }
 
         if let Some(assigned_to_id) = request.assigned_to_id {
@@ -394,23 +787,98 @@ impl TicketService {
                 .bind(user_id)
                 .bind(tenant_id)
                 .bind(ticket_id)
-                .execute(self.db.pool())
+                .execute(&mut *tx)
                 .await?;
         }
 
-        if let Some(queue_id) = request.queue_id {
-            sqlx::query("UPDATE tickets SET queue_id = $1, last_updated_by_id = $2, updated_at = NOW() WHERE tenant_id = $3 AND id = $4")
-                .bind(queue_id)
-                .bind(user_id)
-                .bind(tenant_id)
-                .bind(ticket_id)
-                .execute(self.db.pool())
-                .await?;
+        tx.commit().await?;
+
+        if closing {
+            // A failed survey must never block closing the ticket
+            if let Err(e) = self.csat.send_survey(&ticket).await {
+                tracing::warn!("CSAT survey for ticket {} failed: {}", ticket_id, e);
+            }
+        }
+
+        // Recalculate SLA when priority changes
+        if request.priority_id.is_some() {
+            self.calculate_sla_dates(tenant_id, ticket_id).await?;
+        }
+
+        if let Some(queue_id) = request.queue_id.filter(|id| *id != ticket.queue_id) {
+            self.requeue(tenant_id, ticket_id, queue_id, user_id, request).await?;
         }
 
         // TODO: Run automation rules for on_update trigger
 
-        self.get_ticket(tenant_id, ticket_id).await
+        let updated = self.get_ticket(tenant_id, ticket_id).await?;
+        let changes = ticket.changes(&updated);
+        if !changes.is_empty() {
+            let payload = updated_payload("ticket", &updated, &changes);
+            // Subscribers being unreachable must never fail the update
+            if let Err(e) = self.webhooks.enqueue(tenant_id, TICKET_UPDATED_EVENT, &payload).await {
+                tracing::warn!("Webhook for ticket {} update failed: {}", ticket_id, e);
+            }
+        }
+
+        Ok(updated)
+    }
+
+    /// Move a ticket to another queue, applying the queue's defaults to what
+    /// the update leaves unset. SLA targets are recalculated when the policy
+    /// or priority changes as a result.
+    async fn requeue(
+        &self,
+        tenant_id: Uuid,
+        ticket_id: Uuid,
+        queue_id: Uuid,
+        user_id: Uuid,
+        request: &UpdateTicketRequest,
+    ) -> AppResult<()> {
+        let queue = self.get_queue(tenant_id, queue_id).await?;
+        // Re-read so earlier parts of the update are seen as current
+        let ticket = self.get_ticket(tenant_id, ticket_id).await?;
+
+        let current = QueueFields {
+            sla_id: ticket.sla_id,
+            priority_id: Some(ticket.priority_id),
+            assigned_to_id: ticket.assigned_to_id,
+            team_id: ticket.team_id,
+        };
+        let fields = queue.entry_fields(
+            &QueueFields {
+                sla_id: request.sla_id,
+                priority_id: request.priority_id,
+                assigned_to_id: request.assigned_to_id,
+                team_id: request.team_id,
+            },
+            &current,
+        );
+
+        sqlx::query(
+            r#"
+            UPDATE tickets
+            SET queue_id = $1, sla_id = $2, priority_id = COALESCE($3, priority_id),
+                assigned_to_id = $4, team_id = $5, last_updated_by_id = $6, updated_at = NOW()
+            WHERE tenant_id = $7 AND id = $8
+            "#,
+        )
+        .bind(queue_id)
+        .bind(fields.sla_id)
+        .bind(fields.priority_id)
+        .bind(fields.assigned_to_id)
+        .bind(fields.team_id)
+        .bind(user_id)
+        .bind(tenant_id)
+        .bind(ticket_id)
+        .execute(self.db.pool())
+        .await?;
+
+        if fields.sla_id != current.sla_id || fields.priority_id != current.priority_id {
+            self.calculate_sla_dates(tenant_id, ticket_id).await?;
+        }
+
+        Ok(())
     }
 
     /// Assign ticket to user
@@ -434,89 +902,1466 @@ impl TicketService {
         self.get_ticket(tenant_id, ticket_id).await
     }
 
-    /// Add note to ticket
-    pub async fn add_note(
-        &self,
-        tenant_id: Uuid,
-        ticket_id: Uuid,
-        user_id: Uuid,
-        request: &CreateNoteRequest,
-    ) -> AppResult<TicketNote> {
-        let note_id = Uuid::new_v4();
-
-        sqlx::query(
+    /// Assign an unassigned ticket to `user_id`. Unlike [`Self::assign_ticket`]
+    /// this never takes a ticket from someone else, so dispatchers racing for
+    /// the same ticket get one winner and a conflict for the rest.
+    pub async fn claim(&self, tenant_id: Uuid, ticket_id: Uuid, user_id: Uuid) -> AppResult<Ticket> {
+        let result = sqlx::query(
             r#"
-            INSERT INTO ticket_notes (id, tenant_id, ticket_id, note_type, content, created_by_id)
-            VALUES ($1, $2, $3, $4, $5, $6)
+            UPDATE tickets SET assigned_to_id = $1, last_updated_by_id = $1, updated_at = NOW()
+            WHERE tenant_id = $2 AND id = $3 AND assigned_to_id IS NULL
             "#,
         )
-        .bind(note_id)
+        .bind(user_id)
         .bind(tenant_id)
         .bind(ticket_id)
-        .bind(request.note_type.as_str())
-        .bind(&request.content)
-        .bind(user_id)
         .execute(self.db.pool())
         .await?;
 
-        // Update ticket's updated_at
-        sqlx::query("UPDATE tickets SET updated_at = NOW(), last_updated_by_id = $1 WHERE id = $2")
-            .bind(user_id)
-            .bind(ticket_id)
-            .execute(self.db.pool())
-            .await?;
-
-        // Record first response if this is a public note and first response hasn't been recorded
-        if request.note_type == NoteType::Public {
-            sqlx::query(
-                "UPDATE tickets SET first_response_at = COALESCE(first_response_at, NOW()) WHERE id = $1",
+        if result.rows_affected() == 0 {
+            let (assignee, assignee_name) = sqlx::query_as::<_, (Option<Uuid>, Option<String>)>(
+                r#"
+                SELECT t.assigned_to_id, u.first_name || ' ' || u.last_name
+                FROM tickets t
+                LEFT JOIN users u ON u.id = t.assigned_to_id
+                WHERE t.tenant_id = $1 AND t.id = $2
+                "#,
             )
+            .bind(tenant_id)
             .bind(ticket_id)
-            .execute(self.db.pool())
-            .await?;
-        }
+            .fetch_optional(self.db.pool())
+            .await?
+            .ok_or_else(|| AppError::not_found("Ticket"))?;
 
-        // TODO: Send email if send_email is true
+            ClaimOutcome::from_update(false, user_id, assignee).into_result(assignee_name.as_deref())?;
+        }
 
-        self.get_note(tenant_id, note_id).await
+        self.get_ticket(tenant_id, ticket_id).await
     }
 
-    /// Get note by ID
-    pub async fn get_note(&self, tenant_id: Uuid, note_id: Uuid) -> AppResult<TicketNote> {
-        let row = sqlx::query_as::<_, TicketNoteRow>(
-            r#"
-            SELECT n.id, n.tenant_id, n.ticket_id, n.note_type, n.content, n.content_html,
-                   n.is_email_sent, n.email_sent_at, n.created_by_id, n.created_at, n.updated_at,
-                   u.first_name || ' ' || u.last_name as created_by_name
-            FROM ticket_notes n
-            LEFT JOIN users u ON n.created_by_id = u.id
-            WHERE n.tenant_id = $1 AND n.id = $2
-            "#,
-        )
-        .bind(tenant_id)
-        .bind(note_id)
-        .fetch_optional(self.db.pool())
+    /// Move a technician's open tickets to another technician, a queue or a
+    /// round-robin of technicians, as one transaction, with an internal note
+    /// on each ticket. Closed tickets keep their assignee.
+    pub async fn reassign_all(
+        &self,
+        tenant_id: Uuid,
+        from_user_id: Uuid,
+        request: &ReassignRequest,
+        user_id: Uuid,
+    ) -> AppResult<Vec<Reassignment>> {
+        request.check(from_user_id)?;
+
+        let mut tx = self.db.pool().begin().await?;
+
+        let mut names: HashMap<Uuid, String> = HashMap::new();
+        let mut user_ids = vec![from_user_id];
+        match &request.to {
+            ReassignTarget::User { user_id } => user_ids.push(*user_id),
+            ReassignTarget::RoundRobin { user_ids: rotation } => user_ids.extend(rotation),
+            ReassignTarget::Queue { .. } => {}
+        }
+        let users: Vec<(Uuid, String, String)> = sqlx::query_as(
+            "SELECT id, first_name || ' ' || last_name, status FROM users WHERE tenant_id = $1 AND id = ANY($2)",
+        )
+        .bind(tenant_id)
+        .bind(&user_ids)
+        .fetch_all(&mut *tx)
+        .await?;
+        for (id, name, status) in users {
+            if id != from_user_id && status != "active" {
+                return Err(AppError::validation_field("to", format!("{} is not an active user", name)));
+            }
+            names.insert(id, name);
+        }
+        if let Some(missing) = user_ids.iter().find(|id| !names.contains_key(id)) {
+            return Err(AppError::NotFound(format!("User {}", missing)));
+        }
+
+        let queue_name = match &request.to {
+            ReassignTarget::Queue { queue_id } => Some(
+                sqlx::query_scalar::<_, String>("SELECT name FROM ticket_queues WHERE tenant_id = $1 AND id = $2")
+                    .bind(tenant_id)
+                    .bind(queue_id)
+                    .fetch_optional(&mut *tx)
+                    .await?
+                    .ok_or_else(|| AppError::NotFound("Queue".to_string()))?,
+            ),
+            _ => None,
+        };
+
+        // Locked so nothing is assigned back to the technician mid-move
+        let candidates: Vec<ReassignCandidate> = sqlx::query_as::<_, (Uuid, Uuid, Option<Uuid>, Option<bool>)>(
+            r#"
+            SELECT t.id, t.company_id, t.queue_id, s.is_closed
+            FROM tickets t
+            JOIN ticket_statuses s ON s.id = t.status_id
+            WHERE t.tenant_id = $1 AND t.assigned_to_id = $2
+            ORDER BY t.created_at
+            FOR UPDATE OF t
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(from_user_id)
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .map(|(ticket_id, company_id, queue_id, is_closed)| ReassignCandidate {
+            ticket_id,
+            company_id,
+            queue_id,
+            is_closed: is_closed.unwrap_or(false),
+        })
+        .collect();
+
+        let reassignments = request.plan(&candidates);
+        for reassignment in &reassignments {
+            sqlx::query(
+                "UPDATE tickets SET assigned_to_id = $1, queue_id = $2, last_updated_by_id = $3, updated_at = NOW() WHERE tenant_id = $4 AND id = $5",
+            )
+            .bind(reassignment.assigned_to_id)
+            .bind(reassignment.queue_id)
+            .bind(user_id)
+            .bind(tenant_id)
+            .bind(reassignment.ticket_id)
+            .execute(&mut *tx)
+            .await?;
+
+            let destination = match (reassignment.assigned_to_id, &queue_name) {
+                (Some(assignee), _) => names[&assignee].clone(),
+                (None, Some(queue)) => format!("the {} queue", queue),
+                (None, None) => "nobody".to_string(),
+            };
+            sqlx::query(
+                "INSERT INTO ticket_notes (id, tenant_id, ticket_id, note_type, content, created_by_id) VALUES ($1, $2, $3, $4, $5, $6)",
+            )
+            .bind(Uuid::new_v4())
+            .bind(tenant_id)
+            .bind(reassignment.ticket_id)
+            .bind(NoteType::Internal.as_str())
+            .bind(format!("Reassigned from {} to {}", names[&from_user_id], destination))
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(reassignments)
+    }
+
+    /// Close many tickets at once. With `suppress_notifications` no customer
+    /// is emailed; status history, an internal note and the audit log are
+    /// recorded either way.
+    pub async fn bulk_close(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        request: &BulkCloseRequest,
+    ) -> AppResult<BulkCloseOutcome> {
+        let status = match request.status_id {
+            Some(status_id) => self.get_status(tenant_id, status_id).await?,
+            None => {
+                let status_id: Uuid = sqlx::query_scalar(
+                    "SELECT id FROM ticket_statuses WHERE tenant_id = $1 AND is_closed = TRUE ORDER BY is_final, sort_order LIMIT 1",
+                )
+                .bind(tenant_id)
+                .fetch_optional(self.db.pool())
+                .await?
+                .ok_or_else(|| AppError::Configuration("No closed ticket status configured".to_string()))?;
+                self.get_status(tenant_id, status_id).await?
+            }
+        };
+        if !status.is_closed {
+            return Err(AppError::validation_field("status_id", format!("'{}' is not a closed status", status.name)));
+        }
+        let codes = self.get_resolution_codes(tenant_id).await?;
+        status.validate_resolution_code(request.resolution_code.as_deref(), &codes)?;
+        let workflow = self.status_workflow(tenant_id).await?;
+
+        let mut tx = self.db.pool().begin().await?;
+
+        let candidates: Vec<BulkCloseCandidate> = sqlx::query_as::<_, (Uuid, Uuid, bool, bool)>(
+            r#"
+            SELECT id, status_id, closed_at IS NOT NULL, contact_id IS NOT NULL
+            FROM tickets
+            WHERE tenant_id = $1 AND id = ANY($2)
+            FOR UPDATE
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(&request.ticket_ids)
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .map(|(ticket_id, status_id, is_closed, has_contact)| BulkCloseCandidate {
+            ticket_id,
+            status_id,
+            is_closed,
+            has_contact,
+        })
+        .collect();
+
+        let outcome = request.plan(&candidates, &status, &workflow);
+        for closure in &outcome.closed {
+            sqlx::query(
+                "UPDATE tickets SET status_id = $1, closed_at = NOW(), resolved_at = COALESCE(resolved_at, NOW()), resolution_code = COALESCE($2, resolution_code), last_updated_by_id = $3, updated_at = NOW() WHERE tenant_id = $4 AND id = $5",
+            )
+            .bind(status.id)
+            .bind(&request.resolution_code)
+            .bind(user_id)
+            .bind(tenant_id)
+            .bind(closure.ticket_id)
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                "INSERT INTO ticket_notes (id, tenant_id, ticket_id, note_type, content, created_by_id) VALUES ($1, $2, $3, $4, $5, $6)",
+            )
+            .bind(Uuid::new_v4())
+            .bind(tenant_id)
+            .bind(closure.ticket_id)
+            .bind(NoteType::Internal.as_str())
+            .bind(&closure.activity)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+            let entry = NewAuditEntry::new(AuditAction::Update, "ticket", Some(closure.ticket_id))
+                .by(Some(user_id))
+                .old_values(serde_json::json!({ "status_id": closure.from_status_id }))
+                .new_values(serde_json::json!({
+                    "status_id": status.id,
+                    "resolution_code": request.resolution_code,
+                    "bulk_close": true,
+                    "suppress_notifications": request.suppress_notifications,
+                }))
+                .request_id(request_id::current());
+            AuditService::record_in(&mut *tx, tenant_id, entry).await?;
+        }
+
+        tx.commit().await?;
+
+        for closure in &outcome.closed {
+            self.record_status_change(tenant_id, closure.ticket_id, status.id, Some(user_id))
+                .await?;
+
+            if closure.notify_customer {
+                // A failed survey must never block closing the ticket
+                let ticket = self.get_ticket(tenant_id, closure.ticket_id).await?;
+                if let Err(e) = self.csat.send_survey(&ticket).await {
+                    tracing::warn!("CSAT survey for ticket {} failed: {}", closure.ticket_id, e);
+                }
+            }
+        }
+
+        Ok(outcome)
+    }
+
+    /// Add note to ticket
+    pub async fn add_note(
+        &self,
+        tenant_id: Uuid,
+        ticket_id: Uuid,
+        user_id: Uuid,
+        request: &CreateNoteRequest,
+    ) -> AppResult<TicketNote> {
+        let note_id = self.insert_note(tenant_id, ticket_id, user_id, request, true).await?;
+
+        if request.send_email && request.note_type != NoteType::Internal {
+            if let Err(e) = self.send_note_email(tenant_id, ticket_id, note_id, None).await {
+                tracing::warn!("Email for note {} could not be sent: {}", note_id, e);
+            }
+        }
+
+        self.get_note(tenant_id, note_id).await
+    }
+
+    /// Store a note and touch the ticket. Public notes that count as a
+    /// response record the ticket's first response.
+    async fn insert_note(
+        &self,
+        tenant_id: Uuid,
+        ticket_id: Uuid,
+        user_id: Uuid,
+        request: &CreateNoteRequest,
+        counts_as_first_response: bool,
+    ) -> AppResult<Uuid> {
+        let note_id = Uuid::new_v4();
+
+        sqlx::query(
+            r#"
+            INSERT INTO ticket_notes (id, tenant_id, ticket_id, note_type, content, counts_as_first_response, created_by_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(note_id)
+        .bind(tenant_id)
+        .bind(ticket_id)
+        .bind(request.note_type.as_str())
+        .bind(&request.content)
+        .bind(counts_as_first_response)
+        .bind(user_id)
+        .execute(self.db.pool())
+        .await?;
+
+        // Update ticket's updated_at
+        sqlx::query("UPDATE tickets SET updated_at = NOW(), last_updated_by_id = $1 WHERE id = $2")
+            .bind(user_id)
+            .bind(ticket_id)
+            .execute(self.db.pool())
+            .await?;
+
+        // Record first response if this is a public note and first response hasn't been recorded
+        if request.note_type.is_first_response(counts_as_first_response) {
+            sqlx::query(
+                "UPDATE tickets SET first_response_at = COALESCE(first_response_at, NOW()) WHERE id = $1",
+            )
+            .bind(ticket_id)
+            .execute(self.db.pool())
+            .await?;
+        }
+
+        Ok(note_id)
+    }
+
+    /// Email the contact that their ticket was received, if the queue or
+    /// tenant has acknowledgements on. The email is kept as a public note
+    /// that leaves the first-response SLA running.
+    async fn acknowledge(&self, ticket: &Ticket, user_id: Uuid) -> AppResult<()> {
+        let settings = self.ticket_settings(ticket.tenant_id).await?;
+        let queue = self.get_queue(ticket.tenant_id, ticket.queue_id).await?;
+        let timezone = TenantTimezone::load(self.db.pool(), ticket.tenant_id).await?;
+        let url = format!("{}/portal/tickets/{}", self.base_url.trim_end_matches('/'), ticket.id);
+        let Some(acknowledgement) = TicketAcknowledgement::for_new_ticket(ticket, &settings, &queue, timezone, &url)
+        else {
+            return Ok(());
+        };
+
+        let Some(contact_id) = ticket.contact_id else {
+            return Ok(());
+        };
+        let contact = sqlx::query_as::<_, (Option<String>, Option<String>)>(
+            "SELECT email, locale FROM contacts WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(ticket.tenant_id)
+        .bind(contact_id)
+        .fetch_optional(self.db.pool())
+        .await?;
+        let Some((Some(_), locale)) = contact else {
+            return Ok(());
+        };
+
+        let (_, rendered) = self
+            .notifications
+            .render_template(
+                ticket.tenant_id,
+                "ticket.acknowledged",
+                NotificationChannel::Email,
+                locale.as_deref().unwrap_or("en"),
+                &acknowledgement.context,
+            )
+            .await?;
+
+        let note = CreateNoteRequest {
+            note_type: NoteType::Public,
+            content: rendered.body_text,
+            send_email: true,
+        };
+        let note_id = self.insert_note(ticket.tenant_id, ticket.id, user_id, &note, false).await?;
+        self.send_note_email(ticket.tenant_id, ticket.id, note_id, rendered.subject).await
+    }
+
+    /// Email a note to the ticket contact, threaded onto earlier messages for
+    /// the ticket. `subject` replaces the usual `[T000123] Title` subject.
+    async fn send_note_email(
+        &self,
+        tenant_id: Uuid,
+        ticket_id: Uuid,
+        note_id: Uuid,
+        subject: Option<String>,
+    ) -> AppResult<()> {
+        let ticket = self.get_ticket(tenant_id, ticket_id).await?;
+        let Some(contact_id) = ticket.contact_id else {
+            return Ok(());
+        };
+
+        let email: Option<String> =
+            sqlx::query_scalar("SELECT email FROM contacts WHERE tenant_id = $1 AND id = $2")
+                .bind(tenant_id)
+                .bind(contact_id)
+                .fetch_optional(self.db.pool())
+                .await?
+                .flatten();
+        let Some(email) = email else {
+            return Ok(());
+        };
+
+        // The inbound message that opened the ticket, then earlier note emails
+        let earlier: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT message_id FROM (
+                SELECT email_message_id AS message_id, created_at, 0 AS ord
+                FROM tickets WHERE tenant_id = $1 AND id = $2 AND email_message_id IS NOT NULL
+                UNION ALL
+                SELECT email_message_id, created_at, 1
+                FROM ticket_notes
+                WHERE tenant_id = $1 AND ticket_id = $2 AND email_message_id IS NOT NULL AND id <> $3
+            ) m
+            ORDER BY ord, created_at
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(ticket_id)
+        .bind(note_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let note = self.get_note(tenant_id, note_id).await?;
+        let message_id = note_message_id(note_id, &self.message_id_domain);
+        let subject_prefix = if earlier.is_empty() { "" } else { "Re: " };
+        let from = self
+            .queue_from_address(tenant_id, ticket.queue_id)
+            .await?
+            .map(|address| address.from_header());
+
+        let outgoing = OutgoingEmail {
+            to: email.clone(),
+            subject: subject.unwrap_or_else(|| format!("{}[{}] {}", subject_prefix, ticket.ticket_number, ticket.title)),
+            body_text: note.content,
+            body_html: note.content_html,
+            template_id: None,
+            from,
+            thread: Some(reply_thread_headers(message_id.clone(), &earlier)),
+            attachments: Vec::new(),
+        };
+        self.notifications.send_email(tenant_id, None, &outgoing).await?;
+
+        sqlx::query(
+            r#"
+            UPDATE ticket_notes
+            SET email_message_id = $1, is_email_sent = TRUE, email_sent_at = NOW(), email_recipients = $2
+            WHERE id = $3
+            "#,
+        )
+        .bind(&message_id)
+        .bind(vec![email])
+        .bind(note_id)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Find the ticket an inbound email replies to.
+    ///
+    /// `In-Reply-To` and `References` are matched against stored note and ticket
+    /// Message-IDs first; the `[T000123]` subject tag is only a fallback.
+    pub async fn resolve_reply_ticket(
+        &self,
+        tenant_id: Uuid,
+        headers: &InboundThreadHeaders,
+    ) -> AppResult<Option<Uuid>> {
+        let candidates = headers.candidates();
+
+        if !candidates.is_empty() {
+            let known: std::collections::HashMap<String, Uuid> = sqlx::query_as::<_, (String, Uuid)>(
+                r#"
+                SELECT email_message_id, ticket_id FROM ticket_notes
+                WHERE tenant_id = $1 AND email_message_id = ANY($2)
+                UNION ALL
+                SELECT email_message_id, id FROM tickets
+                WHERE tenant_id = $1 AND email_message_id = ANY($2)
+                "#,
+            )
+            .bind(tenant_id)
+            .bind(&candidates)
+            .fetch_all(self.db.pool())
+            .await?
+            .into_iter()
+            .collect();
+
+            if let Some(ticket_id) = headers.resolve_ticket(&known) {
+                return Ok(Some(ticket_id));
+            }
+        }
+
+        match headers.subject_ticket_number() {
+            Some(number) => Ok(sqlx::query_scalar(
+                "SELECT id FROM tickets WHERE tenant_id = $1 AND ticket_number = $2",
+            )
+            .bind(tenant_id)
+            .bind(number)
+            .fetch_optional(self.db.pool())
+            .await?),
+            None => Ok(None),
+        }
+    }
+
+    /// Remember the Message-ID of an inbound email stored as a note so later
+    /// replies to it are threaded too
+    pub async fn record_note_message_id(
+        &self,
+        tenant_id: Uuid,
+        note_id: Uuid,
+        message_id: &str,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE ticket_notes SET email_message_id = $1
+            WHERE tenant_id = $2 AND id = $3
+              AND NOT EXISTS (SELECT 1 FROM ticket_notes WHERE tenant_id = $2 AND email_message_id = $1)
+            "#,
+        )
+        .bind(message_id)
+        .bind(tenant_id)
+        .bind(note_id)
+        .execute(self.db.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Add a customer reply, reopening the ticket if it closed recently
+    pub async fn add_customer_note(
+        &self,
+        tenant_id: Uuid,
+        ticket_id: Uuid,
+        user_id: Uuid,
+        content: &str,
+    ) -> AppResult<TicketNote> {
+        let ticket = self.get_ticket(tenant_id, ticket_id).await?;
+
+        let request = CreateNoteRequest {
+            note_type: NoteType::Public,
+            content: content.to_string(),
+            send_email: false,
+        };
+        let note = self.add_note(tenant_id, ticket_id, user_id, &request).await?;
+
+        if let Some(closed_at) = ticket.closed_at {
+            let settings = self.ticket_settings(tenant_id).await?;
+            if settings.within_reopen_window(closed_at, Utc::now()) {
+                self.reopen(tenant_id, ticket_id, user_id, "Customer replied")
+                    .await?;
+            }
+        }
+
+        Ok(note)
+    }
+
+    /// Reopen a closed ticket, applying the tenant's SLA-on-reopen mode
+    pub async fn reopen(
+        &self,
+        tenant_id: Uuid,
+        ticket_id: Uuid,
+        user_id: Uuid,
+        reason: &str,
+    ) -> AppResult<Ticket> {
+        let ticket = self.get_ticket(tenant_id, ticket_id).await?;
+        if ticket.closed_at.is_none() {
+            return Err(AppError::BadRequest("Ticket is not closed".to_string()));
+        }
+
+        let open_status_id: Uuid = sqlx::query_scalar(
+            "SELECT id FROM ticket_statuses WHERE tenant_id = $1 AND is_closed = FALSE ORDER BY is_default DESC, sort_order LIMIT 1",
+        )
+        .bind(tenant_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::Configuration("No open ticket status configured".to_string()))?;
+
+        let settings = self.ticket_settings(tenant_id).await?;
+        let sla = settings.reopen_sla_mode.plan(&ticket, Utc::now());
+
+        // The status and the SLA clock change together
+        let mut tx = self.db.pool().begin().await?;
+        sqlx::query(
+            "UPDATE tickets SET status_id = $1, closed_at = NULL, resolved_at = NULL, resolution_code = NULL, last_updated_by_id = $2, updated_at = NOW() WHERE tenant_id = $3 AND id = $4",
+        )
+        .bind(open_status_id)
+        .bind(user_id)
+        .bind(tenant_id)
+        .bind(ticket_id)
+        .execute(&mut *tx)
+        .await?;
+
+        Self::record_status_change_in(&mut tx, tenant_id, ticket_id, open_status_id, Some(user_id)).await?;
+
+        match sla {
+            ReopenSla::Recalculate => {
+                Self::calculate_sla_dates_in(&mut tx, tenant_id, ticket_id).await?;
+            }
+            ReopenSla::Shift {
+                first_response_due,
+                sla_due_date,
+            } => {
+                sqlx::query(
+                    "UPDATE tickets SET first_response_due = $1, sla_due_date = $2, resolution_due = $2 WHERE tenant_id = $3 AND id = $4",
+                )
+                .bind(first_response_due)
+                .bind(sla_due_date)
+                .bind(tenant_id)
+                .bind(ticket_id)
+                .execute(&mut *tx)
+                .await?;
+            }
+            ReopenSla::Clear => {
+                sqlx::query(
+                    "UPDATE tickets SET first_response_due = NULL, sla_due_date = NULL, resolution_due = NULL WHERE tenant_id = $1 AND id = $2",
+                )
+                .bind(tenant_id)
+                .bind(ticket_id)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+        tx.commit().await?;
+
+        let note = CreateNoteRequest {
+            note_type: NoteType::Internal,
+            content: format!("Ticket reopened: {}", reason),
+            send_email: false,
+        };
+        self.add_note(tenant_id, ticket_id, user_id, &note).await?;
+
+        self.get_ticket(tenant_id, ticket_id).await
+    }
+
+    /// Close tickets that have been resolved for longer than `grace` without
+    /// any update, posting a system note on each. Tickets touched within the
+    /// grace window are skipped, and running again closes nothing twice.
+    /// Returns the ids of the tickets closed.
+    pub async fn auto_close_resolved(
+        &self,
+        tenant_id: Uuid,
+        grace: chrono::Duration,
+        now: chrono::DateTime<Utc>,
+    ) -> AppResult<Vec<Uuid>> {
+        let final_status_id: Option<Uuid> = sqlx::query_scalar(
+            "SELECT id FROM ticket_statuses WHERE tenant_id = $1 AND is_final = TRUE ORDER BY sort_order LIMIT 1",
+        )
+        .bind(tenant_id)
+        .fetch_optional(self.db.pool())
+        .await?;
+        let Some(final_status_id) = final_status_id else {
+            return Ok(Vec::new());
+        };
+
+        let cutoff = now - grace;
+        let candidates: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT t.id
+            FROM tickets t
+            JOIN ticket_statuses s ON s.id = t.status_id
+            WHERE t.tenant_id = $1 AND s.is_closed = TRUE AND s.is_final = FALSE
+              AND COALESCE(t.resolved_at, t.closed_at) <= $2 AND t.updated_at <= $2
+            ORDER BY t.resolved_at
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(cutoff)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let mut closed = Vec::new();
+        for ticket_id in candidates {
+            let ticket = self.get_ticket(tenant_id, ticket_id).await?;
+            if !ticket.auto_close_due(grace, now) {
+                continue;
+            }
+
+            // The close, its status history and the note land together
+            let mut tx = self.db.pool().begin().await?;
+
+            // Only if nothing changed since the ticket was read
+            let result = sqlx::query(
+                "UPDATE tickets SET status_id = $1, updated_at = NOW() WHERE tenant_id = $2 AND id = $3 AND status_id = $4 AND updated_at = $5",
+            )
+            .bind(final_status_id)
+            .bind(tenant_id)
+            .bind(ticket_id)
+            .bind(ticket.status_id)
+            .bind(ticket.updated_at)
+            .execute(&mut *tx)
+            .await?;
+            if result.rows_affected() == 0 {
+                continue;
+            }
+
+            Self::record_status_change_in(&mut tx, tenant_id, ticket_id, final_status_id, None).await?;
+            let note = format!(
+                "Ticket closed automatically after {} days resolved without a response",
+                grace.num_days()
+            );
+            Self::insert_system_note(&mut tx, tenant_id, ticket_id, &note).await?;
+
+            tx.commit().await?;
+
+            closed.push(ticket_id);
+        }
+
+        Ok(closed)
+    }
+
+    /// Hide a ticket from active views until `until`, when the unsnooze job
+    /// brings it back and reminds the assignee. Snoozing again moves the
+    /// reminder; the earlier job finds the snooze changed and does nothing.
+    pub async fn snooze(
+        &self,
+        tenant_id: Uuid,
+        ticket_id: Uuid,
+        until: chrono::DateTime<Utc>,
+        user_id: Uuid,
+    ) -> AppResult<Ticket> {
+        let ticket = self.get_ticket(tenant_id, ticket_id).await?;
+        if ticket.closed_at.is_some() {
+            return Err(AppError::BadRequest("Closed tickets cannot be snoozed".to_string()));
+        }
+
+        // Read back as stored, for the job to compare against
+        let until: chrono::DateTime<Utc> = sqlx::query_scalar(
+            r#"
+            UPDATE tickets
+            SET snoozed_until = $1, snoozed_at = COALESCE(snoozed_at, NOW()), snoozed_by_id = $2,
+                last_updated_by_id = $2, updated_at = NOW()
+            WHERE tenant_id = $3 AND id = $4
+            RETURNING snoozed_until
+            "#,
+        )
+        .bind(until)
+        .bind(user_id)
+        .bind(tenant_id)
+        .bind(ticket_id)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        self.jobs
+            .enqueue(
+                NewJob::new(
+                    UNSNOOZE_TICKET_JOB,
+                    serde_json::json!({ "ticket_id": ticket_id, "snoozed_until": until }),
+                )
+                .for_tenant(tenant_id)
+                .run_at(until),
+            )
+            .await?;
+
+        self.get_ticket(tenant_id, ticket_id).await
+    }
+
+    /// End a snooze early. The ticket's job finds nothing to do when it runs.
+    pub async fn unsnooze(&self, tenant_id: Uuid, ticket_id: Uuid, user_id: Uuid) -> AppResult<Ticket> {
+        let ticket = self.get_ticket(tenant_id, ticket_id).await?;
+        if ticket.snoozed_until.is_none() {
+            return Ok(ticket);
+        }
+
+        let mut tx = self.db.pool().begin().await?;
+        self.end_snooze(&mut tx, &ticket, Some(user_id), Utc::now()).await?;
+        tx.commit().await?;
+
+        self.get_ticket(tenant_id, ticket_id).await
+    }
+
+    /// Clear the snooze, moving SLA targets back by the time snoozed if the
+    /// tenant has snoozing pause the clock
+    async fn end_snooze(
+        &self,
+        conn: &mut sqlx::PgConnection,
+        ticket: &Ticket,
+        user_id: Option<Uuid>,
+        now: chrono::DateTime<Utc>,
+    ) -> AppResult<()> {
+        let settings = self.ticket_settings(ticket.tenant_id).await?;
+        let sla = match ticket.snoozed_at {
+            Some(snoozed_at) if settings.snooze_pauses_sla && now > snoozed_at => {
+                Some(ticket.sla_after_snooze(now - snoozed_at))
+            }
+            _ => None,
+        };
+
+        sqlx::query(
+            r#"
+            UPDATE tickets
+            SET snoozed_until = NULL, snoozed_at = NULL, snoozed_by_id = NULL,
+                first_response_due = COALESCE($1, first_response_due),
+                sla_due_date = COALESCE($2, sla_due_date),
+                resolution_due = COALESCE($3, resolution_due),
+                last_updated_by_id = COALESCE($4, last_updated_by_id), updated_at = NOW()
+            WHERE tenant_id = $5 AND id = $6
+            "#,
+        )
+        .bind(sla.and_then(|sla| sla.first_response_due))
+        .bind(sla.and_then(|sla| sla.sla_due_date))
+        .bind(sla.and_then(|sla| sla.resolution_due))
+        .bind(user_id)
+        .bind(ticket.tenant_id)
+        .bind(ticket.id)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Handler for `UNSNOOZE_TICKET_JOB`, for registering with the job worker
+    pub async fn run_unsnooze_ticket_job(&self, job: Job) -> AppResult<()> {
+        let tenant_id = job
+            .tenant_id
+            .ok_or_else(|| AppError::internal("Unsnooze job has no tenant"))?;
+        let ticket_id: Uuid = job.payload["ticket_id"]
+            .as_str()
+            .and_then(|id| id.parse().ok())
+            .ok_or_else(|| AppError::internal("Unsnooze job has no ticket_id"))?;
+        let snoozed_until: Option<chrono::DateTime<Utc>> = job.payload["snoozed_until"]
+            .as_str()
+            .and_then(|until| until.parse().ok());
+
+        let ticket = match self.get_ticket(tenant_id, ticket_id).await {
+            Ok(ticket) => ticket,
+            Err(AppError::NotFound(_)) => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        // Cancelled or snoozed again since this job was queued
+        if ticket.snoozed_until.is_none() || ticket.snoozed_until != snoozed_until {
+            return Ok(());
+        }
+
+        let mut tx = self.db.pool().begin().await?;
+        self.end_snooze(&mut tx, &ticket, None, Utc::now()).await?;
+        Self::insert_system_note(&mut tx, tenant_id, ticket_id, "Snooze ended").await?;
+        tx.commit().await?;
+
+        if let Err(e) = self.send_snooze_reminder(&ticket).await {
+            tracing::warn!("Failed to send snooze reminder for ticket {}: {}", ticket.ticket_number, e);
+        }
+        Ok(())
+    }
+
+    /// Remind the assignee, or whoever snoozed an unassigned ticket, that it's back
+    async fn send_snooze_reminder(&self, ticket: &Ticket) -> AppResult<()> {
+        let Some(user_id) = ticket.assigned_to_id.or(ticket.snoozed_by_id) else {
+            return Ok(());
+        };
+
+        let user = sqlx::query_as::<_, (String, String)>(
+            "SELECT email, locale FROM users WHERE tenant_id = $1 AND id = $2 AND status = 'active'",
+        )
+        .bind(ticket.tenant_id)
+        .bind(user_id)
+        .fetch_optional(self.db.pool())
+        .await?;
+        let Some((email, locale)) = user else {
+            return Ok(());
+        };
+
+        let context = serde_json::json!({
+            "ticket": {
+                "number": ticket.ticket_number,
+                "title": ticket.title,
+                "url": format!("{}/tickets/{}", self.base_url.trim_end_matches('/'), ticket.id),
+            },
+        });
+        let (template, rendered) = self
+            .notifications
+            .render_template(ticket.tenant_id, "ticket.snooze_ended", NotificationChannel::Email, &locale, &context)
+            .await?;
+        let outgoing = self.notifications.templated_email(ticket.tenant_id, email, &template, rendered).await?;
+
+        self.notifications
+            .send_email(ticket.tenant_id, Some(user_id), &outgoing)
+            .await?;
+        Ok(())
+    }
+
+    /// Close the ticket's current status stay and open one for `status_id`.
+    /// Call after the ticket's status changes; `user_id` is `None` for
+    /// automation. Recording the status the ticket is already in is a no-op.
+    pub async fn record_status_change(
+        &self,
+        tenant_id: Uuid,
+        ticket_id: Uuid,
+        status_id: Uuid,
+        user_id: Option<Uuid>,
+    ) -> AppResult<()> {
+        let mut tx = self.db.pool().begin().await?;
+        Self::record_status_change_in(&mut tx, tenant_id, ticket_id, status_id, user_id).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// `record_status_change` inside the caller's transaction
+    async fn record_status_change_in(
+        conn: &mut sqlx::PgConnection,
+        tenant_id: Uuid,
+        ticket_id: Uuid,
+        status_id: Uuid,
+        user_id: Option<Uuid>,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE ticket_status_history SET left_at = NOW()
+            WHERE tenant_id = $1 AND ticket_id = $2 AND left_at IS NULL AND status_id <> $3
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(ticket_id)
+        .bind(status_id)
+        .execute(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO ticket_status_history (tenant_id, ticket_id, status_id, changed_by_id)
+            SELECT $1, $2, $3, $4
+            WHERE NOT EXISTS (
+                SELECT 1 FROM ticket_status_history WHERE ticket_id = $2 AND left_at IS NULL
+            )
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(ticket_id)
+        .bind(status_id)
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Post an internal note with no author, for changes the system made
+    async fn insert_system_note(
+        conn: &mut sqlx::PgConnection,
+        tenant_id: Uuid,
+        ticket_id: Uuid,
+        content: &str,
+    ) -> AppResult<()> {
+        sqlx::query(
+            "INSERT INTO ticket_notes (id, tenant_id, ticket_id, note_type, content, created_by_id) VALUES ($1, $2, $3, $4, $5, NULL)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(tenant_id)
+        .bind(ticket_id)
+        .bind(NoteType::Internal.as_str())
+        .bind(content)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Status stays of a ticket, oldest first
+    pub async fn status_history(&self, tenant_id: Uuid, ticket_id: Uuid) -> AppResult<Vec<StatusHistoryEntry>> {
+        let rows = sqlx::query_as::<_, StatusHistoryRow>(
+            r#"
+            SELECT h.id, h.ticket_id, h.status_id, s.name AS status_name, h.changed_by_id,
+                   h.entered_at, h.left_at
+            FROM ticket_status_history h
+            JOIN ticket_statuses s ON s.id = h.status_id
+            WHERE h.tenant_id = $1 AND h.ticket_id = $2
+            ORDER BY h.entered_at
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(ticket_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Total time a ticket has spent in each status
+    pub async fn status_durations(&self, tenant_id: Uuid, ticket_id: Uuid) -> AppResult<Vec<StatusDuration>> {
+        self.get_ticket(tenant_id, ticket_id).await?;
+        let history = self.status_history(tenant_id, ticket_id).await?;
+
+        Ok(StatusDuration::from_history(&history, Utc::now()))
+    }
+
+    /// Load the tenant's ticket settings
+    pub async fn ticket_settings(&self, tenant_id: Uuid) -> AppResult<TicketSettings> {
+        TicketSettings::load(self.db.pool(), tenant_id).await
+    }
+
+    /// Which tickets a user may list, or `None` for all of them
+    pub async fn ticket_scope(&self, user: &CurrentUser) -> AppResult<Option<TicketScope>> {
+        let visibility = self.ticket_settings(user.tenant_id).await?.visibility;
+        if TicketScope::for_user(visibility, user.role, user.id, Vec::new()).is_none() {
+            return Ok(None);
+        }
+
+        let team_ids: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT m.team_id
+            FROM team_members m
+            JOIN teams tm ON tm.id = m.team_id
+            WHERE m.tenant_id = $1 AND m.user_id = $2 AND tm.is_active = TRUE
+            "#,
+        )
+        .bind(user.tenant_id)
+        .bind(user.id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(TicketScope::for_user(visibility, user.role, user.id, team_ids))
+    }
+
+    /// Get note by ID
+    pub async fn get_note(&self, tenant_id: Uuid, note_id: Uuid) -> AppResult<TicketNote> {
+        let row = sqlx::query_as::<_, TicketNoteRow>(
+            r#"
+            SELECT n.id, n.tenant_id, n.ticket_id, n.note_type, n.content, n.content_html,
+                   n.is_email_sent, n.email_sent_at, n.counts_as_first_response, n.created_by_id,
+                   n.created_at, n.updated_at,
+                   u.first_name || ' ' || u.last_name as created_by_name
+            FROM ticket_notes n
+            LEFT JOIN users u ON n.created_by_id = u.id
+            WHERE n.tenant_id = $1 AND n.id = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(note_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Note".to_string()))?;
+
+        Ok(row.into())
+    }
+
+    /// Get notes for a ticket
+    pub async fn get_ticket_notes(
+        &self,
+        tenant_id: Uuid,
+        ticket_id: Uuid,
+    ) -> AppResult<Vec<TicketNote>> {
+        let rows = sqlx::query_as::<_, TicketNoteRow>(
+            r#"
+            SELECT n.id, n.tenant_id, n.ticket_id, n.note_type, n.content, n.content_html,
+                   n.is_email_sent, n.email_sent_at, n.counts_as_first_response, n.created_by_id,
+                   n.created_at, n.updated_at,
+                   u.first_name || ' ' || u.last_name as created_by_name
+            FROM ticket_notes n
+            LEFT JOIN users u ON n.created_by_id = u.id
+            WHERE n.tenant_id = $1 AND n.ticket_id = $2
+            ORDER BY n.created_at DESC
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(ticket_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// The ticket laid out for printing: details, the notes `audience` may
+    /// see, and the time logged
+    pub async fn ticket_document(
+        &self,
+        tenant_id: Uuid,
+        ticket_id: Uuid,
+        audience: DocumentAudience,
+    ) -> AppResult<TicketDocument> {
+        let details = self.document_details(tenant_id, ticket_id).await?;
+        let notes = self.get_ticket_notes(tenant_id, ticket_id).await?;
+
+        let time_entries = sqlx::query_as::<_, TicketDocumentTimeEntryRow>(
+            r#"
+            SELECT e.date, u.first_name || ' ' || u.last_name AS user_name, e.duration_minutes,
+                   COALESCE(e.is_billable, TRUE) AS is_billable, e.notes, e.internal_notes
+            FROM time_entries e
+            JOIN users u ON u.id = e.user_id
+            WHERE e.tenant_id = $1 AND e.ticket_id = $2
+            ORDER BY e.date, e.start_time NULLS LAST, e.created_at
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(ticket_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let branding = self.notifications.branding(tenant_id).await?;
+
+        Ok(TicketDocument::new(
+            details,
+            notes,
+            time_entries.into_iter().map(Into::into).collect(),
+            audience,
+            &branding,
+        ))
+    }
+
+    /// Ticket details as printed and shared
+    async fn document_details(&self, tenant_id: Uuid, ticket_id: Uuid) -> AppResult<TicketDocumentDetails> {
+        let row = sqlx::query_as::<_, TicketDocumentDetailsRow>(
+            r#"
+            SELECT t.ticket_number, t.title, t.description, s.name AS status, p.name AS priority,
+                   c.name AS company_name,
+                   ct.first_name || ' ' || ct.last_name AS contact_name,
+                   u.first_name || ' ' || u.last_name AS assigned_to_name,
+                   t.resolution_code, t.created_at, t.resolved_at, t.closed_at
+            FROM tickets t
+            JOIN ticket_statuses s ON s.id = t.status_id
+            JOIN ticket_priorities p ON p.id = t.priority_id
+            JOIN companies c ON c.id = t.company_id
+            LEFT JOIN contacts ct ON ct.id = t.contact_id
+            LEFT JOIN users u ON u.id = t.assigned_to_id
+            WHERE t.tenant_id = $1 AND t.id = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(ticket_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Ticket".to_string()))?;
+
+        Ok(row.into())
+    }
+
+    /// Create a read-only public link to the ticket's timeline, valid for `ttl`
+    pub async fn create_share_link(
+        &self,
+        tenant_id: Uuid,
+        ticket_id: Uuid,
+        user_id: Uuid,
+        ttl: chrono::Duration,
+    ) -> AppResult<CreatedShareLink> {
+        self.get_ticket(tenant_id, ticket_id).await?;
+
+        let row = sqlx::query_as::<_, TicketShareLinkRow>(&format!(
+            r#"
+            INSERT INTO ticket_share_links (tenant_id, ticket_id, token, expires_at, created_by_id)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING {}
+            "#,
+            SHARE_LINK_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(ticket_id)
+        .bind(generate_token(48))
+        .bind(Utc::now() + ttl)
+        .bind(user_id)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        let link: TicketShareLink = row.into();
+        Ok(CreatedShareLink {
+            url: format!("{}/api/v1/shared-tickets/{}", self.base_url.trim_end_matches('/'), link.token),
+            link,
+        })
+    }
+
+    /// Share links issued for a ticket, newest first
+    pub async fn list_share_links(&self, tenant_id: Uuid, ticket_id: Uuid) -> AppResult<Vec<TicketShareLink>> {
+        let rows = sqlx::query_as::<_, TicketShareLinkRow>(&format!(
+            "SELECT {} FROM ticket_share_links WHERE tenant_id = $1 AND ticket_id = $2 ORDER BY created_at DESC",
+            SHARE_LINK_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(ticket_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Revoke a share link; it stops working immediately
+    pub async fn revoke_share_link(&self, tenant_id: Uuid, ticket_id: Uuid, link_id: Uuid) -> AppResult<()> {
+        let result = sqlx::query(
+            "UPDATE ticket_share_links SET revoked_at = NOW() WHERE tenant_id = $1 AND ticket_id = $2 AND id = $3 AND revoked_at IS NULL",
+        )
+        .bind(tenant_id)
+        .bind(ticket_id)
+        .bind(link_id)
+        .execute(self.db.pool())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Share link".to_string()));
+        }
+        Ok(())
+    }
+
+    /// The public timeline behind a share link token. With `tenant_id`, links
+    /// issued by other tenants are not found.
+    pub async fn shared_timeline(&self, tenant_id: Option<Uuid>, token: &str) -> AppResult<TicketTimeline> {
+        let link: TicketShareLink = sqlx::query_as::<_, TicketShareLinkRow>(&format!(
+            "SELECT {} FROM ticket_share_links WHERE token = $1 AND ($2::UUID IS NULL OR tenant_id = $2)",
+            SHARE_LINK_COLUMNS
+        ))
+        .bind(token)
+        .bind(tenant_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Shared ticket".to_string()))?
+        .into();
+        link.ensure_active(Utc::now())?;
+
+        let details = self.document_details(link.tenant_id, link.ticket_id).await?;
+        let notes = self.get_ticket_notes(link.tenant_id, link.ticket_id).await?;
+        let history = self.status_history(link.tenant_id, link.ticket_id).await?;
+
+        Ok(TicketTimeline::public(&details, &notes, &history, &link))
+    }
+
+    /// Every ticket matching `filter` as CSV, in list order, fetched a batch
+    /// at a time. Stops at `MAX_EXPORT_ROWS`.
+    pub async fn export_tickets_csv(&self, tenant_id: Uuid, filter: &TicketFilter) -> AppResult<String> {
+        let mut names = TicketExportNames {
+            statuses: self.get_statuses(tenant_id).await?.into_iter().map(|s| (s.id, s.name)).collect(),
+            priorities: self.get_priorities(tenant_id).await?.into_iter().map(|p| (p.id, p.name)).collect(),
+            queues: self.get_queues(tenant_id).await?.into_iter().map(|q| (q.id, q.name)).collect(),
+            ..TicketExportNames::default()
+        };
+
+        let mut csv = ticket_csv_header();
+        let mut exported = 0;
+        let mut pagination = PaginationParams {
+            page: 1,
+            per_page: EXPORT_BATCH_SIZE,
+            sort: None,
+            sort_dir: "desc".to_string(),
+            count: CountMode::Auto,
+        };
+        while exported < MAX_EXPORT_ROWS {
+            let (tickets, _) = self.list_tickets(tenant_id, filter, &pagination).await?;
+            let batch_len = tickets.len();
+
+            let company_ids: Vec<Uuid> = tickets
+                .iter()
+                .map(|ticket| ticket.company_id)
+                .filter(|id| !names.companies.contains_key(id))
+                .collect();
+            if !company_ids.is_empty() {
+                let companies: Vec<(Uuid, String)> =
+                    sqlx::query_as("SELECT id, name FROM companies WHERE tenant_id = $1 AND id = ANY($2)")
+                        .bind(tenant_id)
+                        .bind(&company_ids)
+                        .fetch_all(self.db.pool())
+                        .await?;
+                names.companies.extend(companies);
+            }
+            let user_ids: Vec<Uuid> = tickets
+                .iter()
+                .filter_map(|ticket| ticket.assigned_to_id)
+                .filter(|id| !names.users.contains_key(id))
+                .collect();
+            if !user_ids.is_empty() {
+                let users: Vec<(Uuid, String)> = sqlx::query_as(
+                    "SELECT id, first_name || ' ' || last_name FROM users WHERE tenant_id = $1 AND id = ANY($2)",
+                )
+                .bind(tenant_id)
+                .bind(&user_ids)
+                .fetch_all(self.db.pool())
+                .await?;
+                names.users.extend(users);
+            }
+
+            for ticket in tickets.iter().take(MAX_EXPORT_ROWS - exported) {
+                csv.push_str(&ticket_csv_row(ticket, &names));
+            }
+            exported += batch_len;
+            if batch_len < EXPORT_BATCH_SIZE as usize {
+                break;
+            }
+            pagination.page += 1;
+        }
+
+        Ok(csv)
+    }
+
+    /// Link two tickets. Duplicate links and self-links are rejected.
+    pub async fn link_tickets(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        from_ticket_id: Uuid,
+        to_ticket_id: Uuid,
+        link_type: TicketLinkType,
+    ) -> AppResult<TicketLink> {
+        let (from_ticket_id, to_ticket_id) =
+            TicketLink::endpoints(from_ticket_id, to_ticket_id, link_type)?;
+
+        // Both ends must belong to the tenant
+        self.get_ticket(tenant_id, from_ticket_id).await?;
+        self.get_ticket(tenant_id, to_ticket_id).await?;
+
+        let row = sqlx::query_as::<_, TicketLinkRow>(
+            r#"
+            INSERT INTO ticket_links (tenant_id, from_ticket_id, to_ticket_id, link_type, created_by_id)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (from_ticket_id, to_ticket_id, link_type) DO NOTHING
+            RETURNING id, tenant_id, from_ticket_id, to_ticket_id, link_type, created_by_id, created_at
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(from_ticket_id)
+        .bind(to_ticket_id)
+        .bind(link_type.as_str())
+        .bind(user_id)
+        .fetch_optional(self.db.pool())
         .await?
-        .ok_or_else(|| AppError::NotFound("Note".to_string()))?;
+        .ok_or_else(|| AppError::Conflict("Ticket link".to_string()))?;
 
         Ok(row.into())
     }
 
-    /// Get notes for a ticket
-    pub async fn get_ticket_notes(
+    /// Merge tickets into `target_id`. The target takes the earliest creation
+    /// time, the highest priority and every tag, with an audit entry naming
+    /// the ticket each came from. The sources' notes, attachments and time
+    /// move onto the target, and each source is closed as a duplicate of it.
+    pub async fn merge_tickets(
         &self,
         tenant_id: Uuid,
-        ticket_id: Uuid,
-    ) -> AppResult<Vec<TicketNote>> {
-        let rows = sqlx::query_as::<_, TicketNoteRow>(
+        user_id: Uuid,
+        target_id: Uuid,
+        request: &MergeTicketsRequest,
+    ) -> AppResult<Ticket> {
+        if request.source_ticket_ids.contains(&target_id) {
+            return Err(AppError::validation_field(
+                "source_ticket_ids",
+                "A ticket cannot be merged into itself",
+            ));
+        }
+
+        let target = self.get_ticket(tenant_id, target_id).await?;
+        let mut sources = Vec::with_capacity(request.source_ticket_ids.len());
+        for source_id in &request.source_ticket_ids {
+            if !sources.iter().any(|t: &Ticket| t.id == *source_id) {
+                sources.push(self.get_ticket(tenant_id, *source_id).await?);
+            }
+        }
+
+        let closed_status_id: Uuid = sqlx::query_scalar(
+            "SELECT id FROM ticket_statuses WHERE tenant_id = $1 AND is_closed = TRUE ORDER BY is_default DESC, sort_order LIMIT 1",
+        )
+        .bind(tenant_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::Configuration("No closed ticket status configured".to_string()))?;
+
+        let priority_ranks: HashMap<Uuid, i32> = self
+            .get_priorities(tenant_id)
+            .await?
+            .into_iter()
+            .map(|p| (p.id, p.sort_order))
+            .collect();
+        let merge = TicketMerge::plan(&target, &sources, &priority_ranks);
+
+        let mut tx = self.db.pool().begin().await?;
+
+        sqlx::query(
             r#"
-            SELECT n.id, n.tenant_id, n.ticket_id, n.note_type, n.content, n.content_html,
-                   n.is_email_sent, n.email_sent_at, n.created_by_id, n.created_at, n.updated_at,
-                   u.first_name || ' ' || u.last_name as created_by_name
-            FROM ticket_notes n
-            LEFT JOIN users u ON n.created_by_id = u.id
-            WHERE n.tenant_id = $1 AND n.ticket_id = $2
-            ORDER BY n.created_at DESC
+            UPDATE tickets
+            SET created_at = $1, priority_id = $2, tags = $3, rule_tags = $4, last_updated_by_id = $5, updated_at = NOW()
+            WHERE tenant_id = $6 AND id = $7
+            "#,
+        )
+        .bind(merge.created_at.value)
+        .bind(merge.priority_id.value)
+        .bind(&merge.tags)
+        .bind(&merge.rule_tags)
+        .bind(user_id)
+        .bind(tenant_id)
+        .bind(target_id)
+        .execute(&mut *tx)
+        .await?;
+
+        for table in ["ticket_notes", "ticket_attachments", "time_entries"] {
+            sqlx::query(&format!(
+                "UPDATE {} SET ticket_id = $1 WHERE tenant_id = $2 AND ticket_id = ANY($3)",
+                table
+            ))
+            .bind(target_id)
+            .bind(tenant_id)
+            .bind(&merge.source_ids)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE tickets
+            SET status_id = $1, closed_at = COALESCE(closed_at, NOW()), last_updated_by_id = $2, updated_at = NOW()
+            WHERE tenant_id = $3 AND id = ANY($4)
+            "#,
+        )
+        .bind(closed_status_id)
+        .bind(user_id)
+        .bind(tenant_id)
+        .bind(&merge.source_ids)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO ticket_links (tenant_id, from_ticket_id, to_ticket_id, link_type, created_by_id)
+            SELECT $1, source_id, $2, 'duplicate_of', $3 FROM UNNEST($4::UUID[]) AS source_id
+            ON CONFLICT (from_ticket_id, to_ticket_id, link_type) DO NOTHING
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(target_id)
+        .bind(user_id)
+        .bind(&merge.source_ids)
+        .execute(&mut *tx)
+        .await?;
+
+        let entry = NewAuditEntry::new(AuditAction::Update, "ticket", Some(target_id))
+            .by(Some(user_id))
+            .old_values(serde_json::json!({
+                "created_at": target.created_at,
+                "priority_id": target.priority_id,
+                "tags": target.tags,
+            }))
+            .new_values(serde_json::to_value(&merge)?)
+            .request_id(request_id::current());
+        AuditService::record_in(&mut *tx, tenant_id, entry).await?;
+
+        tx.commit().await?;
+
+        for source_id in &merge.source_ids {
+            self.record_status_change(tenant_id, *source_id, closed_status_id, Some(user_id))
+                .await?;
+        }
+        if merge.priority_id.value != target.priority_id {
+            self.calculate_sla_dates(tenant_id, target_id).await?;
+        }
+
+        self.get_ticket(tenant_id, target_id).await
+    }
+
+    /// Remove a link from either of its tickets
+    pub async fn unlink(&self, tenant_id: Uuid, ticket_id: Uuid, link_id: Uuid) -> AppResult<()> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM ticket_links
+            WHERE tenant_id = $1 AND id = $2 AND (from_ticket_id = $3 OR to_ticket_id = $3)
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(link_id)
+        .bind(ticket_id)
+        .execute(self.db.pool())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Ticket link".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Tickets linked to a ticket, described from that ticket's side
+    pub async fn related(&self, tenant_id: Uuid, ticket_id: Uuid) -> AppResult<Vec<RelatedTicket>> {
+        let rows = sqlx::query_as::<_, RelatedTicketRow>(
+            r#"
+            SELECT l.id, l.tenant_id, l.from_ticket_id, l.to_ticket_id, l.link_type, l.created_by_id,
+                   l.created_at, t.ticket_number, t.title
+            FROM ticket_links l
+            JOIN tickets t ON t.id = CASE WHEN l.from_ticket_id = $2 THEN l.to_ticket_id ELSE l.from_ticket_id END
+            WHERE l.tenant_id = $1 AND (l.from_ticket_id = $2 OR l.to_ticket_id = $2)
+            ORDER BY l.created_at
             "#,
         )
         .bind(tenant_id)
@@ -524,16 +2369,77 @@ impl TicketService {
         .fetch_all(self.db.pool())
         .await?;
 
-        Ok(rows.into_iter().map(Into::into).collect())
+        Ok(rows
+            .into_iter()
+            .filter_map(|RelatedTicketRow { link, ticket_number, title }| {
+                let link = TicketLink::from(link);
+                let (other, relation) = link.relation_for(ticket_id)?;
+
+                Some(RelatedTicket {
+                    link_id: link.id,
+                    ticket_id: other,
+                    ticket_number,
+                    title,
+                    relation,
+                    linked_at: link.created_at,
+                })
+            })
+            .collect())
+    }
+
+    /// A ticket as returned by the API, its SLA status judged by its policy's
+    /// warning threshold
+    pub async fn ticket_response(&self, ticket: Ticket) -> AppResult<TicketResponse> {
+        let warning = self.sla_warning(ticket.tenant_id, ticket.sla_id).await?;
+        let sla_status = ticket.sla_status_at(&warning, Utc::now());
+
+        let mut response = TicketResponse::from(ticket);
+        response.sla_status = sla_status;
+        Ok(response)
+    }
+
+    /// The warning threshold of a ticket's SLA policy, or the default
+    pub async fn sla_warning(&self, tenant_id: Uuid, sla_id: Option<Uuid>) -> AppResult<SlaWarning> {
+        let Some(sla_id) = sla_id else {
+            return Ok(SlaWarning::default());
+        };
+
+        let row = sqlx::query_as::<_, (i16, Option<f64>)>(
+            "SELECT warning_percent, warning_hours::FLOAT8 FROM sla_policies WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(tenant_id)
+        .bind(sla_id)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        Ok(row
+            .map(|(percent, max_hours)| SlaWarning {
+                remaining_fraction: f64::from(percent) / 100.0,
+                max_hours,
+            })
+            .unwrap_or_default())
     }
 
     /// Calculate SLA due dates for a ticket
     async fn calculate_sla_dates(&self, tenant_id: Uuid, ticket_id: Uuid) -> AppResult<()> {
+        let mut conn = self.db.pool().acquire().await?;
+        Self::calculate_sla_dates_in(&mut conn, tenant_id, ticket_id).await
+    }
+
+    /// `calculate_sla_dates` inside the caller's transaction
+    async fn calculate_sla_dates_in(conn: &mut sqlx::PgConnection, tenant_id: Uuid, ticket_id: Uuid) -> AppResult<()> {
         // Get ticket details
-        let ticket = self.get_ticket(tenant_id, ticket_id).await?;
+        let (ticket_sla_id, priority_id) = sqlx::query_as::<_, (Option<Uuid>, Uuid)>(
+            "SELECT sla_id, priority_id FROM tickets WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(tenant_id)
+        .bind(ticket_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Ticket".to_string()))?;
 
         // Get SLA policy
-        let sla_id = match ticket.sla_id {
+        let sla_id = match ticket_sla_id {
             Some(id) => id,
             None => {
                 // Try to get default SLA
@@ -541,7 +2447,7 @@ impl TicketService {
                     "SELECT id FROM sla_policies WHERE tenant_id = $1 AND is_default = TRUE LIMIT 1",
                 )
                 .bind(tenant_id)
-                .fetch_optional(self.db.pool())
+                .fetch_optional(&mut *conn)
                 .await?;
 
                 match default {
@@ -551,66 +2457,360 @@ impl TicketService {
             }
         };
 
-        // Get SLA targets for this priority
-        let targets = sqlx::query_as::<_, (Option<f64>, Option<f64>)>(
+        // Get SLA targets for this priority, and the base targets its multiplier scales
+        let targets = sqlx::query_as::<_, PriorityTargetsRow>(
+            r#"
+            SELECT p.sla_multiplier::FLOAT8 AS sla_multiplier,
+                   own.id IS NOT NULL AS has_own,
+                   own.first_response_hours::FLOAT8 AS own_first_response_hours,
+                   own.resolution_hours::FLOAT8 AS own_resolution_hours,
+                   base.id IS NOT NULL AS has_base,
+                   base.first_response_hours::FLOAT8 AS base_first_response_hours,
+                   base.resolution_hours::FLOAT8 AS base_resolution_hours
+            FROM ticket_priorities p
+            LEFT JOIN sla_targets own ON own.sla_policy_id = $1 AND own.priority_id = p.id
+            LEFT JOIN sla_targets base ON base.sla_policy_id = $1 AND base.priority_id = (
+                SELECT id FROM ticket_priorities
+                WHERE tenant_id = p.tenant_id AND is_default = TRUE
+                ORDER BY sort_order
+                LIMIT 1
+            )
+            WHERE p.tenant_id = $2 AND p.id = $3
+            "#,
+        )
+        .bind(sla_id)
+        .bind(tenant_id)
+        .bind(priority_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        let target = targets.and_then(|row| {
+            let own = row.has_own.then_some(SlaTarget {
+                first_response_hours: row.own_first_response_hours,
+                resolution_hours: row.own_resolution_hours,
+            });
+            let base = row.has_base.then_some(SlaTarget {
+                first_response_hours: row.base_first_response_hours,
+                resolution_hours: row.base_resolution_hours,
+            });
+            SlaTarget::for_priority(own, base, row.sla_multiplier.unwrap_or(1.0))
+        });
+
+        if let Some(target) = target {
+            let (first_response_due, sla_due_date) = target.due_dates(Utc::now());
+
+            sqlx::query(
+                "UPDATE tickets SET sla_id = $1, first_response_due = $2, sla_due_date = $3, resolution_due = $3 WHERE id = $4",
+            )
+            .bind(sla_id)
+            .bind(first_response_due)
+            .bind(sla_due_date)
+            .bind(ticket_id)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Get a single ticket status
+    pub async fn get_status(&self, tenant_id: Uuid, status_id: Uuid) -> AppResult<TicketStatus> {
+        let row = sqlx::query_as::<_, TicketStatusRow>(
+            r#"
+            SELECT id, tenant_id, name, color, is_closed, is_default, sort_order,
+                   requires_resolution_code, is_final
+            FROM ticket_statuses
+            WHERE tenant_id = $1 AND id = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(status_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Ticket status".to_string()))?;
+
+        Ok(row.into())
+    }
+
+    /// Get ticket statuses for tenant
+    pub async fn get_statuses(&self, tenant_id: Uuid) -> AppResult<Vec<TicketStatus>> {
+        let rows = sqlx::query_as::<_, TicketStatusRow>(
+            r#"
+            SELECT id, tenant_id, name, color, is_closed, is_default, sort_order,
+                   requires_resolution_code, is_final
+            FROM ticket_statuses
+            WHERE tenant_id = $1
+            ORDER BY sort_order
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Get the tenant's allowed status transitions
+    pub async fn list_status_transitions(&self, tenant_id: Uuid) -> AppResult<Vec<StatusTransition>> {
+        let rows = sqlx::query_as::<_, (Uuid, Uuid)>(
+            "SELECT from_status_id, to_status_id FROM ticket_status_transitions WHERE tenant_id = $1 ORDER BY created_at",
+        )
+        .bind(tenant_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(from_status_id, to_status_id)| StatusTransition {
+                from_status_id,
+                to_status_id,
+            })
+            .collect())
+    }
+
+    async fn status_workflow(&self, tenant_id: Uuid) -> AppResult<StatusWorkflow> {
+        Ok(StatusWorkflow::new(&self.list_status_transitions(tenant_id).await?))
+    }
+
+    /// Replace the statuses a status may move to. An empty list lifts the
+    /// restriction.
+    pub async fn set_status_transitions(
+        &self,
+        tenant_id: Uuid,
+        from_status_id: Uuid,
+        request: &SetStatusTransitionsRequest,
+    ) -> AppResult<Vec<StatusTransition>> {
+        self.get_status(tenant_id, from_status_id).await?;
+
+        let mut to_status_ids = request.to_status_ids.clone();
+        to_status_ids.sort();
+        to_status_ids.dedup();
+        to_status_ids.retain(|id| *id != from_status_id);
+
+        let known: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM ticket_statuses WHERE tenant_id = $1 AND id = ANY($2)")
+            .bind(tenant_id)
+            .bind(&to_status_ids)
+            .fetch_one(self.db.pool())
+            .await?;
+        if known as usize != to_status_ids.len() {
+            return Err(AppError::validation_field("to_status_ids", "Unknown ticket status"));
+        }
+
+        let mut tx = self.db.pool().begin().await?;
+
+        sqlx::query("DELETE FROM ticket_status_transitions WHERE tenant_id = $1 AND from_status_id = $2")
+            .bind(tenant_id)
+            .bind(from_status_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for to_status_id in &to_status_ids {
+            sqlx::query(
+                "INSERT INTO ticket_status_transitions (tenant_id, from_status_id, to_status_id) VALUES ($1, $2, $3)",
+            )
+            .bind(tenant_id)
+            .bind(from_status_id)
+            .bind(to_status_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(to_status_ids
+            .into_iter()
+            .map(|to_status_id| StatusTransition {
+                from_status_id,
+                to_status_id,
+            })
+            .collect())
+    }
+
+    /// Get active resolution codes for tenant
+    pub async fn get_resolution_codes(&self, tenant_id: Uuid) -> AppResult<Vec<ResolutionCode>> {
+        let rows = sqlx::query_as::<_, ResolutionCodeRow>(
+            r#"
+            SELECT id, tenant_id, code, name, description, is_active, sort_order
+            FROM ticket_resolution_codes
+            WHERE tenant_id = $1 AND is_active = TRUE
+            ORDER BY sort_order
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Get ticket priorities for tenant
+    pub async fn get_priorities(&self, tenant_id: Uuid) -> AppResult<Vec<TicketPriority>> {
+        let rows = sqlx::query_as::<_, TicketPriorityRow>(
+            r#"
+            SELECT id, tenant_id, name, color, icon, sla_multiplier, sort_order, is_default
+            FROM ticket_priorities
+            WHERE tenant_id = $1
+            ORDER BY sort_order
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Get ticket queues for tenant
+    pub async fn get_queues(&self, tenant_id: Uuid) -> AppResult<Vec<TicketQueue>> {
+        let rows = sqlx::query_as::<_, TicketQueueRow>(&format!(
+            "SELECT {} FROM ticket_queues WHERE tenant_id = $1 ORDER BY sort_order",
+            QUEUE_COLUMNS
+        ))
+        .bind(tenant_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Get a single ticket queue
+    pub async fn get_queue(&self, tenant_id: Uuid, queue_id: Uuid) -> AppResult<TicketQueue> {
+        let row = sqlx::query_as::<_, TicketQueueRow>(&format!(
+            "SELECT {} FROM ticket_queues WHERE tenant_id = $1 AND id = $2",
+            QUEUE_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(queue_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::not_found("Queue"))?;
+
+        Ok(row.into())
+    }
+
+    /// The tenant's default queue, used for new tickets with no queue mapping
+    pub async fn default_queue_id(&self, tenant_id: Uuid) -> AppResult<Uuid> {
+        sqlx::query_scalar(
+            "SELECT id FROM ticket_queues WHERE tenant_id = $1 AND is_default = TRUE LIMIT 1",
+        )
+        .bind(tenant_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::Configuration("No default queue configured".to_string()))
+    }
+
+    /// Inbound addresses mapped to queues
+    pub async fn list_queue_addresses(&self, tenant_id: Uuid) -> AppResult<Vec<QueueEmailAddress>> {
+        let rows = sqlx::query_as::<_, QueueEmailAddressRow>(
+            r#"
+            SELECT id, tenant_id, queue_id, email_address, from_name, created_at, updated_at
+            FROM queue_email_addresses
+            WHERE tenant_id = $1
+            ORDER BY email_address
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Route an inbound address to a queue. Each address maps to one queue.
+    pub async fn create_queue_address(
+        &self,
+        tenant_id: Uuid,
+        request: &CreateQueueEmailAddressRequest,
+    ) -> AppResult<QueueEmailAddress> {
+        let email_address = normalize_email_address(&request.email_address)
+            .ok_or_else(|| AppError::validation_field("email_address", "Invalid email address"))?;
+
+        let queue_exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM ticket_queues WHERE tenant_id = $1 AND id = $2)",
+        )
+        .bind(tenant_id)
+        .bind(request.queue_id)
+        .fetch_one(self.db.pool())
+        .await?;
+        if !queue_exists {
+            return Err(AppError::NotFound("Queue".to_string()));
+        }
+
+        let row = sqlx::query_as::<_, QueueEmailAddressRow>(
             r#"
-            SELECT first_response_hours, resolution_hours
-            FROM sla_targets
-            WHERE sla_policy_id = $1 AND priority_id = $2
+            INSERT INTO queue_email_addresses (tenant_id, queue_id, email_address, from_name)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (tenant_id, email_address) DO NOTHING
+            RETURNING id, tenant_id, queue_id, email_address, from_name, created_at, updated_at
             "#,
         )
-        .bind(sla_id)
-        .bind(ticket.priority_id)
+        .bind(tenant_id)
+        .bind(request.queue_id)
+        .bind(&email_address)
+        .bind(&request.from_name)
         .fetch_optional(self.db.pool())
-        .await?;
-
-        if let Some((first_response_hours, resolution_hours)) = targets {
-            let now = Utc::now();
+        .await?
+        .ok_or_else(|| AppError::Conflict("Queue email address".to_string()))?;
 
-            let first_response_due = first_response_hours
-                .map(|h| now + chrono::Duration::minutes((h * 60.0) as i64));
-            let sla_due_date = resolution_hours
-                .map(|h| now + chrono::Duration::minutes((h * 60.0) as i64));
+        Ok(row.into())
+    }
 
-            sqlx::query(
-                "UPDATE tickets SET sla_id = $1, first_response_due = $2, sla_due_date = $3, resolution_due = $3 WHERE id = $4",
-            )
-            .bind(sla_id)
-            .bind(first_response_due)
-            .bind(sla_due_date)
-            .bind(ticket_id)
+    /// Remove an address mapping; mail to it falls back to the default queue
+    pub async fn delete_queue_address(&self, tenant_id: Uuid, address_id: Uuid) -> AppResult<()> {
+        let result = sqlx::query("DELETE FROM queue_email_addresses WHERE tenant_id = $1 AND id = $2")
+            .bind(tenant_id)
+            .bind(address_id)
             .execute(self.db.pool())
             .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Queue email address".to_string()));
         }
 
         Ok(())
     }
 
-    /// Get ticket statuses for tenant
-    pub async fn get_statuses(&self, tenant_id: Uuid) -> AppResult<Vec<TicketStatus>> {
-        let rows = sqlx::query_as::<_, TicketStatusRow>(
+    /// The address replies for a queue are sent from, if the queue has one
+    async fn queue_from_address(
+        &self,
+        tenant_id: Uuid,
+        queue_id: Uuid,
+    ) -> AppResult<Option<QueueEmailAddress>> {
+        let row = sqlx::query_as::<_, QueueEmailAddressRow>(
             r#"
-            SELECT id, tenant_id, name, color, is_closed, is_default, sort_order
-            FROM ticket_statuses
-            WHERE tenant_id = $1
-            ORDER BY sort_order
+            SELECT id, tenant_id, queue_id, email_address, from_name, created_at, updated_at
+            FROM queue_email_addresses
+            WHERE tenant_id = $1 AND queue_id = $2
+            ORDER BY created_at
+            LIMIT 1
             "#,
         )
         .bind(tenant_id)
-        .fetch_all(self.db.pool())
+        .bind(queue_id)
+        .fetch_optional(self.db.pool())
         .await?;
 
-        Ok(rows.into_iter().map(Into::into).collect())
+        Ok(row.map(Into::into))
     }
 
-    /// Get ticket priorities for tenant
-    pub async fn get_priorities(&self, tenant_id: Uuid) -> AppResult<Vec<TicketPriority>> {
-        let rows = sqlx::query_as::<_, TicketPriorityRow>(
+    /// Active tag rules, applied to tickets as they are saved
+    async fn tag_rules(&self, tenant_id: Uuid) -> AppResult<Vec<TagRule>> {
+        Ok(self
+            .list_tag_rules(tenant_id)
+            .await?
+            .into_iter()
+            .filter(|rule| rule.is_active)
+            .collect())
+    }
+
+    /// Tag rules for tenant
+    pub async fn list_tag_rules(&self, tenant_id: Uuid) -> AppResult<Vec<TagRule>> {
+        let rows = sqlx::query_as::<_, TagRuleRow>(
             r#"
-            SELECT id, tenant_id, name, color, icon, sla_multiplier, sort_order, is_default
-            FROM ticket_priorities
+            SELECT id, tenant_id, match_type, pattern, tag, is_active, created_at, updated_at
+            FROM tag_rules
             WHERE tenant_id = $1
-            ORDER BY sort_order
+            ORDER BY tag, pattern
             "#,
         )
         .bind(tenant_id)
@@ -620,28 +2820,47 @@ impl TicketService {
         Ok(rows.into_iter().map(Into::into).collect())
     }
 
-    /// Get ticket queues for tenant
-    pub async fn get_queues(&self, tenant_id: Uuid) -> AppResult<Vec<TicketQueue>> {
-        let rows = sqlx::query_as::<_, TicketQueueRow>(
+    /// Add a tag rule. It applies to tickets from their next save.
+    pub async fn create_tag_rule(&self, tenant_id: Uuid, request: &CreateTagRuleRequest) -> AppResult<TagRule> {
+        request.check()?;
+
+        let row = sqlx::query_as::<_, TagRuleRow>(
             r#"
-            SELECT id, tenant_id, name, description, color, icon, is_default, sort_order
-            FROM ticket_queues
-            WHERE tenant_id = $1
-            ORDER BY sort_order
+            INSERT INTO tag_rules (tenant_id, match_type, pattern, tag)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id, tenant_id, match_type, pattern, tag, is_active, created_at, updated_at
             "#,
         )
         .bind(tenant_id)
-        .fetch_all(self.db.pool())
+        .bind(request.match_type.as_str())
+        .bind(&request.pattern)
+        .bind(request.tag.trim())
+        .fetch_one(self.db.pool())
         .await?;
 
-        Ok(rows.into_iter().map(Into::into).collect())
+        Ok(row.into())
+    }
+
+    /// Remove a tag rule. Tags it already applied stay until the ticket is next saved.
+    pub async fn delete_tag_rule(&self, tenant_id: Uuid, rule_id: Uuid) -> AppResult<()> {
+        let result = sqlx::query("DELETE FROM tag_rules WHERE tenant_id = $1 AND id = $2")
+            .bind(tenant_id)
+            .bind(rule_id)
+            .execute(self.db.pool())
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Tag rule".to_string()));
+        }
+
+        Ok(())
     }
 
     /// Get ticket types for tenant
     pub async fn get_types(&self, tenant_id: Uuid) -> AppResult<Vec<TicketType>> {
         let rows = sqlx::query_as::<_, TicketTypeRow>(
             r#"
-            SELECT id, tenant_id, name, description, icon, is_active, sort_order
+            SELECT id, tenant_id, name, description, icon, is_active, sort_order, required_fields
             FROM ticket_types
             WHERE tenant_id = $1 AND is_active = TRUE
             ORDER BY sort_order
@@ -653,6 +2872,24 @@ impl TicketService {
 
         Ok(rows.into_iter().map(Into::into).collect())
     }
+
+    /// Get a ticket type by ID
+    pub async fn get_type(&self, tenant_id: Uuid, type_id: Uuid) -> AppResult<TicketType> {
+        let row = sqlx::query_as::<_, TicketTypeRow>(
+            r#"
+            SELECT id, tenant_id, name, description, icon, is_active, sort_order, required_fields
+            FROM ticket_types
+            WHERE tenant_id = $1 AND id = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(type_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Ticket type".to_string()))?;
+
+        Ok(row.into())
+    }
 }
 
 // ============================================================================
@@ -687,6 +2924,7 @@ struct TicketRow {
     resolution_due: Option<chrono::DateTime<Utc>>,
     resolved_at: Option<chrono::DateTime<Utc>>,
     closed_at: Option<chrono::DateTime<Utc>>,
+    resolution_code: Option<String>,
     scheduled_start: Option<chrono::DateTime<Utc>>,
     scheduled_end: Option<chrono::DateTime<Utc>>,
     estimated_hours: Option<rust_decimal::Decimal>,
@@ -696,6 +2934,10 @@ struct TicketRow {
     asset_id: Option<Uuid>,
     custom_fields: serde_json::Value,
     tags: Vec<String>,
+    rule_tags: Vec<String>,
+    snoozed_until: Option<chrono::DateTime<Utc>>,
+    snoozed_at: Option<chrono::DateTime<Utc>>,
+    snoozed_by_id: Option<Uuid>,
     created_by_id: Uuid,
     last_updated_by_id: Option<Uuid>,
     created_at: chrono::DateTime<Utc>,
@@ -731,6 +2973,7 @@ impl From<TicketRow> for Ticket {
             resolution_due: row.resolution_due,
             resolved_at: row.resolved_at,
             closed_at: row.closed_at,
+            resolution_code: row.resolution_code,
             scheduled_start: row.scheduled_start,
             scheduled_end: row.scheduled_end,
             estimated_hours: row.estimated_hours.map(|d| d.to_string().parse().unwrap_or(0.0)),
@@ -740,6 +2983,10 @@ impl From<TicketRow> for Ticket {
             asset_id: row.asset_id,
             custom_fields: row.custom_fields,
             tags: row.tags,
+            rule_tags: row.rule_tags,
+            snoozed_until: row.snoozed_until,
+            snoozed_at: row.snoozed_at,
+            snoozed_by_id: row.snoozed_by_id,
             created_by_id: row.created_by_id,
             last_updated_by_id: row.last_updated_by_id,
             created_at: row.created_at,
@@ -748,6 +2995,97 @@ impl From<TicketRow> for Ticket {
     }
 }
 
+#[derive(sqlx::FromRow)]
+struct TicketLinkRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    from_ticket_id: Uuid,
+    to_ticket_id: Uuid,
+    link_type: String,
+    created_by_id: Uuid,
+    created_at: chrono::DateTime<Utc>,
+}
+
+impl From<TicketLinkRow> for TicketLink {
+    fn from(row: TicketLinkRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            from_ticket_id: row.from_ticket_id,
+            to_ticket_id: row.to_ticket_id,
+            link_type: TicketLinkType::from_str(&row.link_type).unwrap_or(TicketLinkType::RelatedTo),
+            created_by_id: row.created_by_id,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct RelatedTicketRow {
+    #[sqlx(flatten)]
+    link: TicketLinkRow,
+    ticket_number: String,
+    title: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct TicketDocumentDetailsRow {
+    ticket_number: String,
+    title: String,
+    description: Option<String>,
+    status: String,
+    priority: String,
+    company_name: String,
+    contact_name: Option<String>,
+    assigned_to_name: Option<String>,
+    resolution_code: Option<String>,
+    created_at: chrono::DateTime<Utc>,
+    resolved_at: Option<chrono::DateTime<Utc>>,
+    closed_at: Option<chrono::DateTime<Utc>>,
+}
+
+impl From<TicketDocumentDetailsRow> for TicketDocumentDetails {
+    fn from(row: TicketDocumentDetailsRow) -> Self {
+        Self {
+            ticket_number: row.ticket_number,
+            title: row.title,
+            description: row.description,
+            status: row.status,
+            priority: row.priority,
+            company_name: row.company_name,
+            contact_name: row.contact_name,
+            assigned_to_name: row.assigned_to_name,
+            resolution_code: row.resolution_code,
+            created_at: row.created_at,
+            resolved_at: row.resolved_at,
+            closed_at: row.closed_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct TicketDocumentTimeEntryRow {
+    date: chrono::NaiveDate,
+    user_name: String,
+    duration_minutes: i32,
+    is_billable: bool,
+    notes: Option<String>,
+    internal_notes: Option<String>,
+}
+
+impl From<TicketDocumentTimeEntryRow> for TicketDocumentTimeEntry {
+    fn from(row: TicketDocumentTimeEntryRow) -> Self {
+        Self {
+            date: row.date,
+            user_name: row.user_name,
+            duration_minutes: row.duration_minutes,
+            is_billable: row.is_billable,
+            notes: row.notes,
+            internal_notes: row.internal_notes,
+        }
+    }
+}
+
 #[derive(sqlx::FromRow)]
 struct TicketNoteRow {
     id: Uuid,
@@ -758,7 +3096,8 @@ struct TicketNoteRow {
     content_html: Option<String>,
     is_email_sent: bool,
     email_sent_at: Option<chrono::DateTime<Utc>>,
-    created_by_id: Uuid,
+    counts_as_first_response: bool,
+    created_by_id: Option<Uuid>,
     created_at: chrono::DateTime<Utc>,
     updated_at: chrono::DateTime<Utc>,
     created_by_name: Option<String>,
@@ -775,6 +3114,7 @@ impl From<TicketNoteRow> for TicketNote {
             content_html: row.content_html,
             is_email_sent: row.is_email_sent,
             email_sent_at: row.email_sent_at,
+            counts_as_first_response: row.counts_as_first_response,
             created_by_id: row.created_by_id,
             created_by_name: row.created_by_name,
             created_at: row.created_at,
@@ -792,6 +3132,8 @@ struct TicketStatusRow {
     is_closed: bool,
     is_default: bool,
     sort_order: i32,
+    requires_resolution_code: bool,
+    is_final: bool,
 }
 
 impl From<TicketStatusRow> for TicketStatus {
@@ -804,10 +3146,49 @@ impl From<TicketStatusRow> for TicketStatus {
             is_closed: row.is_closed,
             is_default: row.is_default,
             sort_order: row.sort_order,
+            requires_resolution_code: row.requires_resolution_code,
+            is_final: row.is_final,
         }
     }
 }
 
+#[derive(sqlx::FromRow)]
+struct ResolutionCodeRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    code: String,
+    name: String,
+    description: Option<String>,
+    is_active: bool,
+    sort_order: i32,
+}
+
+impl From<ResolutionCodeRow> for ResolutionCode {
+    fn from(row: ResolutionCodeRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            code: row.code,
+            name: row.name,
+            description: row.description,
+            is_active: row.is_active,
+            sort_order: row.sort_order,
+        }
+    }
+}
+
+/// A priority's multiplier, its own targets under a policy and the policy's base targets
+#[derive(sqlx::FromRow)]
+struct PriorityTargetsRow {
+    sla_multiplier: Option<f64>,
+    has_own: bool,
+    own_first_response_hours: Option<f64>,
+    own_resolution_hours: Option<f64>,
+    has_base: bool,
+    base_first_response_hours: Option<f64>,
+    base_resolution_hours: Option<f64>,
+}
+
 #[derive(sqlx::FromRow)]
 struct TicketPriorityRow {
     id: Uuid,
@@ -845,6 +3226,11 @@ struct TicketQueueRow {
     icon: Option<String>,
     is_default: bool,
     sort_order: i32,
+    default_sla_id: Option<Uuid>,
+    default_priority_id: Option<Uuid>,
+    default_assignee_id: Option<Uuid>,
+    default_team_id: Option<Uuid>,
+    auto_acknowledge: Option<bool>,
 }
 
 impl From<TicketQueueRow> for TicketQueue {
@@ -858,6 +3244,63 @@ impl From<TicketQueueRow> for TicketQueue {
             icon: row.icon,
             is_default: row.is_default,
             sort_order: row.sort_order,
+            default_sla_id: row.default_sla_id,
+            default_priority_id: row.default_priority_id,
+            default_assignee_id: row.default_assignee_id,
+            default_team_id: row.default_team_id,
+            auto_acknowledge: row.auto_acknowledge,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct QueueEmailAddressRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    queue_id: Uuid,
+    email_address: String,
+    from_name: Option<String>,
+    created_at: chrono::DateTime<Utc>,
+    updated_at: chrono::DateTime<Utc>,
+}
+
+impl From<QueueEmailAddressRow> for QueueEmailAddress {
+    fn from(row: QueueEmailAddressRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            queue_id: row.queue_id,
+            email_address: row.email_address,
+            from_name: row.from_name,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct TagRuleRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    match_type: String,
+    pattern: String,
+    tag: String,
+    is_active: bool,
+    created_at: chrono::DateTime<Utc>,
+    updated_at: chrono::DateTime<Utc>,
+}
+
+impl From<TagRuleRow> for TagRule {
+    fn from(row: TagRuleRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            match_type: TagRuleMatch::from_str(&row.match_type).unwrap_or_default(),
+            pattern: row.pattern,
+            tag: row.tag,
+            is_active: row.is_active,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
         }
     }
 }
@@ -871,6 +3314,7 @@ struct TicketTypeRow {
     icon: Option<String>,
     is_active: bool,
     sort_order: i32,
+    required_fields: Vec<String>,
 }
 
 impl From<TicketTypeRow> for TicketType {
@@ -883,6 +3327,220 @@ impl From<TicketTypeRow> for TicketType {
             icon: row.icon,
             is_active: row.is_active,
             sort_order: row.sort_order,
+            required_fields: row.required_fields,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct StatusHistoryRow {
+    id: Uuid,
+    ticket_id: Uuid,
+    status_id: Uuid,
+    status_name: String,
+    changed_by_id: Option<Uuid>,
+    entered_at: chrono::DateTime<chrono::Utc>,
+    left_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<StatusHistoryRow> for StatusHistoryEntry {
+    fn from(row: StatusHistoryRow) -> Self {
+        Self {
+            id: row.id,
+            ticket_id: row.ticket_id,
+            status_id: row.status_id,
+            status_name: row.status_name,
+            changed_by_id: row.changed_by_id,
+            entered_at: row.entered_at,
+            left_at: row.left_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct TicketShareLinkRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    ticket_id: Uuid,
+    token: String,
+    expires_at: chrono::DateTime<Utc>,
+    created_by_id: Option<Uuid>,
+    revoked_at: Option<chrono::DateTime<Utc>>,
+    created_at: chrono::DateTime<Utc>,
+}
+
+impl From<TicketShareLinkRow> for TicketShareLink {
+    fn from(row: TicketShareLinkRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            ticket_id: row.ticket_id,
+            token: row.token,
+            expires_at: row.expires_at,
+            created_by_id: row.created_by_id,
+            revoked_at: row.revoked_at,
+            created_at: row.created_at,
+        }
+    }
+}
+
+// Run against a migrated database with
+// `DATABASE_URL=postgres://... cargo test -- --ignored`
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A tenant of its own, with New, Open and Closed statuses, a technician
+    /// and one ticket in New
+    struct Fixture {
+        service: TicketService,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        ticket_id: Uuid,
+        new: Uuid,
+        open: Uuid,
+        closed: Uuid,
+    }
+
+    async fn fixture() -> Fixture {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must point at a migrated database");
+        let db = Database::new(&url).await.unwrap();
+        let pool = db.pool();
+        let tenant_id = Uuid::new_v4();
+
+        sqlx::query("INSERT INTO tenants (id, name, slug) VALUES ($1, 'Test', $2)")
+            .bind(tenant_id)
+            .bind(format!("test-{}", tenant_id))
+            .execute(pool)
+            .await
+            .unwrap();
+
+        let status = |name: &'static str, is_closed: bool, sort_order: i32| {
+            sqlx::query_scalar::<_, Uuid>(
+                r#"
+                INSERT INTO ticket_statuses (tenant_id, name, color, is_closed, is_default, sort_order)
+                VALUES ($1, $2, '#6B7280', $3, $4, $5)
+                RETURNING id
+                "#,
+            )
+            .bind(tenant_id)
+            .bind(name)
+            .bind(is_closed)
+            .bind(sort_order == 1)
+            .bind(sort_order)
+            .fetch_one(pool)
+        };
+        let new = status("New", false, 1).await.unwrap();
+        let open = status("Open", false, 2).await.unwrap();
+        let closed = status("Closed", true, 3).await.unwrap();
+
+        let priority_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO ticket_priorities (tenant_id, name, color, is_default) VALUES ($1, 'Medium', '#EAB308', TRUE) RETURNING id",
+        )
+        .bind(tenant_id)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+        let queue_id: Uuid =
+            sqlx::query_scalar("INSERT INTO ticket_queues (tenant_id, name, is_default) VALUES ($1, 'Support', TRUE) RETURNING id")
+                .bind(tenant_id)
+                .fetch_one(pool)
+                .await
+                .unwrap();
+        let company_id: Uuid = sqlx::query_scalar("INSERT INTO companies (tenant_id, name) VALUES ($1, 'Acme') RETURNING id")
+            .bind(tenant_id)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+        let user_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (tenant_id, email, first_name, last_name) VALUES ($1, 'sam@example.com', 'Sam', 'Tech') RETURNING id",
+        )
+        .bind(tenant_id)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+
+        let ticket_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO tickets (tenant_id, ticket_number, title, status_id, priority_id, queue_id, company_id, created_by_id)
+            VALUES ($1, 'T000001', 'Printer jam', $2, $3, $4, $5, $6)
+            RETURNING id
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(new)
+        .bind(priority_id)
+        .bind(queue_id)
+        .bind(company_id)
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+
+        Fixture {
+            service: TicketService::new(db),
+            tenant_id,
+            user_id,
+            ticket_id,
+            new,
+            open,
+            closed,
         }
     }
+
+    #[tokio::test]
+    #[ignore = "needs a migrated database in DATABASE_URL"]
+    async fn test_rejected_transition_leaves_ticket_untouched() {
+        let f = fixture().await;
+        let transitions = SetStatusTransitionsRequest { to_status_ids: vec![f.open] };
+        f.service
+            .set_status_transitions(f.tenant_id, f.new, &transitions)
+            .await
+            .unwrap();
+
+        let request: UpdateTicketRequest = serde_json::from_value(serde_json::json!({
+            "title": "Printer jam on floor 2",
+            "tags": ["printer"],
+            "status_id": f.closed,
+        }))
+        .unwrap();
+        let err = f
+            .service
+            .update_ticket(f.tenant_id, f.ticket_id, f.user_id, &request)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Validation { .. }));
+
+        let ticket = f.service.get_ticket(f.tenant_id, f.ticket_id).await.unwrap();
+        assert_eq!(ticket.title, "Printer jam");
+        assert!(ticket.tags.is_empty());
+        assert_eq!(ticket.status_id, f.new);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a migrated database in DATABASE_URL"]
+    async fn test_racing_claims_have_one_winner() {
+        let f = fixture().await;
+        let rival_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (tenant_id, email, first_name, last_name) VALUES ($1, 'dana@example.com', 'Dana', 'Ortiz') RETURNING id",
+        )
+        .bind(f.tenant_id)
+        .fetch_one(f.service.db.pool())
+        .await
+        .unwrap();
+
+        let (mine, theirs) = tokio::join!(
+            f.service.claim(f.tenant_id, f.ticket_id, f.user_id),
+            f.service.claim(f.tenant_id, f.ticket_id, rival_id),
+        );
+        assert_eq!(mine.is_ok() as u8 + theirs.is_ok() as u8, 1);
+
+        let ticket = f.service.get_ticket(f.tenant_id, f.ticket_id).await.unwrap();
+        let (winner, loser) = if mine.is_ok() { (f.user_id, theirs) } else { (rival_id, mine) };
+        assert_eq!(ticket.assigned_to_id, Some(winner));
+        assert!(matches!(loser, Err(AppError::Conflict(_))));
+
+        // The winner claiming again is a no-op
+        f.service.claim(f.tenant_id, f.ticket_id, winner).await.unwrap();
+    }
 }
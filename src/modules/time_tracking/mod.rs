@@ -1 +1,15 @@
-//! time tracking module placeholder
+//! Time Tracking Module
+//!
+//! Time entries and expenses, and their approval workflow.
+
+mod models;
+#[cfg(feature = "server")]
+mod service;
+#[cfg(feature = "server")]
+mod routes;
+
+pub use models::*;
+#[cfg(feature = "server")]
+pub use service::TimeTrackingService;
+#[cfg(feature = "server")]
+pub use routes::{expense_routes, time_entry_routes};
@@ -0,0 +1,901 @@
+//! Time tracking models and types
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc, Weekday};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::modules::auth::UserRole;
+use crate::modules::calendar::WorkingHours;
+use crate::utils::error::AppError;
+use crate::utils::tenant_settings::TenantSettings;
+use crate::utils::timezone::TenantTimezone;
+
+// ============================================================================
+// ENUMS
+// ============================================================================
+
+/// Time entry approval status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalStatus {
+    #[default]
+    Pending,
+    Approved,
+    Rejected,
+}
+
+impl ApprovalStatus {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "pending" => Some(Self::Pending),
+            "approved" => Some(Self::Approved),
+            "rejected" => Some(Self::Rejected),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Approved => "approved",
+            Self::Rejected => "rejected",
+        }
+    }
+}
+
+/// How an entry reached its approval decision
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalMethod {
+    /// Matched a tenant auto-approval rule
+    Auto,
+    /// Reviewed by a manager
+    Manual,
+}
+
+impl ApprovalMethod {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "auto" => Some(Self::Auto),
+            "manual" => Some(Self::Manual),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Manual => "manual",
+        }
+    }
+}
+
+// ============================================================================
+// TIME ENTRIES
+// ============================================================================
+
+/// Time entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeEntry {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub date: NaiveDate,
+    pub start_time: Option<NaiveTime>,
+    pub end_time: Option<NaiveTime>,
+    pub duration_minutes: i32,
+    pub work_type_id: Uuid,
+    pub ticket_id: Option<Uuid>,
+    pub project_id: Option<Uuid>,
+    pub task_id: Option<Uuid>,
+    pub company_id: Uuid,
+    pub contract_id: Option<Uuid>,
+    pub notes: Option<String>,
+    pub is_billable: bool,
+    /// Rate resolved when the entry was logged
+    pub hourly_rate: Option<Decimal>,
+    pub total_amount: Option<Decimal>,
+    /// Rate card the rate came from; `None` for the work type's default rate
+    pub rate_card_id: Option<Uuid>,
+    /// Company rate the rate came from, in place of a rate card
+    pub company_rate_id: Option<Uuid>,
+    pub is_after_hours: bool,
+    pub approval_status: ApprovalStatus,
+    pub approval_method: Option<ApprovalMethod>,
+    pub approved_by_id: Option<Uuid>,
+    pub approved_at: Option<DateTime<Utc>>,
+    pub rejection_reason: Option<String>,
+    pub is_flagged: bool,
+    pub flag_reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl TimeEntry {
+    /// Only pending entries can be approved or rejected by a manager
+    pub fn ensure_reviewable(&self) -> Result<(), AppError> {
+        if self.approval_status != ApprovalStatus::Pending {
+            return Err(AppError::BadRequest(format!(
+                "Time entry is already {}",
+                self.approval_status.as_str()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Create time entry request
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CreateTimeEntryRequest {
+    pub date: NaiveDate,
+    pub start_time: Option<NaiveTime>,
+    pub end_time: Option<NaiveTime>,
+    #[validate(range(min = 1, max = 1440))]
+    pub duration_minutes: i32,
+    pub work_type_id: Uuid,
+    pub ticket_id: Option<Uuid>,
+    pub project_id: Option<Uuid>,
+    pub task_id: Option<Uuid>,
+    pub company_id: Uuid,
+    pub contract_id: Option<Uuid>,
+    pub notes: Option<String>,
+    pub internal_notes: Option<String>,
+    #[serde(default = "default_true")]
+    pub is_billable: bool,
+    /// Ask for manager review regardless of the auto-approval rules
+    #[validate(length(min = 1, max = 500))]
+    pub flag_reason: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Reject time entry request
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct RejectTimeEntryRequest {
+    #[validate(length(min = 1, max = 1000))]
+    pub reason: String,
+}
+
+// ============================================================================
+// EXPENSES
+// ============================================================================
+
+/// Expense claimed by a user, possibly billed on to the client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Expense {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub company_id: Option<Uuid>,
+    pub ticket_id: Option<Uuid>,
+    pub project_id: Option<Uuid>,
+    pub expense_date: NaiveDate,
+    pub category: String,
+    pub description: String,
+    pub amount: Decimal,
+    pub is_billable: bool,
+    /// Approved straight away unless an approval chain covers the amount
+    pub approval_status: ApprovalStatus,
+    pub approved_at: Option<DateTime<Utc>>,
+    pub rejection_reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Create expense request
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CreateExpenseRequest {
+    pub expense_date: NaiveDate,
+    #[validate(length(min = 1, max = 50))]
+    pub category: String,
+    #[validate(length(min = 1, max = 2000))]
+    pub description: String,
+    pub amount: Decimal,
+    pub company_id: Option<Uuid>,
+    pub ticket_id: Option<Uuid>,
+    pub project_id: Option<Uuid>,
+    #[serde(default = "default_true")]
+    pub is_billable: bool,
+}
+
+impl CreateExpenseRequest {
+    pub fn check(&self) -> Result<(), AppError> {
+        if self.amount < Decimal::ZERO {
+            return Err(AppError::validation_field("amount", "Must not be negative"));
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// APPROVAL RULES
+// ============================================================================
+
+/// Per-tenant approval rules, stored in `tenant_settings` under the `time_tracking` category
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimeApprovalSettings {
+    /// Entries shorter than this many minutes approve automatically
+    pub auto_approve_under_minutes: Option<i32>,
+    /// Entries logged by these roles approve automatically
+    pub trusted_roles: Vec<UserRole>,
+    /// What happens to an entry overlapping time the user already logged
+    pub overlap_policy: OverlapPolicy,
+}
+
+impl TenantSettings for TimeApprovalSettings {
+    const CATEGORY: &'static str = "time_tracking";
+}
+
+impl TimeApprovalSettings {
+    /// Decide whether a new entry can skip manager review.
+    ///
+    /// Flagged entries always wait for review.
+    pub fn auto_approves(&self, duration_minutes: i32, role: UserRole, flagged: bool) -> bool {
+        if flagged {
+            return false;
+        }
+
+        let under_threshold = self
+            .auto_approve_under_minutes
+            .is_some_and(|limit| duration_minutes < limit);
+
+        under_threshold || self.trusted_roles.contains(&role)
+    }
+}
+
+// ============================================================================
+// OVERLAPS
+// ============================================================================
+
+/// How entries overlapping the user's other logged time are handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlapPolicy {
+    Allow,
+    /// Logged, but flagged for manager review
+    #[default]
+    Flag,
+    Reject,
+}
+
+/// The wall-clock time an entry covers, in the user's time zone
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeSpan {
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+}
+
+impl TimeSpan {
+    /// Span of an entry with a start time, ending at its end time or after
+    /// its duration. Entries logged as a duration alone have no span.
+    pub fn of(date: NaiveDate, start_time: Option<NaiveTime>, end_time: Option<NaiveTime>, duration_minutes: i32) -> Option<Self> {
+        let start = date.and_time(start_time?);
+        let end = match end_time {
+            Some(end_time) if end_time > start.time() => date.and_time(end_time),
+            _ => start + Duration::minutes(duration_minutes.into()),
+        };
+        Some(Self { start, end })
+    }
+
+    /// Spans that only touch, one ending as the next starts, don't overlap
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+}
+
+/// Time the user already has logged, or is timing now
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoggedTime {
+    /// `None` for the running timer
+    pub entry_id: Option<Uuid>,
+    pub span: TimeSpan,
+}
+
+impl OverlapPolicy {
+    /// Check `span` against the user's logged time. Returns the reason to
+    /// flag the entry with, or an error when overlaps are rejected.
+    pub fn check(&self, span: Option<TimeSpan>, logged: &[LoggedTime]) -> Result<Option<String>, AppError> {
+        let Some(span) = span else {
+            return Ok(None);
+        };
+        if *self == Self::Allow {
+            return Ok(None);
+        }
+
+        let overlapping: Vec<&LoggedTime> = logged.iter().filter(|logged| logged.span.overlaps(&span)).collect();
+        let Some(first) = overlapping.first() else {
+            return Ok(None);
+        };
+
+        let reason = match (overlapping.len(), first.entry_id) {
+            (1, None) => "Overlaps the running timer".to_string(),
+            (1, Some(_)) => format!(
+                "Overlaps time logged {}-{}",
+                first.span.start.format("%H:%M"),
+                first.span.end.format("%H:%M")
+            ),
+            (count, _) => format!("Overlaps {} other time entries", count),
+        };
+        match self {
+            Self::Reject => Err(AppError::validation_field("start_time", reason)),
+            _ => Ok(Some(reason)),
+        }
+    }
+}
+
+// ============================================================================
+// TIMERS
+// ============================================================================
+
+/// A running timer; stopping it logs a time entry. Each user has at most one.
+#[derive(Debug, Clone, Serialize)]
+pub struct ActiveTimer {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub ticket_id: Option<Uuid>,
+    pub company_id: Option<Uuid>,
+    pub work_type_id: Option<Uuid>,
+    pub notes: Option<String>,
+    pub started_at: DateTime<Utc>,
+}
+
+impl ActiveTimer {
+    /// Time the timer has covered up to `now`, in the user's time zone
+    pub fn span(&self, now: DateTime<Utc>, timezone: TenantTimezone) -> TimeSpan {
+        TimeSpan {
+            start: timezone.local_datetime(self.started_at),
+            end: timezone.local_datetime(now.max(self.started_at)),
+        }
+    }
+
+    /// The entry stopping the timer at `now` logs. A timer run past
+    /// midnight keeps its start time and duration, without an end time.
+    pub fn entry_request(
+        &self,
+        now: DateTime<Utc>,
+        timezone: TenantTimezone,
+        request: &StopTimerRequest,
+    ) -> Result<CreateTimeEntryRequest, AppError> {
+        let span = self.span(now, timezone);
+        let duration_minutes = (span.end - span.start).num_minutes().max(1);
+        if duration_minutes > 1440 {
+            return Err(AppError::BadRequest(
+                "Timer ran for more than a day; log the time manually".to_string(),
+            ));
+        }
+
+        Ok(CreateTimeEntryRequest {
+            date: span.start.date(),
+            start_time: Some(span.start.time()),
+            end_time: (span.end.date() == span.start.date()).then(|| span.end.time()),
+            duration_minutes: duration_minutes as i32,
+            work_type_id: request
+                .work_type_id
+                .or(self.work_type_id)
+                .ok_or_else(|| AppError::validation_field("work_type_id", "Choose a work type"))?,
+            ticket_id: self.ticket_id,
+            project_id: None,
+            task_id: None,
+            company_id: request
+                .company_id
+                .or(self.company_id)
+                .ok_or_else(|| AppError::validation_field("company_id", "Choose a company"))?,
+            contract_id: None,
+            notes: request.notes.clone().or_else(|| self.notes.clone()),
+            internal_notes: None,
+            is_billable: request.is_billable,
+            flag_reason: None,
+        })
+    }
+}
+
+/// Start timing work
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct StartTimerRequest {
+    pub ticket_id: Option<Uuid>,
+    pub company_id: Option<Uuid>,
+    pub work_type_id: Option<Uuid>,
+    #[validate(length(max = 2000))]
+    pub notes: Option<String>,
+}
+
+/// Stop the running timer; anything set here replaces what it started with
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct StopTimerRequest {
+    pub company_id: Option<Uuid>,
+    pub work_type_id: Option<Uuid>,
+    #[validate(length(max = 2000))]
+    pub notes: Option<String>,
+    #[serde(default = "default_true")]
+    pub is_billable: bool,
+}
+
+// ============================================================================
+// RATES
+// ============================================================================
+
+/// Which rate card applies to an entry; lower levels are more specific
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateCardLevel {
+    Contract,
+    Company,
+    Default,
+}
+
+/// A rate card's terms for one work type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateCardRate {
+    pub rate_card_id: Uuid,
+    pub level: RateCardLevel,
+    pub hourly_rate: Decimal,
+    /// Explicit after-hours rate; otherwise the hourly rate times the multiplier
+    pub after_hours_rate: Option<Decimal>,
+    pub after_hours_multiplier: Decimal,
+    pub business_day_start: NaiveTime,
+    pub business_day_end: NaiveTime,
+    pub weekends_after_hours: bool,
+}
+
+impl RateCardRate {
+    /// Whether work starting at `start_time` on `date` falls outside the
+    /// technician's working hours, or the card's business day when they have
+    /// no schedule. Entries without a start time are billed at the standard rate.
+    pub fn is_after_hours(
+        &self,
+        date: NaiveDate,
+        start_time: Option<NaiveTime>,
+        shift: Option<&WorkingHours>,
+    ) -> bool {
+        let Some(start) = start_time else {
+            return false;
+        };
+        if let Some(shift) = shift.filter(|shift| !shift.is_empty()) {
+            return !shift.is_working_at(date, start);
+        }
+        if self.weekends_after_hours && matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+            return true;
+        }
+        start < self.business_day_start || start >= self.business_day_end
+    }
+}
+
+/// An hourly rate agreed with a company from a date on, replacing its
+/// company and default rate cards for work on or after that date
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompanyRate {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub company_id: Uuid,
+    /// `None` covers every work type without a rate of its own
+    pub work_type_id: Option<Uuid>,
+    pub hourly_rate: Decimal,
+    /// Explicit after-hours rate; otherwise the hourly rate times the
+    /// multiplier of the card it replaces
+    pub after_hours_rate: Option<Decimal>,
+    pub effective_from: NaiveDate,
+    pub created_by_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl CompanyRate {
+    /// The rate in effect for `work_type_id` on `date`: the latest one
+    /// effective on or before it, a rate for the work type beating a
+    /// company-wide one
+    pub fn in_effect(rates: &[CompanyRate], work_type_id: Uuid, date: NaiveDate) -> Option<&CompanyRate> {
+        rates
+            .iter()
+            .filter(|rate| rate.effective_from <= date)
+            .filter(|rate| rate.work_type_id.map_or(true, |id| id == work_type_id))
+            .max_by_key(|rate| (rate.work_type_id.is_some(), rate.effective_from))
+    }
+}
+
+/// Add a rate to a company's rate history
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CreateCompanyRateRequest {
+    pub work_type_id: Option<Uuid>,
+    pub hourly_rate: Decimal,
+    pub after_hours_rate: Option<Decimal>,
+    pub effective_from: NaiveDate,
+}
+
+impl CreateCompanyRateRequest {
+    /// Reject negative rates
+    pub fn check(&self) -> Result<(), AppError> {
+        if self.hourly_rate < Decimal::ZERO {
+            return Err(AppError::validation_field("hourly_rate", "Rate can't be negative"));
+        }
+        if self.after_hours_rate.is_some_and(|rate| rate < Decimal::ZERO) {
+            return Err(AppError::validation_field("after_hours_rate", "Rate can't be negative"));
+        }
+        Ok(())
+    }
+}
+
+/// Rate an entry is billed at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EffectiveRate {
+    pub hourly_rate: Decimal,
+    pub rate_card_id: Option<Uuid>,
+    pub company_rate_id: Option<Uuid>,
+    pub is_after_hours: bool,
+}
+
+impl EffectiveRate {
+    /// Resolve the rate for work on `date` starting at `start_time` from the rate
+    /// cards covering the entry's work type. The most specific card wins; with
+    /// none, the work type's default rate applies. `shift` is the technician's
+    /// working hours, which decide what is after hours.
+    pub fn resolve(
+        candidates: &[RateCardRate],
+        default_rate: Option<Decimal>,
+        date: NaiveDate,
+        start_time: Option<NaiveTime>,
+        shift: Option<&WorkingHours>,
+    ) -> Option<Self> {
+        let Some(card) = candidates.iter().min_by_key(|rate| rate.level) else {
+            return default_rate.map(|hourly_rate| Self {
+                hourly_rate,
+                rate_card_id: None,
+                company_rate_id: None,
+                is_after_hours: false,
+            });
+        };
+
+        let is_after_hours = card.is_after_hours(date, start_time, shift);
+        let hourly_rate = if is_after_hours {
+            card.after_hours_rate
+                .unwrap_or_else(|| (card.hourly_rate * card.after_hours_multiplier).round_dp(2))
+        } else {
+            card.hourly_rate
+        };
+
+        Some(Self {
+            hourly_rate,
+            rate_card_id: Some(card.rate_card_id),
+            company_rate_id: None,
+            is_after_hours,
+        })
+    }
+
+    /// As [`EffectiveRate::resolve`], with the company's rate in effect on the
+    /// entry's date taking the place of its company and default cards. A
+    /// contract's card still wins. The card the rate replaces still decides
+    /// what is after hours and the after-hours multiplier.
+    pub fn resolve_for_company(
+        candidates: &[RateCardRate],
+        company_rate: Option<&CompanyRate>,
+        default_rate: Option<Decimal>,
+        date: NaiveDate,
+        start_time: Option<NaiveTime>,
+        shift: Option<&WorkingHours>,
+    ) -> Option<Self> {
+        let card = candidates.iter().min_by_key(|rate| rate.level);
+        let Some(company_rate) = company_rate.filter(|_| card.map_or(true, |c| c.level != RateCardLevel::Contract))
+        else {
+            return Self::resolve(candidates, default_rate, date, start_time, shift);
+        };
+
+        let is_after_hours = card.is_some_and(|card| card.is_after_hours(date, start_time, shift));
+        let hourly_rate = match card {
+            Some(card) if is_after_hours => company_rate
+                .after_hours_rate
+                .unwrap_or_else(|| (company_rate.hourly_rate * card.after_hours_multiplier).round_dp(2)),
+            _ => company_rate.hourly_rate,
+        };
+
+        Some(Self {
+            hourly_rate,
+            rate_card_id: None,
+            company_rate_id: Some(company_rate.id),
+            is_after_hours,
+        })
+    }
+
+    /// Amount billed for `duration_minutes` at this rate
+    pub fn total(&self, duration_minutes: i32) -> Decimal {
+        (self.hourly_rate * Decimal::from(duration_minutes) / Decimal::from(60)).round_dp(2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> TimeApprovalSettings {
+        TimeApprovalSettings {
+            auto_approve_under_minutes: Some(15),
+            trusted_roles: vec![UserRole::Manager],
+        }
+    }
+
+    #[test]
+    fn test_small_entry_auto_approves() {
+        assert!(settings().auto_approves(10, UserRole::Technician, false));
+    }
+
+    #[test]
+    fn test_large_entry_stays_pending() {
+        let settings = settings();
+        assert!(!settings.auto_approves(15, UserRole::Technician, false));
+        assert!(!settings.auto_approves(240, UserRole::Technician, false));
+
+        // Flagged entries wait for review even when they would otherwise qualify
+        assert!(!settings.auto_approves(10, UserRole::Technician, true));
+        assert!(!settings.auto_approves(240, UserRole::Manager, true));
+    }
+
+    #[test]
+    fn test_trusted_role_auto_approves() {
+        assert!(settings().auto_approves(240, UserRole::Manager, false));
+        assert!(!TimeApprovalSettings::default().auto_approves(1, UserRole::Manager, false));
+    }
+
+    #[test]
+    fn test_settings_from_rows() {
+        let rows = vec![
+            ("auto_approve_under_minutes".to_string(), serde_json::json!(30)),
+            ("trusted_roles".to_string(), serde_json::json!(["admin", "manager"])),
+            ("unknown".to_string(), serde_json::json!(true)),
+        ];
+
+        let settings = TimeApprovalSettings::from_rows(rows);
+        assert_eq!(settings.auto_approve_under_minutes, Some(30));
+        assert_eq!(settings.trusted_roles, vec![UserRole::Admin, UserRole::Manager]);
+
+        let malformed = vec![("trusted_roles".to_string(), serde_json::json!("everyone"))];
+        assert_eq!(TimeApprovalSettings::from_rows(malformed), TimeApprovalSettings::default());
+    }
+
+    fn rate(level: RateCardLevel, hourly_rate: i64) -> RateCardRate {
+        RateCardRate {
+            rate_card_id: Uuid::new_v4(),
+            level,
+            hourly_rate: Decimal::from(hourly_rate),
+            after_hours_rate: None,
+            after_hours_multiplier: Decimal::new(15, 1),
+            business_day_start: NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+            business_day_end: NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+            weekends_after_hours: true,
+        }
+    }
+
+    fn at(hour: u32) -> Option<NaiveTime> {
+        NaiveTime::from_hms_opt(hour, 0, 0)
+    }
+
+    #[test]
+    fn test_after_hours_multiplier_applies_outside_business_day() {
+        // A Wednesday
+        let weekday = NaiveDate::from_ymd_opt(2025, 3, 12).unwrap();
+        let cards = vec![rate(RateCardLevel::Default, 150)];
+
+        let daytime = EffectiveRate::resolve(&cards, None, weekday, at(10), None).unwrap();
+        assert_eq!(daytime.hourly_rate, Decimal::from(150));
+        assert!(!daytime.is_after_hours);
+
+        let evening = EffectiveRate::resolve(&cards, None, weekday, at(18), None).unwrap();
+        assert_eq!(evening.hourly_rate, Decimal::from(225));
+        assert!(evening.is_after_hours);
+        assert_eq!(evening.total(90), Decimal::new(33750, 2));
+
+        let saturday = weekday + chrono::Duration::days(3);
+        assert!(EffectiveRate::resolve(&cards, None, saturday, at(10), None).unwrap().is_after_hours);
+
+        // An explicit after-hours rate takes the place of the multiplier
+        let mut explicit = rate(RateCardLevel::Default, 150);
+        explicit.after_hours_rate = Some(Decimal::from(200));
+        let early = EffectiveRate::resolve(&[explicit], None, weekday, at(6), None).unwrap();
+        assert_eq!(early.hourly_rate, Decimal::from(200));
+
+        // Without a start time the standard rate applies
+        assert!(!EffectiveRate::resolve(&cards, None, saturday, None, None).unwrap().is_after_hours);
+    }
+
+    #[test]
+    fn test_after_hours_follows_technician_shift() {
+        use crate::modules::calendar::AvailabilityWindow;
+        use crate::utils::timezone::TenantTimezone;
+
+        // A night shift, Wednesday 18:00 to 23:59
+        let weekday = NaiveDate::from_ymd_opt(2025, 3, 12).unwrap();
+        let night_shift = WorkingHours {
+            timezone: TenantTimezone::default(),
+            windows: vec![AvailabilityWindow {
+                day_of_week: 3,
+                start_time: NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+                end_time: NaiveTime::from_hms_opt(23, 59, 0).unwrap(),
+            }],
+        };
+        let cards = vec![rate(RateCardLevel::Default, 150)];
+
+        let evening = EffectiveRate::resolve(&cards, None, weekday, at(20), Some(&night_shift)).unwrap();
+        assert!(!evening.is_after_hours);
+        assert!(EffectiveRate::resolve(&cards, None, weekday, at(10), Some(&night_shift)).unwrap().is_after_hours);
+
+        // Without a schedule the card's business day applies
+        let unscheduled = WorkingHours { windows: vec![], ..night_shift };
+        assert!(EffectiveRate::resolve(&cards, None, weekday, at(20), Some(&unscheduled)).unwrap().is_after_hours);
+    }
+
+    #[test]
+    fn test_contract_rate_card_overrides_company_and_default() {
+        let date = NaiveDate::from_ymd_opt(2025, 3, 12).unwrap();
+        let contract = rate(RateCardLevel::Contract, 120);
+        let cards = vec![
+            rate(RateCardLevel::Default, 150),
+            contract.clone(),
+            rate(RateCardLevel::Company, 135),
+        ];
+
+        let resolved = EffectiveRate::resolve(&cards, Some(Decimal::from(175)), date, at(9), None).unwrap();
+        assert_eq!(resolved.hourly_rate, Decimal::from(120));
+        assert_eq!(resolved.rate_card_id, Some(contract.rate_card_id));
+
+        // Without a contract card the company's card beats the default
+        let without_contract = vec![cards[0].clone(), cards[2].clone()];
+        let company = EffectiveRate::resolve(&without_contract, None, date, at(9), None).unwrap();
+        assert_eq!(company.hourly_rate, Decimal::from(135));
+
+        // No card covers the work type: fall back to its default rate
+        let fallback = EffectiveRate::resolve(&[], Some(Decimal::from(175)), date, at(9), None).unwrap();
+        assert_eq!(fallback.hourly_rate, Decimal::from(175));
+        assert_eq!(fallback.rate_card_id, None);
+        assert!(EffectiveRate::resolve(&[], None, date, at(9), None).is_none());
+    }
+
+    fn company_rate(work_type_id: Option<Uuid>, hourly_rate: i64, effective_from: NaiveDate) -> CompanyRate {
+        CompanyRate {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::nil(),
+            company_id: Uuid::nil(),
+            work_type_id,
+            hourly_rate: Decimal::from(hourly_rate),
+            after_hours_rate: None,
+            effective_from,
+            created_by_id: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_entries_bill_at_the_rate_in_effect_on_their_date() {
+        let work_type = Uuid::new_v4();
+        let change = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let rates = vec![
+            company_rate(None, 140, NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()),
+            company_rate(None, 160, change),
+        ];
+        let cards = vec![rate(RateCardLevel::Default, 150)];
+        let resolve = |date: NaiveDate| {
+            let in_effect = CompanyRate::in_effect(&rates, work_type, date);
+            EffectiveRate::resolve_for_company(&cards, in_effect, None, date, at(10), None).unwrap()
+        };
+
+        // Back-dated before the change: the old rate
+        let before = resolve(NaiveDate::from_ymd_opt(2025, 2, 28).unwrap());
+        assert_eq!(before.hourly_rate, Decimal::from(140));
+        assert_eq!(before.company_rate_id, Some(rates[0].id));
+
+        let after = resolve(change);
+        assert_eq!(after.hourly_rate, Decimal::from(160));
+        assert_eq!(after.company_rate_id, Some(rates[1].id));
+        assert_eq!(after.rate_card_id, None);
+
+        // Before any company rate the cards apply as usual
+        let earliest = resolve(NaiveDate::from_ymd_opt(2023, 12, 31).unwrap());
+        assert_eq!(earliest.hourly_rate, Decimal::from(150));
+        assert_eq!(earliest.company_rate_id, None);
+    }
+
+    #[test]
+    fn test_company_rate_specificity_and_contract_precedence() {
+        let date = NaiveDate::from_ymd_opt(2025, 3, 12).unwrap();
+        let (onsite, remote) = (Uuid::new_v4(), Uuid::new_v4());
+        let rates = vec![
+            company_rate(None, 160, NaiveDate::from_ymd_opt(2025, 1, 1).unwrap()),
+            company_rate(Some(onsite), 190, NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()),
+        ];
+        assert_eq!(CompanyRate::in_effect(&rates, onsite, date).unwrap().hourly_rate, Decimal::from(190));
+        assert_eq!(CompanyRate::in_effect(&rates, remote, date).unwrap().hourly_rate, Decimal::from(160));
+
+        // After hours uses the replaced card's multiplier
+        let cards = vec![rate(RateCardLevel::Company, 135)];
+        let evening = EffectiveRate::resolve_for_company(&cards, rates.first(), None, date, at(19), None).unwrap();
+        assert!(evening.is_after_hours);
+        assert_eq!(evening.hourly_rate, Decimal::from(240));
+
+        // A contract's card beats the company's rate
+        let contract = vec![rate(RateCardLevel::Contract, 120), rate(RateCardLevel::Company, 135)];
+        let resolved = EffectiveRate::resolve_for_company(&contract, rates.first(), None, date, at(10), None).unwrap();
+        assert_eq!(resolved.hourly_rate, Decimal::from(120));
+        assert_eq!(resolved.company_rate_id, None);
+    }
+
+    fn span(date: NaiveDate, start: u32, end: u32) -> Option<TimeSpan> {
+        TimeSpan::of(date, at(start), at(end), ((end - start) * 60) as i32)
+    }
+
+    #[test]
+    fn test_overlapping_entries_are_flagged() {
+        let date = NaiveDate::from_ymd_opt(2025, 3, 12).unwrap();
+        let logged = [LoggedTime { entry_id: Some(Uuid::new_v4()), span: span(date, 9, 11).unwrap() }];
+
+        // 10:00-12:00 overlaps 09:00-11:00
+        let reason = OverlapPolicy::Flag.check(span(date, 10, 12), &logged).unwrap();
+        assert_eq!(reason.as_deref(), Some("Overlaps time logged 09:00-11:00"));
+        assert!(OverlapPolicy::Reject.check(span(date, 10, 12), &logged).is_err());
+        assert_eq!(OverlapPolicy::Allow.check(span(date, 10, 12), &logged).unwrap(), None);
+
+        // An entry inside another, or given by duration alone, overlaps too
+        let within = TimeSpan::of(date, NaiveTime::from_hms_opt(9, 30, 0), None, 15);
+        assert!(OverlapPolicy::Flag.check(within, &logged).unwrap().is_some());
+
+        // The running timer counts as logged time
+        let timer = ActiveTimer {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            ticket_id: None,
+            company_id: None,
+            work_type_id: None,
+            notes: None,
+            started_at: date.and_hms_opt(13, 0, 0).unwrap().and_utc(),
+        };
+        let running = LoggedTime {
+            entry_id: None,
+            span: timer.span(date.and_hms_opt(15, 0, 0).unwrap().and_utc(), TenantTimezone::default()),
+        };
+        let reason = OverlapPolicy::Flag.check(span(date, 14, 16), &[running]).unwrap();
+        assert_eq!(reason.as_deref(), Some("Overlaps the running timer"));
+    }
+
+    #[test]
+    fn test_adjacent_entries_are_accepted() {
+        let date = NaiveDate::from_ymd_opt(2025, 3, 12).unwrap();
+        let logged = [
+            LoggedTime { entry_id: Some(Uuid::new_v4()), span: span(date, 9, 11).unwrap() },
+            LoggedTime { entry_id: Some(Uuid::new_v4()), span: span(date, 12, 13).unwrap() },
+        ];
+
+        // 11:00-12:00 fills the gap exactly
+        assert_eq!(OverlapPolicy::Reject.check(span(date, 11, 12), &logged).unwrap(), None);
+        // Same hours on another day
+        assert_eq!(OverlapPolicy::Reject.check(span(date.succ_opt().unwrap(), 9, 11), &logged).unwrap(), None);
+        // Entries without a start time can't be placed, so never overlap
+        assert_eq!(OverlapPolicy::Reject.check(None, &logged).unwrap(), None);
+    }
+
+    #[test]
+    fn test_stopped_timer_becomes_an_entry() {
+        let started_at = NaiveDate::from_ymd_opt(2025, 3, 12).unwrap().and_hms_opt(9, 15, 0).unwrap().and_utc();
+        let timer = ActiveTimer {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            ticket_id: Some(Uuid::new_v4()),
+            company_id: Some(Uuid::new_v4()),
+            work_type_id: None,
+            notes: Some("Swapped the switch".to_string()),
+            started_at,
+        };
+        let mut stop = StopTimerRequest { company_id: None, work_type_id: None, notes: None, is_billable: true };
+        let now = started_at + Duration::minutes(50);
+
+        // A work type is needed from one or the other
+        assert!(timer.entry_request(now, TenantTimezone::default(), &stop).is_err());
+        stop.work_type_id = Some(Uuid::new_v4());
+        let entry = timer.entry_request(now, TenantTimezone::default(), &stop).unwrap();
+        assert_eq!(entry.start_time, at(9).map(|t| t + Duration::minutes(15)));
+        assert_eq!(entry.end_time, NaiveTime::from_hms_opt(10, 5, 0));
+        assert_eq!(entry.duration_minutes, 50);
+        assert_eq!(entry.ticket_id, timer.ticket_id);
+        assert_eq!(entry.notes.as_deref(), Some("Swapped the switch"));
+
+        assert!(timer.entry_request(started_at + Duration::hours(25), TenantTimezone::default(), &stop).is_err());
+    }
+}
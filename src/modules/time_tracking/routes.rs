@@ -0,0 +1,241 @@
+//! Time tracking API routes
+
+use axum::{
+    extract::{Path, State},
+    routing::{delete, get, post},
+    Json, Router,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::{
+    ActiveTimer, CompanyRate, CreateCompanyRateRequest, CreateExpenseRequest, CreateTimeEntryRequest, Expense,
+    RejectTimeEntryRequest, StartTimerRequest, StopTimerRequest, TimeEntry, TimeTrackingService,
+};
+use crate::modules::auth::{RequireAuth, RequireManager};
+use crate::utils::error::AppResult;
+use crate::utils::validation::ValidatedJson;
+
+#[derive(Clone)]
+pub struct TimeTrackingRouterState {
+    pub time_service: Arc<TimeTrackingService>,
+}
+
+/// Create the time entry router
+pub fn time_entry_routes(time_service: TimeTrackingService) -> Router {
+    let state = TimeTrackingRouterState {
+        time_service: Arc::new(time_service),
+    };
+
+    Router::new()
+        .route("/", post(create_entry))
+        .route("/pending", get(list_pending))
+        .route("/timer", get(get_timer).delete(discard_timer))
+        .route("/timer/start", post(start_timer))
+        .route("/timer/stop", post(stop_timer))
+        .route("/company-rates/:company_id", get(list_company_rates).post(create_company_rate))
+        .route("/company-rates/:company_id/:rate_id", delete(delete_company_rate))
+        .route("/:entry_id", get(get_entry))
+        .route("/:entry_id/approve", post(approve_entry))
+        .route("/:entry_id/reject", post(reject_entry))
+        .with_state(state)
+}
+
+/// Create the expense router. Approval decisions go through the approvals API.
+pub fn expense_routes(time_service: TimeTrackingService) -> Router {
+    let state = TimeTrackingRouterState {
+        time_service: Arc::new(time_service),
+    };
+
+    Router::new()
+        .route("/", post(create_expense))
+        .route("/:expense_id", get(get_expense))
+        .with_state(state)
+}
+
+// ============================================================================
+// TIME ENTRY HANDLERS
+// ============================================================================
+
+async fn create_entry(
+    State(state): State<TimeTrackingRouterState>,
+    RequireAuth(user): RequireAuth,
+    Json(request): Json<CreateTimeEntryRequest>,
+) -> AppResult<Json<TimeEntry>> {
+    request.validate()?;
+
+    let entry = state
+        .time_service
+        .create_entry(user.tenant_id, user.id, user.role, &request)
+        .await?;
+
+    Ok(Json(entry))
+}
+
+async fn get_entry(
+    State(state): State<TimeTrackingRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(entry_id): Path<Uuid>,
+) -> AppResult<Json<TimeEntry>> {
+    let entry = state.time_service.get_entry(user.tenant_id, entry_id).await?;
+
+    Ok(Json(entry))
+}
+
+// ============================================================================
+// COMPANY RATE HANDLERS
+// ============================================================================
+
+async fn list_company_rates(
+    State(state): State<TimeTrackingRouterState>,
+    RequireManager(user, _): RequireManager,
+    Path(company_id): Path<Uuid>,
+) -> AppResult<Json<Vec<CompanyRate>>> {
+    let rates = state.time_service.company_rates(user.tenant_id, company_id).await?;
+
+    Ok(Json(rates))
+}
+
+async fn create_company_rate(
+    State(state): State<TimeTrackingRouterState>,
+    RequireManager(user, _): RequireManager,
+    Path(company_id): Path<Uuid>,
+    Json(request): Json<CreateCompanyRateRequest>,
+) -> AppResult<Json<CompanyRate>> {
+    request.validate()?;
+
+    let rate = state
+        .time_service
+        .create_company_rate(user.tenant_id, company_id, user.id, &request)
+        .await?;
+
+    Ok(Json(rate))
+}
+
+async fn delete_company_rate(
+    State(state): State<TimeTrackingRouterState>,
+    RequireManager(user, _): RequireManager,
+    Path((company_id, rate_id)): Path<(Uuid, Uuid)>,
+) -> AppResult<()> {
+    state
+        .time_service
+        .delete_company_rate(user.tenant_id, company_id, rate_id)
+        .await
+}
+
+// ============================================================================
+// TIMER HANDLERS
+// ============================================================================
+
+async fn get_timer(
+    State(state): State<TimeTrackingRouterState>,
+    RequireAuth(user): RequireAuth,
+) -> AppResult<Json<Option<ActiveTimer>>> {
+    let timer = state.time_service.get_timer(user.tenant_id, user.id).await?;
+
+    Ok(Json(timer))
+}
+
+async fn start_timer(
+    State(state): State<TimeTrackingRouterState>,
+    RequireAuth(user): RequireAuth,
+    Json(request): Json<StartTimerRequest>,
+) -> AppResult<Json<ActiveTimer>> {
+    request.validate()?;
+
+    let timer = state.time_service.start_timer(user.tenant_id, user.id, &request).await?;
+
+    Ok(Json(timer))
+}
+
+async fn stop_timer(
+    State(state): State<TimeTrackingRouterState>,
+    RequireAuth(user): RequireAuth,
+    Json(request): Json<StopTimerRequest>,
+) -> AppResult<Json<TimeEntry>> {
+    request.validate()?;
+
+    let entry = state
+        .time_service
+        .stop_timer(user.tenant_id, user.id, user.role, &request)
+        .await?;
+
+    Ok(Json(entry))
+}
+
+async fn discard_timer(
+    State(state): State<TimeTrackingRouterState>,
+    RequireAuth(user): RequireAuth,
+) -> AppResult<()> {
+    state.time_service.discard_timer(user.tenant_id, user.id).await
+}
+
+// ============================================================================
+// APPROVAL HANDLERS
+// ============================================================================
+
+async fn list_pending(
+    State(state): State<TimeTrackingRouterState>,
+    RequireManager(user, _): RequireManager,
+) -> AppResult<Json<Vec<TimeEntry>>> {
+    let entries = state.time_service.list_pending(user.tenant_id).await?;
+
+    Ok(Json(entries))
+}
+
+async fn approve_entry(
+    State(state): State<TimeTrackingRouterState>,
+    RequireManager(user, _): RequireManager,
+    Path(entry_id): Path<Uuid>,
+) -> AppResult<Json<TimeEntry>> {
+    let entry = state
+        .time_service
+        .approve_entry(user.tenant_id, user.id, entry_id)
+        .await?;
+
+    Ok(Json(entry))
+}
+
+async fn reject_entry(
+    State(state): State<TimeTrackingRouterState>,
+    RequireManager(user, _): RequireManager,
+    Path(entry_id): Path<Uuid>,
+    Json(request): Json<RejectTimeEntryRequest>,
+) -> AppResult<Json<TimeEntry>> {
+    request.validate()?;
+
+    let entry = state
+        .time_service
+        .reject_entry(user.tenant_id, user.id, entry_id, &request)
+        .await?;
+
+    Ok(Json(entry))
+}
+
+// ============================================================================
+// EXPENSE HANDLERS
+// ============================================================================
+
+async fn create_expense(
+    State(state): State<TimeTrackingRouterState>,
+    RequireAuth(user): RequireAuth,
+    ValidatedJson(request): ValidatedJson<CreateExpenseRequest>,
+) -> AppResult<Json<Expense>> {
+    let expense = state
+        .time_service
+        .create_expense(user.tenant_id, user.id, &request)
+        .await?;
+
+    Ok(Json(expense))
+}
+
+async fn get_expense(
+    State(state): State<TimeTrackingRouterState>,
+    RequireAuth(user): RequireAuth,
+    Path(expense_id): Path<Uuid>,
+) -> AppResult<Json<Expense>> {
+    let expense = state.time_service.get_expense(user.tenant_id, expense_id).await?;
+
+    Ok(Json(expense))
+}
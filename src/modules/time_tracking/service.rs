@@ -0,0 +1,779 @@
+//! Time tracking service implementation
+
+use chrono::{Duration, NaiveDate, NaiveTime, Utc};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::modules::approvals::{ApprovalService, ApprovalSubject};
+use crate::modules::auth::UserRole;
+use crate::modules::calendar::CalendarService;
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::tenant_settings::TenantSettings;
+use crate::utils::timezone::TenantTimezone;
+
+use super::models::*;
+
+const COMPANY_RATE_COLUMNS: &str =
+    "id, tenant_id, company_id, work_type_id, hourly_rate, after_hours_rate, effective_from, created_by_id, created_at";
+
+/// Time entry and approval service
+#[derive(Clone)]
+pub struct TimeTrackingService {
+    db: Database,
+    calendar: CalendarService,
+}
+
+impl TimeTrackingService {
+    pub fn new(db: Database) -> Self {
+        Self {
+            calendar: CalendarService::new(db.clone()),
+            db,
+        }
+    }
+
+    /// Tenant approval rules
+    pub async fn approval_settings(&self, tenant_id: Uuid) -> AppResult<TimeApprovalSettings> {
+        TimeApprovalSettings::load(self.db.pool(), tenant_id).await
+    }
+
+    /// Rate an entry is billed at: its work type on the contract's rate card,
+    /// else the company rate in effect on the entry's date, else the company's
+    /// card, else the tenant default card, else the work type's default rate,
+    /// with the after-hours rate for work outside the technician's working
+    /// hours (or the card's business day without them)
+    pub async fn effective_rate(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        request: &CreateTimeEntryRequest,
+    ) -> AppResult<Option<EffectiveRate>> {
+        let default_rate: Option<Decimal> = sqlx::query_scalar::<_, Option<Decimal>>(
+            "SELECT default_rate FROM work_types WHERE id = $1 AND tenant_id = $2",
+        )
+        .bind(request.work_type_id)
+        .bind(tenant_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::validation_field("work_type_id", "Unknown work type"))?;
+
+        let candidates = sqlx::query_as::<_, RateCardRateRow>(
+            r#"
+            SELECT rc.id AS rate_card_id,
+                   CASE WHEN rc.contract_id IS NOT NULL THEN 0
+                        WHEN rc.company_id IS NOT NULL THEN 1
+                        ELSE 2 END AS level,
+                   i.hourly_rate, i.after_hours_rate, rc.after_hours_multiplier,
+                   rc.business_day_start, rc.business_day_end, rc.weekends_after_hours
+            FROM rate_cards rc
+            JOIN rate_card_items i ON i.rate_card_id = rc.id
+            WHERE rc.tenant_id = $1
+              AND i.work_type_id = $2
+              AND (
+                  rc.contract_id = $3
+                  OR (rc.contract_id IS NULL AND rc.company_id = $4)
+                  OR (rc.contract_id IS NULL AND rc.company_id IS NULL AND rc.is_default = TRUE)
+              )
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(request.work_type_id)
+        .bind(request.contract_id)
+        .bind(request.company_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let candidates: Vec<RateCardRate> = candidates.into_iter().map(Into::into).collect();
+        let company_rates = self.company_rates(tenant_id, request.company_id).await?;
+        let company_rate = CompanyRate::in_effect(&company_rates, request.work_type_id, request.date);
+        let shift = self.calendar.working_hours(tenant_id, user_id).await?;
+        Ok(EffectiveRate::resolve_for_company(
+            &candidates,
+            company_rate,
+            default_rate,
+            request.date,
+            request.start_time,
+            Some(&shift),
+        ))
+    }
+
+    /// A company's rate history, oldest first
+    pub async fn company_rates(&self, tenant_id: Uuid, company_id: Uuid) -> AppResult<Vec<CompanyRate>> {
+        let rows = sqlx::query_as::<_, CompanyRateRow>(&format!(
+            "SELECT {} FROM company_rates WHERE tenant_id = $1 AND company_id = $2 ORDER BY effective_from, work_type_id",
+            COMPANY_RATE_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(company_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Add a rate to a company's history. Entries already logged keep the
+    /// rate they were billed at.
+    pub async fn create_company_rate(
+        &self,
+        tenant_id: Uuid,
+        company_id: Uuid,
+        user_id: Uuid,
+        request: &CreateCompanyRateRequest,
+    ) -> AppResult<CompanyRate> {
+        request.check()?;
+
+        let row = sqlx::query_as::<_, CompanyRateRow>(&format!(
+            r#"
+            INSERT INTO company_rates (tenant_id, company_id, work_type_id, hourly_rate, after_hours_rate,
+                                       effective_from, created_by_id)
+            SELECT $1, c.id, $3, $4, $5, $6, $7
+            FROM companies c
+            WHERE c.tenant_id = $1 AND c.id = $2
+            RETURNING {}
+            "#,
+            COMPANY_RATE_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(company_id)
+        .bind(request.work_type_id)
+        .bind(request.hourly_rate)
+        .bind(request.after_hours_rate)
+        .bind(request.effective_from)
+        .bind(user_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Company".to_string()))?;
+
+        Ok(row.into())
+    }
+
+    /// Remove a rate from a company's history
+    pub async fn delete_company_rate(&self, tenant_id: Uuid, company_id: Uuid, rate_id: Uuid) -> AppResult<()> {
+        let result = sqlx::query("DELETE FROM company_rates WHERE tenant_id = $1 AND company_id = $2 AND id = $3")
+            .bind(tenant_id)
+            .bind(company_id)
+            .bind(rate_id)
+            .execute(self.db.pool())
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Company rate".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Log time, approving it straight away when it matches an auto-approval rule.
+    /// Overlaps with the user's other time are flagged or rejected per the
+    /// tenant's overlap policy.
+    pub async fn create_entry(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        role: UserRole,
+        request: &CreateTimeEntryRequest,
+    ) -> AppResult<TimeEntry> {
+        self.insert_entry(tenant_id, user_id, role, request, true).await
+    }
+
+    async fn insert_entry(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        role: UserRole,
+        request: &CreateTimeEntryRequest,
+        include_timer: bool,
+    ) -> AppResult<TimeEntry> {
+        if let (Some(start), Some(end)) = (request.start_time, request.end_time) {
+            if end <= start {
+                return Err(AppError::validation_field("end_time", "End time must be after start time"));
+            }
+        }
+
+        let settings = self.approval_settings(tenant_id).await?;
+        let span = TimeSpan::of(request.date, request.start_time, request.end_time, request.duration_minutes);
+        let overlap = match span {
+            Some(_) if settings.overlap_policy != OverlapPolicy::Allow => {
+                let logged = self.logged_time(tenant_id, user_id, request.date, include_timer).await?;
+                settings.overlap_policy.check(span, &logged)?
+            }
+            _ => None,
+        };
+        let flag_reason = match (&request.flag_reason, overlap) {
+            (Some(reason), Some(overlap)) => Some(format!("{}; {}", reason, overlap)),
+            (reason, overlap) => reason.clone().or(overlap),
+        };
+        let flagged = flag_reason.is_some();
+        let auto = settings.auto_approves(request.duration_minutes, role, flagged);
+
+        let rate = self.effective_rate(tenant_id, user_id, request).await?;
+        let total_amount = rate
+            .filter(|_| request.is_billable)
+            .map(|rate| rate.total(request.duration_minutes));
+
+        let (status, method) = if auto {
+            (ApprovalStatus::Approved, Some(ApprovalMethod::Auto))
+        } else {
+            (ApprovalStatus::Pending, None)
+        };
+
+        let row = sqlx::query_as::<_, TimeEntryRow>(
+            r#"
+            INSERT INTO time_entries (
+                tenant_id, user_id, date, start_time, end_time, duration_minutes, work_type_id,
+                ticket_id, project_id, task_id, company_id, contract_id, notes, internal_notes,
+                is_billable, approval_status, approval_method, approved_at, is_flagged, flag_reason,
+                hourly_rate, total_amount, rate_card_id, company_rate_id, is_after_hours
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17,
+                    CASE WHEN $16 = 'approved' THEN NOW() END, $18, $19, $20, $21, $22, $23, $24)
+            RETURNING id, tenant_id, user_id, date, start_time, end_time, duration_minutes, work_type_id,
+                      ticket_id, project_id, task_id, company_id, contract_id, notes, is_billable,
+                      hourly_rate, total_amount, rate_card_id, company_rate_id, is_after_hours,
+                      approval_status, approval_method, approved_by_id, approved_at, rejection_reason,
+                      is_flagged, flag_reason, created_at, updated_at
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(request.date)
+        .bind(request.start_time)
+        .bind(request.end_time)
+        .bind(request.duration_minutes)
+        .bind(request.work_type_id)
+        .bind(request.ticket_id)
+        .bind(request.project_id)
+        .bind(request.task_id)
+        .bind(request.company_id)
+        .bind(request.contract_id)
+        .bind(&request.notes)
+        .bind(&request.internal_notes)
+        .bind(request.is_billable)
+        .bind(status.as_str())
+        .bind(method.map(|m| m.as_str()))
+        .bind(flagged)
+        .bind(&flag_reason)
+        .bind(rate.map(|rate| rate.hourly_rate))
+        .bind(total_amount)
+        .bind(rate.and_then(|rate| rate.rate_card_id))
+        .bind(rate.and_then(|rate| rate.company_rate_id))
+        .bind(rate.is_some_and(|rate| rate.is_after_hours))
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(row.into())
+    }
+
+    /// The user's timed entries around `date`, plus the running timer when
+    /// `include_timer`. Rejected entries don't count.
+    async fn logged_time(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        date: NaiveDate,
+        include_timer: bool,
+    ) -> AppResult<Vec<LoggedTime>> {
+        // A day either side catches entries running past midnight
+        let rows = sqlx::query_as::<_, (Uuid, NaiveDate, NaiveTime, Option<NaiveTime>, i32)>(
+            r#"
+            SELECT id, date, start_time, end_time, duration_minutes
+            FROM time_entries
+            WHERE tenant_id = $1 AND user_id = $2
+              AND start_time IS NOT NULL
+              AND approval_status <> 'rejected'
+              AND date BETWEEN $3 AND $4
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(date - Duration::days(1))
+        .bind(date + Duration::days(1))
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let mut logged: Vec<LoggedTime> = rows
+            .into_iter()
+            .filter_map(|(id, date, start_time, end_time, duration_minutes)| {
+                TimeSpan::of(date, Some(start_time), end_time, duration_minutes)
+                    .map(|span| LoggedTime { entry_id: Some(id), span })
+            })
+            .collect();
+
+        if include_timer {
+            if let Some(timer) = self.get_timer(tenant_id, user_id).await? {
+                let timezone = self.user_timezone(tenant_id, user_id).await?;
+                logged.push(LoggedTime { entry_id: None, span: timer.span(Utc::now(), timezone) });
+            }
+        }
+
+        Ok(logged)
+    }
+
+    async fn user_timezone(&self, tenant_id: Uuid, user_id: Uuid) -> AppResult<TenantTimezone> {
+        let timezone: Option<String> =
+            sqlx::query_scalar("SELECT timezone FROM users WHERE tenant_id = $1 AND id = $2")
+                .bind(tenant_id)
+                .bind(user_id)
+                .fetch_optional(self.db.pool())
+                .await?;
+
+        Ok(timezone.as_deref().and_then(TenantTimezone::parse).unwrap_or_default())
+    }
+
+    /// The user's running timer, if any
+    pub async fn get_timer(&self, tenant_id: Uuid, user_id: Uuid) -> AppResult<Option<ActiveTimer>> {
+        let row = sqlx::query_as::<_, ActiveTimerRow>(
+            r#"
+            SELECT id, user_id, ticket_id, company_id, work_type_id, notes, started_at
+            FROM active_timers
+            WHERE tenant_id = $1 AND user_id = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .fetch_optional(self.db.pool())
+        .await?;
+
+        Ok(row.map(Into::into))
+    }
+
+    /// Start a timer for the user, who can only run one at a time
+    pub async fn start_timer(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        request: &StartTimerRequest,
+    ) -> AppResult<ActiveTimer> {
+        let row = sqlx::query_as::<_, ActiveTimerRow>(
+            r#"
+            INSERT INTO active_timers (tenant_id, user_id, ticket_id, company_id, work_type_id, notes, started_at)
+            VALUES ($1, $2, $3, $4, $5, $6, NOW())
+            ON CONFLICT (user_id) DO NOTHING
+            RETURNING id, user_id, ticket_id, company_id, work_type_id, notes, started_at
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(request.ticket_id)
+        .bind(request.company_id)
+        .bind(request.work_type_id)
+        .bind(&request.notes)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::Conflict("A timer is already running".to_string()))?;
+
+        Ok(row.into())
+    }
+
+    /// Stop the user's timer and log its time as an entry, checked for
+    /// overlaps like any other
+    pub async fn stop_timer(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        role: UserRole,
+        request: &StopTimerRequest,
+    ) -> AppResult<TimeEntry> {
+        let timer = self
+            .get_timer(tenant_id, user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Timer".to_string()))?;
+        let timezone = self.user_timezone(tenant_id, user_id).await?;
+        let entry_request = timer.entry_request(Utc::now(), timezone, request)?;
+
+        let entry = self.insert_entry(tenant_id, user_id, role, &entry_request, false).await?;
+
+        sqlx::query("DELETE FROM active_timers WHERE tenant_id = $1 AND id = $2")
+            .bind(tenant_id)
+            .bind(timer.id)
+            .execute(self.db.pool())
+            .await?;
+
+        Ok(entry)
+    }
+
+    /// Discard the user's timer without logging anything
+    pub async fn discard_timer(&self, tenant_id: Uuid, user_id: Uuid) -> AppResult<()> {
+        let result = sqlx::query("DELETE FROM active_timers WHERE tenant_id = $1 AND user_id = $2")
+            .bind(tenant_id)
+            .bind(user_id)
+            .execute(self.db.pool())
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Timer".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Get a time entry by ID
+    pub async fn get_entry(&self, tenant_id: Uuid, entry_id: Uuid) -> AppResult<TimeEntry> {
+        let row = sqlx::query_as::<_, TimeEntryRow>(
+            r#"
+            SELECT id, tenant_id, user_id, date, start_time, end_time, duration_minutes, work_type_id,
+                   ticket_id, project_id, task_id, company_id, contract_id, notes, is_billable,
+                   hourly_rate, total_amount, rate_card_id, company_rate_id, is_after_hours,
+                   approval_status, approval_method, approved_by_id, approved_at, rejection_reason,
+                   is_flagged, flag_reason, created_at, updated_at
+            FROM time_entries
+            WHERE tenant_id = $1 AND id = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(entry_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Time entry".to_string()))?;
+
+        Ok(row.into())
+    }
+
+    /// Entries waiting for manager review, flagged entries first
+    pub async fn list_pending(&self, tenant_id: Uuid) -> AppResult<Vec<TimeEntry>> {
+        let rows = sqlx::query_as::<_, TimeEntryRow>(
+            r#"
+            SELECT id, tenant_id, user_id, date, start_time, end_time, duration_minutes, work_type_id,
+                   ticket_id, project_id, task_id, company_id, contract_id, notes, is_billable,
+                   hourly_rate, total_amount, rate_card_id, company_rate_id, is_after_hours,
+                   approval_status, approval_method, approved_by_id, approved_at, rejection_reason,
+                   is_flagged, flag_reason, created_at, updated_at
+            FROM time_entries
+            WHERE tenant_id = $1 AND approval_status = 'pending'
+            ORDER BY is_flagged DESC, date, created_at
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Manually approve a pending entry
+    pub async fn approve_entry(
+        &self,
+        tenant_id: Uuid,
+        approver_id: Uuid,
+        entry_id: Uuid,
+    ) -> AppResult<TimeEntry> {
+        self.get_entry(tenant_id, entry_id).await?.ensure_reviewable()?;
+
+        sqlx::query(
+            r#"
+            UPDATE time_entries
+            SET approval_status = 'approved', approval_method = 'manual',
+                approved_by_id = $1, approved_at = NOW(), rejection_reason = NULL, updated_at = NOW()
+            WHERE tenant_id = $2 AND id = $3
+            "#,
+        )
+        .bind(approver_id)
+        .bind(tenant_id)
+        .bind(entry_id)
+        .execute(self.db.pool())
+        .await?;
+
+        self.get_entry(tenant_id, entry_id).await
+    }
+
+    /// Reject a pending entry with a reason
+    pub async fn reject_entry(
+        &self,
+        tenant_id: Uuid,
+        approver_id: Uuid,
+        entry_id: Uuid,
+        request: &RejectTimeEntryRequest,
+    ) -> AppResult<TimeEntry> {
+        self.get_entry(tenant_id, entry_id).await?.ensure_reviewable()?;
+
+        sqlx::query(
+            r#"
+            UPDATE time_entries
+            SET approval_status = 'rejected', approval_method = 'manual',
+                approved_by_id = $1, approved_at = NOW(), rejection_reason = $2, updated_at = NOW()
+            WHERE tenant_id = $3 AND id = $4
+            "#,
+        )
+        .bind(approver_id)
+        .bind(&request.reason)
+        .bind(tenant_id)
+        .bind(entry_id)
+        .execute(self.db.pool())
+        .await?;
+
+        self.get_entry(tenant_id, entry_id).await
+    }
+
+    /// Log an expense and start its approval. Amounts no approval chain
+    /// covers are approved straight away.
+    pub async fn create_expense(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        request: &CreateExpenseRequest,
+    ) -> AppResult<Expense> {
+        request.check()?;
+
+        let expense_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO expenses (
+                tenant_id, user_id, company_id, ticket_id, project_id, expense_date,
+                category, description, amount, is_billable
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING id
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(request.company_id)
+        .bind(request.ticket_id)
+        .bind(request.project_id)
+        .bind(request.expense_date)
+        .bind(&request.category)
+        .bind(&request.description)
+        .bind(request.amount)
+        .bind(request.is_billable)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        let approval = ApprovalService::new(self.db.clone())
+            .submit(tenant_id, ApprovalSubject::Expense, expense_id, request.amount, user_id)
+            .await?;
+
+        if approval.is_none() {
+            sqlx::query(
+                "UPDATE expenses SET approval_status = 'approved', approved_at = NOW() WHERE id = $1 AND tenant_id = $2",
+            )
+            .bind(expense_id)
+            .bind(tenant_id)
+            .execute(self.db.pool())
+            .await?;
+        }
+
+        self.get_expense(tenant_id, expense_id).await
+    }
+
+    /// Get an expense by ID
+    pub async fn get_expense(&self, tenant_id: Uuid, expense_id: Uuid) -> AppResult<Expense> {
+        let row = sqlx::query_as::<_, ExpenseRow>(
+            r#"
+            SELECT id, tenant_id, user_id, company_id, ticket_id, project_id, expense_date, category,
+                   description, amount, is_billable, approval_status, approved_at, rejection_reason,
+                   created_at, updated_at
+            FROM expenses
+            WHERE tenant_id = $1 AND id = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(expense_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Expense".to_string()))?;
+
+        Ok(row.into())
+    }
+}
+
+// ============================================================================
+// DATABASE ROW TYPES
+// ============================================================================
+
+#[derive(sqlx::FromRow)]
+struct TimeEntryRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    date: chrono::NaiveDate,
+    start_time: Option<chrono::NaiveTime>,
+    end_time: Option<chrono::NaiveTime>,
+    duration_minutes: i32,
+    work_type_id: Uuid,
+    ticket_id: Option<Uuid>,
+    project_id: Option<Uuid>,
+    task_id: Option<Uuid>,
+    company_id: Uuid,
+    contract_id: Option<Uuid>,
+    notes: Option<String>,
+    is_billable: Option<bool>,
+    hourly_rate: Option<Decimal>,
+    total_amount: Option<Decimal>,
+    rate_card_id: Option<Uuid>,
+    company_rate_id: Option<Uuid>,
+    is_after_hours: bool,
+    approval_status: Option<String>,
+    approval_method: Option<String>,
+    approved_by_id: Option<Uuid>,
+    approved_at: Option<chrono::DateTime<chrono::Utc>>,
+    rejection_reason: Option<String>,
+    is_flagged: bool,
+    flag_reason: Option<String>,
+    created_at: chrono::DateTime<chrono::Utc>,
+    updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<TimeEntryRow> for TimeEntry {
+    fn from(row: TimeEntryRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            user_id: row.user_id,
+            date: row.date,
+            start_time: row.start_time,
+            end_time: row.end_time,
+            duration_minutes: row.duration_minutes,
+            work_type_id: row.work_type_id,
+            ticket_id: row.ticket_id,
+            project_id: row.project_id,
+            task_id: row.task_id,
+            company_id: row.company_id,
+            contract_id: row.contract_id,
+            notes: row.notes,
+            is_billable: row.is_billable.unwrap_or(true),
+            hourly_rate: row.hourly_rate,
+            total_amount: row.total_amount,
+            rate_card_id: row.rate_card_id,
+            company_rate_id: row.company_rate_id,
+            is_after_hours: row.is_after_hours,
+            approval_status: row
+                .approval_status
+                .as_deref()
+                .and_then(ApprovalStatus::from_str)
+                .unwrap_or_default(),
+            approval_method: row.approval_method.as_deref().and_then(ApprovalMethod::from_str),
+            approved_by_id: row.approved_by_id,
+            approved_at: row.approved_at,
+            rejection_reason: row.rejection_reason,
+            is_flagged: row.is_flagged,
+            flag_reason: row.flag_reason,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct ActiveTimerRow {
+    id: Uuid,
+    user_id: Uuid,
+    ticket_id: Option<Uuid>,
+    company_id: Option<Uuid>,
+    work_type_id: Option<Uuid>,
+    notes: Option<String>,
+    started_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<ActiveTimerRow> for ActiveTimer {
+    fn from(row: ActiveTimerRow) -> Self {
+        Self {
+            id: row.id,
+            user_id: row.user_id,
+            ticket_id: row.ticket_id,
+            company_id: row.company_id,
+            work_type_id: row.work_type_id,
+            notes: row.notes,
+            started_at: row.started_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct RateCardRateRow {
+    rate_card_id: Uuid,
+    level: i32,
+    hourly_rate: Decimal,
+    after_hours_rate: Option<Decimal>,
+    after_hours_multiplier: Decimal,
+    business_day_start: chrono::NaiveTime,
+    business_day_end: chrono::NaiveTime,
+    weekends_after_hours: bool,
+}
+
+impl From<RateCardRateRow> for RateCardRate {
+    fn from(row: RateCardRateRow) -> Self {
+        Self {
+            rate_card_id: row.rate_card_id,
+            level: match row.level {
+                0 => RateCardLevel::Contract,
+                1 => RateCardLevel::Company,
+                _ => RateCardLevel::Default,
+            },
+            hourly_rate: row.hourly_rate,
+            after_hours_rate: row.after_hours_rate,
+            after_hours_multiplier: row.after_hours_multiplier,
+            business_day_start: row.business_day_start,
+            business_day_end: row.business_day_end,
+            weekends_after_hours: row.weekends_after_hours,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct ExpenseRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    company_id: Option<Uuid>,
+    ticket_id: Option<Uuid>,
+    project_id: Option<Uuid>,
+    expense_date: chrono::NaiveDate,
+    category: String,
+    description: String,
+    amount: Decimal,
+    is_billable: bool,
+    approval_status: String,
+    approved_at: Option<chrono::DateTime<chrono::Utc>>,
+    rejection_reason: Option<String>,
+    created_at: chrono::DateTime<chrono::Utc>,
+    updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<ExpenseRow> for Expense {
+    fn from(row: ExpenseRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            user_id: row.user_id,
+            company_id: row.company_id,
+            ticket_id: row.ticket_id,
+            project_id: row.project_id,
+            expense_date: row.expense_date,
+            category: row.category,
+            description: row.description,
+            amount: row.amount,
+            is_billable: row.is_billable,
+            approval_status: ApprovalStatus::from_str(&row.approval_status).unwrap_or_default(),
+            approved_at: row.approved_at,
+            rejection_reason: row.rejection_reason,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct CompanyRateRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    company_id: Uuid,
+    work_type_id: Option<Uuid>,
+    hourly_rate: Decimal,
+    after_hours_rate: Option<Decimal>,
+    effective_from: NaiveDate,
+    created_by_id: Option<Uuid>,
+    created_at: chrono::DateTime<Utc>,
+}
+
+impl From<CompanyRateRow> for CompanyRate {
+    fn from(row: CompanyRateRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            company_id: row.company_id,
+            work_type_id: row.work_type_id,
+            hourly_rate: row.hourly_rate,
+            after_hours_rate: row.after_hours_rate,
+            effective_from: row.effective_from,
+            created_by_id: row.created_by_id,
+            created_at: row.created_at,
+        }
+    }
+}
@@ -0,0 +1,16 @@
+//! Webhooks Module
+//!
+//! Outbound event subscriptions and their signing secrets, the delivery outbox,
+//! test sends and delivery replay.
+
+mod models;
+#[cfg(feature = "server")]
+mod service;
+#[cfg(feature = "server")]
+mod routes;
+
+pub use models::*;
+#[cfg(feature = "server")]
+pub use service::WebhookService;
+#[cfg(feature = "server")]
+pub use routes::webhook_routes;
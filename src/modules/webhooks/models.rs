@@ -0,0 +1,670 @@
+//! Webhook models and types
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::utils::error::{AppError, AppResult, FieldError};
+
+/// Event type header sent with every delivery
+pub const EVENT_HEADER: &str = "X-PSA-Event";
+/// Id of the first delivery of an event; stable across replays so receivers
+/// can use it as an idempotency key
+pub const DELIVERY_HEADER: &str = "X-PSA-Delivery";
+/// Present with value `true` on replayed deliveries
+pub const REPLAY_HEADER: &str = "X-PSA-Replay";
+/// Id of the replay attempt itself
+pub const REPLAY_ID_HEADER: &str = "X-PSA-Replay-Id";
+/// `sha256=` and the hex HMAC-SHA256 of the body, keyed by the subscription
+/// secret
+pub const SIGNATURE_HEADER: &str = "X-PSA-Signature";
+
+/// Event type of the sample sent by a test delivery
+pub const TEST_EVENT: &str = "webhook.test";
+
+/// Most deliveries re-sent by a single range replay
+pub const MAX_RANGE_REPLAY: usize = 500;
+
+/// Job that sends pending deliveries from the outbox
+pub const WEBHOOK_DELIVERY_JOB: &str = "webhooks.deliver";
+
+// ============================================================================
+// SUBSCRIPTIONS
+// ============================================================================
+
+/// An endpoint receiving events for a tenant
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookSubscription {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub url: String,
+    pub events: Vec<String>,
+    /// Shape of the payloads sent to the endpoint
+    pub payload_version: PayloadVersion,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl WebhookSubscription {
+    /// Whether events of this type are sent to the subscription
+    pub fn subscribes_to(&self, event_type: &str) -> bool {
+        self.is_active && self.events.iter().any(|e| e == "*" || e == event_type)
+    }
+}
+
+/// A new subscription together with its signing secret. This is the only
+/// response the secret appears in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatedWebhookSubscription {
+    #[serde(flatten)]
+    pub subscription: WebhookSubscription,
+    pub secret: String,
+}
+
+/// A random signing secret for a new subscription
+pub fn generate_secret() -> String {
+    format!("whsec_{}", crate::utils::crypto::generate_token(32))
+}
+
+/// Create subscription request
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CreateWebhookRequest {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+    #[validate(length(min = 1, max = 2000))]
+    pub url: String,
+    #[validate(length(min = 1, max = 100))]
+    pub events: Vec<String>,
+    /// Defaults to the latest version
+    #[serde(default)]
+    pub payload_version: Option<PayloadVersion>,
+}
+
+impl CreateWebhookRequest {
+    pub fn check(&self) -> AppResult<()> {
+        check_endpoint(&self.url)?;
+        check_events(&self.events)
+    }
+}
+
+/// Update subscription request; absent fields are left as they are
+#[derive(Debug, Clone, Deserialize, Validate, Default)]
+pub struct UpdateWebhookRequest {
+    #[validate(length(min = 1, max = 255))]
+    pub name: Option<String>,
+    #[validate(length(min = 1, max = 2000))]
+    pub url: Option<String>,
+    #[validate(length(min = 1, max = 100))]
+    pub events: Option<Vec<String>>,
+    pub payload_version: Option<PayloadVersion>,
+    pub is_active: Option<bool>,
+}
+
+impl UpdateWebhookRequest {
+    pub fn check(&self) -> AppResult<()> {
+        if let Some(ref url) = self.url {
+            check_endpoint(url)?;
+        }
+        if let Some(ref events) = self.events {
+            check_events(events)?;
+        }
+        Ok(())
+    }
+}
+
+/// Endpoints must be absolute http(s) URLs
+fn check_endpoint(url: &str) -> AppResult<()> {
+    match url::Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") && parsed.host().is_some() => Ok(()),
+        _ => Err(AppError::validation_field("url", "Must be an http or https URL")),
+    }
+}
+
+/// Event types look like `ticket.created`, or `*` for everything
+fn check_events(events: &[String]) -> AppResult<()> {
+    let valid = |event: &String| {
+        event == "*"
+            || (event.contains('.')
+                && event
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '.' || c == '_'))
+    };
+    match events.iter().find(|event| !valid(event)) {
+        Some(event) => Err(AppError::validation_field("events", format!("{} is not an event type", event))),
+        None => Ok(()),
+    }
+}
+
+/// Signature header value for a body: `sha256=` and the hex HMAC-SHA256
+pub fn sign_payload(secret: &str, body: &[u8]) -> String {
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    let digest: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!("sha256={}", digest)
+}
+
+/// Payload of the sample event sent by a test delivery
+pub fn test_payload(subscription: &WebhookSubscription, now: DateTime<Utc>) -> serde_json::Value {
+    serde_json::json!({
+        "event": TEST_EVENT,
+        "subscription_id": subscription.id,
+        "subscription_name": subscription.name,
+        "sent_at": now,
+        "message": "This is a test delivery from PSA Platform",
+    })
+}
+
+// ============================================================================
+// PAYLOAD VERSIONS
+// ============================================================================
+
+/// Payload shape a subscription is pinned to. Integrators move to a newer
+/// version when they are ready; older versions keep being served.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(into = "i32", try_from = "i32")]
+pub enum PayloadVersion {
+    /// The event's fields at the top level, with `version` alongside
+    V1,
+    /// An envelope: `version`, `type`, `occurred_at`, and the event's fields
+    /// under `data`
+    V2,
+}
+
+impl PayloadVersion {
+    pub const ALL: [Self; 2] = [Self::V1, Self::V2];
+    /// Given to new subscriptions that don't pick one
+    pub const LATEST: Self = Self::V2;
+
+    pub fn from_number(number: i32) -> Option<Self> {
+        match number {
+            1 => Some(Self::V1),
+            2 => Some(Self::V2),
+            _ => None,
+        }
+    }
+
+    pub fn number(&self) -> i32 {
+        match self {
+            Self::V1 => 1,
+            Self::V2 => 2,
+        }
+    }
+
+    /// The body sent for `event` in this version
+    pub fn render(&self, event: &WebhookEvent) -> serde_json::Value {
+        match self {
+            Self::V1 => render_v1(event),
+            Self::V2 => render_v2(event),
+        }
+    }
+}
+
+impl Default for PayloadVersion {
+    fn default() -> Self {
+        Self::LATEST
+    }
+}
+
+impl From<PayloadVersion> for i32 {
+    fn from(version: PayloadVersion) -> Self {
+        version.number()
+    }
+}
+
+impl TryFrom<i32> for PayloadVersion {
+    type Error = String;
+
+    fn try_from(number: i32) -> Result<Self, Self::Error> {
+        Self::from_number(number).ok_or_else(|| format!("unsupported payload version {}", number))
+    }
+}
+
+/// An event as raised internally, before it is shaped for a subscription's
+/// pinned payload version
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebhookEvent {
+    pub event_type: String,
+    /// The event's fields, e.g. the ticket and its `changes`
+    pub data: serde_json::Value,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl WebhookEvent {
+    pub fn new(event_type: impl Into<String>, data: serde_json::Value, occurred_at: DateTime<Utc>) -> Self {
+        Self {
+            event_type: event_type.into(),
+            data,
+            occurred_at,
+        }
+    }
+}
+
+fn render_v1(event: &WebhookEvent) -> serde_json::Value {
+    let mut payload = match &event.data {
+        serde_json::Value::Object(map) => map.clone(),
+        other => {
+            let mut map = serde_json::Map::new();
+            map.insert("data".to_string(), other.clone());
+            map
+        }
+    };
+    payload.insert("version".to_string(), PayloadVersion::V1.number().into());
+    serde_json::Value::Object(payload)
+}
+
+fn render_v2(event: &WebhookEvent) -> serde_json::Value {
+    serde_json::json!({
+        "version": PayloadVersion::V2.number(),
+        "type": event.event_type,
+        "occurred_at": event.occurred_at,
+        "data": event.data,
+    })
+}
+
+// ============================================================================
+// DELIVERIES
+// ============================================================================
+
+/// Outcome of the latest attempt at a delivery
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    #[default]
+    Pending,
+    Succeeded,
+    Failed,
+}
+
+impl DeliveryStatus {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "pending" => Some(Self::Pending),
+            "succeeded" => Some(Self::Succeeded),
+            "failed" => Some(Self::Failed),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+        }
+    }
+
+    /// Status recorded for an HTTP response code; anything outside 2xx failed
+    pub fn from_response(status: u16) -> Self {
+        if (200..300).contains(&status) {
+            Self::Succeeded
+        } else {
+            Self::Failed
+        }
+    }
+}
+
+/// One event sent, or to be sent, to one subscription
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub subscription_id: Uuid,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub status: DeliveryStatus,
+    pub attempts: i32,
+    pub response_status: Option<i32>,
+    pub error: Option<String>,
+    /// Original delivery when this one is a replay
+    pub replay_of_id: Option<Uuid>,
+    /// `X-Request-Id` of the API request that queued the delivery
+    pub request_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+impl WebhookDelivery {
+    pub fn is_replay(&self) -> bool {
+        self.replay_of_id.is_some()
+    }
+
+    /// The first delivery of this event, which replays of a replay also point at
+    pub fn original_id(&self) -> Uuid {
+        self.replay_of_id.unwrap_or(self.id)
+    }
+
+    /// A pending copy of this delivery to send again, queued by `request_id`
+    pub fn replay(&self, replay_id: Uuid, request_id: Option<String>, now: DateTime<Utc>) -> Self {
+        Self {
+            id: replay_id,
+            tenant_id: self.tenant_id,
+            subscription_id: self.subscription_id,
+            event_type: self.event_type.clone(),
+            payload: self.payload.clone(),
+            status: DeliveryStatus::Pending,
+            attempts: 0,
+            response_status: None,
+            error: None,
+            replay_of_id: Some(self.original_id()),
+            request_id,
+            created_at: now,
+            delivered_at: None,
+        }
+    }
+
+    /// Headers sent with the payload
+    pub fn headers(&self) -> Vec<(&'static str, String)> {
+        let mut headers = vec![
+            (EVENT_HEADER, self.event_type.clone()),
+            (DELIVERY_HEADER, self.original_id().to_string()),
+        ];
+        if self.is_replay() {
+            headers.push((REPLAY_HEADER, "true".to_string()));
+            headers.push((REPLAY_ID_HEADER, self.id.to_string()));
+        }
+        headers
+    }
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+/// One field an update changed, sent in the `changes` array of `*.updated` events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: serde_json::Value,
+    pub new: serde_json::Value,
+}
+
+impl FieldChange {
+    /// Top-level fields that differ between two versions of an entity, by
+    /// field name, leaving out `ignore`
+    pub fn diff<T: Serialize>(before: &T, after: &T, ignore: &[&str]) -> Vec<Self> {
+        let as_object = |value: &T| match serde_json::to_value(value) {
+            Ok(serde_json::Value::Object(map)) => map,
+            _ => serde_json::Map::new(),
+        };
+        let (before, after) = (as_object(before), as_object(after));
+
+        let mut fields: Vec<&String> = before.keys().chain(after.keys()).collect();
+        fields.sort();
+        fields.dedup();
+
+        fields
+            .into_iter()
+            .filter(|field| !ignore.contains(&field.as_str()))
+            .filter_map(|field| {
+                let old = before.get(field).cloned().unwrap_or_default();
+                let new = after.get(field).cloned().unwrap_or_default();
+                (old != new).then(|| Self {
+                    field: field.clone(),
+                    old,
+                    new,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Payload of an `*.updated` event: the entity as it is now under
+/// `entity_key`, and what the update changed
+pub fn updated_payload<T: Serialize>(entity_key: &str, entity: &T, changes: &[FieldChange]) -> serde_json::Value {
+    serde_json::json!({
+        entity_key: entity,
+        "changes": changes,
+    })
+}
+
+/// Delivery history filter
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct DeliveryFilter {
+    pub status: Option<DeliveryStatus>,
+    pub event_type: Option<String>,
+    /// `false` hides replays, `true` shows only replays
+    pub is_replay: Option<bool>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Deliveries to re-send from one subscription, by original timestamp
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct ReplayRange {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    /// Only replay deliveries with this status, e.g. `failed` after an outage
+    pub status: Option<DeliveryStatus>,
+}
+
+impl ReplayRange {
+    pub fn check(&self) -> AppResult<()> {
+        if self.to <= self.from {
+            return Err(AppError::validation(
+                "Invalid replay range",
+                vec![FieldError::new("to", "Must be after from", "range")],
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether an original delivery falls in the range
+    pub fn includes(&self, delivery: &WebhookDelivery) -> bool {
+        !delivery.is_replay()
+            && delivery.created_at >= self.from
+            && delivery.created_at < self.to
+            && self.status.map_or(true, |status| delivery.status == status)
+    }
+
+    /// Deliveries to replay from `deliveries`, oldest first so receivers see
+    /// events in the order they happened
+    pub fn plan<'a>(&self, deliveries: &'a [WebhookDelivery]) -> Vec<&'a WebhookDelivery> {
+        let mut planned: Vec<&WebhookDelivery> =
+            deliveries.iter().filter(|delivery| self.includes(delivery)).collect();
+        planned.sort_by_key(|delivery| (delivery.created_at, delivery.id));
+        planned.truncate(MAX_RANGE_REPLAY);
+        planned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn delivery(created_at: DateTime<Utc>, status: DeliveryStatus) -> WebhookDelivery {
+        WebhookDelivery {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::nil(),
+            subscription_id: Uuid::nil(),
+            event_type: "ticket.created".to_string(),
+            payload: serde_json::json!({ "ticket_number": 1042 }),
+            status,
+            attempts: 3,
+            response_status: Some(503),
+            error: Some("Service Unavailable".to_string()),
+            replay_of_id: None,
+            request_id: None,
+            created_at,
+            delivered_at: None,
+        }
+    }
+
+    fn subscription() -> WebhookSubscription {
+        WebhookSubscription {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::nil(),
+            name: "Ops channel".to_string(),
+            url: "https://hooks.example.com/psa".to_string(),
+            events: vec!["ticket.created".to_string()],
+            payload_version: PayloadVersion::V1,
+            is_active: false,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_create_returns_secret_once() {
+        let secret = generate_secret();
+        assert!(secret.starts_with("whsec_"));
+        assert_ne!(secret, generate_secret());
+
+        let created = CreatedWebhookSubscription {
+            subscription: subscription(),
+            secret: secret.clone(),
+        };
+        let json = serde_json::to_value(&created).unwrap();
+        assert_eq!(json["secret"], secret);
+        assert_eq!(json["url"], "https://hooks.example.com/psa");
+
+        // Every later read of the subscription leaves it out
+        let json = serde_json::to_value(&created.subscription).unwrap();
+        assert!(json.get("secret").is_none());
+
+        let request = CreateWebhookRequest {
+            name: "Ops channel".to_string(),
+            url: "ftp://hooks.example.com".to_string(),
+            events: vec!["ticket.created".to_string()],
+            payload_version: None,
+        };
+        assert!(request.check().is_err());
+        let bad_event = CreateWebhookRequest {
+            url: "https://hooks.example.com".to_string(),
+            events: vec!["Ticket Created".to_string()],
+            ..request.clone()
+        };
+        assert!(bad_event.check().is_err());
+        let everything = CreateWebhookRequest {
+            url: "https://hooks.example.com".to_string(),
+            events: vec!["*".to_string()],
+            ..request
+        };
+        assert!(everything.check().is_ok());
+        assert!(UpdateWebhookRequest::default().check().is_ok());
+    }
+
+    #[test]
+    fn test_test_send_is_signed() {
+        assert_eq!(
+            sign_payload("key", b"The quick brown fox jumps over the lazy dog"),
+            "sha256=f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+
+        // Paused subscriptions can still be tested
+        let subscription = subscription();
+        let payload = test_payload(&subscription, Utc::now());
+        assert_eq!(payload["event"], TEST_EVENT);
+        assert_eq!(payload["subscription_id"], subscription.id.to_string());
+
+        let body = serde_json::to_vec(&payload).unwrap();
+        let signature = sign_payload("whsec_test", &body);
+        assert_eq!(signature, sign_payload("whsec_test", &body));
+        assert_ne!(signature, sign_payload("whsec_other", &body));
+        assert_ne!(signature, sign_payload("whsec_test", b"{}"));
+    }
+
+    #[test]
+    fn test_replay_of_failed_delivery_is_marked() {
+        let now = Utc::now();
+        let failed = delivery(now - Duration::hours(2), DeliveryStatus::Failed);
+        assert_eq!(failed.headers().len(), 2);
+
+        let replay = failed.replay(Uuid::new_v4(), Some("req-1".to_string()), now);
+        assert_eq!(replay.status, DeliveryStatus::Pending);
+        assert_eq!(replay.attempts, 0);
+        assert_eq!(replay.payload, failed.payload);
+        assert_eq!(replay.replay_of_id, Some(failed.id));
+
+        let headers = replay.headers();
+        assert!(headers.contains(&(REPLAY_HEADER, "true".to_string())));
+        assert!(headers.contains(&(REPLAY_ID_HEADER, replay.id.to_string())));
+        // Receivers dedupe on the original id
+        assert!(headers.contains(&(DELIVERY_HEADER, failed.id.to_string())));
+
+        // Replaying a replay still points at the original
+        let again = replay.replay(Uuid::new_v4(), None, now);
+        assert_eq!(again.original_id(), failed.id);
+    }
+
+    #[test]
+    fn test_range_replay_orders_by_original_timestamp() {
+        let start = Utc::now() - Duration::days(1);
+        let third = delivery(start + Duration::hours(3), DeliveryStatus::Failed);
+        let first = delivery(start + Duration::hours(1), DeliveryStatus::Failed);
+        let succeeded = delivery(start + Duration::hours(2), DeliveryStatus::Succeeded);
+        let second = delivery(start + Duration::hours(2), DeliveryStatus::Failed);
+        let outside = delivery(start + Duration::hours(9), DeliveryStatus::Failed);
+        let earlier_replay = first.replay(Uuid::new_v4(), None, start + Duration::hours(4));
+
+        let deliveries = vec![
+            third.clone(),
+            outside,
+            succeeded.clone(),
+            earlier_replay,
+            first.clone(),
+            second.clone(),
+        ];
+
+        let range = ReplayRange {
+            from: start,
+            to: start + Duration::hours(8),
+            status: Some(DeliveryStatus::Failed),
+        };
+        assert!(range.check().is_ok());
+        let planned: Vec<Uuid> = range.plan(&deliveries).iter().map(|d| d.id).collect();
+        assert_eq!(planned, vec![first.id, second.id, third.id]);
+
+        let any_status = ReplayRange { status: None, ..range.clone() };
+        assert_eq!(any_status.plan(&deliveries).len(), 4);
+
+        let backwards = ReplayRange { from: range.to, to: range.from, status: None };
+        assert!(matches!(backwards.check(), Err(AppError::Validation { .. })));
+    }
+
+    #[test]
+    fn test_pinned_versions_shape_the_same_event_differently() {
+        let occurred_at = Utc::now();
+        let ticket = serde_json::json!({ "id": Uuid::nil(), "ticket_number": "T001042", "status": "in_progress" });
+        let changes = vec![FieldChange {
+            field: "status".to_string(),
+            old: "new".into(),
+            new: "in_progress".into(),
+        }];
+        let event = WebhookEvent::new("ticket.updated", updated_payload("ticket", &ticket, &changes), occurred_at);
+
+        let v1 = PayloadVersion::V1.render(&event);
+        assert_eq!(v1["version"], 1);
+        assert_eq!(v1["ticket"], ticket);
+        assert_eq!(v1["changes"][0]["field"], "status");
+        assert!(v1.get("data").is_none());
+
+        let v2 = PayloadVersion::V2.render(&event);
+        assert_eq!(v2["version"], 2);
+        assert_eq!(v2["type"], "ticket.updated");
+        assert_eq!(v2["occurred_at"], serde_json::to_value(occurred_at).unwrap());
+        assert_eq!(v2["data"]["ticket"], ticket);
+        assert!(v2.get("ticket").is_none());
+
+        assert_ne!(v1, v2);
+
+        // Pinned versions are plain numbers on the wire
+        let mut pinned = subscription();
+        pinned.payload_version = PayloadVersion::V2;
+        assert_eq!(serde_json::to_value(&pinned).unwrap()["payload_version"], 2);
+        assert_eq!(serde_json::from_str::<PayloadVersion>("1").unwrap(), PayloadVersion::V1);
+        assert!(serde_json::from_str::<PayloadVersion>("3").is_err());
+        assert_eq!(PayloadVersion::default(), PayloadVersion::LATEST);
+    }
+}
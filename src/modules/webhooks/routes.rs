@@ -0,0 +1,197 @@
+//! Webhook API routes
+
+use axum::{
+    extract::{OriginalUri, Path, Query, State},
+    routing::{delete, get, patch, post},
+    Json, Router,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::{
+    CreateWebhookRequest, CreatedWebhookSubscription, DeliveryFilter, ReplayRange,
+    UpdateWebhookRequest, WebhookDelivery, WebhookService, WebhookSubscription,
+};
+use crate::modules::auth::RequireAdmin;
+use crate::utils::error::AppResult;
+use crate::utils::pagination::{PaginatedJson, PaginatedResponse, PaginationParams};
+
+#[derive(Clone)]
+pub struct WebhookRouterState {
+    pub webhook_service: Arc<WebhookService>,
+}
+
+/// Create the webhook router
+pub fn webhook_routes(webhook_service: WebhookService) -> Router {
+    let state = WebhookRouterState {
+        webhook_service: Arc::new(webhook_service),
+    };
+
+    Router::new()
+        // Subscriptions
+        .route("/", get(list_subscriptions))
+        .route("/", post(create_subscription))
+        .route("/:subscription_id", get(get_subscription))
+        .route("/:subscription_id", patch(update_subscription))
+        .route("/:subscription_id", delete(delete_subscription))
+        .route("/:subscription_id/test", post(test_webhook))
+        // Deliveries
+        .route("/:subscription_id/deliveries", get(list_deliveries))
+        .route("/:subscription_id/replay", post(replay_range))
+        .route("/deliveries/:delivery_id/replay", post(replay_delivery))
+        .with_state(state)
+}
+
+// ============================================================================
+// SUBSCRIPTION HANDLERS
+// ============================================================================
+
+async fn list_subscriptions(
+    State(state): State<WebhookRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+) -> AppResult<Json<Vec<WebhookSubscription>>> {
+    let subscriptions = state.webhook_service.list_subscriptions(user.tenant_id).await?;
+    Ok(Json(subscriptions))
+}
+
+async fn create_subscription(
+    State(state): State<WebhookRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Json(request): Json<CreateWebhookRequest>,
+) -> AppResult<Json<CreatedWebhookSubscription>> {
+    request.validate()?;
+
+    let created = state
+        .webhook_service
+        .create_subscription(user.tenant_id, user.id, &request)
+        .await?;
+
+    Ok(Json(created))
+}
+
+async fn get_subscription(
+    State(state): State<WebhookRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Path(subscription_id): Path<Uuid>,
+) -> AppResult<Json<WebhookSubscription>> {
+    let subscription = state
+        .webhook_service
+        .get_subscription(user.tenant_id, subscription_id)
+        .await?;
+
+    Ok(Json(subscription))
+}
+
+async fn update_subscription(
+    State(state): State<WebhookRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Path(subscription_id): Path<Uuid>,
+    Json(request): Json<UpdateWebhookRequest>,
+) -> AppResult<Json<WebhookSubscription>> {
+    request.validate()?;
+
+    let subscription = state
+        .webhook_service
+        .update_subscription(user.tenant_id, subscription_id, &request)
+        .await?;
+
+    Ok(Json(subscription))
+}
+
+async fn delete_subscription(
+    State(state): State<WebhookRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Path(subscription_id): Path<Uuid>,
+) -> AppResult<()> {
+    state
+        .webhook_service
+        .delete_subscription(user.tenant_id, subscription_id)
+        .await
+}
+
+async fn test_webhook(
+    State(state): State<WebhookRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Path(subscription_id): Path<Uuid>,
+) -> AppResult<Json<WebhookDelivery>> {
+    let delivery = state
+        .webhook_service
+        .test_webhook(user.tenant_id, subscription_id)
+        .await?;
+
+    Ok(Json(delivery))
+}
+
+// ============================================================================
+// DELIVERY HANDLERS
+// ============================================================================
+
+async fn list_deliveries(
+    State(state): State<WebhookRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Path(subscription_id): Path<Uuid>,
+    Query(filter): Query<DeliveryFilter>,
+    Query(pagination): Query<PaginationParams>,
+    OriginalUri(uri): OriginalUri,
+) -> AppResult<PaginatedJson<WebhookDelivery>> {
+    let (deliveries, total) = state
+        .webhook_service
+        .list_deliveries(user.tenant_id, subscription_id, &filter, &pagination)
+        .await?;
+
+    Ok(PaginatedResponse::from_params(deliveries, &pagination, total).with_links(&uri))
+}
+
+async fn replay_delivery(
+    State(state): State<WebhookRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Path(delivery_id): Path<Uuid>,
+) -> AppResult<Json<WebhookDelivery>> {
+    let replay = state
+        .webhook_service
+        .replay_delivery(user.tenant_id, delivery_id)
+        .await?;
+
+    Ok(Json(replay))
+}
+
+async fn replay_range(
+    State(state): State<WebhookRouterState>,
+    RequireAdmin(user, _): RequireAdmin,
+    Path(subscription_id): Path<Uuid>,
+    Json(range): Json<ReplayRange>,
+) -> AppResult<Json<Vec<WebhookDelivery>>> {
+    range.validate()?;
+
+    let replays = state
+        .webhook_service
+        .replay_range(user.tenant_id, subscription_id, &range)
+        .await?;
+
+    Ok(Json(replays))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::webhooks::DeliveryStatus;
+
+    #[test]
+    fn test_delivery_history_query() {
+        let uri: axum::http::Uri = "/settings/webhooks/x/deliveries?status=failed&event_type=webhook.test&is_replay=false&page=2&per_page=10"
+            .parse()
+            .unwrap();
+        let Query(filter) = Query::<DeliveryFilter>::try_from_uri(&uri).unwrap();
+        assert_eq!(filter.status, Some(DeliveryStatus::Failed));
+        assert_eq!(filter.event_type.as_deref(), Some("webhook.test"));
+        assert_eq!(filter.is_replay, Some(false));
+
+        let Query(pagination) = Query::<PaginationParams>::try_from_uri(&uri).unwrap();
+        assert_eq!(pagination.offset(), 10);
+
+        let uri: axum::http::Uri = "/settings/webhooks/x/deliveries".parse().unwrap();
+        let Query(filter) = Query::<DeliveryFilter>::try_from_uri(&uri).unwrap();
+        assert!(filter.status.is_none() && filter.is_replay.is_none());
+    }
+}
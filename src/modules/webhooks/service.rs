@@ -0,0 +1,665 @@
+//! Webhook service implementation
+
+use std::time::Duration;
+
+use chrono::Utc;
+use reqwest::Client;
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::modules::jobs::{Job, JobQueue, NewJob, PgJobQueue};
+use crate::utils::crypto::{decrypt, encrypt, parse_encryption_key};
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::pagination::PaginationParams;
+use crate::utils::request_id;
+
+use super::models::*;
+
+/// How long an endpoint has to answer before the attempt counts as failed
+const DELIVERY_TIMEOUT_SECS: u64 = 10;
+
+/// Longest error text stored on a delivery
+const MAX_ERROR_CHARS: usize = 1000;
+
+/// Pending deliveries sent per claim loop of the delivery job
+const DELIVERY_BATCH_SIZE: usize = 50;
+
+/// Webhook outbox and delivery service
+#[derive(Clone)]
+pub struct WebhookService {
+    db: Database,
+    http: Client,
+    jobs: PgJobQueue,
+}
+
+impl WebhookService {
+    pub fn new(db: Database) -> Self {
+        let http = Client::builder()
+            .timeout(Duration::from_secs(DELIVERY_TIMEOUT_SECS))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            jobs: PgJobQueue::new(db.clone()),
+            db,
+            http,
+        }
+    }
+
+    /// Queue an event for every active subscription that wants it, returning
+    /// the delivery ids. Each delivery gets the payload rendered for its
+    /// subscription's pinned version.
+    pub async fn enqueue(
+        &self,
+        tenant_id: Uuid,
+        event_type: &str,
+        payload: &serde_json::Value,
+    ) -> AppResult<Vec<Uuid>> {
+        let subscriptions: Vec<(Uuid, i32)> = sqlx::query_as(
+            r#"
+            SELECT id, payload_version
+            FROM webhook_subscriptions
+            WHERE tenant_id = $1 AND is_active = TRUE AND ($2 = ANY(events) OR '*' = ANY(events))
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(event_type)
+        .fetch_all(self.db.pool())
+        .await?;
+        if subscriptions.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let event = WebhookEvent::new(event_type, payload.clone(), Utc::now());
+        let (subscription_ids, payloads): (Vec<Uuid>, Vec<serde_json::Value>) = subscriptions
+            .into_iter()
+            .map(|(id, version)| {
+                let version = PayloadVersion::from_number(version).unwrap_or(PayloadVersion::V1);
+                (id, version.render(&event))
+            })
+            .unzip();
+
+        let ids = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            INSERT INTO webhook_deliveries (tenant_id, subscription_id, event_type, payload, request_id)
+            SELECT $1, subscription_id, $2, payload, $5
+            FROM UNNEST($3::UUID[], $4::JSONB[]) AS rendered(subscription_id, payload)
+            RETURNING id
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(event_type)
+        .bind(&subscription_ids)
+        .bind(&payloads)
+        .bind(request_id::current())
+        .fetch_all(self.db.pool())
+        .await?;
+
+        // The periodic sweep picks the deliveries up if this can't be queued
+        let job = NewJob::new(WEBHOOK_DELIVERY_JOB, serde_json::json!({})).for_tenant(tenant_id);
+        if let Err(e) = self.jobs.enqueue(job).await {
+            tracing::warn!("Webhook delivery job for tenant {} could not be queued: {}", tenant_id, e);
+        }
+
+        Ok(ids)
+    }
+
+    /// Send up to `limit` pending deliveries, oldest first. Each one is
+    /// claimed with `SKIP LOCKED` until its outcome is stored, so workers on
+    /// several instances never send the same delivery twice. A delivery that
+    /// can't be attempted is stored as failed and the rest still go out.
+    pub async fn deliver_pending(&self, limit: usize) -> AppResult<Vec<WebhookDelivery>> {
+        let mut delivered = Vec::new();
+        while delivered.len() < limit {
+            let mut tx = self.db.pool().begin().await?;
+            let row = sqlx::query_as::<_, WebhookDeliveryRow>(&format!(
+                r#"
+                SELECT {} FROM webhook_deliveries
+                WHERE status = 'pending'
+                ORDER BY created_at
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+                "#,
+                DELIVERY_COLUMNS
+            ))
+            .fetch_optional(&mut *tx)
+            .await?;
+            let Some(row) = row else {
+                break;
+            };
+
+            let mut delivery: WebhookDelivery = row.into();
+            let attempted = match self.get_subscription(delivery.tenant_id, delivery.subscription_id).await {
+                Ok(subscription) => self.attempt(&subscription, &mut delivery).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = attempted {
+                tracing::warn!("Webhook delivery {} could not be attempted: {}", delivery.id, e);
+                delivery.attempts += 1;
+                delivery.status = DeliveryStatus::Failed;
+                delivery.response_status = None;
+                delivery.error = Some(e.to_string().chars().take(MAX_ERROR_CHARS).collect());
+                delivery.delivered_at = Some(Utc::now());
+            }
+
+            Self::record_attempt(&mut *tx, &delivery).await?;
+            tx.commit().await?;
+            delivered.push(delivery);
+        }
+
+        Ok(delivered)
+    }
+
+    /// Handler for `WEBHOOK_DELIVERY_JOB`, for registering with the job worker
+    pub async fn run_deliver_job(&self, _job: Job) -> AppResult<()> {
+        loop {
+            let delivered = self.deliver_pending(DELIVERY_BATCH_SIZE).await?;
+            if delivered.len() < DELIVERY_BATCH_SIZE {
+                return Ok(());
+            }
+        }
+    }
+
+    fn encryption_key() -> AppResult<[u8; 32]> {
+        let key = std::env::var("ENCRYPTION_KEY")
+            .map_err(|_| AppError::Configuration("ENCRYPTION_KEY is not set".to_string()))?;
+        parse_encryption_key(&key)
+    }
+
+    /// List the tenant's subscriptions
+    pub async fn list_subscriptions(&self, tenant_id: Uuid) -> AppResult<Vec<WebhookSubscription>> {
+        let rows = sqlx::query_as::<_, WebhookSubscriptionRow>(&format!(
+            "SELECT {} FROM webhook_subscriptions WHERE tenant_id = $1 ORDER BY name",
+            SUBSCRIPTION_COLUMNS
+        ))
+        .bind(tenant_id)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Create a subscription with a new signing secret, returned this once
+    pub async fn create_subscription(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        request: &CreateWebhookRequest,
+    ) -> AppResult<CreatedWebhookSubscription> {
+        request.check()?;
+
+        let secret = generate_secret();
+        let secret_encrypted = encrypt(&secret, &Self::encryption_key()?)?;
+
+        let row = sqlx::query_as::<_, WebhookSubscriptionRow>(&format!(
+            r#"
+            INSERT INTO webhook_subscriptions (tenant_id, name, url, events, payload_version, secret_encrypted, created_by_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING {}
+            "#,
+            SUBSCRIPTION_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(&request.name)
+        .bind(&request.url)
+        .bind(&request.events)
+        .bind(request.payload_version.unwrap_or_default().number())
+        .bind(&secret_encrypted)
+        .bind(user_id)
+        .fetch_one(self.db.pool())
+        .await?;
+
+        Ok(CreatedWebhookSubscription {
+            subscription: row.into(),
+            secret,
+        })
+    }
+
+    /// Update a subscription. The secret can't be read back or changed here.
+    pub async fn update_subscription(
+        &self,
+        tenant_id: Uuid,
+        subscription_id: Uuid,
+        request: &UpdateWebhookRequest,
+    ) -> AppResult<WebhookSubscription> {
+        request.check()?;
+
+        let row = sqlx::query_as::<_, WebhookSubscriptionRow>(&format!(
+            r#"
+            UPDATE webhook_subscriptions SET
+                name = COALESCE($3, name),
+                url = COALESCE($4, url),
+                events = COALESCE($5, events),
+                payload_version = COALESCE($6, payload_version),
+                is_active = COALESCE($7, is_active)
+            WHERE tenant_id = $1 AND id = $2
+            RETURNING {}
+            "#,
+            SUBSCRIPTION_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(subscription_id)
+        .bind(&request.name)
+        .bind(&request.url)
+        .bind(&request.events)
+        .bind(request.payload_version.map(|version| version.number()))
+        .bind(request.is_active)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Webhook subscription".to_string()))?;
+
+        Ok(row.into())
+    }
+
+    /// Delete a subscription and its delivery history
+    pub async fn delete_subscription(&self, tenant_id: Uuid, subscription_id: Uuid) -> AppResult<()> {
+        let result = sqlx::query("DELETE FROM webhook_subscriptions WHERE tenant_id = $1 AND id = $2")
+            .bind(tenant_id)
+            .bind(subscription_id)
+            .execute(self.db.pool())
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Webhook subscription".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Send a signed sample event to the subscription's endpoint now, paused
+    /// or not, in its pinned payload version. The attempt is kept in the
+    /// delivery history like any other.
+    pub async fn test_webhook(&self, tenant_id: Uuid, subscription_id: Uuid) -> AppResult<WebhookDelivery> {
+        let subscription = self.get_subscription(tenant_id, subscription_id).await?;
+        let now = Utc::now();
+        let event = WebhookEvent::new(TEST_EVENT, test_payload(&subscription, now), now);
+
+        let row = sqlx::query_as::<_, WebhookDeliveryRow>(&format!(
+            r#"
+            INSERT INTO webhook_deliveries (tenant_id, subscription_id, event_type, payload, request_id)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING {}
+            "#,
+            DELIVERY_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(subscription_id)
+        .bind(TEST_EVENT)
+        .bind(subscription.payload_version.render(&event))
+        .bind(request_id::current())
+        .fetch_one(self.db.pool())
+        .await?;
+
+        self.send(&subscription, row.into()).await
+    }
+
+    /// Get a subscription
+    pub async fn get_subscription(&self, tenant_id: Uuid, subscription_id: Uuid) -> AppResult<WebhookSubscription> {
+        let row = sqlx::query_as::<_, WebhookSubscriptionRow>(&format!(
+            "SELECT {} FROM webhook_subscriptions WHERE tenant_id = $1 AND id = $2",
+            SUBSCRIPTION_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(subscription_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Webhook subscription".to_string()))?;
+
+        Ok(row.into())
+    }
+
+    /// Get a delivery
+    pub async fn get_delivery(&self, tenant_id: Uuid, delivery_id: Uuid) -> AppResult<WebhookDelivery> {
+        let row = sqlx::query_as::<_, WebhookDeliveryRow>(&format!(
+            "SELECT {} FROM webhook_deliveries WHERE tenant_id = $1 AND id = $2",
+            DELIVERY_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(delivery_id)
+        .fetch_optional(self.db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Webhook delivery".to_string()))?;
+
+        Ok(row.into())
+    }
+
+    /// Delivery history for a subscription, newest first
+    pub async fn list_deliveries(
+        &self,
+        tenant_id: Uuid,
+        subscription_id: Uuid,
+        filter: &DeliveryFilter,
+        pagination: &PaginationParams,
+    ) -> AppResult<(Vec<WebhookDelivery>, u64)> {
+        // Filter placeholders follow the fixed binds, which differ between the two queries
+        let where_clause = |first_param: usize| {
+            let mut conditions = vec!["tenant_id = $1".to_string(), "subscription_id = $2".to_string()];
+            let mut param_idx = first_param;
+
+            if filter.status.is_some() {
+                conditions.push(format!("status = ${}", param_idx));
+                param_idx += 1;
+            }
+            if filter.event_type.is_some() {
+                conditions.push(format!("event_type = ${}", param_idx));
+                param_idx += 1;
+            }
+            if filter.from.is_some() {
+                conditions.push(format!("created_at >= ${}", param_idx));
+                param_idx += 1;
+            }
+            if filter.to.is_some() {
+                conditions.push(format!("created_at < ${}", param_idx));
+            }
+            match filter.is_replay {
+                Some(true) => conditions.push("replay_of_id IS NOT NULL".to_string()),
+                Some(false) => conditions.push("replay_of_id IS NULL".to_string()),
+                None => {}
+            }
+
+            conditions.join(" AND ")
+        };
+
+        let query = format!(
+            r#"
+            SELECT {}
+            FROM webhook_deliveries
+            WHERE {}
+            ORDER BY created_at DESC
+            LIMIT $3 OFFSET $4
+            "#,
+            DELIVERY_COLUMNS,
+            where_clause(5),
+        );
+        let count_query = format!("SELECT COUNT(*) FROM webhook_deliveries WHERE {}", where_clause(3));
+
+        let mut cq = sqlx::query_scalar::<_, i64>(&count_query)
+            .bind(tenant_id)
+            .bind(subscription_id);
+        if let Some(status) = filter.status {
+            cq = cq.bind(status.as_str());
+        }
+        if let Some(ref event_type) = filter.event_type {
+            cq = cq.bind(event_type.clone());
+        }
+        if let Some(from) = filter.from {
+            cq = cq.bind(from);
+        }
+        if let Some(to) = filter.to {
+            cq = cq.bind(to);
+        }
+        let total = cq.fetch_one(self.db.pool()).await?;
+
+        let page = pagination.clamped(total as u64);
+        let mut q = sqlx::query_as::<_, WebhookDeliveryRow>(&query)
+            .bind(tenant_id)
+            .bind(subscription_id)
+            .bind(page.limit() as i32)
+            .bind(page.offset() as i32);
+        if let Some(status) = filter.status {
+            q = q.bind(status.as_str());
+        }
+        if let Some(ref event_type) = filter.event_type {
+            q = q.bind(event_type.clone());
+        }
+        if let Some(from) = filter.from {
+            q = q.bind(from);
+        }
+        if let Some(to) = filter.to {
+            q = q.bind(to);
+        }
+        let rows = q.fetch_all(self.db.pool()).await?;
+
+        Ok((rows.into_iter().map(Into::into).collect(), total as u64))
+    }
+
+    /// Re-send a stored delivery's payload as a new, replay-marked delivery
+    pub async fn replay_delivery(&self, tenant_id: Uuid, delivery_id: Uuid) -> AppResult<WebhookDelivery> {
+        let original = self.get_delivery(tenant_id, delivery_id).await?;
+        let subscription = self.get_subscription(tenant_id, original.subscription_id).await?;
+
+        self.replay(&subscription, &original).await
+    }
+
+    /// Re-send a subscription's deliveries created in `range`, in the order the
+    /// events originally happened
+    pub async fn replay_range(
+        &self,
+        tenant_id: Uuid,
+        subscription_id: Uuid,
+        range: &ReplayRange,
+    ) -> AppResult<Vec<WebhookDelivery>> {
+        range.check()?;
+        let subscription = self.get_subscription(tenant_id, subscription_id).await?;
+
+        let rows = sqlx::query_as::<_, WebhookDeliveryRow>(&format!(
+            r#"
+            SELECT {}
+            FROM webhook_deliveries
+            WHERE tenant_id = $1 AND subscription_id = $2 AND replay_of_id IS NULL
+              AND created_at >= $3 AND created_at < $4
+              AND ($5::VARCHAR IS NULL OR status = $5)
+            ORDER BY created_at, id
+            LIMIT $6
+            "#,
+            DELIVERY_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(subscription_id)
+        .bind(range.from)
+        .bind(range.to)
+        .bind(range.status.map(|status| status.as_str()))
+        .bind(MAX_RANGE_REPLAY as i64)
+        .fetch_all(self.db.pool())
+        .await?;
+
+        let originals: Vec<WebhookDelivery> = rows.into_iter().map(Into::into).collect();
+
+        // Sequential on purpose: receivers get the events in their original order
+        let mut replays = Vec::new();
+        for original in range.plan(&originals) {
+            replays.push(self.replay(&subscription, original).await?);
+        }
+
+        Ok(replays)
+    }
+
+    async fn replay(
+        &self,
+        subscription: &WebhookSubscription,
+        original: &WebhookDelivery,
+    ) -> AppResult<WebhookDelivery> {
+        let replay = original.replay(Uuid::new_v4(), request_id::current(), Utc::now());
+
+        sqlx::query(
+            r#"
+            INSERT INTO webhook_deliveries
+                (id, tenant_id, subscription_id, event_type, payload, status, replay_of_id, request_id, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+        )
+        .bind(replay.id)
+        .bind(replay.tenant_id)
+        .bind(replay.subscription_id)
+        .bind(&replay.event_type)
+        .bind(&replay.payload)
+        .bind(replay.status.as_str())
+        .bind(replay.replay_of_id)
+        .bind(&replay.request_id)
+        .bind(replay.created_at)
+        .execute(self.db.pool())
+        .await?;
+
+        self.send(subscription, replay).await
+    }
+
+    /// POST the payload and record the outcome. Endpoint errors are stored on
+    /// the delivery rather than returned.
+    async fn send(
+        &self,
+        subscription: &WebhookSubscription,
+        mut delivery: WebhookDelivery,
+    ) -> AppResult<WebhookDelivery> {
+        self.attempt(subscription, &mut delivery).await?;
+        let mut conn = self.db.pool().acquire().await?;
+        Self::record_attempt(&mut *conn, &delivery).await?;
+
+        Ok(delivery)
+    }
+
+    /// POST the payload, setting the outcome on the delivery
+    async fn attempt(&self, subscription: &WebhookSubscription, delivery: &mut WebhookDelivery) -> AppResult<()> {
+        let body = serde_json::to_vec(&delivery.payload)
+            .map_err(|e| AppError::Internal(format!("Webhook payload error: {}", e)))?;
+
+        let mut request = self
+            .http
+            .post(&subscription.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        for (name, value) in delivery.headers() {
+            request = request.header(name, value);
+        }
+        if let Some(secret) = self.signing_secret(subscription).await? {
+            request = request.header(SIGNATURE_HEADER, sign_payload(&secret, &body));
+        }
+        let request = request.body(body);
+
+        delivery.attempts += 1;
+        match request.send().await {
+            Ok(response) => {
+                let status = response.status().as_u16();
+                delivery.status = DeliveryStatus::from_response(status);
+                delivery.response_status = Some(status as i32);
+                delivery.error = match delivery.status {
+                    DeliveryStatus::Succeeded => None,
+                    _ => {
+                        let body = response.text().await.unwrap_or_default();
+                        Some(body.chars().take(MAX_ERROR_CHARS).collect())
+                    }
+                };
+            }
+            Err(err) => {
+                tracing::warn!("Webhook delivery {} failed: {}", delivery.id, err);
+                delivery.status = DeliveryStatus::Failed;
+                delivery.response_status = None;
+                delivery.error = Some(err.to_string().chars().take(MAX_ERROR_CHARS).collect());
+            }
+        }
+        delivery.delivered_at = Some(Utc::now());
+
+        Ok(())
+    }
+
+    async fn record_attempt(conn: &mut PgConnection, delivery: &WebhookDelivery) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = $1, attempts = $2, response_status = $3, error = $4, delivered_at = $5
+            WHERE id = $6
+            "#,
+        )
+        .bind(delivery.status.as_str())
+        .bind(delivery.attempts)
+        .bind(delivery.response_status)
+        .bind(&delivery.error)
+        .bind(delivery.delivered_at)
+        .bind(delivery.id)
+        .execute(conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// The subscription's secret, or `None` for subscriptions from before
+    /// deliveries were signed
+    async fn signing_secret(&self, subscription: &WebhookSubscription) -> AppResult<Option<String>> {
+        let encrypted: Option<String> =
+            sqlx::query_scalar("SELECT secret_encrypted FROM webhook_subscriptions WHERE tenant_id = $1 AND id = $2")
+                .bind(subscription.tenant_id)
+                .bind(subscription.id)
+                .fetch_optional(self.db.pool())
+                .await?
+                .flatten();
+
+        encrypted
+            .map(|encrypted| decrypt(&encrypted, &Self::encryption_key()?))
+            .transpose()
+    }
+}
+
+// ============================================================================
+// DATABASE ROW TYPES
+// ============================================================================
+
+const SUBSCRIPTION_COLUMNS: &str =
+    "id, tenant_id, name, url, events, payload_version, is_active, created_at, updated_at";
+
+const DELIVERY_COLUMNS: &str = "id, tenant_id, subscription_id, event_type, payload, status, attempts, \
+     response_status, error, replay_of_id, request_id, created_at, delivered_at";
+
+#[derive(sqlx::FromRow)]
+struct WebhookSubscriptionRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    name: String,
+    url: String,
+    events: Vec<String>,
+    payload_version: i32,
+    is_active: bool,
+    created_at: chrono::DateTime<chrono::Utc>,
+    updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<WebhookSubscriptionRow> for WebhookSubscription {
+    fn from(row: WebhookSubscriptionRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            name: row.name,
+            url: row.url,
+            events: row.events,
+            payload_version: PayloadVersion::from_number(row.payload_version).unwrap_or(PayloadVersion::V1),
+            is_active: row.is_active,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct WebhookDeliveryRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    subscription_id: Uuid,
+    event_type: String,
+    payload: serde_json::Value,
+    status: String,
+    attempts: i32,
+    response_status: Option<i32>,
+    error: Option<String>,
+    replay_of_id: Option<Uuid>,
+    request_id: Option<String>,
+    created_at: chrono::DateTime<chrono::Utc>,
+    delivered_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<WebhookDeliveryRow> for WebhookDelivery {
+    fn from(row: WebhookDeliveryRow) -> Self {
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            subscription_id: row.subscription_id,
+            event_type: row.event_type,
+            payload: row.payload,
+            status: DeliveryStatus::from_str(&row.status).unwrap_or_default(),
+            attempts: row.attempts,
+            response_status: row.response_status,
+            error: row.error,
+            replay_of_id: row.replay_of_id,
+            request_id: row.request_id,
+            created_at: row.created_at,
+            delivered_at: row.delivered_at,
+        }
+    }
+}
@@ -6,6 +6,7 @@ use crate::components::{
     AppLayout, Button, ButtonVariant, Card, PageHeader, SearchInput,
     Select, SelectOption, Badge, BadgeVariant,
     DataTable, Table, TableHead, TableBody, TableRow, TableHeader, TableCell,
+    EditableSelectCell, SaveFuture, AlertType, Toast, ToastContainer,
     EmptyState, Modal, Textarea,
     PlusIcon, IconSize, ClockIcon, UserCircleIcon,
 };
@@ -17,6 +18,7 @@ pub fn TicketListPage() -> Element {
     let mut search = use_signal(String::new);
     let mut status_filter = use_signal(String::new);
     let mut priority_filter = use_signal(String::new);
+    let mut toasts = use_signal(Vec::<Toast>::new);
 
     let status_options = vec![
         SelectOption::new("", "All Statuses"),
@@ -35,6 +37,31 @@ pub fn TicketListPage() -> Element {
         SelectOption::new("low", "Low"),
     ];
 
+    // The same lists without the "All" entry, for editing rows
+    let row_options = InlineOptions {
+        statuses: status_options[1..].to_vec(),
+        priorities: priority_options[1..].to_vec(),
+        assignees: vec![
+            SelectOption {
+                value: String::new(),
+                label: "Unassigned".to_string(),
+                disabled: true,
+            },
+            SelectOption::new("1", "John Smith"),
+            SelectOption::new("2", "Jane Doe"),
+        ],
+    };
+
+    let on_error = move |message: String| {
+        let id = format!("inline-edit-{}", toasts.read().len());
+        toasts.write().push(Toast {
+            id,
+            toast_type: AlertType::Error,
+            message,
+            title: Some("Change not saved".to_string()),
+        });
+    };
+
     rsx! {
         AppLayout { title: "Tickets",
             PageHeader {
@@ -104,58 +131,142 @@ pub fn TicketListPage() -> Element {
                             number: "TKT-1234",
                             title: "Email server not responding",
                             company: "Acme Corp",
-                            status: "Open",
-                            priority: "High",
-                            assigned_to: "John Smith",
+                            status: "open",
+                            priority: "high",
+                            assigned_to: "1",
                             updated: "5 min ago",
+                            options: row_options.clone(),
+                            on_error: on_error,
                         }
                         TicketRow {
                             id: "2",
                             number: "TKT-1233",
                             title: "New user setup request",
                             company: "TechStart Inc",
-                            status: "In Progress",
-                            priority: "Medium",
-                            assigned_to: "Jane Doe",
+                            status: "in_progress",
+                            priority: "medium",
+                            assigned_to: "2",
                             updated: "1 hour ago",
+                            options: row_options.clone(),
+                            on_error: on_error,
                         }
                         TicketRow {
                             id: "3",
                             number: "TKT-1232",
                             title: "Printer configuration for new office",
                             company: "Global Widgets",
-                            status: "Pending",
-                            priority: "Low",
-                            assigned_to: "Unassigned",
+                            status: "pending",
+                            priority: "low",
+                            assigned_to: "",
                             updated: "2 hours ago",
+                            options: row_options.clone(),
+                            on_error: on_error,
                         }
                         TicketRow {
                             id: "4",
                             number: "TKT-1231",
                             title: "VPN connection issues for remote workers",
                             company: "Acme Corp",
-                            status: "Open",
-                            priority: "Critical",
-                            assigned_to: "John Smith",
+                            status: "open",
+                            priority: "critical",
+                            assigned_to: "1",
                             updated: "3 hours ago",
+                            options: row_options.clone(),
+                            on_error: on_error,
                         }
                         TicketRow {
                             id: "5",
                             number: "TKT-1230",
                             title: "Software license renewal required",
                             company: "TechStart Inc",
-                            status: "Resolved",
-                            priority: "Medium",
-                            assigned_to: "Jane Doe",
+                            status: "resolved",
+                            priority: "medium",
+                            assigned_to: "2",
                             updated: "1 day ago",
+                            options: row_options.clone(),
+                            on_error: on_error,
                         }
                     }
                 }
             }
+
+            ToastContainer {
+                toasts: toasts.read().clone(),
+                ondismiss: move |id: String| toasts.write().retain(|toast| toast.id != id),
+            }
+        }
+    }
+}
+
+/// Choices for the inline-editable list columns
+#[derive(Clone, PartialEq)]
+struct InlineOptions {
+    statuses: Vec<SelectOption>,
+    priorities: Vec<SelectOption>,
+    assignees: Vec<SelectOption>,
+}
+
+/// A ticket field editable from the list
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum InlineField {
+    Status,
+    Priority,
+    Assignee,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum InlineMethod {
+    Put,
+    Post,
+}
+
+/// The API call an inline edit makes
+#[derive(Clone, Debug, PartialEq)]
+struct InlineUpdate {
+    method: InlineMethod,
+    path: String,
+    body: serde_json::Value,
+}
+
+impl InlineField {
+    /// Status and priority go through `update_ticket`; the assignee through
+    /// `assign_ticket`, which also notifies them
+    fn update(self, ticket_id: &str, value: &str) -> InlineUpdate {
+        let (method, path, field) = match self {
+            Self::Status => (InlineMethod::Put, format!("/tickets/{}", ticket_id), "status_id"),
+            Self::Priority => (InlineMethod::Put, format!("/tickets/{}", ticket_id), "priority_id"),
+            Self::Assignee => (InlineMethod::Post, format!("/tickets/{}/assign", ticket_id), "assigned_to_id"),
+        };
+
+        InlineUpdate {
+            method,
+            path,
+            body: serde_json::json!({ field: value }),
         }
     }
 }
 
+fn save_inline(update: InlineUpdate) -> SaveFuture {
+    Box::pin(async move {
+        #[cfg(feature = "web")]
+        {
+            use crate::hooks::api;
+
+            let result = match update.method {
+                InlineMethod::Put => api::put::<serde_json::Value, _>(&update.path, &update.body).await,
+                InlineMethod::Post => api::post::<serde_json::Value, _>(&update.path, &update.body).await,
+            };
+            result.map(|_| ())
+        }
+
+        #[cfg(not(feature = "web"))]
+        {
+            let _ = update;
+            Err("Not available outside the browser".to_string())
+        }
+    })
+}
+
 #[derive(Props, Clone, PartialEq)]
 struct TicketRowProps {
     id: String,
@@ -164,27 +275,18 @@ struct TicketRowProps {
     company: String,
     status: String,
     priority: String,
+    /// Assignee id, empty when unassigned
     assigned_to: String,
     updated: String,
+    options: InlineOptions,
+    on_error: EventHandler<String>,
 }
 
 #[component]
 fn TicketRow(props: TicketRowProps) -> Element {
-    let status_variant = match props.status.as_str() {
-        "Open" => BadgeVariant::Blue,
-        "In Progress" => BadgeVariant::Yellow,
-        "Pending" => BadgeVariant::Gray,
-        "Resolved" => BadgeVariant::Green,
-        "Closed" => BadgeVariant::Gray,
-        _ => BadgeVariant::Gray,
-    };
-
-    let priority_variant = match props.priority.as_str() {
-        "Critical" => BadgeVariant::Red,
-        "High" => BadgeVariant::Red,
-        "Medium" => BadgeVariant::Yellow,
-        "Low" => BadgeVariant::Green,
-        _ => BadgeVariant::Gray,
+    let save = |field: InlineField| {
+        let ticket_id = props.id.clone();
+        move |value: String| save_inline(field.update(&ticket_id, &value))
     };
 
     rsx! {
@@ -203,18 +305,23 @@ fn TicketRow(props: TicketRowProps) -> Element {
                 }
             }
             TableCell { "{props.company}" }
-            TableCell {
-                Badge { variant: status_variant, "{props.status}" }
+            EditableSelectCell {
+                value: props.status.clone(),
+                options: props.options.statuses.clone(),
+                on_save: save(InlineField::Status),
+                on_error: props.on_error,
             }
-            TableCell {
-                Badge { variant: priority_variant, "{props.priority}" }
+            EditableSelectCell {
+                value: props.priority.clone(),
+                options: props.options.priorities.clone(),
+                on_save: save(InlineField::Priority),
+                on_error: props.on_error,
             }
-            TableCell {
-                if props.assigned_to == "Unassigned" {
-                    span { class: "text-gray-400 italic", "Unassigned" }
-                } else {
-                    span { "{props.assigned_to}" }
-                }
+            EditableSelectCell {
+                value: props.assigned_to.clone(),
+                options: props.options.assignees.clone(),
+                on_save: save(InlineField::Assignee),
+                on_error: props.on_error,
             }
             TableCell { class: "text-gray-500",
                 "{props.updated}"
@@ -572,3 +679,52 @@ fn TimelineItem(props: TimelineItemProps) -> Element {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::InlineEdit;
+
+    #[test]
+    fn test_inline_edit_updates_then_reverts_on_failure() {
+        // Stands in for save_inline: records the call and fails it
+        fn save(calls: &mut Vec<InlineUpdate>, update: InlineUpdate) -> Result<(), String> {
+            calls.push(update);
+            Err("Request failed with status: 500".to_string())
+        }
+        let mut calls = Vec::new();
+
+        // Shown straight away, saved through update_ticket with the ticket's id
+        let mut status = InlineEdit::new("open");
+        let value = status.begin("pending".to_string()).unwrap();
+        assert_eq!(status.shown, "pending");
+        assert!(status.begin("closed".to_string()).is_none(), "one save at a time");
+        let result = save(&mut calls, InlineField::Status.update("42", &value));
+        assert_eq!(
+            calls[0],
+            InlineUpdate {
+                method: InlineMethod::Put,
+                path: "/tickets/42".to_string(),
+                body: serde_json::json!({ "status_id": "pending" }),
+            }
+        );
+
+        // The failure comes back for the toast and the old value returns
+        assert_eq!(status.settle(result), Err("Request failed with status: 500".to_string()));
+        assert_eq!(status.shown, "open");
+        assert!(!status.saving);
+
+        // Assignee changes go through assign_ticket
+        let mut assignee = InlineEdit::new("");
+        let value = assignee.begin("7".to_string()).unwrap();
+        let _ = save(&mut calls, InlineField::Assignee.update("42", &value));
+        assert_eq!(calls[1].method, InlineMethod::Post);
+        assert_eq!(calls[1].path, "/tickets/42/assign");
+        assert_eq!(calls[1].body, serde_json::json!({ "assigned_to_id": "7" }));
+
+        // A save that succeeds sticks; re-picking the same value saves nothing
+        assert_eq!(assignee.settle(Ok(())), Ok(()));
+        assert_eq!(assignee.committed, "7");
+        assert!(assignee.begin("7".to_string()).is_none());
+    }
+}
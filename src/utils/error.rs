@@ -173,56 +173,62 @@ impl AppError {
         }
     }
 
-    /// Get the error code for API responses
+    /// Machine-readable error code for API responses. These are part of the
+    /// API contract: clients switch on them, so never rename one.
     pub fn error_code(&self) -> &'static str {
         match self {
-            Self::Unauthorized => "UNAUTHORIZED",
-            Self::Forbidden(_) => "FORBIDDEN",
-            Self::NotFound(_) => "NOT_FOUND",
-            Self::Validation { .. } => "VALIDATION_ERROR",
-            Self::Conflict(_) => "CONFLICT",
-            Self::BadRequest(_) => "BAD_REQUEST",
-            Self::RateLimited => "RATE_LIMITED",
-            Self::Database(_) => "DATABASE_ERROR",
-            Self::ExternalService { .. } => "EXTERNAL_SERVICE_ERROR",
-            Self::Internal(_) => "INTERNAL_ERROR",
-            Self::Configuration(_) => "CONFIGURATION_ERROR",
-            Self::Tenant(_) => "TENANT_ERROR",
-            Self::File(_) => "FILE_ERROR",
-            Self::Email(_) => "EMAIL_ERROR",
-            Self::Payment(_) => "PAYMENT_ERROR",
-            Self::Integration(_) => "INTEGRATION_ERROR",
+            Self::Unauthorized => "unauthorized",
+            Self::Forbidden(_) => "forbidden",
+            Self::NotFound(_) => "not_found",
+            Self::Validation { .. } => "validation_error",
+            Self::Conflict(_) => "conflict",
+            Self::BadRequest(_) => "bad_request",
+            Self::RateLimited => "rate_limited",
+            Self::Database(_) => "database_error",
+            Self::ExternalService { .. } => "external_service_error",
+            Self::Internal(_) => "internal_error",
+            Self::Configuration(_) => "configuration_error",
+            Self::Tenant(_) => "tenant_error",
+            Self::File(_) => "file_error",
+            Self::Email(_) => "email_error",
+            Self::Payment(_) => "payment_error",
+            Self::Integration(_) => "integration_error",
         }
     }
 }
 
-/// API error response format
+/// JSON body of every API error response
+///
+/// ```json
+/// { "code": "validation_error", "message": "Validation failed: ...",
+///   "field_errors": [{ "field": "title", "message": "...", "code": "length" }],
+///   "request_id": "0f6c..." }
+/// ```
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ErrorResponse {
-    pub error: ErrorDetail,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ErrorDetail {
+    /// Stable code from [`AppError::error_code`]
     pub code: String,
     pub message: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub errors: Option<Vec<FieldError>>,
+    /// Per-field detail, only for `validation_error`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field_errors: Option<Vec<FieldError>>,
+    /// Correlation id of the failed request, for support reports
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
 }
 
 impl From<AppError> for ErrorResponse {
     fn from(error: AppError) -> Self {
-        let errors = match &error {
+        let field_errors = match &error {
             AppError::Validation { errors, .. } => Some(errors.clone()),
             _ => None,
         };
 
         Self {
-            error: ErrorDetail {
-                code: error.error_code().to_string(),
-                message: error.to_string(),
-                errors,
-            },
+            code: error.error_code().to_string(),
+            message: error.to_string(),
+            field_errors,
+            request_id: None,
         }
     }
 }
@@ -240,7 +246,8 @@ mod server_impl {
             let status = StatusCode::from_u16(self.status_code())
                 .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
 
-            let body = ErrorResponse::from(self);
+            let mut body = ErrorResponse::from(self);
+            body.request_id = crate::utils::request_id::current();
 
             (status, Json(body)).into_response()
         }
@@ -310,19 +317,17 @@ mod server_impl {
 
 impl From<validator::ValidationErrors> for AppError {
     fn from(errors: validator::ValidationErrors) -> Self {
-        let field_errors: Vec<FieldError> = errors
+        let mut field_errors: Vec<FieldError> = errors
             .field_errors()
             .iter()
             .flat_map(|(field, errs)| {
                 errs.iter().map(|e| {
-                    FieldError::new(
-                        field.to_string(),
-                        e.message.clone().unwrap_or_default().to_string(),
-                        e.code.to_string(),
-                    )
+                    FieldError::new(field.to_string(), validation_message(e), e.code.to_string())
                 })
             })
             .collect();
+        // The validator keeps fields in a map; sort so responses are stable
+        field_errors.sort_by(|a, b| a.field.cmp(&b.field));
 
         Self::Validation {
             message: "Validation failed".to_string(),
@@ -331,6 +336,28 @@ impl From<validator::ValidationErrors> for AppError {
     }
 }
 
+/// The rule's own message, or one describing the failed rule
+fn validation_message(error: &validator::ValidationError) -> String {
+    if let Some(message) = &error.message {
+        return message.to_string();
+    }
+
+    let param = |name: &str| error.params.get(name).map(|value| value.to_string());
+    match (error.code.as_ref(), param("min"), param("max")) {
+        ("length", Some(min), Some(max)) => format!("Must be between {} and {} characters", min, max),
+        ("length", Some(min), None) if min == "1" => "Must not be empty".to_string(),
+        ("length", Some(min), None) => format!("Must be at least {} characters", min),
+        ("length", None, Some(max)) => format!("Must be at most {} characters", max),
+        ("range", Some(min), Some(max)) => format!("Must be between {} and {}", min, max),
+        ("range", Some(min), None) => format!("Must be at least {}", min),
+        ("range", None, Some(max)) => format!("Must be at most {}", max),
+        ("email", _, _) => "Must be a valid email address".to_string(),
+        ("url", _, _) => "Must be a valid URL".to_string(),
+        ("required", _, _) => "This field is required".to_string(),
+        _ => "Invalid value".to_string(),
+    }
+}
+
 impl From<serde_json::Error> for AppError {
     fn from(err: serde_json::Error) -> Self {
         Self::BadRequest(format!("JSON error: {}", err))
@@ -368,10 +395,43 @@ mod tests {
 
     #[test]
     fn test_app_error_codes() {
-        assert_eq!(AppError::Unauthorized.error_code(), "UNAUTHORIZED");
-        assert_eq!(AppError::Forbidden("test".to_string()).error_code(), "FORBIDDEN");
-        assert_eq!(AppError::NotFound("test".to_string()).error_code(), "NOT_FOUND");
-        assert_eq!(AppError::RateLimited.error_code(), "RATE_LIMITED");
+        assert_eq!(AppError::Unauthorized.error_code(), "unauthorized");
+        assert_eq!(AppError::Forbidden("test".to_string()).error_code(), "forbidden");
+        assert_eq!(AppError::NotFound("test".to_string()).error_code(), "not_found");
+        assert_eq!(AppError::RateLimited.error_code(), "rate_limited");
+    }
+
+    #[test]
+    fn test_every_variant_code_and_status() {
+        let s = || "test".to_string();
+        let cases = vec![
+            (AppError::Unauthorized, "unauthorized", 401),
+            (AppError::Forbidden(s()), "forbidden", 403),
+            (AppError::NotFound(s()), "not_found", 404),
+            (AppError::validation(s(), vec![]), "validation_error", 422),
+            (AppError::Conflict(s()), "conflict", 409),
+            (AppError::BadRequest(s()), "bad_request", 400),
+            (AppError::RateLimited, "rate_limited", 429),
+            (AppError::Database(s()), "database_error", 500),
+            (AppError::external_service("Stripe", "down"), "external_service_error", 502),
+            (AppError::Internal(s()), "internal_error", 500),
+            (AppError::Configuration(s()), "configuration_error", 500),
+            (AppError::Tenant(s()), "tenant_error", 400),
+            (AppError::File(s()), "file_error", 400),
+            (AppError::Email(s()), "email_error", 500),
+            (AppError::Payment(s()), "payment_error", 402),
+            (AppError::Integration(s()), "integration_error", 502),
+        ];
+
+        for (error, code, status) in cases {
+            assert_eq!(error.status_code(), status, "{:?}", error);
+            let json = serde_json::to_value(ErrorResponse::from(error)).unwrap();
+            assert_eq!(json["code"], code);
+            assert!(json["message"].is_string());
+            // Only validation errors carry field detail
+            assert_eq!(json.get("field_errors").is_some(), code == "validation_error");
+            assert!(json.get("request_id").is_none());
+        }
     }
 
     #[test]
@@ -440,9 +500,9 @@ mod tests {
         };
         let response = ErrorResponse::from(error);
 
-        assert_eq!(response.error.code, "VALIDATION_ERROR");
-        assert!(response.error.errors.is_some());
-        assert_eq!(response.error.errors.unwrap().len(), 1);
+        assert_eq!(response.code, "validation_error");
+        assert!(response.field_errors.is_some());
+        assert_eq!(response.field_errors.unwrap().len(), 1);
     }
 
     #[test]
@@ -450,8 +510,8 @@ mod tests {
         let error = AppError::Unauthorized;
         let response = ErrorResponse::from(error);
 
-        assert_eq!(response.error.code, "UNAUTHORIZED");
-        assert!(response.error.errors.is_none());
+        assert_eq!(response.code, "unauthorized");
+        assert!(response.field_errors.is_none());
     }
 
     #[test]
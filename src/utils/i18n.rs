@@ -0,0 +1,183 @@
+//! Translations for user-facing strings
+//!
+//! Messages live in one JSON catalog per locale under `locales/`, keyed by
+//! dotted names such as `nav.tickets`. A lookup tries the user's or contact's
+//! locale, then its language (`fr-CA` -> `fr`), then the tenant's default
+//! locale, then English. A key missing everywhere comes back as the key
+//! itself so it is easy to spot.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Locale every catalog falls back to; its catalog has every key
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Catalogs compiled into the binary
+const CATALOGS: &[(&str, &str)] = &[
+    ("en", include_str!("../../locales/en.json")),
+    ("es", include_str!("../../locales/es.json")),
+    ("fr", include_str!("../../locales/fr.json")),
+    ("de", include_str!("../../locales/de.json")),
+];
+
+type Catalog = HashMap<String, String>;
+
+fn catalogs() -> &'static HashMap<&'static str, Catalog> {
+    static CATALOGS_BY_LOCALE: OnceLock<HashMap<&'static str, Catalog>> = OnceLock::new();
+    CATALOGS_BY_LOCALE.get_or_init(|| {
+        CATALOGS
+            .iter()
+            .map(|(locale, source)| {
+                let catalog = serde_json::from_str(source)
+                    .unwrap_or_else(|e| panic!("locales/{}.json is not a flat string map: {}", locale, e));
+                (*locale, catalog)
+            })
+            .collect()
+    })
+}
+
+/// Locales with a catalog
+pub fn supported_locales() -> Vec<&'static str> {
+    CATALOGS.iter().map(|(locale, _)| *locale).collect()
+}
+
+/// Where to look for a message: the reader's locale, then the tenant's
+/// default. English is always tried last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocaleChain<'a> {
+    pub locale: &'a str,
+    pub tenant_default: Option<&'a str>,
+}
+
+impl<'a> LocaleChain<'a> {
+    pub fn new(locale: &'a str) -> Self {
+        Self {
+            locale,
+            tenant_default: None,
+        }
+    }
+
+    pub fn with_tenant_default(mut self, tenant_default: &'a str) -> Self {
+        self.tenant_default = Some(tenant_default).filter(|locale| !locale.trim().is_empty());
+        self
+    }
+
+    /// Locales to try, most preferred first, without repeats
+    pub fn candidates(&self) -> Vec<&'a str> {
+        let mut candidates: Vec<&'a str> = Vec::new();
+        let sources = [Some(self.locale), self.tenant_default, Some(DEFAULT_LOCALE)];
+        for locale in sources.into_iter().flatten().map(str::trim).filter(|locale| !locale.is_empty()) {
+            for candidate in [locale, language(locale)] {
+                if !candidates.iter().any(|seen| seen.eq_ignore_ascii_case(candidate)) {
+                    candidates.push(candidate);
+                }
+            }
+        }
+        candidates
+    }
+}
+
+impl<'a> From<&'a str> for LocaleChain<'a> {
+    fn from(locale: &'a str) -> Self {
+        Self::new(locale)
+    }
+}
+
+impl<'a> From<&'a String> for LocaleChain<'a> {
+    fn from(locale: &'a String) -> Self {
+        Self::new(locale)
+    }
+}
+
+impl<'a> From<&LocaleChain<'a>> for LocaleChain<'a> {
+    fn from(chain: &LocaleChain<'a>) -> Self {
+        *chain
+    }
+}
+
+/// `fr` for `fr-CA` or `fr_CA`
+fn language(locale: &str) -> &str {
+    locale.split(['-', '_']).next().unwrap_or(locale)
+}
+
+/// The message for `key` and the catalog it came from
+pub fn lookup<'a>(key: &str, locale: impl Into<LocaleChain<'a>>) -> Option<(&'static str, &'static str)> {
+    let catalogs = catalogs();
+    locale.into().candidates().into_iter().find_map(|candidate| {
+        let (&locale, catalog) = catalogs
+            .iter()
+            .find(|(locale, _)| locale.eq_ignore_ascii_case(candidate))?;
+        catalog.get(key).map(|message| (locale, message.as_str()))
+    })
+}
+
+/// Translate `key`, replacing `{name}` with each argument. Placeholders
+/// without an argument are left as they are, so `{{ticket.number}}` in a
+/// template string passes through untouched.
+pub fn t<'a>(key: &str, locale: impl Into<LocaleChain<'a>>, args: &[(&str, &str)]) -> String {
+    let Some((_, message)) = lookup(key, locale) else {
+        return key.to_string();
+    };
+
+    args.iter().fold(message.to_string(), |message, (name, value)| {
+        message.replace(&format!("{{{}}}", name), value)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalog_lookup() {
+        assert_eq!(t("nav.tickets", "en", &[]), "Tickets");
+        assert_eq!(t("nav.tickets", "de", &[]), "Tickets");
+        assert_eq!(t("nav.settings", "fr", &[]), "Paramètres");
+        assert_eq!(t("nav.settings", "es", &[]), "Configuración");
+
+        // Regional locales use their language's catalog
+        assert_eq!(t("nav.settings", "fr-CA", &[]), "Paramètres");
+        assert_eq!(lookup("nav.settings", "ES_mx").map(|(locale, _)| locale), Some("es"));
+
+        // Every catalog is valid and English has every key any other has
+        let english = &catalogs()[DEFAULT_LOCALE];
+        for locale in supported_locales() {
+            for key in catalogs()[locale].keys() {
+                assert!(english.contains_key(key), "{} is in {} but not en", key, locale);
+            }
+        }
+    }
+
+    #[test]
+    fn test_argument_interpolation() {
+        let args = [("from", "11"), ("to", "20"), ("total", "42")];
+        assert_eq!(t("table.showing", "en", &args), "Showing 11 to 20 of 42 results");
+        assert_eq!(t("table.showing", "de", &args), "11 bis 20 von 42 Ergebnissen");
+
+        // Template placeholders aren't arguments
+        let subject = t("notifications.ticket.created.subject", "en", &[("ticket", "ignored")]);
+        assert_eq!(subject, "New Ticket #{{ticket.number}}: {{ticket.title}}");
+
+        // Missing arguments leave the placeholder
+        assert_eq!(t("table.showing", "en", &[("total", "42")]), "Showing {from} to {to} of 42 results");
+    }
+
+    #[test]
+    fn test_fallback_when_key_missing() {
+        // The German catalog has no email templates: the tenant's default
+        // comes first, then English
+        let chain = LocaleChain::new("de-AT").with_tenant_default("fr");
+        assert_eq!(chain.candidates(), vec!["de-AT", "de", "fr", "en"]);
+        assert_eq!(lookup("notifications.invoice.sent.subject", "de"), lookup("notifications.invoice.sent.subject", "en"));
+        assert_eq!(
+            lookup("notifications.invoice.sent.subject", &chain).map(|(locale, _)| locale),
+            Some("fr")
+        );
+        assert_eq!(t("app.name", &chain, &[]), "PSA Platform");
+
+        // Unknown locales and keys
+        assert_eq!(t("nav.tickets", "xx", &[]), "Tickets");
+        assert_eq!(t("nav.no_such_label", "fr", &[]), "nav.no_such_label");
+        assert_eq!(LocaleChain::new("en").with_tenant_default(" ").candidates(), vec!["en"]);
+    }
+}
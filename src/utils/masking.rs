@@ -0,0 +1,165 @@
+//! Deterministic masking of personal data for non-production copies
+//!
+//! Each value is replaced by one derived from a keyed HMAC of the original,
+//! so the same email, phone number or name masks the same way everywhere it
+//! appears and records that shared it still match after loading.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// Kind of personal data held in a column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaskKind {
+    Email,
+    Phone,
+    Name,
+}
+
+/// Masks personal data with a secret key. Output is stable for a key, so
+/// repeated exports line up with each other, and can't be reversed by
+/// hashing guesses without it.
+#[derive(Clone)]
+pub struct DataMasker {
+    key: Vec<u8>,
+}
+
+impl DataMasker {
+    pub fn new(key: &[u8]) -> Self {
+        Self { key: key.to_vec() }
+    }
+
+    /// Hex HMAC-SHA256 of a value, separated by kind so an email and a name
+    /// with the same text don't share a digest
+    fn digest(&self, kind: &str, value: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC accepts keys of any length");
+        mac.update(kind.as_bytes());
+        mac.update(b":");
+        mac.update(value.as_bytes());
+        mac.finalize()
+            .into_bytes()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    /// Mask the mailbox and domain separately, so addresses at one domain
+    /// still share a domain. The `.invalid` TLD keeps mail from going anywhere.
+    pub fn mask_email(&self, email: &str) -> String {
+        let email = email.trim().to_lowercase();
+        match email.rsplit_once('@') {
+            Some((mailbox, domain)) => format!(
+                "{}@{}.invalid",
+                &self.digest("mailbox", mailbox)[..12],
+                &self.digest("domain", domain)[..8]
+            ),
+            None => format!("{}@masked.invalid", &self.digest("mailbox", &email)[..12]),
+        }
+    }
+
+    /// Replace every digit, keeping the number's length and punctuation.
+    /// Numbers with the same digits mask to the same digits however they are
+    /// formatted.
+    pub fn mask_phone(&self, phone: &str) -> String {
+        let digits: String = phone.chars().filter(|c| c.is_ascii_digit()).collect();
+        let mut masked = self
+            .digest("phone", &digits)
+            .into_bytes()
+            .into_iter()
+            .cycle()
+            .map(|byte| char::from(b'0' + byte % 10));
+
+        phone
+            .chars()
+            .map(|c| if c.is_ascii_digit() { masked.next().unwrap_or('0') } else { c })
+            .collect()
+    }
+
+    pub fn mask_name(&self, name: &str) -> String {
+        format!("Name-{}", &self.digest("name", &name.trim().to_lowercase())[..8])
+    }
+
+    pub fn mask(&self, kind: MaskKind, value: &str) -> String {
+        match kind {
+            MaskKind::Email => self.mask_email(value),
+            MaskKind::Phone => self.mask_phone(value),
+            MaskKind::Name => self.mask_name(value),
+        }
+    }
+
+    /// Mask the given columns of a row in place. Strings and arrays of
+    /// strings are masked; nulls and missing columns are left alone.
+    pub fn mask_row(&self, columns: &[(&str, MaskKind)], row: &mut serde_json::Value) {
+        for (column, kind) in columns {
+            match row.get_mut(*column) {
+                Some(serde_json::Value::String(value)) => *value = self.mask(*kind, value),
+                Some(serde_json::Value::Array(values)) => {
+                    for value in values.iter_mut() {
+                        if let serde_json::Value::String(value) = value {
+                            *value = self.mask(*kind, value);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_masking_is_deterministic() {
+        let masker = DataMasker::new(b"staging-key");
+        let email = masker.mask_email("Jane.Doe@acme.com");
+
+        assert_eq!(email, masker.mask_email("Jane.Doe@acme.com"));
+        assert_eq!(email, DataMasker::new(b"staging-key").mask_email(" jane.doe@ACME.com"));
+        assert!(email.ends_with(".invalid") && !email.contains("jane"));
+        assert_ne!(email, DataMasker::new(b"other-key").mask_email("Jane.Doe@acme.com"));
+        assert_ne!(email, masker.mask_email("john.doe@acme.com"));
+
+        let phone = masker.mask_phone("(555) 123-4567");
+        assert_eq!(phone, masker.mask_phone("(555) 123-4567"));
+        assert_eq!(phone.len(), "(555) 123-4567".len());
+        assert_eq!(&phone[..1], "(");
+        assert_ne!(phone, "(555) 123-4567");
+
+        assert_eq!(masker.mask_name("Jane"), masker.mask_name("Jane"));
+        assert_ne!(masker.mask_name("Jane"), masker.mask_name("John"));
+    }
+
+    #[test]
+    fn test_masking_keeps_references_consistent() {
+        let masker = DataMasker::new(b"staging-key");
+        let contact_columns = [("email", MaskKind::Email), ("last_name", MaskKind::Name), ("phone", MaskKind::Phone)];
+        let mut contact = serde_json::json!({
+            "id": "7c1e",
+            "email": "jane@acme.com",
+            "last_name": "Doe",
+            "phone": "555-123-4567",
+            "mobile": null,
+        });
+        let mut note = serde_json::json!({
+            "contact_id": "7c1e",
+            "email_recipients": ["jane@acme.com", "ops@acme.com"],
+        });
+
+        masker.mask_row(&contact_columns, &mut contact);
+        masker.mask_row(&[("email_recipients", MaskKind::Email)], &mut note);
+
+        // Ids pass through and the same address masks the same in both tables
+        assert_eq!(note["contact_id"], contact["id"]);
+        assert_eq!(note["email_recipients"][0], contact["email"]);
+        assert_eq!(contact["mobile"], serde_json::Value::Null);
+
+        // Addresses at one domain still share one
+        let domain = |value: &serde_json::Value| value.as_str().unwrap().split('@').nth(1).unwrap().to_string();
+        assert_eq!(domain(&note["email_recipients"][0]), domain(&note["email_recipients"][1]));
+
+        // A number typed differently elsewhere masks to the same digits
+        let digits = |value: &str| value.chars().filter(|c| c.is_ascii_digit()).collect::<String>();
+        assert_eq!(digits(contact["phone"].as_str().unwrap()), digits(&masker.mask_phone("(555) 123 4567")));
+    }
+}
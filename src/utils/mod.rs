@@ -2,7 +2,19 @@
 
 pub mod crypto;
 pub mod error;
+pub mod i18n;
+pub mod masking;
 pub mod pagination;
+pub mod pdf;
+#[cfg(feature = "server")]
+pub mod public_page;
+pub mod reconcile;
+#[cfg(feature = "server")]
+pub mod request_id;
+#[cfg(feature = "server")]
+pub mod storage;
+pub mod tenant_settings;
+pub mod timezone;
 pub mod validation;
 
 // Re-exports
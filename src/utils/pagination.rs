@@ -16,6 +16,9 @@ pub struct PaginationParams {
     /// Sort direction (asc/desc)
     #[serde(default = "default_sort_dir")]
     pub sort_dir: String,
+    /// Whether the total may be estimated
+    #[serde(default)]
+    pub count: CountMode,
 }
 
 fn default_page() -> u32 {
@@ -51,21 +54,128 @@ impl PaginationParams {
 
     /// Check if sort direction is ascending
     pub fn is_ascending(&self) -> bool {
-        self.sort_dir.to_lowercase() == "asc"
+        self.sort_direction() == SortDirection::Asc
     }
 
-    /// Get SQL ORDER BY clause
-    pub fn order_by(&self, default_field: &str, allowed_fields: &[&str]) -> String {
+    /// Requested sort direction; anything other than `asc`/`desc` falls back to descending
+    pub fn sort_direction(&self) -> SortDirection {
+        SortDirection::from_param(&self.sort_dir).unwrap_or_default()
+    }
+
+    /// Last page for a result set, never less than 1
+    pub fn last_page(&self, total: u64) -> u32 {
+        (total.div_ceil(self.per_page() as u64) as u32).max(1)
+    }
+
+    /// Copy of these params with the page clamped into `1..=last_page`
+    pub fn clamped(&self, total: u64) -> Self {
+        Self {
+            page: self.page.clamp(1, self.last_page(total)),
+            ..self.clone()
+        }
+    }
+
+    /// Like [`Self::clamped`], except that an estimated total leaves the page
+    /// alone: the estimate can fall short of the real row count, so pages past
+    /// it may still hold rows.
+    pub fn clamped_to(&self, total: ListTotal) -> Self {
+        if total.is_estimate {
+            self.clone()
+        } else {
+            self.clamped(total.count)
+        }
+    }
+
+    /// Get SQL ORDER BY clause.
+    ///
+    /// The column is always taken from `allowed_fields` (or `default_field`), never
+    /// from the request, so user input cannot reach the SQL string. Both must be
+    /// string literals in the calling code. An unknown `sort` falls back to the
+    /// default column.
+    pub fn order_by(&self, default_field: &'static str, allowed_fields: &[&'static str]) -> String {
         let field = self
             .sort
-            .as_ref()
-            .filter(|f| allowed_fields.contains(&f.as_str()))
-            .map(|s| s.as_str())
+            .as_deref()
+            .and_then(|sort| allowed_fields.iter().copied().find(|f| *f == sort))
             .unwrap_or(default_field);
 
-        let direction = if self.is_ascending() { "ASC" } else { "DESC" };
+        format!("{} {}", field, self.sort_direction().as_sql())
+    }
+}
+
+/// Unfiltered lists the planner expects to hold at least this many rows
+/// report its estimate as the total instead of running `COUNT(*)`
+pub const ESTIMATED_COUNT_THRESHOLD: u64 = 50_000;
+
+/// How a list's total is counted (`?count=exact` to always count)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CountMode {
+    /// Estimate large unfiltered lists, count everything else
+    #[default]
+    Auto,
+    Exact,
+}
 
-        format!("{} {}", field, direction)
+impl CountMode {
+    /// Whether to report `estimated_rows` rather than count. Filtered lists
+    /// are always counted: the planner's guess for them is too rough.
+    pub fn use_estimate(self, is_filtered: bool, estimated_rows: u64) -> bool {
+        self == Self::Auto && !is_filtered && estimated_rows >= ESTIMATED_COUNT_THRESHOLD
+    }
+}
+
+/// Rows the planner expects, from an `EXPLAIN (FORMAT JSON)` result
+pub fn planner_row_estimate(plan: &serde_json::Value) -> Option<u64> {
+    plan.get(0)?
+        .get("Plan")?
+        .get("Plan Rows")?
+        .as_f64()
+        .map(|rows| rows.max(0.0) as u64)
+}
+
+/// A list's total, counted or estimated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ListTotal {
+    pub count: u64,
+    pub is_estimate: bool,
+}
+
+impl ListTotal {
+    pub fn exact(count: u64) -> Self {
+        Self { count, is_estimate: false }
+    }
+
+    pub fn estimated(count: u64) -> Self {
+        Self { count, is_estimate: true }
+    }
+}
+
+/// Sort direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortDirection {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortDirection {
+    /// Parse `asc`/`desc` in any case; anything else is rejected
+    pub fn from_param(s: &str) -> Option<Self> {
+        if s.eq_ignore_ascii_case("asc") {
+            Some(Self::Asc)
+        } else if s.eq_ignore_ascii_case("desc") {
+            Some(Self::Desc)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            Self::Asc => "ASC",
+            Self::Desc => "DESC",
+        }
     }
 }
 
@@ -93,12 +203,15 @@ pub struct PaginationMeta {
     pub has_next: bool,
     /// Whether there is a previous page
     pub has_prev: bool,
+    /// Whether `total` (and so `total_pages`) is an estimate
+    #[serde(default)]
+    pub is_estimate: bool,
 }
 
 impl<T> PaginatedResponse<T> {
     /// Create a new paginated response
     pub fn new(data: Vec<T>, page: u32, per_page: u32, total: u64) -> Self {
-        let total_pages = ((total as f64) / (per_page as f64)).ceil() as u32;
+        let total_pages = total.div_ceil(per_page.max(1) as u64) as u32;
 
         Self {
             data,
@@ -109,15 +222,62 @@ impl<T> PaginatedResponse<T> {
                 total_pages,
                 has_next: page < total_pages,
                 has_prev: page > 1,
+                is_estimate: false,
             },
         }
     }
 
-    /// Create from pagination params
+    /// Create from pagination params, clamping an out-of-range page to the last page
     pub fn from_params(data: Vec<T>, params: &PaginationParams, total: u64) -> Self {
+        let params = params.clamped(total);
         Self::new(data, params.page, params.per_page(), total)
     }
 
+    /// Like [`Self::from_params`], flagging an estimated total. A page past
+    /// an estimate is reported as requested, with a next page if it came
+    /// back full.
+    pub fn from_total(data: Vec<T>, params: &PaginationParams, total: ListTotal) -> Self {
+        let params = params.clamped_to(total);
+        let full = data.len() as u32 >= params.per_page();
+        let mut response = Self::new(data, params.page, params.per_page(), total.count);
+        response.meta.is_estimate = total.is_estimate;
+        response.meta.has_next |= total.is_estimate && full;
+        response
+    }
+
+    /// RFC 8288 `Link` header value with first/prev/next/last relations.
+    ///
+    /// `path` and `query` come from the request URI; any existing `page` parameter
+    /// is replaced and the rest of the query string is kept.
+    pub fn link_header(&self, path: &str, query: Option<&str>) -> Option<String> {
+        if self.meta.total_pages == 0 {
+            return None;
+        }
+
+        let kept: Vec<&str> = query
+            .unwrap_or_default()
+            .split('&')
+            .filter(|pair| !pair.is_empty() && *pair != "page" && !pair.starts_with("page="))
+            .collect();
+        let link = |page: u32, rel: &str| {
+            let mut params = kept.clone();
+            let page_param = format!("page={}", page);
+            params.push(&page_param);
+            format!("<{}?{}>; rel=\"{}\"", path, params.join("&"), rel)
+        };
+
+        let mut links = vec![link(1, "first")];
+        if self.meta.has_prev {
+            links.push(link(self.meta.page - 1, "prev"));
+        }
+        if self.meta.has_next {
+            links.push(link(self.meta.page + 1, "next"));
+        }
+        links.push(link(self.meta.total_pages, "last"));
+
+        Some(links.join(", "))
+    }
+
     /// Map the data items to a new type
     pub fn map<U, F>(self, f: F) -> PaginatedResponse<U>
     where
@@ -130,6 +290,85 @@ impl<T> PaginatedResponse<T> {
     }
 }
 
+#[cfg(feature = "server")]
+mod server {
+    use super::PaginatedResponse;
+    use axum::{
+        http::{header, HeaderValue, Uri},
+        response::{IntoResponse, Response},
+        Json,
+    };
+    use serde::Serialize;
+
+    /// JSON page response with a `Link` header for navigation
+    pub struct PaginatedJson<T> {
+        pub body: PaginatedResponse<T>,
+        pub link: Option<String>,
+    }
+
+    impl<T> PaginatedResponse<T> {
+        /// Attach `Link` headers built from the request URI
+        pub fn with_links(self, uri: &Uri) -> PaginatedJson<T> {
+            let link = self.link_header(uri.path(), uri.query());
+            PaginatedJson { body: self, link }
+        }
+    }
+
+    impl<T: Serialize> IntoResponse for PaginatedJson<T> {
+        fn into_response(self) -> Response {
+            let link = self.link.and_then(|l| HeaderValue::from_str(&l).ok());
+            let mut response = Json(self.body).into_response();
+            if let Some(link) = link {
+                response.headers_mut().insert(header::LINK, link);
+            }
+            response
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+pub use server::PaginatedJson;
+
+/// How much of each entity a list endpoint returns (`?view=summary`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseView {
+    /// The complete entity
+    #[default]
+    Full,
+    /// A compact projection for clients on slow links
+    Summary,
+}
+
+impl ResponseView {
+    /// Convert a list entity into the chosen view's response type
+    pub fn item<T, F, S>(self, value: T) -> ViewItem<F, S>
+    where
+        F: From<T>,
+        S: From<T>,
+    {
+        match self {
+            Self::Full => ViewItem::Full(F::from(value)),
+            Self::Summary => ViewItem::Summary(S::from(value)),
+        }
+    }
+}
+
+/// View parameter that can be combined with pagination
+#[derive(Debug, Clone, Copy, Deserialize, Default)]
+pub struct ViewParams {
+    #[serde(default)]
+    pub view: ResponseView,
+}
+
+/// A list item in one of two shapes, serialized as just that shape
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum ViewItem<F, S> {
+    Full(F),
+    Summary(S),
+}
+
 /// Filter parameters that can be combined with pagination
 #[derive(Debug, Clone, Deserialize, Default)]
 pub struct FilterParams {
@@ -256,6 +495,68 @@ mod tests {
         assert_eq!(invalid_sort.order_by("created_at", allowed), "created_at DESC");
     }
 
+    #[test]
+    fn test_order_by_rejects_hostile_input() {
+        let allowed = &["name", "created_at"];
+        let hostile_fields = [
+            "created_at; DROP TABLE tickets",
+            "created_at DESC, (SELECT pg_sleep(10))",
+            "name--",
+            "name ",
+            " name",
+            "NAME",
+            "name\0",
+            "1",
+            "",
+            "\"name\"",
+            "created_at/**/ASC",
+        ];
+        let hostile_dirs = [
+            "asc; DROP TABLE tickets",
+            "ASC NULLS FIRST",
+            "desc,name",
+            " asc",
+            "ascending",
+            "",
+            "\u{0430}sc",
+        ];
+
+        for sort in hostile_fields {
+            for dir in hostile_dirs.iter().chain(["asc", "DESC"].iter()) {
+                let params = PaginationParams {
+                    sort: Some(sort.to_string()),
+                    sort_dir: dir.to_string(),
+                    ..Default::default()
+                };
+                let clause = params.order_by("created_at", allowed);
+                assert!(
+                    clause == "created_at ASC" || clause == "created_at DESC",
+                    "{:?}/{:?} produced {:?}",
+                    sort,
+                    dir,
+                    clause
+                );
+            }
+        }
+
+        for dir in hostile_dirs {
+            let params = PaginationParams {
+                sort: Some("name".to_string()),
+                sort_dir: dir.to_string(),
+                ..Default::default()
+            };
+            assert_eq!(params.order_by("created_at", allowed), "name DESC");
+        }
+    }
+
+    #[test]
+    fn test_sort_direction_from_param() {
+        assert_eq!(SortDirection::from_param("asc"), Some(SortDirection::Asc));
+        assert_eq!(SortDirection::from_param("Desc"), Some(SortDirection::Desc));
+        assert_eq!(SortDirection::from_param("asc "), None);
+        assert_eq!(SortDirection::from_param("up"), None);
+    }
+
     #[test]
     fn test_paginated_response() {
         let data = vec![1, 2, 3, 4, 5];
@@ -281,6 +582,134 @@ mod tests {
         assert!(response.meta.has_prev);
     }
 
+    #[test]
+    fn test_paginated_response_middle_page() {
+        let response = PaginatedResponse::new(vec![1, 2, 3, 4, 5], 2, 5, 17);
+
+        assert_eq!(response.meta.total_pages, 4);
+        assert!(response.meta.has_next);
+        assert!(response.meta.has_prev);
+    }
+
+    #[test]
+    fn test_out_of_range_page_clamps_to_last() {
+        let params = PaginationParams {
+            page: 99,
+            per_page: 5,
+            ..Default::default()
+        };
+        assert_eq!(params.clamped(17).page, 4);
+        assert_eq!(params.clamped(17).offset(), 15);
+
+        let response = PaginatedResponse::from_params(vec![1, 2], &params, 17);
+        assert_eq!(response.meta.page, 4);
+        assert!(!response.meta.has_next);
+        assert!(response.meta.has_prev);
+
+        // An empty result set still reports page 1
+        let empty = PaginatedResponse::<i32>::from_params(vec![], &params, 0);
+        assert_eq!(empty.meta.page, 1);
+        assert_eq!(empty.meta.total_pages, 0);
+        assert!(!empty.meta.has_next);
+        assert!(!empty.meta.has_prev);
+
+        let page_zero = PaginationParams {
+            page: 0,
+            per_page: 5,
+            ..Default::default()
+        };
+        assert_eq!(page_zero.clamped(17).page, 1);
+    }
+
+    #[test]
+    fn test_clamped_offset_matches_reported_page() {
+        for total in [0, 1, 4, 5, 6, 17, 20] {
+            for page in [0, 1, 2, 4, 5, 99] {
+                let params = PaginationParams {
+                    page,
+                    per_page: 5,
+                    ..Default::default()
+                };
+                let response = PaginatedResponse::<i32>::from_params(vec![], &params, total);
+                assert_eq!(params.clamped(total).offset(), (response.meta.page - 1) * 5);
+                assert!((params.clamped(total).offset() as u64) < total.max(1));
+            }
+        }
+    }
+
+    #[test]
+    fn test_link_header() {
+        let first = PaginatedResponse::new(vec![1], 1, 5, 17);
+        assert_eq!(
+            first.link_header("/api/v1/tickets", Some("per_page=5&page=1")).unwrap(),
+            "</api/v1/tickets?per_page=5&page=1>; rel=\"first\", \
+             </api/v1/tickets?per_page=5&page=2>; rel=\"next\", \
+             </api/v1/tickets?per_page=5&page=4>; rel=\"last\""
+        );
+
+        let middle = PaginatedResponse::new(vec![1], 2, 5, 17);
+        let links = middle.link_header("/api/v1/tickets", None).unwrap();
+        assert!(links.contains("</api/v1/tickets?page=1>; rel=\"prev\""));
+        assert!(links.contains("</api/v1/tickets?page=3>; rel=\"next\""));
+
+        let last = PaginatedResponse::new(vec![1], 4, 5, 17);
+        let links = last.link_header("/api/v1/tickets", Some("q=disk")).unwrap();
+        assert!(links.contains("</api/v1/tickets?q=disk&page=3>; rel=\"prev\""));
+        assert!(!links.contains("rel=\"next\""));
+
+        let empty = PaginatedResponse::<i32>::new(vec![], 1, 5, 0);
+        assert!(empty.link_header("/api/v1/tickets", None).is_none());
+    }
+
+    #[test]
+    fn test_large_unfiltered_lists_use_estimated_counts() {
+        let above = ESTIMATED_COUNT_THRESHOLD;
+        let below = ESTIMATED_COUNT_THRESHOLD - 1;
+
+        assert!(CountMode::Auto.use_estimate(false, above));
+        assert!(!CountMode::Auto.use_estimate(false, below));
+        // Filters and an explicit request both force an exact count
+        assert!(!CountMode::Auto.use_estimate(true, above * 10));
+        assert!(!CountMode::Exact.use_estimate(false, above * 10));
+
+        let plan = serde_json::json!([{ "Plan": { "Node Type": "Seq Scan", "Plan Rows": 1234567.0 } }]);
+        assert_eq!(planner_row_estimate(&plan), Some(1_234_567));
+        assert_eq!(planner_row_estimate(&serde_json::json!([])), None);
+
+        let params: PaginationParams = serde_json::from_value(serde_json::json!({ "count": "exact" })).unwrap();
+        assert_eq!(params.count, CountMode::Exact);
+
+        let estimated = PaginatedResponse::from_total(vec![1, 2], &params, ListTotal::estimated(1_234_567));
+        assert!(estimated.meta.is_estimate);
+        assert_eq!(estimated.meta.total, 1_234_567);
+        assert!(!PaginatedResponse::from_total(vec![1], &params, ListTotal::exact(1)).meta.is_estimate);
+    }
+
+    #[test]
+    fn test_page_past_an_estimate_is_served() {
+        let params = PaginationParams {
+            page: 6,
+            per_page: 5,
+            ..Default::default()
+        };
+
+        // The table holds more rows than the planner estimated
+        let estimated = ListTotal::estimated(17);
+        assert_eq!(params.clamped_to(estimated).page, 6);
+        assert_eq!(params.clamped_to(estimated).offset(), 25);
+
+        let response = PaginatedResponse::from_total(vec![1, 2, 3, 4, 5], &params, estimated);
+        assert_eq!(response.meta.page, 6);
+        assert!(response.meta.has_next);
+        assert!(response.meta.has_prev);
+        let short = PaginatedResponse::from_total(vec![1, 2], &params, estimated);
+        assert!(!short.meta.has_next);
+
+        // An exact total still clamps
+        assert_eq!(params.clamped_to(ListTotal::exact(17)).page, 4);
+        assert_eq!(PaginatedResponse::from_total(vec![1, 2], &params, ListTotal::exact(17)).meta.page, 4);
+    }
+
     #[test]
     fn test_paginated_response_map() {
         let data = vec![1, 2, 3];
@@ -0,0 +1,196 @@
+//! Minimal PDF writer for plain-text documents
+//!
+//! Lays lines of text out on A4 pages in Helvetica, breaking pages as they
+//! fill. Enough for emailing an invoice or quote as a file; the styled
+//! layout is left to the web renderer.
+
+/// A4 in points
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 56.0;
+
+/// A line of text and how large to set it
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfLine {
+    pub text: String,
+    pub size: f32,
+    pub bold: bool,
+}
+
+impl PdfLine {
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            size: 10.0,
+            bold: false,
+        }
+    }
+
+    pub fn heading(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            size: 16.0,
+            bold: true,
+        }
+    }
+
+    pub fn blank() -> Self {
+        Self::text("")
+    }
+
+    fn leading(&self) -> f32 {
+        self.size * 1.4
+    }
+}
+
+/// Render lines into a PDF file
+pub fn render_text_pdf(title: &str, lines: &[PdfLine]) -> Vec<u8> {
+    let pages = paginate(lines);
+
+    // Objects: 1 catalog, 2 page tree, 3 regular font, 4 bold font, 5 info,
+    // then a page and its content stream for each page
+    let mut objects: Vec<Vec<u8>> = Vec::new();
+    let page_ids: Vec<usize> = (0..pages.len()).map(|i| 6 + i * 2).collect();
+
+    objects.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+    objects.push(
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            page_ids.iter().map(|id| format!("{} 0 R", id)).collect::<Vec<_>>().join(" "),
+            pages.len()
+        )
+        .into_bytes(),
+    );
+    objects.push(b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_vec());
+    objects.push(b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>".to_vec());
+    let mut info = b"<< /Title ".to_vec();
+    info.extend(pdf_string(title));
+    info.extend(b" /Producer (PSA) >>");
+    objects.push(info);
+
+    for (i, page) in pages.iter().enumerate() {
+        let content_id = page_ids[i] + 1;
+        objects.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                 /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                PAGE_WIDTH, PAGE_HEIGHT, content_id
+            )
+            .into_bytes(),
+        );
+
+        let stream = page_stream(page);
+        let mut content = format!("<< /Length {} >>\nstream\n", stream.len()).into_bytes();
+        content.extend(stream);
+        content.extend(b"\nendstream");
+        objects.push(content);
+    }
+
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend(format!("{} 0 obj\n", i + 1).into_bytes());
+        pdf.extend(object);
+        pdf.extend(b"\nendobj\n");
+    }
+
+    let xref = pdf.len();
+    pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).into_bytes());
+    for offset in offsets {
+        pdf.extend(format!("{:010} 00000 n \n", offset).into_bytes());
+    }
+    pdf.extend(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R /Info 5 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        )
+        .into_bytes(),
+    );
+    pdf
+}
+
+/// Split lines into pages that fit between the margins
+fn paginate(lines: &[PdfLine]) -> Vec<Vec<&PdfLine>> {
+    let usable = PAGE_HEIGHT - 2.0 * MARGIN;
+    let mut pages: Vec<Vec<&PdfLine>> = vec![Vec::new()];
+    let mut used = 0.0;
+
+    for line in lines {
+        if used + line.leading() > usable && !pages.last().is_some_and(Vec::is_empty) {
+            pages.push(Vec::new());
+            used = 0.0;
+        }
+        used += line.leading();
+        if let Some(page) = pages.last_mut() {
+            page.push(line);
+        }
+    }
+    pages
+}
+
+fn page_stream(lines: &[&PdfLine]) -> Vec<u8> {
+    let mut stream = Vec::new();
+    let mut y = PAGE_HEIGHT - MARGIN;
+
+    for line in lines {
+        y -= line.leading();
+        if line.text.is_empty() {
+            continue;
+        }
+        let font = if line.bold { "F2" } else { "F1" };
+        stream.extend(format!("BT /{} {} Tf {} {} Td ", font, line.size, MARGIN, y).into_bytes());
+        stream.extend(pdf_string(&line.text));
+        stream.extend(b" Tj ET\n");
+    }
+    stream
+}
+
+/// A PDF literal string in WinAnsi; characters outside it print as `?`
+fn pdf_string(text: &str) -> Vec<u8> {
+    let mut out = vec![b'('];
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                out.push(b'\\');
+                out.push(c as u8);
+            }
+            '\u{20}'..='\u{7e}' => out.push(c as u8),
+            '\u{a0}'..='\u{ff}' => out.push(c as u32 as u8),
+            '€' => out.push(0x80),
+            '\t' => out.push(b' '),
+            _ => out.push(b'?'),
+        }
+    }
+    out.push(b')');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_renders_pdf_with_escaped_text_and_page_breaks() {
+        let mut lines = vec![PdfLine::heading("Invoice INV-0042"), PdfLine::text("Support (March) 100%\\")];
+        lines.extend((0..80).map(|i| PdfLine::text(format!("Line {}", i))));
+
+        let pdf = render_text_pdf("INV-0042", &lines);
+        let text = String::from_utf8_lossy(&pdf);
+
+        assert!(text.starts_with("%PDF-1.4"));
+        assert!(text.trim_end().ends_with("%%EOF"));
+        assert!(text.contains(r"(Support \(March\) 100%\\) Tj"));
+        // 82 lines don't fit on one page
+        assert!(text.contains("/Count 2"));
+
+        // The cross-reference table points at each object
+        let xref_at: usize = text.rsplit("startxref\n").next().unwrap().lines().next().unwrap().parse().unwrap();
+        assert!(text[xref_at..].starts_with("xref"));
+        for (i, entry) in text[xref_at..].lines().skip(3).take(4).enumerate() {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(text[offset..].starts_with(&format!("{} 0 obj", i + 1)));
+        }
+    }
+}
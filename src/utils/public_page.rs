@@ -0,0 +1,120 @@
+//! Plain HTML pages for token links opened from emails
+//!
+//! Survey, booking and unsubscribe links open in a browser without a login.
+//! Their GET only shows a page; the change itself is a form POST, so mail
+//! scanners that prefetch every link don't act for the recipient.
+
+use axum::extract::{FromRequest, Request};
+use axum::http::{header, HeaderMap};
+use axum::response::Html;
+use axum::{Form, Json};
+use serde::de::DeserializeOwned;
+
+use crate::utils::error::AppError;
+
+/// Escape text for use in HTML content or a quoted attribute
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A standalone page. `title` is escaped; `body` is HTML and must already be.
+pub fn page(title: &str, body: &str) -> Html<String> {
+    Html(format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<meta name="robots" content="noindex">
+<title>{title}</title>
+<style>body{{font-family:system-ui,sans-serif;max-width:32rem;margin:4rem auto;padding:0 1rem;color:#111827}}
+button{{padding:.5rem 1rem;font-size:1rem}}textarea{{width:100%;min-height:5rem}}</style>
+</head>
+<body>
+<h1>{title}</h1>
+{body}
+</body>
+</html>"#,
+        title = escape(title),
+        body = body,
+    ))
+}
+
+/// Whether a request was posted by an HTML form
+pub fn is_form(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/x-www-form-urlencoded"))
+}
+
+/// A body posted by a page's form, or as JSON by an API client, so one
+/// route serves both and can answer each in kind
+#[derive(Debug, Clone)]
+pub enum FormOrJson<T> {
+    Form(T),
+    Json(T),
+}
+
+impl<T> FormOrJson<T> {
+    pub fn is_form(&self) -> bool {
+        matches!(self, Self::Form(_))
+    }
+
+    pub fn into_inner(self) -> T {
+        match self {
+            Self::Form(value) | Self::Json(value) => value,
+        }
+    }
+}
+
+impl<T, S> FromRequest<S> for FormOrJson<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if is_form(request.headers()) {
+            let Form(value) = Form::<T>::from_request(request, state)
+                .await
+                .map_err(|e| AppError::BadRequest(e.body_text()))?;
+            Ok(Self::Form(value))
+        } else {
+            let Json(value) = Json::<T>::from_request(request, state)
+                .await
+                .map_err(|e| AppError::BadRequest(e.body_text()))?;
+            Ok(Self::Json(value))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape(r#"<a href="x">Tom & Jerry's</a>"#), "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;");
+        assert_eq!(escape("plain"), "plain");
+    }
+
+    #[test]
+    fn test_page_escapes_title_only() {
+        let Html(html) = page("Unsubscribe <jane@acme.com>", "<p>Done</p>");
+        assert!(html.contains("<title>Unsubscribe &lt;jane@acme.com&gt;</title>"));
+        assert!(html.contains("<p>Done</p>"));
+    }
+}
@@ -0,0 +1,224 @@
+//! Conflict resolution for two-way integration sync
+//!
+//! An integration that both pushes PSA records out and pulls remote edits
+//! back will eventually see the same record changed on both sides between
+//! passes. `reconcile` decides which copy survives; callers apply the
+//! decision and record it so flagged conflicts can be reviewed.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// How to settle a record changed on both sides since the last sync
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// The copy modified most recently wins; ties go to the PSA
+    LastWriteWins,
+    /// The PSA copy always wins
+    #[default]
+    LocalWins,
+    /// The remote copy always wins
+    RemoteWins,
+    /// Neither side is touched until someone resolves it
+    FlagForReview,
+}
+
+impl ConflictPolicy {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "last_write_wins" => Some(Self::LastWriteWins),
+            "local_wins" => Some(Self::LocalWins),
+            "remote_wins" => Some(Self::RemoteWins),
+            "flag_for_review" => Some(Self::FlagForReview),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::LastWriteWins => "last_write_wins",
+            Self::LocalWins => "local_wins",
+            Self::RemoteWins => "remote_wins",
+            Self::FlagForReview => "flag_for_review",
+        }
+    }
+}
+
+/// One side's copy of a record and when it last changed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Versioned<T> {
+    pub value: T,
+    pub modified_at: DateTime<Utc>,
+}
+
+impl<T> Versioned<T> {
+    pub fn new(value: T, modified_at: DateTime<Utc>) -> Self {
+        Self { value, modified_at }
+    }
+}
+
+/// What the sync should do with the record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncDecision {
+    /// Both copies already agree
+    InSync,
+    /// Overwrite the remote copy with the PSA's
+    PushLocal,
+    /// Overwrite the PSA copy with the remote's
+    PullRemote,
+    /// Leave both copies alone and ask a person
+    FlagForReview,
+}
+
+impl SyncDecision {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "in_sync" => Some(Self::InSync),
+            "push_local" => Some(Self::PushLocal),
+            "pull_remote" => Some(Self::PullRemote),
+            "flag_for_review" => Some(Self::FlagForReview),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::InSync => "in_sync",
+            Self::PushLocal => "push_local",
+            Self::PullRemote => "pull_remote",
+            Self::FlagForReview => "flag_for_review",
+        }
+    }
+}
+
+/// The decision and whether the policy had to be consulted to reach it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Reconciliation {
+    pub decision: SyncDecision,
+    pub policy: ConflictPolicy,
+    /// Both sides changed since the last sync
+    pub conflict: bool,
+}
+
+/// Decide which copy of a record survives.
+///
+/// Copies that agree are in sync. When only one side changed since
+/// `last_synced_at` that side wins outright; the policy only settles records
+/// changed on both sides, or never synced before.
+pub fn reconcile<T: PartialEq>(
+    local: &Versioned<T>,
+    remote: &Versioned<T>,
+    last_synced_at: Option<DateTime<Utc>>,
+    policy: ConflictPolicy,
+) -> Reconciliation {
+    let resolved = |decision, conflict| Reconciliation {
+        decision,
+        policy,
+        conflict,
+    };
+
+    if local.value == remote.value {
+        return resolved(SyncDecision::InSync, false);
+    }
+
+    if let Some(synced_at) = last_synced_at {
+        let local_changed = local.modified_at > synced_at;
+        let remote_changed = remote.modified_at > synced_at;
+        if local_changed && !remote_changed {
+            return resolved(SyncDecision::PushLocal, false);
+        }
+        if remote_changed && !local_changed {
+            return resolved(SyncDecision::PullRemote, false);
+        }
+    }
+
+    let decision = match policy {
+        ConflictPolicy::LastWriteWins if remote.modified_at > local.modified_at => SyncDecision::PullRemote,
+        ConflictPolicy::LastWriteWins | ConflictPolicy::LocalWins => SyncDecision::PushLocal,
+        ConflictPolicy::RemoteWins => SyncDecision::PullRemote,
+        ConflictPolicy::FlagForReview => SyncDecision::FlagForReview,
+    };
+    resolved(decision, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 6, 3, hour, 0, 0).unwrap()
+    }
+
+    /// Synced at 09:00, then edited locally at 11:00 and remotely at 10:00
+    fn divergent() -> (Versioned<&'static str>, Versioned<&'static str>, Option<DateTime<Utc>>) {
+        (Versioned::new("local", at(11)), Versioned::new("remote", at(10)), Some(at(9)))
+    }
+
+    #[test]
+    fn test_last_write_wins_takes_the_newer_copy() {
+        let (local, remote, synced_at) = divergent();
+        let result = reconcile(&local, &remote, synced_at, ConflictPolicy::LastWriteWins);
+        assert_eq!(result.decision, SyncDecision::PushLocal);
+        assert!(result.conflict);
+
+        let remote = Versioned::new("remote", at(12));
+        let result = reconcile(&local, &remote, synced_at, ConflictPolicy::LastWriteWins);
+        assert_eq!(result.decision, SyncDecision::PullRemote);
+
+        // A tie goes to the PSA
+        let remote = Versioned::new("remote", at(11));
+        let result = reconcile(&local, &remote, synced_at, ConflictPolicy::LastWriteWins);
+        assert_eq!(result.decision, SyncDecision::PushLocal);
+    }
+
+    #[test]
+    fn test_local_wins_even_when_remote_is_newer() {
+        let (local, _, synced_at) = divergent();
+        let remote = Versioned::new("remote", at(12));
+        let result = reconcile(&local, &remote, synced_at, ConflictPolicy::LocalWins);
+        assert_eq!(result.decision, SyncDecision::PushLocal);
+        assert!(result.conflict);
+    }
+
+    #[test]
+    fn test_remote_wins_even_when_local_is_newer() {
+        let (local, remote, synced_at) = divergent();
+        let result = reconcile(&local, &remote, synced_at, ConflictPolicy::RemoteWins);
+        assert_eq!(result.decision, SyncDecision::PullRemote);
+        assert!(result.conflict);
+    }
+
+    #[test]
+    fn test_flag_for_review_touches_neither_side() {
+        let (local, remote, synced_at) = divergent();
+        let result = reconcile(&local, &remote, synced_at, ConflictPolicy::FlagForReview);
+        assert_eq!(result.decision, SyncDecision::FlagForReview);
+        assert!(result.conflict);
+
+        // Never synced before counts as changed on both sides
+        let result = reconcile(&local, &remote, None, ConflictPolicy::FlagForReview);
+        assert_eq!(result.decision, SyncDecision::FlagForReview);
+    }
+
+    #[test]
+    fn test_one_sided_changes_skip_the_policy() {
+        let synced_at = Some(at(9));
+        let stale_local = Versioned::new("local", at(8));
+        let fresh_remote = Versioned::new("remote", at(10));
+        let result = reconcile(&stale_local, &fresh_remote, synced_at, ConflictPolicy::LocalWins);
+        assert_eq!(result.decision, SyncDecision::PullRemote);
+        assert!(!result.conflict);
+
+        let fresh_local = Versioned::new("local", at(10));
+        let stale_remote = Versioned::new("remote", at(8));
+        let result = reconcile(&fresh_local, &stale_remote, synced_at, ConflictPolicy::RemoteWins);
+        assert_eq!(result.decision, SyncDecision::PushLocal);
+        assert!(!result.conflict);
+
+        let same = Versioned::new("local", at(12));
+        let result = reconcile(&fresh_local, &same, synced_at, ConflictPolicy::FlagForReview);
+        assert_eq!(result.decision, SyncDecision::InSync);
+    }
+}
@@ -0,0 +1,132 @@
+//! Request correlation ids
+//!
+//! Every API request carries an `X-Request-Id`, taken from the caller when it
+//! sends a usable one and generated otherwise. The id is echoed on the
+//! response, recorded on the request's tracing span, included in error bodies
+//! and stored on audit and webhook rows written while handling the request.
+
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use uuid::Uuid;
+
+/// Header carrying the id in both directions
+pub static X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest caller-supplied id that is accepted as is
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// The id of the request being handled, if any
+pub fn current() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Use the caller's id when it is short printable ASCII, otherwise a new UUID
+pub fn resolve(incoming: Option<&HeaderValue>) -> String {
+    incoming
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN)
+        .filter(|id| id.bytes().all(|b| b.is_ascii_graphic()))
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string())
+}
+
+/// Span for a request, with its correlation id as a field. Used by the
+/// `TraceLayer`, which runs inside [`request_id_middleware`].
+pub fn request_span<B>(request: &axum::http::Request<B>) -> tracing::Span {
+    let request_id = request
+        .headers()
+        .get(&X_REQUEST_ID)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    tracing::info_span!(
+        "request",
+        request_id = %request_id,
+        method = %request.method(),
+        uri = %request.uri(),
+    )
+}
+
+/// Assign the request id and make it available for the rest of the request.
+/// Mount as the outermost layer.
+pub async fn request_id_middleware(mut request: Request, next: Next) -> Response {
+    let request_id = resolve(request.headers().get(&X_REQUEST_ID));
+    let header = HeaderValue::from_str(&request_id).ok();
+
+    if let Some(ref value) = header {
+        request.headers_mut().insert(X_REQUEST_ID.clone(), value.clone());
+    }
+
+    let mut response = REQUEST_ID.scope(request_id, next.run(request)).await;
+
+    if let Some(value) = header {
+        response.headers_mut().insert(X_REQUEST_ID.clone(), value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware, routing::get, Router};
+    use tower::ServiceExt;
+
+    use crate::utils::error::AppError;
+
+    fn app() -> Router {
+        Router::new()
+            .route("/echo", get(|| async { current().unwrap_or_default() }))
+            .route("/fail", get(|| async { Err::<(), _>(AppError::not_found("Ticket")) }))
+            .layer(middleware::from_fn(request_id_middleware))
+    }
+
+    #[test]
+    fn test_resolve_keeps_usable_ids_only() {
+        assert_eq!(resolve(Some(&HeaderValue::from_static("req-42"))), "req-42");
+
+        let generated = resolve(Some(&HeaderValue::from_static("has space")));
+        assert!(Uuid::parse_str(&generated).is_ok());
+        assert!(Uuid::parse_str(&resolve(None)).is_ok());
+        assert!(current().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_incoming_request_id_is_echoed_and_scoped() {
+        let request = axum::http::Request::builder()
+            .uri("/echo")
+            .header("X-Request-Id", "support-case-1042")
+            .body(Body::empty())
+            .unwrap();
+        let response = app().oneshot(request).await.unwrap();
+
+        assert_eq!(response.headers()["x-request-id"], "support-case-1042");
+        let body = axum::body::to_bytes(response.into_body(), 1024).await.unwrap();
+        // The handler, and so its span and any audit rows, saw the same id
+        assert_eq!(&body[..], b"support-case-1042");
+    }
+
+    #[tokio::test]
+    async fn test_error_body_includes_request_id() {
+        let request = axum::http::Request::builder()
+            .uri("/fail")
+            .header("X-Request-Id", "req-7")
+            .body(Body::empty())
+            .unwrap();
+        let response = app().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), 404);
+
+        let body = axum::body::to_bytes(response.into_body(), 4096).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["request_id"], "req-7");
+        assert_eq!(json["code"], "not_found");
+    }
+}
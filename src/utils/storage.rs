@@ -0,0 +1,140 @@
+//! File storage backends
+//!
+//! Services store uploaded files through a [`FileStorage`] under a key such
+//! as `{tenant_id}/avatars/{id}.png`, leaving where the bytes live to the
+//! backend. [`LocalStorage`] keeps them on disk.
+
+use std::future::Future;
+use std::path::PathBuf;
+
+use crate::utils::error::{AppError, AppResult};
+
+/// Where uploaded files are kept
+pub trait FileStorage: Send + Sync {
+    fn put(&self, key: &str, content: &[u8]) -> impl Future<Output = AppResult<()>> + Send;
+
+    /// The stored file, or `None` if there is none under `key`
+    fn get(&self, key: &str) -> impl Future<Output = AppResult<Option<Vec<u8>>>> + Send;
+
+    /// Remove a file; removing one that isn't there is not an error
+    fn delete(&self, key: &str) -> impl Future<Output = AppResult<()>> + Send;
+}
+
+/// Keys are relative paths made of plain segments, so one can't reach
+/// outside the storage root
+pub fn check_key(key: &str) -> AppResult<()> {
+    let valid = !key.is_empty()
+        && key.split('/').all(|segment| {
+            !segment.is_empty()
+                && segment != "."
+                && segment != ".."
+                && segment.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::BadRequest(format!("Invalid storage key '{}'", key)))
+    }
+}
+
+/// Files under a directory on the local disk
+#[derive(Debug, Clone)]
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Storage under `MEDIA_STORAGE_DIR`, `./data/media` by default
+    pub fn from_env() -> Self {
+        Self::new(std::env::var("MEDIA_STORAGE_DIR").unwrap_or_else(|_| "./data/media".to_string()))
+    }
+
+    fn path(&self, key: &str) -> AppResult<PathBuf> {
+        check_key(key)?;
+        Ok(self.root.join(key))
+    }
+}
+
+impl FileStorage for LocalStorage {
+    async fn put(&self, key: &str, content: &[u8]) -> AppResult<()> {
+        let path = self.path(key)?;
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir)
+                .await
+                .map_err(|e| AppError::internal(format!("Failed to create storage directory: {}", e)))?;
+        }
+        tokio::fs::write(&path, content)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to store {}: {}", key, e)))
+    }
+
+    async fn get(&self, key: &str) -> AppResult<Option<Vec<u8>>> {
+        match tokio::fs::read(self.path(key)?).await {
+            Ok(content) => Ok(Some(content)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(AppError::internal(format!("Failed to read {}: {}", key, e))),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> AppResult<()> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(AppError::internal(format!("Failed to remove {}: {}", key, e)))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Files held in memory, for tests
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    files: std::sync::Mutex<std::collections::HashMap<String, Vec<u8>>>,
+}
+
+#[cfg(test)]
+impl MemoryStorage {
+    pub fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.files.lock().unwrap().keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+}
+
+#[cfg(test)]
+impl FileStorage for MemoryStorage {
+    async fn put(&self, key: &str, content: &[u8]) -> AppResult<()> {
+        check_key(key)?;
+        self.files.lock().unwrap().insert(key.to_string(), content.to_vec());
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> AppResult<Option<Vec<u8>>> {
+        Ok(self.files.lock().unwrap().get(key).cloned())
+    }
+
+    async fn delete(&self, key: &str) -> AppResult<()> {
+        self.files.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_stay_inside_the_root() {
+        assert!(check_key("0b6c/avatars/4f1e.png").is_ok());
+        assert!(check_key("../etc/passwd").is_err());
+        assert!(check_key("/etc/passwd").is_err());
+        assert!(check_key("a//b").is_err());
+        assert!(check_key("a/b c").is_err());
+        assert!(check_key("").is_err());
+    }
+}
@@ -0,0 +1,78 @@
+//! Typed views of `tenant_settings` categories
+//!
+//! Settings are stored one `(key, value)` row per setting under a category.
+//! A module describes its category as a `#[serde(default)]` struct and
+//! implements `TenantSettings` for it.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// One category of `tenant_settings` read into a struct
+pub trait TenantSettings: Default + Serialize + DeserializeOwned {
+    /// `tenant_settings.category` the rows are stored under
+    const CATEGORY: &'static str;
+
+    /// Build settings from `(key, value)` rows, falling back to defaults for anything missing or malformed
+    fn from_rows(rows: Vec<(String, serde_json::Value)>) -> Self {
+        let mut settings = Self::default();
+
+        for (key, value) in rows {
+            let mut candidate = serde_json::to_value(&settings).unwrap_or_default();
+            if let Some(map) = candidate.as_object_mut() {
+                map.insert(key, value);
+            }
+            if let Ok(parsed) = serde_json::from_value(candidate) {
+                settings = parsed;
+            }
+        }
+
+        settings
+    }
+
+    /// Load a tenant's settings in this category
+    #[cfg(feature = "server")]
+    fn load(
+        pool: &sqlx::PgPool,
+        tenant_id: uuid::Uuid,
+    ) -> impl std::future::Future<Output = crate::utils::error::AppResult<Self>> + Send {
+        async move {
+            let rows = sqlx::query_as::<_, (String, serde_json::Value)>(
+                "SELECT key, value FROM tenant_settings WHERE tenant_id = $1 AND category = $2",
+            )
+            .bind(tenant_id)
+            .bind(Self::CATEGORY)
+            .fetch_all(pool)
+            .await?;
+
+            Ok(Self::from_rows(rows))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    #[serde(default)]
+    struct Sample {
+        enabled: bool,
+        limit: Option<i32>,
+    }
+
+    impl TenantSettings for Sample {
+        const CATEGORY: &'static str = "sample";
+    }
+
+    #[test]
+    fn test_from_rows_skips_malformed_values() {
+        let settings = Sample::from_rows(vec![
+            ("enabled".to_string(), serde_json::json!(true)),
+            ("limit".to_string(), serde_json::json!("ten")),
+            ("unknown".to_string(), serde_json::json!(1)),
+        ]);
+
+        assert_eq!(settings, Sample { enabled: true, limit: None });
+    }
+}
@@ -0,0 +1,188 @@
+//! Tenant time zone helpers
+//!
+//! Timestamps are stored in UTC. Anything grouped by calendar day - "due
+//! today" filters, daily report rows - is computed in the tenant's time zone
+//! so a ticket due at 23:00 local time is not counted on the next day.
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+/// A tenant's IANA time zone, stored in `tenant_settings` as `general.timezone`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TenantTimezone(Tz);
+
+impl Default for TenantTimezone {
+    fn default() -> Self {
+        Self(Tz::UTC)
+    }
+}
+
+impl TenantTimezone {
+    /// Parse an IANA name such as `America/Chicago`
+    pub fn parse(name: &str) -> Option<Self> {
+        name.trim().parse::<Tz>().ok().map(Self)
+    }
+
+    /// Read the stored setting value, falling back to UTC when missing or unknown
+    pub fn from_setting(value: Option<serde_json::Value>) -> Self {
+        value
+            .as_ref()
+            .and_then(|value| value.as_str())
+            .and_then(Self::parse)
+            .unwrap_or_default()
+    }
+
+    /// Load a tenant's time zone from its settings
+    #[cfg(feature = "server")]
+    pub async fn load(pool: &sqlx::PgPool, tenant_id: uuid::Uuid) -> crate::utils::error::AppResult<Self> {
+        let value: Option<serde_json::Value> = sqlx::query_scalar(
+            "SELECT value FROM tenant_settings WHERE tenant_id = $1 AND category = 'general' AND key = 'timezone'",
+        )
+        .bind(tenant_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(Self::from_setting(value))
+    }
+
+    /// IANA name, also understood by PostgreSQL's `AT TIME ZONE`
+    pub fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    /// Calendar date of an instant in the tenant's time zone
+    pub fn local_date(&self, at: DateTime<Utc>) -> NaiveDate {
+        at.with_timezone(&self.0).date_naive()
+    }
+
+    /// Wall-clock date and time of an instant in the time zone
+    pub fn local_datetime(&self, at: DateTime<Utc>) -> NaiveDateTime {
+        at.with_timezone(&self.0).naive_local()
+    }
+
+    /// The instant a local wall-clock time names. A time skipped by a DST
+    /// change resolves to the first minute after the gap; a repeated one to
+    /// its first occurrence.
+    pub fn to_utc(&self, local: NaiveDateTime) -> DateTime<Utc> {
+        (0..=180)
+            .map(|minutes| local + Duration::minutes(minutes))
+            .find_map(|local| self.0.from_local_datetime(&local).earliest())
+            .map(|at| at.with_timezone(&Utc))
+            .unwrap_or_else(|| Utc.from_utc_datetime(&local))
+    }
+
+    /// First instant of a local day. Where DST skips midnight the day starts at
+    /// the first local time that exists.
+    pub fn day_start(&self, date: NaiveDate) -> DateTime<Utc> {
+        (0..24)
+            .filter_map(|hour| date.and_hms_opt(hour, 0, 0))
+            .find_map(|local| self.0.from_local_datetime(&local).earliest())
+            .map(|start| start.with_timezone(&Utc))
+            .unwrap_or_else(|| Utc.from_utc_datetime(&date.and_time(chrono::NaiveTime::MIN)))
+    }
+
+    /// `[start, end)` of a local day in UTC; 23 or 25 hours long across DST changes
+    pub fn day_range(&self, date: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+        (self.day_start(date), self.day_start(date + Duration::days(1)))
+    }
+
+    /// Whether `due` falls on the same local day as `now`
+    pub fn is_due_today(&self, due: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        self.local_date(due) == self.local_date(now)
+    }
+
+    /// Local days touched by `[from, to)`, in order
+    pub fn days_between(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<NaiveDate> {
+        if to <= from {
+            return Vec::new();
+        }
+
+        let last = self.local_date(to - Duration::nanoseconds(1));
+        self.local_date(from)
+            .iter_days()
+            .take_while(|day| *day <= last)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_setting_fallback() {
+        assert_eq!(TenantTimezone::parse("America/Chicago").unwrap().name(), "America/Chicago");
+        assert_eq!(TenantTimezone::parse("Mars/Olympus"), None);
+
+        let chicago = TenantTimezone::from_setting(Some(serde_json::json!("America/Chicago")));
+        assert_eq!(chicago.name(), "America/Chicago");
+        assert_eq!(TenantTimezone::from_setting(Some(serde_json::json!(5))), TenantTimezone::default());
+        assert_eq!(TenantTimezone::from_setting(None).name(), "UTC");
+    }
+
+    #[test]
+    fn test_day_bucketing_around_local_midnight() {
+        let chicago = TenantTimezone::parse("America/Chicago").unwrap();
+
+        // 23:00 CDT on March 14 is already 04:00 UTC on March 15
+        let late_evening = utc("2025-03-15T04:00:00Z");
+        assert_eq!(chicago.local_date(late_evening), date("2025-03-14"));
+        assert_eq!(TenantTimezone::default().local_date(late_evening), date("2025-03-15"));
+
+        // One minute after local midnight is the next day
+        assert_eq!(chicago.local_date(utc("2025-03-15T05:01:00Z")), date("2025-03-15"));
+
+        let (start, end) = chicago.day_range(date("2025-03-14"));
+        assert_eq!(start, utc("2025-03-14T05:00:00Z"));
+        assert_eq!(end, utc("2025-03-15T05:00:00Z"));
+        assert!(start <= late_evening && late_evening < end);
+    }
+
+    #[test]
+    fn test_local_time_to_utc_across_dst() {
+        let chicago = TenantTimezone::parse("America/Chicago").unwrap();
+        let local = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+
+        assert_eq!(chicago.to_utc(local("2025-03-14 07:00")), utc("2025-03-14T12:00:00Z"));
+        // 02:30 doesn't exist on March 9; the clock reads 03:00 CDT next
+        assert_eq!(chicago.to_utc(local("2025-03-09 02:30")), utc("2025-03-09T08:00:00Z"));
+        // 01:30 happens twice on November 2; the first is CDT
+        assert_eq!(chicago.to_utc(local("2025-11-02 01:30")), utc("2025-11-02T06:30:00Z"));
+    }
+
+    #[test]
+    fn test_due_today_in_tenant_timezone() {
+        let chicago = TenantTimezone::parse("America/Chicago").unwrap();
+        let now = utc("2025-03-14T15:00:00Z"); // 10:00 local
+
+        // Due 23:00 local today, which is tomorrow in UTC
+        assert!(chicago.is_due_today(utc("2025-03-15T04:00:00Z"), now));
+        assert!(!TenantTimezone::default().is_due_today(utc("2025-03-15T04:00:00Z"), now));
+
+        // Due 00:30 local tomorrow
+        assert!(!chicago.is_due_today(utc("2025-03-15T05:30:00Z"), now));
+    }
+
+    #[test]
+    fn test_day_range_across_dst_change() {
+        let chicago = TenantTimezone::parse("America/Chicago").unwrap();
+
+        // Clocks go forward on March 9, 2025: a 23 hour day
+        let (start, end) = chicago.day_range(date("2025-03-09"));
+        assert_eq!(end - start, Duration::hours(23));
+
+        let days = chicago.days_between(utc("2025-03-08T06:00:00Z"), utc("2025-03-11T05:00:00Z"));
+        assert_eq!(days, vec![date("2025-03-08"), date("2025-03-09"), date("2025-03-10")]);
+        assert!(chicago.days_between(end, start).is_empty());
+    }
+}
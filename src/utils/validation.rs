@@ -167,6 +167,52 @@ pub fn truncate(s: &str, max_len: usize) -> String {
     }
 }
 
+/// JSON body extractor that also runs the body's `Validate` rules.
+///
+/// Rejections are structured errors: a body missing a required field or
+/// failing a rule is a 422 with `field_errors`, anything else unparseable
+/// is a 400.
+#[cfg(feature = "server")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidatedJson<T>(pub T);
+
+#[cfg(feature = "server")]
+static MISSING_FIELD_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"missing field `([^`]+)`").unwrap()
+});
+
+#[cfg(feature = "server")]
+impl<T, S> axum::extract::FromRequest<S> for ValidatedJson<T>
+where
+    T: serde::de::DeserializeOwned + validator::Validate,
+    S: Send + Sync,
+{
+    type Rejection = crate::utils::error::AppError;
+
+    async fn from_request(request: axum::extract::Request, state: &S) -> Result<Self, Self::Rejection> {
+        let axum::Json(value) = axum::Json::<T>::from_request(request, state)
+            .await
+            .map_err(json_rejection)?;
+        value.validate()?;
+        Ok(Self(value))
+    }
+}
+
+/// Map a JSON body rejection, naming the field when serde reports one missing
+#[cfg(feature = "server")]
+fn json_rejection(rejection: axum::extract::rejection::JsonRejection) -> crate::utils::error::AppError {
+    use crate::utils::error::{AppError, FieldError};
+
+    let text = rejection.body_text();
+    match MISSING_FIELD_REGEX.captures(&text) {
+        Some(captures) => AppError::validation(
+            "Validation failed",
+            vec![FieldError::new(&captures[1], "This field is required", "required")],
+        ),
+        None => AppError::BadRequest(text),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -220,4 +266,60 @@ mod tests {
         assert_eq!(truncate("Hello", 10), "Hello");
         assert_eq!(truncate("Hello World", 8), "Hello...");
     }
+
+    #[cfg(feature = "server")]
+    mod extractor {
+        use super::super::ValidatedJson;
+        use axum::{body::Body, http::Request, routing::post, Router};
+        use serde::Deserialize;
+        use tower::ServiceExt;
+        use validator::Validate;
+
+        #[derive(Deserialize, Validate)]
+        struct CreateThing {
+            #[validate(length(min = 1, max = 10))]
+            title: String,
+            #[allow(dead_code)]
+            priority: String,
+        }
+
+        async fn post_json(body: &'static str) -> (u16, serde_json::Value) {
+            let app = Router::new().route(
+                "/things",
+                post(|ValidatedJson(thing): ValidatedJson<CreateThing>| async move { thing.title }),
+            );
+            let request = Request::builder()
+                .method("POST")
+                .uri("/things")
+                .header("Content-Type", "application/json")
+                .body(Body::from(body))
+                .unwrap();
+            let response = app.oneshot(request).await.unwrap();
+            let status = response.status().as_u16();
+            let bytes = axum::body::to_bytes(response.into_body(), 4096).await.unwrap();
+            (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+        }
+
+        #[tokio::test]
+        async fn test_over_length_field_is_structured_422() {
+            let (status, json) = post_json(r#"{"title": "far too long a title", "priority": "high"}"#).await;
+            assert_eq!(status, 422);
+            assert_eq!(json["code"], "validation_error");
+            assert_eq!(json["field_errors"][0]["field"], "title");
+            assert_eq!(json["field_errors"][0]["code"], "length");
+            assert_eq!(json["field_errors"][0]["message"], "Must be between 1 and 10 characters");
+        }
+
+        #[tokio::test]
+        async fn test_missing_required_field_is_structured_422() {
+            let (status, json) = post_json(r#"{"title": "ok"}"#).await;
+            assert_eq!(status, 422);
+            assert_eq!(json["field_errors"][0]["field"], "priority");
+            assert_eq!(json["field_errors"][0]["code"], "required");
+
+            let (status, json) = post_json("{not json").await;
+            assert_eq!(status, 400);
+            assert_eq!(json["code"], "bad_request");
+        }
+    }
 }